@@ -61,6 +61,13 @@ pub enum ActorExitStatus {
     #[error("downstream actor exited")]
     DownstreamClosed,
 
+    /// The actor tried to send a message to a downstream actor, but the downstream actor queue
+    /// remained full past the timeout of the send operation.
+    ///
+    /// Unlike `DownstreamClosed`, the downstream actor is still alive, but too slow to keep up.
+    #[error("downstream actor timed out")]
+    DownstreamTimeout,
+
     /// The actor was killed.
     ///
     /// It can happen because:
@@ -93,8 +100,11 @@ impl ActorExitStatus {
 }
 
 impl From<SendError> for ActorExitStatus {
-    fn from(_: SendError) -> Self {
-        ActorExitStatus::DownstreamClosed
+    fn from(send_error: SendError) -> Self {
+        match send_error {
+            SendError::Timeout => ActorExitStatus::DownstreamTimeout,
+            SendError::Disconnected | SendError::Full => ActorExitStatus::DownstreamClosed,
+        }
     }
 }
 
@@ -21,15 +21,20 @@ use std::convert::Infallible;
 use std::fmt;
 use std::future::Future;
 use std::ops::Deref;
+#[cfg(any(test, feature = "testsuite"))]
+use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+#[cfg(any(test, feature = "testsuite"))]
+use std::sync::Weak;
+use std::time::{Duration, Instant};
 
 use quickwit_common::metrics::IntCounter;
 use quickwit_common::{KillSwitch, Progress, ProtectedZoneGuard};
 use tokio::sync::{oneshot, watch};
 use tracing::{debug, error};
 
+use crate::actor_metrics::MessageCounter;
 use crate::actor_state::AtomicState;
 use crate::registry::ActorRegistry;
 use crate::spawn_builder::{SpawnBuilder, SpawnContext};
@@ -65,11 +70,38 @@ pub struct ActorContextInner<A: Actor> {
     spawn_ctx: SpawnContext,
     self_mailbox: Mailbox<A>,
     progress: Progress,
+    message_counter: MessageCounter,
     actor_state: AtomicState,
     backpressure_micros_counter_opt: Option<IntCounter>,
     observable_state_tx: watch::Sender<A::ObservableState>,
     // Boolean marking the presence of an observe message in the actor's high priority queue.
     observe_enqueued: AtomicBool,
+    // Number of low priority messages the actor may process while paused.
+    // See `Universe::deliver_next_to`.
+    #[cfg(any(test, feature = "testsuite"))]
+    step_credits: AtomicUsize,
+    // Whether the actor is blocked waiting for its next message.
+    // See `Universe::run_until_idle`.
+    #[cfg(any(test, feature = "testsuite"))]
+    waiting_for_messages: AtomicBool,
+}
+
+impl<A: Actor> ActorContextInner<A> {
+    /// Returns true if the actor is blocked waiting for a message while none of the messages it
+    /// can currently process is pending.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn is_idle(&self) -> bool {
+        let actor_state = self.actor_state.get_state();
+        if actor_state.is_exit() || !self.waiting_for_messages.load(Ordering::SeqCst) {
+            return false;
+        }
+        if actor_state == ActorState::Paused {
+            // A paused actor only processes commands and scheduled messages.
+            self.self_mailbox.is_high_priority_empty()
+        } else {
+            self.self_mailbox.is_empty()
+        }
+    }
 }
 
 impl<A: Actor> ActorContext<A> {
@@ -84,10 +116,15 @@ impl<A: Actor> ActorContext<A> {
                 self_mailbox,
                 spawn_ctx,
                 progress: Progress::default(),
+                message_counter: MessageCounter::new(*crate::HEARTBEAT, Instant::now()),
                 actor_state: AtomicState::default(),
                 observable_state_tx,
                 backpressure_micros_counter_opt,
                 observe_enqueued: AtomicBool::new(false),
+                #[cfg(any(test, feature = "testsuite"))]
+                step_credits: AtomicUsize::new(0),
+                #[cfg(any(test, feature = "testsuite"))]
+                waiting_for_messages: AtomicBool::new(false),
             }
             .into(),
         }
@@ -155,6 +192,41 @@ impl<A: Actor> ActorContext<A> {
         future.await
     }
 
+    /// Executes a future, protecting the actor for at most `max_protected_duration`.
+    ///
+    /// Progress is recorded every time the future is polled. On top of that, the actor stays
+    /// in a protected zone until the future completes or `max_protected_duration` elapses,
+    /// which covers futures that are legitimately pending for a long time, like a large upload.
+    ///
+    /// Past that deadline, the actor only makes progress when the future is polled, so a
+    /// future that is never woken up again eventually fails the health check instead of
+    /// being protected forever as with `protect_future`.
+    pub async fn protect_future_for<Fut, T>(
+        &self,
+        future: Fut,
+        max_protected_duration: Duration,
+    ) -> T
+    where
+        Fut: Future<Output = T>,
+    {
+        let mut future = std::pin::pin!(future);
+        let mut progress_recording_future = std::future::poll_fn(|cx| {
+            self.progress.record_progress();
+            future.as_mut().poll(cx)
+        });
+        {
+            let _guard = self.protect_zone();
+            let scheduler_client = &self.spawn_ctx().scheduler_client;
+            if let Ok(output) = scheduler_client
+                .timeout(max_protected_duration, &mut progress_recording_future)
+                .await
+            {
+                return output;
+            }
+        }
+        progress_recording_future.await
+    }
+
     /// Cooperatively yields, while keeping the actor protected.
     pub async fn yield_now(&self) {
         self.protect_future(tokio::task::yield_now()).await;
@@ -174,6 +246,10 @@ impl<A: Actor> ActorContext<A> {
         &self.progress
     }
 
+    pub(crate) fn message_counter(&self) -> &MessageCounter {
+        &self.message_counter
+    }
+
     pub fn spawn_actor<SpawnedActor: Actor>(&self) -> SpawnBuilder<SpawnedActor> {
         self.spawn_ctx.clone().spawn_builder()
     }
@@ -207,6 +283,39 @@ impl<A: Actor> ActorContext<A> {
         self.actor_state.resume();
     }
 
+    /// Allows the actor to process one low priority message while paused.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn add_step_credit(&self) {
+        self.step_credits.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn has_step_credit(&self) -> bool {
+        self.step_credits.load(Ordering::SeqCst) > 0
+    }
+
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn consume_step_credit(&self) {
+        self.step_credits.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Revokes the unused step credits and returns their number.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn clear_step_credits(&self) -> usize {
+        self.step_credits.swap(0, Ordering::SeqCst)
+    }
+
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn set_waiting_for_messages(&self, waiting_for_messages: bool) {
+        self.waiting_for_messages
+            .store(waiting_for_messages, Ordering::SeqCst);
+    }
+
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn downgrade(&self) -> Weak<ActorContextInner<A>> {
+        Arc::downgrade(&self.inner)
+    }
+
     /// Sets the queue as observed and returns the previous value.
     /// This method is used to make sure we do not have Observe messages
     /// stacking up in the observe queue.
@@ -268,6 +377,32 @@ impl<A: Actor> ActorContext<A> {
             .await
     }
 
+    /// Same as `send_message`, but returns `SendError::Timeout` if the queue of the destination
+    /// mailbox is still full after `timeout`.
+    ///
+    /// Propagating the error makes the actor exit with `ActorExitStatus::DownstreamTimeout`,
+    /// which activates its kill switch.
+    pub async fn send_message_with_timeout<DestActor: Actor, M>(
+        &self,
+        mailbox: &Mailbox<DestActor>,
+        msg: M,
+        timeout: Duration,
+    ) -> Result<oneshot::Receiver<DestActor::Reply>, SendError>
+    where
+        DestActor: DeferableReplyHandler<M>,
+        M: fmt::Debug + Send + 'static,
+    {
+        let _guard = self.protect_zone();
+        debug!(from=%self.self_mailbox.actor_instance_id(), send=%mailbox.actor_instance_id(), msg=?msg);
+        mailbox
+            .send_message_with_timeout_and_backpressure_counter(
+                msg,
+                timeout,
+                self.backpressure_micros_counter_opt.as_ref(),
+            )
+            .await
+    }
+
     pub async fn ask<DestActor: Actor, M, T>(
         &self,
         mailbox: &Mailbox<DestActor>,
@@ -371,6 +506,7 @@ impl<A: Actor> ActorContext<A> {
 fn should_activate_kill_switch(exit_status: &ActorExitStatus) -> bool {
     match exit_status {
         ActorExitStatus::DownstreamClosed => true,
+        ActorExitStatus::DownstreamTimeout => true,
         ActorExitStatus::Failure(_) => true,
         ActorExitStatus::Panicked => true,
         ActorExitStatus::Success => false,
@@ -19,11 +19,13 @@
 
 use std::fmt;
 use std::ops::Deref;
+use std::time::Instant;
 
 use serde::Serialize;
 use tokio::sync::{oneshot, watch};
 use tracing::error;
 
+use crate::actor_metrics::ActorMetrics;
 use crate::actor_state::ActorState;
 use crate::command::Observe;
 use crate::mailbox::Priority;
@@ -128,6 +130,20 @@ impl<A: Actor> ActorHandle<A> {
         self.actor_context.state()
     }
 
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn actor_context(&self) -> &ActorContext<A> {
+        &self.actor_context
+    }
+
+    /// Returns the number of messages processed by the actor and its processing rate over the
+    /// last heartbeat window.
+    ///
+    /// Unlike `observe()`, this does not send any message to the actor, so it can be called on
+    /// an actor that is stuck processing a message.
+    pub fn metrics(&self) -> ActorMetrics {
+        self.actor_context.message_counter().metrics(Instant::now())
+    }
+
     /// Process all of the pending messages, and returns a snapshot of
     /// the observable state of the actor after this.
     ///
@@ -248,6 +264,33 @@ impl<A: Actor> ActorHandle<A> {
         self.join().await
     }
 
+    /// Gracefully drains the actor: the messages already in its mailbox are processed, then the
+    /// actor exits with the `Success` exit status and its finalize function is called.
+    ///
+    /// Unlike `quit`, pending messages are not dropped. Messages sent after the drain was
+    /// requested are not processed. A paused actor is resumed, as it would otherwise never
+    /// process its pending messages.
+    ///
+    /// The kill switch is not actionated.
+    pub async fn drain(self) -> (ActorExitStatus, A::ObservableState) {
+        self.resume();
+        let _ = self
+            .actor_context
+            .mailbox()
+            .send_message(Command::ExitWithSuccess)
+            .await;
+        self.join().await
+    }
+
+    /// Returns `true` once the actor task has terminated, in which case `join` resolves without
+    /// waiting.
+    ///
+    /// Unlike `state().is_exit()`, which flips right before the actor is dropped, this
+    /// guarantees that the post-mortem observation of the actor has been collected.
+    pub fn is_terminated(&self) -> bool {
+        self.join_handle.try_join().is_some()
+    }
+
     /// Waits until the actor exits by itself. This is the equivalent of `Thread::join`.
     pub async fn join(self) -> (ActorExitStatus, A::ObservableState) {
         let exit_status = self.join_handle.join().await;
@@ -301,6 +344,7 @@ mod tests {
     use async_trait::async_trait;
 
     use super::*;
+    use crate::tests::{Ping, PingReceiverActor};
     use crate::{Handler, Universe};
 
     #[derive(Default)]
@@ -346,6 +390,9 @@ mod tests {
     #[derive(Debug)]
     struct Exit;
 
+    #[derive(Debug)]
+    struct Fail;
+
     #[async_trait]
     impl Handler<Exit> for ExitActor {
         type Reply = ();
@@ -360,6 +407,20 @@ mod tests {
         }
     }
 
+    #[async_trait]
+    impl Handler<Fail> for ExitActor {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            _msg: Fail,
+            _ctx: &ActorContext<Self>,
+        ) -> Result<(), ActorExitStatus> {
+            self.count += 1;
+            Err(anyhow::anyhow!("upload failed").into())
+        }
+    }
+
     #[tokio::test]
     async fn test_panic_in_actor() -> anyhow::Result<()> {
         let universe = Universe::with_accelerated_time();
@@ -382,6 +443,39 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_actor_handle_termination() {
+        let universe = Universe::new();
+
+        // Graceful disconnect: the last mailbox is dropped.
+        let (mailbox, handle) = universe.spawn_builder().spawn(PingReceiverActor::default());
+        mailbox.send_message(Ping).await.unwrap();
+        assert!(!handle.is_terminated());
+        drop(mailbox);
+        universe.run_until_idle().await;
+        assert!(handle.is_terminated());
+        // The post-mortem observation is available as soon as the actor is terminated.
+        assert_eq!(*handle.last_observation(), 1);
+        let (exit_status, ping_count) = handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Success));
+        assert_eq!(ping_count, 1);
+
+        // Actor error: the error is carried by the exit status.
+        let (mailbox, handle) = universe.spawn_builder().spawn(ExitActor::default());
+        mailbox.send_message(Fail).await.unwrap();
+        let (exit_status, count) = handle.join().await;
+        let ActorExitStatus::Failure(error) = exit_status else {
+            panic!("expected a failure exit status, got {exit_status:?}");
+        };
+        assert_eq!(error.to_string(), "upload failed");
+        assert_eq!(count, 1);
+
+        // Kill switch.
+        let (_mailbox, handle) = universe.spawn_builder().spawn(PingReceiverActor::default());
+        let (exit_status, _) = handle.kill().await;
+        assert!(matches!(exit_status, ActorExitStatus::Killed));
+    }
+
     #[derive(Default)]
     struct ObserveActor {
         observe: AtomicU32,
@@ -431,4 +525,24 @@ mod tests {
         assert!(num_obs < 8);
         universe.assert_quit().await;
     }
+
+    #[tokio::test]
+    async fn test_actor_handle_metrics() {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, handle) = universe.spawn_builder().spawn(PingReceiverActor::default());
+        let metrics = handle.metrics();
+        assert_eq!(metrics.num_processed_messages, 0);
+        assert!(metrics.last_processed_at.is_none());
+
+        for _ in 0..3 {
+            mailbox.send_message(Ping).await.unwrap();
+        }
+        let ping_count = handle.process_pending_and_observe().await.state;
+        assert_eq!(ping_count, 3);
+        // The observe command itself may or may not be counted yet.
+        let metrics = handle.metrics();
+        assert!(metrics.num_processed_messages >= 3);
+        assert!(metrics.last_processed_at.is_some());
+        universe.assert_quit().await;
+    }
 }
@@ -0,0 +1,149 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Message processing metrics of an actor, as returned by `ActorHandle::metrics()`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ActorMetrics {
+    /// Number of messages, including commands, processed by the actor since it was spawned.
+    pub num_processed_messages: u64,
+    /// Instant at which the actor finished processing its last message.
+    pub last_processed_at: Option<Instant>,
+    /// Processing rate over the last complete heartbeat window, or over the current window if
+    /// the actor was spawned less than a heartbeat ago.
+    pub messages_per_sec: f64,
+}
+
+/// Counts the messages processed by an actor.
+///
+/// The counter is updated by the actor loop and read from the actor handle. It is independent
+/// from the actor `Progress`, so it has no effect on the health checks.
+pub(crate) struct MessageCounter {
+    window: Duration,
+    inner: Mutex<MessageCounterInner>,
+}
+
+struct MessageCounterInner {
+    num_processed_messages: u64,
+    last_processed_at: Option<Instant>,
+    window_start: Instant,
+    window_start_num_processed_messages: u64,
+    last_window_messages_per_sec: Option<f64>,
+}
+
+impl MessageCounterInner {
+    fn current_window_messages_per_sec(&self, now: Instant) -> f64 {
+        let elapsed_secs = now
+            .saturating_duration_since(self.window_start)
+            .as_secs_f64();
+        if elapsed_secs == 0.0 {
+            return 0.0;
+        }
+        (self.num_processed_messages - self.window_start_num_processed_messages) as f64
+            / elapsed_secs
+    }
+
+    fn maybe_roll_window(&mut self, now: Instant, window: Duration) {
+        if now.saturating_duration_since(self.window_start) < window {
+            return;
+        }
+        self.last_window_messages_per_sec = Some(self.current_window_messages_per_sec(now));
+        self.window_start = now;
+        self.window_start_num_processed_messages = self.num_processed_messages;
+    }
+}
+
+impl MessageCounter {
+    pub fn new(window: Duration, now: Instant) -> Self {
+        let inner = MessageCounterInner {
+            num_processed_messages: 0,
+            last_processed_at: None,
+            window_start: now,
+            window_start_num_processed_messages: 0,
+            last_window_messages_per_sec: None,
+        };
+        MessageCounter {
+            window,
+            inner: Mutex::new(inner),
+        }
+    }
+
+    pub fn record_processed_message(&self, now: Instant) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.num_processed_messages += 1;
+        inner.last_processed_at = Some(now);
+        inner.maybe_roll_window(now, self.window);
+    }
+
+    pub fn metrics(&self, now: Instant) -> ActorMetrics {
+        let mut inner = self.inner.lock().unwrap();
+        inner.maybe_roll_window(now, self.window);
+        let messages_per_sec = inner
+            .last_window_messages_per_sec
+            .unwrap_or_else(|| inner.current_window_messages_per_sec(now));
+        ActorMetrics {
+            num_processed_messages: inner.num_processed_messages,
+            last_processed_at: inner.last_processed_at,
+            messages_per_sec,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_message_counter() {
+        let start = Instant::now();
+        let message_counter = MessageCounter::new(Duration::from_secs(10), start);
+
+        let metrics = message_counter.metrics(start);
+        assert_eq!(metrics.num_processed_messages, 0);
+        assert_eq!(metrics.last_processed_at, None);
+        assert_eq!(metrics.messages_per_sec, 0.0);
+
+        for i in 1..=4 {
+            message_counter.record_processed_message(start + Duration::from_secs(i));
+        }
+        let metrics = message_counter.metrics(start + Duration::from_secs(4));
+        assert_eq!(metrics.num_processed_messages, 4);
+        assert_eq!(
+            metrics.last_processed_at,
+            Some(start + Duration::from_secs(4))
+        );
+        assert_eq!(metrics.messages_per_sec, 1.0);
+
+        // Closes the first window: 5 messages over 10 seconds.
+        message_counter.record_processed_message(start + Duration::from_secs(10));
+        let metrics = message_counter.metrics(start + Duration::from_secs(15));
+        assert_eq!(metrics.num_processed_messages, 5);
+        assert_eq!(metrics.messages_per_sec, 0.5);
+
+        // No message was processed during the second window.
+        let metrics = message_counter.metrics(start + Duration::from_secs(20));
+        assert_eq!(metrics.messages_per_sec, 0.0);
+        assert_eq!(
+            metrics.last_processed_at,
+            Some(start + Duration::from_secs(10))
+        );
+    }
+}
@@ -19,6 +19,7 @@
 
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Mutex;
+use std::time::Duration;
 
 use flume::TryRecvError;
 use thiserror::Error;
@@ -68,6 +69,8 @@ pub enum SendError {
     Disconnected,
     #[error("the channel is full")]
     Full,
+    #[error("the channel remained full until the send operation timed out")]
+    Timeout,
 }
 
 #[derive(Debug, Error)]
@@ -159,6 +162,14 @@ impl<T> Sender<T> {
         self.low_priority_tx.is_disconnected()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.high_priority_tx.is_empty() && self.low_priority_tx.is_empty()
+    }
+
+    pub fn is_high_priority_empty(&self) -> bool {
+        self.high_priority_tx.is_empty()
+    }
+
     pub fn try_send_low_priority(&self, msg: T) -> Result<(), TrySendError<T>> {
         self.low_priority_tx.try_send(msg)?;
         Ok(())
@@ -169,6 +180,19 @@ impl<T> Sender<T> {
         Ok(())
     }
 
+    /// Same as `send_low_priority`, but gives up after `timeout`, in which case the message is
+    /// dropped.
+    pub async fn send_low_priority_with_timeout(
+        &self,
+        msg: T,
+        timeout: Duration,
+    ) -> Result<(), SendError> {
+        tokio::time::timeout(timeout, self.low_priority_tx.send_async(msg))
+            .await
+            .map_err(|_elapsed| SendError::Timeout)??;
+        Ok(())
+    }
+
     pub fn send_high_priority(&self, msg: T) -> Result<(), SendError> {
         self.high_priority_tx.send(msg)?;
         Ok(())
@@ -258,6 +282,18 @@ impl<T> Receiver<T> {
         }
     }
 
+    /// Receives a low priority message, ignoring the high priority queue.
+    pub fn try_recv_low_priority(&self) -> Result<T, RecvError> {
+        if let Some(pending_msg) = self.pending_low_priority_message.take() {
+            return Ok(pending_msg);
+        }
+        match self.low_priority_rx.try_recv() {
+            Ok(low_msg) => Ok(low_msg),
+            Err(TryRecvError::Disconnected) => Err(RecvError::Disconnected),
+            Err(TryRecvError::Empty) => Err(RecvError::NoMessageAvailable),
+        }
+    }
+
     pub async fn recv_high_priority(&self) -> T {
         self.high_priority_rx
             .recv_async()
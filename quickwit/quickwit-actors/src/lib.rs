@@ -35,6 +35,7 @@ use tokio::time::Duration;
 mod actor;
 mod actor_context;
 mod actor_handle;
+mod actor_metrics;
 mod actor_state;
 #[doc(hidden)]
 pub mod channel_with_priority;
@@ -64,6 +65,7 @@ use tracing::{info, warn};
 pub use universe::Universe;
 
 pub use self::actor_context::ActorContext;
+pub use self::actor_metrics::ActorMetrics;
 pub use self::actor_state::ActorState;
 pub use self::channel_with_priority::{QueueCapacity, RecvError, SendError, TrySendError};
 pub use self::mailbox::{Inbox, Mailbox, WeakMailbox};
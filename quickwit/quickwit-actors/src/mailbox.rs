@@ -22,10 +22,11 @@ use std::convert::Infallible;
 use std::fmt;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
-use std::time::Instant;
+use std::time::Duration;
 
 use quickwit_common::metrics::IntCounter;
 use tokio::sync::oneshot;
+use tokio::time::Instant;
 
 use crate::channel_with_priority::{Receiver, Sender, TrySendError};
 use crate::envelope::{wrap_in_envelope, Envelope};
@@ -130,6 +131,18 @@ impl<A: Actor> Mailbox<A> {
         self.inner.tx.is_disconnected()
     }
 
+    /// Returns true if no message is waiting in the mailbox.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn is_empty(&self) -> bool {
+        self.inner.tx.is_empty()
+    }
+
+    /// Returns true if no command or scheduled message is waiting in the mailbox.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn is_high_priority_empty(&self) -> bool {
+        self.inner.tx.is_high_priority_empty()
+    }
+
     /// Sends a message to the actor owning the associated inbox.
     ///
     /// From an actor context, use the `ActorContext::send_message` method instead.
@@ -147,6 +160,46 @@ impl<A: Actor> Mailbox<A> {
             .await
     }
 
+    /// Sends a message to the actor owning the associated inbox, waiting at most `timeout` for
+    /// the actor queue to have room for it.
+    ///
+    /// Unlike `send_message`, this method does not hang if the actor is stuck: it returns
+    /// `SendError::Timeout` and the message is dropped. `SendError::Disconnected` is returned if
+    /// the actor has already exited.
+    pub async fn send_message_with_timeout<M>(
+        &self,
+        message: M,
+        timeout: Duration,
+    ) -> Result<oneshot::Receiver<A::Reply>, SendError>
+    where
+        A: DeferableReplyHandler<M>,
+        M: fmt::Debug + Send + 'static,
+    {
+        self.send_message_with_timeout_and_backpressure_counter(message, timeout, None)
+            .await
+    }
+
+    /// Same as `send_message_with_timeout`, but increases `backpressure_micros_counter_opt` by
+    /// the amount of microseconds of backpressure experienced, like
+    /// `send_message_with_backpressure_counter`.
+    pub async fn send_message_with_timeout_and_backpressure_counter<M>(
+        &self,
+        message: M,
+        timeout: Duration,
+        backpressure_micros_counter_opt: Option<&IntCounter>,
+    ) -> Result<oneshot::Receiver<A::Reply>, SendError>
+    where
+        A: DeferableReplyHandler<M>,
+        M: fmt::Debug + Send + 'static,
+    {
+        self.send_message_with_timeout_opt_and_backpressure_counter(
+            message,
+            Some(timeout),
+            backpressure_micros_counter_opt,
+        )
+        .await
+    }
+
     /// Attempts to queue a message in the low priority channel of the mailbox.
     ///
     /// If sending the message would block, the method simply returns `TrySendError::Full(message)`.
@@ -198,6 +251,24 @@ impl<A: Actor> Mailbox<A> {
         message: M,
         backpressure_micros_counter_opt: Option<&IntCounter>,
     ) -> Result<oneshot::Receiver<A::Reply>, SendError>
+    where
+        A: DeferableReplyHandler<M>,
+        M: fmt::Debug + Send + 'static,
+    {
+        self.send_message_with_timeout_opt_and_backpressure_counter(
+            message,
+            None,
+            backpressure_micros_counter_opt,
+        )
+        .await
+    }
+
+    async fn send_message_with_timeout_opt_and_backpressure_counter<M>(
+        &self,
+        message: M,
+        timeout_opt: Option<Duration>,
+        backpressure_micros_counter_opt: Option<&IntCounter>,
+    ) -> Result<oneshot::Receiver<A::Reply>, SendError>
     where
         A: DeferableReplyHandler<M>,
         M: fmt::Debug + Send + 'static,
@@ -208,11 +279,16 @@ impl<A: Actor> Mailbox<A> {
             Err(TrySendError::Full(envelope)) => {
                 if let Some(backpressure_micros_counter) = backpressure_micros_counter_opt {
                     let now = Instant::now();
-                    self.inner.tx.send_low_priority(envelope).await?;
+                    let send_result = self
+                        .send_low_priority_with_timeout_opt(envelope, timeout_opt)
+                        .await;
+                    // The time spent waiting counts as backpressure, even if the send timed out.
                     let elapsed = now.elapsed();
                     backpressure_micros_counter.inc_by(elapsed.as_micros() as u64);
+                    send_result?;
                 } else {
-                    self.inner.tx.send_low_priority(envelope).await?;
+                    self.send_low_priority_with_timeout_opt(envelope, timeout_opt)
+                        .await?;
                 }
                 Ok(response_rx)
             }
@@ -220,6 +296,21 @@ impl<A: Actor> Mailbox<A> {
         }
     }
 
+    async fn send_low_priority_with_timeout_opt(
+        &self,
+        envelope: Envelope<A>,
+        timeout_opt: Option<Duration>,
+    ) -> Result<(), SendError> {
+        if let Some(timeout) = timeout_opt {
+            self.inner
+                .tx
+                .send_low_priority_with_timeout(envelope, timeout)
+                .await
+        } else {
+            self.inner.tx.send_low_priority(envelope).await
+        }
+    }
+
     pub fn send_message_with_high_priority<M>(
         &self,
         message: M,
@@ -340,6 +431,11 @@ impl<A: Actor> Inbox<A> {
         self.rx.try_recv()
     }
 
+    #[cfg(any(test, feature = "testsuite"))]
+    pub(crate) fn try_recv_low_priority_only(&self) -> Result<Envelope<A>, RecvError> {
+        self.rx.try_recv_low_priority()
+    }
+
     pub async fn recv_typed_message<M: 'static>(&self) -> Option<M> {
         while let Ok(mut envelope) = self.rx.recv().await {
             if let Some(msg) = envelope.message_typed() {
@@ -426,7 +522,6 @@ impl<A: Actor> WeakMailbox<A> {
 #[cfg(test)]
 mod tests {
     use std::mem;
-    use std::time::Duration;
 
     use super::*;
     use crate::tests::{Ping, PingReceiverActor};
@@ -592,6 +687,91 @@ mod tests {
         ));
     }
 
+    #[tokio::test]
+    async fn test_send_with_timeout() {
+        let universe = Universe::with_accelerated_time();
+        let back_pressure_actor = BackPressureActor;
+        let (mailbox, _handle) = universe.spawn_builder().spawn(back_pressure_actor);
+        // We send a first message to make sure the actor has been properly spawned and is listening
+        // for new messages.
+        mailbox
+            .ask_with_backpressure_counter(Duration::default(), None)
+            .await
+            .unwrap();
+        mailbox
+            .send_message_with_timeout(Duration::default(), Duration::from_secs(1))
+            .await
+            .unwrap()
+            .await
+            .unwrap();
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_send_with_timeout_blocked_actor() {
+        let universe = Universe::with_accelerated_time();
+        let back_pressure_actor = BackPressureActor;
+        let (mailbox, _handle) = universe.spawn_builder().spawn(back_pressure_actor);
+        mailbox
+            .ask_with_backpressure_counter(Duration::default(), None)
+            .await
+            .unwrap();
+        // With the clock paused, the runtime jumps to the earliest timer when it is idle, so the
+        // send times out after 50ms of virtual time, well before the actor wakes up.
+        tokio::time::pause();
+
+        // The actor is stuck processing this message for a while and its queue has no capacity.
+        mailbox
+            .send_message(Duration::from_millis(500))
+            .await
+            .unwrap();
+        let backpressure_micros_counter =
+            IntCounter::new("test_counter", "help for test_counter").unwrap();
+        let start = Instant::now();
+        let send_error = mailbox
+            .send_message_with_timeout_and_backpressure_counter(
+                Duration::default(),
+                Duration::from_millis(50),
+                Some(&backpressure_micros_counter),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(send_error, SendError::Timeout));
+        let elapsed = start.elapsed();
+        assert!(elapsed >= Duration::from_millis(50));
+        assert!(elapsed < Duration::from_millis(500));
+        // The time spent waiting before the timeout counts as backpressure.
+        assert!(backpressure_micros_counter.get() >= 50_000);
+
+        tokio::time::resume();
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_send_with_timeout_full_mailbox() {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, inbox) = universe
+            .create_mailbox::<PingReceiverActor>("hello".to_string(), QueueCapacity::Bounded(1));
+        assert!(mailbox.try_send_message(Ping).is_ok());
+        let send_error = mailbox
+            .send_message_with_timeout(Ping, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(send_error, SendError::Timeout));
+        // A slow downstream actor is not reported as a closed one.
+        assert!(matches!(
+            ActorExitStatus::from(send_error),
+            ActorExitStatus::DownstreamTimeout
+        ));
+
+        mem::drop(inbox);
+        let send_error = mailbox
+            .send_message_with_timeout(Ping, Duration::from_millis(50))
+            .await
+            .unwrap_err();
+        assert!(matches!(send_error, SendError::Disconnected));
+    }
+
     #[tokio::test]
     async fn test_weak_mailbox_ref_count() {
         let universe = Universe::with_accelerated_time();
@@ -20,6 +20,8 @@
 use std::any::{Any, TypeId};
 use std::collections::HashMap;
 use std::pin::Pin;
+#[cfg(any(test, feature = "testsuite"))]
+use std::sync::Weak;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 
@@ -30,19 +32,25 @@ use serde::Serialize;
 use serde_json::Value as JsonValue;
 use tokio::task::JoinHandle;
 
+#[cfg(any(test, feature = "testsuite"))]
+use crate::actor_context::ActorContextInner;
 use crate::command::Observe;
 use crate::mailbox::WeakMailbox;
-use crate::{Actor, ActorExitStatus, Command, Mailbox};
+use crate::{Actor, ActorContext, ActorExitStatus, Command, Mailbox};
 
 struct TypedJsonObservable<A: Actor> {
     actor_instance_id: String,
     weak_mailbox: WeakMailbox<A>,
     join_handle: ActorJoinHandle,
+    #[cfg(any(test, feature = "testsuite"))]
+    weak_ctx: Weak<ActorContextInner<A>>,
 }
 
 #[async_trait]
 trait JsonObservable: Sync + Send {
     fn is_disconnected(&self) -> bool;
+    #[cfg(any(test, feature = "testsuite"))]
+    fn is_idle(&self) -> bool;
     fn any(&self) -> &dyn Any;
     fn actor_instance_id(&self) -> &str;
     async fn observe(&self) -> Option<JsonValue>;
@@ -58,6 +66,18 @@ impl<A: Actor> JsonObservable for TypedJsonObservable<A> {
             .map(|mailbox| mailbox.is_disconnected())
             .unwrap_or(true)
     }
+    #[cfg(any(test, feature = "testsuite"))]
+    fn is_idle(&self) -> bool {
+        // An exited actor is only idle once its task has terminated, i.e. once its post-mortem
+        // observation has been collected.
+        if self.join_handle.try_join().is_some() {
+            return true;
+        }
+        self.weak_ctx
+            .upgrade()
+            .map(|ctx| ctx.is_idle())
+            .unwrap_or(true)
+    }
     fn any(&self) -> &dyn Any {
         &self.weak_mailbox
     }
@@ -121,10 +141,10 @@ pub struct ActorObservation {
 }
 
 impl ActorRegistry {
-    pub fn register<A: Actor>(&self, mailbox: &Mailbox<A>, join_handle: ActorJoinHandle) {
+    pub fn register<A: Actor>(&self, ctx: &ActorContext<A>, join_handle: ActorJoinHandle) {
         let typed_id = TypeId::of::<A>();
-        let actor_instance_id = mailbox.actor_instance_id().to_string();
-        let weak_mailbox = mailbox.downgrade();
+        let actor_instance_id = ctx.actor_instance_id().to_string();
+        let weak_mailbox = ctx.mailbox().downgrade();
         self.actors
             .write()
             .unwrap()
@@ -135,6 +155,8 @@ impl ActorRegistry {
                 weak_mailbox,
                 actor_instance_id,
                 join_handle,
+                #[cfg(any(test, feature = "testsuite"))]
+                weak_ctx: ctx.downgrade(),
             }));
     }
 
@@ -195,6 +217,21 @@ impl ActorRegistry {
         actor_ids.into_iter().zip(res).collect()
     }
 
+    /// Returns true if every registered actor is idle. See `ActorContextInner::is_idle`.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub fn is_idle(&self) -> bool {
+        self.actors
+            .read()
+            .unwrap()
+            .values()
+            .all(|registry_for_type| {
+                registry_for_type
+                    .observables
+                    .iter()
+                    .all(|obs| obs.is_idle())
+            })
+    }
+
     pub fn is_empty(&self) -> bool {
         self.actors
             .read()
@@ -255,6 +292,12 @@ impl ActorJoinHandle {
     pub(crate) async fn join(&self) -> ActorExitStatus {
         self.holder.clone().await
     }
+
+    /// Returns the exit status of the actor if its task has already terminated, without
+    /// waiting.
+    pub(crate) fn try_join(&self) -> Option<ActorExitStatus> {
+        self.holder.clone().now_or_never()
+    }
 }
 
 #[cfg(test)]
@@ -23,10 +23,16 @@ use std::collections::BinaryHeap;
 use std::future::Future;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
-use std::time::{Duration, Instant};
+#[cfg(not(any(test, feature = "testsuite")))]
+use std::time::Instant;
+use std::time::Duration;
 
 use tokio::sync::oneshot;
 use tokio::task::JoinHandle;
+// In tests, the scheduler follows tokio's clock so that scheduled messages fire when a paused
+// runtime clock is advanced (see `Universe::advance_time`).
+#[cfg(any(test, feature = "testsuite"))]
+use tokio::time::Instant;
 
 type Callback = Box<dyn FnOnce() + Sync + Send + 'static>;
 
@@ -436,4 +442,21 @@ mod tests {
         assert!(elapsed.as_millis() < 50);
         universe.assert_quit().await;
     }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_scheduler_follows_paused_tokio_clock() {
+        let count: Arc<AtomicUsize> = Default::default();
+        let simple_actor = ClockActor {
+            count: count.clone(),
+        };
+        let universe = Universe::new();
+        universe.spawn_builder().spawn(simple_actor);
+        universe.run_until_idle().await;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        universe.advance_time(Duration::from_millis(500)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 1);
+        universe.advance_time(Duration::from_secs(9)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 10);
+        universe.assert_quit().await;
+    }
 }
@@ -17,6 +17,8 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::time::Instant;
+
 use anyhow::Context;
 use quickwit_common::metrics::IntCounter;
 use sync_wrapper::SyncWrapper;
@@ -30,6 +32,7 @@ use crate::scheduler::{NoAdvanceTimeGuard, SchedulerClient};
 use crate::supervisor::Supervisor;
 use crate::{
     Actor, ActorContext, ActorExitStatus, ActorHandle, KillSwitch, Mailbox, QueueCapacity,
+    RecvError,
 };
 
 #[derive(Clone)]
@@ -166,7 +169,9 @@ impl<A: Actor> SpawnBuilder<A> {
         let loop_async_actor_future =
             async move { actor_loop(actor, inbox, no_advance_time_guard, ctx).await };
         let join_handle = ActorJoinHandle::new(runtime_handle.spawn(loop_async_actor_future));
-        ctx_clone.registry().register(&mailbox, join_handle.clone());
+        ctx_clone
+            .registry()
+            .register(&ctx_clone, join_handle.clone());
         let actor_handle = ActorHandle::new(state_rx, join_handle, ctx_clone);
         (mailbox, actor_handle)
     }
@@ -209,7 +214,9 @@ impl<A: Actor + Default> SpawnBuilder<A> {
 /// If a high priority message is arrives first it is guaranteed to be processed first.
 /// This other way around is however not guaranteed.
 async fn recv_envelope<A: Actor>(inbox: &mut Inbox<A>, ctx: &ActorContext<A>) -> Envelope<A> {
-    if ctx.state().is_running() {
+    #[cfg(any(test, feature = "testsuite"))]
+    ctx.set_waiting_for_messages(true);
+    let envelope = if ctx.state().is_running() {
         ctx.protect_future(inbox.recv()).await.expect(
             "Disconnection should be impossible because the ActorContext holds a Mailbox too",
         )
@@ -217,19 +224,48 @@ async fn recv_envelope<A: Actor>(inbox: &mut Inbox<A>, ctx: &ActorContext<A>) ->
         // The actor is paused. We only process command and scheduled message.
         ctx.protect_future(inbox.recv_cmd_and_scheduled_msg_only())
             .await
-    }
+    };
+    #[cfg(any(test, feature = "testsuite"))]
+    ctx.set_waiting_for_messages(false);
+    envelope
 }
 
 fn try_recv_envelope<A: Actor>(inbox: &mut Inbox<A>, ctx: &ActorContext<A>) -> Option<Envelope<A>> {
     if ctx.state().is_running() {
         inbox.try_recv()
     } else {
-        // The actor is paused. We only process command and scheduled message.
-        inbox.try_recv_cmd_and_scheduled_msg_only()
+        // The actor is paused. We only process command and scheduled message, unless the actor
+        // was asked to step through its low priority messages.
+        inbox
+            .try_recv_cmd_and_scheduled_msg_only()
+            .or_else(|_| try_recv_step_envelope(inbox, ctx))
     }
     .ok()
 }
 
+/// Receives the next low priority message of a paused actor that was allowed to process it by
+/// `Universe::deliver_next_to`.
+#[cfg(any(test, feature = "testsuite"))]
+fn try_recv_step_envelope<A: Actor>(
+    inbox: &mut Inbox<A>,
+    ctx: &ActorContext<A>,
+) -> Result<Envelope<A>, RecvError> {
+    if !ctx.has_step_credit() {
+        return Err(RecvError::NoMessageAvailable);
+    }
+    let envelope = inbox.try_recv_low_priority_only()?;
+    ctx.consume_step_credit();
+    Ok(envelope)
+}
+
+#[cfg(not(any(test, feature = "testsuite")))]
+fn try_recv_step_envelope<A: Actor>(
+    _inbox: &mut Inbox<A>,
+    _ctx: &ActorContext<A>,
+) -> Result<Envelope<A>, RecvError> {
+    Err(RecvError::NoMessageAvailable)
+}
+
 struct ActorExecutionEnv<A: Actor> {
     actor: SyncWrapper<A>,
     inbox: Inbox<A>,
@@ -257,6 +293,9 @@ impl<A: Actor> ActorExecutionEnv<A> {
         envelope
             .handle_message(self.actor.get_mut(), &self.ctx)
             .await?;
+        self.ctx
+            .message_counter()
+            .record_processed_message(Instant::now());
         Ok(())
     }
 
@@ -288,6 +327,12 @@ impl<A: Actor> ActorExecutionEnv<A> {
             if self.inbox.is_empty() {
                 break;
             }
+            if !self.ctx.state().is_running() {
+                // The actor is paused and cannot process its pending messages. Rather than
+                // spinning, we wait for the next command.
+                let envelope = recv_envelope(&mut self.inbox, &self.ctx).await;
+                self.process_one_message(envelope).await?;
+            }
         }
         self.actor.get_mut().on_drained_messages(&self.ctx).await?;
         self.ctx.idle();
@@ -335,7 +380,7 @@ impl<A: Actor> ActorExecutionEnv<A> {
             ActorExitStatus::Failure(err) => {
                 error!(cause=?err, exit_status=?exit_status, "actor-failure");
             }
-            ActorExitStatus::Panicked => {
+            ActorExitStatus::DownstreamTimeout | ActorExitStatus::Panicked => {
                 error!(exit_status=?exit_status, "actor-failure");
             }
         }
@@ -106,7 +106,8 @@ impl<A: Actor> Actor for Supervisor<A> {
             }
             ActorExitStatus::Failure(_)
             | ActorExitStatus::Success
-            | ActorExitStatus::DownstreamClosed => {}
+            | ActorExitStatus::DownstreamClosed
+            | ActorExitStatus::DownstreamTimeout => {}
             ActorExitStatus::Panicked => {}
         }
 
@@ -173,7 +174,8 @@ impl<A: Actor> Supervisor<A> {
             ActorExitStatus::Killed => {
                 self.metrics.num_kills += 1;
             }
-            ActorExitStatus::Failure(_err) => {
+            // The downstream actor is still alive, so the actor is respawned like a failed one.
+            ActorExitStatus::Failure(_) | ActorExitStatus::DownstreamTimeout => {
                 self.metrics.num_errors += 1;
             }
             ActorExitStatus::Panicked => {
@@ -392,6 +394,37 @@ mod tests {
         ));
     }
 
+    #[tokio::test(start_paused = true)]
+    async fn test_supervisor_kills_frozen_actor_in_virtual_time() {
+        let universe = Universe::new();
+        let actor = FailingActor::default();
+        let (mailbox, supervisor_handle) = universe.spawn_builder().supervise(actor);
+        mailbox
+            .send_message(FailingActorMessage::Freeze(
+                crate::HEARTBEAT.mul_f32(3.0f32),
+            ))
+            .await
+            .unwrap();
+        // The first health check sees the progress made when the freeze started.
+        universe
+            .advance_time(crate::HEARTBEAT.mul_f32(1.5f32))
+            .await;
+        assert_eq!(supervisor_handle.observe().await.metrics.num_kills, 0);
+        // The second one identifies the actor as frozen.
+        universe
+            .advance_time(crate::HEARTBEAT.mul_f32(2.0f32))
+            .await;
+        assert_eq!(supervisor_handle.observe().await.metrics.num_kills, 1);
+        assert_eq!(
+            mailbox.ask(FailingActorMessage::Increment).await.unwrap(),
+            1
+        );
+        assert!(!matches!(
+            supervisor_handle.quit().await.0,
+            ActorExitStatus::Panicked
+        ));
+    }
+
     #[tokio::test]
     async fn test_supervisor_forwards_quit_commands() {
         let universe = Universe::with_accelerated_time();
@@ -20,6 +20,7 @@
 use std::cell::Cell;
 use std::collections::HashMap;
 use std::ops::Mul;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
@@ -288,6 +289,111 @@ async fn test_timeouting_actor() {
     buggy_handle.kill().await;
 }
 
+struct SlowActor;
+
+#[derive(Debug)]
+struct SleepFor(Duration);
+
+#[derive(Debug)]
+struct WaitFor(tokio::sync::oneshot::Receiver<()>);
+
+impl Actor for SlowActor {
+    type ObservableState = ();
+
+    fn observable_state(&self) {}
+}
+
+#[async_trait]
+impl Handler<SleepFor> for SlowActor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        sleep_for: SleepFor,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        ctx.protect_future_for(tokio::time::sleep(sleep_for.0), crate::HEARTBEAT.mul(5))
+            .await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<WaitFor> for SlowActor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        wait_for: WaitFor,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        let _ = ctx
+            .protect_future_for(wait_for.0, crate::HEARTBEAT.mul(2))
+            .await;
+        Ok(())
+    }
+}
+
+#[tokio::test]
+async fn test_protect_future_for_keeps_pending_actor_alive() {
+    let universe = Universe::new();
+    let (slow_mailbox, slow_handle) = universe.spawn_builder().spawn(SlowActor);
+    slow_mailbox
+        .send_message(SleepFor(crate::HEARTBEAT.mul(3)))
+        .await
+        .unwrap();
+    for _ in 0..4 {
+        tokio::time::sleep(*crate::HEARTBEAT).await;
+        assert_eq!(slow_handle.check_health(true), Health::Healthy);
+    }
+    universe.assert_quit().await;
+}
+
+#[tokio::test]
+async fn test_protect_future_for_does_not_protect_never_woken_future() {
+    let universe = Universe::new();
+    let (slow_mailbox, slow_handle) = universe.spawn_builder().spawn(SlowActor);
+    let (wake_tx, wake_rx) = tokio::sync::oneshot::channel();
+    slow_mailbox.send_message(WaitFor(wake_rx)).await.unwrap();
+
+    tokio::time::sleep(*crate::HEARTBEAT).await;
+    assert_eq!(slow_handle.check_health(true), Health::Healthy);
+
+    // Once the protection has expired, the future is not polled anymore.
+    tokio::time::sleep(crate::HEARTBEAT.mul(2)).await;
+    assert_eq!(slow_handle.check_health(true), Health::Healthy);
+    assert_eq!(slow_handle.check_health(true), Health::FailureOrUnhealthy);
+    wake_tx.send(()).unwrap();
+    universe.assert_quit().await;
+}
+
+#[tokio::test]
+async fn test_drain_actor() {
+    let universe = Universe::with_accelerated_time();
+    let (ping_mailbox, ping_handle) = universe.spawn_builder().spawn(PingReceiverActor::default());
+    for _ in 0u32..100u32 {
+        assert!(ping_mailbox.send_message(Ping).await.is_ok());
+    }
+    let (exit_status, ping_count) = ping_handle.drain().await;
+    assert!(exit_status.is_success());
+    assert_eq!(ping_count, 100);
+    universe.assert_quit().await;
+}
+
+#[tokio::test]
+async fn test_drain_paused_actor() {
+    let universe = Universe::with_accelerated_time();
+    let (ping_mailbox, ping_handle) = universe.spawn_builder().spawn(PingReceiverActor::default());
+    ping_handle.pause();
+    for _ in 0u32..100u32 {
+        assert!(ping_mailbox.send_message(Ping).await.is_ok());
+    }
+    let (exit_status, ping_count) = ping_handle.drain().await;
+    assert!(exit_status.is_success());
+    assert_eq!(ping_count, 100);
+    universe.assert_quit().await;
+}
+
 #[tokio::test]
 async fn test_pause_actor() {
     quickwit_common::setup_logging_for_tests();
@@ -725,3 +831,99 @@ async fn test_unsync_actor_message() {
 
     universe.assert_quit().await;
 }
+
+struct RecordingActor {
+    name: &'static str,
+    log: Arc<Mutex<Vec<(&'static str, usize)>>>,
+}
+
+#[derive(Debug)]
+struct Record(usize);
+
+impl Actor for RecordingActor {
+    type ObservableState = ();
+
+    fn observable_state(&self) {}
+}
+
+#[async_trait]
+impl Handler<Record> for RecordingActor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        record: Record,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        self.log.lock().unwrap().push((self.name, record.0));
+        Ok(())
+    }
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_universe_deliver_next_to_controls_interleaving() {
+    let universe = Universe::new();
+    let log: Arc<Mutex<Vec<(&'static str, usize)>>> = Default::default();
+    let (left_mailbox, left_handle) = universe.spawn_builder().spawn(RecordingActor {
+        name: "left",
+        log: log.clone(),
+    });
+    let (right_mailbox, right_handle) = universe.spawn_builder().spawn(RecordingActor {
+        name: "right",
+        log: log.clone(),
+    });
+    left_handle.pause();
+    right_handle.pause();
+    universe.run_until_idle().await;
+
+    for i in 0..2 {
+        left_mailbox.send_message(Record(i)).await.unwrap();
+        right_mailbox.send_message(Record(i)).await.unwrap();
+    }
+    universe.run_until_idle().await;
+    assert!(log.lock().unwrap().is_empty());
+
+    assert!(universe.deliver_next_to(&right_handle).await);
+    assert!(universe.deliver_next_to(&left_handle).await);
+    assert!(universe.deliver_next_to(&right_handle).await);
+    assert!(!universe.deliver_next_to(&right_handle).await);
+    assert_eq!(
+        *log.lock().unwrap(),
+        [("right", 0), ("left", 0), ("right", 1)]
+    );
+    left_handle.resume();
+    universe.run_until_idle().await;
+    assert_eq!(log.lock().unwrap().last(), Some(&("left", 1)));
+    universe.assert_quit().await;
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_universe_run_until_idle_waits_for_pending_messages() {
+    let universe = Universe::new();
+    let log: Arc<Mutex<Vec<(&'static str, usize)>>> = Default::default();
+    let (mailbox, handle) = universe.spawn_builder().spawn(RecordingActor {
+        name: "recorder",
+        log: log.clone(),
+    });
+    for i in 0..10 {
+        mailbox.send_message(Record(i)).await.unwrap();
+    }
+    let start = tokio::time::Instant::now();
+    universe.run_until_idle().await;
+    assert_eq!(tokio::time::Instant::now(), start);
+    assert_eq!(log.lock().unwrap().len(), 10);
+    assert_eq!(handle.state(), ActorState::Idle);
+    universe.assert_quit().await;
+}
+
+#[tokio::test(start_paused = true)]
+#[should_panic(expected = "the universe is not idle")]
+async fn test_universe_run_until_idle_panics_on_actor_awaiting_timer() {
+    let universe = Universe::new();
+    let (slow_mailbox, _slow_handle) = universe.spawn_builder().spawn(SlowActor);
+    slow_mailbox
+        .send_message(SleepFor(Duration::from_secs(1)))
+        .await
+        .unwrap();
+    universe.run_until_idle().await;
+}
@@ -26,6 +26,13 @@ use crate::registry::ActorObservation;
 use crate::scheduler::start_scheduler;
 use crate::spawn_builder::{SpawnBuilder, SpawnContext};
 use crate::{Actor, ActorExitStatus, Command, Inbox, Mailbox, QueueCapacity};
+#[cfg(any(test, feature = "testsuite"))]
+use crate::{ActorHandle, ActorState};
+
+/// Maximum number of times the universe yields to the runtime while waiting for its actors to
+/// become idle. See `Universe::run_until_idle`.
+#[cfg(any(test, feature = "testsuite"))]
+const MAX_YIELDS_UNTIL_IDLE: usize = 10_000;
 
 /// Universe serves as the top-level context in which Actor can be spawned.
 /// It is *not* a singleton. A typical application will usually have only one universe hosting all
@@ -125,6 +132,71 @@ impl Universe {
         self.spawn_ctx.registry.quit().await
     }
 
+    /// Waits until every actor of the universe is idle, i.e. has terminated or is waiting for its
+    /// next message while none of the messages it can process is pending in its mailbox.
+    ///
+    /// This is the building block of deterministic tests, and is meant to be used with a paused
+    /// tokio clock (`#[tokio::test(start_paused = true)]`). The clock does not move: actors only
+    /// run when this function yields to the runtime. An actor whose handler awaits a timer
+    /// cannot become idle that way, so this function panics if the universe is still busy after
+    /// `MAX_YIELDS_UNTIL_IDLE` yields. Use `Universe::advance_time` in that case. Tasks that are
+    /// not actors are not waited for.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub async fn run_until_idle(&self) {
+        assert!(
+            self.yield_until_idle(MAX_YIELDS_UNTIL_IDLE).await,
+            "the universe is not idle after {MAX_YIELDS_UNTIL_IDLE} yields, an actor is still \
+             processing a message"
+        );
+    }
+
+    /// Advances the paused tokio clock by `duration`, firing the scheduled messages and timers
+    /// of the universe in order, then lets the actors process the messages they received.
+    ///
+    /// Unlike `Universe::run_until_idle`, this function does not require the universe to become
+    /// idle: actors whose handler awaits a later timer remain busy, which makes it possible to
+    /// test heartbeat expiry without sleeping.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub async fn advance_time(&self, duration: Duration) {
+        tokio::time::sleep(duration).await;
+        self.yield_until_idle(MAX_YIELDS_UNTIL_IDLE).await;
+    }
+
+    /// Yields to the runtime until every actor of the universe is idle, at most `max_num_yields`
+    /// times. Returns whether the universe is idle.
+    #[cfg(any(test, feature = "testsuite"))]
+    async fn yield_until_idle(&self, max_num_yields: usize) -> bool {
+        for _ in 0..max_num_yields {
+            if self.spawn_ctx.registry.is_idle() {
+                return true;
+            }
+            tokio::task::yield_now().await;
+        }
+        self.spawn_ctx.registry.is_idle()
+    }
+
+    /// Makes a paused actor process the next message of its low priority queue, then waits until
+    /// the universe is idle. Returns false if the actor had no pending message.
+    ///
+    /// Pausing actors and delivering their messages one by one lets tests control the
+    /// interleaving of messages between actors. Commands and scheduled messages are still
+    /// processed as soon as they arrive. See `Universe::run_until_idle`.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub async fn deliver_next_to<A: Actor>(&self, actor_handle: &ActorHandle<A>) -> bool {
+        assert_eq!(
+            actor_handle.state(),
+            ActorState::Paused,
+            "only paused actors can be stepped through their messages"
+        );
+        let actor_context = actor_handle.actor_context();
+        actor_context.add_step_credit();
+        let _ = actor_context
+            .mailbox()
+            .send_message_with_high_priority(Command::Nudge);
+        self.run_until_idle().await;
+        actor_context.clear_step_credits() == 0
+    }
+
     /// Gracefully quits all registered actors and asserts that none of them panicked.
     ///
     /// This is useful for testing purposes to detect failed asserts in actors.
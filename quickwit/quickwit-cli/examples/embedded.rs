@@ -0,0 +1,107 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Indexes an NDJSON file and searches it within a single process.
+//!
+//! ```bash
+//! cargo run --example embedded -- <path/to/docs.json> <query>
+//! ```
+//!
+//! The documents are expected to have a `message` text field, searched by default.
+
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use anyhow::Context;
+use quickwit_cli::embedded::{EmbeddedQuickwit, SourceIndexingConfig};
+use quickwit_common::uri::Uri;
+use quickwit_config::{
+    load_index_config_from_user_config, ConfigFormat, SourceConfig, SourceInputFormat, SourceParams,
+};
+use quickwit_search::SearchResponseRest;
+use quickwit_serve::{search_request_from_api_request, SearchRequestQueryString};
+
+const INDEX_CONFIG_YAML: &str = r#"
+version: 0.6
+index_id: embedded-example
+doc_mapping:
+  mode: dynamic
+  field_mappings:
+    - name: message
+      type: text
+search_settings:
+  default_search_fields: [message]
+"#;
+
+// The embedded mode runs on the runtime provided by the caller.
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let docs_path = PathBuf::from(args.next().context("missing path to the documents")?);
+    let query = args.next().context("missing query")?;
+
+    let metastore_uri = Uri::from_str("ram:///embedded-example/metastore")?;
+    let default_index_root_uri = Uri::from_str("ram:///embedded-example/indexes")?;
+    let mut embedded_quickwit =
+        EmbeddedQuickwit::open(&metastore_uri, &default_index_root_uri).await?;
+
+    let index_config = load_index_config_from_user_config(
+        ConfigFormat::Yaml,
+        INDEX_CONFIG_YAML.as_bytes(),
+        &default_index_root_uri,
+    )?;
+    let index_id = index_config.index_id.clone();
+    embedded_quickwit.create_index(index_config).await?;
+
+    let pipeline_id = embedded_quickwit
+        .start_source(SourceIndexingConfig {
+            index_id: index_id.clone(),
+            source_config: SourceConfig {
+                source_id: "embedded-file-source".to_string(),
+                max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+                desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+                enabled: true,
+                source_params: SourceParams::file(docs_path),
+                transform_config: None,
+                input_format: SourceInputFormat::Json,
+            },
+        })
+        .await?;
+    let statistics = embedded_quickwit.wait_for_source(&pipeline_id).await?;
+    println!(
+        "Indexed {} documents into {} split(s).",
+        statistics.num_docs, statistics.num_published_splits
+    );
+
+    let search_request = search_request_from_api_request(
+        vec![index_id],
+        SearchRequestQueryString {
+            query,
+            max_hits: 10,
+            ..Default::default()
+        },
+    )?;
+    let search_response = embedded_quickwit.search(search_request).await?;
+    let search_response_rest = SearchResponseRest::try_from(search_response)?;
+    println!("{}", serde_json::to_string_pretty(&search_response_rest)?);
+
+    embedded_quickwit.shutdown().await?;
+    Ok(())
+}
@@ -21,8 +21,11 @@ use anyhow::{bail, Context};
 use clap::{arg, Arg, ArgAction, ArgMatches, Command};
 use tracing::Level;
 
+use crate::cluster::{build_cluster_command, ClusterCliCommand};
 use crate::index::{build_index_command, IndexCliCommand};
+use crate::ping::{build_ping_command, PingCliCommand};
 use crate::service::{build_run_command, RunCliCommand};
+use crate::snapshot::{build_snapshot_command, SnapshotCliCommand};
 use crate::source::{build_source_command, SourceCliCommand};
 use crate::split::{build_split_command, SplitCliCommand};
 use crate::tool::{build_tool_command, ToolCliCommand};
@@ -50,7 +53,10 @@ pub fn build_cli() -> Command {
         .subcommand(build_index_command().display_order(2))
         .subcommand(build_source_command().display_order(3))
         .subcommand(build_split_command().display_order(4))
-        .subcommand(build_tool_command().display_order(5))
+        .subcommand(build_snapshot_command().display_order(5))
+        .subcommand(build_cluster_command().display_order(6))
+        .subcommand(build_tool_command().display_order(7))
+        .subcommand(build_ping_command().display_order(8))
         .arg_required_else_help(true)
         .disable_help_subcommand(true)
         .subcommand_required(true)
@@ -61,8 +67,11 @@ pub enum CliCommand {
     Run(RunCliCommand),
     Index(IndexCliCommand),
     Split(SplitCliCommand),
+    Snapshot(SnapshotCliCommand),
+    Cluster(ClusterCliCommand),
     Source(SourceCliCommand),
     Tool(ToolCliCommand),
+    Ping(PingCliCommand),
 }
 
 impl CliCommand {
@@ -72,7 +81,10 @@ impl CliCommand {
             CliCommand::Index(subcommand) => subcommand.default_log_level(),
             CliCommand::Source(_) => Level::ERROR,
             CliCommand::Split(_) => Level::ERROR,
+            CliCommand::Snapshot(_) => Level::ERROR,
+            CliCommand::Cluster(_) => Level::ERROR,
             CliCommand::Tool(_) => Level::ERROR,
+            CliCommand::Ping(_) => Level::ERROR,
         }
     }
 
@@ -85,7 +97,10 @@ impl CliCommand {
             "run" => RunCliCommand::parse_cli_args(submatches).map(CliCommand::Run),
             "source" => SourceCliCommand::parse_cli_args(submatches).map(CliCommand::Source),
             "split" => SplitCliCommand::parse_cli_args(submatches).map(CliCommand::Split),
+            "snapshot" => SnapshotCliCommand::parse_cli_args(submatches).map(CliCommand::Snapshot),
+            "cluster" => ClusterCliCommand::parse_cli_args(submatches).map(CliCommand::Cluster),
             "tool" => ToolCliCommand::parse_cli_args(submatches).map(CliCommand::Tool),
+            "ping" => PingCliCommand::parse_cli_args(submatches).map(CliCommand::Ping),
             _ => bail!("unknown command `{subcommand}`"),
         }
     }
@@ -96,7 +111,10 @@ impl CliCommand {
             CliCommand::Run(subcommand) => subcommand.execute().await,
             CliCommand::Source(subcommand) => subcommand.execute().await,
             CliCommand::Split(subcommand) => subcommand.execute().await,
+            CliCommand::Snapshot(subcommand) => subcommand.execute().await,
+            CliCommand::Cluster(subcommand) => subcommand.execute().await,
             CliCommand::Tool(subcommand) => subcommand.execute().await,
+            CliCommand::Ping(subcommand) => subcommand.execute().await,
         }
     }
 }
@@ -0,0 +1,152 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::{bail, Context};
+use clap::{ArgMatches, Command};
+use colored::Colorize;
+use quickwit_cluster::{ClusterMemberInfo, ClusterMemberState};
+use tabled::{Table, Tabled};
+use tracing::debug;
+
+use crate::{client_args, make_table, ClientArgs};
+
+pub fn build_cluster_command() -> Command {
+    Command::new("cluster")
+        .about("Inspects the cluster formed by the Quickwit nodes.")
+        .args(client_args())
+        .subcommand(
+            Command::new("members")
+                .about("Lists the members of the cluster known by the node, with their state."),
+        )
+        .arg_required_else_help(true)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ListMembersArgs {
+    pub client_args: ClientArgs,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ClusterCliCommand {
+    Members(ListMembersArgs),
+}
+
+impl ClusterCliCommand {
+    pub fn parse_cli_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, submatches) = matches
+            .remove_subcommand()
+            .context("failed to parse cluster subcommand")?;
+        match subcommand.as_str() {
+            "members" => Self::parse_members_args(submatches),
+            _ => bail!("unknown cluster subcommand `{subcommand}`"),
+        }
+    }
+
+    fn parse_members_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        Ok(Self::Members(ListMembersArgs { client_args }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::Members(args) => list_members_cli(args).await,
+        }
+    }
+}
+
+async fn list_members_cli(args: ListMembersArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "list-members");
+    let qw_client = args.client_args.client();
+    let members_info = qw_client
+        .cluster()
+        .members()
+        .await
+        .context("failed to list cluster members")?;
+    println!("{}", make_members_table(&members_info, "Cluster members"));
+    Ok(())
+}
+
+fn make_members_table(members_info: &[ClusterMemberInfo], title: &str) -> Table {
+    let rows = members_info.iter().map(|member_info| MemberRow {
+        node_id: member_info.node_id.clone(),
+        generation_id: member_info.generation_id,
+        gossip_advertise_addr: member_info.gossip_advertise_addr.to_string(),
+        grpc_advertise_addr: member_info
+            .grpc_advertise_addr
+            .map(|grpc_advertise_addr| grpc_advertise_addr.to_string())
+            .unwrap_or_default(),
+        enabled_services: member_info.enabled_services.join(", "),
+        state: format_member_state(member_info.state),
+    });
+    make_table(title, rows, false)
+}
+
+fn format_member_state(state: ClusterMemberState) -> String {
+    match state {
+        ClusterMemberState::Ready => "ready".green().to_string(),
+        ClusterMemberState::Live => "live".yellow().to_string(),
+        ClusterMemberState::Dead => "dead".red().to_string(),
+    }
+}
+
+#[derive(Tabled)]
+struct MemberRow {
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Generation")]
+    generation_id: u64,
+    #[tabled(rename = "Gossip address")]
+    gossip_advertise_addr: String,
+    #[tabled(rename = "gRPC address")]
+    grpc_advertise_addr: String,
+    #[tabled(rename = "Services")]
+    enabled_services: String,
+    #[tabled(rename = "State")]
+    state: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use reqwest::Url;
+
+    use super::*;
+    use crate::cli::{build_cli, CliCommand};
+
+    #[test]
+    fn test_parse_cluster_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "cluster",
+            "members",
+            "--endpoint",
+            "http://127.0.0.1:8000",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_endpoint = Url::from_str("http://127.0.0.1:8000").unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Cluster(ClusterCliCommand::Members(ListMembersArgs {
+                client_args,
+            })) if client_args.cluster_endpoint == expected_endpoint
+        ));
+        Ok(())
+    }
+}
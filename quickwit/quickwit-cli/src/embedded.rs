@@ -0,0 +1,443 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Embedded mode: indexes and searches in the calling process, without a server or a cluster.
+//!
+//! [`EmbeddedQuickwit`] is meant for small deployments and integration tests. It runs the
+//! indexing pipelines as in-process actors and serves searches directly from the index storage,
+//! the same way `quickwit tool local-ingest` and `quickwit tool local-search` do.
+//!
+//! # Runtime
+//!
+//! The caller provides the Tokio runtime: [`EmbeddedQuickwit::open`] must be called from within
+//! a Tokio runtime, and fails otherwise. Searches and metastore calls run on that runtime. The
+//! indexing actors run on the process-wide actor runtimes, which are started by the first call to
+//! [`EmbeddedQuickwit::open`] and live until the process exits.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{bail, Context};
+use quickwit_actors::{ActorExitStatus, ActorHandle, Mailbox, Universe};
+use quickwit_common::pubsub::EventBroker;
+use quickwit_common::runtimes::RuntimesConfig;
+use quickwit_common::uri::Uri;
+use quickwit_config::service::QuickwitService;
+use quickwit_config::{ConfigFormat, IndexConfig, IndexerConfig, NodeConfig, SourceConfig};
+use quickwit_index_management::IndexService;
+use quickwit_indexing::actors::{DrainPipeline, IndexingService};
+use quickwit_indexing::models::{DetachIndexingPipeline, IndexingStatistics, SpawnPipeline};
+use quickwit_indexing::IndexingPipeline;
+use quickwit_ingest::IngesterPool;
+use quickwit_metastore::IndexMetadata;
+use quickwit_proto::indexing::IndexingPipelineId;
+use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_proto::search::{SearchRequest, SearchResponse};
+use quickwit_proto::types::PipelineUid;
+use quickwit_search::single_node_search;
+use quickwit_storage::StorageResolver;
+use serde_json::json;
+use tempfile::TempDir;
+use tracing::info;
+
+use crate::tool::create_empty_cluster;
+use crate::{get_resolvers, start_actor_runtimes};
+
+/// Describes a source to index with [`EmbeddedQuickwit::start_source`].
+#[derive(Clone, Debug)]
+pub struct SourceIndexingConfig {
+    /// ID of the target index.
+    pub index_id: String,
+    /// Source to read documents from.
+    pub source_config: SourceConfig,
+}
+
+/// A Quickwit instance running indexing and search in the calling process.
+///
+/// See the [module documentation](self) for the runtime expectations.
+pub struct EmbeddedQuickwit {
+    metastore: MetastoreServiceClient,
+    storage_resolver: StorageResolver,
+    universe: Universe,
+    indexing_service_mailbox: Mailbox<IndexingService>,
+    indexing_service_handle: ActorHandle<IndexingService>,
+    indexing_pipelines: HashMap<IndexingPipelineId, ActorHandle<IndexingPipeline>>,
+    // Holds the indexing scratch directories and the local split store. Removed on drop.
+    _data_dir: TempDir,
+}
+
+impl EmbeddedQuickwit {
+    /// Opens an embedded instance backed by the metastore at `metastore_uri`. Indexes created
+    /// through the instance are stored under `default_index_root_uri` unless their config
+    /// specifies an index URI.
+    ///
+    /// Both URIs can point to local files or to RAM (`ram:///...`).
+    pub async fn open(
+        metastore_uri: &Uri,
+        default_index_root_uri: &Uri,
+    ) -> anyhow::Result<EmbeddedQuickwit> {
+        if tokio::runtime::Handle::try_current().is_err() {
+            bail!("the embedded mode must be opened from within a Tokio runtime");
+        }
+        let data_dir = tempfile::Builder::new()
+            .prefix("quickwit-embedded-")
+            .tempdir()
+            .context("failed to create data directory")?;
+        let node_config_json = json!({
+            "version": "0.6",
+            "node_id": "embedded",
+            "metastore_uri": metastore_uri.as_str(),
+            "default_index_root_uri": default_index_root_uri.as_str(),
+            "data_dir": data_dir.path(),
+        });
+        let node_config = NodeConfig::load(
+            ConfigFormat::Json,
+            serde_json::to_string(&node_config_json)?.as_bytes(),
+        )
+        .await
+        .context("failed to build node config")?;
+        let (storage_resolver, metastore_resolver) =
+            get_resolvers(&node_config.storage_configs, &node_config.metastore_configs);
+        let metastore = metastore_resolver
+            .resolve(&node_config.metastore_uri)
+            .await?;
+
+        let runtimes_config = RuntimesConfig::default();
+        start_actor_runtimes(
+            runtimes_config,
+            &HashSet::from_iter([QuickwitService::Indexer]),
+        )?;
+        // No other node is aware of this cluster.
+        let cluster = create_empty_cluster(&node_config).await?;
+        let indexing_service = IndexingService::new(
+            node_config.node_id.clone(),
+            node_config.data_dir_path.clone(),
+            IndexerConfig::default(),
+            runtimes_config.num_threads_blocking,
+            cluster,
+            metastore.clone(),
+            None,
+            IngesterPool::default(),
+            storage_resolver.clone(),
+            EventBroker::default(),
+        )
+        .await?;
+        let universe = Universe::new();
+        let (indexing_service_mailbox, indexing_service_handle) =
+            universe.spawn_builder().spawn(indexing_service);
+        info!(metastore_uri=%metastore_uri, "opened embedded quickwit");
+
+        Ok(EmbeddedQuickwit {
+            metastore,
+            storage_resolver,
+            universe,
+            indexing_service_mailbox,
+            indexing_service_handle,
+            indexing_pipelines: HashMap::new(),
+            _data_dir: data_dir,
+        })
+    }
+
+    /// Returns a client for the metastore backing this instance.
+    pub fn metastore(&self) -> MetastoreServiceClient {
+        self.metastore.clone()
+    }
+
+    /// Creates an index. Fails if the index already exists.
+    pub async fn create_index(&self, index_config: IndexConfig) -> anyhow::Result<IndexMetadata> {
+        let mut index_service =
+            IndexService::new(self.metastore.clone(), self.storage_resolver.clone());
+        let index_metadata = index_service.create_index(index_config, false).await?;
+        Ok(index_metadata)
+    }
+
+    /// Spawns an indexing pipeline reading from the given source.
+    ///
+    /// The documents become searchable as soon as the pipeline publishes the splits holding them.
+    /// The returned ID identifies the pipeline in [`Self::observe_source`] and
+    /// [`Self::wait_for_source`].
+    pub async fn start_source(
+        &mut self,
+        source_indexing_config: SourceIndexingConfig,
+    ) -> anyhow::Result<IndexingPipelineId> {
+        let pipeline_id = self
+            .indexing_service_mailbox
+            .ask_for_res(SpawnPipeline {
+                index_id: source_indexing_config.index_id,
+                source_config: source_indexing_config.source_config,
+                pipeline_uid: PipelineUid::new(),
+            })
+            .await?;
+        let indexing_pipeline_handle = self
+            .indexing_service_mailbox
+            .ask_for_res(DetachIndexingPipeline {
+                pipeline_id: pipeline_id.clone(),
+            })
+            .await?;
+        self.indexing_pipelines
+            .insert(pipeline_id.clone(), indexing_pipeline_handle);
+        Ok(pipeline_id)
+    }
+
+    /// Returns the latest statistics of a running pipeline, or `None` if the pipeline is unknown
+    /// or has already been waited for.
+    pub fn observe_source(&self, pipeline_id: &IndexingPipelineId) -> Option<IndexingStatistics> {
+        let indexing_pipeline_handle = self.indexing_pipelines.get(pipeline_id)?;
+        indexing_pipeline_handle.refresh_observe();
+        Some(indexing_pipeline_handle.last_observation())
+    }
+
+    /// Waits for a pipeline to exit, which happens when a finite source such as a file has been
+    /// fully indexed, and returns its final statistics.
+    pub async fn wait_for_source(
+        &mut self,
+        pipeline_id: &IndexingPipelineId,
+    ) -> anyhow::Result<IndexingStatistics> {
+        let indexing_pipeline_handle = self
+            .indexing_pipelines
+            .remove(pipeline_id)
+            .with_context(|| format!("pipeline `{pipeline_id}` not found"))?;
+        let (exit_status, statistics) = indexing_pipeline_handle.join().await;
+        if !exit_status.is_success() {
+            bail!("pipeline `{pipeline_id}` failed: {exit_status:?}");
+        }
+        Ok(statistics)
+    }
+
+    /// Searches the published splits of the target indexes.
+    ///
+    /// Search requests can be built from query string parameters with
+    /// [`quickwit_serve::search_request_from_api_request`].
+    pub async fn search(&self, search_request: SearchRequest) -> anyhow::Result<SearchResponse> {
+        let search_response = single_node_search(
+            search_request,
+            self.metastore.clone(),
+            self.storage_resolver.clone(),
+        )
+        .await?;
+        Ok(search_response)
+    }
+
+    /// Shuts the instance down gracefully.
+    ///
+    /// The running pipelines stop reading from their sources and exit once the documents read so
+    /// far have been published. Returns the final statistics of these pipelines.
+    pub async fn shutdown(self) -> anyhow::Result<HashMap<IndexingPipelineId, IndexingStatistics>> {
+        for indexing_pipeline_handle in self.indexing_pipelines.values() {
+            // The pipeline may have exited already, in which case there is nothing to drain.
+            let _ = indexing_pipeline_handle
+                .mailbox()
+                .send_message(DrainPipeline)
+                .await;
+        }
+        let mut pipelines_statistics = HashMap::with_capacity(self.indexing_pipelines.len());
+        let mut failed_pipeline_ids = Vec::new();
+
+        for (pipeline_id, indexing_pipeline_handle) in self.indexing_pipelines {
+            let (exit_status, statistics) = indexing_pipeline_handle.join().await;
+            if !exit_status.is_success() {
+                failed_pipeline_ids.push(pipeline_id.to_string());
+            }
+            pipelines_statistics.insert(pipeline_id, statistics);
+        }
+        self.universe
+            .send_exit_with_success(&self.indexing_service_mailbox)
+            .await?;
+        let (indexing_service_exit_status, _) = self.indexing_service_handle.join().await;
+        self.universe.quit().await;
+
+        if !failed_pipeline_ids.is_empty() {
+            bail!(
+                "failed to drain indexing pipeline(s) `{}`",
+                failed_pipeline_ids.join(", ")
+            );
+        }
+        if !matches!(
+            indexing_service_exit_status,
+            ActorExitStatus::Success | ActorExitStatus::Quit
+        ) {
+            bail!(indexing_service_exit_status);
+        }
+        Ok(pipelines_statistics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::num::NonZeroUsize;
+    use std::str::FromStr;
+
+    use quickwit_common::rand::append_random_suffix;
+    use quickwit_config::{
+        load_index_config_from_user_config, SourceInputFormat, SourceParams, VoidSourceParams,
+    };
+    use quickwit_serve::{search_request_from_api_request, SearchRequestQueryString};
+
+    use super::*;
+
+    const INDEX_CONFIG_YAML: &str = r#"
+version: 0.6
+index_id: {index_id}
+doc_mapping:
+  field_mappings:
+    - name: ts
+      type: datetime
+      input_formats: [unix_timestamp]
+      fast: true
+    - name: level
+      type: text
+    - name: message
+      type: text
+  timestamp_field: ts
+indexing_settings:
+  commit_timeout: 1s
+search_settings:
+  default_search_fields: [message]
+"#;
+
+    async fn open_embedded_for_test(index_id: &str) -> EmbeddedQuickwit {
+        let metastore_uri = Uri::from_str(&format!("ram:///{index_id}/metastore")).unwrap();
+        let default_index_root_uri = Uri::from_str(&format!("ram:///{index_id}/indexes")).unwrap();
+        let embedded_quickwit = EmbeddedQuickwit::open(&metastore_uri, &default_index_root_uri)
+            .await
+            .unwrap();
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            INDEX_CONFIG_YAML.replace("{index_id}", index_id).as_bytes(),
+            &default_index_root_uri,
+        )
+        .unwrap();
+        embedded_quickwit.create_index(index_config).await.unwrap();
+        embedded_quickwit
+    }
+
+    fn source_config_for_test(source_id: &str, source_params: SourceParams) -> SourceConfig {
+        SourceConfig {
+            source_id: source_id.to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params,
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        }
+    }
+
+    #[test]
+    fn test_embedded_quickwit_requires_runtime() {
+        let metastore_uri = Uri::for_test("ram:///embedded/metastore");
+        let default_index_root_uri = Uri::for_test("ram:///embedded/indexes");
+        let error = futures::executor::block_on(EmbeddedQuickwit::open(
+            &metastore_uri,
+            &default_index_root_uri,
+        ))
+        .err()
+        .unwrap();
+        assert!(error.to_string().contains("Tokio runtime"));
+    }
+
+    #[tokio::test]
+    async fn test_embedded_quickwit_ingest_then_search() {
+        let index_id = append_random_suffix("test-embedded");
+        let mut embedded_quickwit = open_embedded_for_test(&index_id).await;
+
+        let mut docs_file = tempfile::NamedTempFile::new().unwrap();
+        for (ts, level, message) in [
+            (1_700_000_000, "info", "embedded hello"),
+            (1_700_000_001, "warn", "embedded world"),
+            (1_700_000_002, "info", "another message"),
+        ] {
+            writeln!(
+                docs_file,
+                r#"{{"ts": {ts}, "level": "{level}", "message": "{message}"}}"#
+            )
+            .unwrap();
+        }
+        docs_file.flush().unwrap();
+
+        let pipeline_id = embedded_quickwit
+            .start_source(SourceIndexingConfig {
+                index_id: index_id.clone(),
+                source_config: source_config_for_test(
+                    "file-source",
+                    SourceParams::file(docs_file.path()),
+                ),
+            })
+            .await
+            .unwrap();
+        assert!(embedded_quickwit.observe_source(&pipeline_id).is_some());
+
+        let statistics = embedded_quickwit
+            .wait_for_source(&pipeline_id)
+            .await
+            .unwrap();
+        assert_eq!(statistics.num_docs, 3);
+        assert_eq!(statistics.num_invalid_docs, 0);
+        assert_eq!(statistics.num_published_splits, 1);
+        assert!(embedded_quickwit.observe_source(&pipeline_id).is_none());
+
+        let search_request = search_request_from_api_request(
+            vec![index_id.clone()],
+            SearchRequestQueryString {
+                query: "embedded".to_string(),
+                max_hits: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let search_response = embedded_quickwit.search(search_request).await.unwrap();
+        assert_eq!(search_response.num_hits, 2);
+
+        let search_request = search_request_from_api_request(
+            vec![index_id],
+            SearchRequestQueryString {
+                query: "level:info".to_string(),
+                max_hits: 10,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let search_response = embedded_quickwit.search(search_request).await.unwrap();
+        assert_eq!(search_response.num_hits, 2);
+
+        let pipelines_statistics = embedded_quickwit.shutdown().await.unwrap();
+        assert!(pipelines_statistics.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_embedded_quickwit_shutdown_drains_pipelines() {
+        let index_id = append_random_suffix("test-embedded-shutdown");
+        let mut embedded_quickwit = open_embedded_for_test(&index_id).await;
+
+        // The void source never exits on its own.
+        let pipeline_id = embedded_quickwit
+            .start_source(SourceIndexingConfig {
+                index_id,
+                source_config: source_config_for_test(
+                    "void-source",
+                    SourceParams::Void(VoidSourceParams),
+                ),
+            })
+            .await
+            .unwrap();
+        let pipelines_statistics = embedded_quickwit.shutdown().await.unwrap();
+        assert_eq!(pipelines_statistics.len(), 1);
+        assert_eq!(pipelines_statistics[&pipeline_id].num_docs, 0);
+    }
+}
@@ -37,16 +37,24 @@ use itertools::Itertools;
 use numfmt::{Formatter, Scales};
 use quickwit_actors::ActorHandle;
 use quickwit_common::uri::Uri;
-use quickwit_config::{ConfigFormat, IndexConfig};
+use quickwit_common::HumanDuration;
+use quickwit_config::{build_doc_mapper, load_index_config_from_user_config, ConfigFormat};
 use quickwit_indexing::models::IndexingStatistics;
 use quickwit_indexing::IndexingPipeline;
 use quickwit_metastore::{IndexMetadata, Split, SplitState};
-use quickwit_proto::search::{CountHits, SortField, SortOrder};
+use quickwit_proto::search::{
+    CountHits, ExplainResult, PartialHit, SearchStats, SortByValue, SortField, SortOrder,
+    SortValue, SplitExplanation,
+};
+use quickwit_query::BooleanOperand;
 use quickwit_rest_client::models::IngestSource;
 use quickwit_rest_client::rest_client::{CommitType, IngestEvent};
-use quickwit_search::SearchResponseRest;
+use quickwit_rest_client::routing::DocRouter;
+use quickwit_search::{encode_page_token, SearchResponseRest};
 use quickwit_serve::{ListSplitsQueryParams, SearchRequestQueryString, SortBy};
 use quickwit_storage::{load_file, StorageResolver};
+use serde_json::Value as JsonValue;
+use tabled::builder::Builder;
 use tabled::settings::object::{FirstRow, Rows, Segment};
 use tabled::settings::panel::Footer;
 use tabled::settings::{Alignment, Disable, Format, Modify, Panel, Rotate, Style};
@@ -54,7 +62,7 @@ use tabled::{Table, Tabled};
 use thousands::Separable;
 use tracing::{debug, Level};
 
-use crate::checklist::GREEN_COLOR;
+use crate::checklist::{GREEN_COLOR, RED_COLOR};
 use crate::stats::{mean, percentile, std_deviation};
 use crate::{client_args, make_table, prompt_confirmation, ClientArgs, THROUGHPUT_WINDOW_SIZE};
 
@@ -72,6 +80,8 @@ pub fn build_index_command() -> Command {
                         .required(true),
                     arg!(--overwrite "Overwrites pre-existing index. This will delete all existing data stored at `index-uri` before creating a new index.")
                         .required(false),
+                    arg!(--"validate-only" "Validates the index config and prints the resulting tantivy schema without creating the index.")
+                        .required(false),
                 ])
             )
         .subcommand(
@@ -122,11 +132,16 @@ pub fn build_index_command() -> Command {
                 .about("Ingest NDJSON documents with the ingest API.")
                 .long_about("Reads NDJSON documents from a file or streamed from stdin and sends them into ingest API.")
                 .args(&[
-                    arg!(--index <INDEX> "ID of the target index")
+                    arg!(--index <INDEX> "ID of the target index. With `--routing-field`, template of the target index IDs, e.g. `logs-{tenant}`.")
                         .display_order(1)
                         .required(true),
                     arg!(--"input-path" <INPUT_PATH> "Location of the input file.")
                         .required(false),
+                    arg!(--"routing-field" <ROUTING_FIELD> "Routes each document to the index obtained by replacing the `{<routing-field>}` placeholder of `--index` with the value of this field.")
+                        .required(false),
+                    arg!(--"default-index" <DEFAULT_INDEX> "ID of the index receiving the documents without a routing field value. If unspecified, these documents are skipped.")
+                        .requires("routing-field")
+                        .required(false),
                     arg!(--"batch-size-limit" <BATCH_SIZE_LIMIT> "Size limit of each submitted document batch.")
                         .required(false),
                     Arg::new("wait")
@@ -156,10 +171,11 @@ pub fn build_index_command() -> Command {
         .subcommand(
             Command::new("search")
                 .display_order(7)
-                .about("Searches an index.")
+                .about("Searches one or several indexes.")
                 .args(&[
-                    arg!(--index <INDEX> "ID of the target index")
+                    arg!(--index <INDEX> "ID or ID pattern of the target index, e.g. `logs-*`. Can be specified multiple times to search several indexes.")
                         .display_order(1)
+                        .action(ArgAction::Append)
                         .required(true),
                     arg!(--query <QUERY> "Query expressed in natural query language ((barack AND obama) OR \"president of united states\"). Learn more on https://quickwit.io/docs/reference/search-language.")
                         .display_order(2)
@@ -182,8 +198,35 @@ pub fn build_index_command() -> Command {
                         .required(false),
                     arg!(--"end-timestamp" <TIMESTAMP> "Filters out documents after that timestamp (time-series indexes only).")
                         .required(false),
+                    arg!(--tags <TAG> "Restricts the search to splits carrying at least one of these tags, e.g. `tenant:acme`. Can be specified multiple times.")
+                        .action(ArgAction::Append)
+                        .required(false),
+                    arg!(--snapshot <SNAPSHOT_ID> "Searches the index as it was when this snapshot was created.")
+                        .required(false),
+                    arg!(--"geo-bbox" <GEO_BBOX> "JSON serialized bounding box filter on a `geo_point` field, e.g. `{\"field\": \"location\", \"top_left\": \"48.9,2.2\", \"bottom_right\": \"48.8,2.4\"}`.")
+                        .required(false),
+                    arg!(--"geo-distance" <GEO_DISTANCE> "JSON serialized distance filter on a `geo_point` field, e.g. `{\"field\": \"location\", \"center\": \"48.85,2.35\", \"radius_m\": 1000}`.")
+                        .required(false),
                     arg!(--"sort-by-score" "Sorts documents by their BM25 score.")
                         .required(false),
+                    arg!(--verbose "Includes statistics about the splits searched, like the time spent searching each of them, in the output.")
+                        .required(false),
+                    arg!(--explain "Prints, for every split, whether it was pruned by the time range, its number of matching documents and its top 5 hits with their raw sort values, instead of the hits.")
+                        .required(false),
+                    arg!(--"search-after" <VALUE> "Returns the hits sorted after this value. Accepts an integer, an RFC 3339 timestamp, or the `next_page_token` of a previous search response.")
+                        .required(false),
+                    arg!(--"search-timeout" <SEARCH_TIMEOUT> "Maximum duration of the search, e.g. `500ms` or `5s`. Once it has elapsed, the results collected so far are returned.")
+                        .required(false),
+                    arg!(--fuzzy <DISTANCE> "Matches the terms of the query against the terms of text fields within this Levenshtein distance, from 0 to 2. The terms of JSON fields and of fields captured by the dynamic mode still match exactly.")
+                        .required(false),
+                    arg!(--"default-operator" <OPERATOR> "Operator combining the clauses of the query that are not joined by an explicit `AND` or `OR`. Possible values are `AND` and `OR`. Defaults to `AND`.")
+                        .required(false),
+                    arg!(--"output-format" <OUTPUT_FORMAT> "Output format. Possible values are `json`, `pretty-json`, `csv`, and `table`.")
+                        .default_value("json")
+                        .required(false),
+                    arg!(--"output-fields" <FIELD_NAME> "List of fields displayed in the `csv` and `table` output formats. Defaults to the default search fields of the index. Comma-separated list, e.g. \"field1,field2\".")
+                        .value_delimiter(',')
+                        .required(false),
                 ])
             )
         .arg_required_else_help(true)
@@ -201,6 +244,7 @@ pub struct CreateIndexArgs {
     pub client_args: ClientArgs,
     pub index_config_uri: Uri,
     pub overwrite: bool,
+    pub validate_only: bool,
     pub assume_yes: bool,
 }
 
@@ -217,12 +261,39 @@ pub struct IngestDocsArgs {
     pub input_path_opt: Option<PathBuf>,
     pub batch_size_limit_opt: Option<ByteSize>,
     pub commit_type: CommitType,
+    pub doc_router_opt: Option<DocRouter>,
+}
+
+#[derive(Debug, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Json,
+    PrettyJson,
+    Csv,
+    Table,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(output_format_str: &str) -> anyhow::Result<Self> {
+        match output_format_str {
+            "json" => Ok(OutputFormat::Json),
+            "pretty-json" | "pretty_json" => Ok(OutputFormat::PrettyJson),
+            "csv" => Ok(OutputFormat::Csv),
+            "table" => Ok(OutputFormat::Table),
+            _ => bail!(
+                "unknown output format `{output_format_str}`. supported formats are: `json`, \
+                 `pretty-json`, `csv`, and `table`"
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct SearchIndexArgs {
     pub client_args: ClientArgs,
-    pub index_id: String,
+    pub index_ids: Vec<String>,
     pub query: String,
     pub aggregation: Option<String>,
     pub max_hits: usize,
@@ -231,7 +302,19 @@ pub struct SearchIndexArgs {
     pub snippet_fields: Option<Vec<String>>,
     pub start_timestamp: Option<i64>,
     pub end_timestamp: Option<i64>,
+    pub tags: Option<Vec<String>>,
+    pub snapshot_id: Option<String>,
+    pub geo_bbox: Option<String>,
+    pub geo_distance: Option<String>,
     pub sort_by_score: bool,
+    pub search_after: Option<String>,
+    pub search_timeout: Option<HumanDuration>,
+    pub fuzzy_distance: Option<u32>,
+    pub default_operator: Option<BooleanOperand>,
+    pub verbose: bool,
+    pub explain: bool,
+    pub output_format: OutputFormat,
+    pub output_fields: Option<Vec<String>>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -302,12 +385,14 @@ impl IndexCliCommand {
             .map(|uri| Uri::from_str(&uri))
             .expect("`index-config` should be a required arg.")?;
         let overwrite = matches.get_flag("overwrite");
+        let validate_only = matches.get_flag("validate-only");
         let assume_yes = matches.get_flag("yes");
 
         Ok(Self::Create(CreateIndexArgs {
             client_args,
             index_config_uri,
             overwrite,
+            validate_only,
             assume_yes,
         }))
     }
@@ -356,6 +441,11 @@ impl IndexCliCommand {
         if commit_type == CommitType::Auto && client_args.commit_timeout.is_some() {
             bail!("`--commit-timeout` can only be used with --wait or --force options");
         }
+        let default_index_id_opt = matches.remove_one::<String>("default-index");
+        let doc_router_opt = matches
+            .remove_one::<String>("routing-field")
+            .map(|routing_field| DocRouter::new(routing_field, &index_id, default_index_id_opt))
+            .transpose()?;
 
         Ok(Self::Ingest(IngestDocsArgs {
             client_args,
@@ -363,13 +453,15 @@ impl IndexCliCommand {
             input_path_opt,
             batch_size_limit_opt,
             commit_type,
+            doc_router_opt,
         }))
     }
 
     fn parse_search_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
-        let index_id = matches
-            .remove_one::<String>("index")
-            .expect("`index` should be a required arg.");
+        let index_ids = matches
+            .remove_many::<String>("index")
+            .expect("`index` should be a required arg.")
+            .collect();
         let query = matches
             .remove_one::<String>("query")
             .context("`query` should be a required arg")?;
@@ -398,9 +490,37 @@ impl IndexCliCommand {
             .remove_one::<String>("end-timestamp")
             .map(|ts| ts.parse())
             .transpose()?;
+        let tags = matches
+            .remove_many::<String>("tags")
+            .map(|values| values.collect());
+        let snapshot_id = matches.remove_one::<String>("snapshot");
+        let geo_bbox = matches.remove_one::<String>("geo-bbox");
+        let geo_distance = matches.remove_one::<String>("geo-distance");
+        let search_after = matches.remove_one::<String>("search-after");
+        let search_timeout = matches
+            .remove_one::<String>("search-timeout")
+            .map(|search_timeout_str| HumanDuration::from_str(&search_timeout_str))
+            .transpose()?;
+        let fuzzy_distance = matches
+            .remove_one::<String>("fuzzy")
+            .map(|fuzzy_distance_str| fuzzy_distance_str.parse())
+            .transpose()?;
+        let default_operator = matches
+            .remove_one::<String>("default-operator")
+            .map(|default_operator_str| parse_default_operator(&default_operator_str))
+            .transpose()?;
+        let verbose = matches.get_flag("verbose");
+        let explain = matches.get_flag("explain");
+        let output_format = matches
+            .remove_one::<String>("output-format")
+            .expect("`output-format` should have a default value.")
+            .parse()?;
+        let output_fields = matches
+            .remove_many::<String>("output-fields")
+            .map(|values| values.collect());
         let client_args = ClientArgs::parse(&mut matches)?;
         Ok(Self::Search(SearchIndexArgs {
-            index_id,
+            index_ids,
             query,
             aggregation,
             max_hits,
@@ -409,8 +529,20 @@ impl IndexCliCommand {
             snippet_fields,
             start_timestamp,
             end_timestamp,
+            tags,
+            snapshot_id,
+            geo_bbox,
+            geo_distance,
             client_args,
             sort_by_score,
+            search_after,
+            search_timeout,
+            fuzzy_distance,
+            default_operator,
+            verbose,
+            explain,
+            output_format,
+            output_fields,
         }))
     }
 
@@ -462,13 +594,24 @@ pub async fn clear_index_cli(args: ClearIndexArgs) -> anyhow::Result<()> {
 
 pub async fn create_index_cli(args: CreateIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "create-index");
-    println!("❯ Creating index...");
+    if args.validate_only {
+        println!("❯ Validating index config...");
+    } else {
+        println!("❯ Creating index...");
+    }
     let storage_resolver = StorageResolver::unconfigured();
     let file_content = load_file(&storage_resolver, &args.index_config_uri).await?;
     let index_config_str: String = std::str::from_utf8(&file_content)
         .with_context(|| format!("Invalid utf8: `{}`", args.index_config_uri))?
         .to_string();
-    let config_format = ConfigFormat::sniff_from_uri(&args.index_config_uri)?;
+    let config_format =
+        ConfigFormat::sniff_from_uri_and_content(&args.index_config_uri, &file_content)?;
+    if args.validate_only {
+        let schema_json = validate_index_config(config_format, &file_content)?;
+        println!("{}", serde_json::to_string_pretty(&schema_json)?);
+        println!("{} Index config is valid.", "✔".color(GREEN_COLOR));
+        return Ok(());
+    }
     let qw_client = args.client_args.client();
     // TODO: nice to have: check first if the index exists by send a GET request, if we get a 404,
     // the index does not exist. If it exists, we can display the prompt.
@@ -489,37 +632,101 @@ pub async fn create_index_cli(args: CreateIndexArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Parses and validates an index config without touching the metastore, and returns the tantivy
+/// schema of its doc mapping serialized as JSON. All the problems of the config are reported at
+/// once.
+fn validate_index_config(
+    config_format: ConfigFormat,
+    config_content: &[u8],
+) -> anyhow::Result<JsonValue> {
+    // The index URI of configs that do not declare one is resolved by the cluster when the index
+    // is created, so any root URI will do here.
+    let default_index_root_uri = Uri::from_str("ram:///indexes")?;
+    let index_config =
+        load_index_config_from_user_config(config_format, config_content, &default_index_root_uri)
+            .context("invalid index config")?;
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+    let schema_json = serde_json::to_value(doc_mapper.schema())?;
+    Ok(schema_json)
+}
+
 pub async fn list_index_cli(args: ListIndexesArgs) -> anyhow::Result<()> {
     debug!(args=?args, "list-index");
     let qw_client = args.client_args.client();
-    let indexes_metadatas = qw_client.indexes().list().await?;
-    let index_table = make_list_indexes_table(
-        indexes_metadatas
-            .into_iter()
-            .map(IndexMetadata::into_index_config),
-    );
+    let index_rows = qw_client
+        .indexes()
+        .summaries()
+        .await?
+        .into_iter()
+        .map(|index_summary| IndexRow {
+            index_id: index_summary.index_id,
+            index_uri: index_summary.index_uri,
+            num_published_docs: index_summary.num_published_docs,
+        });
+    let index_table = make_list_indexes_table(index_rows);
     println!("\n{index_table}\n");
     Ok(())
 }
 
-fn make_list_indexes_table<I>(indexes: I) -> Table
-where I: IntoIterator<Item = IndexConfig> {
-    let rows = indexes
+fn make_list_indexes_table<I>(index_rows: I) -> Table
+where I: IntoIterator<Item = IndexRow> {
+    let rows = index_rows
         .into_iter()
-        .map(|index| IndexRow {
-            index_id: index.index_id,
-            index_uri: index.index_uri,
-        })
         .sorted_by(|left, right| left.index_id.cmp(&right.index_id));
     make_table("Indexes", rows, false)
 }
 
+#[derive(Tabled)]
+struct SplitStatsRow {
+    #[tabled(rename = "Split ID")]
+    split_id: String,
+    #[tabled(rename = "Searcher")]
+    searcher_addr: String,
+    #[tabled(rename = "Duration (ms)")]
+    search_duration_millis: String,
+    #[tabled(rename = "Docs scanned")]
+    num_docs_scanned: u64,
+    #[tabled(rename = "Cache hit ratio")]
+    cache_hit_ratio: String,
+    #[tabled(rename = "Partial")]
+    partial: bool,
+}
+
+fn make_split_stats_table(stats: &SearchStats) -> Table {
+    let rows = stats.split_stats.iter().map(|split_stats| SplitStatsRow {
+        split_id: split_stats.split_id.clone(),
+        searcher_addr: split_stats.searcher_addr.clone(),
+        search_duration_millis: format!(
+            "{:.3}",
+            split_stats.search_duration_micros as f64 / 1_000.0
+        ),
+        num_docs_scanned: split_stats.num_docs_scanned,
+        cache_hit_ratio: split_stats
+            .cache_stats
+            .as_ref()
+            .filter(|cache_stats| cache_stats.num_hits + cache_stats.num_misses > 0)
+            .map(|cache_stats| {
+                let num_reads = cache_stats.num_hits + cache_stats.num_misses;
+                format!(
+                    "{:.1}%",
+                    cache_stats.num_hits as f64 * 100.0 / num_reads as f64
+                )
+            })
+            .unwrap_or_else(|| "-".to_string()),
+        partial: split_stats.partial,
+    });
+    let title = format!("Searched splits ({} attempted)", stats.num_attempted_splits);
+    make_table(&title, rows, false)
+}
+
 #[derive(Tabled)]
 struct IndexRow {
     #[tabled(rename = "Index ID")]
     index_id: String,
     #[tabled(rename = "Index URI")]
     index_uri: Uri,
+    #[tabled(rename = "Number of published documents")]
+    num_published_docs: u64,
 }
 
 pub async fn describe_index_cli(args: DescribeIndexArgs) -> anyhow::Result<()> {
@@ -540,6 +747,8 @@ pub struct IndexStats {
     pub index_id: String,
     pub index_uri: Uri,
     pub num_published_splits: usize,
+    pub num_staged_splits: usize,
+    pub num_marked_for_deletion_splits: usize,
     pub size_published_splits: ByteSize,
     pub num_published_docs: u64,
     pub size_published_docs_uncompressed: ByteSize,
@@ -550,7 +759,7 @@ pub struct IndexStats {
 }
 
 impl Tabled for IndexStats {
-    const LENGTH: usize = 9;
+    const LENGTH: usize = 11;
 
     fn fields(&self) -> Vec<Cow<'_, str>> {
         let num_published_docs = format!(
@@ -566,6 +775,8 @@ impl Tabled for IndexStats {
             self.size_published_docs_uncompressed.to_string(),
             separate_thousands(self.num_published_splits),
             self.size_published_splits.to_string(),
+            separate_thousands(self.num_staged_splits),
+            separate_thousands(self.num_marked_for_deletion_splits),
             display_option_in_table(&self.timestamp_field_name),
             display_timestamp(&self.timestamp_range.map(|(start, _end)| start)),
             display_timestamp(&self.timestamp_range.map(|(_start, end)| end)),
@@ -583,6 +794,8 @@ impl Tabled for IndexStats {
             "Size of published documents (uncompressed)",
             "Number of published splits",
             "Size of published splits",
+            "Number of staged splits",
+            "Number of splits marked for deletion",
             "Timestamp field",
             "Timestamp range start",
             "Timestamp range end",
@@ -632,6 +845,14 @@ impl IndexStats {
         index_metadata: IndexMetadata,
         splits: Vec<Split>,
     ) -> anyhow::Result<Self> {
+        let num_splits_in_state = |split_state: SplitState| {
+            splits
+                .iter()
+                .filter(|split| split.split_state == split_state)
+                .count()
+        };
+        let num_staged_splits = num_splits_in_state(SplitState::Staged);
+        let num_marked_for_deletion_splits = num_splits_in_state(SplitState::MarkedForDeletion);
         let published_splits: Vec<Split> = splits
             .into_iter()
             .filter(|split| split.split_state == SplitState::Published)
@@ -694,6 +915,8 @@ impl IndexStats {
             index_id: index_config.index_id.clone(),
             index_uri: index_config.index_uri.clone(),
             num_published_splits: published_splits.len(),
+            num_staged_splits,
+            num_marked_for_deletion_splits,
             size_published_splits: ByteSize(total_num_bytes),
             num_published_docs: total_num_docs,
             size_published_docs_uncompressed: ByteSize(total_uncompressed_num_bytes),
@@ -956,6 +1179,48 @@ pub async fn ingest_docs_cli(args: IngestDocsArgs) -> anyhow::Result<()> {
     let batch_size_limit_opt = args
         .batch_size_limit_opt
         .map(|batch_size_limit| batch_size_limit.as_u64() as usize);
+
+    if let Some(doc_router) = &args.doc_router_opt {
+        let routed_ingest_stats = qw_client
+            .ingest_routed(
+                doc_router,
+                ingest_source,
+                batch_size_limit_opt,
+                Some(&update_progress_bar),
+                args.commit_type,
+            )
+            .await?;
+        progress_bar.finish();
+
+        for (index_id, num_docs) in &routed_ingest_stats.num_docs_per_index {
+            if let Some(error) = routed_ingest_stats.failed_indexes.get(index_id) {
+                println!(
+                    "{} Failed to ingest documents into `{index_id}`: {error}",
+                    "✖".color(RED_COLOR)
+                );
+                continue;
+            }
+            println!(
+                "{} Ingested {num_docs} documents into `{index_id}`.",
+                "✔".color(GREEN_COLOR)
+            );
+        }
+        if routed_ingest_stats.num_unroutable_docs > 0 {
+            println!(
+                "{} Skipped {} documents without a valid value for `{}`.",
+                "✖".color(RED_COLOR),
+                routed_ingest_stats.num_unroutable_docs,
+                doc_router.routing_field()
+            );
+        }
+        if !routed_ingest_stats.failed_indexes.is_empty() {
+            bail!(
+                "failed to ingest documents into {} index(es)",
+                routed_ingest_stats.failed_indexes.len()
+            );
+        }
+        return Ok(());
+    }
     qw_client
         .ingest(
             &args.index_id,
@@ -981,13 +1246,63 @@ fn progress_bar_style() -> ProgressStyle {
     .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
 }
 
-pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchResponseRest> {
+/// Converts the value of the `--search-after` flag into a page token. Integers and RFC 3339
+/// timestamps are turned into a cursor on the first sort field, anything else is assumed to be the
+/// `next_page_token` of a previous search response.
+fn search_after_page_token(search_after: &str) -> anyhow::Result<String> {
+    let sort_value = if let Ok(value) = search_after.parse::<i64>() {
+        SortValue::I64(value)
+    } else if let Ok(value) = search_after.parse::<u64>() {
+        SortValue::U64(value)
+    } else if let Ok(datetime) = chrono::DateTime::parse_from_rfc3339(search_after) {
+        let timestamp_nanos = datetime
+            .timestamp()
+            .checked_mul(1_000_000_000)
+            .and_then(|nanos| nanos.checked_add(datetime.timestamp_subsec_nanos() as i64))
+            .context("search after timestamp is out of range")?;
+        SortValue::I64(timestamp_nanos)
+    } else {
+        return Ok(search_after.to_string());
+    };
+    let partial_hit = PartialHit {
+        sort_value: Some(sort_value.into()),
+        ..Default::default()
+    };
+    Ok(encode_page_token(&partial_hit))
+}
+
+fn parse_default_operator(default_operator_str: &str) -> anyhow::Result<BooleanOperand> {
+    match default_operator_str.to_uppercase().as_str() {
+        "AND" => Ok(BooleanOperand::And),
+        "OR" => Ok(BooleanOperand::Or),
+        _ => bail!(
+            "unknown default operator `{default_operator_str}`. possible values are `AND` and `OR`"
+        ),
+    }
+}
+
+fn search_request_from_args(args: SearchIndexArgs) -> anyhow::Result<SearchRequestQueryString> {
     let aggs: Option<serde_json::Value> = args
         .aggregation
         .map(|aggs_string| {
             serde_json::from_str(&aggs_string).context("failed to deserialize aggregations")
         })
         .transpose()?;
+    let geo_bbox = args
+        .geo_bbox
+        .as_deref()
+        .map(|geo_bbox_string| {
+            serde_json::from_str(geo_bbox_string).context("failed to deserialize geo bounding box")
+        })
+        .transpose()?;
+    let geo_distance = args
+        .geo_distance
+        .as_deref()
+        .map(|geo_distance_string| {
+            serde_json::from_str(geo_distance_string)
+                .context("failed to deserialize geo distance filter")
+        })
+        .transpose()?;
     let sort_by = args
         .sort_by_score
         .then_some(SortBy {
@@ -998,6 +1313,11 @@ pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchRespons
             }],
         })
         .unwrap_or_default();
+    let search_after = args
+        .search_after
+        .as_deref()
+        .map(search_after_page_token)
+        .transpose()?;
     let search_request = SearchRequestQueryString {
         query: args.query,
         aggs,
@@ -1009,21 +1329,286 @@ pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchRespons
         start_offset: args.start_offset as u64,
         sort_by,
         count_all: CountHits::CountAll,
+        tags: args.tags.clone(),
+        snapshot_id: args.snapshot_id.clone(),
+        geo_bbox,
+        geo_distance,
+        search_after,
+        debug: args.verbose,
+        timeout: args.search_timeout,
+        fuzzy_distance: args.fuzzy_distance,
+        default_operator: args.default_operator,
         ..Default::default()
     };
+    Ok(search_request)
+}
+
+pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchResponseRest> {
     let qw_client = args.client_args.client();
-    let search_response = qw_client.search(&args.index_id, search_request).await?;
+    let index_id_patterns = args.index_ids.join(",");
+    let search_request = search_request_from_args(args)?;
+    let search_response = qw_client.search(&index_id_patterns, search_request).await?;
     Ok(search_response)
 }
 
-pub async fn search_index_cli(args: SearchIndexArgs) -> anyhow::Result<()> {
+pub async fn explain_search_index(args: SearchIndexArgs) -> anyhow::Result<ExplainResult> {
+    let qw_client = args.client_args.client();
+    let index_id_patterns = args.index_ids.join(",");
+    let search_request = search_request_from_args(args)?;
+    let explain_result = qw_client
+        .search_explain(&index_id_patterns, search_request)
+        .await?;
+    Ok(explain_result)
+}
+
+pub async fn search_index_cli(mut args: SearchIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "search-index");
-    let search_response_rest = search_index(args).await?;
-    let search_response_json = serde_json::to_string_pretty(&search_response_rest)?;
-    println!("{search_response_json}");
+    if args.explain {
+        let explain_result = explain_search_index(args).await?;
+        print!("{}", make_explain_tree(&explain_result));
+        return Ok(());
+    }
+    let output_format = std::mem::take(&mut args.output_format);
+    let output_fields_opt = args.output_fields.take();
+    let index_ids = args.index_ids.clone();
+    let qw_client = args.client_args.client();
+    let mut search_response_rest = search_index(args).await?;
+
+    for failed_index in &search_response_rest.failed_indexes {
+        eprintln!(
+            "{} index `{}` could not be searched ({}): {}",
+            "Warning:".yellow(),
+            failed_index.index_id,
+            failed_index.error_code,
+            failed_index.message
+        );
+    }
+    if search_response_rest.timed_out {
+        eprintln!(
+            "{} the search timed out, the results only cover the documents scanned before the \
+             timeout",
+            "Warning:".yellow(),
+        );
+    }
+    for warning in &search_response_rest.warnings {
+        eprintln!(
+            "{} index `{}` ({}): {}",
+            "Warning:".yellow(),
+            warning.index_id,
+            warning.code,
+            warning.message
+        );
+    }
+    let search_response_str = match output_format {
+        OutputFormat::Json => serde_json::to_string(&search_response_rest)?,
+        OutputFormat::PrettyJson => serde_json::to_string_pretty(&search_response_rest)?,
+        OutputFormat::Csv | OutputFormat::Table => {
+            let mut output_fields = if let Some(output_fields) = output_fields_opt {
+                output_fields
+            } else if let [index_id] = &index_ids[..] {
+                let index_metadata = qw_client.indexes().get(index_id).await?;
+                let default_search_fields = index_metadata
+                    .index_config
+                    .search_settings
+                    .default_search_fields;
+                if default_search_fields.is_empty() {
+                    hits_field_names(&search_response_rest.hits)
+                } else {
+                    default_search_fields
+                }
+            } else {
+                hits_field_names(&search_response_rest.hits)
+            };
+            // Tells the source index of the hits apart when they come from several indexes.
+            if let Some(hit_index_ids) = search_response_rest.hit_index_ids.take() {
+                add_hit_index_ids(&mut search_response_rest.hits, hit_index_ids);
+                output_fields.insert(0, HIT_INDEX_ID_FIELD_NAME.to_string());
+            }
+            if output_format == OutputFormat::Csv {
+                make_hits_csv(&search_response_rest.hits, &output_fields)
+            } else if let Some(stats) = &search_response_rest.stats {
+                let hits_table = make_hits_table(&search_response_rest.hits, &output_fields);
+                format!("{hits_table}\n{}", make_split_stats_table(stats))
+            } else {
+                make_hits_table(&search_response_rest.hits, &output_fields).to_string()
+            }
+        }
+    };
+    println!("{search_response_str}");
     Ok(())
 }
 
+const TREE_BRANCH: &str = "├── ";
+const TREE_LAST_BRANCH: &str = "└── ";
+const TREE_INDENT: &str = "│   ";
+const TREE_LAST_INDENT: &str = "    ";
+
+/// Returns the branch and the indentation of the children of a tree node.
+fn tree_prefixes(is_last: bool) -> (&'static str, &'static str) {
+    if is_last {
+        (TREE_LAST_BRANCH, TREE_LAST_INDENT)
+    } else {
+        (TREE_BRANCH, TREE_INDENT)
+    }
+}
+
+fn format_sort_value(sort_value_opt: &Option<SortByValue>) -> String {
+    match sort_value_opt
+        .as_ref()
+        .and_then(|sort_value| sort_value.sort_value.as_ref())
+    {
+        Some(SortValue::U64(value)) => value.to_string(),
+        Some(SortValue::I64(value)) => value.to_string(),
+        Some(SortValue::F64(value)) => value.to_string(),
+        Some(SortValue::Boolean(value)) => value.to_string(),
+        None => "-".to_string(),
+    }
+}
+
+fn make_split_explanation_tree(
+    split_explanation: &SplitExplanation,
+    is_last: bool,
+    tree: &mut String,
+) {
+    let (branch, indent) = tree_prefixes(is_last);
+    if split_explanation.pruned_by_time_range {
+        tree.push_str(&format!(
+            "{branch}{} (pruned by time range)\n",
+            split_explanation.split_id
+        ));
+        return;
+    }
+    tree.push_str(&format!(
+        "{branch}{} (searched by {})\n",
+        split_explanation.split_id, split_explanation.searcher_addr
+    ));
+    let mut lines = vec![format!(
+        "candidate docs: {}",
+        split_explanation.num_candidate_docs
+    )];
+    if let Some(error) = &split_explanation.error {
+        lines.push(format!("error: {error}"));
+    }
+    // The top hits are the children of the last line.
+    if !split_explanation.top_hits.is_empty() {
+        lines.push("top hits".to_string());
+    }
+    for (line_idx, line) in lines.iter().enumerate() {
+        let (line_branch, _) = tree_prefixes(line_idx + 1 == lines.len());
+        tree.push_str(&format!("{indent}{line_branch}{line}\n"));
+    }
+    for (hit_idx, partial_hit) in split_explanation.top_hits.iter().enumerate() {
+        let (hit_branch, _) = tree_prefixes(hit_idx + 1 == split_explanation.top_hits.len());
+        let mut sort_values = format_sort_value(&partial_hit.sort_value);
+        if partial_hit.sort_value2.is_some() {
+            sort_values.push_str(&format!(
+                ", {}",
+                format_sort_value(&partial_hit.sort_value2)
+            ));
+        }
+        tree.push_str(&format!(
+            "{indent}{TREE_LAST_INDENT}{hit_branch}segment {}, doc {}: {sort_values}\n",
+            partial_hit.segment_ord, partial_hit.doc_id
+        ));
+    }
+}
+
+/// Renders the explanation of a search as a tree of indexes, splits, and top hits.
+fn make_explain_tree(explain_result: &ExplainResult) -> String {
+    let mut tree = String::new();
+    for (index_id, split_explanations) in &explain_result
+        .splits
+        .iter()
+        .group_by(|split_explanation| &split_explanation.index_id)
+    {
+        tree.push_str(&format!("{index_id}\n"));
+        let split_explanations = split_explanations.collect_vec();
+        for (split_idx, split_explanation) in split_explanations.iter().enumerate() {
+            let is_last = split_idx + 1 == split_explanations.len();
+            make_split_explanation_tree(split_explanation, is_last, &mut tree);
+        }
+    }
+    tree
+}
+
+/// Name of the column holding the index ID of the hits in the CSV and table output formats.
+const HIT_INDEX_ID_FIELD_NAME: &str = "_index";
+
+fn add_hit_index_ids(hits: &mut [JsonValue], hit_index_ids: Vec<String>) {
+    for (hit, index_id) in hits.iter_mut().zip(hit_index_ids) {
+        if let Some(hit_obj) = hit.as_object_mut() {
+            hit_obj.insert(
+                HIT_INDEX_ID_FIELD_NAME.to_string(),
+                JsonValue::String(index_id),
+            );
+        }
+    }
+}
+
+/// Returns the names of the top-level fields of the hits, in order of appearance.
+fn hits_field_names(hits: &[JsonValue]) -> Vec<String> {
+    hits.iter()
+        .filter_map(|hit| hit.as_object())
+        .flat_map(|hit_obj| hit_obj.keys())
+        .unique()
+        .cloned()
+        .collect()
+}
+
+/// Returns the value of the field `field_name` of a hit formatted for the CSV and table output
+/// formats. Nested fields are addressed with dots, e.g. `resource.service`.
+fn hit_field_value(hit: &JsonValue, field_name: &str) -> String {
+    let field_value_opt = hit.get(field_name).or_else(|| {
+        field_name
+            .split('.')
+            .try_fold(hit, |value, field_name_part| value.get(field_name_part))
+    });
+    match field_value_opt {
+        None | Some(JsonValue::Null) => String::new(),
+        Some(JsonValue::String(field_value)) => field_value.clone(),
+        Some(field_value) => field_value.to_string(),
+    }
+}
+
+fn escape_csv_value(value: &str) -> Cow<str> {
+    if value.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+fn make_hits_csv(hits: &[JsonValue], output_fields: &[String]) -> String {
+    let header = output_fields
+        .iter()
+        .map(|field_name| escape_csv_value(field_name))
+        .join(",");
+    let rows = hits.iter().map(|hit| {
+        output_fields
+            .iter()
+            .map(|field_name| escape_csv_value(&hit_field_value(hit, field_name)).into_owned())
+            .join(",")
+    });
+    std::iter::once(header).chain(rows).join("\n")
+}
+
+fn make_hits_table(hits: &[JsonValue], output_fields: &[String]) -> Table {
+    let mut table_builder = Builder::default();
+    table_builder.set_header(output_fields);
+    for hit in hits {
+        table_builder.push_record(
+            output_fields
+                .iter()
+                .map(|field_name| hit_field_value(hit, field_name)),
+        );
+    }
+    let mut table = table_builder.build();
+    table
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Style::ascii());
+    table
+}
+
 pub async fn delete_index_cli(args: DeleteIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "delete-index");
     if !args.dry_run && !args.assume_yes {
@@ -1085,7 +1670,7 @@ pub async fn start_statistics_reporting_loop(
             display_statistics(&mut stdout_handle, &mut throughput_calculator, &observation)?;
         }
 
-        if pipeline_handle.state().is_exit() {
+        if pipeline_handle.is_terminated() {
             break;
         }
     }
@@ -1188,6 +1773,8 @@ fn display_statistics(
     printer.print_value(format_args!("{:>5}", statistics.num_invalid_docs))?;
     printer.print_header("PublSplits")?;
     printer.print_value(format_args!("{:>3}", statistics.num_published_splits))?;
+    printer.print_header("InFlight")?;
+    printer.print_value(format_args!("{:>3}", statistics.num_splits_in_flight))?;
     printer.print_header("Input size")?;
     printer.print_value(format_args!(
         "{:>5}MB",
@@ -1245,6 +1832,7 @@ mod test {
     use std::ops::RangeInclusive;
 
     use quickwit_metastore::SplitMetadata;
+    use quickwit_search::decode_page_token;
 
     use super::*;
 
@@ -1261,6 +1849,34 @@ mod test {
         split_metadata
     }
 
+    #[test]
+    fn test_search_after_page_token() {
+        let page_token = search_after_page_token("1000").unwrap();
+        let partial_hit = decode_page_token(&page_token).unwrap();
+        assert_eq!(
+            partial_hit.sort_value.unwrap().sort_value,
+            Some(SortValue::I64(1000))
+        );
+        assert!(partial_hit.split_id.is_empty());
+
+        let page_token = search_after_page_token("2023-10-01T00:00:01Z").unwrap();
+        let partial_hit = decode_page_token(&page_token).unwrap();
+        assert_eq!(
+            partial_hit.sort_value.unwrap().sort_value,
+            Some(SortValue::I64(1_696_118_401_000_000_000))
+        );
+
+        let next_page_token = encode_page_token(&PartialHit {
+            split_id: "split-1".to_string(),
+            doc_id: 3,
+            ..Default::default()
+        });
+        assert_eq!(
+            search_after_page_token(&next_page_token).unwrap(),
+            next_page_token
+        );
+    }
+
     #[test]
     fn test_index_stats() -> anyhow::Result<()> {
         let index_id = "index-stats-env".to_string();
@@ -1295,6 +1911,8 @@ mod test {
         assert_eq!(index_stats.index_id, index_id);
         assert_eq!(index_stats.index_uri.as_str(), index_uri);
         assert_eq!(index_stats.num_published_splits, 1);
+        assert_eq!(index_stats.num_staged_splits, 0);
+        assert_eq!(index_stats.num_marked_for_deletion_splits, 1);
         assert_eq!(index_stats.size_published_splits, ByteSize::mb(15));
         assert_eq!(index_stats.num_published_docs, 100_000);
         assert_eq!(
@@ -1310,6 +1928,29 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_make_list_indexes_table() {
+        let index_rows = vec![
+            IndexRow {
+                index_id: "index-b".to_string(),
+                index_uri: Uri::for_test("s3://bucket/index-b"),
+                num_published_docs: 42,
+            },
+            IndexRow {
+                index_id: "index-a".to_string(),
+                index_uri: Uri::for_test("s3://bucket/index-a"),
+                num_published_docs: 0,
+            },
+        ];
+        let index_table = make_list_indexes_table(index_rows).to_string();
+        assert!(index_table.contains("Number of published documents"));
+
+        let index_a_pos = index_table.find("index-a").unwrap();
+        let index_b_pos = index_table.find("index-b").unwrap();
+        assert!(index_a_pos < index_b_pos);
+        assert!(index_table.contains("42"));
+    }
+
     #[test]
     fn test_descriptive_stats() -> anyhow::Result<()> {
         let split_id = "stat-test-split".to_string();
@@ -1374,4 +2015,163 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_make_explain_tree() {
+        let explain_result = ExplainResult {
+            splits: vec![
+                SplitExplanation {
+                    split_id: "split-1".to_string(),
+                    index_id: "index-a".to_string(),
+                    num_candidate_docs: 2,
+                    top_hits: vec![
+                        PartialHit {
+                            sort_value: Some(SortValue::F64(1.5).into()),
+                            split_id: "split-1".to_string(),
+                            segment_ord: 0,
+                            doc_id: 3,
+                            ..Default::default()
+                        },
+                        PartialHit {
+                            sort_value: Some(SortValue::F64(0.5).into()),
+                            split_id: "split-1".to_string(),
+                            segment_ord: 1,
+                            doc_id: 7,
+                            ..Default::default()
+                        },
+                    ],
+                    searcher_addr: "127.0.0.1:7281".to_string(),
+                    ..Default::default()
+                },
+                SplitExplanation {
+                    split_id: "split-2".to_string(),
+                    index_id: "index-a".to_string(),
+                    pruned_by_time_range: true,
+                    ..Default::default()
+                },
+                SplitExplanation {
+                    split_id: "split-3".to_string(),
+                    index_id: "index-b".to_string(),
+                    searcher_addr: "127.0.0.1:7281".to_string(),
+                    error: Some("split not found".to_string()),
+                    ..Default::default()
+                },
+            ],
+        };
+        let expected_tree = "\
+index-a
+├── split-1 (searched by 127.0.0.1:7281)
+│   ├── candidate docs: 2
+│   └── top hits
+│       ├── segment 0, doc 3: 1.5
+│       └── segment 1, doc 7: 0.5
+└── split-2 (pruned by time range)
+index-b
+└── split-3 (searched by 127.0.0.1:7281)
+    ├── candidate docs: 0
+    └── error: split not found
+";
+        assert_eq!(make_explain_tree(&explain_result), expected_tree);
+    }
+
+    #[test]
+    fn test_make_hits_csv_and_table() {
+        let hits = vec![
+            serde_json::json!({
+                "author": {"name": "Jimmy, \"Jimbo\""},
+                "title": "Barack Obama",
+                "url": "https://en.wikipedia.org/wiki/Barack_Obama",
+                "views": 42
+            }),
+            serde_json::json!({"body": "...", "title": "Michelle Obama"}),
+        ];
+        assert_eq!(
+            hits_field_names(&hits),
+            ["author", "title", "url", "views", "body"]
+        );
+        let output_fields = vec![
+            "title".to_string(),
+            "views".to_string(),
+            "author.name".to_string(),
+        ];
+        let hits_csv = make_hits_csv(&hits, &output_fields);
+        let expected_hits_csv =
+            "title,views,author.name\nBarack Obama,42,\"Jimmy, \"\"Jimbo\"\"\"\nMichelle Obama,,";
+        assert_eq!(hits_csv, expected_hits_csv);
+
+        let hits_table = make_hits_table(&hits, &output_fields).to_string();
+        let hits_table_lines: Vec<&str> = hits_table.lines().collect();
+        assert_eq!(hits_table_lines.len(), 7);
+        assert_eq!(
+            hits_table_lines[1],
+            "| title          | views | author.name    |"
+        );
+        assert_eq!(
+            hits_table_lines[3],
+            "| Barack Obama   | 42    | Jimmy, \"Jimbo\" |"
+        );
+        assert_eq!(
+            hits_table_lines[5],
+            "| Michelle Obama |       |                |"
+        );
+    }
+
+    #[test]
+    fn test_make_hits_csv_with_hit_index_ids() {
+        let mut hits = vec![
+            serde_json::json!({"title": "Barack Obama"}),
+            serde_json::json!({"title": "Michelle Obama"}),
+        ];
+        add_hit_index_ids(
+            &mut hits,
+            vec!["wikipedia-en".to_string(), "wikipedia-fr".to_string()],
+        );
+        let output_fields = vec![HIT_INDEX_ID_FIELD_NAME.to_string(), "title".to_string()];
+        let hits_csv = make_hits_csv(&hits, &output_fields);
+        assert_eq!(
+            hits_csv,
+            "_index,title\nwikipedia-en,Barack Obama\nwikipedia-fr,Michelle Obama"
+        );
+    }
+
+    #[test]
+    fn test_validate_index_config() {
+        let index_config_yaml = r#"
+            version: 0.6
+            index_id: wikipedia
+            doc_mapping:
+                mode: strict
+                field_mappings:
+                    - name: title
+                      type: text
+            search_settings:
+                default_search_fields: [title]
+        "#;
+        let schema_json =
+            validate_index_config(ConfigFormat::Yaml, index_config_yaml.as_bytes()).unwrap();
+        assert!(schema_json
+            .as_array()
+            .unwrap()
+            .iter()
+            .any(|field_entry| field_entry["name"] == "title"));
+
+        let invalid_index_config_yaml = r#"
+            version: 0.6
+            index_id: wikipedia
+            doc_mapping:
+                mode: strict
+                field_mappings:
+                    - name: title
+                      type: text
+                timestamp_field: timestamp
+            search_settings:
+                default_search_fields: [title, body]
+        "#;
+        let error = validate_index_config(ConfigFormat::Yaml, invalid_index_config_yaml.as_bytes())
+            .unwrap_err();
+        let error_message = format!("{error:#}");
+        assert!(error_message.contains("found 2 problems"));
+        assert!(error_message.contains("could not find timestamp field `timestamp`"));
+        assert!(error_message.contains("Unknown default search field: `body`"));
+    }
 }
@@ -28,6 +28,7 @@ use dialoguer::theme::ColorfulTheme;
 use dialoguer::Confirm;
 use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::uri::Uri;
+use quickwit_common::HumanDuration;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{
     ConfigFormat, MetastoreConfigs, NodeConfig, SourceConfig, StorageConfigs,
@@ -50,12 +51,16 @@ use crate::checklist::run_checklist;
 
 pub mod checklist;
 pub mod cli;
+pub mod cluster;
+pub mod embedded;
 pub mod index;
 #[cfg(feature = "jemalloc")]
 pub mod jemalloc;
 pub mod logger;
 pub mod metrics;
+pub mod ping;
 pub mod service;
+pub mod snapshot;
 pub mod source;
 pub mod split;
 pub mod stats;
@@ -195,8 +200,8 @@ pub fn parse_duration_or_none(duration_with_unit_str: &str) -> anyhow::Result<Ti
     if duration_with_unit_str == "none" {
         Ok(Timeout::none())
     } else {
-        humantime::parse_duration(duration_with_unit_str)
-            .map(Timeout::new)
+        HumanDuration::from_str(duration_with_unit_str)
+            .map(|duration| Timeout::new(duration.into()))
             .context("failed to parse timeout")
     }
 }
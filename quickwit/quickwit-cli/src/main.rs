@@ -96,18 +96,21 @@ mod tests {
     use quickwit_cli::cli::{build_cli, CliCommand};
     use quickwit_cli::index::{
         ClearIndexArgs, CreateIndexArgs, DeleteIndexArgs, DescribeIndexArgs, IndexCliCommand,
-        IngestDocsArgs, SearchIndexArgs,
+        IngestDocsArgs, OutputFormat, SearchIndexArgs,
     };
+    use quickwit_cli::service::RunCliCommand;
     use quickwit_cli::split::{DescribeSplitArgs, SplitCliCommand};
     use quickwit_cli::tool::{
         ExtractSplitArgs, GarbageCollectIndexArgs, LocalIngestDocsArgs, LocalSearchArgs, MergeArgs,
-        ToolCliCommand,
+        PublishSplitArgs, ToolCliCommand,
     };
     use quickwit_cli::ClientArgs;
     use quickwit_common::uri::Uri;
-    use quickwit_config::SourceInputFormat;
+    use quickwit_common::HumanDuration;
+    use quickwit_config::{FileSourceCompression, SourceInputFormat};
     use quickwit_rest_client::models::Timeout;
     use quickwit_rest_client::rest_client::CommitType;
+    use quickwit_rest_client::routing::DocRouter;
     use reqwest::Url;
 
     #[test]
@@ -157,6 +160,7 @@ mod tests {
             client_args: ClientArgs::default(),
             index_config_uri: expected_index_config_uri.clone(),
             overwrite: false,
+            validate_only: false,
             assume_yes: false,
         }));
         assert_eq!(command, expected_cmd);
@@ -174,10 +178,29 @@ mod tests {
             client_args: ClientArgs::default(),
             index_config_uri: expected_index_config_uri,
             overwrite: true,
+            validate_only: false,
             assume_yes: false,
         }));
         assert_eq!(command, expected_cmd);
 
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "create",
+            "--index-config",
+            "index-conf.yaml",
+            "--validate-only",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Create(CreateIndexArgs {
+                validate_only: true,
+                overwrite: false,
+                ..
+            }))
+        ));
+
         Ok(())
     }
 
@@ -197,6 +220,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::Auto,
+                    doc_router_opt: None,
                 })) if &index_id == "wikipedia"
                 && client_args.timeout.is_none()
                 && client_args.connect_timeout.is_none()
@@ -228,6 +252,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::Auto,
+                    doc_router_opt: None,
                 })) if &index_id == "wikipedia"
                 && client_args.timeout.is_none()
                 && client_args.connect_timeout.is_none()
@@ -256,6 +281,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: Some(batch_size_limit),
                     commit_type: CommitType::Force,
+                    doc_router_opt: None,
                 })) if &index_id == "wikipedia"
                         && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                         && client_args.timeout.is_none()
@@ -285,6 +311,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: Some(batch_size_limit),
                     commit_type: CommitType::WaitFor,
+                    doc_router_opt: None,
                 })) if &index_id == "wikipedia"
                     && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                     && client_args.timeout.is_none()
@@ -315,6 +342,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::Auto,
+                    doc_router_opt: None,
                 })) if &index_id == "wikipedia"
                         && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                         && client_args.timeout == Some(Timeout::from_secs(10))
@@ -346,6 +374,7 @@ mod tests {
                     input_path_opt: None,
                     batch_size_limit_opt: None,
                     commit_type: CommitType::WaitFor,
+                    doc_router_opt: None,
                 })) if &index_id == "wikipedia"
                         && client_args.cluster_endpoint == Url::from_str("http://127.0.0.1:7280").unwrap()
                         && client_args.timeout == Some(Timeout::none())
@@ -353,6 +382,43 @@ mod tests {
                         && client_args.commit_timeout == Some(Timeout::from_hours(4))
         ));
 
+        // Plain integers are interpreted as seconds.
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "ingest",
+            "--index",
+            "wikipedia",
+            "--timeout",
+            "30",
+            "--force",
+            "--commit-timeout",
+            "2m30s",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Ingest(
+                IngestDocsArgs {
+                    client_args,
+                    commit_type: CommitType::Force,
+                    ..
+                })) if client_args.timeout == Some(Timeout::from_secs(30))
+                        && client_args.commit_timeout == Some(Timeout::from_secs(150))
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "ingest",
+            "--index",
+            "wikipedia",
+            "--connect-timeout",
+            "10 parsecs",
+        ])?;
+        let error = CliCommand::parse_cli_args(matches).unwrap_err();
+        assert_eq!(error.to_string(), "failed to parse timeout");
+
         let app = build_cli().no_binary_name(true);
         assert_eq!(
             app.try_get_matches_from([
@@ -370,6 +436,61 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_ingest_routing_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "ingest",
+            "--index",
+            "logs-{tenant}",
+            "--routing-field",
+            "tenant",
+            "--default-index",
+            "logs-default",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_doc_router =
+            DocRouter::new("tenant", "logs-{tenant}", Some("logs-default".to_string()))?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Ingest(
+                IngestDocsArgs {
+                    index_id,
+                    doc_router_opt: Some(doc_router),
+                    ..
+                })) if index_id == "logs-{tenant}" && doc_router == expected_doc_router
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "ingest",
+            "--index",
+            "logs",
+            "--routing-field",
+            "tenant",
+        ])?;
+        let error = CliCommand::parse_cli_args(matches).unwrap_err();
+        assert!(error.to_string().contains("`{tenant}`"));
+
+        let app = build_cli().no_binary_name(true);
+        assert_eq!(
+            app.try_get_matches_from([
+                "index",
+                "ingest",
+                "--index",
+                "logs",
+                "--default-index",
+                "logs-default",
+            ])
+            .unwrap_err()
+            .kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_parse_local_ingest_args() {
         let app = build_cli().no_binary_name(true);
@@ -385,8 +506,13 @@ mod tests {
                 "--keep-cache",
                 "--input-format",
                 "plain",
+                "--compression",
+                "gzip",
                 "--transform-script",
                 ".message = downcase(string!(.message))",
+                "--dry-run",
+                "--rejected-docs-path",
+                "/rejected.json",
             ])
             .unwrap();
         let command = CliCommand::parse_cli_args(matches).unwrap();
@@ -398,16 +524,98 @@ mod tests {
                     index_id,
                     input_path_opt: None,
                     input_format,
+                    compression_opt: Some(FileSourceCompression::Gzip),
                     overwrite,
                     vrl_script: Some(vrl_script),
                     clear_cache,
+                    limit_bytes_opt: None,
+                    dry_run,
+                    rejected_docs_path_opt: Some(rejected_docs_path),
                 })) if &index_id == "wikipedia"
                        && config_uri == Uri::from_str("file:///config.yaml").unwrap()
                        && vrl_script == ".message = downcase(string!(.message))"
                        && overwrite
                        && !clear_cache
+                       && dry_run
+                       && rejected_docs_path == PathBuf::from("/rejected.json")
                        && input_format == SourceInputFormat::PlainText,
         ));
+
+        let app = build_cli().no_binary_name(true);
+        assert_eq!(
+            app.try_get_matches_from([
+                "tool",
+                "local-ingest",
+                "--index",
+                "wikipedia",
+                "--config",
+                "/config.yaml",
+                "--rejected-docs-path",
+                "/rejected.json",
+            ])
+            .unwrap_err()
+            .kind(),
+            clap::error::ErrorKind::MissingRequiredArgument
+        );
+    }
+
+    #[test]
+    fn test_parse_local_ingest_limit_bytes_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "tool",
+                "local-ingest",
+                "--index",
+                "wikipedia",
+                "--config",
+                "/config.yaml",
+                "--limit-bytes",
+                "10GB",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(matches).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Tool(ToolCliCommand::LocalIngest(LocalIngestDocsArgs {
+                limit_bytes_opt: Some(limit_bytes),
+                dry_run: false,
+                ..
+            })) if limit_bytes == ByteSize::gb(10)
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "tool",
+                "local-ingest",
+                "--index",
+                "wikipedia",
+                "--config",
+                "/config.yaml",
+                "--limit-bytes",
+                "ten gigabytes",
+            ])
+            .unwrap();
+        CliCommand::parse_cli_args(matches).unwrap_err();
+
+        let app = build_cli().no_binary_name(true);
+        assert_eq!(
+            app.try_get_matches_from([
+                "tool",
+                "local-ingest",
+                "--index",
+                "wikipedia",
+                "--config",
+                "/config.yaml",
+                "--limit-bytes",
+                "10GB",
+                "--dry-run",
+            ])
+            .unwrap_err()
+            .kind(),
+            clap::error::ErrorKind::ArgumentConflict
+        );
     }
 
     #[test]
@@ -425,7 +633,7 @@ mod tests {
         assert!(matches!(
             command,
             CliCommand::Index(IndexCliCommand::Search(SearchIndexArgs {
-                index_id,
+                index_ids,
                 query,
                 max_hits: 20,
                 start_offset: 0,
@@ -433,9 +641,32 @@ mod tests {
                 snippet_fields: None,
                 start_timestamp: None,
                 end_timestamp: None,
+                tags: None,
                 aggregation: None,
+                output_format: OutputFormat::Json,
+                output_fields: None,
                 ..
-            })) if &index_id == "wikipedia" && &query == "Barack Obama"
+            })) if index_ids == ["wikipedia"] && &query == "Barack Obama"
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "search",
+            "--index",
+            "logs-2023-12-01",
+            "--index",
+            "logs-2023-12-02",
+            "--query",
+            "level:error",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Search(SearchIndexArgs {
+                index_ids,
+                ..
+            })) if index_ids == ["logs-2023-12-01", "logs-2023-12-02"]
         ));
 
         let app = build_cli().no_binary_name(true);
@@ -459,13 +690,32 @@ mod tests {
             "url",
             "--snippet-fields",
             "body",
+            "--tags",
+            "tenant:acme",
+            "--tags",
+            "region:us-east",
+            "--geo-distance",
+            r#"{"field": "location", "center": "48.85,2.35", "radius_m": 1000}"#,
+            "--search-after",
+            "1000",
+            "--search-timeout",
+            "5s",
+            "--fuzzy",
+            "1",
+            "--default-operator",
+            "or",
+            "--verbose",
+            "--output-format",
+            "csv",
+            "--output-fields",
+            "title,url",
         ])?;
         let command = CliCommand::parse_cli_args(matches)?;
         assert!(matches!(
             command,
             CliCommand::Index(IndexCliCommand::Search(SearchIndexArgs {
                 client_args: _,
-                index_id,
+                index_ids,
                 query,
                 aggregation: None,
                 max_hits: 50,
@@ -474,11 +724,47 @@ mod tests {
                 snippet_fields: Some(snippet_field_names),
                 start_timestamp: Some(0),
                 end_timestamp: Some(1),
+                tags: Some(tags),
+                snapshot_id: None,
+                geo_bbox: None,
+                geo_distance: Some(geo_distance),
                 sort_by_score: false,
-            })) if &index_id == "wikipedia"
+                search_after: Some(search_after),
+                search_timeout: Some(search_timeout),
+                fuzzy_distance: Some(1),
+                default_operator: Some(quickwit_query::BooleanOperand::Or),
+                verbose: true,
+                explain: false,
+                output_format: OutputFormat::Csv,
+                output_fields: Some(output_field_names),
+            })) if index_ids == ["wikipedia"]
                   && query == "Barack Obama"
                   && search_field_names == vec!["title".to_string(), "url".to_string()]
                   && snippet_field_names == vec!["body".to_string()]
+                  && tags == vec!["tenant:acme".to_string(), "region:us-east".to_string()]
+                  && geo_distance.contains("radius_m")
+                  && search_after == "1000"
+                  && search_timeout == HumanDuration::from_secs(5)
+                  && output_field_names == vec!["title".to_string(), "url".to_string()]
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "search",
+            "--index",
+            "wikipedia",
+            "--query",
+            "Barack Obama",
+            "--explain",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Search(SearchIndexArgs {
+                explain: true,
+                ..
+            }))
         ));
         Ok(())
     }
@@ -509,6 +795,8 @@ mod tests {
                 start_timestamp: None,
                 end_timestamp: None,
                 aggregation: None,
+                output_format: OutputFormat::Json,
+                output_fields: None,
                 ..
             })) if &index_id == "wikipedia" && &query == "Barack Obama"
         ));
@@ -660,6 +948,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_publish_split_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "publish-split",
+            "--index",
+            "wikipedia",
+            "--split",
+            "ABC",
+            "--force",
+            "--config",
+            "/config.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Tool(ToolCliCommand::PublishSplit(PublishSplitArgs {
+                index_id,
+                split_id,
+                force: true,
+                ..
+            })) if &index_id == "wikipedia" && &split_id == "ABC"
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_parse_garbage_collect_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -702,9 +1017,67 @@ mod tests {
                 index_id,
                 grace_period,
                 config_uri,
+                ttl_opt: None,
                 dry_run: true,
             })) if &index_id == "wikipedia" && grace_period == Duration::from_secs(5 * 60) && config_uri == expected_config_uri
         ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "gc",
+            "--index",
+            "wikipedia",
+            "--ttl",
+            "30d",
+            "--config",
+            "/config.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Tool(ToolCliCommand::GarbageCollect(GarbageCollectIndexArgs {
+                ttl_opt: Some(ttl),
+                ..
+            })) if ttl == Duration::from_secs(30 * 24 * 60 * 60)
+        ));
+
+        // Plain integers are interpreted as seconds.
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "gc",
+            "--index",
+            "wikipedia",
+            "--grace-period",
+            "90",
+            "--config",
+            "/config.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Tool(ToolCliCommand::GarbageCollect(GarbageCollectIndexArgs {
+                grace_period,
+                ..
+            })) if grace_period == Duration::from_secs(90)
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "gc",
+            "--index",
+            "wikipedia",
+            "--grace-period",
+            "1.5h",
+            "--config",
+            "/config.yaml",
+        ])?;
+        let error = CliCommand::parse_cli_args(matches).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("failed to parse duration `1.5h`"));
         Ok(())
     }
 
@@ -733,6 +1106,76 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_run_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(["run", "--config", "/config.yaml"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_cmd = CliCommand::Run(RunCliCommand {
+            config_uri: Uri::from_str("file:///config.yaml").unwrap(),
+            services: None,
+            drain_timeout: Duration::from_secs(30),
+            metrics_listen_addr_opt: None,
+        });
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(["run", "--config", "/config.yaml", "--drain-timeout", "5"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_cmd = CliCommand::Run(RunCliCommand {
+            config_uri: Uri::from_str("file:///config.yaml").unwrap(),
+            services: None,
+            drain_timeout: Duration::from_secs(5),
+            metrics_listen_addr_opt: None,
+        });
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(["run", "--config", "/config.yaml", "--drain-timeout", "2m"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_cmd = CliCommand::Run(RunCliCommand {
+            config_uri: Uri::from_str("file:///config.yaml").unwrap(),
+            services: None,
+            drain_timeout: Duration::from_secs(120),
+            metrics_listen_addr_opt: None,
+        });
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "run",
+            "--config",
+            "/config.yaml",
+            "--metrics-addr",
+            "0.0.0.0:9090",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let expected_cmd = CliCommand::Run(RunCliCommand {
+            config_uri: Uri::from_str("file:///config.yaml").unwrap(),
+            services: None,
+            drain_timeout: Duration::from_secs(30),
+            metrics_listen_addr_opt: Some(([0, 0, 0, 0], 9090).into()),
+        });
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "run",
+            "--config",
+            "/config.yaml",
+            "--metrics-addr",
+            "localhost",
+        ])?;
+        let error = CliCommand::parse_cli_args(matches).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "failed to parse metrics address `localhost`"
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_parse_no_color() {
         let previous_no_color_res = std::env::var("NO_COLOR");
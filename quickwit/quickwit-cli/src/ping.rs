@@ -0,0 +1,117 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use clap::{arg, ArgMatches, Command};
+use colored::Colorize;
+use humantime::format_duration;
+use quickwit_proto::search::HealthCheckRequest;
+use quickwit_search::create_search_client_from_grpc_addr;
+use tracing::debug;
+
+pub fn build_ping_command() -> Command {
+    Command::new("ping")
+        .about("Checks that a node is up and reachable over gRPC.")
+        .args(&[
+            arg!(--endpoint <ENDPOINT> "gRPC address of the node formatted as `host:port`.")
+                .default_value("127.0.0.1:7281")
+                .required(false),
+        ])
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct PingCliCommand {
+    pub endpoint: String,
+}
+
+impl PingCliCommand {
+    pub fn parse_cli_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let endpoint = matches
+            .remove_one::<String>("endpoint")
+            .expect("`endpoint` should have a default value.");
+        Ok(Self { endpoint })
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        debug!(args=?self, "ping");
+        let grpc_addr = resolve_grpc_addr(&self.endpoint).await?;
+        let mut search_client = create_search_client_from_grpc_addr(grpc_addr);
+        let start = Instant::now();
+        let health_check_response = search_client
+            .health_check(HealthCheckRequest {})
+            .await
+            .with_context(|| format!("failed to ping node at `{}`", self.endpoint))?;
+        let latency = start.elapsed();
+        let uptime = Duration::from_secs(health_check_response.uptime_secs);
+        println!("{} node at `{}` is up", "✔".green(), self.endpoint);
+        println!("  latency: {}ms", latency.as_millis());
+        println!("  uptime:  {}", format_duration(uptime));
+        println!("  indexes: {}", health_check_response.num_indexes);
+        Ok(())
+    }
+}
+
+async fn resolve_grpc_addr(endpoint: &str) -> anyhow::Result<SocketAddr> {
+    tokio::net::lookup_host(endpoint)
+        .await
+        .with_context(|| format!("failed to resolve endpoint `{endpoint}`"))?
+        .next()
+        .with_context(|| format!("endpoint `{endpoint}` did not resolve to any address"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{build_cli, CliCommand};
+
+    #[test]
+    fn test_parse_ping_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec!["ping"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert_eq!(
+            command,
+            CliCommand::Ping(PingCliCommand {
+                endpoint: "127.0.0.1:7281".to_string()
+            })
+        );
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec!["ping", "--endpoint", "searcher-1:7281"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert_eq!(
+            command,
+            CliCommand::Ping(PingCliCommand {
+                endpoint: "searcher-1:7281".to_string()
+            })
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_grpc_addr() {
+        let grpc_addr = resolve_grpc_addr("127.0.0.1:7281").await.unwrap();
+        assert_eq!(grpc_addr, "127.0.0.1:7281".parse().unwrap());
+
+        resolve_grpc_addr("127.0.0.1").await.unwrap_err();
+    }
+}
@@ -18,18 +18,22 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::HashSet;
+use std::net::SocketAddr;
 use std::str::FromStr;
+use std::time::Duration;
 
+use anyhow::Context;
 use clap::{arg, ArgAction, ArgMatches, Command};
 use itertools::Itertools;
 use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::uri::{Protocol, Uri};
+use quickwit_common::HumanDuration;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::NodeConfig;
 use quickwit_serve::serve_quickwit;
 use quickwit_telemetry::payload::{QuickwitFeature, QuickwitTelemetryInfo, TelemetryEvent};
 use tokio::signal;
-use tracing::debug;
+use tracing::{debug, info};
 
 use crate::{config_cli_arg, get_resolvers, load_node_config, start_actor_runtimes};
 
@@ -42,6 +46,11 @@ pub fn build_run_command() -> Command {
             arg!(--"service" <SERVICE> "Services (`indexer`, `searcher`, `metastore`, `control-plane`, or `janitor`) to run. If unspecified, all the supported services are started.")
                 .action(ArgAction::Append)
                 .required(false),
+            arg!(--"drain-timeout" <DRAIN_TIMEOUT> "Maximum duration given to in-flight REST and gRPC requests to complete when the node shuts down on SIGINT or SIGTERM, e.g. `30s` or `2m`. Plain integers are interpreted as seconds.")
+                .default_value("30s")
+                .required(false),
+            arg!(--"metrics-addr" <METRICS_ADDR> "Address, e.g. `0.0.0.0:9090`, on which to serve the Prometheus `/metrics` endpoint instead of the REST API port.")
+                .required(false),
         ])
 }
 
@@ -49,6 +58,8 @@ pub fn build_run_command() -> Command {
 pub struct RunCliCommand {
     pub config_uri: Uri,
     pub services: Option<HashSet<QuickwitService>>,
+    pub drain_timeout: Duration,
+    pub metrics_listen_addr_opt: Option<SocketAddr>,
 }
 
 impl RunCliCommand {
@@ -67,9 +78,23 @@ impl RunCliCommand {
                 services
             })
             .transpose()?;
+        let drain_timeout: Duration = matches
+            .remove_one::<String>("drain-timeout")
+            .map(|duration_str| HumanDuration::from_str(&duration_str))
+            .expect("`drain-timeout` should have a default value.")?
+            .into();
+        let metrics_listen_addr_opt = matches
+            .remove_one::<String>("metrics-addr")
+            .map(|metrics_addr| {
+                SocketAddr::from_str(&metrics_addr)
+                    .with_context(|| format!("failed to parse metrics address `{metrics_addr}`"))
+            })
+            .transpose()?;
         Ok(RunCliCommand {
             config_uri,
             services,
+            drain_timeout,
+            metrics_listen_addr_opt,
         })
     }
 
@@ -91,9 +116,8 @@ impl RunCliCommand {
         let runtimes_config = RuntimesConfig::default();
         start_actor_runtimes(runtimes_config, &node_config.enabled_services)?;
         let shutdown_signal = Box::pin(async move {
-            signal::ctrl_c()
-                .await
-                .expect("Registering a signal handler for SIGINT should not fail.");
+            wait_for_termination_signal().await;
+            info!("received termination signal, shutting down");
         });
         let serve_result = serve_quickwit(
             node_config,
@@ -101,6 +125,8 @@ impl RunCliCommand {
             metastore_resolver,
             storage_resolver,
             shutdown_signal,
+            self.drain_timeout,
+            self.metrics_listen_addr_opt,
         )
         .await;
         let return_code = match serve_result {
@@ -116,6 +142,25 @@ impl RunCliCommand {
     }
 }
 
+/// Resolves when the process receives SIGINT or, on Unix, SIGTERM.
+async fn wait_for_termination_signal() {
+    #[cfg(unix)]
+    {
+        let mut sigterm = signal::unix::signal(signal::unix::SignalKind::terminate())
+            .expect("Registering a signal handler for SIGTERM should not fail.");
+        tokio::select! {
+            ctrl_c_res = signal::ctrl_c() => {
+                ctrl_c_res.expect("Registering a signal handler for SIGINT should not fail.");
+            }
+            _ = sigterm.recv() => {}
+        }
+    }
+    #[cfg(not(unix))]
+    signal::ctrl_c()
+        .await
+        .expect("Registering a signal handler for SIGINT should not fail.");
+}
+
 fn quickwit_telemetry_info(config: &NodeConfig) -> QuickwitTelemetryInfo {
     let mut features = HashSet::new();
     if config.indexer_config.enable_otlp_endpoint {
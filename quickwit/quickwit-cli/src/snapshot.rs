@@ -0,0 +1,277 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::{bail, Context};
+use clap::{arg, ArgMatches, Command};
+use colored::Colorize;
+use itertools::Itertools;
+use quickwit_proto::metastore::IndexSnapshot;
+use tabled::{Table, Tabled};
+use time::OffsetDateTime;
+use tracing::debug;
+
+use crate::checklist::GREEN_COLOR;
+use crate::{client_args, make_table, prompt_confirmation, ClientArgs};
+
+pub fn build_snapshot_command() -> Command {
+    Command::new("snapshot")
+        .about("Manages index snapshots: creates, lists, deletes...")
+        .args(client_args())
+        .subcommand(
+            Command::new("create")
+                .about("Creates a snapshot of the splits currently published in an index.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                ])
+            )
+        .subcommand(
+            Command::new("list")
+                .about("Lists the snapshots of an index.")
+                .alias("ls")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                ])
+            )
+        .subcommand(
+            Command::new("delete")
+                .about("Deletes a snapshot. The splits it references can then be garbage collected.")
+                .alias("del")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--snapshot <SNAPSHOT_ID> "ID of the target snapshot")
+                        .display_order(2)
+                        .required(true),
+                    arg!(-y --"yes" "Assume \"yes\" as an answer to all prompts and run non-interactively.")
+                        .required(false),
+                ])
+            )
+        .arg_required_else_help(true)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CreateSnapshotArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ListSnapshotsArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct DeleteSnapshotArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub snapshot_id: String,
+    pub assume_yes: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SnapshotCliCommand {
+    Create(CreateSnapshotArgs),
+    List(ListSnapshotsArgs),
+    Delete(DeleteSnapshotArgs),
+}
+
+impl SnapshotCliCommand {
+    pub fn parse_cli_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, submatches) = matches
+            .remove_subcommand()
+            .context("failed to parse snapshot subcommand")?;
+        match subcommand.as_str() {
+            "create" => Self::parse_create_args(submatches),
+            "list" => Self::parse_list_args(submatches),
+            "delete" => Self::parse_delete_args(submatches),
+            _ => bail!("unknown snapshot subcommand `{subcommand}`"),
+        }
+    }
+
+    fn parse_create_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        Ok(Self::Create(CreateSnapshotArgs {
+            client_args,
+            index_id,
+        }))
+    }
+
+    fn parse_list_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        Ok(Self::List(ListSnapshotsArgs {
+            client_args,
+            index_id,
+        }))
+    }
+
+    fn parse_delete_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let snapshot_id = matches
+            .remove_one::<String>("snapshot")
+            .expect("`snapshot` should be a required arg.");
+        let assume_yes = matches.get_flag("yes");
+        Ok(Self::Delete(DeleteSnapshotArgs {
+            client_args,
+            index_id,
+            snapshot_id,
+            assume_yes,
+        }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::Create(args) => create_snapshot_cli(args).await,
+            Self::List(args) => list_snapshots_cli(args).await,
+            Self::Delete(args) => delete_snapshot_cli(args).await,
+        }
+    }
+}
+
+async fn create_snapshot_cli(args: CreateSnapshotArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "create-snapshot");
+    let qw_client = args.client_args.client();
+    let snapshot = qw_client
+        .snapshots(&args.index_id)
+        .create()
+        .await
+        .context("failed to create snapshot")?;
+    println!(
+        "{} Snapshot `{}` successfully created with {} split(s).",
+        "✔".color(GREEN_COLOR),
+        snapshot.snapshot_id,
+        snapshot.split_ids.len()
+    );
+    Ok(())
+}
+
+async fn list_snapshots_cli(args: ListSnapshotsArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "list-snapshots");
+    let qw_client = args.client_args.client();
+    let snapshots = qw_client
+        .snapshots(&args.index_id)
+        .list()
+        .await
+        .context("failed to list snapshots")?;
+    println!("{}", make_snapshot_table(&snapshots, "Snapshots"));
+    Ok(())
+}
+
+async fn delete_snapshot_cli(args: DeleteSnapshotArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "delete-snapshot");
+    if !args.assume_yes {
+        let prompt = "This operation will delete the snapshot, the splits it references will be \
+                      deleted after the next garbage collection if they are no longer published. \
+                      Do you want to proceed?";
+        if !prompt_confirmation(prompt, false) {
+            return Ok(());
+        }
+    }
+    let qw_client = args.client_args.client();
+    qw_client
+        .snapshots(&args.index_id)
+        .delete(&args.snapshot_id)
+        .await
+        .context("failed to delete snapshot")?;
+    println!("{} Snapshot successfully deleted.", "✔".color(GREEN_COLOR));
+    Ok(())
+}
+
+fn make_snapshot_table(snapshots: &[IndexSnapshot], title: &str) -> Table {
+    let rows = snapshots
+        .iter()
+        .map(|snapshot| {
+            let created_at = OffsetDateTime::from_unix_timestamp(snapshot.create_timestamp)
+                .expect("Failed to create `OffsetDateTime` from snapshot create timestamp.");
+            SnapshotRow {
+                snapshot_id: snapshot.snapshot_id.clone(),
+                num_splits: snapshot.split_ids.len(),
+                created_at,
+            }
+        })
+        .sorted_by(|left, right| left.created_at.cmp(&right.created_at));
+    make_table(title, rows, false)
+}
+
+#[derive(Tabled)]
+struct SnapshotRow {
+    #[tabled(rename = "ID")]
+    snapshot_id: String,
+    #[tabled(rename = "Num splits")]
+    num_splits: usize,
+    #[tabled(rename = "Created at")]
+    created_at: OffsetDateTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{build_cli, CliCommand};
+
+    #[test]
+    fn test_parse_snapshot_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(vec!["snapshot", "create", "--index", "hdfs-logs"])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Snapshot(SnapshotCliCommand::Create(CreateSnapshotArgs {
+                index_id,
+                ..
+            })) if index_id == "hdfs-logs"
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "snapshot",
+            "delete",
+            "--index",
+            "hdfs-logs",
+            "--snapshot",
+            "01HC7HGY6Z8VDPMVQ3A4H7D9DW",
+            "--yes",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Snapshot(SnapshotCliCommand::Delete(DeleteSnapshotArgs {
+                index_id,
+                snapshot_id,
+                assume_yes: true,
+                ..
+            })) if index_id == "hdfs-logs" && snapshot_id == "01HC7HGY6Z8VDPMVQ3A4H7D9DW"
+        ));
+        Ok(())
+    }
+}
@@ -20,12 +20,14 @@
 use std::str::FromStr;
 
 use anyhow::{bail, Context};
+use bytesize::ByteSize;
 use clap::{arg, ArgMatches, Command};
 use colored::Colorize;
 use itertools::Itertools;
 use quickwit_metastore::{Split, SplitState};
-use quickwit_serve::ListSplitsQueryParams;
+use quickwit_serve::{ListSplitsQueryParams, SplitMetadataUpdate};
 use tabled::{Table, Tabled};
+use time::format_description::well_known::Rfc3339;
 use time::{format_description, Date, OffsetDateTime, PrimitiveDateTime};
 use tracing::debug;
 
@@ -63,6 +65,14 @@ pub fn build_split_command() -> Command {
                     arg!(--"end-date" <END_DATE> "Selects the splits that contain documents before this date (time-series indexes only).")
                         .display_order(7)
                         .required(false),
+                    arg!(--"start-timestamp" <START_TIMESTAMP> "Selects the splits that contain documents after this timestamp, in seconds since the Unix epoch (time-series indexes only).")
+                        .display_order(8)
+                        .required(false)
+                        .conflicts_with("start-date"),
+                    arg!(--"end-timestamp" <END_TIMESTAMP> "Selects the splits that contain documents before this timestamp, in seconds since the Unix epoch (time-series indexes only).")
+                        .display_order(9)
+                        .required(false)
+                        .conflicts_with("end-date"),
                     // See #2762:
                     // arg!(--tags <TAGS> "Selects the splits whose tags are all included in this comma-separated list of tags.")
                     //     .display_order(6)
@@ -70,8 +80,12 @@ pub fn build_split_command() -> Command {
                     //     .use_value_delimiter(true),
                     arg!(--"output-format" <OUTPUT_FORMAT> "Output format. Possible values are `table`, `json`, and `pretty-json`.")
                         .alias("format")
-                        .display_order(8)
+                        .display_order(10)
+                        .required(false),
+                    arg!(--"output-ids-only" "Only outputs the IDs of the splits, one per line, so they can be piped into other commands.")
+                        .display_order(11)
                         .required(false)
+                        .conflicts_with("output-format"),
                 ])
             )
         .subcommand(
@@ -104,6 +118,30 @@ pub fn build_split_command() -> Command {
                         .required(false),
                 ])
             )
+        .subcommand(
+            Command::new("update")
+                .about("Corrects the metadata of a published split. Only the attributes provided are updated.")
+                .args(&[
+                    arg!(--index <INDEX_ID> "Target index ID")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--split <SPLIT_ID> "ID of the target split")
+                        .display_order(2)
+                        .required(true),
+                    arg!(--"num-docs" <NUM_DOCS> "Number of documents of the split.")
+                        .display_order(3)
+                        .required(false),
+                    arg!(--"uncompressed-size" <UNCOMPRESSED_SIZE> "Uncompressed size of the documents of the split, for instance `512MB`.")
+                        .display_order(4)
+                        .required(false),
+                    arg!(--"start-timestamp" <START_TIMESTAMP> "Lower bound of the time range of the split, in seconds since the Unix epoch (time-series indexes only).")
+                        .display_order(5)
+                        .required(false),
+                    arg!(--"end-timestamp" <END_TIMESTAMP> "Upper bound of the time range of the split, in seconds since the Unix epoch (time-series indexes only).")
+                        .display_order(6)
+                        .required(false),
+                ])
+            )
         .arg_required_else_help(true)
 }
 
@@ -112,6 +150,8 @@ enum OutputFormat {
     Table, // Default
     Json,
     PrettyJson,
+    /// One split ID per line, selected with `--output-ids-only`.
+    Ids,
 }
 
 impl FromStr for OutputFormat {
@@ -152,6 +192,17 @@ pub struct MarkForDeletionArgs {
     pub assume_yes: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct UpdateSplitArgs {
+    pub client_args: ClientArgs,
+    pub index_id: String,
+    pub split_id: String,
+    pub num_docs: Option<usize>,
+    pub uncompressed_docs_size_in_bytes: Option<u64>,
+    pub time_range_start: Option<i64>,
+    pub time_range_end: Option<i64>,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct DescribeSplitArgs {
     pub client_args: ClientArgs,
@@ -164,6 +215,7 @@ pub struct DescribeSplitArgs {
 pub enum SplitCliCommand {
     List(ListSplitArgs),
     MarkForDeletion(MarkForDeletionArgs),
+    Update(UpdateSplitArgs),
     Describe(DescribeSplitArgs),
 }
 
@@ -176,6 +228,7 @@ impl SplitCliCommand {
             "describe" => Self::parse_describe_args(submatches),
             "list" => Self::parse_list_args(submatches),
             "mark-for-deletion" => Self::parse_mark_for_deletion_args(submatches),
+            "update" => Self::parse_update_args(submatches),
             _ => bail!("unknown split subcommand `{subcommand}`"),
         }
     }
@@ -205,14 +258,20 @@ impl SplitCliCommand {
             .remove_one::<String>("create-date")
             .map(|date_str| parse_date(&date_str, "create"))
             .transpose()?;
-        let start_date = matches
-            .remove_one::<String>("start-date")
-            .map(|date_str| parse_date(&date_str, "start"))
-            .transpose()?;
-        let end_date = matches
-            .remove_one::<String>("end-date")
-            .map(|date_str| parse_date(&date_str, "end"))
-            .transpose()?;
+        let start_date = match matches.remove_one::<String>("start-timestamp") {
+            Some(timestamp_str) => Some(parse_timestamp(&timestamp_str, "start")?),
+            None => matches
+                .remove_one::<String>("start-date")
+                .map(|date_str| parse_date(&date_str, "start"))
+                .transpose()?,
+        };
+        let end_date = match matches.remove_one::<String>("end-timestamp") {
+            Some(timestamp_str) => Some(parse_timestamp(&timestamp_str, "end")?),
+            None => matches
+                .remove_one::<String>("end-date")
+                .map(|date_str| parse_date(&date_str, "end"))
+                .transpose()?,
+        };
         // let tags = matches.values_of("tags").map(|values| {
         //     TagFilterAst::And(
         //         values
@@ -224,11 +283,15 @@ impl SplitCliCommand {
         //             .collect(),
         //     )
         // });
-        let output_format = matches
-            .remove_one::<String>("output-format")
-            .map(|s| OutputFormat::from_str(s.as_str()))
-            .transpose()?
-            .unwrap_or(OutputFormat::Table);
+        let output_format = if matches.get_flag("output-ids-only") {
+            OutputFormat::Ids
+        } else {
+            matches
+                .remove_one::<String>("output-format")
+                .map(|s| OutputFormat::from_str(s.as_str()))
+                .transpose()?
+                .unwrap_or(OutputFormat::Table)
+        };
         Ok(Self::List(ListSplitArgs {
             client_args,
             index_id,
@@ -261,6 +324,58 @@ impl SplitCliCommand {
         }))
     }
 
+    fn parse_update_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let client_args = ClientArgs::parse(&mut matches)?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let split_id = matches
+            .remove_one::<String>("split")
+            .expect("`split` should be a required arg.");
+        let num_docs = matches
+            .remove_one::<String>("num-docs")
+            .map(|num_docs| num_docs.parse::<usize>())
+            .transpose()
+            .context("failed to parse `--num-docs`")?;
+        let uncompressed_docs_size_in_bytes = matches
+            .remove_one::<String>("uncompressed-size")
+            .map(|size| size.parse::<ByteSize>())
+            .transpose()
+            .map_err(|error| anyhow::anyhow!(error))
+            .context("failed to parse `--uncompressed-size`")?
+            .map(|size| size.as_u64());
+        let time_range_start = matches
+            .remove_one::<String>("start-timestamp")
+            .map(|timestamp| timestamp.parse::<i64>())
+            .transpose()
+            .context("failed to parse `--start-timestamp`")?;
+        let time_range_end = matches
+            .remove_one::<String>("end-timestamp")
+            .map(|timestamp| timestamp.parse::<i64>())
+            .transpose()
+            .context("failed to parse `--end-timestamp`")?;
+
+        if num_docs.is_none()
+            && uncompressed_docs_size_in_bytes.is_none()
+            && time_range_start.is_none()
+            && time_range_end.is_none()
+        {
+            bail!(
+                "nothing to update: at least one of `--num-docs`, `--uncompressed-size`, \
+                 `--start-timestamp`, or `--end-timestamp` must be provided"
+            );
+        }
+        Ok(Self::Update(UpdateSplitArgs {
+            client_args,
+            index_id,
+            split_id,
+            num_docs,
+            uncompressed_docs_size_in_bytes,
+            time_range_start,
+            time_range_end,
+        }))
+    }
+
     fn parse_describe_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
         let index_id = matches
             .remove_one::<String>("index")
@@ -283,6 +398,7 @@ impl SplitCliCommand {
         match self {
             Self::List(args) => list_split_cli(args).await,
             Self::MarkForDeletion(args) => mark_splits_for_deletion_cli(args).await,
+            Self::Update(args) => update_split_cli(args).await,
             Self::Describe(args) => describe_split_cli(args).await,
         }
     }
@@ -308,15 +424,24 @@ async fn list_split_cli(args: ListSplitArgs) -> anyhow::Result<()> {
         .list(list_splits_query_params)
         .await
         .context("failed to list splits")?;
-    let output = match args.output_format {
-        OutputFormat::Json => serde_json::to_string(&splits)?,
-        OutputFormat::PrettyJson => serde_json::to_string_pretty(&splits)?,
-        OutputFormat::Table => make_split_table(&splits, "Splits").to_string(),
-    };
-    println!("{output}");
+    let output = format_splits(&splits, &args.output_format)?;
+    // An empty line would be read as a split ID when piping IDs into another command.
+    if !output.is_empty() {
+        println!("{output}");
+    }
     Ok(())
 }
 
+fn format_splits(splits: &[Split], output_format: &OutputFormat) -> anyhow::Result<String> {
+    let output = match output_format {
+        OutputFormat::Json => serde_json::to_string(splits)?,
+        OutputFormat::PrettyJson => serde_json::to_string_pretty(splits)?,
+        OutputFormat::Table => make_split_table(splits, "Splits").to_string(),
+        OutputFormat::Ids => splits.iter().map(|split| split.split_id()).join("\n"),
+    };
+    Ok(output)
+}
+
 async fn mark_splits_for_deletion_cli(args: MarkForDeletionArgs) -> anyhow::Result<()> {
     debug!(args=?args, "mark-splits-for-deletion");
     println!("❯ Marking splits for deletion...");
@@ -339,6 +464,28 @@ async fn mark_splits_for_deletion_cli(args: MarkForDeletionArgs) -> anyhow::Resu
     Ok(())
 }
 
+async fn update_split_cli(args: UpdateSplitArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "update-split");
+    println!("❯ Updating split metadata...");
+    let qw_client = args.client_args.client();
+    let split_metadata_update = SplitMetadataUpdate {
+        num_docs: args.num_docs,
+        uncompressed_docs_size_in_bytes: args.uncompressed_docs_size_in_bytes,
+        time_range_start: args.time_range_start,
+        time_range_end: args.time_range_end,
+    };
+    qw_client
+        .splits(&args.index_id)
+        .update_metadata(&args.split_id, split_metadata_update)
+        .await?;
+    println!(
+        "{} Split `{}` successfully updated.",
+        "✔".color(GREEN_COLOR),
+        args.split_id
+    );
+    Ok(())
+}
+
 #[derive(Tabled)]
 struct FileRow {
     #[tabled(rename = "File Name")]
@@ -405,7 +552,11 @@ fn make_split_table(splits: &[Split], title: &str) -> Table {
         .iter()
         .map(|split| {
             let time_range = if let Some(time_range) = &split.split_metadata.time_range {
-                format!("[{time_range:?}]")
+                format!(
+                    "[{}, {}]",
+                    format_timestamp(*time_range.start()),
+                    format_timestamp(*time_range.end())
+                )
             } else {
                 "[*]".to_string()
             };
@@ -419,7 +570,8 @@ fn make_split_table(splits: &[Split], title: &str) -> Table {
                 split_id: split.split_metadata.split_id.clone(),
                 split_state: split.split_state,
                 num_docs: split.split_metadata.num_docs,
-                size_mega_bytes: split.split_metadata.uncompressed_docs_size_in_bytes / 1_000_000,
+                size: ByteSize(split.split_metadata.uncompressed_docs_size_in_bytes).to_string(),
+                generation: split.split_metadata.num_merge_ops,
                 created_at,
                 updated_at,
                 time_range,
@@ -454,6 +606,28 @@ fn parse_date(date_arg: &str, option_name: &str) -> anyhow::Result<OffsetDateTim
     );
 }
 
+fn parse_timestamp(timestamp_arg: &str, option_name: &str) -> anyhow::Result<OffsetDateTime> {
+    timestamp_arg
+        .parse::<i64>()
+        .ok()
+        .and_then(|timestamp| OffsetDateTime::from_unix_timestamp(timestamp).ok())
+        .with_context(|| {
+            format!(
+                "failed to parse --{option_name}-timestamp option parameter `{timestamp_arg}`. \
+                 expected a number of seconds since the Unix epoch"
+            )
+        })
+}
+
+/// Formats a Unix timestamp in seconds as an RFC 3339 (ISO 8601) date, falling back to the raw
+/// timestamp if it is out of range.
+fn format_timestamp(timestamp: i64) -> String {
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .ok()
+        .and_then(|datetime| datetime.format(&Rfc3339).ok())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
 fn parse_split_state(split_state_arg: &str) -> anyhow::Result<SplitState> {
     let split_state = match split_state_arg.to_lowercase().as_str() {
         "staged" => SplitState::Staged,
@@ -475,8 +649,11 @@ struct SplitRow {
     split_state: SplitState,
     #[tabled(rename = "Num docs")]
     num_docs: usize,
-    #[tabled(rename = "Size (MB)")]
-    size_mega_bytes: u64,
+    #[tabled(rename = "Size")]
+    size: String,
+    /// Number of merge operations the split went through.
+    #[tabled(rename = "Generation")]
+    generation: usize,
     #[tabled(rename = "Created at")]
     created_at: OffsetDateTime,
     #[tabled(rename = "Updated at")]
@@ -487,6 +664,7 @@ struct SplitRow {
 
 #[cfg(test)]
 mod tests {
+    use quickwit_metastore::SplitMetadata;
     use reqwest::Url;
     use time::macros::datetime;
 
@@ -550,9 +728,103 @@ mod tests {
                    // && tags == expected_tags
                    && output_format == expected_output_format
         ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "split",
+            "list",
+            "--index",
+            "hdfs",
+            "--output-ids-only",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Split(SplitCliCommand::List(ListSplitArgs {
+                output_format: OutputFormat::Ids,
+                ..
+            }))
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let error = app
+            .try_get_matches_from(vec![
+                "split",
+                "list",
+                "--index",
+                "hdfs",
+                "--output-ids-only",
+                "--output-format",
+                "json",
+            ])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "split",
+            "list",
+            "--index",
+            "hdfs",
+            "--start-timestamp",
+            "1608768000",
+            "--end-timestamp",
+            "1608900120",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Split(SplitCliCommand::List(ListSplitArgs {
+                start_date,
+                end_date,
+                ..
+            })) if start_date == expected_start_date && end_date == expected_end_date
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let error = app
+            .try_get_matches_from(vec![
+                "split",
+                "list",
+                "--index",
+                "hdfs",
+                "--start-timestamp",
+                "1608768000",
+                "--start-date",
+                "2020-12-24",
+            ])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
         Ok(())
     }
 
+    #[test]
+    fn test_format_timestamp() {
+        assert_eq!(format_timestamp(1608900120), "2020-12-25T12:42:00Z");
+        assert_eq!(format_timestamp(i64::MAX), i64::MAX.to_string());
+    }
+
+    #[test]
+    fn test_format_splits_ids_only() {
+        let splits: Vec<Split> = ["split-1", "split-2"]
+            .into_iter()
+            .map(|split_id| Split {
+                split_state: SplitState::Published,
+                update_timestamp: 0,
+                publish_timestamp: None,
+                split_metadata: SplitMetadata {
+                    split_id: split_id.to_string(),
+                    ..Default::default()
+                },
+            })
+            .collect();
+        assert_eq!(
+            format_splits(&splits, &OutputFormat::Ids).unwrap(),
+            "split-1\nsplit-2"
+        );
+        assert_eq!(format_splits(&[], &OutputFormat::Ids).unwrap(), "");
+    }
+
     #[test]
     fn test_parse_split_mark_for_deletion_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -583,6 +855,48 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_split_update_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "split",
+            "update",
+            "--index",
+            "wikipedia",
+            "--split",
+            "ABC",
+            "--num-docs",
+            "42",
+            "--uncompressed-size",
+            "1KB",
+            "--end-timestamp",
+            "1700000000",
+        ])?;
+        let command = CliCommand::parse_cli_args(matches)?;
+        let CliCommand::Split(SplitCliCommand::Update(args)) = command else {
+            panic!("expected `SplitCliCommand::Update`, got `{command:?}`");
+        };
+        assert_eq!(args.index_id, "wikipedia");
+        assert_eq!(args.split_id, "ABC");
+        assert_eq!(args.num_docs, Some(42));
+        assert_eq!(args.uncompressed_docs_size_in_bytes, Some(1_000));
+        assert_eq!(args.time_range_start, None);
+        assert_eq!(args.time_range_end, Some(1_700_000_000));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "split",
+            "update",
+            "--index",
+            "wikipedia",
+            "--split",
+            "ABC",
+        ])?;
+        let error = CliCommand::parse_cli_args(matches).unwrap_err();
+        assert!(error.to_string().contains("nothing to update"));
+        Ok(())
+    }
+
     #[test]
     fn test_parse_split_describe_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -17,15 +17,17 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashSet, VecDeque};
-use std::io::{stdout, IsTerminal, Stdout, Write};
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::io::{IsTerminal, Stderr, Write};
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::{Duration, Instant};
-use std::{env, fmt, io};
+use std::{env, io};
 
 use anyhow::{bail, Context};
+use async_compression::tokio::bufread::GzipDecoder;
+use bytesize::ByteSize;
 use clap::{arg, ArgMatches, Command};
 use colored::{ColoredString, Colorize};
 use humantime::format_duration;
@@ -34,18 +36,24 @@ use quickwit_cluster::{ChannelTransport, Cluster, ClusterMember, FailureDetector
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::uri::Uri;
+use quickwit_common::HumanDuration;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{
-    IndexerConfig, NodeConfig, SourceConfig, SourceInputFormat, SourceParams, TransformConfig,
-    VecSourceParams, CLI_INGEST_SOURCE_ID,
+    build_doc_mapper, FileSourceCompression, FileSourceParams, IndexerConfig, NodeConfig,
+    SourceConfig, SourceInputFormat, SourceParams, TransformConfig, VecSourceParams,
+    CLI_INGEST_SOURCE_ID,
 };
+use quickwit_doc_mapper::{DocMapper, DocParsingError};
 use quickwit_index_management::{clear_cache_directory, IndexService};
-use quickwit_indexing::actors::{IndexingService, MergePipeline, MergePipelineId};
+use quickwit_indexing::actors::{
+    watch_indexing_progress, IndexingService, MergePipeline, MergePipelineId,
+};
 use quickwit_indexing::models::{
     DetachIndexingPipeline, DetachMergePipeline, IndexingStatistics, SpawnPipeline,
 };
 use quickwit_indexing::IndexingPipeline;
 use quickwit_ingest::IngesterPool;
+use quickwit_metastore::checkpoint::PartitionId;
 use quickwit_metastore::IndexMetadataResponseExt;
 use quickwit_proto::indexing::CpuCapacity;
 use quickwit_proto::metastore::{IndexMetadataRequest, MetastoreService, MetastoreServiceClient};
@@ -57,6 +65,9 @@ use quickwit_serve::{
 };
 use quickwit_storage::{BundleStorage, Storage};
 use thousands::Separable;
+use tokio::io::{
+    AsyncBufRead, AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, BufWriter,
+};
 use tracing::{debug, info};
 
 use crate::checklist::{GREEN_COLOR, RED_COLOR};
@@ -65,6 +76,9 @@ use crate::{
     THROUGHPUT_WINDOW_SIZE,
 };
 
+/// Maximum number of invalid documents reported by `local-ingest --dry-run`.
+const MAX_DRY_RUN_ERRORS: usize = 50;
+
 pub fn build_tool_command() -> Command {
     Command::new("tool")
         .about("Performs utility operations. Requires a node config.")
@@ -73,7 +87,7 @@ pub fn build_tool_command() -> Command {
             Command::new("local-ingest")
                 .display_order(10)
                 .about("Indexes NDJSON documents locally.")
-                .long_about("Local ingest indexes locally NDJSON documents from a file or from stdin and uploads splits on the configured storage.")
+                .long_about("Local ingest indexes locally NDJSON documents from a file or from stdin and uploads splits on the configured storage. When ingesting a file, progress is checkpointed along with the published splits: if a previous run on the same file was interrupted, ingestion resumes where it left off, unless `--overwrite` is set.")
                 .args(&[
                     arg!(--index <INDEX> "ID of the target index")
                         .display_order(1)
@@ -83,12 +97,22 @@ pub fn build_tool_command() -> Command {
                     arg!(--"input-format" <INPUT_FORMAT> "Format of the input data.")
                         .default_value("json")
                         .required(false),
+                    arg!(--compression <COMPRESSION> "Compression of the input data. Possible value is `gzip`. Inferred from the `.gz` extension of the input file if not set.")
+                        .required(false),
                     arg!(--overwrite "Overwrites pre-existing index.")
                         .required(false),
                     arg!(--"transform-script" <SCRIPT> "VRL program to transform docs before ingesting.")
                         .required(false),
                     arg!(--"keep-cache" "Does not clear local cache directory upon completion.")
                         .required(false),
+                    arg!(--"limit-bytes" <LIMIT_BYTES> "Stops ingesting once this amount of input has been read, for instance `10GB`, and commits the documents read so far. Running the command again resumes from there.")
+                        .conflicts_with("dry-run")
+                        .required(false),
+                    arg!(--"dry-run" "Parses the documents with the doc mapping of the index and reports the invalid ones, without indexing them.")
+                        .required(false),
+                    arg!(--"rejected-docs-path" <REJECTED_DOCS_PATH> "Writes the invalid documents found during a dry run to this NDJSON file, so they can be fixed and replayed.")
+                        .requires("dry-run")
+                        .required(false),
                 ])
             )
         .subcommand(
@@ -138,6 +162,21 @@ pub fn build_tool_command() -> Command {
                     arg!(--"target-dir" <TARGET_DIR> "Directory to extract the split to."),
                 ])
             )
+        .subcommand(
+            Command::new("publish-split")
+                .about("Registers and publishes a split present in storage but unknown to the metastore.")
+                .long_about("Reconstructs the metadata of a split from its file in the index storage, then stages and publishes it. Use it to recover splits after the metastore and the storage diverged.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(true),
+                    arg!(--split <SPLIT> "ID of the target split")
+                        .display_order(2)
+                        .required(true),
+                    arg!(--force "Replaces a staged or marked for deletion entry of the split in the metastore.")
+                        .required(false),
+                ])
+            )
         .subcommand(
             Command::new("gc")
                 .display_order(10)
@@ -146,9 +185,11 @@ pub fn build_tool_command() -> Command {
                     arg!(--index <INDEX> "ID of the target index")
                         .display_order(1)
                         .required(true),
-                    arg!(--"grace-period" <GRACE_PERIOD> "Threshold period after which stale staged splits are garbage collected.")
+                    arg!(--"grace-period" <GRACE_PERIOD> "Threshold period after which stale staged splits and splits marked for deletion are garbage collected.")
                         .default_value("1h")
                         .required(false),
+                    arg!(--ttl <TTL> "Marks for deletion the published splits whose time range ended more than `ttl` ago, for instance `30d`.")
+                        .required(false),
                     arg!(--"dry-run" "Executes the command in dry run mode and only displays the list of splits candidates for garbage collection.")
                         .required(false),
                 ])
@@ -175,9 +216,13 @@ pub struct LocalIngestDocsArgs {
     pub index_id: String,
     pub input_path_opt: Option<PathBuf>,
     pub input_format: SourceInputFormat,
+    pub compression_opt: Option<FileSourceCompression>,
     pub overwrite: bool,
     pub vrl_script: Option<String>,
     pub clear_cache: bool,
+    pub limit_bytes_opt: Option<ByteSize>,
+    pub dry_run: bool,
+    pub rejected_docs_path_opt: Option<PathBuf>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -200,6 +245,7 @@ pub struct GarbageCollectIndexArgs {
     pub config_uri: Uri,
     pub index_id: String,
     pub grace_period: Duration,
+    pub ttl_opt: Option<Duration>,
     pub dry_run: bool,
 }
 
@@ -218,6 +264,14 @@ pub struct ExtractSplitArgs {
     pub target_dir: PathBuf,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct PublishSplitArgs {
+    pub config_uri: Uri,
+    pub index_id: String,
+    pub split_id: String,
+    pub force: bool,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum ToolCliCommand {
     GarbageCollect(GarbageCollectIndexArgs),
@@ -225,6 +279,7 @@ pub enum ToolCliCommand {
     LocalSearch(LocalSearchArgs),
     Merge(MergeArgs),
     ExtractSplit(ExtractSplitArgs),
+    PublishSplit(PublishSplitArgs),
 }
 
 impl ToolCliCommand {
@@ -238,6 +293,7 @@ impl ToolCliCommand {
             "local-search" => Self::parse_local_search_args(submatches),
             "merge" => Self::parse_merge_args(submatches),
             "extract-split" => Self::parse_extract_split_args(submatches),
+            "publish-split" => Self::parse_publish_split_args(submatches),
             _ => bail!("unknown tool subcommand `{subcommand}`"),
         }
     }
@@ -263,18 +319,36 @@ impl ToolCliCommand {
             .map(|input_format| SourceInputFormat::from_str(&input_format))
             .expect("`input-format` should have a default value.")
             .map_err(|err| anyhow::anyhow!(err))?;
+        let compression_opt = matches
+            .remove_one::<String>("compression")
+            .map(|compression| FileSourceCompression::from_str(&compression))
+            .transpose()
+            .map_err(|err| anyhow::anyhow!(err))?;
         let overwrite = matches.get_flag("overwrite");
         let vrl_script = matches.remove_one::<String>("transform-script");
         let clear_cache = !matches.get_flag("keep-cache");
+        let limit_bytes_opt = matches
+            .remove_one::<String>("limit-bytes")
+            .map(|limit| limit.parse::<ByteSize>())
+            .transpose()
+            .map_err(|error| anyhow::anyhow!(error))?;
+        let dry_run = matches.get_flag("dry-run");
+        let rejected_docs_path_opt = matches
+            .remove_one::<String>("rejected-docs-path")
+            .map(PathBuf::from);
 
         Ok(Self::LocalIngest(LocalIngestDocsArgs {
             config_uri,
             index_id,
             input_path_opt,
             input_format,
+            compression_opt,
             overwrite,
             vrl_script,
             clear_cache,
+            limit_bytes_opt,
+            dry_run,
+            rejected_docs_path_opt,
         }))
     }
 
@@ -356,12 +430,19 @@ impl ToolCliCommand {
             .expect("`index` should be a required arg.");
         let grace_period = matches
             .get_one("grace-period")
-            .map(|duration_str: &String| humantime::parse_duration(duration_str))
-            .expect("`grace-period` should have a default value.")?;
+            .map(|duration_str: &String| HumanDuration::from_str(duration_str))
+            .expect("`grace-period` should have a default value.")?
+            .into();
+        let ttl_opt = matches
+            .get_one("ttl")
+            .map(|duration_str: &String| HumanDuration::from_str(duration_str))
+            .transpose()?
+            .map(Duration::from);
         let dry_run = matches.get_flag("dry-run");
         Ok(Self::GarbageCollect(GarbageCollectIndexArgs {
             index_id,
             grace_period,
+            ttl_opt,
             dry_run,
             config_uri,
         }))
@@ -390,6 +471,26 @@ impl ToolCliCommand {
         }))
     }
 
+    fn parse_publish_split_args(mut matches: ArgMatches) -> anyhow::Result<Self> {
+        let config_uri = matches
+            .remove_one::<String>("config")
+            .map(|uri_str| Uri::from_str(&uri_str))
+            .expect("`config` should be a required arg.")?;
+        let index_id = matches
+            .remove_one::<String>("index")
+            .expect("`index` should be a required arg.");
+        let split_id = matches
+            .remove_one::<String>("split")
+            .expect("`split` should be a required arg.");
+        let force = matches.get_flag("force");
+        Ok(Self::PublishSplit(PublishSplitArgs {
+            config_uri,
+            index_id,
+            split_id,
+            force,
+        }))
+    }
+
     pub async fn execute(self) -> anyhow::Result<()> {
         match self {
             Self::GarbageCollect(args) => garbage_collect_index_cli(args).await,
@@ -397,24 +498,36 @@ impl ToolCliCommand {
             Self::LocalSearch(args) => local_search_cli(args).await,
             Self::Merge(args) => merge_cli(args).await,
             Self::ExtractSplit(args) => extract_split_cli(args).await,
+            Self::PublishSplit(args) => publish_split_cli(args).await,
         }
     }
 }
 
 pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<()> {
     debug!(args=?args, "local-ingest-docs");
-    println!("❯ Ingesting documents locally...");
-
+    if args.dry_run {
+        println!("❯ Validating documents locally (dry run)...");
+    } else {
+        println!("❯ Ingesting documents locally...");
+    }
     let config = load_node_config(&args.config_uri).await?;
     let (storage_resolver, metastore_resolver) =
         get_resolvers(&config.storage_configs, &config.metastore_configs);
     let mut metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
 
-    let source_params = if let Some(filepath) = args.input_path_opt.as_ref() {
-        SourceParams::file(filepath)
+    let file_source_params = if let Some(filepath) = args.input_path_opt.as_ref() {
+        FileSourceParams::file(filepath)
     } else {
-        SourceParams::stdin()
+        FileSourceParams::stdin()
     };
+    let file_source_params = file_source_params
+        .with_compression(args.compression_opt)
+        .with_max_num_bytes(args.limit_bytes_opt);
+
+    if args.dry_run {
+        return dry_run_local_ingest_docs_cli(&args, &mut metastore, &file_source_params).await;
+    }
+    let source_params = SourceParams::File(file_source_params.clone());
     let transform_config = args
         .vrl_script
         .map(|vrl_script| TransformConfig::new(vrl_script, None));
@@ -438,6 +551,13 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
     if args.overwrite {
         let mut index_service = IndexService::new(metastore.clone(), storage_resolver.clone());
         index_service.clear_index(&args.index_id).await?;
+    } else if let Some(resume_offset) =
+        local_ingest_resume_offset(&mut metastore, &args.index_id, &file_source_params).await?
+    {
+        println!(
+            "❯ Resuming ingestion at byte offset {resume_offset}: the preceding documents were \
+             indexed by a previous run. Use `--overwrite` to start over."
+        );
     }
     // The indexing service needs to update its cluster chitchat state so that the control plane is
     // aware of the running tasks. We thus create a fake cluster to instantiate the indexing service
@@ -493,9 +613,7 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
              {eof_shortcut}."
         );
     }
-    let statistics =
-        start_statistics_reporting_loop(indexing_pipeline_handle, args.input_path_opt.is_none())
-            .await?;
+    let statistics = start_statistics_reporting_loop(indexing_pipeline_handle).await?;
     merge_pipeline_handle.quit().await;
     // Shutdown the indexing server.
     universe
@@ -503,6 +621,14 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
         .await?;
     indexing_server_handle.join().await;
     universe.quit().await;
+    if let Some(limit_bytes) = args.limit_bytes_opt {
+        if statistics.reached_max_num_bytes {
+            println!("❯ Stopped after reading {limit_bytes} of input (`--limit-bytes`).");
+            if args.input_path_opt.is_some() {
+                println!("Run the command again to ingest the rest of the file.");
+            }
+        }
+    }
     if statistics.num_published_splits > 0 {
         println!(
             "Now, you can query the index with the following command:\nquickwit index search \
@@ -526,6 +652,213 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
     }
 }
 
+/// Returns the offset in the input file up to which a previous run ingested documents, as
+/// recorded in the checkpoint of the CLI ingest source. The file source resumes from this offset.
+async fn local_ingest_resume_offset(
+    metastore: &mut MetastoreServiceClient,
+    index_id: &str,
+    file_source_params: &FileSourceParams,
+) -> anyhow::Result<Option<u64>> {
+    let Some(filepath) = &file_source_params.filepath else {
+        return Ok(None);
+    };
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(index_id.to_string()))
+        .await?
+        .deserialize_index_metadata()?;
+    let partition_id = PartitionId::from(filepath.to_string_lossy().to_string());
+    let resume_offset_opt = index_metadata
+        .checkpoint
+        .source_checkpoint(CLI_INGEST_SOURCE_ID)
+        .and_then(|source_checkpoint| source_checkpoint.position_for_partition(&partition_id))
+        .and_then(|position| position.as_u64())
+        .filter(|offset| *offset > 0);
+    Ok(resume_offset_opt)
+}
+
+/// Parses the documents to ingest with the doc mapper of the index, reporting the invalid ones
+/// with their line number. Nothing is indexed nor written to the storage.
+async fn dry_run_local_ingest_docs_cli(
+    args: &LocalIngestDocsArgs,
+    metastore: &mut MetastoreServiceClient,
+    file_source_params: &FileSourceParams,
+) -> anyhow::Result<()> {
+    if args.input_format != SourceInputFormat::Json {
+        bail!("`--dry-run` only supports the `json` input format");
+    }
+    if args.vrl_script.is_some() {
+        bail!("`--dry-run` does not support `--transform-script`");
+    }
+    let index_metadata = metastore
+        .index_metadata(IndexMetadataRequest::for_index_id(args.index_id.clone()))
+        .await?
+        .deserialize_index_metadata()?;
+    let doc_mapper = build_doc_mapper(
+        &index_metadata.index_config.doc_mapping,
+        &index_metadata.index_config.search_settings,
+    )?;
+    let reader: Box<dyn AsyncRead + Send + Unpin> =
+        if let Some(filepath) = &file_source_params.filepath {
+            let file = tokio::fs::File::open(filepath)
+                .await
+                .with_context(|| format!("failed to open input file `{}`", filepath.display()))?;
+            Box::new(file)
+        } else {
+            if io::stdin().is_terminal() {
+                println!("Please, enter JSON documents one line at a time.");
+            }
+            Box::new(tokio::io::stdin())
+        };
+    let reader: Box<dyn AsyncRead + Send + Unpin> = match file_source_params.effective_compression()
+    {
+        Some(FileSourceCompression::Gzip) => {
+            let mut gzip_decoder = GzipDecoder::new(BufReader::new(reader));
+            gzip_decoder.multiple_members(true);
+            Box::new(gzip_decoder)
+        }
+        None => reader,
+    };
+    let rejected_docs_writer_opt = if let Some(rejected_docs_path) = &args.rejected_docs_path_opt {
+        let file = tokio::fs::File::create(rejected_docs_path)
+            .await
+            .with_context(|| {
+                format!(
+                    "failed to create rejected docs file `{}`",
+                    rejected_docs_path.display()
+                )
+            })?;
+        Some(BufWriter::new(file))
+    } else {
+        None
+    };
+    let DryRunReport {
+        statistics,
+        invalid_docs,
+        num_invalid_docs_per_kind,
+    } = validate_docs(
+        &*doc_mapper,
+        BufReader::new(reader),
+        MAX_DRY_RUN_ERRORS,
+        rejected_docs_writer_opt,
+    )
+    .await?;
+
+    for (line_number, error) in &invalid_docs {
+        println!("{} line {line_number}: {error}", "✘".color(RED_COLOR));
+    }
+    if statistics.num_invalid_docs as usize > invalid_docs.len() {
+        println!(
+            "... and {} other invalid documents.",
+            statistics.num_invalid_docs as usize - invalid_docs.len()
+        );
+    }
+    println!(
+        "{} documents parsed, {} invalid.",
+        statistics.num_docs.separate_with_commas(),
+        statistics.num_invalid_docs.separate_with_commas()
+    );
+    for (kind, num_invalid_docs) in &num_invalid_docs_per_kind {
+        println!("{:>10} {kind}", num_invalid_docs.separate_with_commas());
+    }
+    if let Some(rejected_docs_path) = &args.rejected_docs_path_opt {
+        if statistics.num_invalid_docs > 0 {
+            println!(
+                "Invalid documents written to `{}`.",
+                rejected_docs_path.display()
+            );
+        }
+    }
+    match statistics.num_invalid_docs {
+        0 => {
+            println!("{} All documents are valid.", "✔".color(GREEN_COLOR));
+            Ok(())
+        }
+        num_invalid_docs => bail!("{num_invalid_docs} documents are invalid"),
+    }
+}
+
+/// Outcome of a `local-ingest --dry-run`.
+struct DryRunReport {
+    /// Statistics of the run, with `num_docs`, `num_invalid_docs`, `num_parse_errors`, and
+    /// `num_schema_errors` populated.
+    statistics: IndexingStatistics,
+    /// Line number and parsing error of the first invalid documents.
+    invalid_docs: Vec<(usize, DocParsingError)>,
+    /// Number of invalid documents for each kind of error, see [`invalid_doc_kind`].
+    num_invalid_docs_per_kind: BTreeMap<String, u64>,
+}
+
+/// Describes the kind of a parsing error, including the offending field when known, so that
+/// invalid documents can be grouped by cause.
+fn invalid_doc_kind(error: &DocParsingError) -> String {
+    match error {
+        DocParsingError::NotJsonObject(_) => "invalid JSON".to_string(),
+        DocParsingError::ValueError(field, _) => format!("invalid value for field `{field}`"),
+        DocParsingError::NoSuchFieldInSchema(_) => "field not declared in the schema".to_string(),
+        DocParsingError::MultiValuesNotSupported(field) => {
+            format!("multiple values for single-valued field `{field}`")
+        }
+        DocParsingError::RequiredField(field) => format!("missing required field `{field}`"),
+        DocParsingError::MaxDepthExceeded(_) => "maximum nesting depth exceeded".to_string(),
+        DocParsingError::MaxNumFieldsExceeded(_) => "maximum number of fields exceeded".to_string(),
+        DocParsingError::DuplicateKey(_) => "duplicate key".to_string(),
+    }
+}
+
+/// Parses the NDJSON documents of `reader` with `doc_mapper` without indexing them.
+///
+/// The line number and parsing error of up to `max_errors` invalid documents are kept in the
+/// report. When a writer is provided, every invalid document is copied to it verbatim.
+async fn validate_docs<R: AsyncBufRead + Unpin, W: AsyncWrite + Unpin>(
+    doc_mapper: &dyn DocMapper,
+    reader: R,
+    max_errors: usize,
+    mut rejected_docs_writer_opt: Option<W>,
+) -> anyhow::Result<DryRunReport> {
+    let mut statistics = IndexingStatistics::default();
+    let mut invalid_docs = Vec::new();
+    let mut num_invalid_docs_per_kind: BTreeMap<String, u64> = BTreeMap::new();
+    let mut lines = reader.lines();
+    let mut line_number = 0;
+
+    while let Some(line) = lines.next_line().await? {
+        line_number += 1;
+        if line.trim().is_empty() {
+            continue;
+        }
+        statistics.num_docs += 1;
+
+        if let Err(error) = doc_mapper.doc_from_json_str(&line) {
+            statistics.num_invalid_docs += 1;
+
+            if matches!(error, DocParsingError::NotJsonObject(_)) {
+                statistics.num_parse_errors += 1;
+            } else {
+                statistics.num_schema_errors += 1;
+            }
+            *num_invalid_docs_per_kind
+                .entry(invalid_doc_kind(&error))
+                .or_default() += 1;
+
+            if let Some(rejected_docs_writer) = rejected_docs_writer_opt.as_mut() {
+                rejected_docs_writer.write_all(line.as_bytes()).await?;
+                rejected_docs_writer.write_all(b"\n").await?;
+            }
+            if invalid_docs.len() < max_errors {
+                invalid_docs.push((line_number, error));
+            }
+        }
+    }
+    if let Some(mut rejected_docs_writer) = rejected_docs_writer_opt {
+        rejected_docs_writer.flush().await?;
+    }
+    Ok(DryRunReport {
+        statistics,
+        invalid_docs,
+        num_invalid_docs_per_kind,
+    })
+}
+
 pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
     debug!(args=?args, "local-search");
     println!("❯ Searching directly on the index storage (without calling REST API)...");
@@ -551,6 +884,19 @@ pub async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
         format: BodyFormat::Json,
         sort_by,
         count_all: CountHits::CountAll,
+        tags: None,
+        exists: None,
+        missing: None,
+        disable_synonyms: false,
+        snapshot_id: None,
+        geo_bbox: None,
+        geo_distance: None,
+        search_after: None,
+        debug: false,
+        timeout: None,
+        fuzzy_distance: None,
+        num_searchers: None,
+        default_operator: None,
     };
     let search_request =
         search_request_from_api_request(vec![args.index_id], search_request_query_string)?;
@@ -660,8 +1006,46 @@ pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow:
     let metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
     let mut index_service = IndexService::new(metastore, storage_resolver);
     let removal_info = index_service
-        .garbage_collect_index(&args.index_id, args.grace_period, args.dry_run)
+        .garbage_collect_index(
+            &args.index_id,
+            args.grace_period,
+            args.ttl_opt,
+            args.dry_run,
+        )
         .await?;
+    if args.dry_run && !removal_info.skipped_splits.is_empty() {
+        println!(
+            "The following splits were updated within the grace period of {} and will be skipped.",
+            format_duration(args.grace_period)
+        );
+        for split_info in &removal_info.skipped_splits {
+            println!(" - {}", split_info.split_id);
+        }
+    }
+    if args.dry_run && !removal_info.protected_splits.is_empty() {
+        println!(
+            "The following splits are referenced by an index snapshot and will be skipped until \
+             the snapshot is deleted."
+        );
+        for split_info in &removal_info.protected_splits {
+            println!(" - {}", split_info.split_id);
+        }
+    }
+    if let Some(ttl) = args
+        .ttl_opt
+        .filter(|_| !removal_info.expired_splits.is_empty())
+    {
+        let verb = if args.dry_run { "will be" } else { "were" };
+        println!(
+            "The following splits ended more than {} ago and {verb} marked for deletion. Their \
+             files are garbage collected once the grace period has elapsed.",
+            format_duration(ttl)
+        );
+        for split_info in &removal_info.expired_splits {
+            println!(" - {}", split_info.split_id);
+        }
+    }
+
     if removal_info.removed_split_entries.is_empty() && removal_info.failed_splits.is_empty() {
         println!("No dangling files to garbage collect.");
         return Ok(());
@@ -747,38 +1131,48 @@ async fn extract_split_cli(args: ExtractSplitArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Starts a tokio task that displays the indexing statistics
-/// every once in awhile.
+async fn publish_split_cli(args: PublishSplitArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "publish-split");
+    println!("❯ Publishing split from storage...");
+
+    let config = load_node_config(&args.config_uri).await?;
+    let (storage_resolver, metastore_resolver) =
+        get_resolvers(&config.storage_configs, &config.metastore_configs);
+    let metastore = metastore_resolver.resolve(&config.metastore_uri).await?;
+    let mut index_service = IndexService::new(metastore, storage_resolver);
+    let split_metadata = index_service
+        .publish_split_from_storage(&args.index_id, &args.split_id, args.force)
+        .await?;
+    println!(
+        "{} Split `{}` successfully published with {} documents.",
+        "✔".color(GREEN_COLOR),
+        split_metadata.split_id,
+        split_metadata.num_docs.separate_with_commas()
+    );
+    Ok(())
+}
+
+/// Displays the indexing statistics every second until the pipeline exits, then prints an end of
+/// task report.
 pub async fn start_statistics_reporting_loop(
     pipeline_handle: ActorHandle<IndexingPipeline>,
-    is_stdin: bool,
 ) -> anyhow::Result<IndexingStatistics> {
-    let mut stdout_handle = stdout();
     let start_time = Instant::now();
-    let mut throughput_calculator = ThroughputCalculator::new(start_time);
-    let mut report_interval = tokio::time::interval(Duration::from_secs(1));
+    let mut progress_reporter = ProgressReporter::new(start_time);
+    // 1 second is the frequency at which we update statistics in the console.
+    let (mut progress_rx, progress_join_handle) =
+        watch_indexing_progress(pipeline_handle, Duration::from_secs(1));
 
-    loop {
-        // TODO fixme. The way we wait today is a bit lame: if the indexing pipeline exits, we will
-        // still wait up to an entire heartbeat...  Ideally we should  select between two
-        // futures.
-        report_interval.tick().await;
-        // Try to receive with a timeout of 1 second.
-        // 1 second is also the frequency at which we update statistic in the console
-        pipeline_handle.refresh_observe();
+    // The channel closes once the final statistics of the pipeline have been published.
+    while progress_rx.changed().await.is_ok() {
+        let statistics = progress_rx.borrow_and_update().clone();
 
-        let observation = pipeline_handle.last_observation();
-
-        // Let's not display live statistics to allow screen to scroll.
-        if observation.num_docs > 0 {
-            display_statistics(&mut stdout_handle, &mut throughput_calculator, &observation)?;
-        }
-
-        if pipeline_handle.state().is_exit() {
-            break;
+        if statistics.num_docs > 0 {
+            progress_reporter.report(&statistics)?;
         }
     }
-    let (pipeline_exit_status, pipeline_statistics) = pipeline_handle.join().await;
+    progress_reporter.finish()?;
+    let (pipeline_exit_status, pipeline_statistics) = progress_join_handle.await?;
     if !pipeline_exit_status.is_success() {
         bail!(pipeline_exit_status);
     }
@@ -787,14 +1181,6 @@ pub async fn start_statistics_reporting_loop(
     if pipeline_statistics.num_docs == 0 {
         return Ok(pipeline_statistics);
     }
-
-    if is_stdin {
-        display_statistics(
-            &mut stdout_handle,
-            &mut throughput_calculator,
-            &pipeline_statistics,
-        )?;
-    }
     // display end of task report
     println!();
     let secs = Duration::from_secs(start_time.elapsed().as_secs());
@@ -814,11 +1200,12 @@ pub async fn start_statistics_reporting_loop(
             * 100.0;
 
         println!(
-            "Indexed {} out of {} documents in {}. Failed to index {} document(s). {}\n",
+            "Indexed {} out of {} documents in {}. Failed to index {} document(s){}. {}\n",
             num_indexed_docs,
             pipeline_statistics.num_docs.separate_with_commas(),
             format_duration(secs),
             pipeline_statistics.num_invalid_docs.separate_with_commas(),
+            format_invalid_docs_breakdown(&pipeline_statistics),
             colorize_error_rate(error_rate),
         );
     }
@@ -826,6 +1213,28 @@ pub async fn start_statistics_reporting_loop(
     Ok(pipeline_statistics)
 }
 
+/// Formats the number of invalid documents per error kind, for instance ` (3 parse errors, 1
+/// schema error)`. Returns an empty string if no error kind has been recorded.
+fn format_invalid_docs_breakdown(statistics: &IndexingStatistics) -> String {
+    let breakdown = [
+        (statistics.num_parse_errors, "parse error"),
+        (statistics.num_schema_errors, "schema error"),
+        (statistics.num_transform_errors, "transform error"),
+    ]
+    .into_iter()
+    .filter(|(num_errors, _)| *num_errors > 0)
+    .map(|(num_errors, kind)| {
+        let plural = if num_errors > 1 { "s" } else { "" };
+        format!("{} {kind}{plural}", num_errors.separate_with_commas())
+    })
+    .collect::<Vec<_>>()
+    .join(", ");
+    if breakdown.is_empty() {
+        return breakdown;
+    }
+    format!(" ({breakdown})")
+}
+
 fn colorize_error_rate(error_rate: f64) -> ColoredString {
     let error_rate_message = format!("({error_rate:.1}% error rate)");
     if error_rate < 1.0 {
@@ -837,63 +1246,110 @@ fn colorize_error_rate(error_rate: f64) -> ColoredString {
     }
 }
 
-/// A struct to print data on the standard output.
-struct Printer<'a> {
-    pub stdout: &'a mut Stdout,
+/// Interval between two progress lines when stderr is not a terminal.
+const PLAIN_PROGRESS_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Renders the live indexing statistics on stderr.
+///
+/// When stderr is a terminal, a single progress line is refreshed in place. Otherwise, for instance
+/// in CI logs, a plain line is printed every [`PLAIN_PROGRESS_INTERVAL`] instead.
+struct ProgressReporter {
+    stderr: Stderr,
+    is_terminal: bool,
+    bytes_throughput_calculator: ThroughputCalculator,
+    docs_throughput_calculator: ThroughputCalculator,
+    last_line_instant_opt: Option<Instant>,
 }
 
-impl<'a> Printer<'a> {
-    pub fn print_header(&mut self, header: &str) -> io::Result<()> {
-        write!(&mut self.stdout, " {}", header.bright_blue())?;
-        Ok(())
+impl ProgressReporter {
+    fn new(start_time: Instant) -> Self {
+        let stderr = io::stderr();
+        let is_terminal = stderr.is_terminal();
+        Self {
+            stderr,
+            is_terminal,
+            bytes_throughput_calculator: ThroughputCalculator::new(start_time),
+            docs_throughput_calculator: ThroughputCalculator::new(start_time),
+            last_line_instant_opt: None,
+        }
     }
 
-    pub fn print_value(&mut self, fmt_args: fmt::Arguments) -> io::Result<()> {
-        write!(&mut self.stdout, " {fmt_args}")
+    fn report(&mut self, statistics: &IndexingStatistics) -> io::Result<()> {
+        // The throughputs are computed on every report so that they always cover the last few
+        // seconds, even when the line is not printed.
+        let bytes_per_sec = self
+            .bytes_throughput_calculator
+            .calculate(statistics.total_bytes_processed);
+        let docs_per_sec = self
+            .docs_throughput_calculator
+            .calculate(statistics.num_docs);
+        let now = Instant::now();
+
+        if !self.is_terminal {
+            if let Some(last_line_instant) = self.last_line_instant_opt {
+                if now.duration_since(last_line_instant) < PLAIN_PROGRESS_INTERVAL {
+                    return Ok(());
+                }
+            }
+        }
+        self.last_line_instant_opt = Some(now);
+
+        let progress_line = format_progress_line(
+            statistics,
+            self.bytes_throughput_calculator.elapsed_time(),
+            docs_per_sec,
+            bytes_per_sec,
+        );
+        if self.is_terminal {
+            // Moves the cursor back to the start of the line and clears it.
+            write!(self.stderr, "\r\x1b[2K{progress_line}")?;
+        } else {
+            writeln!(self.stderr, "{progress_line}")?;
+        }
+        self.stderr.flush()
     }
 
-    pub fn flush(&mut self) -> io::Result<()> {
-        self.stdout.flush()
+    /// Ends the progress line so that what comes next starts on a fresh line.
+    fn finish(&mut self) -> io::Result<()> {
+        if self.is_terminal && self.last_line_instant_opt.is_some() {
+            writeln!(self.stderr)?;
+        }
+        Ok(())
     }
 }
 
-fn display_statistics(
-    stdout: &mut Stdout,
-    throughput_calculator: &mut ThroughputCalculator,
+fn format_progress_line(
     statistics: &IndexingStatistics,
-) -> anyhow::Result<()> {
-    let elapsed_duration = time::Duration::try_from(throughput_calculator.elapsed_time())?;
-    let elapsed_time = format!(
-        "{:02}:{:02}:{:02}",
-        elapsed_duration.whole_hours(),
-        elapsed_duration.whole_minutes() % 60,
-        elapsed_duration.whole_seconds() % 60
-    );
-    let throughput_mb_s = throughput_calculator.calculate(statistics.total_bytes_processed);
-    let mut printer = Printer { stdout };
-    printer.print_header("Num docs")?;
-    printer.print_value(format_args!("{:>7}", statistics.num_docs))?;
-    printer.print_header("Parse errs")?;
-    printer.print_value(format_args!("{:>5}", statistics.num_invalid_docs))?;
-    printer.print_header("PublSplits")?;
-    printer.print_value(format_args!("{:>3}", statistics.num_published_splits))?;
-    printer.print_header("Input size")?;
-    printer.print_value(format_args!(
-        "{:>5}MB",
-        statistics.total_bytes_processed / 1_000_000
-    ))?;
-    printer.print_header("Thrghput")?;
-    printer.print_value(format_args!("{throughput_mb_s:>5.2}MB/s"))?;
-    printer.print_header("Time")?;
-    printer.print_value(format_args!("{elapsed_time}\n"))?;
-    printer.flush()?;
-    Ok(())
+    elapsed_time: Duration,
+    docs_per_sec: f64,
+    bytes_per_sec: f64,
+) -> String {
+    let elapsed_secs = elapsed_time.as_secs();
+    let num_indexed_docs = statistics
+        .num_docs
+        .saturating_sub(statistics.num_invalid_docs);
+    format!(
+        "Docs parsed {} | indexed {} | parse errs {} | Read {}MB | Uploaded {}MB | Splits staged \
+         {} published {} | {} docs/s {:.2}MB/s | {:02}:{:02}:{:02}",
+        statistics.num_docs.separate_with_commas(),
+        num_indexed_docs.separate_with_commas(),
+        statistics.num_invalid_docs.separate_with_commas(),
+        (statistics.total_bytes_processed / 1_000_000).separate_with_commas(),
+        (statistics.total_size_splits / 1_000_000).separate_with_commas(),
+        statistics.num_staged_splits,
+        statistics.num_published_splits,
+        (docs_per_sec.round() as u64).separate_with_commas(),
+        bytes_per_sec / 1_000_000f64,
+        elapsed_secs / 3_600,
+        elapsed_secs / 60 % 60,
+        elapsed_secs % 60,
+    )
 }
 
 /// ThroughputCalculator is used to calculate throughput.
 struct ThroughputCalculator {
-    /// Stores the time series of processed bytes value.
-    processed_bytes_values: VecDeque<(Instant, u64)>,
+    /// Stores the time series of processed values (bytes, documents...).
+    processed_values: VecDeque<(Instant, u64)>,
     /// Store the time this calculator started
     start_time: Instant,
 }
@@ -901,25 +1357,24 @@ struct ThroughputCalculator {
 impl ThroughputCalculator {
     /// Creates new instance.
     pub fn new(start_time: Instant) -> Self {
-        let processed_bytes_values: VecDeque<(Instant, u64)> = (0..THROUGHPUT_WINDOW_SIZE)
+        let processed_values: VecDeque<(Instant, u64)> = (0..THROUGHPUT_WINDOW_SIZE)
             .map(|_| (start_time, 0u64))
             .collect();
         Self {
-            processed_bytes_values,
+            processed_values,
             start_time,
         }
     }
 
-    /// Calculates the throughput.
-    pub fn calculate(&mut self, current_processed_bytes: u64) -> f64 {
-        self.processed_bytes_values.pop_front();
+    /// Calculates the throughput, in units per second, over the last few calls.
+    pub fn calculate(&mut self, current_processed_value: u64) -> f64 {
+        self.processed_values.pop_front();
         let current_instant = Instant::now();
-        let (first_instant, first_processed_bytes) = *self.processed_bytes_values.front().unwrap();
+        let (first_instant, first_processed_value) = *self.processed_values.front().unwrap();
         let elapsed_time = (current_instant - first_instant).as_millis() as f64 / 1_000f64;
-        self.processed_bytes_values
-            .push_back((current_instant, current_processed_bytes));
-        (current_processed_bytes - first_processed_bytes) as f64
-            / 1_000_000f64
+        self.processed_values
+            .push_back((current_instant, current_processed_value));
+        current_processed_value.saturating_sub(first_processed_value) as f64
             / elapsed_time.max(1f64)
     }
 
@@ -928,7 +1383,7 @@ impl ThroughputCalculator {
     }
 }
 
-async fn create_empty_cluster(config: &NodeConfig) -> anyhow::Result<Cluster> {
+pub(crate) async fn create_empty_cluster(config: &NodeConfig) -> anyhow::Result<Cluster> {
     let node_id: NodeId = config.node_id.clone().into();
     let self_node = ClusterMember {
         node_id,
@@ -952,3 +1407,60 @@ async fn create_empty_cluster(config: &NodeConfig) -> anyhow::Result<Cluster> {
 
     Ok(cluster)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_progress_line() {
+        let statistics = IndexingStatistics {
+            num_docs: 12_345,
+            num_invalid_docs: 45,
+            num_staged_splits: 3,
+            num_published_splits: 2,
+            total_bytes_processed: 52_000_000,
+            total_size_splits: 21_000_000,
+            ..Default::default()
+        };
+        let progress_line = format_progress_line(
+            &statistics,
+            Duration::from_secs(3_725),
+            1_234.4,
+            2_500_000.0,
+        );
+        assert_eq!(
+            progress_line,
+            "Docs parsed 12,345 | indexed 12,300 | parse errs 45 | Read 52MB | Uploaded 21MB | \
+             Splits staged 3 published 2 | 1,234 docs/s 2.50MB/s | 01:02:05"
+        );
+    }
+
+    #[test]
+    fn test_format_invalid_docs_breakdown() {
+        let statistics = IndexingStatistics {
+            num_invalid_docs: 4,
+            num_parse_errors: 3,
+            num_schema_errors: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_invalid_docs_breakdown(&statistics),
+            " (3 parse errors, 1 schema error)"
+        );
+
+        let statistics = IndexingStatistics {
+            num_invalid_docs: 4,
+            ..Default::default()
+        };
+        assert_eq!(format_invalid_docs_breakdown(&statistics), "");
+    }
+
+    #[test]
+    fn test_throughput_calculator() {
+        let start_time = Instant::now() - Duration::from_secs(2);
+        let mut throughput_calculator = ThroughputCalculator::new(start_time);
+        let throughput = throughput_calculator.calculate(1_000);
+        assert!((400.0..=500.0).contains(&throughput));
+    }
+}
@@ -25,13 +25,14 @@ use std::path::Path;
 use std::str::FromStr;
 
 use anyhow::Result;
+use bytesize::ByteSize;
 use clap::error::ErrorKind;
 use helpers::{TestEnv, TestStorageType};
 use quickwit_cli::checklist::ChecklistError;
 use quickwit_cli::cli::build_cli;
 use quickwit_cli::index::{
-    create_index_cli, delete_index_cli, search_index, CreateIndexArgs, DeleteIndexArgs,
-    SearchIndexArgs,
+    create_index_cli, delete_index_cli, explain_search_index, search_index, CreateIndexArgs,
+    DeleteIndexArgs, OutputFormat, SearchIndexArgs,
 };
 use quickwit_cli::tool::{
     garbage_collect_index_cli, local_ingest_docs_cli, GarbageCollectIndexArgs, LocalIngestDocsArgs,
@@ -49,6 +50,7 @@ use quickwit_proto::metastore::{
     DeleteSplitsRequest, EntityKind, IndexMetadataRequest, ListSplitsRequest,
     MarkSplitsForDeletionRequest, MetastoreError, MetastoreService, StageSplitsRequest,
 };
+use quickwit_query::BooleanOperand;
 use serde_json::{json, Number, Value};
 use tokio::time::{sleep, Duration};
 
@@ -62,6 +64,7 @@ async fn create_logs_index(test_env: &TestEnv) -> anyhow::Result<()> {
         },
         index_config_uri: test_env.index_config_uri.clone(),
         overwrite: false,
+        validate_only: false,
         assume_yes: true,
     };
     create_index_cli(args).await
@@ -73,9 +76,13 @@ async fn local_ingest_docs(input_path: &Path, test_env: &TestEnv) -> anyhow::Res
         index_id: test_env.index_id.clone(),
         input_path_opt: Some(input_path.to_path_buf()),
         input_format: SourceInputFormat::Json,
+        compression_opt: None,
         overwrite: false,
         clear_cache: true,
+        limit_bytes_opt: None,
         vrl_script: None,
+        dry_run: false,
+        rejected_docs_path_opt: None,
     };
     local_ingest_docs_cli(args).await
 }
@@ -125,6 +132,7 @@ async fn test_cmd_create_no_index_uri() {
         },
         index_config_uri: index_config_without_uri,
         overwrite: false,
+        validate_only: false,
         assume_yes: true,
     };
 
@@ -153,6 +161,7 @@ async fn test_cmd_create_overwrite() {
         },
         index_config_uri: index_config_without_uri,
         overwrite: true,
+        validate_only: false,
         assume_yes: true,
     };
 
@@ -185,9 +194,13 @@ async fn test_cmd_ingest_on_non_existing_index() {
         index_id: "index-does-not-exist".to_string(),
         input_path_opt: Some(test_env.resource_files["logs"].clone()),
         input_format: SourceInputFormat::Json,
+        compression_opt: None,
         overwrite: false,
         clear_cache: true,
+        limit_bytes_opt: None,
         vrl_script: None,
+        dry_run: false,
+        rejected_docs_path_opt: None,
     };
 
     let error = local_ingest_docs_cli(args).await.unwrap_err();
@@ -215,9 +228,13 @@ async fn test_ingest_docs_cli_keep_cache() {
         index_id,
         input_path_opt: Some(test_env.resource_files["logs"].clone()),
         input_format: SourceInputFormat::Json,
+        compression_opt: None,
         overwrite: false,
         clear_cache: false,
+        limit_bytes_opt: None,
         vrl_script: None,
+        dry_run: false,
+        rejected_docs_path_opt: None,
     };
 
     local_ingest_docs_cli(args).await.unwrap();
@@ -242,9 +259,13 @@ async fn test_ingest_docs_cli() {
         index_id: index_id.clone(),
         input_path_opt: Some(test_env.resource_files["logs"].clone()),
         input_format: SourceInputFormat::Json,
+        compression_opt: None,
         overwrite: false,
         clear_cache: true,
+        limit_bytes_opt: None,
         vrl_script: None,
+        dry_run: false,
+        rejected_docs_path_opt: None,
     };
 
     local_ingest_docs_cli(args).await.unwrap();
@@ -273,9 +294,13 @@ async fn test_ingest_docs_cli() {
         index_id: test_env.index_id,
         input_path_opt: Some(test_env.data_dir_path.join("file-does-not-exist.json")),
         input_format: SourceInputFormat::Json,
+        compression_opt: None,
         overwrite: false,
         clear_cache: true,
+        limit_bytes_opt: None,
         vrl_script: None,
+        dry_run: false,
+        rejected_docs_path_opt: None,
     };
 
     let error = local_ingest_docs_cli(args).await.unwrap_err();
@@ -288,6 +313,128 @@ async fn test_ingest_docs_cli() {
     ));
 }
 
+#[tokio::test]
+async fn test_ingest_docs_cli_resumes_after_interruption() {
+    quickwit_common::setup_logging_for_tests();
+    let index_id = append_random_suffix("test-index-resume");
+    let test_env = create_test_env(index_id.clone(), TestStorageType::LocalFileSystem)
+        .await
+        .unwrap();
+    test_env.start_server().await.unwrap();
+    create_logs_index(&test_env).await.unwrap();
+    let index_uid = test_env.index_metadata().await.unwrap().index_uid;
+
+    let input_path = test_env.resource_files["logs"].clone();
+    let docs = std::fs::read_to_string(&input_path).unwrap();
+    let num_docs = docs.lines().count();
+    let first_docs_num_bytes: usize = docs.split_inclusive('\n').take(2).map(str::len).sum();
+
+    // The first run stops after the first two documents and publishes them in a split, as if the
+    // process had crashed right after publishing its first split.
+    let args = LocalIngestDocsArgs {
+        config_uri: test_env.config_uri.clone(),
+        index_id: index_id.clone(),
+        input_path_opt: Some(input_path.clone()),
+        input_format: SourceInputFormat::Json,
+        compression_opt: None,
+        overwrite: false,
+        clear_cache: true,
+        limit_bytes_opt: Some(ByteSize::b(first_docs_num_bytes as u64)),
+        vrl_script: None,
+        dry_run: false,
+        rejected_docs_path_opt: None,
+    };
+    local_ingest_docs_cli(args).await.unwrap();
+
+    // The second run resumes from the checkpoint of the first one.
+    local_ingest_docs(&input_path, &test_env).await.unwrap();
+
+    let splits_metadata: Vec<SplitMetadata> = test_env
+        .metastore()
+        .await
+        .list_splits(ListSplitsRequest::try_from_index_uid(index_uid).unwrap())
+        .await
+        .unwrap()
+        .collect_splits_metadata()
+        .await
+        .unwrap();
+    assert_eq!(splits_metadata.len(), 2);
+    let mut split_num_docs: Vec<usize> = splits_metadata
+        .iter()
+        .map(|split_metadata| split_metadata.num_docs)
+        .collect();
+    split_num_docs.sort();
+    // No document was indexed twice.
+    assert_eq!(split_num_docs, [2, num_docs - 2]);
+}
+
+#[tokio::test]
+async fn test_ingest_docs_cli_dry_run() {
+    quickwit_common::setup_logging_for_tests();
+    let index_id = append_random_suffix("test-index-dry-run");
+    let test_env = create_test_env(index_id.clone(), TestStorageType::LocalFileSystem)
+        .await
+        .unwrap();
+    test_env.start_server().await.unwrap();
+    create_logs_index(&test_env).await.unwrap();
+    let index_uid = test_env.index_metadata().await.unwrap().index_uid;
+
+    let valid_docs = std::fs::read_to_string(&test_env.resource_files["logs"]).unwrap();
+    let input_path = test_env.data_dir_path.join("dry-run-docs.json");
+    std::fs::write(
+        &input_path,
+        format!("{valid_docs}\nnot a json document\n[1, 2]\n"),
+    )
+    .unwrap();
+    let rejected_docs_path = test_env.data_dir_path.join("rejected-docs.json");
+
+    let args = LocalIngestDocsArgs {
+        config_uri: test_env.config_uri.clone(),
+        index_id: index_id.clone(),
+        input_path_opt: Some(input_path),
+        input_format: SourceInputFormat::Json,
+        compression_opt: None,
+        overwrite: false,
+        clear_cache: true,
+        limit_bytes_opt: None,
+        vrl_script: None,
+        dry_run: true,
+        rejected_docs_path_opt: Some(rejected_docs_path.clone()),
+    };
+    let error = local_ingest_docs_cli(args).await.unwrap_err();
+    assert_eq!(error.to_string(), "2 documents are invalid");
+
+    let rejected_docs = std::fs::read_to_string(&rejected_docs_path).unwrap();
+    assert_eq!(rejected_docs, "not a json document\n[1, 2]\n");
+
+    // Nothing was indexed.
+    let splits_metadata: Vec<SplitMetadata> = test_env
+        .metastore()
+        .await
+        .list_splits(ListSplitsRequest::try_from_index_uid(index_uid).unwrap())
+        .await
+        .unwrap()
+        .collect_splits_metadata()
+        .await
+        .unwrap();
+    assert!(splits_metadata.is_empty());
+
+    let args = LocalIngestDocsArgs {
+        config_uri: test_env.config_uri.clone(),
+        index_id,
+        input_path_opt: Some(test_env.resource_files["logs"].clone()),
+        input_format: SourceInputFormat::Json,
+        compression_opt: None,
+        overwrite: false,
+        clear_cache: true,
+        limit_bytes_opt: None,
+        vrl_script: None,
+        dry_run: true,
+        rejected_docs_path_opt: None,
+    };
+    local_ingest_docs_cli(args).await.unwrap();
+}
+
 /// Helper function to compare a json payload.
 ///
 /// It will serialize and deserialize the value in order
@@ -370,7 +517,7 @@ async fn test_cmd_search_aggregation() {
 
     // search with aggregation
     let args = SearchIndexArgs {
-        index_id: test_env.index_id,
+        index_ids: vec![test_env.index_id],
         query: "paris OR tokio OR london".to_string(),
         aggregation: Some(serde_json::to_string(&aggregation).unwrap()),
         max_hits: 10,
@@ -379,11 +526,23 @@ async fn test_cmd_search_aggregation() {
         snippet_fields: None,
         start_timestamp: None,
         end_timestamp: None,
+        tags: None,
+        snapshot_id: None,
+        geo_bbox: None,
+        geo_distance: None,
         client_args: ClientArgs {
             cluster_endpoint: test_env.cluster_endpoint,
             ..Default::default()
         },
         sort_by_score: false,
+        search_after: None,
+        search_timeout: None,
+        fuzzy_distance: None,
+        default_operator: None,
+        verbose: false,
+        explain: false,
+        output_format: OutputFormat::Json,
+        output_fields: None,
     };
     let search_response = search_index(args).await.unwrap();
 
@@ -453,7 +612,7 @@ async fn test_cmd_search_with_snippets() -> Result<()> {
 
     // search with snippets
     let args = SearchIndexArgs {
-        index_id: test_env.index_id,
+        index_ids: vec![test_env.index_id],
         query: "event:baz".to_string(),
         aggregation: None,
         max_hits: 10,
@@ -462,11 +621,23 @@ async fn test_cmd_search_with_snippets() -> Result<()> {
         snippet_fields: Some(vec!["event".to_string()]),
         start_timestamp: None,
         end_timestamp: None,
+        tags: None,
+        snapshot_id: None,
+        geo_bbox: None,
+        geo_distance: None,
         client_args: ClientArgs {
             cluster_endpoint: test_env.cluster_endpoint,
             ..Default::default()
         },
         sort_by_score: false,
+        search_after: None,
+        search_timeout: None,
+        fuzzy_distance: None,
+        default_operator: None,
+        verbose: false,
+        explain: false,
+        output_format: OutputFormat::Json,
+        output_fields: None,
     };
     let search_response = search_index(args).await.unwrap();
     assert_eq!(search_response.hits.len(), 1);
@@ -481,6 +652,75 @@ async fn test_cmd_search_with_snippets() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_cmd_search_explain() -> Result<()> {
+    quickwit_common::setup_logging_for_tests();
+    let index_id = append_random_suffix("test-search-explain-cmd");
+    let test_env = create_test_env(index_id, TestStorageType::LocalFileSystem)
+        .await
+        .unwrap();
+    test_env.start_server().await.unwrap();
+    create_logs_index(&test_env).await.unwrap();
+
+    local_ingest_docs(test_env.resource_files["logs"].as_path(), &test_env)
+        .await
+        .unwrap();
+
+    let create_explain_args = |start_timestamp: Option<i64>| SearchIndexArgs {
+        index_ids: vec![test_env.index_id.clone()],
+        query: "event:baz".to_string(),
+        aggregation: None,
+        max_hits: 10,
+        start_offset: 0,
+        search_fields: None,
+        snippet_fields: None,
+        start_timestamp,
+        end_timestamp: None,
+        tags: None,
+        snapshot_id: None,
+        geo_bbox: None,
+        geo_distance: None,
+        client_args: ClientArgs {
+            cluster_endpoint: test_env.cluster_endpoint.clone(),
+            ..Default::default()
+        },
+        sort_by_score: false,
+        search_after: None,
+        search_timeout: None,
+        fuzzy_distance: None,
+        default_operator: None,
+        verbose: false,
+        explain: true,
+        output_format: OutputFormat::Json,
+        output_fields: None,
+    };
+    let explain_result = explain_search_index(create_explain_args(None))
+        .await
+        .unwrap();
+    assert!(!explain_result.splits.is_empty());
+    let num_candidate_docs: u64 = explain_result
+        .splits
+        .iter()
+        .map(|split_explanation| split_explanation.num_candidate_docs)
+        .sum();
+    assert_eq!(num_candidate_docs, 1);
+    assert!(explain_result
+        .splits
+        .iter()
+        .all(|split_explanation| !split_explanation.pruned_by_time_range));
+
+    // All the documents are older than 2100-01-01.
+    let explain_result = explain_search_index(create_explain_args(Some(4_102_444_800)))
+        .await
+        .unwrap();
+    assert!(!explain_result.splits.is_empty());
+    assert!(explain_result
+        .splits
+        .iter()
+        .all(|split_explanation| split_explanation.pruned_by_time_range));
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_search_index_cli() {
     quickwit_common::setup_logging_for_tests();
@@ -496,7 +736,7 @@ async fn test_search_index_cli() {
             cluster_endpoint: test_env.cluster_endpoint.clone(),
             ..Default::default()
         },
-        index_id: index_id.clone(),
+        index_ids: vec![index_id.clone()],
         query: query.to_string(),
         aggregation: None,
         max_hits: 20,
@@ -505,7 +745,19 @@ async fn test_search_index_cli() {
         snippet_fields: None,
         start_timestamp: None,
         end_timestamp: None,
+        tags: None,
+        snapshot_id: None,
+        geo_bbox: None,
+        geo_distance: None,
         sort_by_score: false,
+        search_after: None,
+        search_timeout: None,
+        fuzzy_distance: None,
+        default_operator: None,
+        verbose: false,
+        explain: false,
+        output_format: OutputFormat::Json,
+        output_fields: None,
     };
 
     local_ingest_docs(test_env.resource_files["logs"].as_path(), &test_env)
@@ -531,6 +783,22 @@ async fn test_search_index_cli() {
     // search_index_cli calls search_index and prints the SearchResponse
     let search_res = search_index(args).await.unwrap();
     assert_eq!(search_res.num_hits, 0);
+
+    // Clauses without an explicit operator are combined with `AND` by default.
+    let args = create_search_args("level:info city:paris");
+    let search_res = search_index(args).await.unwrap();
+    assert_eq!(search_res.num_hits, 1);
+
+    let mut args = create_search_args("level:info city:paris");
+    args.default_operator = Some(BooleanOperand::Or);
+    let search_res = search_index(args).await.unwrap();
+    assert_eq!(search_res.num_hits, 4);
+
+    // Phrase queries and field-scoped terms can be mixed with the default operator.
+    let mut args = create_search_args("event:\"biz\" city:tokio");
+    args.default_operator = Some(BooleanOperand::Or);
+    let search_res = search_index(args).await.unwrap();
+    assert_eq!(search_res.num_hits, 2);
 }
 
 #[tokio::test]
@@ -663,6 +931,7 @@ async fn test_garbage_collect_cli_no_grace() {
         config_uri: test_env.config_uri.clone(),
         index_id: index_id.clone(),
         grace_period: Duration::from_secs(3600),
+        ttl_opt: None,
         dry_run,
     };
 
@@ -707,6 +976,20 @@ async fn test_garbage_collect_cli_no_grace() {
 
     garbage_collect_index_cli(args).await.unwrap();
 
+    // Splits `MarkedForDeletion` within grace period should still exist.
+    for split_id in split_ids.iter() {
+        let split_file = quickwit_common::split_file(split_id);
+        let split_filepath = index_path.join(split_file);
+        assert_eq!(split_filepath.try_exists().unwrap(), true);
+    }
+
+    let args = GarbageCollectIndexArgs {
+        grace_period: Duration::ZERO,
+        ..create_gc_args(false)
+    };
+
+    garbage_collect_index_cli(args).await.unwrap();
+
     // If split is `MarkedForDeletion` it should be deleted after gc run
     for split_id in split_ids.iter() {
         let split_file = quickwit_common::split_file(split_id);
@@ -872,6 +1155,25 @@ async fn test_garbage_collect_index_cli() {
 
     garbage_collect_index_cli(args).await.unwrap();
 
+    // Marking the split for deletion bumps its update timestamp, so the split is only deleted
+    // once the grace period has passed again.
+    let mut metastore = refresh_metastore(metastore).await.unwrap();
+    let splits = metastore
+        .list_splits(ListSplitsRequest::try_from_index_uid(index_uid.clone()).unwrap())
+        .await
+        .unwrap()
+        .collect_splits()
+        .await
+        .unwrap();
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].split_state, SplitState::MarkedForDeletion);
+    assert_eq!(split_path.try_exists().unwrap(), true);
+
+    // Overriding the grace period avoids waiting for it to pass again.
+    let args = create_gc_args(0);
+
+    garbage_collect_index_cli(args).await.unwrap();
+
     let mut metastore = refresh_metastore(metastore).await.unwrap();
     let splits = metastore
         .list_splits(ListSplitsRequest::try_from_index_uid(index_uid).unwrap())
@@ -979,7 +1281,7 @@ async fn test_all_with_s3_localstack_cli() {
             cluster_endpoint: test_env.cluster_endpoint.clone(),
             ..Default::default()
         },
-        index_id: index_id.clone(),
+        index_ids: vec![index_id.clone()],
         query: "level:info".to_string(),
         aggregation: None,
         max_hits: 20,
@@ -988,7 +1290,19 @@ async fn test_all_with_s3_localstack_cli() {
         snippet_fields: None,
         start_timestamp: None,
         end_timestamp: None,
+        tags: None,
+        snapshot_id: None,
+        geo_bbox: None,
+        geo_distance: None,
         sort_by_score: false,
+        search_after: None,
+        search_timeout: None,
+        fuzzy_distance: None,
+        default_operator: None,
+        verbose: false,
+        explain: false,
+        output_format: OutputFormat::Json,
+        output_fields: None,
     };
 
     let search_res = search_index(args).await.unwrap();
@@ -23,6 +23,7 @@ use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context;
 use predicates::str;
@@ -156,6 +157,8 @@ impl TestEnv {
         let run_command = RunCliCommand {
             config_uri: self.config_uri.clone(),
             services: Some(QuickwitService::supported_services()),
+            drain_timeout: Duration::from_secs(1),
+            metrics_listen_addr_opt: None,
         };
         tokio::spawn(async move {
             if let Err(error) = run_command.execute().await {
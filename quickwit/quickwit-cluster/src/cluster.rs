@@ -327,6 +327,32 @@ impl Cluster {
         }
     }
 
+    /// Returns the members of the cluster known by the node, live and dead, sorted by node ID.
+    pub async fn members_info(&self) -> Vec<ClusterMemberInfo> {
+        let chitchat = self.chitchat().await;
+        let chitchat_guard = chitchat.lock().await;
+
+        let live_members = chitchat_guard.live_nodes().map(|chitchat_id| {
+            let node_state_opt = chitchat_guard.node_state(chitchat_id);
+            let state = if node_state_opt.map_or(false, |node_state| node_state.is_ready()) {
+                ClusterMemberState::Ready
+            } else {
+                ClusterMemberState::Live
+            };
+            build_cluster_member_info(chitchat_id, node_state_opt, state)
+        });
+        let dead_members = chitchat_guard.dead_nodes().map(|chitchat_id| {
+            let node_state_opt = chitchat_guard.node_state(chitchat_id);
+            build_cluster_member_info(chitchat_id, node_state_opt, ClusterMemberState::Dead)
+        });
+        live_members
+            .chain(dead_members)
+            .sorted_by(|left, right| {
+                (&left.node_id, left.generation_id).cmp(&(&right.node_id, right.generation_id))
+            })
+            .collect()
+    }
+
     /// Leaves the cluster.
     pub async fn shutdown(self) {
         info!(
@@ -608,6 +634,73 @@ pub struct ClusterSnapshot {
     pub chitchat_state_snapshot: ClusterStateSnapshot,
 }
 
+/// Liveness state of a cluster member, from the point of view of the node reporting it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ClusterMemberState {
+    /// The node is alive and ready to handle requests.
+    Ready,
+    /// The node is alive but not ready.
+    Live,
+    /// The node is flagged as dead or faulty.
+    Dead,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct ClusterMemberInfo {
+    #[schema(example = "node-1")]
+    /// The unique identifier of the node in the cluster.
+    pub node_id: String,
+
+    #[schema(example = 1683736537)]
+    /// A numeric identifier incremented every time the node leaves and rejoins the cluster.
+    pub generation_id: u64,
+
+    #[schema(example = "127.0.0.1:7280", value_type = String)]
+    /// The socket address peers should use to gossip with the node.
+    pub gossip_advertise_addr: SocketAddr,
+
+    #[schema(example = "127.0.0.1:7281", value_type = Option<String>)]
+    /// The socket address peers should use to communicate with the node via gRPC. Not set if the
+    /// node state does not hold it.
+    pub grpc_advertise_addr: Option<SocketAddr>,
+
+    #[schema(example = json!(["indexer", "searcher"]))]
+    /// The services enabled on the node.
+    pub enabled_services: Vec<String>,
+
+    /// Whether the node is ready, live but not ready, or dead.
+    pub state: ClusterMemberState,
+}
+
+fn build_cluster_member_info(
+    chitchat_id: &ChitchatId,
+    node_state_opt: Option<&NodeState>,
+    state: ClusterMemberState,
+) -> ClusterMemberInfo {
+    let grpc_advertise_addr =
+        node_state_opt.and_then(|node_state| node_state.grpc_advertise_addr().ok());
+    let enabled_services: Vec<String> = node_state_opt
+        .and_then(|node_state| node_state.get(ENABLED_SERVICES_KEY))
+        .map(|enabled_services_str| {
+            enabled_services_str
+                .split(',')
+                .filter(|service_str| !service_str.is_empty())
+                .map(ToString::to_string)
+                .sorted()
+                .collect()
+        })
+        .unwrap_or_default();
+    ClusterMemberInfo {
+        node_id: chitchat_id.node_id.clone(),
+        generation_id: chitchat_id.generation_id,
+        gossip_advertise_addr: chitchat_id.gossip_advertise_addr,
+        grpc_advertise_addr,
+        enabled_services,
+        state,
+    }
+}
+
 /// Computes the gRPC port from the listen address for tests.
 #[cfg(any(test, feature = "testsuite"))]
 pub fn grpc_addr_from_listen_addr_for_test(listen_addr: SocketAddr) -> SocketAddr {
@@ -947,6 +1040,65 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_cluster_members_info() {
+        let transport = ChannelTransport::default();
+        let node_1 =
+            create_cluster_for_test(Vec::new(), &["searcher", "indexer"], &transport, true)
+                .await
+                .unwrap();
+        let peer_seeds = vec![node_1.gossip_listen_addr.to_string()];
+        let node_2 = create_cluster_for_test(peer_seeds, &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let wait_secs = Duration::from_secs(30);
+        node_1
+            .wait_for_ready_members(|members| members.len() == 2, wait_secs)
+            .await
+            .unwrap();
+
+        let members_info = node_1.members_info().await;
+        assert_eq!(members_info.len(), 2);
+
+        let member_info_1 = members_info
+            .iter()
+            .find(|member_info| member_info.node_id == node_1.self_node_id())
+            .unwrap();
+        assert_eq!(member_info_1.generation_id, 1);
+        assert_eq!(
+            member_info_1.gossip_advertise_addr,
+            node_1.gossip_advertise_addr()
+        );
+        assert_eq!(
+            member_info_1.grpc_advertise_addr,
+            Some(grpc_addr_from_listen_addr_for_test(
+                node_1.gossip_advertise_addr()
+            ))
+        );
+        assert_eq!(member_info_1.enabled_services, ["indexer", "searcher"]);
+        assert_eq!(member_info_1.state, ClusterMemberState::Ready);
+
+        let member_info_2 = members_info
+            .iter()
+            .find(|member_info| member_info.node_id == node_2.self_node_id())
+            .unwrap();
+        assert_eq!(member_info_2.enabled_services, ["searcher"]);
+        assert_eq!(member_info_2.state, ClusterMemberState::Ready);
+
+        // The members reflect the nodes leaving the cluster.
+        let node_2_id = node_2.self_node_id().to_string();
+        node_2.shutdown().await;
+        node_1
+            .wait_for_ready_members(|members| members.len() == 1, wait_secs)
+            .await
+            .unwrap();
+        let members_info = node_1.members_info().await;
+        assert!(members_info.iter().all(|member_info| {
+            member_info.node_id != node_2_id || member_info.state == ClusterMemberState::Dead
+        }));
+        node_1.shutdown().await;
+    }
+
     #[tokio::test]
     async fn test_chitchat_state_set_high_number_of_tasks() {
         let transport = ChannelTransport::default();
@@ -38,7 +38,9 @@ pub use crate::change::ClusterChange;
 pub use crate::cluster::{
     create_cluster_for_test, create_cluster_for_test_with_id, grpc_addr_from_listen_addr_for_test,
 };
-pub use crate::cluster::{Cluster, ClusterSnapshot, NodeIdSchema};
+pub use crate::cluster::{
+    Cluster, ClusterMemberInfo, ClusterMemberState, ClusterSnapshot, NodeIdSchema,
+};
 pub use crate::member::{ClusterMember, INDEXING_CPU_CAPACITY_KEY};
 pub use crate::node::ClusterNode;
 
@@ -0,0 +1,607 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Geographic points, bounding boxes, and the filters applied on `geo_point` fields.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+/// Mean radius of the Earth, in meters.
+pub const EARTH_RADIUS_METERS: f64 = 6_371_008.8;
+
+const MAX_QUANTIZED: f64 = u32::MAX as f64;
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum GeoError {
+    #[error("latitude must be within [-90, 90], got `{0}`")]
+    InvalidLatitude(f64),
+    #[error("longitude must be within [-180, 180], got `{0}`")]
+    InvalidLongitude(f64),
+    #[error("invalid geo point `{0}`: expected `lat,lon`")]
+    InvalidFormat(String),
+    #[error(
+        "the top left corner latitude `{top}` is below the bottom right corner latitude `{bottom}`"
+    )]
+    InvertedBoundingBox { top: f64, bottom: f64 },
+    #[error("distance radius must be a positive number of meters, got `{0}`")]
+    InvalidRadius(f64),
+}
+
+/// A point on Earth, expressed in decimal degrees.
+///
+/// Geo points are validated on construction: the latitude is within [-90, 90] and the longitude
+/// within [-180, 180]. They deserialize from an object `{"lat": 48.85, "lon": 2.35}`, a GeoJSON
+/// array `[2.35, 48.85]` (longitude first), or a string `"48.85,2.35"` (latitude first), and
+/// always serialize as an object.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "GeoPointRepr")]
+pub struct GeoPoint {
+    lat: f64,
+    lon: f64,
+}
+
+// Coordinates are finite and `-0.0` is normalized to `0.0` on construction, so equality is
+// reflexive and consistent with the hash of the coordinates' bits.
+impl Eq for GeoPoint {}
+
+impl Hash for GeoPoint {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.lat.to_bits().hash(state);
+        self.lon.to_bits().hash(state);
+    }
+}
+
+impl GeoPoint {
+    pub fn new(lat: f64, lon: f64) -> Result<GeoPoint, GeoError> {
+        if !(-90.0..=90.0).contains(&lat) {
+            return Err(GeoError::InvalidLatitude(lat));
+        }
+        if !(-180.0..=180.0).contains(&lon) {
+            return Err(GeoError::InvalidLongitude(lon));
+        }
+        // Adding `0.0` turns `-0.0` into `0.0`.
+        Ok(GeoPoint {
+            lat: lat + 0.0,
+            lon: lon + 0.0,
+        })
+    }
+
+    pub fn lat(&self) -> f64 {
+        self.lat
+    }
+
+    pub fn lon(&self) -> f64 {
+        self.lon
+    }
+
+    /// Encodes the point into the `u64` stored in the fast field of `geo_point` fields.
+    ///
+    /// The latitude is quantized into the upper 32 bits and the longitude into the lower 32 bits.
+    /// A single column keeps both coordinates of a document together, so filters read one value
+    /// per document. The quantization step is about 4.2e-8 degrees, i.e. less than 5mm at the
+    /// equator.
+    pub fn to_u64(&self) -> u64 {
+        let lat_quantized = ((self.lat + 90.0) / 180.0 * MAX_QUANTIZED).round() as u64;
+        let lon_quantized = ((self.lon + 180.0) / 360.0 * MAX_QUANTIZED).round() as u64;
+        (lat_quantized << 32) | lon_quantized
+    }
+
+    /// Decodes a point encoded with [`GeoPoint::to_u64`].
+    pub fn from_u64(encoded: u64) -> GeoPoint {
+        let lat_quantized = (encoded >> 32) as f64;
+        let lon_quantized = (encoded & u32::MAX as u64) as f64;
+        let lat = (lat_quantized / MAX_QUANTIZED * 180.0 - 90.0).clamp(-90.0, 90.0);
+        let lon = (lon_quantized / MAX_QUANTIZED * 360.0 - 180.0).clamp(-180.0, 180.0);
+        GeoPoint {
+            lat: lat + 0.0,
+            lon: lon + 0.0,
+        }
+    }
+
+    /// Returns the great-circle distance between two points, computed with the haversine
+    /// formula.
+    pub fn haversine_distance_meters(&self, other: &GeoPoint) -> f64 {
+        let lat_rad = self.lat.to_radians();
+        let other_lat_rad = other.lat.to_radians();
+        let half_delta_lat = (other_lat_rad - lat_rad) / 2.0;
+        let half_delta_lon = (other.lon - self.lon).to_radians() / 2.0;
+        let a = half_delta_lat.sin().powi(2)
+            + lat_rad.cos() * other_lat_rad.cos() * half_delta_lon.sin().powi(2);
+        // Rounding errors can push `a` slightly above 1 for antipodal points.
+        2.0 * EARTH_RADIUS_METERS * a.sqrt().min(1.0).asin()
+    }
+}
+
+impl fmt::Display for GeoPoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{},{}", self.lat, self.lon)
+    }
+}
+
+impl FromStr for GeoPoint {
+    type Err = GeoError;
+
+    /// Parses a `lat,lon` string.
+    fn from_str(geo_point_str: &str) -> Result<Self, Self::Err> {
+        let invalid_format = || GeoError::InvalidFormat(geo_point_str.to_string());
+        let (lat_str, lon_str) = geo_point_str.split_once(',').ok_or_else(invalid_format)?;
+        let lat: f64 = lat_str.trim().parse().map_err(|_| invalid_format())?;
+        let lon: f64 = lon_str.trim().parse().map_err(|_| invalid_format())?;
+        GeoPoint::new(lat, lon)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum GeoPointRepr {
+    // GeoJSON order: longitude first. This variant must come before `Object` because a derived
+    // struct also deserializes from a sequence.
+    Array([f64; 2]),
+    Object { lat: f64, lon: f64 },
+    String(String),
+}
+
+impl TryFrom<GeoPointRepr> for GeoPoint {
+    type Error = GeoError;
+
+    fn try_from(repr: GeoPointRepr) -> Result<Self, Self::Error> {
+        match repr {
+            GeoPointRepr::Object { lat, lon } => GeoPoint::new(lat, lon),
+            GeoPointRepr::Array([lon, lat]) => GeoPoint::new(lat, lon),
+            GeoPointRepr::String(geo_point_str) => geo_point_str.parse(),
+        }
+    }
+}
+
+/// An area delimited by two parallels and two meridians.
+///
+/// When the left longitude is greater than the right longitude, the box crosses the antimeridian:
+/// it spans from the left longitude eastward to 180, then from -180 to the right longitude.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct GeoBoundingBox {
+    pub top_left: GeoPoint,
+    pub bottom_right: GeoPoint,
+}
+
+impl GeoBoundingBox {
+    pub fn new(top_left: GeoPoint, bottom_right: GeoPoint) -> Result<GeoBoundingBox, GeoError> {
+        if top_left.lat < bottom_right.lat {
+            return Err(GeoError::InvertedBoundingBox {
+                top: top_left.lat,
+                bottom: bottom_right.lat,
+            });
+        }
+        Ok(GeoBoundingBox {
+            top_left,
+            bottom_right,
+        })
+    }
+
+    /// Returns the bounding box containing only `point`.
+    pub fn from_point(point: GeoPoint) -> GeoBoundingBox {
+        GeoBoundingBox {
+            top_left: point,
+            bottom_right: point,
+        }
+    }
+
+    pub fn top(&self) -> f64 {
+        self.top_left.lat
+    }
+
+    pub fn bottom(&self) -> f64 {
+        self.bottom_right.lat
+    }
+
+    pub fn left(&self) -> f64 {
+        self.top_left.lon
+    }
+
+    pub fn right(&self) -> f64 {
+        self.bottom_right.lon
+    }
+
+    pub fn crosses_antimeridian(&self) -> bool {
+        self.left() > self.right()
+    }
+
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        if point.lat < self.bottom() || point.lat > self.top() {
+            return false;
+        }
+        if self.crosses_antimeridian() {
+            point.lon >= self.left() || point.lon <= self.right()
+        } else {
+            point.lon >= self.left() && point.lon <= self.right()
+        }
+    }
+
+    pub fn intersects(&self, other: &GeoBoundingBox) -> bool {
+        if self.bottom() > other.top() || other.bottom() > self.top() {
+            return false;
+        }
+        self.lon_ranges().iter().any(|&(left, right)| {
+            other
+                .lon_ranges()
+                .iter()
+                .any(|&(other_left, other_right)| left <= other_right && other_left <= right)
+        })
+    }
+
+    /// Grows the box so that it contains `point`, without ever crossing the antimeridian.
+    ///
+    /// This is meant for boxes computed from the points of a split, which never cross the
+    /// antimeridian.
+    pub fn extend(&mut self, point: &GeoPoint) {
+        self.top_left.lat = self.top_left.lat.max(point.lat);
+        self.top_left.lon = self.top_left.lon.min(point.lon);
+        self.bottom_right.lat = self.bottom_right.lat.min(point.lat);
+        self.bottom_right.lon = self.bottom_right.lon.max(point.lon);
+    }
+
+    /// Splits the longitude span into non-wrapping `(left, right)` ranges.
+    fn lon_ranges(&self) -> Vec<(f64, f64)> {
+        if self.crosses_antimeridian() {
+            vec![(self.left(), 180.0), (-180.0, self.right())]
+        } else {
+            vec![(self.left(), self.right())]
+        }
+    }
+}
+
+/// Filter retaining the documents with a point within a bounding box.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeoBoundingBoxFilter {
+    pub field: String,
+    pub top_left: GeoPoint,
+    pub bottom_right: GeoPoint,
+}
+
+impl GeoBoundingBoxFilter {
+    pub fn bounding_box(&self) -> Result<GeoBoundingBox, GeoError> {
+        GeoBoundingBox::new(self.top_left, self.bottom_right)
+    }
+}
+
+/// Filter retaining the documents with a point within `radius_m` meters of `center`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeoDistanceFilter {
+    pub field: String,
+    pub center: GeoPoint,
+    pub radius_m: f64,
+}
+
+// The radius is validated to be a finite number by `GeoDistanceFilter::bounding_box`, and JSON
+// cannot represent NaN.
+impl Eq for GeoDistanceFilter {}
+
+impl GeoDistanceFilter {
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        self.center.haversine_distance_meters(point) <= self.radius_m
+    }
+
+    /// Returns the smallest bounding box containing the circle.
+    pub fn bounding_box(&self) -> Result<GeoBoundingBox, GeoError> {
+        if !self.radius_m.is_finite() || self.radius_m < 0.0 {
+            return Err(GeoError::InvalidRadius(self.radius_m));
+        }
+        let angular_radius = self.radius_m / EARTH_RADIUS_METERS;
+        let delta_lat = angular_radius.to_degrees();
+        let top = self.center.lat + delta_lat;
+        let bottom = self.center.lat - delta_lat;
+        // When the circle contains a pole, it spans all the meridians.
+        if top >= 90.0 || bottom <= -90.0 {
+            return GeoBoundingBox::new(
+                GeoPoint::new(top.min(90.0), -180.0)?,
+                GeoPoint::new(bottom.max(-90.0), 180.0)?,
+            );
+        }
+        let sin_delta_lon = angular_radius.sin() / self.center.lat.to_radians().cos();
+        if sin_delta_lon >= 1.0 {
+            return GeoBoundingBox::new(GeoPoint::new(top, -180.0)?, GeoPoint::new(bottom, 180.0)?);
+        }
+        let delta_lon = sin_delta_lon.asin().to_degrees();
+        GeoBoundingBox::new(
+            GeoPoint::new(top, wrap_longitude(self.center.lon - delta_lon))?,
+            GeoPoint::new(bottom, wrap_longitude(self.center.lon + delta_lon))?,
+        )
+    }
+}
+
+fn wrap_longitude(lon: f64) -> f64 {
+    if lon < -180.0 {
+        lon + 360.0
+    } else if lon > 180.0 {
+        lon - 360.0
+    } else {
+        lon
+    }
+}
+
+/// A filter on a `geo_point` field, applied on the fast field at collection time.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeoFilter {
+    GeoBbox(GeoBoundingBoxFilter),
+    GeoDistance(GeoDistanceFilter),
+}
+
+impl GeoFilter {
+    pub fn field(&self) -> &str {
+        match self {
+            GeoFilter::GeoBbox(bbox_filter) => &bbox_filter.field,
+            GeoFilter::GeoDistance(distance_filter) => &distance_filter.field,
+        }
+    }
+
+    /// Returns a bounding box containing all the points matching the filter. It also validates
+    /// the filter.
+    pub fn bounding_box(&self) -> Result<GeoBoundingBox, GeoError> {
+        match self {
+            GeoFilter::GeoBbox(bbox_filter) => bbox_filter.bounding_box(),
+            GeoFilter::GeoDistance(distance_filter) => distance_filter.bounding_box(),
+        }
+    }
+
+    pub fn contains(&self, point: &GeoPoint) -> bool {
+        match self {
+            GeoFilter::GeoBbox(bbox_filter) => GeoBoundingBox {
+                top_left: bbox_filter.top_left,
+                bottom_right: bbox_filter.bottom_right,
+            }
+            .contains(point),
+            GeoFilter::GeoDistance(distance_filter) => distance_filter.contains(point),
+        }
+    }
+}
+
+impl From<GeoBoundingBoxFilter> for GeoFilter {
+    fn from(bbox_filter: GeoBoundingBoxFilter) -> Self {
+        GeoFilter::GeoBbox(bbox_filter)
+    }
+}
+
+impl From<GeoDistanceFilter> for GeoFilter {
+    fn from(distance_filter: GeoDistanceFilter) -> Self {
+        GeoFilter::GeoDistance(distance_filter)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn point(lat: f64, lon: f64) -> GeoPoint {
+        GeoPoint::new(lat, lon).unwrap()
+    }
+
+    fn bbox(top: f64, left: f64, bottom: f64, right: f64) -> GeoBoundingBox {
+        GeoBoundingBox::new(point(top, left), point(bottom, right)).unwrap()
+    }
+
+    #[test]
+    fn test_geo_point_deserialize_formats() {
+        let expected = point(48.85, 2.35);
+        let from_object: GeoPoint =
+            serde_json::from_value(json!({"lat": 48.85, "lon": 2.35})).unwrap();
+        assert_eq!(from_object, expected);
+        let from_array: GeoPoint = serde_json::from_value(json!([2.35, 48.85])).unwrap();
+        assert_eq!(from_array, expected);
+        let from_string: GeoPoint = serde_json::from_value(json!("48.85, 2.35")).unwrap();
+        assert_eq!(from_string, expected);
+
+        assert_eq!(
+            serde_json::to_value(expected).unwrap(),
+            json!({"lat": 48.85, "lon": 2.35})
+        );
+    }
+
+    #[test]
+    fn test_geo_point_deserialize_invalid() {
+        for invalid_json in [
+            json!({"lat": 91.0, "lon": 0.0}),
+            json!({"lat": 0.0, "lon": -180.5}),
+            json!([0.0, 91.0]),
+            json!([1.0, 2.0, 3.0]),
+            json!("48.85"),
+            json!("north,east"),
+            json!({"lat": 48.85}),
+            json!(true),
+        ] {
+            serde_json::from_value::<GeoPoint>(invalid_json.clone())
+                .expect_err(&format!("`{invalid_json}` should be invalid"));
+        }
+    }
+
+    #[test]
+    fn test_geo_point_u64_roundtrip() {
+        for geo_point in [
+            point(0.0, 0.0),
+            point(90.0, 180.0),
+            point(-90.0, -180.0),
+            point(48.858_37, 2.294_481),
+            point(-33.856_784, 151.215_297),
+        ] {
+            let decoded = GeoPoint::from_u64(geo_point.to_u64());
+            assert!((decoded.lat() - geo_point.lat()).abs() < 1e-7);
+            assert!((decoded.lon() - geo_point.lon()).abs() < 1e-7);
+            assert!(geo_point.haversine_distance_meters(&decoded) < 0.01);
+        }
+        // Encoding preserves the latitude order.
+        assert!(point(10.0, 179.0).to_u64() < point(10.1, -179.0).to_u64());
+    }
+
+    #[test]
+    fn test_haversine_distance() {
+        let paris = point(48.8566, 2.3522);
+        let london = point(51.5074, -0.1278);
+        let distance = paris.haversine_distance_meters(&london);
+        assert!((distance - 343_560.0).abs() < 1_000.0, "{distance}");
+        assert_eq!(paris.haversine_distance_meters(&paris), 0.0);
+
+        // All the meridians meet at the poles.
+        let north_pole = point(90.0, 0.0);
+        assert!(north_pole.haversine_distance_meters(&point(90.0, 120.0)) < 1e-6);
+        let near_pole_1 = point(89.9999, 0.0);
+        let near_pole_2 = point(89.9999, 180.0);
+        let distance = near_pole_1.haversine_distance_meters(&near_pole_2);
+        assert!((distance - 22.24).abs() < 0.01, "{distance}");
+
+        // Antipodal points.
+        let distance = point(0.0, 0.0).haversine_distance_meters(&point(0.0, 180.0));
+        assert!((distance - std::f64::consts::PI * EARTH_RADIUS_METERS).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_bounding_box_contains() {
+        let europe = bbox(60.0, -10.0, 35.0, 30.0);
+        assert!(!europe.crosses_antimeridian());
+        assert!(europe.contains(&point(48.85, 2.35)));
+        assert!(europe.contains(&point(60.0, -10.0)));
+        assert!(!europe.contains(&point(61.0, 2.35)));
+        assert!(!europe.contains(&point(48.85, 31.0)));
+
+        GeoBoundingBox::new(point(10.0, 0.0), point(20.0, 0.0)).unwrap_err();
+    }
+
+    #[test]
+    fn test_bounding_box_antimeridian() {
+        // Fiji spans across the antimeridian.
+        let fiji = bbox(-15.0, 176.0, -20.0, -178.0);
+        assert!(fiji.crosses_antimeridian());
+        assert!(fiji.contains(&point(-17.7, 178.0)));
+        assert!(fiji.contains(&point(-17.7, 180.0)));
+        assert!(fiji.contains(&point(-17.7, -180.0)));
+        assert!(fiji.contains(&point(-16.5, -179.0)));
+        assert!(!fiji.contains(&point(-17.7, 0.0)));
+        assert!(!fiji.contains(&point(-17.7, 175.0)));
+        assert!(!fiji.contains(&point(-17.7, -177.0)));
+
+        assert!(fiji.intersects(&bbox(-16.0, 177.0, -17.0, 178.0)));
+        assert!(fiji.intersects(&bbox(-16.0, -179.5, -17.0, -179.0)));
+        assert!(!fiji.intersects(&bbox(-16.0, -170.0, -17.0, 170.0)));
+        assert!(!fiji.intersects(&bbox(-21.0, 177.0, -22.0, 178.0)));
+        assert!(fiji.intersects(&bbox(0.0, 100.0, -30.0, -100.0)));
+    }
+
+    #[test]
+    fn test_bounding_box_extend() {
+        let mut split_bbox = GeoBoundingBox::from_point(point(10.0, 20.0));
+        split_bbox.extend(&point(-5.0, 25.0));
+        split_bbox.extend(&point(3.0, -170.0));
+        assert_eq!(split_bbox, bbox(10.0, -170.0, -5.0, 25.0));
+    }
+
+    #[test]
+    fn test_distance_filter_bounding_box() {
+        let distance_filter = GeoDistanceFilter {
+            field: "location".to_string(),
+            center: point(0.0, 0.0),
+            radius_m: 111_195.0,
+        };
+        let circle_bbox = distance_filter.bounding_box().unwrap();
+        assert!((circle_bbox.top() - 1.0).abs() < 1e-3);
+        assert!((circle_bbox.bottom() + 1.0).abs() < 1e-3);
+        assert!((circle_bbox.left() + 1.0).abs() < 1e-3);
+        assert!((circle_bbox.right() - 1.0).abs() < 1e-3);
+
+        // The circle wraps around the antimeridian.
+        let distance_filter = GeoDistanceFilter {
+            field: "location".to_string(),
+            center: point(0.0, 179.5),
+            radius_m: 111_195.0,
+        };
+        let circle_bbox = distance_filter.bounding_box().unwrap();
+        assert!(circle_bbox.crosses_antimeridian());
+        assert!(circle_bbox.contains(&point(0.0, -179.6)));
+        assert!(distance_filter.contains(&point(0.0, -179.6)));
+        assert!(!distance_filter.contains(&point(0.0, -179.4)));
+
+        // The circle contains the north pole.
+        let distance_filter = GeoDistanceFilter {
+            field: "location".to_string(),
+            center: point(89.5, 10.0),
+            radius_m: 100_000.0,
+        };
+        let circle_bbox = distance_filter.bounding_box().unwrap();
+        assert_eq!(circle_bbox.top(), 90.0);
+        assert_eq!(circle_bbox.left(), -180.0);
+        assert_eq!(circle_bbox.right(), 180.0);
+        assert!(distance_filter.contains(&point(89.8, -170.0)));
+        assert!(circle_bbox.contains(&point(89.8, -170.0)));
+        assert!(!distance_filter.contains(&point(88.0, -170.0)));
+
+        // Near the pole, one degree of longitude is much shorter than at the equator.
+        let distance_filter = GeoDistanceFilter {
+            field: "location".to_string(),
+            center: point(85.0, 0.0),
+            radius_m: 50_000.0,
+        };
+        let circle_bbox = distance_filter.bounding_box().unwrap();
+        assert!(circle_bbox.right() > 5.0);
+        assert!(distance_filter.contains(&point(85.0, 5.0)));
+        assert!(circle_bbox.contains(&point(85.0, 5.0)));
+
+        let distance_filter = GeoDistanceFilter {
+            field: "location".to_string(),
+            center: point(0.0, 0.0),
+            radius_m: -1.0,
+        };
+        distance_filter.bounding_box().unwrap_err();
+    }
+
+    #[test]
+    fn test_geo_filter_serde() {
+        let geo_filter: GeoFilter = serde_json::from_value(json!({
+            "geo_bbox": {
+                "field": "location",
+                "top_left": {"lat": 10.0, "lon": 170.0},
+                "bottom_right": "-10.0,-170.0",
+            }
+        }))
+        .unwrap();
+        assert_eq!(geo_filter.field(), "location");
+        assert!(geo_filter.contains(&point(0.0, 180.0)));
+        assert!(!geo_filter.contains(&point(0.0, 0.0)));
+
+        let geo_filter: GeoFilter = serde_json::from_value(json!({
+            "geo_distance": {
+                "field": "location",
+                "center": [2.35, 48.85],
+                "radius_m": 1000.0,
+            }
+        }))
+        .unwrap();
+        let GeoFilter::GeoDistance(distance_filter) = &geo_filter else {
+            panic!("expected a distance filter, got `{geo_filter:?}`");
+        };
+        assert_eq!(distance_filter.center, point(48.85, 2.35));
+
+        let geo_filter_json = serde_json::to_string(&geo_filter).unwrap();
+        let geo_filter_deser: GeoFilter = serde_json::from_str(&geo_filter_json).unwrap();
+        assert_eq!(geo_filter_deser, geo_filter);
+    }
+}
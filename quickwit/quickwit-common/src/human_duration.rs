@@ -0,0 +1,304 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+/// A duration expressed in a human-friendly way: `500ms`, `90s`, `15m`, `2h30m`, `3 days`, ...
+///
+/// For backward compatibility with the settings that used to be expressed as an integer number of
+/// seconds, plain integers are also accepted and interpreted as seconds.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct HumanDuration(Duration);
+
+impl HumanDuration {
+    pub const fn new(duration: Duration) -> Self {
+        Self(duration)
+    }
+
+    pub const fn from_secs(secs: u64) -> Self {
+        Self(Duration::from_secs(secs))
+    }
+
+    pub const fn as_duration(&self) -> Duration {
+        self.0
+    }
+}
+
+impl From<Duration> for HumanDuration {
+    fn from(duration: Duration) -> Self {
+        Self(duration)
+    }
+}
+
+impl From<HumanDuration> for Duration {
+    fn from(human_duration: HumanDuration) -> Self {
+        human_duration.0
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, thiserror::Error)]
+#[error(
+    "failed to parse duration `{input}`: {reason}. expected a plain number of seconds or a number \
+     followed by a unit among `ns`, `us`, `ms`, `s`, `m`, `h`, `d`, `w`, `M`, and `y`, for \
+     instance `90s`, `15m`, or `2h30m`"
+)]
+pub struct HumanDurationParseError {
+    input: String,
+    reason: String,
+}
+
+impl FromStr for HumanDuration {
+    type Err = HumanDurationParseError;
+
+    fn from_str(duration_str: &str) -> Result<Self, Self::Err> {
+        let trimmed_duration_str = duration_str.trim();
+        let parse_error = |reason: String| HumanDurationParseError {
+            input: duration_str.to_string(),
+            reason,
+        };
+        if !trimmed_duration_str.is_empty()
+            && trimmed_duration_str
+                .bytes()
+                .all(|byte| byte.is_ascii_digit())
+        {
+            let secs = trimmed_duration_str
+                .parse::<u64>()
+                .map_err(|error| parse_error(error.to_string()))?;
+            return Ok(Self::from_secs(secs));
+        }
+        humantime::parse_duration(trimmed_duration_str)
+            .map(Self)
+            .map_err(|error| parse_error(error.to_string()))
+    }
+}
+
+impl fmt::Display for HumanDuration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", humantime::format_duration(self.0))
+    }
+}
+
+impl Serialize for HumanDuration {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where S: Serializer {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for HumanDuration {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        struct HumanDurationVisitor;
+
+        impl<'de> de::Visitor<'de> for HumanDurationVisitor {
+            type Value = HumanDuration;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str(
+                    "a human-friendly duration such as `90s` or `2h30m`, or a number of seconds",
+                )
+            }
+
+            fn visit_u64<E>(self, secs: u64) -> Result<Self::Value, E>
+            where E: de::Error {
+                Ok(HumanDuration::from_secs(secs))
+            }
+
+            fn visit_i64<E>(self, secs: i64) -> Result<Self::Value, E>
+            where E: de::Error {
+                u64::try_from(secs)
+                    .map(HumanDuration::from_secs)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Signed(secs), &self))
+            }
+
+            fn visit_str<E>(self, duration_str: &str) -> Result<Self::Value, E>
+            where E: de::Error {
+                duration_str.parse().map_err(E::custom)
+            }
+        }
+        deserializer.deserialize_any(HumanDurationVisitor)
+    }
+}
+
+/// Serializes a duration as an integer number of seconds, for the settings whose persisted format
+/// predates [`HumanDuration`]. Sub-second remainders are rounded up to the next second.
+pub fn serialize_duration_as_secs<S>(
+    duration: &HumanDuration,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_u64(duration_as_secs_ceil(duration.0))
+}
+
+/// Same as [`serialize_duration_as_secs`] for optional settings.
+pub fn serialize_opt_duration_as_secs<S>(
+    duration_opt: &Option<HumanDuration>,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match duration_opt {
+        Some(duration) => serializer.serialize_some(&duration_as_secs_ceil(duration.0)),
+        None => serializer.serialize_none(),
+    }
+}
+
+fn duration_as_secs_ceil(duration: Duration) -> u64 {
+    if duration.subsec_nanos() > 0 {
+        duration.as_secs() + 1
+    } else {
+        duration.as_secs()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_human_duration_from_str() {
+        let test_cases = [
+            ("0", Duration::ZERO),
+            ("42", Duration::from_secs(42)),
+            (" 30 ", Duration::from_secs(30)),
+            ("0s", Duration::ZERO),
+            ("500ms", Duration::from_millis(500)),
+            ("90s", Duration::from_secs(90)),
+            ("15m", Duration::from_secs(15 * 60)),
+            ("2h", Duration::from_secs(2 * 3600)),
+            ("2h30m", Duration::from_secs(2 * 3600 + 30 * 60)),
+            ("2h 30m 15s", Duration::from_secs(2 * 3600 + 30 * 60 + 15)),
+            ("1d", Duration::from_secs(24 * 3600)),
+            ("3 days", Duration::from_secs(3 * 24 * 3600)),
+            ("1 hour", Duration::from_secs(3600)),
+            ("2w", Duration::from_secs(2 * 7 * 24 * 3600)),
+            ("10us", Duration::from_micros(10)),
+            ("7ns", Duration::from_nanos(7)),
+        ];
+        for (duration_str, expected_duration) in test_cases {
+            let human_duration = HumanDuration::from_str(duration_str).unwrap();
+            assert_eq!(
+                human_duration.as_duration(),
+                expected_duration,
+                "failed to parse `{duration_str}`"
+            );
+        }
+    }
+
+    #[test]
+    fn test_human_duration_from_str_errors() {
+        for duration_str in [
+            "",
+            "  ",
+            "s",
+            "-1",
+            "-5s",
+            "1.5h",
+            "10 parsecs",
+            "h2",
+            "1e3",
+        ] {
+            let error = HumanDuration::from_str(duration_str).unwrap_err();
+            let error_message = error.to_string();
+            assert!(
+                error_message.starts_with(&format!("failed to parse duration `{duration_str}`")),
+                "unexpected error message for `{duration_str}`: {error_message}"
+            );
+            assert!(error_message.contains("`90s`, `15m`, or `2h30m`"));
+        }
+        // The number of seconds overflows `u64`.
+        HumanDuration::from_str("99999999999999999999").unwrap_err();
+    }
+
+    #[test]
+    fn test_human_duration_display_round_trip() {
+        let test_cases = [
+            (Duration::ZERO, "0s"),
+            (Duration::from_millis(500), "500ms"),
+            (Duration::from_secs(90), "1m 30s"),
+            (Duration::from_secs(2 * 3600 + 30 * 60), "2h 30m"),
+            (Duration::from_secs(48 * 3600), "2days"),
+        ];
+        for (duration, expected_display) in test_cases {
+            let human_duration = HumanDuration::new(duration);
+            let display = human_duration.to_string();
+            assert_eq!(display, expected_display);
+            assert_eq!(display.parse::<HumanDuration>().unwrap(), human_duration);
+        }
+    }
+
+    #[test]
+    fn test_human_duration_serde() {
+        let human_duration: HumanDuration = serde_json::from_str(r#""2h30m""#).unwrap();
+        assert_eq!(human_duration, HumanDuration::from_secs(2 * 3600 + 30 * 60));
+
+        let human_duration: HumanDuration = serde_json::from_str("90").unwrap();
+        assert_eq!(human_duration, HumanDuration::from_secs(90));
+
+        let human_duration: HumanDuration = serde_json::from_str(r#""90""#).unwrap();
+        assert_eq!(human_duration, HumanDuration::from_secs(90));
+
+        let error = serde_json::from_str::<HumanDuration>("-1").unwrap_err();
+        assert!(error.to_string().contains("a number of seconds"));
+
+        let error = serde_json::from_str::<HumanDuration>(r#""forever""#).unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("failed to parse duration `forever`"));
+
+        let serialized = serde_json::to_string(&HumanDuration::from_secs(90)).unwrap();
+        assert_eq!(serialized, r#""1m 30s""#);
+        let deserialized: HumanDuration = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(deserialized, HumanDuration::from_secs(90));
+    }
+
+    #[test]
+    fn test_serialize_duration_as_secs() {
+        #[derive(Serialize)]
+        struct Settings {
+            #[serde(serialize_with = "serialize_duration_as_secs")]
+            timeout: HumanDuration,
+            #[serde(serialize_with = "serialize_opt_duration_as_secs")]
+            period_opt: Option<HumanDuration>,
+        }
+        let settings = Settings {
+            timeout: HumanDuration::from_secs(301),
+            period_opt: None,
+        };
+        assert_eq!(
+            serde_json::to_string(&settings).unwrap(),
+            r#"{"timeout":301,"period_opt":null}"#
+        );
+        let settings = Settings {
+            timeout: HumanDuration::new(Duration::from_millis(1_500)),
+            period_opt: Some(HumanDuration::from_secs(3600)),
+        };
+        assert_eq!(
+            serde_json::to_string(&settings).unwrap(),
+            r#"{"timeout":2,"period_opt":3600}"#
+        );
+    }
+}
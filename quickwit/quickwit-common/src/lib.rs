@@ -23,6 +23,8 @@ mod coolid;
 
 pub mod binary_heap;
 pub mod fs;
+pub mod geo;
+mod human_duration;
 pub mod io;
 mod kill_switch;
 pub mod metrics;
@@ -31,9 +33,11 @@ mod path_hasher;
 mod progress;
 pub mod pubsub;
 pub mod rand;
+pub mod rate_limited_tracing;
 pub mod rate_limiter;
 pub mod rendezvous_hasher;
 pub mod retry;
+pub mod retry_budget;
 pub mod runtimes;
 pub mod shared_consts;
 pub mod sorted_iter;
@@ -44,6 +48,7 @@ pub mod test_utils;
 pub mod tower;
 pub mod type_map;
 pub mod uri;
+mod validation_errors;
 
 use std::env;
 use std::fmt::{Debug, Display};
@@ -51,11 +56,16 @@ use std::ops::{Range, RangeInclusive};
 use std::str::FromStr;
 
 pub use coolid::new_coolid;
+pub use human_duration::{
+    serialize_duration_as_secs, serialize_opt_duration_as_secs, HumanDuration,
+    HumanDurationParseError,
+};
 pub use kill_switch::KillSwitch;
 pub use path_hasher::PathHasher;
 pub use progress::{Progress, ProtectedZoneGuard};
 pub use stream_utils::{BoxStream, ServiceStream};
 use tracing::{error, info};
+pub use validation_errors::ValidationErrors;
 
 pub fn chunk_range(range: Range<usize>, chunk_size: usize) -> impl Iterator<Item = Range<usize>> {
     range.clone().step_by(chunk_size).map(move |block_start| {
@@ -156,6 +156,22 @@ impl Drop for GaugeGuard {
     }
 }
 
+/// Same as [`GaugeGuard`] for gauges that are not `'static`, such as the gauges of a gauge vec.
+pub struct OwnedGaugeGuard(IntGauge);
+
+impl OwnedGaugeGuard {
+    pub fn from_gauge(gauge: IntGauge) -> Self {
+        gauge.inc();
+        Self(gauge)
+    }
+}
+
+impl Drop for OwnedGaugeGuard {
+    fn drop(&mut self) {
+        self.0.dec();
+    }
+}
+
 pub fn metrics_text_payload() -> String {
     let metric_families = prometheus::gather();
     let mut buffer = Vec::new();
@@ -0,0 +1,102 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Logs an event through `tracing` at most `limit_per_min` times per minute for a given
+/// callsite. Events above the limit are dropped.
+#[macro_export]
+macro_rules! rate_limited_tracing {
+    ($log_macro:ident, limit_per_min = $limit:literal, $($args:tt)*) => {{
+        static CALLSITE_STATE: ::std::sync::Mutex<Option<(::std::time::Instant, u32)>> =
+            ::std::sync::Mutex::new(None);
+        if $crate::rate_limited_tracing::should_log(&CALLSITE_STATE, $limit) {
+            ::tracing::$log_macro!($($args)*);
+        }
+    }};
+}
+
+/// Rate-limited version of `tracing::warn!`.
+#[macro_export]
+macro_rules! rate_limited_warn {
+    (limit_per_min = $limit:literal, $($args:tt)*) => {
+        $crate::rate_limited_tracing!(warn, limit_per_min = $limit, $($args)*)
+    };
+}
+
+#[doc(hidden)]
+pub fn should_log(callsite_state: &Mutex<Option<(Instant, u32)>>, limit_per_min: u32) -> bool {
+    should_log_at(callsite_state, limit_per_min, Instant::now())
+}
+
+fn should_log_at(
+    callsite_state: &Mutex<Option<(Instant, u32)>>,
+    limit_per_min: u32,
+    now: Instant,
+) -> bool {
+    let mut callsite_state_guard = callsite_state.lock().unwrap();
+    match callsite_state_guard.as_mut() {
+        Some((window_start, count)) if now.duration_since(*window_start) < RATE_LIMIT_WINDOW => {
+            if *count >= limit_per_min {
+                return false;
+            }
+            *count += 1;
+            true
+        }
+        _ => {
+            *callsite_state_guard = Some((now, 1));
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_should_log_at() {
+        let callsite_state = Mutex::new(None);
+        let now = Instant::now();
+
+        assert!(should_log_at(&callsite_state, 2, now));
+        assert!(should_log_at(&callsite_state, 2, now));
+        assert!(!should_log_at(&callsite_state, 2, now));
+        assert!(!should_log_at(
+            &callsite_state,
+            2,
+            now + Duration::from_secs(59)
+        ));
+        assert!(should_log_at(
+            &callsite_state,
+            2,
+            now + Duration::from_secs(60)
+        ));
+    }
+
+    #[test]
+    fn test_rate_limited_warn() {
+        for _ in 0..3 {
+            rate_limited_warn!(limit_per_min = 2, "rate limited warning");
+        }
+    }
+}
@@ -25,6 +25,8 @@ use futures::Future;
 use rand::Rng;
 use tracing::{debug, warn};
 
+use crate::retry_budget::{global_retry_budget, RetryBudget, RetryLayer, RETRY_BUDGET_EXHAUSTED};
+
 const DEFAULT_MAX_ATTEMPTS: usize = 30;
 const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(250);
 const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(20);
@@ -127,6 +129,19 @@ pub async fn retry_with_mockable_sleep<U, E, Fut>(
     f: impl Fn() -> Fut,
     mockable_sleep: impl MockableSleep,
 ) -> Result<U, E>
+where
+    Fut: Future<Output = Result<U, E>>,
+    E: Retryable + Debug + 'static,
+{
+    retry_with_budget(retry_params, f, mockable_sleep, &global_retry_budget()).await
+}
+
+async fn retry_with_budget<U, E, Fut>(
+    retry_params: &RetryParams,
+    f: impl Fn() -> Fut,
+    mockable_sleep: impl MockableSleep,
+    retry_budget: &RetryBudget,
+) -> Result<U, E>
 where
     Fut: Future<Output = Result<U, E>>,
     E: Retryable + Debug + 'static,
@@ -138,6 +153,9 @@ where
 
         let error = match response {
             Ok(response) => {
+                if num_attempts == 0 {
+                    retry_budget.record_primary_success();
+                }
                 return Ok(response);
             }
             Err(error) => error,
@@ -154,6 +172,14 @@ where
             );
             return Err(error);
         }
+        if !retry_budget.try_acquire_retry(RetryLayer::Storage) {
+            warn!(
+                num_attempts=%num_attempts,
+                error=?error,
+                "request failed, not retrying: {RETRY_BUDGET_EXHAUSTED}"
+            );
+            return Err(error);
+        }
         let delay = retry_params.compute_delay(num_attempts);
         debug!(
             num_attempts=%num_attempts,
@@ -175,12 +201,16 @@ where
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::sync::RwLock;
     use std::time::Duration;
 
     use futures::future::ready;
 
-    use super::{retry_with_mockable_sleep, MockableSleep, RetryParams, Retryable};
+    use super::{
+        retry_with_budget, retry_with_mockable_sleep, MockableSleep, RetryParams, Retryable,
+    };
+    use crate::retry_budget::RetryBudget;
 
     #[derive(Debug, Eq, PartialEq)]
     pub enum Retry<E> {
@@ -258,4 +288,81 @@ mod tests {
             .collect();
         assert_eq!(simulate_retries(retry_sequence).await, Ok(()));
     }
+
+    /// Sends `num_requests` requests to a backend failing the request attempts for which
+    /// `fails(attempt_id)` is true. Returns the number of successful requests and the total
+    /// number of attempts received by the backend.
+    async fn simulate_backend(
+        num_requests: usize,
+        fails: impl Fn(usize) -> bool,
+        retry_budget: &RetryBudget,
+    ) -> (usize, usize) {
+        let retry_params = RetryParams {
+            max_attempts: 3,
+            ..RetryParams::for_test()
+        };
+        let num_attempts = AtomicUsize::new(0);
+        let mut num_successes = 0;
+
+        for _ in 0..num_requests {
+            let result = retry_with_budget(
+                &retry_params,
+                || {
+                    let attempt_id = num_attempts.fetch_add(1, Ordering::Relaxed);
+                    if fails(attempt_id) {
+                        ready(Err(Retry::Transient(attempt_id)))
+                    } else {
+                        ready(Ok(()))
+                    }
+                },
+                NoopSleep,
+                retry_budget,
+            )
+            .await;
+            if result.is_ok() {
+                num_successes += 1;
+            }
+        }
+        (num_successes, num_attempts.load(Ordering::Relaxed))
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget_bounds_amplification_on_high_failure_rate() {
+        // Without budget, each request is attempted `max_attempts` times.
+        let (num_successes, num_attempts) =
+            simulate_backend(1_000, |_| true, &RetryBudget::unlimited()).await;
+        assert_eq!(num_successes, 0);
+        assert_eq!(num_attempts, 3_000);
+
+        // With a budget of 10% and at most 20 retries, the backend receives at most 20 retries on
+        // top of the primary requests since none of them succeeds.
+        let (num_successes, num_attempts) =
+            simulate_backend(1_000, |_| true, &RetryBudget::new(10, 20)).await;
+        assert_eq!(num_successes, 0);
+        assert_eq!(num_attempts, 1_020);
+
+        // Half of the attempts fail: the retries stay under 10% of the successful primary
+        // requests, plus the initial 20 retries.
+        let (_, num_attempts) = simulate_backend(
+            1_000,
+            |attempt_id| attempt_id % 2 == 0,
+            &RetryBudget::new(10, 20),
+        )
+        .await;
+        assert!(num_attempts <= 1_000 + 20 + 1_000 / 10);
+    }
+
+    #[tokio::test]
+    async fn test_retry_budget_allows_retries_on_low_failure_rate() {
+        // One attempt out of 50 fails: the retries are covered by the budget and all the requests
+        // eventually succeed.
+        let (num_successes, num_attempts) = simulate_backend(
+            1_000,
+            |attempt_id| attempt_id % 50 == 0,
+            &RetryBudget::new(10, 20),
+        )
+        .await;
+        assert_eq!(num_successes, 1_000);
+        assert!(num_attempts > 1_000);
+    }
 }
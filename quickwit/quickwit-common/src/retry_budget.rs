@@ -0,0 +1,263 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+use once_cell::sync::{Lazy, OnceCell};
+use tracing::debug;
+
+use crate::metrics::{new_counter_vec, new_gauge, IntCounterVec, IntGauge};
+
+/// Tag of the failures returned instead of a retry denied by the retry budget.
+pub const RETRY_BUDGET_EXHAUSTED: &str = "retry_budget_exhausted";
+
+/// A retry, in hundredths of a retry.
+const ONE_RETRY: u64 = 100;
+
+static GLOBAL_RETRY_BUDGET: OnceCell<Arc<RetryBudget>> = OnceCell::new();
+
+struct RetryBudgetMetrics {
+    retries_total: IntCounterVec<2>,
+    available_retries: IntGauge,
+    utilization_percent: IntGauge,
+}
+
+impl Default for RetryBudgetMetrics {
+    fn default() -> Self {
+        RetryBudgetMetrics {
+            retries_total: new_counter_vec(
+                "retry_budget_retries_total",
+                "Number of retries submitted to the retry budget, per retry layer and outcome \
+                 (`allowed` or `retry_budget_exhausted`).",
+                "quickwit",
+                ["layer", "outcome"],
+            ),
+            available_retries: new_gauge(
+                "retry_budget_available_retries",
+                "Number of retries currently available in the retry budget.",
+                "quickwit",
+            ),
+            utilization_percent: new_gauge(
+                "retry_budget_utilization_percent",
+                "Percentage of the retry budget currently consumed.",
+                "quickwit",
+            ),
+        }
+    }
+}
+
+static RETRY_BUDGET_METRICS: Lazy<RetryBudgetMetrics> = Lazy::new(RetryBudgetMetrics::default);
+
+/// The retry-capable layers sharing the retry budget of the node.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum RetryLayer {
+    /// Leaf search, leaf search stream and fetch docs requests retried on another searcher.
+    ClusterClient,
+    /// Requests retried with exponential backoff, mostly by the object storage clients.
+    Storage,
+    /// gRPC requests retried by the tower retry layer.
+    GrpcClient,
+}
+
+impl RetryLayer {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RetryLayer::ClusterClient => "cluster_client",
+            RetryLayer::Storage => "storage",
+            RetryLayer::GrpcClient => "grpc_client",
+        }
+    }
+}
+
+/// Token bucket capping the retries of a node to a fraction of its successful primary requests,
+/// so that an outage of a backend does not multiply the number of requests it receives.
+///
+/// Each successful primary request deposits `retry_ratio_percent` hundredths of a retry in the
+/// bucket and each retry withdraws a whole one. The bucket starts full and holds at most
+/// `max_retries` retries, which lets short bursts of failures be retried.
+///
+/// An unlimited budget allows all retries, which is the behavior when no budget is configured.
+pub struct RetryBudget {
+    token_bucket_opt: Option<TokenBucket>,
+}
+
+struct TokenBucket {
+    /// Available retries, in hundredths of a retry.
+    balance: AtomicU64,
+    deposit_per_success: u64,
+    max_balance: u64,
+}
+
+impl RetryBudget {
+    /// Creates a budget allowing `retry_ratio_percent` retries for 100 successful primary
+    /// requests, with at most `max_retries` retries available at once.
+    pub fn new(retry_ratio_percent: u32, max_retries: u32) -> Self {
+        let max_balance = max_retries as u64 * ONE_RETRY;
+        let token_bucket = TokenBucket {
+            balance: AtomicU64::new(max_balance),
+            deposit_per_success: retry_ratio_percent as u64,
+            max_balance,
+        };
+        let retry_budget = Self {
+            token_bucket_opt: Some(token_bucket),
+        };
+        retry_budget.update_balance_metrics(max_balance);
+        retry_budget
+    }
+
+    /// Creates a budget allowing all retries.
+    pub fn unlimited() -> Self {
+        Self {
+            token_bucket_opt: None,
+        }
+    }
+
+    pub fn is_unlimited(&self) -> bool {
+        self.token_bucket_opt.is_none()
+    }
+
+    /// Refills the budget. Must be called when a primary request, i.e. not a retry, succeeds.
+    pub fn record_primary_success(&self) {
+        let Some(token_bucket) = &self.token_bucket_opt else {
+            return;
+        };
+        let previous_balance = token_bucket
+            .balance
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+                Some((balance + token_bucket.deposit_per_success).min(token_bucket.max_balance))
+            })
+            .expect("the closure should always return `Some`");
+        let balance =
+            (previous_balance + token_bucket.deposit_per_success).min(token_bucket.max_balance);
+        self.update_balance_metrics(balance);
+    }
+
+    /// Withdraws a retry from the budget. Returns `false` if the budget is exhausted, in which
+    /// case the caller must fail immediately instead of retrying.
+    pub fn try_acquire_retry(&self, layer: RetryLayer) -> bool {
+        let Some(token_bucket) = &self.token_bucket_opt else {
+            return true;
+        };
+        let withdraw_result =
+            token_bucket
+                .balance
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |balance| {
+                    balance.checked_sub(ONE_RETRY)
+                });
+        let outcome = match withdraw_result {
+            Ok(previous_balance) => {
+                self.update_balance_metrics(previous_balance - ONE_RETRY);
+                "allowed"
+            }
+            Err(_) => {
+                debug!(layer = layer.as_str(), "{RETRY_BUDGET_EXHAUSTED}");
+                RETRY_BUDGET_EXHAUSTED
+            }
+        };
+        RETRY_BUDGET_METRICS
+            .retries_total
+            .with_label_values([layer.as_str(), outcome])
+            .inc();
+        withdraw_result.is_ok()
+    }
+
+    fn update_balance_metrics(&self, balance: u64) {
+        let Some(token_bucket) = &self.token_bucket_opt else {
+            return;
+        };
+        RETRY_BUDGET_METRICS
+            .available_retries
+            .set((balance / ONE_RETRY) as i64);
+        let utilization_percent = if token_bucket.max_balance == 0 {
+            100
+        } else {
+            100 - balance * 100 / token_bucket.max_balance
+        };
+        RETRY_BUDGET_METRICS
+            .utilization_percent
+            .set(utilization_percent as i64);
+    }
+}
+
+/// Sets the retry budget shared by all the retry layers of the node. Returns `false` if the
+/// budget was already set or used.
+pub fn set_global_retry_budget(retry_budget: RetryBudget) -> bool {
+    GLOBAL_RETRY_BUDGET.set(Arc::new(retry_budget)).is_ok()
+}
+
+/// Returns the retry budget shared by all the retry layers of the node, unlimited unless set
+/// with [`set_global_retry_budget`].
+pub fn global_retry_budget() -> Arc<RetryBudget> {
+    GLOBAL_RETRY_BUDGET
+        .get_or_init(|| Arc::new(RetryBudget::unlimited()))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlimited_retry_budget() {
+        let retry_budget = RetryBudget::unlimited();
+        assert!(retry_budget.is_unlimited());
+
+        for _ in 0..1_000 {
+            assert!(retry_budget.try_acquire_retry(RetryLayer::Storage));
+        }
+    }
+
+    #[test]
+    fn test_retry_budget_starts_full() {
+        let retry_budget = RetryBudget::new(10, 3);
+        assert!(!retry_budget.is_unlimited());
+
+        for _ in 0..3 {
+            assert!(retry_budget.try_acquire_retry(RetryLayer::Storage));
+        }
+        assert!(!retry_budget.try_acquire_retry(RetryLayer::Storage));
+    }
+
+    #[test]
+    fn test_retry_budget_refills_from_primary_successes() {
+        let retry_budget = RetryBudget::new(10, 3);
+
+        for _ in 0..3 {
+            assert!(retry_budget.try_acquire_retry(RetryLayer::ClusterClient));
+        }
+        for _ in 0..9 {
+            retry_budget.record_primary_success();
+        }
+        assert!(!retry_budget.try_acquire_retry(RetryLayer::ClusterClient));
+
+        retry_budget.record_primary_success();
+        assert!(retry_budget.try_acquire_retry(RetryLayer::ClusterClient));
+        assert!(!retry_budget.try_acquire_retry(RetryLayer::ClusterClient));
+
+        // The budget never holds more than `max_retries` retries.
+        for _ in 0..1_000 {
+            retry_budget.record_primary_success();
+        }
+        for _ in 0..3 {
+            assert!(retry_budget.try_acquire_retry(RetryLayer::GrpcClient));
+        }
+        assert!(!retry_budget.try_acquire_retry(RetryLayer::GrpcClient));
+    }
+}
@@ -30,6 +30,7 @@ use tower::Layer;
 use tracing::debug;
 
 use crate::retry::{RetryParams, Retryable};
+use crate::retry_budget::{self, global_retry_budget, RETRY_BUDGET_EXHAUSTED};
 
 /// Retry layer copy/pasted from `tower::retry::RetryLayer`
 /// but which implements `Clone`.
@@ -96,12 +97,27 @@ where
 
     fn retry(&self, _request: &R, result: Result<&T, &E>) -> Option<Self::Future> {
         match result {
-            Ok(_) => None,
+            Ok(_) => {
+                if self.num_attempts == 0 {
+                    global_retry_budget().record_primary_success();
+                }
+                None
+            }
             Err(error) => {
                 let num_attempts = self.num_attempts + 1;
 
                 if !error.is_retryable() || num_attempts >= self.retry_params.max_attempts {
                     None
+                } else if !global_retry_budget()
+                    .try_acquire_retry(retry_budget::RetryLayer::GrpcClient)
+                {
+                    debug!(
+                        num_attempts=%num_attempts,
+                        error=?error,
+                        "{} request failed, not retrying: {RETRY_BUDGET_EXHAUSTED}",
+                        type_name::<R>()
+                    );
+                    None
                 } else {
                     let delay = self.retry_params.compute_delay(num_attempts);
                     debug!(
@@ -42,6 +42,7 @@ pub enum Protocol {
     PostgreSQL = 5,
     Ram = 6,
     S3 = 7,
+    DynamoDB = 8,
 }
 
 impl Protocol {
@@ -49,6 +50,7 @@ impl Protocol {
         match &self {
             Protocol::Actor => "actor",
             Protocol::Azure => "azure",
+            Protocol::DynamoDB => "dynamodb",
             Protocol::File => "file",
             Protocol::Grpc => "grpc",
             Protocol::PostgreSQL => "postgresql",
@@ -70,7 +72,7 @@ impl Protocol {
     }
 
     pub fn is_database(&self) -> bool {
-        matches!(&self, Protocol::PostgreSQL)
+        matches!(&self, Protocol::DynamoDB | Protocol::PostgreSQL)
     }
 }
 
@@ -86,6 +88,7 @@ impl FromStr for Protocol {
     fn from_str(protocol: &str) -> anyhow::Result<Self> {
         match protocol {
             "azure" => Ok(Protocol::Azure),
+            "dynamodb" => Ok(Protocol::DynamoDB),
             "file" => Ok(Protocol::File),
             "grpc" => Ok(Protocol::Grpc),
             "actor" => Ok(Protocol::Actor),
@@ -171,7 +174,7 @@ impl Uri {
     }
 
     /// Returns the parent URI.
-    /// Does not apply to PostgreSQL URIs.
+    /// Does not apply to database URIs.
     pub fn parent(&self) -> Option<Uri> {
         if self.protocol().is_database() {
             return None;
@@ -199,7 +202,7 @@ impl Uri {
 
     /// Returns the last component of the URI.
     pub fn file_name(&self) -> Option<&Path> {
-        if self.protocol() == Protocol::PostgreSQL {
+        if self.protocol().is_database() {
             return None;
         }
         let path = self.path();
@@ -233,6 +236,11 @@ impl Uri {
                 .join(path)
                 .to_string_lossy()
                 .to_string(),
+            Protocol::DynamoDB => bail!(
+                "cannot join DynamoDB URI `{}` with path `{:?}`",
+                self.uri,
+                path
+            ),
             Protocol::PostgreSQL => bail!(
                 "cannot join PostgreSQL URI `{}` with path `{:?}`",
                 self.uri,
@@ -503,6 +511,10 @@ mod tests {
             Uri::for_test("postgresql://localhost:5432/metastore").protocol(),
             Protocol::PostgreSQL
         );
+        assert_eq!(
+            Uri::for_test("dynamodb://quickwit-metastore").protocol(),
+            Protocol::DynamoDB
+        );
     }
 
     #[test]
@@ -0,0 +1,111 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+/// Accumulates the problems found while validating a config, so that they can all be reported at
+/// once rather than one at a time.
+#[derive(Debug, Default)]
+pub struct ValidationErrors {
+    errors: Vec<anyhow::Error>,
+}
+
+impl ValidationErrors {
+    /// Records the error of `result`, if any, and returns its value otherwise.
+    pub fn check<T>(&mut self, result: anyhow::Result<T>) -> Option<T> {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.push(error);
+                None
+            }
+        }
+    }
+
+    /// Records a problem. The problems of a nested `ValidationErrors` are recorded individually.
+    pub fn push(&mut self, error: anyhow::Error) {
+        match error.downcast::<ValidationErrors>() {
+            Ok(validation_errors) => self.errors.extend(validation_errors.errors),
+            Err(error) => self.errors.push(error),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns the problem found if there is only one, or an error listing all of them otherwise.
+    pub fn into_result(mut self) -> anyhow::Result<()> {
+        match self.errors.len() {
+            0 => Ok(()),
+            1 => Err(self.errors.pop().expect("there should be one error")),
+            _ => Err(self.into()),
+        }
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        write!(formatter, "found {} problems:", self.errors.len())?;
+        for error in &self.errors {
+            write!(formatter, "\n  - {error:#}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    #[test]
+    fn test_validation_errors() {
+        let validation_errors = ValidationErrors::default();
+        assert!(validation_errors.is_empty());
+        validation_errors.into_result().unwrap();
+
+        let mut validation_errors = ValidationErrors::default();
+        assert_eq!(validation_errors.check(Ok(1)), Some(1));
+        assert_eq!(validation_errors.check::<()>(Err(anyhow!("first"))), None);
+        assert_eq!(validation_errors.len(), 1);
+        let error = validation_errors.into_result().unwrap_err();
+        assert_eq!(error.to_string(), "first");
+
+        let mut nested_validation_errors = ValidationErrors::default();
+        nested_validation_errors.push(anyhow!("second"));
+        nested_validation_errors.push(anyhow!("third"));
+        let mut validation_errors = ValidationErrors::default();
+        validation_errors.push(anyhow!("first"));
+        validation_errors.push(nested_validation_errors.into_result().unwrap_err());
+        assert_eq!(validation_errors.len(), 3);
+        let error = validation_errors.into_result().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "found 3 problems:\n  - first\n  - second\n  - third"
+        );
+    }
+}
@@ -29,13 +29,14 @@ use anyhow::Context;
 use bytesize::ByteSize;
 use chrono::Utc;
 use cron::Schedule;
-use humantime::parse_duration;
 use quickwit_common::uri::Uri;
+use quickwit_common::{serialize_duration_as_secs, serialize_opt_duration_as_secs, HumanDuration};
 use quickwit_doc_mapper::{
-    DefaultDocMapper, DefaultDocMapperBuilder, DocMapper, FieldMappingEntry, Mode, ModeType,
-    QuickwitJsonOptions, TokenizerEntry,
+    DefaultDocMapper, DefaultDocMapperBuilder, DocMapper, FieldMappingEntry, JsonParsingOptions,
+    Mode, ModeType, QuickwitJsonOptions, TokenizerEntry,
 };
 use quickwit_proto::types::IndexId;
+use quickwit_query::query_ast::SynonymTable;
 use serde::{Deserialize, Serialize};
 pub use serialize::load_index_config_from_user_config;
 
@@ -87,6 +88,17 @@ pub struct DocMapping {
     #[schema(value_type = u32)]
     #[serde(default = "DefaultDocMapper::default_max_num_partitions")]
     pub max_num_partitions: NonZeroU32,
+    /// Accepts human-friendly durations, e.g. `1h`, under the `timestamp_partition_period` key,
+    /// but is always serialized as a number of seconds.
+    #[schema(value_type = Option<u64>)]
+    #[serde(rename = "timestamp_partition_period_secs")]
+    #[serde(alias = "timestamp_partition_period")]
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_opt_duration_as_secs")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_partition_period: Option<HumanDuration>,
+    #[serde(default)]
+    pub json_parsing: JsonParsingOptions,
     #[serde(default)]
     pub tokenizers: Vec<TokenizerEntry>,
 }
@@ -139,9 +151,14 @@ impl Default for IndexingResources {
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct IndexingSettings {
-    #[schema(default = 60)]
-    #[serde(default = "IndexingSettings::default_commit_timeout_secs")]
-    pub commit_timeout_secs: usize,
+    /// Maximum time documents wait before being committed. Accepts human-friendly durations,
+    /// e.g. `5m`, under the `commit_timeout` key, but is always serialized as a number of seconds.
+    #[schema(value_type = u64, default = 60)]
+    #[serde(rename = "commit_timeout_secs")]
+    #[serde(alias = "commit_timeout")]
+    #[serde(default = "IndexingSettings::default_commit_timeout")]
+    #[serde(serialize_with = "serialize_duration_as_secs")]
+    pub commit_timeout: HumanDuration,
     #[schema(default = 8)]
     #[serde(default = "IndexingSettings::default_docstore_compression_level")]
     pub docstore_compression_level: i32,
@@ -163,11 +180,11 @@ pub struct IndexingSettings {
 
 impl IndexingSettings {
     pub fn commit_timeout(&self) -> Duration {
-        Duration::from_secs(self.commit_timeout_secs as u64)
+        self.commit_timeout.as_duration()
     }
 
-    fn default_commit_timeout_secs() -> usize {
-        60
+    fn default_commit_timeout() -> HumanDuration {
+        HumanDuration::from_secs(60)
     }
 
     pub fn default_docstore_blocksize() -> usize {
@@ -194,7 +211,7 @@ impl IndexingSettings {
 impl Default for IndexingSettings {
     fn default() -> Self {
         Self {
-            commit_timeout_secs: Self::default_commit_timeout_secs(),
+            commit_timeout: Self::default_commit_timeout(),
             docstore_blocksize: Self::default_docstore_blocksize(),
             docstore_compression_level: Self::default_docstore_compression_level(),
             split_num_docs_target: Self::default_split_num_docs_target(),
@@ -209,6 +226,49 @@ impl Default for IndexingSettings {
 pub struct SearchSettings {
     #[serde(default)]
     pub default_search_fields: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub synonyms: Option<SynonymsConfig>,
+}
+
+/// Synonym rules used to expand full-text queries at search time.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SynonymsConfig {
+    /// Inline synonym rules, e.g. `k8s, kubernetes` or `lb => load balancer`.
+    #[serde(default)]
+    pub rules: Vec<String>,
+    /// URI of a file holding additional synonym rules, one per line.
+    #[schema(value_type = Option<String>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rules_uri: Option<Uri>,
+    /// Interval at which the rules file is reloaded. Accepts human-friendly durations, e.g. `1m`,
+    /// but is always serialized as a number of seconds.
+    #[schema(value_type = u64, default = 300)]
+    #[serde(default = "SynonymsConfig::default_refresh_interval")]
+    #[serde(serialize_with = "serialize_duration_as_secs")]
+    pub refresh_interval: HumanDuration,
+    /// Maximum number of synonyms a single term or phrase expands into.
+    #[serde(default = "SynonymsConfig::default_max_expansions")]
+    pub max_expansions: usize,
+}
+
+impl SynonymsConfig {
+    fn default_refresh_interval() -> HumanDuration {
+        HumanDuration::from_secs(5 * 60)
+    }
+
+    fn default_max_expansions() -> usize {
+        10
+    }
+
+    pub fn refresh_interval(&self) -> Duration {
+        self.refresh_interval.as_duration()
+    }
+
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        SynonymTable::parse(self.rules.iter().map(String::as_str))?;
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
@@ -240,12 +300,14 @@ impl RetentionPolicy {
     }
 
     pub fn retention_period(&self) -> anyhow::Result<Duration> {
-        parse_duration(&self.retention_period).with_context(|| {
-            format!(
-                "failed to parse retention period `{}`",
-                self.retention_period
-            )
-        })
+        HumanDuration::from_str(&self.retention_period)
+            .map(Duration::from)
+            .with_context(|| {
+                format!(
+                    "failed to parse retention period `{}`",
+                    self.retention_period
+                )
+            })
     }
 
     pub fn evaluation_schedule(&self) -> anyhow::Result<Schedule> {
@@ -380,6 +442,7 @@ impl IndexConfig {
                 r#"attributes.server"#.to_string(),
                 r"attributes.server\.status".to_string(),
             ],
+            synonyms: None,
         };
         IndexConfig {
             index_id: index_id.to_string(),
@@ -451,6 +514,8 @@ impl TestableForRegression for IndexConfig {
             mode: Mode::default(),
             partition_key: Some("tenant_id".to_string()),
             max_num_partitions: NonZeroU32::new(100).unwrap(),
+            timestamp_partition_period: None,
+            json_parsing: JsonParsingOptions::default(),
             timestamp_field: Some("timestamp".to_string()),
             tokenizers: vec![tokenizer],
         };
@@ -469,7 +534,7 @@ impl TestableForRegression for IndexConfig {
             ..Default::default()
         };
         let indexing_settings = IndexingSettings {
-            commit_timeout_secs: 301,
+            commit_timeout: HumanDuration::from_secs(301),
             split_num_docs_target: 10_000_001,
             merge_policy,
             resources: indexing_resources,
@@ -477,6 +542,7 @@ impl TestableForRegression for IndexConfig {
         };
         let search_settings = SearchSettings {
             default_search_fields: vec!["message".to_string()],
+            synonyms: None,
         };
         IndexConfig {
             index_id: "my-index".to_string(),
@@ -529,6 +595,8 @@ pub fn build_doc_mapper(
         mode: doc_mapping.mode.clone(),
         partition_key: doc_mapping.partition_key.clone(),
         max_num_partitions: doc_mapping.max_num_partitions,
+        timestamp_partition_period: doc_mapping.timestamp_partition_period,
+        json_parsing: doc_mapping.json_parsing,
         tokenizers: doc_mapping.tokenizers.clone(),
     };
     Ok(Arc::new(builder.try_build()?))
@@ -596,7 +664,10 @@ mod tests {
             index_config.doc_mapping.timestamp_field.unwrap(),
             "timestamp"
         );
-        assert_eq!(index_config.indexing_settings.commit_timeout_secs, 61);
+        assert_eq!(
+            index_config.indexing_settings.commit_timeout(),
+            Duration::from_secs(61)
+        );
         assert_eq!(
             index_config.indexing_settings.merge_policy,
             MergePolicyConfig::StableLog(StableLogMergePolicyConfig {
@@ -617,6 +688,7 @@ mod tests {
             index_config.search_settings,
             SearchSettings {
                 default_search_fields: vec!["severity_text".to_string(), "body".to_string()],
+                synonyms: None,
             }
         );
     }
@@ -659,6 +731,7 @@ mod tests {
                 index_config.search_settings,
                 SearchSettings {
                     default_search_fields: vec!["body".to_string()],
+                    synonyms: None,
                 }
             );
         }
@@ -681,7 +754,7 @@ mod tests {
             assert_eq!(
                 index_config.indexing_settings,
                 IndexingSettings {
-                    commit_timeout_secs: 42,
+                    commit_timeout: HumanDuration::from_secs(42),
                     merge_policy: MergePolicyConfig::default(),
                     resources: IndexingResources {
                         ..Default::default()
@@ -693,11 +766,68 @@ mod tests {
                 index_config.search_settings,
                 SearchSettings {
                     default_search_fields: vec!["body".to_string()],
+                    synonyms: None,
                 }
             );
         }
     }
 
+    #[test]
+    fn test_indexing_settings_serializes_commit_timeout_as_secs() {
+        let indexing_settings_yaml = r#"
+            commit_timeout: 5m 1s
+        "#;
+        let indexing_settings =
+            serde_yaml::from_str::<IndexingSettings>(indexing_settings_yaml).unwrap();
+        assert_eq!(indexing_settings.commit_timeout(), Duration::from_secs(301));
+
+        let indexing_settings_json = serde_json::to_value(&indexing_settings).unwrap();
+        assert_eq!(indexing_settings_json["commit_timeout_secs"], 301);
+        assert!(indexing_settings_json.get("commit_timeout").is_none());
+
+        let indexing_settings_yaml = r#"
+            commit_timeout_secs: 301
+        "#;
+        let indexing_settings =
+            serde_yaml::from_str::<IndexingSettings>(indexing_settings_yaml).unwrap();
+        assert_eq!(indexing_settings.commit_timeout(), Duration::from_secs(301));
+    }
+
+    #[test]
+    fn test_index_config_with_human_durations() {
+        let index_config_filepath = get_index_config_filepath("human-durations-hdfs-logs.yaml");
+        let file_content = std::fs::read_to_string(index_config_filepath).unwrap();
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            file_content.as_bytes(),
+            &Uri::for_test("s3://defaultbucket/"),
+        )
+        .unwrap();
+        assert_eq!(
+            index_config.indexing_settings.commit_timeout(),
+            Duration::from_secs(61)
+        );
+        let indexing_settings_json = serde_json::to_value(&index_config.indexing_settings).unwrap();
+        assert_eq!(indexing_settings_json["commit_timeout_secs"], 61);
+        assert!(indexing_settings_json.get("commit_timeout").is_none());
+    }
+
+    #[test]
+    fn test_doc_mapping_serializes_timestamp_partition_period_as_secs() {
+        let doc_mapping_yaml = r#"
+            timestamp_field: timestamp
+            timestamp_partition_period: 1h
+        "#;
+        let doc_mapping = serde_yaml::from_str::<DocMapping>(doc_mapping_yaml).unwrap();
+        assert_eq!(
+            doc_mapping.timestamp_partition_period,
+            Some(HumanDuration::from_secs(3600))
+        );
+        let doc_mapping_json = serde_json::to_value(&doc_mapping).unwrap();
+        assert_eq!(doc_mapping_json["timestamp_partition_period_secs"], 3600);
+        assert!(doc_mapping_json.get("timestamp_partition_period").is_none());
+    }
+
     #[test]
     #[should_panic(expected = "empty URI")]
     fn test_config_validates_uris() {
@@ -752,7 +882,106 @@ mod tests {
         assert!(parsing_config_error
             .root_cause()
             .to_string()
-            .contains("failed to parse human-readable duration `x`"));
+            .contains("failed to parse duration `x`"));
+    }
+
+    #[test]
+    fn test_index_config_with_integer_maturation_duration() {
+        let config_yaml = r#"
+            version: 0.6
+            index_id: hdfs-logs
+            index_uri: "s3://my-index"
+            doc_mapping: {}
+            indexing_settings:
+              merge_policy:
+                type: stable_log
+                maturation_period: 3600
+        "#;
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            config_yaml.as_bytes(),
+            &Uri::for_test("s3://my-index"),
+        )
+        .unwrap();
+        let MergePolicyConfig::StableLog(stable_log_config) =
+            index_config.indexing_settings.merge_policy
+        else {
+            panic!("expected a stable log merge policy");
+        };
+        assert_eq!(
+            stable_log_config.maturation_period,
+            Duration::from_secs(3600)
+        );
+    }
+
+    #[test]
+    fn test_index_config_with_synonyms() {
+        let config_yaml = r#"
+            version: 0.6
+            index_id: hdfs-logs
+            index_uri: "s3://my-index"
+            doc_mapping: {}
+            search_settings:
+              synonyms:
+                rules:
+                  - k8s, kubernetes
+                  - lb => load balancer
+                rules_uri: s3://my-bucket/synonyms.txt
+        "#;
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            config_yaml.as_bytes(),
+            &Uri::for_test("s3://my-index"),
+        )
+        .unwrap();
+        let synonyms_config = index_config.search_settings.synonyms.unwrap();
+        assert_eq!(synonyms_config.rules.len(), 2);
+        assert_eq!(
+            synonyms_config.rules_uri.unwrap(),
+            "s3://my-bucket/synonyms.txt"
+        );
+        assert_eq!(synonyms_config.refresh_interval(), Duration::from_secs(300));
+        assert_eq!(synonyms_config.max_expansions, 10);
+
+        let config_yaml = r#"
+            version: 0.6
+            index_id: hdfs-logs
+            index_uri: "s3://my-index"
+            doc_mapping: {}
+            search_settings:
+              synonyms:
+                rules:
+                  - kubernetes
+        "#;
+        let error = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            config_yaml.as_bytes(),
+            &Uri::for_test("s3://my-index"),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("invalid synonym rule"));
+
+        let config_yaml = r#"
+            version: 0.6
+            index_id: hdfs-logs
+            index_uri: "s3://my-index"
+            doc_mapping: {}
+            search_settings:
+              synonyms:
+                rules:
+                  - k8s, kubernetes
+                refresh_interval: every now and then
+        "#;
+        let error = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            config_yaml.as_bytes(),
+            &Uri::for_test("s3://my-index"),
+        )
+        .unwrap_err();
+        assert!(error
+            .root_cause()
+            .to_string()
+            .contains("failed to parse duration `every now and then`"));
     }
 
     #[test]
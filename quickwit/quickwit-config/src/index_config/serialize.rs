@@ -19,6 +19,7 @@
 
 use anyhow::Context;
 use quickwit_common::uri::Uri;
+use quickwit_common::ValidationErrors;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
@@ -83,27 +84,38 @@ impl IndexConfigForSerialization {
         self,
         default_index_root_uri: Option<&Uri>,
     ) -> anyhow::Result<IndexConfig> {
-        validate_identifier("Index ID", &self.index_id)?;
+        // All the problems of the config are reported at once.
+        let mut validation_errors = ValidationErrors::default();
 
-        let index_uri = self.index_uri_or_fallback_to_default(default_index_root_uri)?;
+        validation_errors.check(validate_identifier("Index ID", &self.index_id));
+
+        let index_uri_opt =
+            validation_errors.check(self.index_uri_or_fallback_to_default(default_index_root_uri));
 
         if let Some(retention_policy) = &self.retention_policy {
-            retention_policy.validate()?;
+            validation_errors.check(retention_policy.validate());
 
             if self.doc_mapping.timestamp_field.is_none() {
-                anyhow::bail!(
+                validation_errors.push(anyhow::anyhow!(
                     "failed to validate index config. the retention policy requires a timestamp \
                      field, but the indexing settings do not declare one"
-                );
+                ));
             }
         }
 
         // Note: this needs a deep refactoring to separate the doc mapping configuration,
         // and doc mapper implementations.
         // TODO see if we should store the byproducton the IndexConfig.
-        build_doc_mapper(&self.doc_mapping, &self.search_settings)?;
+        validation_errors.check(build_doc_mapper(&self.doc_mapping, &self.search_settings));
+
+        validation_errors.check(self.indexing_settings.merge_policy.validate());
+
+        if let Some(synonyms_config) = &self.search_settings.synonyms {
+            validation_errors.check(synonyms_config.validate());
+        }
 
-        self.indexing_settings.merge_policy.validate()?;
+        validation_errors.into_result()?;
+        let index_uri = index_uri_opt.expect("the index URI should have been validated");
 
         Ok(IndexConfig {
             index_id: self.index_id,
@@ -225,6 +237,35 @@ mod test {
         assert!(validation_err.contains("the retention policy requires a timestamp field"));
     }
 
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let invalid_index_config: IndexConfigForSerialization = serde_yaml::from_str(
+            r#"
+            index_id: hdfs logs
+            index_uri: s3://quickwit-indexes/hdfs-logs
+
+            doc_mapping:
+                mode: strict
+                field_mappings:
+                    - name: body
+                      type: text
+                timestamp_field: timestamp
+
+            search_settings:
+                default_search_fields: [body, severity_text]
+        "#,
+        )
+        .unwrap();
+        let validation_err = invalid_index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.starts_with("found 3 problems:"));
+        assert!(validation_err.contains("Index ID"));
+        assert!(validation_err.contains("could not find timestamp field `timestamp`"));
+        assert!(validation_err.contains("Unknown default search field: `severity_text`"));
+    }
+
     #[test]
     fn test_minimal_index_config_missing_root_uri_no_default_uri() {
         let config_yaml = r#"
@@ -21,7 +21,7 @@
 
 use std::str::FromStr;
 
-use anyhow::{bail, Context};
+use anyhow::{anyhow, bail, Context};
 use json_comments::StripComments;
 use once_cell::sync::Lazy;
 use quickwit_common::net::is_valid_hostname;
@@ -44,27 +44,30 @@ mod templating;
 use index_config::serialize::{IndexConfigV0_6, VersionedIndexConfig};
 pub use index_config::{
     build_doc_mapper, load_index_config_from_user_config, DocMapping, IndexConfig,
-    IndexingResources, IndexingSettings, RetentionPolicy, SearchSettings,
+    IndexingResources, IndexingSettings, RetentionPolicy, SearchSettings, SynonymsConfig,
 };
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 use serde_json::Value as JsonValue;
 pub use source_config::{
-    load_source_config_from_user_config, FileSourceParams, GcpPubSubSourceParams,
-    KafkaSourceParams, KinesisSourceParams, PulsarSourceAuth, PulsarSourceParams, RegionOrEndpoint,
-    SourceConfig, SourceInputFormat, SourceParams, TransformConfig, VecSourceParams,
-    VoidSourceParams, CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_SOURCE_ID,
+    load_source_config_from_user_config, FileSourceCompression, FileSourceParams,
+    FileTailSourceParams, GcpPubSubSourceParams, KafkaSourceParams, KinesisSourceParams,
+    PulsarSourceAuth, PulsarSourceParams, PushSourceParams, RegionOrEndpoint, SourceConfig,
+    SourceInputFormat, SourceParams, TransformConfig, VecSourceParams, VoidSourceParams,
+    CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID, INGEST_SOURCE_ID,
 };
-use tracing::warn;
+use tracing::{info, warn};
 
 use crate::merge_policy_config::{
     ConstWriteAmplificationMergePolicyConfig, MergePolicyConfig, StableLogMergePolicyConfig,
 };
 pub use crate::metastore_config::{
-    MetastoreBackend, MetastoreConfig, MetastoreConfigs, PostgresMetastoreConfig,
+    DynamoDbMetastoreConfig, MetastoreBackend, MetastoreConfig, MetastoreConfigs,
+    PostgresMetastoreConfig,
 };
 pub use crate::node_config::{
-    IndexerConfig, IngestApiConfig, JaegerConfig, NodeConfig, SearcherConfig, SplitCacheLimits,
+    IndexerConfig, IngestApiConfig, JaegerConfig, LeafSearchTimeoutConfig, NodeConfig,
+    RetryBudgetConfig, SearchResponseCacheLimits, SearcherConfig, SplitCacheLimits,
     DEFAULT_QW_CONFIG_PATH,
 };
 use crate::source_config::serialize::{SourceConfigV0_6, VersionedSourceConfig};
@@ -78,6 +81,7 @@ pub use crate::storage_config::{
     IndexingResources,
     IndexingSettings,
     SearchSettings,
+    SynonymsConfig,
     RetentionPolicy,
     MergePolicyConfig,
     DocMapping,
@@ -88,11 +92,14 @@ pub use crate::storage_config::{
     SourceInputFormat,
     SourceParams,
     FileSourceParams,
+    FileSourceCompression,
+    FileTailSourceParams,
     GcpPubSubSourceParams,
     KafkaSourceParams,
     KinesisSourceParams,
     PulsarSourceParams,
     PulsarSourceAuth,
+    PushSourceParams,
     RegionOrEndpoint,
     ConstWriteAmplificationMergePolicyConfig,
     StableLogMergePolicyConfig,
@@ -185,6 +192,69 @@ impl ConfigFormat {
             .with_context(|| format!("failed to identify configuration file format {uri}"))
     }
 
+    /// Identifies the format of a config file from its extension and checks that its content is
+    /// syntactically valid for this format. When the extension is missing or not supported, the
+    /// content is parsed as JSON, then as YAML, and the first parser that succeeds wins.
+    ///
+    /// Errors report which parser failed and where in the file.
+    pub fn sniff_from_uri_and_content(uri: &Uri, payload: &[u8]) -> anyhow::Result<ConfigFormat> {
+        if let Some(config_format) = uri
+            .extension()
+            .and_then(|extension_str| ConfigFormat::from_str(extension_str).ok())
+        {
+            config_format
+                .check_syntax(payload)
+                .with_context(|| format!("failed to parse config file `{uri}`"))?;
+            return Ok(config_format);
+        }
+        let mut errors = Vec::new();
+
+        for config_format in [ConfigFormat::Json, ConfigFormat::Yaml] {
+            match config_format.check_syntax(payload) {
+                Ok(()) => {
+                    info!(uri=%uri, format=config_format.as_str(), "sniffed config file format");
+                    return Ok(config_format);
+                }
+                Err(error) => errors.push(error.to_string()),
+            }
+        }
+        bail!(
+            "failed to identify the format of config file `{uri}` from its extension or its \
+             content: {}",
+            errors.join(", ")
+        )
+    }
+
+    /// Checks that the payload holds a mapping written in this format.
+    fn check_syntax(&self, payload: &[u8]) -> anyhow::Result<()> {
+        let is_mapping = match self {
+            ConfigFormat::Json => {
+                let json_value: JsonValue = serde_json::from_reader(StripComments::new(payload))
+                    .map_err(|error| anyhow!("JSON parser failed: {error}"))?;
+                json_value.is_object()
+            }
+            ConfigFormat::Toml => {
+                let payload_str = std::str::from_utf8(payload)
+                    .map_err(|error| anyhow!("TOML parser failed: {error}"))?;
+                toml::from_str::<toml::Table>(payload_str)
+                    .map_err(|error| anyhow!("TOML parser failed: {error}"))?;
+                true
+            }
+            ConfigFormat::Yaml => {
+                let yaml_value: serde_yaml::Value = serde_yaml::from_slice(payload)
+                    .map_err(|error| anyhow!("YAML parser failed: {error}"))?;
+                yaml_value.is_mapping()
+            }
+        };
+        if !is_mapping {
+            bail!(
+                "{} parser failed: the config must be a mapping of keys to values",
+                self.as_str().to_uppercase()
+            );
+        }
+        Ok(())
+    }
+
     pub fn parse<T>(&self, payload: &[u8]) -> anyhow::Result<T>
     where T: DeserializeOwned {
         match self {
@@ -244,8 +314,10 @@ pub trait TestableForRegression: Serialize + DeserializeOwned {
 
 #[cfg(test)]
 mod tests {
+    use quickwit_common::uri::Uri;
+
     use super::validate_identifier;
-    use crate::validate_index_id_pattern;
+    use crate::{validate_index_id_pattern, ConfigFormat};
 
     #[test]
     fn test_validate_identifier() {
@@ -279,4 +351,66 @@ mod tests {
             .to_string()
             .contains("index ID pattern `foo!` is invalid:"));
     }
+
+    #[test]
+    fn test_config_format_sniff_from_uri_and_content() {
+        let yaml_payload = b"version: 0.6\nindex_id: my-index\n";
+        let json_payload = br#"{"version": "0.6", "index_id": "my-index"}"#;
+
+        let yaml_uri = Uri::for_test("file:///config/index.yaml");
+        assert_eq!(
+            ConfigFormat::sniff_from_uri_and_content(&yaml_uri, yaml_payload).unwrap(),
+            ConfigFormat::Yaml
+        );
+        let yml_uri = Uri::for_test("file:///config/index.yml");
+        assert_eq!(
+            ConfigFormat::sniff_from_uri_and_content(&yml_uri, yaml_payload).unwrap(),
+            ConfigFormat::Yaml
+        );
+        let json_uri = Uri::for_test("file:///config/index.json");
+        assert_eq!(
+            ConfigFormat::sniff_from_uri_and_content(&json_uri, json_payload).unwrap(),
+            ConfigFormat::Json
+        );
+        for uri in ["file:///config/index", "file:///config/index.conf"] {
+            let uri = Uri::for_test(uri);
+            assert_eq!(
+                ConfigFormat::sniff_from_uri_and_content(&uri, json_payload).unwrap(),
+                ConfigFormat::Json
+            );
+            assert_eq!(
+                ConfigFormat::sniff_from_uri_and_content(&uri, yaml_payload).unwrap(),
+                ConfigFormat::Yaml
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_format_sniff_from_uri_and_content_errors() {
+        let json_uri = Uri::for_test("file:///config/index.json");
+        let error = ConfigFormat::sniff_from_uri_and_content(
+            &json_uri,
+            b"{\n  \"version\": \"0.6\"\n  \"index_id\": \"my-index\"\n}",
+        )
+        .unwrap_err();
+        let error_chain = format!("{error:#}");
+        assert!(error_chain.contains("JSON parser failed"));
+        assert!(error_chain.contains("line 3"));
+
+        let yaml_uri = Uri::for_test("file:///config/index.yaml");
+        let error = ConfigFormat::sniff_from_uri_and_content(
+            &yaml_uri,
+            b"version: 0.6\nindex_id: [my-index\n",
+        )
+        .unwrap_err();
+        let error_chain = format!("{error:#}");
+        assert!(error_chain.contains("YAML parser failed"));
+        assert!(error_chain.contains("at line"));
+
+        let uri = Uri::for_test("file:///config/index");
+        let error = ConfigFormat::sniff_from_uri_and_content(&uri, b"just some text").unwrap_err();
+        let error_msg = error.to_string();
+        assert!(error_msg.contains("JSON parser failed"));
+        assert!(error_msg.contains("YAML parser failed"));
+    }
 }
@@ -19,7 +19,8 @@
 
 use std::time::Duration;
 
-use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use quickwit_common::HumanDuration;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
@@ -111,19 +112,12 @@ impl Default for StableLogMergePolicyConfig {
 
 fn parse_human_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
 where D: Deserializer<'de> {
-    let value: String = Deserialize::deserialize(deserializer)?;
-    let duration = humantime::parse_duration(&value).map_err(|error| {
-        de::Error::custom(format!(
-            "failed to parse human-readable duration `{value}`: {error:?}",
-        ))
-    })?;
-    Ok(duration)
+    HumanDuration::deserialize(deserializer).map(Duration::from)
 }
 
 fn serialize_duration<S>(value: &Duration, s: S) -> Result<S::Ok, S::Error>
 where S: Serializer {
-    let value_str = humantime::format_duration(*value).to_string();
-    s.serialize_str(&value_str)
+    HumanDuration::from(*value).serialize(s)
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq, utoipa::ToSchema)]
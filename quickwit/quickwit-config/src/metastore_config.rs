@@ -28,6 +28,8 @@ use serde_with::{serde_as, EnumMap};
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MetastoreBackend {
+    #[serde(rename = "dynamodb")]
+    DynamoDB,
     File,
     #[serde(alias = "pg", alias = "postgres")]
     PostgreSQL,
@@ -42,6 +44,9 @@ pub enum MetastoreBackend {
 ///
 ///   postgres:
 ///     max_num_connections: 12
+///
+///   dynamodb:
+///     region: us-east-1
 /// ```
 #[serde_as]
 #[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
@@ -71,6 +76,17 @@ impl MetastoreConfigs {
         Ok(())
     }
 
+    pub fn find_dynamodb(&self) -> Option<&DynamoDbMetastoreConfig> {
+        self.0
+            .iter()
+            .find_map(|metastore_config| match metastore_config {
+                MetastoreConfig::DynamoDB(dynamodb_metastore_config) => {
+                    Some(dynamodb_metastore_config)
+                }
+                _ => None,
+            })
+    }
+
     pub fn find_file(&self) -> Option<&FileMetastoreConfig> {
         self.0
             .iter()
@@ -103,6 +119,8 @@ impl Deref for MetastoreConfigs {
 #[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum MetastoreConfig {
+    #[serde(rename = "dynamodb")]
+    DynamoDB(DynamoDbMetastoreConfig),
     File(FileMetastoreConfig),
     #[serde(alias = "pg", alias = "postgres")]
     PostgreSQL(PostgresMetastoreConfig),
@@ -111,11 +129,19 @@ pub enum MetastoreConfig {
 impl MetastoreConfig {
     pub fn backend(&self) -> MetastoreBackend {
         match self {
+            Self::DynamoDB(_) => MetastoreBackend::DynamoDB,
             Self::File(_) => MetastoreBackend::File,
             Self::PostgreSQL(_) => MetastoreBackend::PostgreSQL,
         }
     }
 
+    pub fn as_dynamodb(&self) -> Option<&DynamoDbMetastoreConfig> {
+        match self {
+            Self::DynamoDB(dynamodb_metastore_config) => Some(dynamodb_metastore_config),
+            _ => None,
+        }
+    }
+
     pub fn as_file(&self) -> Option<&FileMetastoreConfig> {
         match self {
             Self::File(file_metastore_config) => Some(file_metastore_config),
@@ -136,6 +162,12 @@ impl MetastoreConfig {
     }
 }
 
+impl From<DynamoDbMetastoreConfig> for MetastoreConfig {
+    fn from(dynamodb_metastore_config: DynamoDbMetastoreConfig) -> Self {
+        Self::DynamoDB(dynamodb_metastore_config)
+    }
+}
+
 impl From<FileMetastoreConfig> for MetastoreConfig {
     fn from(file_metastore_config: FileMetastoreConfig) -> Self {
         Self::File(file_metastore_config)
@@ -169,6 +201,19 @@ impl PostgresMetastoreConfig {
     }
 }
 
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DynamoDbMetastoreConfig {
+    /// AWS region of the DynamoDB table. Defaults to the region resolved from the environment.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+    /// Custom endpoint, for instance `http://localhost:4566` for localstack.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+}
+
 #[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct FileMetastoreConfig;
@@ -213,6 +258,27 @@ mod tests {
         metastore_configs.validate().unwrap_err();
     }
 
+    #[test]
+    fn test_dynamodb_metastore_config_serde() {
+        let metastore_configs_yaml = r#"
+                dynamodb:
+                    region: eu-west-1
+                    endpoint: http://localhost:4566
+            "#;
+        let metastore_configs: MetastoreConfigs =
+            serde_yaml::from_str(metastore_configs_yaml).unwrap();
+
+        let expected_dynamodb_metastore_config = DynamoDbMetastoreConfig {
+            region: Some("eu-west-1".to_string()),
+            endpoint: Some("http://localhost:4566".to_string()),
+        };
+        assert_eq!(
+            metastore_configs.find_dynamodb().unwrap(),
+            &expected_dynamodb_metastore_config
+        );
+        assert!(metastore_configs.find_postgres().is_none());
+    }
+
     #[test]
     fn test_pg_metastore_config_serde() {
         {
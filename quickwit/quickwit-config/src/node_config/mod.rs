@@ -31,8 +31,9 @@ use bytesize::ByteSize;
 use http::HeaderMap;
 use quickwit_common::net::HostAddr;
 use quickwit_common::uri::Uri;
+use quickwit_common::HumanDuration;
 use quickwit_proto::indexing::CpuCapacity;
-use serde::{Deserialize, Serialize};
+use serde::{de, Deserialize, Deserializer, Serialize};
 use tracing::warn;
 
 use crate::node_config::serialize::load_node_config_with_env;
@@ -160,6 +161,153 @@ impl Default for SplitCacheLimits {
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SearchResponseCacheLimits {
+    #[serde(default = "SearchResponseCacheLimits::default_max_num_entries")]
+    pub max_num_entries: NonZeroUsize,
+    #[serde(
+        alias = "ttl_secs",
+        default = "SearchResponseCacheLimits::default_ttl",
+        deserialize_with = "deserialize_non_zero_duration"
+    )]
+    pub ttl: HumanDuration,
+}
+
+impl SearchResponseCacheLimits {
+    fn default_max_num_entries() -> NonZeroUsize {
+        NonZeroUsize::new(1_000).unwrap()
+    }
+
+    fn default_ttl() -> HumanDuration {
+        HumanDuration::from_secs(10)
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl.as_duration()
+    }
+}
+
+impl Default for SearchResponseCacheLimits {
+    fn default() -> SearchResponseCacheLimits {
+        SearchResponseCacheLimits {
+            max_num_entries: Self::default_max_num_entries(),
+            ttl: Self::default_ttl(),
+        }
+    }
+}
+
+/// Derives the timeout of leaf search requests from the cost of the splits they target, so that
+/// large splits get more time than small ones.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LeafSearchTimeoutConfig {
+    /// Time allotted to a leaf per unit of cost of the splits it searches.
+    #[serde(
+        default = "LeafSearchTimeoutConfig::default_timeout_per_cost_unit",
+        deserialize_with = "deserialize_non_zero_duration"
+    )]
+    pub timeout_per_cost_unit: HumanDuration,
+    #[serde(
+        default = "LeafSearchTimeoutConfig::default_min_timeout",
+        deserialize_with = "deserialize_non_zero_duration"
+    )]
+    pub min_timeout: HumanDuration,
+    #[serde(
+        default = "LeafSearchTimeoutConfig::default_max_timeout",
+        deserialize_with = "deserialize_non_zero_duration"
+    )]
+    pub max_timeout: HumanDuration,
+}
+
+impl LeafSearchTimeoutConfig {
+    fn default_timeout_per_cost_unit() -> HumanDuration {
+        HumanDuration::from_secs(1)
+    }
+
+    fn default_min_timeout() -> HumanDuration {
+        HumanDuration::from_secs(5)
+    }
+
+    fn default_max_timeout() -> HumanDuration {
+        HumanDuration::from_secs(30)
+    }
+
+    pub fn min_timeout(&self) -> Duration {
+        self.min_timeout.as_duration()
+    }
+
+    pub fn max_timeout(&self) -> Duration {
+        self.max_timeout.as_duration()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.min_timeout > self.max_timeout {
+            bail!(
+                "leaf search timeout `min_timeout` must be lower than or equal to `max_timeout`, \
+                 got `{}` and `{}`",
+                self.min_timeout,
+                self.max_timeout
+            );
+        }
+        Ok(())
+    }
+
+    /// Returns the time allotted to searching splits of total cost `cost`: the cost scaled by
+    /// `timeout_per_cost_unit`, bounded by the min and max timeouts.
+    pub fn timeout_for_cost(&self, cost: usize) -> Duration {
+        let timeout_per_cost_unit = self.timeout_per_cost_unit.as_duration();
+        let timeout = timeout_per_cost_unit
+            .checked_mul(u32::try_from(cost).unwrap_or(u32::MAX))
+            .unwrap_or(Duration::MAX);
+        timeout.max(self.min_timeout()).min(self.max_timeout())
+    }
+}
+
+impl Default for LeafSearchTimeoutConfig {
+    fn default() -> LeafSearchTimeoutConfig {
+        LeafSearchTimeoutConfig {
+            timeout_per_cost_unit: Self::default_timeout_per_cost_unit(),
+            min_timeout: Self::default_min_timeout(),
+            max_timeout: Self::default_max_timeout(),
+        }
+    }
+}
+
+/// Caps the retries of the node to a fraction of its successful requests, so that retries do not
+/// amplify the load on a failing backend. The budget is shared by all the retry layers of the
+/// node: searcher failover, storage requests and gRPC clients.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetryBudgetConfig {
+    /// Number of retries allowed for 100 successful requests.
+    #[serde(default = "RetryBudgetConfig::default_retry_ratio_percent")]
+    pub retry_ratio_percent: NonZeroU32,
+    /// Maximum number of retries that can be accumulated, i.e. the size of the bursts of
+    /// failures that can be retried.
+    #[serde(default = "RetryBudgetConfig::default_max_retries")]
+    pub max_retries: NonZeroU32,
+}
+
+impl RetryBudgetConfig {
+    fn default_retry_ratio_percent() -> NonZeroU32 {
+        NonZeroU32::new(10).unwrap()
+    }
+
+    fn default_max_retries() -> NonZeroU32 {
+        NonZeroU32::new(100).unwrap()
+    }
+}
+
+impl Default for RetryBudgetConfig {
+    fn default() -> RetryBudgetConfig {
+        RetryBudgetConfig {
+            retry_ratio_percent: Self::default_retry_ratio_percent(),
+            max_retries: Self::default_max_retries(),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct SearcherConfig {
@@ -170,11 +318,43 @@ pub struct SearcherConfig {
     pub partial_request_cache_capacity: ByteSize,
     pub max_num_concurrent_split_searches: usize,
     pub max_num_concurrent_split_streams: usize,
+    /// Maximum number of times a failing leaf search request is retried on another searcher.
+    pub max_leaf_search_retries: usize,
+    /// How long the root keeps serving the last index metadata and split lists fetched from the
+    /// metastore while the metastore is unreachable. `0` disables the fallback.
+    #[serde(alias = "metastore_max_staleness_secs")]
+    pub metastore_max_staleness: HumanDuration,
+    /// Maximum duration of the listing of the splits of an index on the root. Indexes exceeding
+    /// it are reported as failed instead of failing the whole search request.
+    #[serde(deserialize_with = "deserialize_non_zero_duration")]
+    pub list_splits_timeout: HumanDuration,
+    /// Maximum number of indexes whose splits are listed concurrently for a search request.
+    pub max_num_concurrent_list_splits: NonZeroUsize,
     // Strangely, if None, this will also have the effect of not forwarding
     // to searcher.
     // TODO document and fix if necessary.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub split_cache: Option<SplitCacheLimits>,
+    /// Caches the responses of root search requests. `None` disables the cache.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub search_response_cache: Option<SearchResponseCacheLimits>,
+    /// Timeouts of the leaf search requests, derived from the cost of their splits. `None`
+    /// disables them: leaf search requests are only bounded by the timeout of the search request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub leaf_search_timeout: Option<LeafSearchTimeoutConfig>,
+    /// Retry budget of the node. `None` disables the budget: all retries are allowed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub retry_budget: Option<RetryBudgetConfig>,
+}
+
+impl SearcherConfig {
+    pub fn metastore_max_staleness(&self) -> Duration {
+        self.metastore_max_staleness.as_duration()
+    }
+
+    pub fn list_splits_timeout(&self) -> Duration {
+        self.list_splits_timeout.as_duration()
+    }
 }
 
 impl Default for SearcherConfig {
@@ -185,9 +365,16 @@ impl Default for SearcherConfig {
             partial_request_cache_capacity: ByteSize::mb(64),
             max_num_concurrent_split_streams: 100,
             max_num_concurrent_split_searches: 100,
+            max_leaf_search_retries: 1,
+            metastore_max_staleness: HumanDuration::from_secs(300),
+            list_splits_timeout: HumanDuration::from_secs(10),
+            max_num_concurrent_list_splits: NonZeroUsize::new(10).unwrap(),
             aggregation_memory_limit: ByteSize::mb(500),
             aggregation_bucket_limit: 65000,
             split_cache: None,
+            search_response_cache: None,
+            leaf_search_timeout: None,
+            retry_budget: None,
         }
     }
 }
@@ -251,15 +438,19 @@ pub struct JaegerConfig {
     /// `get_operations`, `get_trace` operations).
     #[serde(default = "JaegerConfig::default_lookback_period_hours")]
     lookback_period_hours: NonZeroU64,
-    /// The assumed maximum duration of a trace in seconds.
+    /// The assumed maximum duration of a trace.
     ///
     /// Finding a trace happens in two phases: the first phase identifies at least one span that
     /// matches the query, while the second phase retrieves the spans that belong to the trace.
-    /// The `max_trace_duration_secs` parameter is used during the second phase to restrict the
+    /// The `max_trace_duration` parameter is used during the second phase to restrict the
     /// search time interval to [span.end_timestamp - max_trace_duration, span.start_timestamp
     /// + max_trace_duration].
-    #[serde(default = "JaegerConfig::default_max_trace_duration_secs")]
-    max_trace_duration_secs: NonZeroU64,
+    #[serde(
+        alias = "max_trace_duration_secs",
+        default = "JaegerConfig::default_max_trace_duration",
+        deserialize_with = "deserialize_non_zero_duration"
+    )]
+    max_trace_duration: HumanDuration,
     /// The maximum number of spans that can be retrieved in a single request.
     #[serde(default = "JaegerConfig::default_max_fetch_spans")]
     pub max_fetch_spans: NonZeroU64,
@@ -271,7 +462,7 @@ impl JaegerConfig {
     }
 
     pub fn max_trace_duration(&self) -> Duration {
-        Duration::from_secs(self.max_trace_duration_secs.get())
+        self.max_trace_duration.as_duration()
     }
 
     fn default_enable_endpoint() -> bool {
@@ -289,8 +480,8 @@ impl JaegerConfig {
         NonZeroU64::new(72).unwrap() // 3 days
     }
 
-    fn default_max_trace_duration_secs() -> NonZeroU64 {
-        NonZeroU64::new(3600).unwrap() // 1 hour
+    fn default_max_trace_duration() -> HumanDuration {
+        HumanDuration::from_secs(3600) // 1 hour
     }
 
     fn default_max_fetch_spans() -> NonZeroU64 {
@@ -303,12 +494,21 @@ impl Default for JaegerConfig {
         Self {
             enable_endpoint: Self::default_enable_endpoint(),
             lookback_period_hours: Self::default_lookback_period_hours(),
-            max_trace_duration_secs: Self::default_max_trace_duration_secs(),
+            max_trace_duration: Self::default_max_trace_duration(),
             max_fetch_spans: Self::default_max_fetch_spans(),
         }
     }
 }
 
+fn deserialize_non_zero_duration<'de, D>(deserializer: D) -> Result<HumanDuration, D::Error>
+where D: Deserializer<'de> {
+    let duration = HumanDuration::deserialize(deserializer)?;
+    if duration.as_duration().is_zero() {
+        return Err(de::Error::custom("duration must be non-zero"));
+    }
+    Ok(duration)
+}
+
 #[derive(Clone, Debug, Serialize)]
 pub struct NodeConfig {
     pub cluster_id: String,
@@ -390,9 +590,32 @@ impl NodeConfig {
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use quickwit_proto::indexing::CpuCapacity;
 
-    use crate::IndexerConfig;
+    use crate::{IndexerConfig, LeafSearchTimeoutConfig};
+
+    #[test]
+    fn test_leaf_search_timeout_for_cost() {
+        let leaf_search_timeout = LeafSearchTimeoutConfig::default();
+        assert_eq!(
+            leaf_search_timeout.timeout_for_cost(1),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            leaf_search_timeout.timeout_for_cost(12),
+            Duration::from_secs(12)
+        );
+        assert_eq!(
+            leaf_search_timeout.timeout_for_cost(1_000),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            leaf_search_timeout.timeout_for_cost(usize::MAX),
+            Duration::from_secs(30)
+        );
+    }
 
     #[test]
     fn test_index_config_serialization() {
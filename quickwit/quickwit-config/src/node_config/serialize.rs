@@ -282,6 +282,9 @@ impl NodeConfigBuilder {
         self.storage_configs.validate()?;
         self.storage_configs.apply_flavors();
         self.ingest_api_config.validate()?;
+        if let Some(leaf_search_timeout) = &self.searcher_config.leaf_search_timeout {
+            leaf_search_timeout.validate()?;
+        }
 
         let node_config = NodeConfig {
             cluster_id: self.cluster_id.resolve(env_vars)?,
@@ -446,14 +449,17 @@ pub fn node_config_for_test() -> NodeConfig {
 mod tests {
     use std::env;
     use std::net::Ipv4Addr;
-    use std::num::NonZeroU64;
+    use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
     use std::path::Path;
+    use std::time::Duration;
 
     use bytesize::ByteSize;
     use itertools::Itertools;
+    use quickwit_common::HumanDuration;
 
     use super::*;
     use crate::storage_config::StorageBackendFlavor;
+    use crate::{LeafSearchTimeoutConfig, RetryBudgetConfig};
 
     fn get_config_filepath(config_filename: &str) -> String {
         format!(
@@ -564,7 +570,20 @@ mod tests {
                 partial_request_cache_capacity: ByteSize::mb(64),
                 max_num_concurrent_split_searches: 150,
                 max_num_concurrent_split_streams: 120,
+                max_leaf_search_retries: 3,
+                metastore_max_staleness: HumanDuration::from_secs(120),
+                list_splits_timeout: HumanDuration::from_secs(20),
+                max_num_concurrent_list_splits: NonZeroUsize::new(5).unwrap(),
                 split_cache: None,
+                search_response_cache: None,
+                leaf_search_timeout: Some(LeafSearchTimeoutConfig {
+                    timeout_per_cost_unit: HumanDuration::new(Duration::from_millis(500)),
+                    ..Default::default()
+                }),
+                retry_budget: Some(RetryBudgetConfig {
+                    retry_ratio_percent: NonZeroU32::new(20).unwrap(),
+                    ..Default::default()
+                }),
             }
         );
         assert_eq!(
@@ -572,7 +591,7 @@ mod tests {
             JaegerConfig {
                 enable_endpoint: true,
                 lookback_period_hours: NonZeroU64::new(24).unwrap(),
-                max_trace_duration_secs: NonZeroU64::new(600).unwrap(),
+                max_trace_duration: HumanDuration::from_secs(600),
                 max_fetch_spans: NonZeroU64::new(1_000).unwrap(),
             }
         );
@@ -1061,9 +1080,53 @@ mod tests {
             max_trace_duration_secs: 0
         "#;
         let error = serde_yaml::from_str::<JaegerConfig>(jaeger_config_yaml).unwrap_err();
-        assert!(error
-            .to_string()
-            .contains("max_trace_duration_secs: invalid value: integer `0`"))
+        assert!(error.to_string().contains("duration must be non-zero"));
+
+        let jaeger_config_yaml = r#"
+            enable_endpoint: true
+            max_trace_duration: 0s
+        "#;
+        let error = serde_yaml::from_str::<JaegerConfig>(jaeger_config_yaml).unwrap_err();
+        assert!(error.to_string().contains("duration must be non-zero"));
+    }
+
+    #[test]
+    fn test_searcher_config_accepts_human_durations() {
+        let searcher_config_yaml = r#"
+            metastore_max_staleness: 2m
+            list_splits_timeout: 30s
+            search_response_cache:
+              ttl: 30s
+        "#;
+        let searcher_config = serde_yaml::from_str::<SearcherConfig>(searcher_config_yaml).unwrap();
+        assert_eq!(
+            searcher_config.metastore_max_staleness(),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            searcher_config.list_splits_timeout(),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            searcher_config.search_response_cache.unwrap().ttl(),
+            Duration::from_secs(30)
+        );
+
+        // The former `*_secs` fields are still accepted.
+        let searcher_config_yaml = r#"
+            metastore_max_staleness_secs: 120
+            search_response_cache:
+              ttl_secs: 30
+        "#;
+        let searcher_config = serde_yaml::from_str::<SearcherConfig>(searcher_config_yaml).unwrap();
+        assert_eq!(
+            searcher_config.metastore_max_staleness(),
+            Duration::from_secs(120)
+        );
+        assert_eq!(
+            searcher_config.search_response_cache.unwrap().ttl(),
+            Duration::from_secs(30)
+        );
     }
 
     #[tokio::test]
@@ -1222,4 +1285,24 @@ mod tests {
         .to_string();
         assert!(error_message.contains("replication factor"));
     }
+
+    #[tokio::test]
+    async fn test_node_config_validates_leaf_search_timeout() {
+        let node_config_yaml = r#"
+            version: 0.6
+            searcher:
+              leaf_search_timeout:
+                min_timeout: 10s
+                max_timeout: 5s
+        "#;
+        let error_message = load_node_config_with_env(
+            ConfigFormat::Yaml,
+            node_config_yaml.as_bytes(),
+            &Default::default(),
+        )
+        .await
+        .unwrap_err()
+        .to_string();
+        assert!(error_message.contains("min_timeout"));
+    }
 }
@@ -19,11 +19,13 @@
 
 pub(crate) mod serialize;
 
+use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 use bytes::Bytes;
+use bytesize::ByteSize;
 use quickwit_common::is_false;
 use quickwit_common::uri::Uri;
 use quickwit_proto::metastore::SourceType;
@@ -93,6 +95,7 @@ impl SourceConfig {
     pub fn source_type(&self) -> SourceType {
         match self.source_params {
             SourceParams::File(_) => SourceType::File,
+            SourceParams::FileTail(_) => SourceType::FileTail,
             SourceParams::GcpPubSub(_) => SourceType::GcpPubsub,
             SourceParams::Ingest => SourceType::IngestV2,
             SourceParams::IngestApi => SourceType::IngestV1,
@@ -100,6 +103,7 @@ impl SourceConfig {
             SourceParams::Kafka(_) => SourceType::Kafka,
             SourceParams::Kinesis(_) => SourceType::Kinesis,
             SourceParams::Pulsar(_) => SourceType::Pulsar,
+            SourceParams::Push(_) => SourceType::Push,
             SourceParams::Vec(_) => SourceType::Vec,
             SourceParams::Void(_) => SourceType::Void,
         }
@@ -109,6 +113,7 @@ impl SourceConfig {
     pub fn params(&self) -> JsonValue {
         match &self.source_params {
             SourceParams::File(params) => serde_json::to_value(params),
+            SourceParams::FileTail(params) => serde_json::to_value(params),
             SourceParams::GcpPubSub(params) => serde_json::to_value(params),
             SourceParams::Ingest => serde_json::to_value(()),
             SourceParams::IngestApi => serde_json::to_value(()),
@@ -116,6 +121,7 @@ impl SourceConfig {
             SourceParams::Kafka(params) => serde_json::to_value(params),
             SourceParams::Kinesis(params) => serde_json::to_value(params),
             SourceParams::Pulsar(params) => serde_json::to_value(params),
+            SourceParams::Push(params) => serde_json::to_value(params),
             SourceParams::Vec(params) => serde_json::to_value(params),
             SourceParams::Void(params) => serde_json::to_value(params),
         }
@@ -229,6 +235,7 @@ impl FromStr for SourceInputFormat {
 #[serde(tag = "source_type", content = "params", rename_all = "snake_case")]
 pub enum SourceParams {
     File(FileSourceParams),
+    FileTail(FileTailSourceParams),
     GcpPubSub(GcpPubSubSourceParams),
     Ingest,
     #[serde(rename = "ingest-api")]
@@ -238,6 +245,7 @@ pub enum SourceParams {
     Kafka(KafkaSourceParams),
     Kinesis(KinesisSourceParams),
     Pulsar(PulsarSourceParams),
+    Push(PushSourceParams),
     Vec(VecSourceParams),
     Void(VoidSourceParams),
 }
@@ -265,6 +273,33 @@ pub struct FileSourceParams {
     #[serde(default)]
     #[serde(deserialize_with = "absolute_filepath_from_str")]
     pub filepath: Option<PathBuf>, //< If None read from stdin.
+    /// Compression of the file. If not set, it is inferred from the file extension.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compression: Option<FileSourceCompression>,
+    /// Maximum number of input bytes read by the source, after which it stops as if it had
+    /// reached the end of the file. Unlimited if not set.
+    #[schema(value_type = Option<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_num_bytes: Option<ByteSize>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum FileSourceCompression {
+    Gzip,
+}
+
+impl FromStr for FileSourceCompression {
+    type Err = String;
+
+    fn from_str(compression_str: &str) -> Result<Self, String> {
+        match compression_str {
+            "gzip" => Ok(Self::Gzip),
+            unknown => Err(format!("unknown file compression: `{unknown}`")),
+        }
+    }
 }
 
 /// Deserializing as an URI first to validate the input.
@@ -286,11 +321,77 @@ impl FileSourceParams {
     pub fn file<P: AsRef<Path>>(filepath: P) -> Self {
         FileSourceParams {
             filepath: Some(filepath.as_ref().to_path_buf()),
+            compression: None,
+            max_num_bytes: None,
         }
     }
 
     pub fn stdin() -> Self {
-        FileSourceParams { filepath: None }
+        FileSourceParams {
+            filepath: None,
+            compression: None,
+            max_num_bytes: None,
+        }
+    }
+
+    pub fn with_compression(mut self, compression_opt: Option<FileSourceCompression>) -> Self {
+        self.compression = compression_opt;
+        self
+    }
+
+    pub fn with_max_num_bytes(mut self, max_num_bytes_opt: Option<ByteSize>) -> Self {
+        self.max_num_bytes = max_num_bytes_opt;
+        self
+    }
+
+    /// Returns the compression of the file, inferring gzip from a `.gz` extension if it is not
+    /// set explicitly.
+    pub fn effective_compression(&self) -> Option<FileSourceCompression> {
+        if self.compression.is_some() {
+            return self.compression;
+        }
+        let extension = self.filepath.as_ref()?.extension()?;
+
+        if extension == "gz" {
+            Some(FileSourceCompression::Gzip)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct FileTailSourceParams {
+    /// Path of the file to tail. The file is local to the indexer running the source, so it must
+    /// be available at this path on every indexer the source may be scheduled on.
+    #[schema(value_type = String)]
+    pub path: PathBuf,
+    /// Size of new lines after which a batch is sent to the indexer.
+    #[schema(value_type = String, default = "500KB")]
+    #[serde(default = "FileTailSourceParams::default_max_batch_bytes")]
+    pub max_batch_bytes: ByteSize,
+    /// Lines longer than this size are dropped.
+    #[schema(value_type = String, default = "10MB")]
+    #[serde(default = "FileTailSourceParams::default_max_line_bytes")]
+    pub max_line_bytes: ByteSize,
+}
+
+impl FileTailSourceParams {
+    pub fn new<P: AsRef<Path>>(path: P) -> Self {
+        FileTailSourceParams {
+            path: path.as_ref().to_path_buf(),
+            max_batch_bytes: Self::default_max_batch_bytes(),
+            max_line_bytes: Self::default_max_line_bytes(),
+        }
+    }
+
+    fn default_max_batch_bytes() -> ByteSize {
+        ByteSize::kb(500)
+    }
+
+    fn default_max_line_bytes() -> ByteSize {
+        ByteSize::mb(10)
     }
 }
 
@@ -449,6 +550,29 @@ fn default_consumer_name() -> String {
     "quickwit".to_string()
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct PushSourceParams {
+    /// Address on which the indexer running the source listens for the documents pushed to the
+    /// index, e.g. `0.0.0.0:7290`.
+    #[schema(value_type = String)]
+    pub bind_addr: SocketAddr,
+    /// Maximum number of documents accepted in a single request. Unbounded if not set.
+    #[schema(value_type = Option<usize>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_batch_size_in_docs: Option<NonZeroUsize>,
+}
+
+impl PushSourceParams {
+    pub fn new(bind_addr: SocketAddr) -> Self {
+        PushSourceParams {
+            bind_addr,
+            max_batch_size_in_docs: None,
+        }
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct TransformConfig {
@@ -752,6 +876,56 @@ mod tests {
                 .unwrap_err();
             assert!(error.to_string().contains("supports multiple pipelines"));
         }
+        {
+            let content = r#"
+            {
+                "version": "0.6",
+                "source_id": "app-logs-file-tail-source",
+                "desired_num_pipelines": 2,
+                "max_num_pipelines_per_indexer": 1,
+                "source_type": "file_tail",
+                "params": {
+                    "path": "/var/log/app.log"
+                }
+            }
+            "#;
+            let error = load_source_config_from_user_config(ConfigFormat::Json, content.as_bytes())
+                .unwrap_err();
+            assert!(error.to_string().contains("supports a single pipeline"));
+        }
+        {
+            let content = r#"
+            {
+                "version": "0.6",
+                "source_id": "app-logs-file-tail-source",
+                "source_type": "file_tail",
+                "params": {
+                    "path": "/var/log/app.log",
+                    "max_line_bytes": 0
+                }
+            }
+            "#;
+            let error = load_source_config_from_user_config(ConfigFormat::Json, content.as_bytes())
+                .unwrap_err();
+            assert!(error.to_string().contains("`max_line_bytes`"));
+        }
+        {
+            let content = r#"
+            {
+                "version": "0.6",
+                "source_id": "app-logs-push-source",
+                "desired_num_pipelines": 2,
+                "max_num_pipelines_per_indexer": 1,
+                "source_type": "push",
+                "params": {
+                    "bind_addr": "0.0.0.0:7290"
+                }
+            }
+            "#;
+            let error = load_source_config_from_user_config(ConfigFormat::Json, content.as_bytes())
+                .unwrap_err();
+            assert!(error.to_string().contains("supports a single pipeline"));
+        }
     }
 
     #[tokio::test]
@@ -809,6 +983,114 @@ mod tests {
                 file_params.filepath.unwrap().as_path(),
                 Path::new(uri.as_str())
             );
+            assert!(file_params.compression.is_none());
+        }
+        {
+            let yaml = r#"
+                filepath: source-path.json
+                compression: gzip
+            "#;
+            let file_params = serde_yaml::from_str::<FileSourceParams>(yaml).unwrap();
+            assert_eq!(file_params.compression, Some(FileSourceCompression::Gzip));
+        }
+    }
+
+    #[test]
+    fn test_file_source_params_effective_compression() {
+        assert_eq!(
+            FileSourceParams::file("logs.json").effective_compression(),
+            None
+        );
+        assert_eq!(
+            FileSourceParams::file("logs.json.gz").effective_compression(),
+            Some(FileSourceCompression::Gzip)
+        );
+        assert_eq!(FileSourceParams::stdin().effective_compression(), None);
+        assert_eq!(
+            FileSourceParams::stdin()
+                .with_compression(Some(FileSourceCompression::Gzip))
+                .effective_compression(),
+            Some(FileSourceCompression::Gzip)
+        );
+    }
+
+    #[test]
+    fn test_file_tail_source_params_serialization() {
+        {
+            let yaml = r#"
+                path: /var/log/app.log
+            "#;
+            let file_tail_params = serde_yaml::from_str::<FileTailSourceParams>(yaml).unwrap();
+            assert_eq!(
+                file_tail_params,
+                FileTailSourceParams::new("/var/log/app.log")
+            );
+            assert_eq!(file_tail_params.max_batch_bytes, ByteSize::kb(500));
+            assert_eq!(file_tail_params.max_line_bytes, ByteSize::mb(10));
+        }
+        {
+            let yaml = r#"
+                path: /var/log/app.log
+                max_batch_bytes: 1000
+                max_line_bytes: 100
+            "#;
+            let file_tail_params = serde_yaml::from_str::<FileTailSourceParams>(yaml).unwrap();
+            assert_eq!(file_tail_params.max_batch_bytes, ByteSize(1000));
+            assert_eq!(file_tail_params.max_line_bytes, ByteSize(100));
+        }
+        {
+            let yaml = r#"
+                path: /var/log/app.log
+                max_batch_bytes: 1MB
+                max_line_bytes: 64KiB
+            "#;
+            let file_tail_params = serde_yaml::from_str::<FileTailSourceParams>(yaml).unwrap();
+            assert_eq!(file_tail_params.max_batch_bytes, ByteSize::mb(1));
+            assert_eq!(file_tail_params.max_line_bytes, ByteSize::kib(64));
+        }
+        {
+            let yaml = r#"
+                path: /var/log/app.log
+                filepath: /var/log/app.log
+            "#;
+            serde_yaml::from_str::<FileTailSourceParams>(yaml).unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_push_source_params_serialization() {
+        {
+            let yaml = r#"
+                bind_addr: 0.0.0.0:7290
+            "#;
+            let push_params = serde_yaml::from_str::<PushSourceParams>(yaml).unwrap();
+            assert_eq!(
+                push_params,
+                PushSourceParams::new("0.0.0.0:7290".parse().unwrap())
+            );
+            let push_params_json = serde_json::to_value(&push_params).unwrap();
+            assert!(push_params_json.get("max_batch_size_in_docs").is_none());
+        }
+        {
+            let yaml = r#"
+                bind_addr: 127.0.0.1:7290
+                max_batch_size_in_docs: 1000
+            "#;
+            let push_params = serde_yaml::from_str::<PushSourceParams>(yaml).unwrap();
+            assert_eq!(push_params.max_batch_size_in_docs, NonZeroUsize::new(1000));
+        }
+        {
+            let yaml = r#"
+                bind_addr: 127.0.0.1:7290
+                max_batch_size_in_docs: 0
+            "#;
+            serde_yaml::from_str::<PushSourceParams>(yaml).unwrap_err();
+        }
+        {
+            let yaml = r#"
+                bind_addr: localhost
+            "#;
+            serde_yaml::from_str::<PushSourceParams>(yaml).unwrap_err();
         }
     }
 
@@ -88,6 +88,38 @@ impl SourceConfigForSerialization {
                     )
                 }
             }
+            SourceParams::FileTail(file_tail_params) => {
+                if file_tail_params.max_batch_bytes.as_u64() == 0 {
+                    bail!(
+                        "source `{}` of type `file_tail` must have a strictly positive \
+                         `max_batch_bytes`",
+                        self.source_id
+                    )
+                }
+                if file_tail_params.max_line_bytes.as_u64() == 0 {
+                    bail!(
+                        "source `{}` of type `file_tail` must have a strictly positive \
+                         `max_line_bytes`",
+                        self.source_id
+                    )
+                }
+                // Several pipelines would read the same lines of the same file.
+                if self.desired_num_pipelines > 1 || self.max_num_pipelines_per_indexer > 1 {
+                    bail!(
+                        "source `{}` of type `file_tail` supports a single pipeline",
+                        self.source_id
+                    )
+                }
+            }
+            SourceParams::Push(_) => {
+                // Several pipelines would listen on the same address.
+                if self.desired_num_pipelines > 1 || self.max_num_pipelines_per_indexer > 1 {
+                    bail!(
+                        "source `{}` of type `push` supports a single pipeline",
+                        self.source_id
+                    )
+                }
+            }
             SourceParams::Kafka(_) | SourceParams::Kinesis(_) | SourceParams::Pulsar(_) => {
                 // TODO consider any validation opportunity
             }
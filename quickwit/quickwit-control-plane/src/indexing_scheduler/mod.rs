@@ -154,11 +154,13 @@ fn get_sources_to_schedule(model: &ControlPlaneModel) -> Vec<SourceToSchedule> {
                     },
                 });
             }
-            SourceType::Kafka
+            SourceType::FileTail
+            | SourceType::Kafka
             | SourceType::Kinesis
             | SourceType::GcpPubsub
             | SourceType::Nats
-            | SourceType::Pulsar => {
+            | SourceType::Pulsar
+            | SourceType::Push => {
                 sources.push(SourceToSchedule {
                     source_uid,
                     source_type: SourceToScheduleType::NonSharded {
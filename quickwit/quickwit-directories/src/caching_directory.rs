@@ -17,23 +17,315 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::ops::Range;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
 use std::{fmt, io};
 
 use async_trait::async_trait;
-use quickwit_storage::ByteRangeCache;
+use lru::LruCache;
+use quickwit_storage::{AsyncDebouncer, ByteRangeCache};
 use tantivy::directory::error::OpenReadError;
 use tantivy::directory::{FileHandle, OwnedBytes};
 use tantivy::{Directory, HasLen};
 
+/// Occupancy of a [`SliceCache`].
+#[derive(Clone, Copy, Debug, Default)]
+struct SliceCacheUsage {
+    num_bytes: u64,
+    num_items: u64,
+    num_evictions: u64,
+}
+
+/// A cache of byte ranges of the files of a directory.
+trait SliceCache: Send + Sync + 'static {
+    fn get_slice(&self, path: &Path, byte_range: Range<usize>) -> Option<OwnedBytes>;
+
+    fn put_slice(&self, path: PathBuf, byte_range: Range<usize>, bytes: OwnedBytes);
+
+    fn usage(&self) -> SliceCacheUsage;
+}
+
+impl SliceCache for ByteRangeCache {
+    fn get_slice(&self, path: &Path, byte_range: Range<usize>) -> Option<OwnedBytes> {
+        ByteRangeCache::get_slice(self, path, byte_range)
+    }
+
+    fn put_slice(&self, path: PathBuf, byte_range: Range<usize>, bytes: OwnedBytes) {
+        ByteRangeCache::put_slice(self, path, byte_range, bytes)
+    }
+
+    fn usage(&self) -> SliceCacheUsage {
+        SliceCacheUsage {
+            num_bytes: self.num_bytes(),
+            num_items: self.num_items(),
+            // The byte range cache never evicts anything.
+            num_evictions: 0,
+        }
+    }
+}
+
+/// Behavior of a bounded [`CachingDirectory`] when a new slice does not fit in its cache.
+///
+/// Slices larger than the capacity of the cache are never cached.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum OverCapacityPolicy {
+    /// Evicts the least recently used slices until the new slice fits.
+    #[default]
+    EvictLru,
+    /// Keeps the cached slices and does not cache the new slice.
+    SkipCaching,
+}
+
+struct NeedMutLruSliceCache {
+    lru_cache: LruCache<(PathBuf, Range<usize>), OwnedBytes>,
+    num_bytes: usize,
+    num_evictions: u64,
+    capacity_in_bytes: usize,
+    max_num_items: NonZeroUsize,
+    over_capacity_policy: OverCapacityPolicy,
+}
+
+impl NeedMutLruSliceCache {
+    fn fits(&self, num_bytes: usize) -> bool {
+        self.num_bytes + num_bytes <= self.capacity_in_bytes
+            && self.lru_cache.len() < self.max_num_items.get()
+    }
+
+    fn put(&mut self, key: (PathBuf, Range<usize>), bytes: OwnedBytes) {
+        if bytes.len() > self.capacity_in_bytes {
+            // The slice does not fit in the cache. We simply don't store it.
+            return;
+        }
+        // Checked before removing the previous slice of the key, so that a slice that does not
+        // fit leaves the cache unchanged.
+        if self.over_capacity_policy == OverCapacityPolicy::SkipCaching && !self.fits(bytes.len()) {
+            return;
+        }
+        if let Some(previous_bytes) = self.lru_cache.pop(&key) {
+            self.num_bytes -= previous_bytes.len();
+        }
+        while !self.fits(bytes.len()) {
+            let Some((_, evicted_bytes)) = self.lru_cache.pop_lru() else {
+                break;
+            };
+            self.num_bytes -= evicted_bytes.len();
+            self.num_evictions += 1;
+        }
+        self.num_bytes += bytes.len();
+        self.lru_cache.put(key, bytes);
+    }
+}
+
+/// A slice cache that evicts the least recently used byte ranges once its byte budget or its
+/// maximum number of items is exceeded.
+///
+/// Unlike `MemorySizedCache`, recently accessed entries are not protected from eviction, so that
+/// the hot byte ranges of a directory (e.g. the term dictionary spine) stay in the cache.
+struct LruSliceCache {
+    inner: Mutex<NeedMutLruSliceCache>,
+}
+
+impl LruSliceCache {
+    fn with_capacity(
+        capacity_in_bytes: usize,
+        max_num_items: NonZeroUsize,
+        over_capacity_policy: OverCapacityPolicy,
+    ) -> Self {
+        LruSliceCache {
+            inner: Mutex::new(NeedMutLruSliceCache {
+                // The number of items is bounded in `NeedMutLruSliceCache::put`.
+                lru_cache: LruCache::unbounded(),
+                num_bytes: 0,
+                num_evictions: 0,
+                capacity_in_bytes,
+                max_num_items,
+                over_capacity_policy,
+            }),
+        }
+    }
+}
+
+impl SliceCache for LruSliceCache {
+    fn get_slice(&self, path: &Path, byte_range: Range<usize>) -> Option<OwnedBytes> {
+        let key = (path.to_path_buf(), byte_range);
+        self.inner.lock().unwrap().lru_cache.get(&key).cloned()
+    }
+
+    fn put_slice(&self, path: PathBuf, byte_range: Range<usize>, bytes: OwnedBytes) {
+        self.inner.lock().unwrap().put((path, byte_range), bytes);
+    }
+
+    fn usage(&self) -> SliceCacheUsage {
+        let inner = self.inner.lock().unwrap();
+        SliceCacheUsage {
+            num_bytes: inner.num_bytes as u64,
+            num_items: inner.lru_cache.len() as u64,
+            num_evictions: inner.num_evictions,
+        }
+    }
+}
+
+/// Statistics of the accesses to the cache of a [`CachingDirectory`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CacheStats {
+    /// Number of reads served from the cache.
+    pub num_hits: u64,
+    /// Number of reads served by the underlying directory.
+    pub num_misses: u64,
+    /// Number of bytes served from the cache.
+    pub num_bytes_hit: u64,
+    /// Number of slices evicted from the cache to make room for new ones.
+    pub num_evictions: u64,
+    /// Number of bytes currently held by the cache.
+    pub num_bytes: u64,
+    /// Number of slices currently held by the cache.
+    pub num_items: u64,
+}
+
+impl CacheStats {
+    /// Returns the ratio of reads served from the cache, or `None` if nothing was read yet.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let num_reads = self.num_hits + self.num_misses;
+        if num_reads == 0 {
+            return None;
+        }
+        Some(self.num_hits as f64 / num_reads as f64)
+    }
+}
+
+#[derive(Default)]
+struct CacheCounters {
+    num_hits: AtomicU64,
+    num_misses: AtomicU64,
+    num_bytes_hit: AtomicU64,
+}
+
+impl CacheCounters {
+    fn record_hit(&self, num_bytes: usize) {
+        self.num_hits.fetch_add(1, Ordering::Relaxed);
+        self.num_bytes_hit
+            .fetch_add(num_bytes as u64, Ordering::Relaxed);
+    }
+
+    fn record_miss(&self) {
+        self.num_misses.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn stats(&self) -> CacheStats {
+        CacheStats {
+            num_hits: self.num_hits.load(Ordering::Relaxed),
+            num_misses: self.num_misses.load(Ordering::Relaxed),
+            num_bytes_hit: self.num_bytes_hit.load(Ordering::Relaxed),
+            ..Default::default()
+        }
+    }
+}
+
+type SliceKey = (PathBuf, Range<usize>);
+
+/// `io::Error` is not `Clone`, so the outcome of a deduplicated read is shared behind an `Arc`.
+type SharedReadResult = Result<OwnedBytes, Arc<io::Error>>;
+
+#[derive(Default)]
+struct InFlightRead {
+    result_opt: Mutex<Option<SharedReadResult>>,
+    result_available: Condvar,
+}
+
+/// Deduplicates concurrent synchronous reads of the same byte range.
+///
+/// The first reader of a byte range reads it, while the other ones block until its result is
+/// available. This is the blocking counterpart of the `AsyncDebouncer` used for asynchronous
+/// reads.
+#[derive(Default)]
+struct SyncReadCoalescer {
+    in_flight_reads: Mutex<HashMap<SliceKey, Arc<InFlightRead>>>,
+}
+
+impl SyncReadCoalescer {
+    fn get_or_read(
+        &self,
+        key: SliceKey,
+        read_fn: impl FnOnce() -> SharedReadResult,
+    ) -> SharedReadResult {
+        let (in_flight_read, is_first_reader) = {
+            let mut in_flight_reads = self.in_flight_reads.lock().unwrap();
+            match in_flight_reads.entry(key.clone()) {
+                Entry::Occupied(entry) => (entry.get().clone(), false),
+                Entry::Vacant(entry) => (entry.insert(Arc::default()).clone(), true),
+            }
+        };
+        if !is_first_reader {
+            let mut result_opt_guard = in_flight_read.result_opt.lock().unwrap();
+            loop {
+                if let Some(result) = result_opt_guard.as_ref() {
+                    return result.clone();
+                }
+                result_opt_guard = in_flight_read
+                    .result_available
+                    .wait(result_opt_guard)
+                    .unwrap();
+            }
+        }
+        let mut publish_guard = PublishReadOnDrop {
+            coalescer: self,
+            key,
+            in_flight_read,
+            result_opt: None,
+        };
+        let result = read_fn();
+        publish_guard.result_opt = Some(result.clone());
+        result
+    }
+
+    #[cfg(test)]
+    fn len(&self) -> usize {
+        self.in_flight_reads.lock().unwrap().len()
+    }
+}
+
+/// Hands the result of a read over to the blocked readers, even if the read panicked.
+struct PublishReadOnDrop<'a> {
+    coalescer: &'a SyncReadCoalescer,
+    key: SliceKey,
+    in_flight_read: Arc<InFlightRead>,
+    result_opt: Option<SharedReadResult>,
+}
+
+impl Drop for PublishReadOnDrop<'_> {
+    fn drop(&mut self) {
+        let result = self.result_opt.take().unwrap_or_else(|| {
+            Err(Arc::new(io::Error::new(
+                io::ErrorKind::Other,
+                "concurrent read of the same byte range panicked",
+            )))
+        });
+        self.coalescer
+            .in_flight_reads
+            .lock()
+            .unwrap()
+            .remove(&self.key);
+        *self.in_flight_read.result_opt.lock().unwrap() = Some(result);
+        self.in_flight_read.result_available.notify_all();
+    }
+}
+
 /// The caching directory is a simple cache that wraps another directory.
+///
+/// Concurrent reads of the same byte range, synchronous or asynchronous, are deduplicated, so
+/// that they share a single read of the underlying directory.
 #[derive(Clone)]
 pub struct CachingDirectory {
     underlying: Arc<dyn Directory>,
-    // TODO fixme: that's a pretty ugly cache we have here.
-    cache: Arc<ByteRangeCache>,
+    cache: Arc<dyn SliceCache>,
+    slice_debouncer: Arc<AsyncDebouncer<SliceKey, SharedReadResult>>,
+    sync_read_coalescer: Arc<SyncReadCoalescer>,
+    counters: Arc<CacheCounters>,
 }
 
 impl CachingDirectory {
@@ -47,6 +339,72 @@ impl CachingDirectory {
             cache: Arc::new(ByteRangeCache::with_infinite_capacity(
                 &quickwit_storage::STORAGE_METRICS.shortlived_cache,
             )),
+            slice_debouncer: Arc::default(),
+            sync_read_coalescer: Arc::default(),
+            counters: Arc::default(),
+        }
+    }
+
+    /// Creates a new CachingDirectory holding at most `capacity_in_bytes` bytes.
+    ///
+    /// The least recently used byte ranges are evicted first.
+    pub fn new_with_capacity_in_bytes(
+        underlying: Arc<dyn Directory>,
+        capacity_in_bytes: usize,
+    ) -> CachingDirectory {
+        CachingDirectory::new_with_lru_capacity(underlying, capacity_in_bytes, NonZeroUsize::MAX)
+    }
+
+    /// Creates a new CachingDirectory holding at most `capacity_in_bytes` bytes and
+    /// `max_num_items` byte ranges.
+    ///
+    /// The least recently used byte ranges are evicted first.
+    pub fn new_with_lru_capacity(
+        underlying: Arc<dyn Directory>,
+        capacity_in_bytes: usize,
+        max_num_items: NonZeroUsize,
+    ) -> CachingDirectory {
+        CachingDirectory::new_with_over_capacity_policy(
+            underlying,
+            capacity_in_bytes,
+            max_num_items,
+            OverCapacityPolicy::EvictLru,
+        )
+    }
+
+    /// Creates a new CachingDirectory holding at most `capacity_in_bytes` bytes and
+    /// `max_num_items` byte ranges, which handles the byte ranges that do not fit in the cache
+    /// according to `over_capacity_policy`.
+    pub fn new_with_over_capacity_policy(
+        underlying: Arc<dyn Directory>,
+        capacity_in_bytes: usize,
+        max_num_items: NonZeroUsize,
+        over_capacity_policy: OverCapacityPolicy,
+    ) -> CachingDirectory {
+        CachingDirectory {
+            underlying,
+            cache: Arc::new(LruSliceCache::with_capacity(
+                capacity_in_bytes,
+                max_num_items,
+                over_capacity_policy,
+            )),
+            slice_debouncer: Arc::default(),
+            sync_read_coalescer: Arc::default(),
+            counters: Arc::default(),
+        }
+    }
+
+    /// Returns the cache statistics accumulated since the creation of the directory.
+    ///
+    /// Concurrent reads of a byte range missing from the cache are served by a single read of the
+    /// underlying directory, but each of them counts as a miss.
+    pub fn stats(&self) -> CacheStats {
+        let usage = self.cache.usage();
+        CacheStats {
+            num_evictions: usage.num_evictions,
+            num_bytes: usage.num_bytes,
+            num_items: usage.num_items,
+            ..self.counters.stats()
         }
     }
 }
@@ -59,7 +417,10 @@ impl fmt::Debug for CachingDirectory {
 
 struct CachingFileHandle {
     path: PathBuf,
-    cache: Arc<ByteRangeCache>,
+    cache: Arc<dyn SliceCache>,
+    slice_debouncer: Arc<AsyncDebouncer<SliceKey, SharedReadResult>>,
+    sync_read_coalescer: Arc<SyncReadCoalescer>,
+    counters: Arc<CacheCounters>,
     underlying_filehandle: Arc<dyn FileHandle>,
 }
 
@@ -78,25 +439,50 @@ impl fmt::Debug for CachingFileHandle {
 impl FileHandle for CachingFileHandle {
     fn read_bytes(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
         if let Some(bytes) = self.cache.get_slice(&self.path, byte_range.clone()) {
+            self.counters.record_hit(bytes.len());
             return Ok(bytes);
         }
-        let owned_bytes = self.underlying_filehandle.read_bytes(byte_range.clone())?;
-        self.cache
-            .put_slice(self.path.clone(), byte_range, owned_bytes.clone());
-        Ok(owned_bytes)
+        self.counters.record_miss();
+        let key = (self.path.clone(), byte_range.clone());
+        self.sync_read_coalescer
+            .get_or_read(key, || {
+                // The slice may have been cached by a concurrent read that completed since the
+                // lookup above.
+                if let Some(owned_bytes) = self.cache.get_slice(&self.path, byte_range.clone()) {
+                    return Ok(owned_bytes);
+                }
+                let owned_bytes = self
+                    .underlying_filehandle
+                    .read_bytes(byte_range.clone())
+                    .map_err(Arc::new)?;
+                self.cache
+                    .put_slice(self.path.clone(), byte_range, owned_bytes.clone());
+                Ok(owned_bytes)
+            })
+            .map_err(|io_error| io::Error::new(io_error.kind(), io_error))
     }
 
     async fn read_bytes_async(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
         if let Some(owned_bytes) = self.cache.get_slice(&self.path, byte_range.clone()) {
+            self.counters.record_hit(owned_bytes.len());
             return Ok(owned_bytes);
         }
-        let read_bytes = self
-            .underlying_filehandle
-            .read_bytes_async(byte_range.clone())
-            .await?;
-        self.cache
-            .put_slice(self.path.clone(), byte_range, read_bytes.clone());
-        Ok(read_bytes)
+        self.counters.record_miss();
+        let key = (self.path.clone(), byte_range.clone());
+        let path = self.path.clone();
+        let cache = self.cache.clone();
+        let underlying_filehandle = self.underlying_filehandle.clone();
+        self.slice_debouncer
+            .get_or_create(key, move || async move {
+                let read_bytes = underlying_filehandle
+                    .read_bytes_async(byte_range.clone())
+                    .await
+                    .map_err(Arc::new)?;
+                cache.put_slice(path, byte_range, read_bytes.clone());
+                Ok(read_bytes)
+            })
+            .await
+            .map_err(|io_error| io::Error::new(io_error.kind(), io_error))
     }
 }
 
@@ -119,6 +505,9 @@ impl Directory for CachingDirectory {
         let caching_file_handle = CachingFileHandle {
             path: path.to_path_buf(),
             cache: self.cache.clone(),
+            slice_debouncer: self.slice_debouncer.clone(),
+            sync_read_coalescer: self.sync_read_coalescer.clone(),
+            counters: self.counters.clone(),
             underlying_filehandle,
         };
         Ok(Arc::new(caching_file_handle))
@@ -139,13 +528,22 @@ impl Directory for CachingDirectory {
 #[cfg(test)]
 mod tests {
 
-    use std::path::Path;
-    use std::sync::Arc;
+    use std::num::NonZeroUsize;
+    use std::ops::Range;
+    use std::path::{Path, PathBuf};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Barrier};
+    use std::time::Duration;
+    use std::{fmt, io};
 
-    use tantivy::directory::RamDirectory;
-    use tantivy::Directory;
+    use async_trait::async_trait;
+    use tantivy::directory::{FileHandle, OwnedBytes, RamDirectory};
+    use tantivy::{Directory, HasLen};
 
-    use super::CachingDirectory;
+    use super::{
+        CacheStats, CachingDirectory, CachingFileHandle, LruSliceCache, OverCapacityPolicy,
+        SliceCache,
+    };
     use crate::DebugProxyDirectory;
 
     #[test]
@@ -155,9 +553,329 @@ mod tests {
         ram_directory.atomic_write(test_path, &b"test"[..])?;
         let debug_proxy_directory = Arc::new(DebugProxyDirectory::wrap(ram_directory));
         let caching_directory = CachingDirectory::new_unbounded(debug_proxy_directory.clone());
+        assert_eq!(caching_directory.stats().hit_ratio(), None);
         caching_directory.atomic_read(test_path)?;
         caching_directory.atomic_read(test_path)?;
         assert_eq!(debug_proxy_directory.drain_read_operations().count(), 1);
+        assert_eq!(
+            caching_directory.stats(),
+            CacheStats {
+                num_hits: 1,
+                num_misses: 1,
+                num_bytes_hit: 4,
+                num_evictions: 0,
+                num_bytes: 4,
+                num_items: 1,
+            }
+        );
+        assert_eq!(caching_directory.stats().hit_ratio(), Some(0.5));
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_directory_lru_evicts_by_bytes() -> tantivy::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let path_a = Path::new("a");
+        let path_b = Path::new("b");
+        let path_c = Path::new("c");
+        ram_directory.atomic_write(path_a, &b"aaaa"[..])?;
+        ram_directory.atomic_write(path_b, &b"bbbb"[..])?;
+        ram_directory.atomic_write(path_c, &b"cccc"[..])?;
+        let debug_proxy_directory = Arc::new(DebugProxyDirectory::wrap(ram_directory));
+        let caching_directory =
+            CachingDirectory::new_with_capacity_in_bytes(debug_proxy_directory.clone(), 8);
+        caching_directory.atomic_read(path_a)?;
+        caching_directory.atomic_read(path_b)?;
+        // `a` becomes the most recently used entry.
+        caching_directory.atomic_read(path_a)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 2);
+
+        // Exceeds the byte budget and evicts `b`, the least recently used entry.
+        caching_directory.atomic_read(path_c)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 1);
+        let cache_stats = caching_directory.stats();
+        assert_eq!(cache_stats.num_evictions, 1);
+        assert_eq!(cache_stats.num_bytes, 8);
+        assert_eq!(cache_stats.num_items, 2);
+
+        caching_directory.atomic_read(path_a)?;
+        caching_directory.atomic_read(path_c)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 0);
+
+        caching_directory.atomic_read(path_b)?;
+        let read_operations: Vec<_> = debug_proxy_directory.drain_read_operations().collect();
+        assert_eq!(read_operations.len(), 1);
+        assert_eq!(read_operations[0].path, path_b);
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_directory_lru_evicts_by_num_items() -> tantivy::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let path_a = Path::new("a");
+        let path_b = Path::new("b");
+        ram_directory.atomic_write(path_a, &b"aaaa"[..])?;
+        ram_directory.atomic_write(path_b, &b"bbbb"[..])?;
+        let debug_proxy_directory = Arc::new(DebugProxyDirectory::wrap(ram_directory));
+        let caching_directory = CachingDirectory::new_with_lru_capacity(
+            debug_proxy_directory.clone(),
+            1_000,
+            NonZeroUsize::new(1).unwrap(),
+        );
+        caching_directory.atomic_read(path_a)?;
+        caching_directory.atomic_read(path_a)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 1);
+
+        caching_directory.atomic_read(path_b)?;
+        caching_directory.atomic_read(path_a)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_caching_directory_skip_caching_over_capacity() -> tantivy::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let path_a = Path::new("a");
+        let path_b = Path::new("b");
+        let path_c = Path::new("c");
+        let path_large = Path::new("large");
+        ram_directory.atomic_write(path_a, &b"aaaa"[..])?;
+        ram_directory.atomic_write(path_b, &b"bbbb"[..])?;
+        ram_directory.atomic_write(path_c, &b"cccc"[..])?;
+        ram_directory.atomic_write(path_large, &[0u8; 16][..])?;
+        let debug_proxy_directory = Arc::new(DebugProxyDirectory::wrap(ram_directory));
+        let caching_directory = CachingDirectory::new_with_over_capacity_policy(
+            debug_proxy_directory.clone(),
+            8,
+            NonZeroUsize::MAX,
+            OverCapacityPolicy::SkipCaching,
+        );
+        caching_directory.atomic_read(path_a)?;
+        caching_directory.atomic_read(path_b)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 2);
+
+        // Exceeds the byte budget: `c` is not cached and `a` and `b` are kept.
+        caching_directory.atomic_read(path_c)?;
+        caching_directory.atomic_read(path_c)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 2);
+        caching_directory.atomic_read(path_a)?;
+        caching_directory.atomic_read(path_b)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 0);
+
+        // Larger than the capacity of the cache.
+        caching_directory.atomic_read(path_large)?;
+        caching_directory.atomic_read(path_large)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 2);
+
+        assert_eq!(
+            caching_directory.stats(),
+            CacheStats {
+                num_hits: 2,
+                num_misses: 6,
+                num_bytes_hit: 8,
+                num_evictions: 0,
+                num_bytes: 8,
+                num_items: 2,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lru_slice_cache_skip_caching_keeps_previous_slice() {
+        let slice_cache =
+            LruSliceCache::with_capacity(8, NonZeroUsize::MAX, OverCapacityPolicy::SkipCaching);
+        let path = PathBuf::from("a");
+        slice_cache.put_slice(path.clone(), 0..4, OwnedBytes::new(b"aaaa".to_vec()));
+        slice_cache.put_slice(PathBuf::from("b"), 0..4, OwnedBytes::new(b"bbbb".to_vec()));
+
+        // The new slice does not fit: the previous slice of the key is kept.
+        slice_cache.put_slice(path.clone(), 0..4, OwnedBytes::new(b"AAAAAAAA".to_vec()));
+        let cached_bytes = slice_cache.get_slice(&path, 0..4).unwrap();
+        assert_eq!(cached_bytes.as_slice(), b"aaaa");
+
+        let usage = slice_cache.usage();
+        assert_eq!(usage.num_bytes, 8);
+        assert_eq!(usage.num_items, 2);
+        assert_eq!(usage.num_evictions, 0);
+    }
+
+    #[test]
+    fn test_caching_directory_evict_lru_over_capacity() -> tantivy::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let path_a = Path::new("a");
+        let path_b = Path::new("b");
+        let path_ab = Path::new("ab");
+        ram_directory.atomic_write(path_a, &b"aaaa"[..])?;
+        ram_directory.atomic_write(path_b, &b"bbbb"[..])?;
+        ram_directory.atomic_write(path_ab, &b"aaaabbbb"[..])?;
+        let debug_proxy_directory = Arc::new(DebugProxyDirectory::wrap(ram_directory));
+        let caching_directory = CachingDirectory::new_with_over_capacity_policy(
+            debug_proxy_directory.clone(),
+            8,
+            NonZeroUsize::MAX,
+            OverCapacityPolicy::EvictLru,
+        );
+        caching_directory.atomic_read(path_a)?;
+        caching_directory.atomic_read(path_b)?;
+
+        // Evicts both `a` and `b` to make room for `ab`.
+        caching_directory.atomic_read(path_ab)?;
+        caching_directory.atomic_read(path_ab)?;
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 3);
+
+        assert_eq!(
+            caching_directory.stats(),
+            CacheStats {
+                num_hits: 1,
+                num_misses: 3,
+                num_bytes_hit: 8,
+                num_evictions: 2,
+                num_bytes: 8,
+                num_items: 1,
+            }
+        );
+        Ok(())
+    }
+
+    #[derive(Default)]
+    struct SlowFileHandle {
+        num_reads: AtomicUsize,
+    }
+
+    impl fmt::Debug for SlowFileHandle {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "SlowFileHandle")
+        }
+    }
+
+    impl HasLen for SlowFileHandle {
+        fn len(&self) -> usize {
+            100
+        }
+    }
+
+    #[async_trait]
+    impl FileHandle for SlowFileHandle {
+        fn read_bytes(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
+            self.num_reads.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(10));
+            Ok(OwnedBytes::new(vec![0u8; byte_range.len()]))
+        }
+
+        async fn read_bytes_async(&self, byte_range: Range<usize>) -> io::Result<OwnedBytes> {
+            self.num_reads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(OwnedBytes::new(vec![0u8; byte_range.len()]))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caching_file_handle_deduplicates_concurrent_reads() {
+        let slow_file_handle = Arc::new(SlowFileHandle::default());
+        let caching_file_handle = CachingFileHandle {
+            path: PathBuf::from("test"),
+            cache: Arc::new(LruSliceCache::with_capacity(
+                1_000,
+                NonZeroUsize::MAX,
+                OverCapacityPolicy::EvictLru,
+            )),
+            slice_debouncer: Arc::default(),
+            sync_read_coalescer: Arc::default(),
+            counters: Arc::default(),
+            underlying_filehandle: slow_file_handle.clone(),
+        };
+        let (bytes_1, bytes_2, bytes_3) = tokio::join!(
+            caching_file_handle.read_bytes_async(0..10),
+            caching_file_handle.read_bytes_async(0..10),
+            caching_file_handle.read_bytes_async(10..20),
+        );
+        assert_eq!(bytes_1.unwrap().len(), 10);
+        assert_eq!(bytes_2.unwrap().len(), 10);
+        assert_eq!(bytes_3.unwrap().len(), 10);
+        assert_eq!(slow_file_handle.num_reads.load(Ordering::SeqCst), 2);
+        assert_eq!(caching_file_handle.slice_debouncer.len(), 0);
+
+        // The slice is now served from the cache.
+        caching_file_handle.read_bytes_async(0..10).await.unwrap();
+        assert_eq!(slow_file_handle.num_reads.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            caching_file_handle.counters.stats(),
+            CacheStats {
+                num_hits: 1,
+                num_misses: 3,
+                num_bytes_hit: 10,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn test_caching_file_handle_deduplicates_concurrent_sync_reads() {
+        let slow_file_handle = Arc::new(SlowFileHandle::default());
+        let caching_file_handle = CachingFileHandle {
+            path: PathBuf::from("test"),
+            cache: Arc::new(LruSliceCache::with_capacity(
+                1_000,
+                NonZeroUsize::MAX,
+                OverCapacityPolicy::EvictLru,
+            )),
+            slice_debouncer: Arc::default(),
+            sync_read_coalescer: Arc::default(),
+            counters: Arc::default(),
+            underlying_filehandle: slow_file_handle.clone(),
+        };
+        let barrier = Barrier::new(8);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    barrier.wait();
+                    let bytes = caching_file_handle.read_bytes(0..10).unwrap();
+                    assert_eq!(bytes.len(), 10);
+                });
+            }
+        });
+        assert_eq!(slow_file_handle.num_reads.load(Ordering::SeqCst), 1);
+        assert_eq!(caching_file_handle.sync_read_coalescer.len(), 0);
+    }
+
+    #[test]
+    fn test_caching_directory_deduplicates_concurrent_sync_reads() -> tantivy::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let test_path = Path::new("test");
+        ram_directory.atomic_write(test_path, &b"test"[..])?;
+        let debug_proxy_directory = Arc::new(DebugProxyDirectory::wrap(ram_directory));
+        let caching_directory =
+            CachingDirectory::new_with_capacity_in_bytes(debug_proxy_directory.clone(), 1_000);
+        let file_handle = caching_directory.get_file_handle(test_path)?;
+        let barrier = Barrier::new(8);
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                scope.spawn(|| {
+                    barrier.wait();
+                    let bytes = file_handle.read_bytes(0..4).unwrap();
+                    assert_eq!(bytes.as_slice(), b"test");
+                });
+            }
+        });
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 1);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_caching_directory_deduplicates_concurrent_async_reads() -> tantivy::Result<()> {
+        let ram_directory = RamDirectory::default();
+        let test_path = Path::new("test");
+        ram_directory.atomic_write(test_path, &b"test"[..])?;
+        let debug_proxy_directory = Arc::new(DebugProxyDirectory::wrap(ram_directory));
+        let caching_directory =
+            CachingDirectory::new_with_capacity_in_bytes(debug_proxy_directory.clone(), 1_000);
+        let file_handle = caching_directory.get_file_handle(test_path)?;
+        let read_futures = (0..8).map(|_| file_handle.read_bytes_async(0..4));
+        for bytes_res in futures::future::join_all(read_futures).await {
+            assert_eq!(bytes_res.unwrap().as_slice(), b"test");
+        }
+        assert_eq!(debug_proxy_directory.drain_read_operations().count(), 1);
         Ok(())
     }
 }
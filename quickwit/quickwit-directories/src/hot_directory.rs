@@ -552,7 +552,12 @@ pub fn write_hotcache<D: Directory>(
 
 #[cfg(test)]
 mod tests {
+    use tantivy::directory::RamDirectory;
+    use tantivy::schema::{Schema, STORED, TEXT};
+    use tantivy::{doc, IndexWriter};
+
     use super::*;
+    use crate::ReadOperation;
 
     #[test]
     fn test_empty_slice_cache_index() -> tantivy::Result<()> {
@@ -726,4 +731,53 @@ mod tests {
             b"name"
         );
     }
+
+    #[test]
+    fn test_write_hotcache_open_index_without_underlying_reads() -> anyhow::Result<()> {
+        let mut schema_builder = Schema::builder();
+        let title_field = schema_builder.add_text_field("title", TEXT | STORED);
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+
+        let ram_directory = RamDirectory::create();
+        let index = Index::create(ram_directory.clone(), schema, Default::default())?;
+        let mut index_writer: IndexWriter = index.writer_with_num_threads(1, 15_000_000)?;
+        index_writer.add_document(doc!(
+            title_field => "The Old Man and the Sea",
+            body_field => "He was an old man who fished alone in a skiff in the Gulf Stream.",
+        ))?;
+        index_writer.add_document(doc!(
+            title_field => "Of Mice and Men",
+            body_field => "A few miles south of Soledad, the Salinas River drops in close.",
+        ))?;
+        index_writer.commit()?;
+
+        let mut hotcache_bytes = Vec::new();
+        write_hotcache(ram_directory.clone(), &mut hotcache_bytes)?;
+
+        let debug_proxy_directory = DebugProxyDirectory::wrap(ram_directory);
+        let hot_directory = HotDirectory::open(
+            debug_proxy_directory.clone(),
+            OwnedBytes::new(hotcache_bytes),
+        )?;
+        let index = Index::open(hot_directory)?;
+        let reader: IndexReader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+        assert_eq!(searcher.num_docs(), 2);
+
+        for segment_reader in searcher.segment_readers() {
+            segment_reader.inverted_index(title_field)?;
+            segment_reader.inverted_index(body_field)?;
+        }
+        let read_operations: Vec<ReadOperation> =
+            debug_proxy_directory.drain_read_operations().collect();
+        assert!(
+            read_operations.is_empty(),
+            "opening the index should be served by the hotcache, got reads: {read_operations:?}"
+        );
+        Ok(())
+    }
 }
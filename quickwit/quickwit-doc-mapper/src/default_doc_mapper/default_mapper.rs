@@ -18,19 +18,21 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{BTreeMap, BTreeSet, HashSet};
-use std::num::NonZeroU32;
+use std::hash::Hasher;
+use std::num::{NonZeroU32, NonZeroU64};
 
 use anyhow::{bail, Context};
 use fnv::FnvHashSet;
-use quickwit_common::PathHasher;
+use quickwit_common::{HumanDuration, PathHasher, ValidationErrors};
 use quickwit_query::create_default_quickwit_tokenizer_manager;
 use quickwit_query::query_ast::QueryAst;
 use quickwit_query::tokenizers::TokenizerManager;
 use serde::{Deserialize, Serialize};
 use serde_json::{self, Value as JsonValue};
+use siphasher::sip::SipHasher;
 use tantivy::query::Query;
 use tantivy::schema::{
-    Field, FieldType, FieldValue, OwnedValue as TantivyValue, Schema, INDEXED, STORED,
+    Field, FieldType, FieldValue, OwnedValue as TantivyValue, Schema, Value, INDEXED, STORED,
 };
 use tantivy::TantivyDocument as Document;
 
@@ -43,8 +45,8 @@ use crate::doc_mapper::{JsonObject, Partition};
 use crate::query_builder::build_query;
 use crate::routing_expression::RoutingExpr;
 use crate::{
-    Cardinality, DocMapper, DocParsingError, Mode, QueryParserError, TokenizerEntry, WarmupInfo,
-    DYNAMIC_FIELD_NAME, FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
+    Cardinality, DocMapper, DocParsingError, JsonParsingOptions, Mode, QueryParserError,
+    TokenizerEntry, WarmupInfo, DYNAMIC_FIELD_NAME, FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
 };
 
 const FIELD_PRESENCE_FIELD: Field = Field::from_field_id(0u32);
@@ -78,11 +80,17 @@ pub struct DefaultDocMapper {
     schema: Schema,
     /// List of field names used for tagging.
     tag_field_names: BTreeSet<String>,
+    /// Names of the `geo_point` fields.
+    geo_point_field_names: BTreeSet<String>,
     /// The partition key is a DSL used to route documents
     /// into specific splits.
     partition_key: RoutingExpr,
     /// Maximum number of partitions
     max_num_partitions: NonZeroU32,
+    /// Timestamp field and duration of the time buckets used to partition documents, if any.
+    timestamp_partition_opt: Option<(Field, NonZeroU64)>,
+    /// Limits and policies applied when parsing JSON documents.
+    json_parsing_options: JsonParsingOptions,
     /// List of required fields. Right now this is unused.
     required_fields: Vec<Field>,
     /// Defines how unmapped fields should be handle.
@@ -110,6 +118,27 @@ impl DefaultDocMapper {
     pub fn default_max_num_partitions() -> NonZeroU32 {
         NonZeroU32::new(200).unwrap()
     }
+
+    /// Combines the partition computed from the partition key with the time bucket the document
+    /// falls into, so that documents of distinct time buckets end up in distinct splits.
+    fn partition_by_timestamp(&self, partition: Partition, doc: &Document) -> Partition {
+        let Some((timestamp_field, period_secs)) = self.timestamp_partition_opt else {
+            return partition;
+        };
+        let Some(timestamp) = doc
+            .get_first(timestamp_field)
+            .and_then(|value| value.as_datetime())
+        else {
+            return partition;
+        };
+        let time_bucket = timestamp
+            .into_timestamp_secs()
+            .div_euclid(period_secs.get() as i64);
+        let mut hasher = SipHasher::new();
+        hasher.write_u64(partition);
+        hasher.write_i64(time_bucket);
+        hasher.finish()
+    }
 }
 
 fn validate_timestamp_field(
@@ -162,32 +191,71 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             None
         };
 
+        // The problems found past this point do not prevent the other checks from running, so
+        // they are all reported at once.
+        let mut validation_errors = ValidationErrors::default();
+
+        let mut is_timestamp_field_valid = false;
         if let Some(timestamp_field_path) = builder.timestamp_field.as_ref() {
-            validate_timestamp_field(timestamp_field_path, &field_mappings)?;
+            let validation_res = validate_timestamp_field(timestamp_field_path, &field_mappings);
+            is_timestamp_field_valid = validation_errors.check(validation_res).is_some();
         };
 
         let schema = schema_builder.build();
 
+        let timestamp_partition_period_secs_opt = match builder.timestamp_partition_period {
+            Some(period) => {
+                let period_secs_opt = NonZeroU64::new(period.as_duration().as_secs());
+                if period_secs_opt.is_none() {
+                    validation_errors.push(anyhow::anyhow!(
+                        "`timestamp_partition_period` must be at least one second"
+                    ));
+                }
+                period_secs_opt
+            }
+            None => None,
+        };
+        let timestamp_partition_opt = match (
+            builder.timestamp_field.as_ref(),
+            timestamp_partition_period_secs_opt,
+        ) {
+            (Some(timestamp_field_path), Some(period_secs)) if is_timestamp_field_valid => {
+                let timestamp_field = schema.get_field(timestamp_field_path)?;
+                Some((timestamp_field, period_secs))
+            }
+            (None, Some(_)) => {
+                validation_errors.push(anyhow::anyhow!(
+                    "`timestamp_partition_period` requires a timestamp field"
+                ));
+                None
+            }
+            _ => None,
+        };
+
+        validation_errors.check(builder.json_parsing.validate());
+
         let tokenizer_manager = create_default_quickwit_tokenizer_manager();
         let mut custom_tokenizer_names = HashSet::new();
         for tokenizer_config_entry in builder.tokenizers.iter() {
             if custom_tokenizer_names.contains(&tokenizer_config_entry.name) {
-                bail!(
+                validation_errors.push(anyhow::anyhow!(
                     "duplicated custom tokenizer: `{}`",
                     tokenizer_config_entry.name
-                );
+                ));
+                continue;
             }
             if tokenizer_manager
                 .get_tokenizer(&tokenizer_config_entry.name)
                 .is_some()
             {
-                bail!(
+                validation_errors.push(anyhow::anyhow!(
                     "custom tokenizer name `{}` should be different from built-in tokenizer's \
                      names",
                     tokenizer_config_entry.name
-                );
+                ));
+                continue;
             }
-            let tokenizer = tokenizer_config_entry
+            let tokenizer_res = tokenizer_config_entry
                 .config
                 .text_analyzer()
                 .map_err(|error| {
@@ -196,7 +264,10 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
                         tokenizer_config_entry.name,
                         error
                     )
-                })?;
+                });
+            let Some(tokenizer) = validation_errors.check(tokenizer_res) else {
+                continue;
+            };
             let does_lowercasing = tokenizer_config_entry
                 .config
                 .filters
@@ -205,25 +276,32 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             tokenizer_manager.register(&tokenizer_config_entry.name, tokenizer, does_lowercasing);
             custom_tokenizer_names.insert(&tokenizer_config_entry.name);
         }
-        validate_fields_tokenizers(&schema, &tokenizer_manager)?;
+        validation_errors.check(validate_fields_tokenizers(&schema, &tokenizer_manager));
 
         // Resolve default search fields
         let mut default_search_field_names = Vec::new();
         for default_search_field_name in &builder.default_search_fields {
             if default_search_field_names.contains(default_search_field_name) {
-                bail!(
+                validation_errors.push(anyhow::anyhow!(
                     "duplicated default search field: `{}`",
                     default_search_field_name
-                )
+                ));
+                continue;
             }
             let dynamic_field = schema.get_field(DYNAMIC_FIELD_NAME).ok();
-            let (default_search_field, _json_path) = schema
-                .find_field_with_default(default_search_field_name, dynamic_field)
-                .with_context(|| {
-                    format!("Unknown default search field: `{default_search_field_name}`")
-                })?;
+            let Some((default_search_field, _json_path)) =
+                schema.find_field_with_default(default_search_field_name, dynamic_field)
+            else {
+                validation_errors.push(anyhow::anyhow!(
+                    "Unknown default search field: `{default_search_field_name}`"
+                ));
+                continue;
+            };
             if !schema.get_field_entry(default_search_field).is_indexed() {
-                bail!("default search field `{default_search_field_name}` is not indexed",);
+                validation_errors.push(anyhow::anyhow!(
+                    "default search field `{default_search_field_name}` is not indexed"
+                ));
+                continue;
             }
             default_search_field_names.push(default_search_field_name.clone());
         }
@@ -231,13 +309,16 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
         // Resolve tag fields
         let mut tag_field_names: BTreeSet<String> = builder.tag_fields.iter().cloned().collect();
         for tag_field_name in &builder.tag_fields {
-            validate_tag(tag_field_name, &schema)?;
+            validation_errors.check(validate_tag(tag_field_name, &schema));
         }
 
         let partition_key_expr: &str = builder.partition_key.as_deref().unwrap_or("");
-        let partition_key = RoutingExpr::new(partition_key_expr).with_context(|| {
+        let partition_key_res = RoutingExpr::new(partition_key_expr).with_context(|| {
             format!("failed to interpret the partition key: `{partition_key_expr}`")
-        })?;
+        });
+        let partition_key = validation_errors
+            .check(partition_key_res)
+            .unwrap_or_default();
 
         // If valid, partition key fields should be considered as tags.
         for partition_key in partition_key.field_names() {
@@ -246,6 +327,14 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             }
         }
 
+        let geo_point_field_names: BTreeSet<String> = field_mappings
+            .geo_point_fields()
+            .into_iter()
+            .map(|field| schema.get_field_name(field).to_string())
+            .collect();
+
+        validation_errors.into_result()?;
+
         let required_fields = Vec::new();
         Ok(DefaultDocMapper {
             schema,
@@ -256,9 +345,12 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             timestamp_field_name: builder.timestamp_field,
             field_mappings,
             tag_field_names,
+            geo_point_field_names,
             required_fields,
             partition_key,
             max_num_partitions: builder.max_num_partitions,
+            timestamp_partition_opt,
+            json_parsing_options: builder.json_parsing,
             mode: builder.mode,
             tokenizer_entries: builder.tokenizers,
             tokenizer_manager,
@@ -366,6 +458,10 @@ impl From<DefaultDocMapper> for DefaultDocMapperBuilder {
             mode: default_doc_mapper.mode,
             partition_key: partition_key_opt,
             max_num_partitions: default_doc_mapper.max_num_partitions,
+            timestamp_partition_period: default_doc_mapper
+                .timestamp_partition_opt
+                .map(|(_, period_secs)| HumanDuration::from_secs(period_secs.get())),
+            json_parsing: default_doc_mapper.json_parsing_options,
             tokenizers: default_doc_mapper.tokenizer_entries,
         }
     }
@@ -436,53 +532,50 @@ fn tantivy_value_to_json(val: TantivyValue) -> JsonValue {
     }
 }
 
-#[inline]
-fn populate_field_presence_for_json_value(
-    json_value: &TantivyValue,
-    path_hasher: &PathHasher,
+fn populate_field_presence_for_json_obj(
+    json_obj: &BTreeMap<String, TantivyValue>,
+    path_hasher: PathHasher,
     is_expand_dots_enabled: bool,
     output: &mut FnvHashSet<u64>,
 ) {
-    match json_value {
-        TantivyValue::Null => {}
-        TantivyValue::Bool(_)
-        | TantivyValue::F64(_)
-        | TantivyValue::I64(_)
-        | TantivyValue::U64(_)
-        | TantivyValue::PreTokStr(_)
-        | TantivyValue::Date(_)
-        | TantivyValue::Facet(_)
-        | TantivyValue::Bytes(_)
-        | TantivyValue::IpAddr(_)
-        | TantivyValue::Str(_) => {
-            output.insert(path_hasher.finish());
-        }
-        TantivyValue::Array(items) => {
-            for item in items {
-                populate_field_presence_for_json_value(
-                    item,
-                    path_hasher,
-                    is_expand_dots_enabled,
-                    output,
-                );
+    // We rely on an explicit stack rather than on recursion so that the depth of the documents
+    // we can handle is not bounded by the size of the thread stack.
+    let mut stack: Vec<(&TantivyValue, PathHasher)> = Vec::new();
+    push_json_obj_children(json_obj, &path_hasher, is_expand_dots_enabled, &mut stack);
+
+    while let Some((json_value, path_hasher)) = stack.pop() {
+        match json_value {
+            TantivyValue::Null => {}
+            TantivyValue::Bool(_)
+            | TantivyValue::F64(_)
+            | TantivyValue::I64(_)
+            | TantivyValue::U64(_)
+            | TantivyValue::PreTokStr(_)
+            | TantivyValue::Date(_)
+            | TantivyValue::Facet(_)
+            | TantivyValue::Bytes(_)
+            | TantivyValue::IpAddr(_)
+            | TantivyValue::Str(_) => {
+                output.insert(path_hasher.finish());
+            }
+            TantivyValue::Array(items) => {
+                for item in items {
+                    stack.push((item, path_hasher.clone()));
+                }
+            }
+            TantivyValue::Object(json_obj) => {
+                push_json_obj_children(json_obj, &path_hasher, is_expand_dots_enabled, &mut stack);
             }
-        }
-        TantivyValue::Object(json_obj) => {
-            populate_field_presence_for_json_obj(
-                json_obj,
-                path_hasher.clone(),
-                is_expand_dots_enabled,
-                output,
-            );
         }
     }
 }
 
-fn populate_field_presence_for_json_obj(
-    json_obj: &BTreeMap<String, TantivyValue>,
-    path_hasher: PathHasher,
+#[inline]
+fn push_json_obj_children<'a>(
+    json_obj: &'a BTreeMap<String, TantivyValue>,
+    path_hasher: &PathHasher,
     is_expand_dots_enabled: bool,
-    output: &mut FnvHashSet<u64>,
+    stack: &mut Vec<(&'a TantivyValue, PathHasher)>,
 ) {
     for (field_key, field_value) in json_obj {
         let mut child_path_hasher = path_hasher.clone();
@@ -493,12 +586,7 @@ fn populate_field_presence_for_json_obj(
         } else {
             child_path_hasher.append(field_key.as_bytes());
         };
-        populate_field_presence_for_json_value(
-            field_value,
-            &child_path_hasher,
-            is_expand_dots_enabled,
-            output,
-        );
+        stack.push((field_value, child_path_hasher));
     }
 }
 
@@ -584,6 +672,7 @@ impl DocMapper for DefaultDocMapper {
         }
 
         self.check_missing_required_fields(&document)?;
+        let partition = self.partition_by_timestamp(partition, &document);
         Ok((partition, document))
     }
 
@@ -634,10 +723,18 @@ impl DocMapper for DefaultDocMapper {
         self.timestamp_field_name.as_deref()
     }
 
+    fn json_parsing_options(&self) -> JsonParsingOptions {
+        self.json_parsing_options
+    }
+
     fn tag_field_names(&self) -> BTreeSet<String> {
         self.tag_field_names.clone()
     }
 
+    fn geo_point_field_names(&self) -> BTreeSet<String> {
+        self.geo_point_field_names.clone()
+    }
+
     fn max_num_partitions(&self) -> NonZeroU32 {
         self.max_num_partitions
     }
@@ -649,18 +746,19 @@ impl DocMapper for DefaultDocMapper {
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeSet, HashMap, HashSet};
 
-    use quickwit_common::PathHasher;
+    use quickwit_common::geo::GeoPoint;
+    use quickwit_common::{HumanDuration, PathHasher};
     use quickwit_query::query_ast::query_ast_from_user_text;
     use serde_json::{self, json, Value as JsonValue};
     use tantivy::schema::{FieldType, IndexRecordOption, OwnedValue as TantivyValue, Type, Value};
 
-    use super::DefaultDocMapper;
+    use super::{DefaultDocMapper, FIELD_PRESENCE_FIELD};
     use crate::default_doc_mapper::field_mapping_entry::DEFAULT_TOKENIZER_NAME;
     use crate::{
-        DefaultDocMapperBuilder, DocMapper, DocParsingError, DYNAMIC_FIELD_NAME,
-        FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
+        DefaultDocMapperBuilder, DocMapper, DocParsingError, DuplicateKeysPolicy,
+        DYNAMIC_FIELD_NAME, FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME,
     };
 
     fn example_json_doc_value() -> JsonValue {
@@ -1320,6 +1418,145 @@ mod tests {
         assert_eq!(tag_fields, vec!["city", "division", "service",]);
     }
 
+    #[test]
+    fn test_timestamp_partition() {
+        let doc_mapper = r#"{
+            "timestamp_field": "timestamp",
+            "partition_key": "service",
+            "timestamp_partition_period": "1h",
+            "field_mappings": [
+                {
+                    "name": "service",
+                    "type": "text",
+                    "tokenizer": "raw"
+                },
+                {
+                    "name": "timestamp",
+                    "type": "datetime",
+                    "input_formats": ["unix_timestamp"],
+                    "fast": true
+                }
+            ]
+        }"#;
+        let doc_mapper = serde_json::from_str::<DefaultDocMapper>(doc_mapper).unwrap();
+        let partition = |service: &str, timestamp: i64| {
+            doc_mapper
+                .doc_from_json_str(&format!(
+                    r#"{{"service": "{service}", "timestamp": {timestamp}}}"#
+                ))
+                .unwrap()
+                .0
+        };
+        assert_eq!(partition("api", 3600), partition("api", 7199));
+        assert_ne!(partition("api", 3599), partition("api", 3600));
+        assert_ne!(partition("api", 3600), partition("web", 3600));
+
+        let doc_mapper_json = serde_json::to_value(&doc_mapper).unwrap();
+        assert_eq!(doc_mapper_json["timestamp_partition_period_secs"], 3600);
+
+        let doc_mapper_builder = DefaultDocMapperBuilder::from(doc_mapper);
+        assert_eq!(
+            doc_mapper_builder.timestamp_partition_period,
+            Some(HumanDuration::from_secs(3600))
+        );
+    }
+
+    #[test]
+    fn test_timestamp_partition_requires_timestamp_field() {
+        let doc_mapper = r#"{
+            "timestamp_partition_period_secs": 3600,
+            "field_mappings": []
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let error = builder.try_build().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`timestamp_partition_period` requires a timestamp field"
+        );
+    }
+
+    #[test]
+    fn test_json_parsing_options() {
+        let doc_mapper = r#"{
+            "json_parsing": {
+                "max_depth": 3,
+                "duplicate_keys": "reject"
+            },
+            "field_mappings": []
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let doc_mapper = builder.try_build().unwrap();
+
+        doc_mapper
+            .doc_from_json_str(r#"{"body": {"attributes": {"count": 3}}}"#)
+            .unwrap();
+
+        let error = doc_mapper
+            .doc_from_json_str(r#"{"body": {"attributes": {"counts": [3]}}}"#)
+            .unwrap_err();
+        assert_eq!(error, DocParsingError::MaxDepthExceeded(3));
+
+        let error = doc_mapper
+            .doc_from_json_str(r#"{"body": "foo", "body": "bar"}"#)
+            .unwrap_err();
+        assert_eq!(error, DocParsingError::DuplicateKey("body".to_string()));
+
+        let doc_mapper_builder = DefaultDocMapperBuilder::from(doc_mapper);
+        assert_eq!(doc_mapper_builder.json_parsing.max_depth, 3);
+        assert_eq!(
+            doc_mapper_builder.json_parsing.duplicate_keys,
+            DuplicateKeysPolicy::Reject
+        );
+    }
+
+    #[test]
+    fn test_json_parsing_options_invalid_max_depth() {
+        let doc_mapper = r#"{
+            "json_parsing": {
+                "max_depth": 1000
+            },
+            "field_mappings": []
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let error = builder.try_build().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "`max_depth` must be between 1 and 127, got `1000`"
+        );
+    }
+
+    #[test]
+    fn test_field_presence_deeply_nested_document() {
+        let doc_mapper = r#"{
+            "index_field_presence": true,
+            "json_parsing": {
+                "max_depth": 127
+            },
+            "mode": "lenient",
+            "field_mappings": [
+                {
+                    "name": "body",
+                    "type": "json",
+                    "fast": false
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let doc_mapper = builder.try_build().unwrap();
+        let json_doc = format!(
+            r#"{{"body": {}"foo"{}}}"#,
+            r#"{"a": ["#.repeat(60),
+            "]}".repeat(60)
+        );
+        let (_, document) = doc_mapper.doc_from_json_str(&json_doc).unwrap();
+        let num_field_presence_values = document
+            .field_values()
+            .iter()
+            .filter(|field_value| field_value.field() == FIELD_PRESENCE_FIELD)
+            .count();
+        assert_eq!(num_field_presence_values, 1);
+    }
+
     #[test]
     fn test_build_doc_mapper_with_tag_field_with_dots_in_its_name() {
         let doc_mapper = r#"{
@@ -1356,6 +1593,57 @@ mod tests {
         serde_json::from_str::<DefaultDocMapper>(doc_mapper).unwrap();
     }
 
+    #[test]
+    fn test_geo_point_fields() {
+        let doc_mapper = r#"{
+            "default_search_fields": [],
+            "field_mappings": [
+                {
+                    "name": "location",
+                    "type": "geo_point"
+                },
+                {
+                    "name": "venue",
+                    "type": "object",
+                    "field_mappings": [
+                        {
+                            "name": "entrance",
+                            "type": "geo_point",
+                            "stored": false
+                        }
+                    ]
+                }
+            ]
+        }"#;
+        let doc_mapper = serde_json::from_str::<DefaultDocMapper>(doc_mapper).unwrap();
+        assert_eq!(
+            doc_mapper.geo_point_field_names(),
+            BTreeSet::from_iter(["location".to_string(), "venue.entrance".to_string()])
+        );
+        let schema = doc_mapper.schema();
+        let location_field_entry = schema.get_field_entry(schema.get_field("location").unwrap());
+        assert!(location_field_entry.is_fast());
+        assert!(location_field_entry.is_stored());
+        assert!(!location_field_entry.is_indexed());
+
+        let (_, doc) = doc_mapper
+            .doc_from_json_str(
+                r#"{"location": [2.35, 48.85], "venue": {"entrance": "48.85,2.35"}}"#,
+            )
+            .unwrap();
+        let named_doc = doc.to_named_doc(&schema);
+        let doc_json = doc_mapper.doc_to_json(named_doc.0).unwrap();
+        let location: GeoPoint = serde_json::from_value(doc_json["location"].clone()).unwrap();
+        assert!(location.haversine_distance_meters(&GeoPoint::new(48.85, 2.35).unwrap()) < 0.01);
+        // The entrance is not stored.
+        assert!(!doc_json.contains_key("venue"));
+
+        let error = doc_mapper
+            .doc_from_json_str(r#"{"location": [[2.35, 48.85], [2.36, 48.86]]}"#)
+            .unwrap_err();
+        assert!(matches!(error, DocParsingError::ValueError(field, _) if field == "location"));
+    }
+
     #[test]
     fn test_fail_to_build_doc_mapper_with_wrong_tag_fields_types() -> anyhow::Result<()> {
         let doc_mapper_one = r#"{
@@ -19,12 +19,13 @@
 
 use std::num::NonZeroU32;
 
+use quickwit_common::{serialize_opt_duration_as_secs, HumanDuration};
 use serde::{Deserialize, Serialize};
 
 use super::tokenizer_entry::TokenizerEntry;
 use super::FieldMappingEntry;
 use crate::default_doc_mapper::QuickwitJsonOptions;
-use crate::DefaultDocMapper;
+use crate::{DefaultDocMapper, JsonParsingOptions};
 
 /// DefaultDocMapperBuilder is here
 /// to create a valid DocMapper.
@@ -62,6 +63,18 @@ pub struct DefaultDocMapperBuilder {
     /// Maximum number of partitions.
     #[serde(default = "DefaultDocMapper::default_max_num_partitions")]
     pub max_num_partitions: NonZeroU32,
+    /// When set, documents are additionally partitioned by time buckets of this duration, e.g.
+    /// `1h`, based on the value of their timestamp field. Plain integers are interpreted as
+    /// seconds. Serialized as a number of seconds.
+    #[serde(rename = "timestamp_partition_period_secs")]
+    #[serde(alias = "timestamp_partition_period")]
+    #[serde(default)]
+    #[serde(serialize_with = "serialize_opt_duration_as_secs")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestamp_partition_period: Option<HumanDuration>,
+    /// Limits and policies applied when parsing JSON documents.
+    #[serde(default)]
+    pub json_parsing: JsonParsingOptions,
     #[serde_multikey(
         deserializer = Mode::from_parts,
         serializer = Mode::into_parts,
@@ -180,6 +193,7 @@ mod tests {
         assert_eq!(default_mapper_builder.mode.mode_type(), ModeType::Dynamic);
         assert_eq!(default_mapper_builder.store_source, false);
         assert!(default_mapper_builder.timestamp_field.is_none());
+        assert!(default_mapper_builder.timestamp_partition_period.is_none());
     }
 
     #[test]
@@ -249,6 +249,29 @@ impl Default for QuickwitIpAddrOptions {
     }
 }
 
+/// Options of `geo_point` fields.
+///
+/// Geo points are always stored in a fast field, encoded as a single `u64` (see
+/// [`GeoPoint::to_u64`](quickwit_common::geo::GeoPoint::to_u64)). They are not indexed.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct QuickwitGeoPointOptions {
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default = "default_as_true")]
+    pub stored: bool,
+}
+
+impl Default for QuickwitGeoPointOptions {
+    fn default() -> Self {
+        Self {
+            description: None,
+            stored: true,
+        }
+    }
+}
+
 #[derive(Clone, PartialEq, Debug, Eq, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct QuickwitTextTokenizer(Cow<'static, str>);
 
@@ -636,6 +659,10 @@ fn deserialize_mapping_type(
             }
             return Ok(FieldMappingType::Object(object_options));
         }
+        QuickwitFieldType::GeoPoint => {
+            let geo_point_options: QuickwitGeoPointOptions = serde_json::from_value(json)?;
+            return Ok(FieldMappingType::GeoPoint(geo_point_options));
+        }
     };
     match typ {
         Type::Str => {
@@ -726,6 +753,7 @@ fn typed_mapping_to_json_params(
         FieldMappingType::Bool(options, _) => serialize_to_map(&options),
         FieldMappingType::Bytes(options, _) => serialize_to_map(&options),
         FieldMappingType::IpAddr(options, _) => serialize_to_map(&options),
+        FieldMappingType::GeoPoint(options) => serialize_to_map(&options),
         FieldMappingType::DateTime(date_time_options, _) => serialize_to_map(&date_time_options),
         FieldMappingType::Json(json_options, _) => serialize_to_map(&json_options),
         FieldMappingType::Object(object_options) => serialize_to_map(&object_options),
@@ -1354,6 +1382,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_geo_point_mapping() {
+        let entry = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "location",
+                "type": "geo_point"
+            }
+            "#,
+        )
+        .unwrap();
+        assert!(matches!(entry.mapping_type, FieldMappingType::GeoPoint(_)));
+        let entry_str = serde_json::to_value(&entry).unwrap();
+        assert_eq!(
+            entry_str,
+            serde_json::json!({
+                "name": "location",
+                "type": "geo_point",
+                "stored": true
+            })
+        );
+
+        let error = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "location",
+                "type": "geo_point",
+                "indexed": true
+            }
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("unknown field `indexed`"));
+    }
+
     #[test]
     fn test_parse_text_mapping() {
         let entry = serde_json::from_str::<FieldMappingEntry>(
@@ -22,8 +22,8 @@ use tantivy::schema::Type;
 use super::date_time_type::QuickwitDateTimeOptions;
 use super::field_mapping_entry::QuickwitBoolOptions;
 use crate::default_doc_mapper::field_mapping_entry::{
-    QuickwitBytesOptions, QuickwitIpAddrOptions, QuickwitJsonOptions, QuickwitNumericOptions,
-    QuickwitObjectOptions, QuickwitTextOptions,
+    QuickwitBytesOptions, QuickwitGeoPointOptions, QuickwitIpAddrOptions, QuickwitJsonOptions,
+    QuickwitNumericOptions, QuickwitObjectOptions, QuickwitTextOptions,
 };
 use crate::Cardinality;
 
@@ -47,6 +47,8 @@ pub(crate) enum FieldMappingType {
     IpAddr(QuickwitIpAddrOptions, Cardinality),
     /// Bytes mapping type configuration.
     Bytes(QuickwitBytesOptions, Cardinality),
+    /// Geo point mapping type configuration. Geo points are single-valued.
+    GeoPoint(QuickwitGeoPointOptions),
     /// Json mapping type configuration.
     Json(QuickwitJsonOptions, Cardinality),
     /// Object mapping type configuration.
@@ -68,6 +70,9 @@ impl FieldMappingType {
             FieldMappingType::Object(_) => {
                 return QuickwitFieldType::Object;
             }
+            FieldMappingType::GeoPoint(_) => {
+                return QuickwitFieldType::GeoPoint;
+            }
         };
         match cardinality {
             Cardinality::SingleValue => QuickwitFieldType::Simple(primitive_type),
@@ -81,6 +86,7 @@ pub enum QuickwitFieldType {
     Simple(Type),
     Object,
     Array(Type),
+    GeoPoint,
 }
 
 impl QuickwitFieldType {
@@ -88,6 +94,7 @@ impl QuickwitFieldType {
         match self {
             QuickwitFieldType::Simple(typ) => primitive_type_to_str(typ).to_string(),
             QuickwitFieldType::Object => "object".to_string(),
+            QuickwitFieldType::GeoPoint => "geo_point".to_string(),
             QuickwitFieldType::Array(typ) => format!("array<{}>", primitive_type_to_str(typ)),
         }
     }
@@ -96,6 +103,9 @@ impl QuickwitFieldType {
         if type_str == "object" {
             return Some(QuickwitFieldType::Object);
         }
+        if type_str == "geo_point" {
+            return Some(QuickwitFieldType::GeoPoint);
+        }
         if type_str.starts_with("array<") && type_str.ends_with('>') {
             let parsed_type_str = parse_primitive_type(&type_str[6..type_str.len() - 1])?;
             return Some(QuickwitFieldType::Array(parsed_type_str));
@@ -159,5 +169,7 @@ mod tests {
         test_parse_type_aux("object2", None);
         test_parse_type_aux("bool", Some(QuickwitFieldType::Simple(Type::Bool)));
         test_parse_type_aux("ip", Some(QuickwitFieldType::Simple(Type::IpAddr)));
+        test_parse_type_aux("geo_point", Some(QuickwitFieldType::GeoPoint));
+        test_parse_type_aux("array<geo_point>", None);
     }
 }
@@ -24,6 +24,7 @@ use std::str::FromStr;
 
 use anyhow::bail;
 use itertools::Itertools;
+use quickwit_common::geo::GeoPoint;
 use serde_json::Value as JsonValue;
 use tantivy::schema::{
     BytesOptions, Field, IntoIpv6Addr, IpAddrOptions, JsonObjectOptions, NumericOptions,
@@ -35,8 +36,8 @@ use tracing::warn;
 use super::date_time_type::QuickwitDateTimeOptions;
 use super::field_mapping_entry::{NumericOutputFormat, QuickwitBoolOptions};
 use crate::default_doc_mapper::field_mapping_entry::{
-    QuickwitBytesOptions, QuickwitIpAddrOptions, QuickwitNumericOptions, QuickwitObjectOptions,
-    QuickwitTextOptions,
+    QuickwitBytesOptions, QuickwitGeoPointOptions, QuickwitIpAddrOptions, QuickwitNumericOptions,
+    QuickwitObjectOptions, QuickwitTextOptions,
 };
 use crate::default_doc_mapper::{FieldMappingType, QuickwitJsonOptions};
 use crate::{Cardinality, DocParsingError, FieldMappingEntry, ModeType};
@@ -50,6 +51,7 @@ pub enum LeafType {
     I64(QuickwitNumericOptions),
     U64(QuickwitNumericOptions),
     IpAddr(QuickwitIpAddrOptions),
+    GeoPoint(QuickwitGeoPointOptions),
     Json(QuickwitJsonOptions),
     Text(QuickwitTextOptions),
 }
@@ -84,6 +86,11 @@ impl LeafType {
                     Err(format!("expected string value, got `{json_val}`"))
                 }
             }
+            LeafType::GeoPoint(_) => {
+                let geo_point: GeoPoint = serde_json::from_value(json_val.clone())
+                    .map_err(|err| format!("failed to parse geo point `{json_val}`: {err}"))?;
+                Ok(TantivyValue::U64(geo_point.to_u64()))
+            }
             LeafType::DateTime(date_time_options) => date_time_options.parse_json(json_val),
             LeafType::Bytes(binary_options) => binary_options.input_format.parse_json(json_val),
             LeafType::Json(_) => {
@@ -120,6 +127,15 @@ impl MappingLeaf {
             // We just ignore `null`.
             return Ok(());
         }
+        if let LeafType::GeoPoint(_) = self.typ {
+            // Geo points are single-valued: an array is a `[lon, lat]` point.
+            let value = self
+                .typ
+                .value_from_json(json_val)
+                .map_err(|err_msg| DocParsingError::ValueError(path.join("."), err_msg))?;
+            document.add_field_value(self.field, value);
+            return Ok(());
+        }
         if let JsonValue::Array(els) = json_val {
             if self.cardinality == Cardinality::SingleValue {
                 return Err(DocParsingError::MultiValuesNotSupported(path.join(".")));
@@ -214,6 +230,12 @@ fn value_to_json(value: TantivyValue, leaf_type: &LeafType) -> Option<JsonValue>
         (TantivyValue::U64(u64_val), LeafType::U64(numeric_options)) => {
             u64_val.to_json(numeric_options.output_format)
         }
+        (TantivyValue::U64(encoded_geo_point), LeafType::GeoPoint(_)) => {
+            let geo_point = GeoPoint::from_u64(*encoded_geo_point);
+            let json_value =
+                serde_json::to_value(geo_point).expect("Json serialization should never fail.");
+            Some(json_value)
+        }
         _ => {
             warn!(
                 "The value type `{:?}` doesn't match the requested type `{:?}`",
@@ -387,6 +409,24 @@ impl MappingNode {
         self.branches.insert(path.to_string(), node);
     }
 
+    /// Returns the fields of the `geo_point` leaves of the tree.
+    pub fn geo_point_fields(&self) -> Vec<Field> {
+        let mut geo_point_fields = Vec::new();
+        for field_name in &self.branches_order {
+            match self.branches.get(field_name).expect("Missing field") {
+                MappingTree::Leaf(leaf) => {
+                    if let LeafType::GeoPoint(_) = leaf.typ {
+                        geo_point_fields.push(leaf.field);
+                    }
+                }
+                MappingTree::Node(child_node) => {
+                    geo_point_fields.extend(child_node.geo_point_fields());
+                }
+            }
+        }
+        geo_point_fields
+    }
+
     pub fn ordered_field_mapping_entries(&self) -> Vec<FieldMappingEntry> {
         assert_eq!(self.branches.len(), self.branches_order.len());
         let mut field_mapping_entries = Vec::new();
@@ -469,6 +509,7 @@ impl From<MappingLeaf> for FieldMappingType {
             LeafType::F64(opt) => FieldMappingType::F64(opt, leaf.cardinality),
             LeafType::Bool(opt) => FieldMappingType::Bool(opt, leaf.cardinality),
             LeafType::IpAddr(opt) => FieldMappingType::IpAddr(opt, leaf.cardinality),
+            LeafType::GeoPoint(opt) => FieldMappingType::GeoPoint(opt),
             LeafType::DateTime(opt) => FieldMappingType::DateTime(opt, leaf.cardinality),
             LeafType::Bytes(opt) => FieldMappingType::Bytes(opt, leaf.cardinality),
             LeafType::Json(opt) => FieldMappingType::Json(opt, leaf.cardinality),
@@ -631,6 +672,17 @@ fn get_ip_address_options(quickwit_ip_address_options: &QuickwitIpAddrOptions) -
     ip_address_options
 }
 
+fn get_numeric_options_for_geo_point_field(
+    quickwit_geo_point_options: &QuickwitGeoPointOptions,
+) -> NumericOptions {
+    // The encoded geo points are only meant to be read from the fast field.
+    let mut numeric_options = NumericOptions::default().set_fast();
+    if quickwit_geo_point_options.stored {
+        numeric_options = numeric_options.set_stored();
+    }
+    numeric_options
+}
+
 /// Creates a tantivy field name for a given field path.
 ///
 /// By field path, we mean the list of `field_name` that are crossed
@@ -749,6 +801,16 @@ fn build_mapping_from_field_type<'a>(
             };
             Ok(MappingTree::Leaf(mapping_leaf))
         }
+        FieldMappingType::GeoPoint(options) => {
+            let numeric_options = get_numeric_options_for_geo_point_field(options);
+            let field = schema_builder.add_u64_field(&field_name, numeric_options);
+            let mapping_leaf = MappingLeaf {
+                field,
+                typ: LeafType::GeoPoint(options.clone()),
+                cardinality: Cardinality::SingleValue,
+            };
+            Ok(MappingTree::Leaf(mapping_leaf))
+        }
         FieldMappingType::DateTime(options, cardinality) => {
             let date_time_options = get_date_time_options(options);
             let field = schema_builder.add_date_field(&field_name, date_time_options);
@@ -793,6 +855,7 @@ fn build_mapping_from_field_type<'a>(
 mod tests {
     use std::net::IpAddr;
 
+    use quickwit_common::geo::GeoPoint;
     use serde_json::{json, Value as JsonValue};
     use tantivy::schema::{Field, IntoIpv6Addr, OwnedValue as TantivyValue, Value};
     use tantivy::{DateTime, TantivyDocument as Document};
@@ -803,7 +866,8 @@ mod tests {
     use crate::default_doc_mapper::date_time_type::QuickwitDateTimeOptions;
     use crate::default_doc_mapper::field_mapping_entry::{
         BinaryFormat, NumericOutputFormat, QuickwitBoolOptions, QuickwitBytesOptions,
-        QuickwitIpAddrOptions, QuickwitNumericOptions, QuickwitTextOptions,
+        QuickwitGeoPointOptions, QuickwitIpAddrOptions, QuickwitNumericOptions,
+        QuickwitTextOptions,
     };
     use crate::Cardinality;
 
@@ -1024,6 +1088,63 @@ mod tests {
         assert!(err.contains("expected string value, got `1200`"));
     }
 
+    #[test]
+    fn test_parse_geo_point_formats() {
+        let expected_encoded_geo_point = GeoPoint::new(48.85, 2.35).unwrap().to_u64();
+        for geo_point_json in [
+            json!({"lat": 48.85, "lon": 2.35}),
+            json!([2.35, 48.85]),
+            json!("48.85,2.35"),
+        ] {
+            let field = Field::from_field_id(10);
+            let leaf_entry = MappingLeaf {
+                field,
+                typ: LeafType::GeoPoint(QuickwitGeoPointOptions::default()),
+                cardinality: Cardinality::SingleValue,
+            };
+            let mut document = Document::default();
+            let mut path = vec!["location".to_string()];
+            leaf_entry
+                .doc_from_json(geo_point_json, &mut document, &mut path)
+                .unwrap();
+            assert_eq!(document.len(), 1);
+            assert_eq!(
+                document.get_first(field).unwrap().as_u64().unwrap(),
+                expected_encoded_geo_point
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_geo_point_should_error() {
+        let typ = LeafType::GeoPoint(QuickwitGeoPointOptions::default());
+        let err = typ
+            .value_from_json(json!({"lat": 91.0, "lon": 2.35}))
+            .unwrap_err();
+        assert!(err.contains("latitude must be within [-90, 90]"), "{err}");
+
+        let err = typ.value_from_json(json!("48.85")).unwrap_err();
+        assert!(
+            err.contains("failed to parse geo point `\"48.85\"`"),
+            "{err}"
+        );
+
+        typ.value_from_json(json!([2.35, 48.85, 0.0])).unwrap_err();
+        typ.value_from_json(json!(48.85)).unwrap_err();
+    }
+
+    #[test]
+    fn test_tantivy_value_to_json_value_geo_point() {
+        let geo_point = GeoPoint::new(-33.856_784, 151.215_297).unwrap();
+        let json_value = value_to_json(
+            TantivyValue::U64(geo_point.to_u64()),
+            &LeafType::GeoPoint(QuickwitGeoPointOptions::default()),
+        )
+        .unwrap();
+        let decoded_geo_point: GeoPoint = serde_json::from_value(json_value).unwrap();
+        assert!(geo_point.haversine_distance_meters(&decoded_geo_point) < 0.01);
+    }
+
     #[test]
     fn test_parse_i64_mutivalued() {
         let typ = LeafType::I64(QuickwitNumericOptions::default());
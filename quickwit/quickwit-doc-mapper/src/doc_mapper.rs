@@ -36,7 +36,7 @@ pub type Partition = u64;
 /// An alias for serde_json's object type.
 pub type JsonObject = serde_json::Map<String, JsonValue>;
 
-use crate::{DocParsingError, QueryParserError};
+use crate::{parse_json_obj, DocParsingError, JsonParsingOptions, QueryParserError};
 
 /// The `DocMapper` trait defines the way of defining how a (json) document,
 /// and the fields it contains, are stored and indexed.
@@ -59,22 +59,19 @@ pub trait DocMapper: Send + Sync + Debug + DynClone + 'static {
         &self,
         json_doc: &[u8],
     ) -> Result<(Partition, Document), DocParsingError> {
-        let json_obj: JsonObject = serde_json::from_slice(json_doc).map_err(|_| {
-            let json_doc_sample: String = std::str::from_utf8(json_doc)
-                .map(|doc_str| doc_str.chars().take(20).chain("...".chars()).collect())
-                .unwrap_or_else(|_| "document contains some invalid UTF-8 characters".to_string());
-            DocParsingError::NotJsonObject(json_doc_sample)
-        })?;
+        let (json_obj, _num_duplicate_keys) =
+            parse_json_obj(json_doc, &self.json_parsing_options())?;
         self.doc_from_json_obj(json_obj)
     }
 
     /// Parses a JSON string into a tantivy [`Document`].
     fn doc_from_json_str(&self, json_doc: &str) -> Result<(Partition, Document), DocParsingError> {
-        let json_obj: JsonObject = serde_json::from_str(json_doc).map_err(|_| {
-            let json_doc_sample: String = json_doc.chars().take(20).chain("...".chars()).collect();
-            DocParsingError::NotJsonObject(json_doc_sample)
-        })?;
-        self.doc_from_json_obj(json_obj)
+        self.doc_from_json_bytes(json_doc.as_bytes())
+    }
+
+    /// Returns the limits and policies applied when parsing JSON documents.
+    fn json_parsing_options(&self) -> JsonParsingOptions {
+        JsonParsingOptions::default()
     }
 
     /// Converts a tantivy named Document to the json format.
@@ -123,6 +120,11 @@ pub trait DocMapper: Send + Sync + Debug + DynClone + 'static {
         Default::default()
     }
 
+    /// Returns the names of the `geo_point` fields.
+    fn geo_point_field_names(&self) -> BTreeSet<String> {
+        Default::default()
+    }
+
     /// Returns the tag `NameField`s on the current schema.
     /// Returns an error if a tag field is not found in this schema.
     fn tag_named_fields(&self) -> anyhow::Result<Vec<NamedField>> {
@@ -188,6 +190,9 @@ pub struct WarmupInfo {
     pub terms_grouped_by_field: HashMap<Field, HashMap<Term, bool>>,
     /// Term ranges to warmup, and whether their position is needed too.
     pub term_ranges_grouped_by_field: HashMap<Field, HashMap<TermRange, bool>>,
+    /// Terms of fuzzy queries, and the maximum Levenshtein distance of the terms they match. The
+    /// postings of the matched terms are warmed up once the term dictionary has been searched.
+    pub fuzzy_terms_grouped_by_field: HashMap<Field, HashMap<Term, u8>>,
 }
 
 impl WarmupInfo {
@@ -213,6 +218,15 @@ impl WarmupInfo {
                 *sub_map.entry(term_range).or_default() |= include_position;
             }
         }
+
+        for (field, fuzzy_terms) in other.fuzzy_terms_grouped_by_field.into_iter() {
+            let sub_map = self.fuzzy_terms_grouped_by_field.entry(field).or_default();
+
+            for (term, fuzzy_distance) in fuzzy_terms.into_iter() {
+                let max_fuzzy_distance = sub_map.entry(term).or_default();
+                *max_fuzzy_distance = (*max_fuzzy_distance).max(fuzzy_distance);
+            }
+        }
     }
 
     /// Simplify a WarmupInfo, removing some redundant tasks
@@ -232,6 +246,8 @@ impl WarmupInfo {
             }
             !terms.is_empty()
         });
+        self.fuzzy_terms_grouped_by_field
+            .retain(|field, _fuzzy_terms| !self.term_dict_fields.contains(field));
         // TODO we could remove from terms_grouped_by_field for ranges with no `limit` in
         // term_ranges_grouped_by_field
     }
@@ -457,6 +473,19 @@ mod tests {
         result
     }
 
+    fn hashmap_fuzzy(elements: &[(u32, &str, u8)]) -> HashMap<Field, HashMap<Term, u8>> {
+        let mut result: HashMap<Field, HashMap<Term, u8>> = HashMap::new();
+        for (field, term, fuzzy_distance) in elements {
+            let field = Field::from_field_id(*field);
+            let term = Term::from_field_text(field, term);
+            result
+                .entry(field)
+                .or_default()
+                .insert(term, *fuzzy_distance);
+        }
+        result
+    }
+
     #[test]
     fn test_warmup_info_merge() {
         let wi_base = WarmupInfo {
@@ -468,6 +497,7 @@ mod tests {
                 (2, "term1", false),
                 (2, "term2", false),
             ]),
+            fuzzy_terms_grouped_by_field: hashmap_fuzzy(&[(3, "term1", 1)]),
         };
 
         // merging with default has no impact
@@ -485,6 +515,7 @@ mod tests {
                 (3, "term1", false),
                 (2, "term2", true),
             ]),
+            fuzzy_terms_grouped_by_field: hashmap_fuzzy(&[(3, "term1", 2), (3, "term2", 1)]),
         };
         wi_base.merge(wi_2.clone());
 
@@ -532,6 +563,11 @@ mod tests {
             );
         }
 
+        assert_eq!(
+            wi_base.fuzzy_terms_grouped_by_field,
+            hashmap_fuzzy(&[(3, "term1", 2), (3, "term2", 1)])
+        );
+
         // merge is idempotent
         let mut wi_cloned = wi_base.clone();
         wi_cloned.merge(wi_2);
@@ -554,6 +590,7 @@ mod tests {
                 (1, "term2", true),
                 (2, "term3", false),
             ]),
+            fuzzy_terms_grouped_by_field: hashmap_fuzzy(&[(1, "term1", 1), (2, "term3", 1)]),
         };
         let expected = WarmupInfo {
             term_dict_fields: hashset_field(&[1]),
@@ -564,6 +601,7 @@ mod tests {
                 (1, "term2", true),
                 (2, "term3", false),
             ]),
+            fuzzy_terms_grouped_by_field: hashmap_fuzzy(&[(2, "term3", 1)]),
         };
 
         warmup_info.simplify();
@@ -57,6 +57,15 @@ pub enum DocParsingError {
     /// The document does not contain a field that is required.
     #[error("the document must contain field {0:?}")]
     RequiredField(String),
+    /// The document is nested deeper than the configured maximum depth.
+    #[error("the document exceeds the maximum nesting depth of {0}")]
+    MaxDepthExceeded(usize),
+    /// The document contains more fields than the configured maximum.
+    #[error("the document contains more than {0} fields")]
+    MaxNumFieldsExceeded(usize),
+    /// The document contains the same key twice within an object.
+    #[error("the document contains a duplicate key: {0:?}")]
+    DuplicateKey(String),
 }
 
 impl From<TantivyDocParsingError> for DocParsingError {
@@ -0,0 +1,527 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use anyhow::ensure;
+use serde::de::{DeserializeSeed, Deserializer, IgnoredAny, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use serde_json::{Number as JsonNumber, Value as JsonValue};
+
+use crate::{DocParsingError, JsonObject};
+
+/// serde_json refuses to parse documents nested 128 levels deep or more, whatever the configured
+/// maximum depth.
+const MAX_DEPTH_UPPER_BOUND: usize = 127;
+
+/// Defines how a document carrying the same key twice within an object is handled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateKeysPolicy {
+    /// The document is rejected.
+    Reject,
+    /// The first occurrence of the key is kept.
+    FirstWins,
+    /// The last occurrence of the key is kept.
+    #[default]
+    LastWins,
+}
+
+/// Limits and policies applied when parsing JSON documents.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct JsonParsingOptions {
+    /// Maximum nesting depth of objects and arrays. The root object has a depth of 1.
+    #[schema(default = 64)]
+    #[serde(default = "JsonParsingOptions::default_max_depth")]
+    pub max_depth: usize,
+    /// Maximum number of keys in a document, nested objects included.
+    #[schema(default = 10_000)]
+    #[serde(default = "JsonParsingOptions::default_max_num_fields")]
+    pub max_num_fields: usize,
+    /// Defines how duplicate keys are handled.
+    #[serde(default)]
+    pub duplicate_keys: DuplicateKeysPolicy,
+}
+
+impl JsonParsingOptions {
+    fn default_max_depth() -> usize {
+        64
+    }
+
+    fn default_max_num_fields() -> usize {
+        10_000
+    }
+
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        ensure!(
+            (1..=MAX_DEPTH_UPPER_BOUND).contains(&self.max_depth),
+            "`max_depth` must be between 1 and {MAX_DEPTH_UPPER_BOUND}, got `{}`",
+            self.max_depth
+        );
+        ensure!(
+            self.max_num_fields > 0,
+            "`max_num_fields` must be strictly positive"
+        );
+        Ok(())
+    }
+}
+
+impl Default for JsonParsingOptions {
+    fn default() -> Self {
+        Self {
+            max_depth: Self::default_max_depth(),
+            max_num_fields: Self::default_max_num_fields(),
+            duplicate_keys: DuplicateKeysPolicy::default(),
+        }
+    }
+}
+
+/// Parses a JSON object from a byte slice, enforcing the limits and the duplicate keys policy
+/// defined in `options`.
+///
+/// On success, returns the object along with the number of duplicate keys that were resolved
+/// by the policy.
+pub fn parse_json_obj(
+    json_doc: &[u8],
+    options: &JsonParsingOptions,
+) -> Result<(JsonObject, usize), DocParsingError> {
+    let mut state = ParserState::new(options);
+    let mut deserializer = serde_json::Deserializer::from_slice(json_doc);
+    let parse_result = JsonObjectSeed { state: &mut state }
+        .deserialize(&mut deserializer)
+        .and_then(|json_obj| deserializer.end().map(|_| json_obj));
+
+    match parse_result {
+        Ok(json_obj) => Ok((json_obj, state.num_duplicate_keys)),
+        Err(_) => Err(state
+            .error_opt
+            .take()
+            .unwrap_or_else(|| DocParsingError::NotJsonObject(json_doc_sample(json_doc)))),
+    }
+}
+
+/// Checks that an already parsed JSON object honors the depth and number of fields limits
+/// defined in `options`.
+///
+/// The object is traversed iteratively, so this function is safe to call on arbitrarily deep
+/// objects.
+pub fn check_json_obj_limits(
+    json_obj: &JsonObject,
+    options: &JsonParsingOptions,
+) -> Result<(), DocParsingError> {
+    let mut num_fields = json_obj.len();
+
+    if num_fields > options.max_num_fields {
+        return Err(DocParsingError::MaxNumFieldsExceeded(
+            options.max_num_fields,
+        ));
+    }
+    let mut stack: Vec<(&JsonValue, usize)> = json_obj
+        .values()
+        .map(|json_value| (json_value, 1))
+        .collect();
+
+    while let Some((json_value, parent_depth)) = stack.pop() {
+        let depth = parent_depth + 1;
+
+        match json_value {
+            JsonValue::Array(json_values) => {
+                if depth > options.max_depth {
+                    return Err(DocParsingError::MaxDepthExceeded(options.max_depth));
+                }
+                stack.extend(json_values.iter().map(|json_value| (json_value, depth)));
+            }
+            JsonValue::Object(json_obj) => {
+                if depth > options.max_depth {
+                    return Err(DocParsingError::MaxDepthExceeded(options.max_depth));
+                }
+                num_fields += json_obj.len();
+
+                if num_fields > options.max_num_fields {
+                    return Err(DocParsingError::MaxNumFieldsExceeded(
+                        options.max_num_fields,
+                    ));
+                }
+                stack.extend(json_obj.values().map(|json_value| (json_value, depth)));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn json_doc_sample(json_doc: &[u8]) -> String {
+    std::str::from_utf8(json_doc)
+        .map(|doc_str| doc_str.chars().take(20).chain("...".chars()).collect())
+        .unwrap_or_else(|_| "document contains some invalid UTF-8 characters".to_string())
+}
+
+struct ParserState<'a> {
+    options: &'a JsonParsingOptions,
+    num_fields: usize,
+    num_duplicate_keys: usize,
+    // serde errors are stringly typed, so we keep track of the typed error here.
+    error_opt: Option<DocParsingError>,
+}
+
+impl<'a> ParserState<'a> {
+    fn new(options: &'a JsonParsingOptions) -> Self {
+        Self {
+            options,
+            num_fields: 0,
+            num_duplicate_keys: 0,
+            error_opt: None,
+        }
+    }
+
+    fn fail<E: serde::de::Error>(&mut self, error: DocParsingError) -> E {
+        let serde_error = E::custom(&error);
+        self.error_opt = Some(error);
+        serde_error
+    }
+
+    fn enter(&self, depth: usize) -> Result<(), DocParsingError> {
+        if depth > self.options.max_depth {
+            return Err(DocParsingError::MaxDepthExceeded(self.options.max_depth));
+        }
+        Ok(())
+    }
+
+    fn visit_json_obj<'de, A>(&mut self, depth: usize, mut map: A) -> Result<JsonObject, A::Error>
+    where A: MapAccess<'de> {
+        if let Err(error) = self.enter(depth) {
+            return Err(self.fail(error));
+        }
+        let mut json_obj = JsonObject::new();
+
+        while let Some(key) = map.next_key::<String>()? {
+            self.num_fields += 1;
+
+            if self.num_fields > self.options.max_num_fields {
+                let error = DocParsingError::MaxNumFieldsExceeded(self.options.max_num_fields);
+                return Err(self.fail(error));
+            }
+            if json_obj.contains_key(&key) {
+                match self.options.duplicate_keys {
+                    DuplicateKeysPolicy::Reject => {
+                        return Err(self.fail(DocParsingError::DuplicateKey(key)));
+                    }
+                    DuplicateKeysPolicy::FirstWins => {
+                        self.num_duplicate_keys += 1;
+                        map.next_value::<IgnoredAny>()?;
+                        continue;
+                    }
+                    DuplicateKeysPolicy::LastWins => {
+                        self.num_duplicate_keys += 1;
+                    }
+                }
+            }
+            let json_value = map.next_value_seed(JsonValueSeed {
+                state: &mut *self,
+                depth,
+            })?;
+            json_obj.insert(key, json_value);
+        }
+        Ok(json_obj)
+    }
+}
+
+struct JsonObjectSeed<'a, 'b> {
+    state: &'b mut ParserState<'a>,
+}
+
+impl<'de> DeserializeSeed<'de> for JsonObjectSeed<'_, '_> {
+    type Value = JsonObject;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_map(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonObjectSeed<'_, '_> {
+    type Value = JsonObject;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        self.state.visit_json_obj(1, map)
+    }
+}
+
+/// Deserializes a JSON value nested in a container of depth `depth`.
+struct JsonValueSeed<'a, 'b> {
+    state: &'b mut ParserState<'a>,
+    depth: usize,
+}
+
+impl<'de> DeserializeSeed<'de> for JsonValueSeed<'_, '_> {
+    type Value = JsonValue;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for JsonValueSeed<'_, '_> {
+    type Value = JsonValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+        Ok(JsonValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E> {
+        Ok(JsonValue::Number(value.into()))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E> {
+        Ok(JsonValue::Number(value.into()))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E> {
+        Ok(JsonNumber::from_f64(value).map_or(JsonValue::Null, JsonValue::Number))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E> {
+        Ok(JsonValue::String(value.to_string()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Self::Value, E> {
+        Ok(JsonValue::String(value))
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E> {
+        Ok(JsonValue::Null)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+    where A: SeqAccess<'de> {
+        let depth = self.depth + 1;
+
+        if let Err(error) = self.state.enter(depth) {
+            return Err(self.state.fail(error));
+        }
+        let mut json_values = Vec::new();
+
+        while let Some(json_value) = seq.next_element_seed(JsonValueSeed {
+            state: &mut *self.state,
+            depth,
+        })? {
+            json_values.push(json_value);
+        }
+        Ok(JsonValue::Array(json_values))
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where A: MapAccess<'de> {
+        self.state
+            .visit_json_obj(self.depth + 1, map)
+            .map(JsonValue::Object)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn nested_arrays(depth: usize) -> String {
+        format!(r#"{{"body": {}{}}}"#, "[".repeat(depth), "]".repeat(depth))
+    }
+
+    fn nested_objects(depth: usize) -> String {
+        format!(
+            r#"{{"body": {}1{}}}"#,
+            r#"{"a": "#.repeat(depth),
+            "}".repeat(depth)
+        )
+    }
+
+    #[test]
+    fn test_parse_json_obj() {
+        let options = JsonParsingOptions::default();
+        let (json_obj, num_duplicate_keys) = parse_json_obj(
+            br#"{"body": "foo", "attributes": {"count": 3, "ratio": 0.5, "tags": [null, true]}}"#,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            JsonValue::Object(json_obj),
+            json!({"body": "foo", "attributes": {"count": 3, "ratio": 0.5, "tags": [null, true]}})
+        );
+        assert_eq!(num_duplicate_keys, 0);
+
+        let error = parse_json_obj(br#"["body"]"#, &options).unwrap_err();
+        assert_eq!(
+            error,
+            DocParsingError::NotJsonObject(r#"["body"]..."#.to_string())
+        );
+
+        let error = parse_json_obj(br#"{"body": "foo"} {}"#, &options).unwrap_err();
+        assert!(matches!(error, DocParsingError::NotJsonObject(_)));
+    }
+
+    #[test]
+    fn test_parse_json_obj_max_depth() {
+        let options = JsonParsingOptions {
+            max_depth: 4,
+            ..Default::default()
+        };
+        // The root object counts as one level.
+        parse_json_obj(nested_arrays(3).as_bytes(), &options).unwrap();
+        parse_json_obj(nested_objects(3).as_bytes(), &options).unwrap();
+
+        let error = parse_json_obj(nested_arrays(4).as_bytes(), &options).unwrap_err();
+        assert_eq!(error, DocParsingError::MaxDepthExceeded(4));
+
+        let error = parse_json_obj(nested_objects(4).as_bytes(), &options).unwrap_err();
+        assert_eq!(error, DocParsingError::MaxDepthExceeded(4));
+    }
+
+    #[test]
+    fn test_parse_json_obj_pathological_depth() {
+        let options = JsonParsingOptions::default();
+
+        for depth in [100, 1_000, 100_000] {
+            let error = parse_json_obj(nested_arrays(depth).as_bytes(), &options).unwrap_err();
+            assert_eq!(error, DocParsingError::MaxDepthExceeded(options.max_depth));
+
+            let error = parse_json_obj(nested_objects(depth).as_bytes(), &options).unwrap_err();
+            assert_eq!(error, DocParsingError::MaxDepthExceeded(options.max_depth));
+        }
+    }
+
+    #[test]
+    fn test_parse_json_obj_max_num_fields() {
+        let options = JsonParsingOptions {
+            max_num_fields: 3,
+            ..Default::default()
+        };
+        parse_json_obj(br#"{"a": 1, "b": {"c": 2}}"#, &options).unwrap();
+
+        let error = parse_json_obj(br#"{"a": 1, "b": {"c": 2, "d": 3}}"#, &options).unwrap_err();
+        assert_eq!(error, DocParsingError::MaxNumFieldsExceeded(3));
+
+        let wide_doc = format!(
+            "{{{}}}",
+            (0..100_000)
+                .map(|i| format!(r#""field_{i}": {i}"#))
+                .collect::<Vec<_>>()
+                .join(",")
+        );
+        let error = parse_json_obj(wide_doc.as_bytes(), &options).unwrap_err();
+        assert_eq!(error, DocParsingError::MaxNumFieldsExceeded(3));
+    }
+
+    #[test]
+    fn test_parse_json_obj_duplicate_keys() {
+        let json_doc = br#"{"a": 1, "b": {"c": 2, "c": 3}, "a": {"d": 4}}"#;
+        {
+            let options = JsonParsingOptions {
+                duplicate_keys: DuplicateKeysPolicy::Reject,
+                ..Default::default()
+            };
+            let error = parse_json_obj(json_doc, &options).unwrap_err();
+            assert_eq!(error, DocParsingError::DuplicateKey("c".to_string()));
+        }
+        {
+            let options = JsonParsingOptions {
+                duplicate_keys: DuplicateKeysPolicy::FirstWins,
+                ..Default::default()
+            };
+            let (json_obj, num_duplicate_keys) = parse_json_obj(json_doc, &options).unwrap();
+            assert_eq!(JsonValue::Object(json_obj), json!({"a": 1, "b": {"c": 2}}));
+            assert_eq!(num_duplicate_keys, 2);
+        }
+        {
+            let options = JsonParsingOptions {
+                duplicate_keys: DuplicateKeysPolicy::LastWins,
+                ..Default::default()
+            };
+            let (json_obj, num_duplicate_keys) = parse_json_obj(json_doc, &options).unwrap();
+            assert_eq!(
+                JsonValue::Object(json_obj),
+                json!({"a": {"d": 4}, "b": {"c": 3}})
+            );
+            assert_eq!(num_duplicate_keys, 2);
+        }
+    }
+
+    #[test]
+    fn test_check_json_obj_limits() {
+        let options = JsonParsingOptions {
+            max_depth: 3,
+            max_num_fields: 4,
+            ..Default::default()
+        };
+        let JsonValue::Object(json_obj) = json!({"a": [1, {"b": 2}], "c": {"d": 3}}) else {
+            unreachable!()
+        };
+        check_json_obj_limits(&json_obj, &options).unwrap();
+
+        let JsonValue::Object(json_obj) = json!({"a": [1, {"b": [2]}]}) else {
+            unreachable!()
+        };
+        let error = check_json_obj_limits(&json_obj, &options).unwrap_err();
+        assert_eq!(error, DocParsingError::MaxDepthExceeded(3));
+
+        let JsonValue::Object(json_obj) = json!({"a": 1, "b": {"c": 2, "d": 3, "e": 4}}) else {
+            unreachable!()
+        };
+        let error = check_json_obj_limits(&json_obj, &options).unwrap_err();
+        assert_eq!(error, DocParsingError::MaxNumFieldsExceeded(4));
+    }
+
+    #[test]
+    fn test_json_parsing_options_validate() {
+        JsonParsingOptions::default().validate().unwrap();
+
+        let options = JsonParsingOptions {
+            max_depth: 0,
+            ..Default::default()
+        };
+        options.validate().unwrap_err();
+
+        let options = JsonParsingOptions {
+            max_depth: 128,
+            ..Default::default()
+        };
+        options.validate().unwrap_err();
+
+        let options = JsonParsingOptions {
+            max_num_fields: 0,
+            ..Default::default()
+        };
+        options.validate().unwrap_err();
+    }
+}
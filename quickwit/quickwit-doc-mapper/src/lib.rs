@@ -28,6 +28,7 @@
 mod default_doc_mapper;
 mod doc_mapper;
 mod error;
+mod json_parsing;
 mod query_builder;
 mod routing_expression;
 
@@ -45,6 +46,9 @@ use default_doc_mapper::{
 };
 pub use doc_mapper::{DocMapper, JsonObject, NamedField, TermRange, WarmupInfo};
 pub use error::{DocParsingError, QueryParserError};
+pub use json_parsing::{
+    check_json_obj_limits, parse_json_obj, DuplicateKeysPolicy, JsonParsingOptions,
+};
 use quickwit_common::shared_consts::FIELD_PRESENCE_FIELD_NAME;
 
 /// Field name reserved for storing the source document.
@@ -68,9 +72,11 @@ pub(crate) enum Cardinality {
 
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(
+    DuplicateKeysPolicy,
     FastFieldOptions,
     FieldMappingEntryForSerialization,
     IndexRecordOptionSchema,
+    JsonParsingOptions,
     ModeType,
     NgramTokenizerOption,
     QuickwitJsonOptions,
@@ -100,9 +100,10 @@ pub(crate) fn build_query(
         with_validation,
     )?;
 
-    let term_set_query_fields = extract_term_set_query_fields(query_ast, &schema)?;
+    let term_dict_fields = extract_term_dict_fields(query_ast, &schema)?;
     let term_ranges_grouped_by_field =
         extract_prefix_term_ranges(query_ast, &schema, tokenizer_manager)?;
+    let fuzzy_terms_grouped_by_field = extract_fuzzy_terms(query_ast, &schema, tokenizer_manager);
 
     let mut terms_grouped_by_field: HashMap<Field, HashMap<_, bool>> = Default::default();
     query.query_terms(&mut |term, need_position| {
@@ -115,9 +116,10 @@ pub(crate) fn build_query(
     });
 
     let warmup_info = WarmupInfo {
-        term_dict_fields: term_set_query_fields,
+        term_dict_fields,
         terms_grouped_by_field,
         term_ranges_grouped_by_field,
+        fuzzy_terms_grouped_by_field,
         fast_field_names,
         ..WarmupInfo::default()
     };
@@ -132,21 +134,21 @@ fn is_fast_field(schema: &Schema, field_name: &str) -> bool {
     false
 }
 
-struct ExtractTermSetFields<'a> {
+struct ExtractTermDictFields<'a> {
     term_dict_fields_to_warm_up: HashSet<Field>,
     schema: &'a Schema,
 }
 
-impl<'a> ExtractTermSetFields<'a> {
+impl<'a> ExtractTermDictFields<'a> {
     fn new(schema: &'a Schema) -> Self {
-        ExtractTermSetFields {
+        ExtractTermDictFields {
             term_dict_fields_to_warm_up: HashSet::new(),
             schema,
         }
     }
 }
 
-impl<'a, 'b> QueryAstVisitor<'a> for ExtractTermSetFields<'b> {
+impl<'a, 'b> QueryAstVisitor<'a> for ExtractTermDictFields<'b> {
     type Err = anyhow::Error;
 
     fn visit_term_set(&mut self, term_set_query: &'a TermSetQuery) -> anyhow::Result<()> {
@@ -162,11 +164,11 @@ impl<'a, 'b> QueryAstVisitor<'a> for ExtractTermSetFields<'b> {
     }
 }
 
-fn extract_term_set_query_fields(
+fn extract_term_dict_fields(
     query_ast: &QueryAst,
     schema: &Schema,
 ) -> anyhow::Result<HashSet<Field>> {
-    let mut visitor = ExtractTermSetFields::new(schema);
+    let mut visitor = ExtractTermDictFields::new(schema);
     visitor.visit(query_ast)?;
     Ok(visitor.term_dict_fields_to_warm_up)
 }
@@ -268,17 +270,60 @@ fn extract_prefix_term_ranges(
     Ok(visitor.term_ranges_to_warm_up)
 }
 
+type FuzzyDistance = u8;
+
+struct ExtractFuzzyTerms<'a> {
+    schema: &'a Schema,
+    tokenizer_manager: &'a TokenizerManager,
+    fuzzy_terms_to_warm_up: HashMap<Field, HashMap<Term, FuzzyDistance>>,
+}
+
+impl<'a, 'b: 'a> QueryAstVisitor<'a> for ExtractFuzzyTerms<'b> {
+    type Err = Infallible;
+
+    fn visit_full_text(&mut self, full_text_query: &'a FullTextQuery) -> Result<(), Self::Err> {
+        let Some(fuzzy_distance) = full_text_query.params.fuzzy_distance else {
+            return Ok(());
+        };
+        for term in full_text_query.get_fuzzy_terms(self.schema, self.tokenizer_manager) {
+            let max_fuzzy_distance = self
+                .fuzzy_terms_to_warm_up
+                .entry(term.field())
+                .or_default()
+                .entry(term)
+                .or_default();
+            *max_fuzzy_distance = (*max_fuzzy_distance).max(fuzzy_distance);
+        }
+        Ok(())
+    }
+}
+
+fn extract_fuzzy_terms(
+    query_ast: &QueryAst,
+    schema: &Schema,
+    tokenizer_manager: &TokenizerManager,
+) -> HashMap<Field, HashMap<Term, FuzzyDistance>> {
+    let mut visitor = ExtractFuzzyTerms {
+        schema,
+        tokenizer_manager,
+        fuzzy_terms_to_warm_up: HashMap::new(),
+    };
+    // This cannot fail. The error type is Infallible.
+    let _: Result<(), Infallible> = visitor.visit(query_ast);
+    visitor.fuzzy_terms_to_warm_up
+}
+
 #[cfg(test)]
 mod test {
     use quickwit_datetime::{parse_date_time_str, DateTimeInputFormat};
     use quickwit_query::create_default_quickwit_tokenizer_manager;
-    use quickwit_query::query_ast::query_ast_from_user_text;
+    use quickwit_query::query_ast::{apply_fuzzy_distance, query_ast_from_user_text};
     use tantivy::columnar::MonotonicallyMappableToU64;
     use tantivy::schema::{Schema, FAST, INDEXED, STORED, TEXT};
     use tantivy::{DateOptions, DateTime, DateTimePrecision};
 
     use super::build_query;
-    use crate::{DYNAMIC_FIELD_NAME, SOURCE_FIELD_NAME};
+    use crate::{DYNAMIC_FIELD_NAME, FIELD_PRESENCE_FIELD_NAME, SOURCE_FIELD_NAME};
 
     enum TestExpectation<'a> {
         Err(&'a str),
@@ -306,6 +351,7 @@ mod test {
         if dynamic_mode {
             schema_builder.add_json_field(DYNAMIC_FIELD_NAME, TEXT);
         }
+        schema_builder.add_u64_field(FIELD_PRESENCE_FIELD_NAME, INDEXED);
         schema_builder.build()
     }
 
@@ -679,4 +725,64 @@ mod test {
         .unwrap();
         assert!(warmup_info.term_dict_fields.is_empty());
     }
+
+    #[test]
+    fn test_build_query_warmup_info_exists() {
+        let query_ast = query_ast_from_user_text(
+            "_exists_:ip AND _exists_:title AND NOT _exists_:u64_fast",
+            None,
+        )
+        .parse_user_query(&[])
+        .unwrap();
+        let schema = make_schema(true);
+        let field_presence_field = schema.get_field(FIELD_PRESENCE_FIELD_NAME).unwrap();
+
+        let (_, warmup_info) = build_query(
+            &query_ast,
+            schema,
+            &create_default_quickwit_tokenizer_manager(),
+            &[],
+            true,
+        )
+        .unwrap();
+        // Fast fields are checked with their column index...
+        assert_eq!(warmup_info.fast_field_names.len(), 2);
+        assert!(warmup_info.fast_field_names.contains("ip"));
+        assert!(warmup_info.fast_field_names.contains("u64_fast"));
+        // ... and indexed fields with a term of the field presence field.
+        assert_eq!(warmup_info.terms_grouped_by_field.len(), 1);
+        assert_eq!(
+            warmup_info.terms_grouped_by_field[&field_presence_field].len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_build_query_warmup_info_fuzzy() {
+        let query_ast = query_ast_from_user_text("desc:helo AND server.running:true", None)
+            .parse_user_query(&[])
+            .unwrap();
+        let fuzzy_query_ast = apply_fuzzy_distance(query_ast, 1);
+
+        let (query, warmup_info) = build_query(
+            &fuzzy_query_ast,
+            make_schema(true),
+            &create_default_quickwit_tokenizer_manager(),
+            &[],
+            true,
+        )
+        .unwrap();
+        assert!(format!("{query:?}").contains("FuzzyTermQuery"));
+        // Only the terms of the dictionary matched by the fuzzy term are warmed up, and only the
+        // text field is matched approximately.
+        assert!(warmup_info.term_dict_fields.is_empty());
+        assert_eq!(warmup_info.fuzzy_terms_grouped_by_field.len(), 1);
+        let desc_field = tantivy::schema::Field::from_field_id(1);
+        let fuzzy_terms = &warmup_info.fuzzy_terms_grouped_by_field[&desc_field];
+        assert_eq!(fuzzy_terms.len(), 1);
+        assert_eq!(
+            fuzzy_terms[&tantivy::Term::from_field_text(desc_field, "helo")],
+            1
+        );
+    }
 }
@@ -25,18 +25,19 @@ use std::time::Duration;
 use futures::Future;
 use quickwit_common::{PrettySample, Progress, ServiceStream};
 use quickwit_metastore::{
-    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
+    ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, Split, SplitInfo,
     SplitMetadata, SplitState,
 };
 use quickwit_proto::metastore::{
-    DeleteSplitsRequest, ListSplitsRequest, ListSplitsResponse, MarkSplitsForDeletionRequest,
-    MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
+    DeleteSplitsRequest, ListIndexSnapshotsRequest, ListSplitsRequest, ListSplitsResponse,
+    MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult, MetastoreService,
+    MetastoreServiceClient,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use quickwit_storage::{BulkDeleteError, Storage};
 use thiserror::Error;
 use time::OffsetDateTime;
-use tracing::{error, instrument};
+use tracing::{error, info, instrument};
 
 /// The maximum number of splits that the GC should delete per attempt.
 const DELETE_SPLITS_BATCH_SIZE: usize = 1000;
@@ -64,12 +65,45 @@ where Fut: Future<Output = T> {
     }
 }
 
+/// Returns the IDs of the splits referenced by the snapshots of an index. These splits must not be
+/// deleted until all the snapshots referencing them are deleted.
+async fn list_snapshot_protected_split_ids(
+    index_uid: &IndexUid,
+    metastore: &mut MetastoreServiceClient,
+    progress_opt: Option<&Progress>,
+) -> MetastoreResult<HashSet<SplitId>> {
+    let list_snapshots_request = ListIndexSnapshotsRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let snapshots = protect_future(
+        progress_opt,
+        metastore.list_index_snapshots(list_snapshots_request),
+    )
+    .await?
+    .snapshots;
+    let protected_split_ids = snapshots
+        .into_iter()
+        .flat_map(|snapshot| snapshot.split_ids)
+        .collect();
+    Ok(protected_split_ids)
+}
+
 /// Information on what splits have and have not been cleaned up by the GC.
 pub struct SplitRemovalInfo {
     /// The set of splits that have been removed.
     pub removed_split_entries: Vec<SplitInfo>,
     /// The set of split ids that were attempted to be removed, but were unsuccessful.
     pub failed_splits: Vec<SplitInfo>,
+    /// The set of splits left untouched because they were updated within the grace period. Only
+    /// populated in dry run mode.
+    pub skipped_splits: Vec<SplitInfo>,
+    /// The set of splits marked for deletion left untouched because they are referenced by an
+    /// index snapshot. Only populated in dry run mode.
+    pub protected_splits: Vec<SplitInfo>,
+    /// The set of published splits that were marked for deletion, or would be in dry run mode,
+    /// because their time range ended before the TTL. Their files are deleted by a later garbage
+    /// collection, once the deletion grace period has elapsed.
+    pub expired_splits: Vec<SplitInfo>,
 }
 
 /// Detect all dangling splits and associated files from the index and removes them.
@@ -109,27 +143,67 @@ pub async fn run_garbage_collect(
     .collect_splits_metadata()
     .await?;
 
+    // We delete splits marked for deletion that have an update timestamp anterior
+    // to `now - deletion_grace_period`.
+    let updated_before_timestamp =
+        OffsetDateTime::now_utc().unix_timestamp() - deletion_grace_period.as_secs() as i64;
+
     if dry_run {
         let marked_for_deletion_query = ListSplitsQuery::for_index(index_uid.clone())
             .with_split_state(SplitState::MarkedForDeletion);
         let marked_for_deletion_request =
             ListSplitsRequest::try_from_list_splits_query(marked_for_deletion_query)?;
-        let mut splits_marked_for_deletion: Vec<SplitMetadata> = protect_future(
+        let splits_marked_for_deletion: Vec<Split> = protect_future(
             progress_opt,
             metastore.list_splits(marked_for_deletion_request),
         )
         .await?
-        .collect_splits_metadata()
+        .collect_splits()
         .await?;
-        splits_marked_for_deletion.extend(deletable_staged_splits);
-
-        let candidate_entries: Vec<SplitInfo> = splits_marked_for_deletion
+        let protected_split_ids =
+            list_snapshot_protected_split_ids(&index_uid, &mut metastore, progress_opt).await?;
+        let (protected_splits, unprotected_splits): (Vec<Split>, Vec<Split>) =
+            splits_marked_for_deletion
+                .into_iter()
+                .partition(|split| protected_split_ids.contains(split.split_id()));
+        let (deletable_splits, skipped_marked_splits): (Vec<Split>, Vec<Split>) =
+            unprotected_splits
+                .into_iter()
+                .partition(|split| split.update_timestamp <= updated_before_timestamp);
+
+        let skipped_staged_query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_split_state(SplitState::Staged)
+            .with_update_timestamp_gt(grace_period_timestamp);
+        let skipped_staged_request =
+            ListSplitsRequest::try_from_list_splits_query(skipped_staged_query)?;
+        let skipped_staged_splits: Vec<SplitMetadata> =
+            protect_future(progress_opt, metastore.list_splits(skipped_staged_request))
+                .await?
+                .collect_splits_metadata()
+                .await?;
+
+        let candidate_entries: Vec<SplitInfo> = deletable_splits
+            .into_iter()
+            .map(|split| split.split_metadata)
+            .chain(deletable_staged_splits)
+            .map(|split| split.as_split_info())
+            .collect();
+        let skipped_entries: Vec<SplitInfo> = skipped_marked_splits
             .into_iter()
+            .map(|split| split.split_metadata)
+            .chain(skipped_staged_splits)
             .map(|split| split.as_split_info())
             .collect();
+        let protected_entries: Vec<SplitInfo> = protected_splits
+            .into_iter()
+            .map(|split| split.split_metadata.as_split_info())
+            .collect();
         return Ok(SplitRemovalInfo {
             removed_split_entries: candidate_entries,
             failed_splits: Vec::new(),
+            skipped_splits: skipped_entries,
+            protected_splits: protected_entries,
+            expired_splits: Vec::new(),
         });
     }
 
@@ -148,11 +222,6 @@ pub async fn run_garbage_collect(
         .await?;
     }
 
-    // We delete splits marked for deletion that have an update timestamp anterior
-    // to `now - deletion_grace_period`.
-    let updated_before_timestamp =
-        OffsetDateTime::now_utc().unix_timestamp() - deletion_grace_period.as_secs() as i64;
-
     let deleted_splits = delete_splits_marked_for_deletion(
         index_uid,
         updated_before_timestamp,
@@ -164,6 +233,61 @@ pub async fn run_garbage_collect(
 
     Ok(deleted_splits)
 }
+
+/// Marks for deletion the published splits whose time range ended before `now - ttl`. Splits
+/// without a time range never expire. Returns the expired splits.
+///
+/// * `index_uid` - The target index UID.
+/// * `metastore` - The metastore managing the target index.
+/// * `ttl` - Period after the end of their time range after which splits expire.
+/// * `dry_run` - Should this only return the list of expired splits without marking them.
+/// * `progress` - For reporting progress (useful when called from within a quickwit actor).
+pub async fn mark_expired_splits_for_deletion(
+    index_uid: IndexUid,
+    mut metastore: MetastoreServiceClient,
+    ttl: Duration,
+    dry_run: bool,
+    progress_opt: Option<&Progress>,
+) -> anyhow::Result<Vec<SplitInfo>> {
+    let expiration_timestamp = OffsetDateTime::now_utc().unix_timestamp() - ttl.as_secs() as i64;
+    let query = ListSplitsQuery::for_index(index_uid.clone())
+        .with_split_state(SplitState::Published)
+        .with_time_range_end_lte(expiration_timestamp);
+
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+    let expired_splits: Vec<SplitInfo> =
+        protect_future(progress_opt, metastore.list_splits(list_splits_request))
+            .await?
+            .collect_splits_metadata()
+            .await?
+            .into_iter()
+            .filter(|split_metadata| split_metadata.time_range.is_some())
+            .map(|split_metadata| split_metadata.as_split_info())
+            .collect();
+
+    if dry_run || expired_splits.is_empty() {
+        return Ok(expired_splits);
+    }
+    let expired_split_ids: Vec<SplitId> = expired_splits
+        .iter()
+        .map(|split_info| split_info.split_id.clone())
+        .collect();
+    info!(
+        index_id=%index_uid.index_id(),
+        split_ids=?PrettySample::new(&expired_split_ids, 5),
+        "marking {} expired splits for deletion",
+        expired_split_ids.len()
+    );
+    let mark_splits_for_deletion_request =
+        MarkSplitsForDeletionRequest::new(index_uid, expired_split_ids);
+    protect_future(
+        progress_opt,
+        metastore.mark_splits_for_deletion(mark_splits_for_deletion_request),
+    )
+    .await?;
+    Ok(expired_splits)
+}
+
 #[instrument(skip(storage, metastore, progress_opt))]
 /// Removes any splits marked for deletion which haven't been
 /// updated after `updated_before_timestamp` in batches of 1000 splits.
@@ -180,10 +304,31 @@ async fn delete_splits_marked_for_deletion(
     let mut removed_splits = Vec::new();
     let mut failed_splits = Vec::new();
 
+    let protected_split_ids =
+        match list_snapshot_protected_split_ids(&index_uid, &mut metastore, progress_opt).await {
+            Ok(protected_split_ids) => protected_split_ids,
+            Err(error) => {
+                // Deleting splits without knowing which ones are protected is not safe.
+                error!(error = ?error, "failed to list index snapshots");
+                return SplitRemovalInfo {
+                    removed_split_entries: Vec::new(),
+                    failed_splits: Vec::new(),
+                    skipped_splits: Vec::new(),
+                    protected_splits: Vec::new(),
+                    expired_splits: Vec::new(),
+                };
+            }
+        };
+
+    // Protected splits stay marked for deletion. The splits are listed in split ID order, so
+    // that the next batches can skip the protected splits found by the previous ones.
+    let mut num_protected_splits = 0;
+
     loop {
         let query = ListSplitsQuery::for_index(index_uid.clone())
             .with_split_state(SplitState::MarkedForDeletion)
             .with_update_timestamp_lte(updated_before_timestamp)
+            .with_offset(num_protected_splits)
             .with_limit(DELETE_SPLITS_BATCH_SIZE);
 
         let list_splits_request = match ListSplitsRequest::try_from_list_splits_query(query) {
@@ -204,7 +349,7 @@ async fn delete_splits_marked_for_deletion(
                 }
             };
 
-        let splits_metadata_to_delete: Vec<SplitMetadata> =
+        let mut splits_metadata_to_delete: Vec<SplitMetadata> =
             match splits_to_delete_stream.collect_splits_metadata().await {
                 Ok(splits) => splits,
                 Err(error) => {
@@ -213,11 +358,21 @@ async fn delete_splits_marked_for_deletion(
                 }
             };
 
-        let num_splits_to_delete = splits_metadata_to_delete.len();
+        let num_listed_splits = splits_metadata_to_delete.len();
 
-        if num_splits_to_delete == 0 {
+        if num_listed_splits == 0 {
             break;
         }
+        splits_metadata_to_delete
+            .retain(|split_metadata| !protected_split_ids.contains(&split_metadata.split_id));
+        num_protected_splits += num_listed_splits - splits_metadata_to_delete.len();
+
+        if splits_metadata_to_delete.is_empty() {
+            if num_listed_splits < DELETE_SPLITS_BATCH_SIZE {
+                break;
+            }
+            continue;
+        }
         let delete_splits_result = delete_splits_from_storage_and_metastore(
             index_uid.clone(),
             storage.clone(),
@@ -235,13 +390,16 @@ async fn delete_splits_marked_for_deletion(
                 break;
             }
         }
-        if num_splits_to_delete < DELETE_SPLITS_BATCH_SIZE {
+        if num_listed_splits < DELETE_SPLITS_BATCH_SIZE {
             break;
         }
     }
     SplitRemovalInfo {
         removed_split_entries: removed_splits,
         failed_splits,
+        skipped_splits: Vec::new(),
+        protected_splits: Vec::new(),
+        expired_splits: Vec::new(),
     }
 }
 
@@ -354,17 +512,21 @@ mod tests {
     use quickwit_common::ServiceStream;
     use quickwit_config::IndexConfig;
     use quickwit_metastore::{
-        metastore_for_test, CreateIndexRequestExt, ListSplitsQuery,
+        metastore_for_test, CreateIndexRequestExt, ListSplitsQuery, ListSplitsResponseExt,
         MetastoreServiceStreamSplitsExt, SplitMetadata, SplitState, StageSplitsRequestExt,
     };
-    use quickwit_proto::metastore::{CreateIndexRequest, EntityKind, StageSplitsRequest};
+    use quickwit_proto::metastore::{
+        CreateIndexRequest, CreateIndexSnapshotRequest, DeleteIndexSnapshotRequest, EmptyResponse,
+        EntityKind, IndexSnapshot, ListIndexSnapshotsResponse, PublishSplitsRequest,
+        StageSplitsRequest,
+    };
     use quickwit_proto::types::IndexUid;
     use quickwit_storage::{
         storage_for_test, BulkDeleteError, DeleteFailure, MockStorage, PutPayload,
     };
 
     use super::*;
-    use crate::run_garbage_collect;
+    use crate::{mark_expired_splits_for_deletion, run_garbage_collect};
 
     #[tokio::test]
     async fn test_run_gc_marks_stale_staged_splits_for_deletion_after_grace_period() {
@@ -567,15 +729,345 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_run_gc_does_not_delete_splits_protected_by_snapshot() {
+        let storage = storage_for_test();
+        let mut metastore = metastore_for_test();
+
+        let index_id = "test-run-gc-snapshot--index";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(index_id, &index_uri);
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+
+        let split_id = "test-run-gc-snapshot--split";
+        let split_metadata = SplitMetadata {
+            split_id: split_id.to_string(),
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata).unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: index_uid.to_string(),
+            staged_split_ids: vec![split_id.to_string()],
+            ..Default::default()
+        };
+        metastore
+            .publish_splits(publish_splits_request)
+            .await
+            .unwrap();
+
+        let create_snapshot_request = CreateIndexSnapshotRequest {
+            index_uid: index_uid.to_string(),
+        };
+        let snapshot = metastore
+            .create_index_snapshot(create_snapshot_request)
+            .await
+            .unwrap();
+        assert_eq!(snapshot.split_ids, [split_id]);
+
+        let mark_splits_for_deletion_request =
+            MarkSplitsForDeletionRequest::new(index_uid.clone(), vec![split_id.to_string()]);
+        metastore
+            .mark_splits_for_deletion(mark_splits_for_deletion_request)
+            .await
+            .unwrap();
+
+        // The split is referenced by the snapshot so it is reported as protected in dry run mode.
+        let split_removal_info = run_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::from_secs(30),
+            Duration::from_secs(0),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(split_removal_info.removed_split_entries.is_empty());
+        assert!(split_removal_info.skipped_splits.is_empty());
+        assert_eq!(split_removal_info.protected_splits.len(), 1);
+        assert_eq!(split_removal_info.protected_splits[0].split_id, split_id);
+
+        // The split is referenced by the snapshot so it is not deleted.
+        let split_removal_info = run_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::from_secs(30),
+            Duration::from_secs(0),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(split_removal_info.removed_split_entries.is_empty());
+
+        let query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_split_state(SplitState::MarkedForDeletion);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query).unwrap();
+        assert_eq!(
+            metastore
+                .list_splits(list_splits_request)
+                .await
+                .unwrap()
+                .collect_splits()
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+
+        // Once the snapshot is deleted, the split can be deleted.
+        let delete_snapshot_request = DeleteIndexSnapshotRequest {
+            index_uid: index_uid.to_string(),
+            snapshot_id: snapshot.snapshot_id,
+        };
+        metastore
+            .delete_index_snapshot(delete_snapshot_request)
+            .await
+            .unwrap();
+
+        let split_removal_info = run_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::from_secs(30),
+            Duration::from_secs(0),
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(split_removal_info.removed_split_entries.len(), 1);
+
+        let query = ListSplitsQuery::for_index(index_uid);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query).unwrap();
+        assert_eq!(
+            metastore
+                .list_splits(list_splits_request)
+                .await
+                .unwrap()
+                .collect_splits()
+                .await
+                .unwrap()
+                .len(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_gc_dry_run_reports_splits_within_grace_period() {
+        let storage = storage_for_test();
+        let mut metastore = metastore_for_test();
+
+        let index_id = "test-run-gc--index";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(index_id, &index_uri);
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+
+        let split_ids = ["test-run-gc--split-1", "test-run-gc--split-2"];
+        let splits_metadata: Vec<SplitMetadata> = split_ids
+            .iter()
+            .map(|split_id| SplitMetadata {
+                split_id: split_id.to_string(),
+                index_uid: index_uid.clone(),
+                ..Default::default()
+            })
+            .collect();
+        let stage_splits_request =
+            StageSplitsRequest::try_from_splits_metadata(index_uid.clone(), splits_metadata)
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+        let mark_splits_for_deletion_request =
+            MarkSplitsForDeletionRequest::new(index_uid.clone(), vec![split_ids[1].to_string()]);
+        metastore
+            .mark_splits_for_deletion(mark_splits_for_deletion_request)
+            .await
+            .unwrap();
+
+        let removal_info = run_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::from_secs(30),
+            Duration::from_secs(30),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(removal_info.removed_split_entries.is_empty());
+        assert!(removal_info.failed_splits.is_empty());
+
+        let mut skipped_split_ids: Vec<String> = removal_info
+            .skipped_splits
+            .into_iter()
+            .map(|split_info| split_info.split_id)
+            .collect();
+        skipped_split_ids.sort();
+        assert_eq!(skipped_split_ids, split_ids);
+
+        let removal_info = run_garbage_collect(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::from_secs(0),
+            Duration::from_secs(0),
+            true,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(removal_info.removed_split_entries.len(), 2);
+        assert!(removal_info.skipped_splits.is_empty());
+
+        // Nothing has been touched in dry run mode.
+        let query = ListSplitsQuery::for_index(index_uid);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query).unwrap();
+        assert_eq!(
+            metastore
+                .list_splits(list_splits_request)
+                .await
+                .unwrap()
+                .collect_splits()
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mark_expired_splits_for_deletion() {
+        let mut metastore = metastore_for_test();
+
+        let index_id = "test-mark-expired-splits--index";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(index_id, &index_uri);
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let index_uid: IndexUid = metastore
+            .create_index(create_index_request)
+            .await
+            .unwrap()
+            .index_uid
+            .into();
+
+        let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let day_secs = 24 * 60 * 60;
+        let split_time_ranges = [
+            (
+                "test-mark-expired-splits--old",
+                Some(0..=now_timestamp - 40 * day_secs),
+            ),
+            (
+                "test-mark-expired-splits--straddling",
+                Some(now_timestamp - 40 * day_secs..=now_timestamp - day_secs),
+            ),
+            ("test-mark-expired-splits--no-time-range", None),
+        ];
+        let splits_metadata: Vec<SplitMetadata> = split_time_ranges
+            .iter()
+            .map(|(split_id, time_range)| SplitMetadata {
+                split_id: split_id.to_string(),
+                index_uid: index_uid.clone(),
+                time_range: time_range.clone(),
+                ..Default::default()
+            })
+            .collect();
+        let split_ids: Vec<String> = split_time_ranges
+            .iter()
+            .map(|(split_id, _)| split_id.to_string())
+            .collect();
+        let stage_splits_request =
+            StageSplitsRequest::try_from_splits_metadata(index_uid.clone(), splits_metadata)
+                .unwrap();
+        metastore.stage_splits(stage_splits_request).await.unwrap();
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: index_uid.to_string(),
+            staged_split_ids: split_ids,
+            ..Default::default()
+        };
+        metastore
+            .publish_splits(publish_splits_request)
+            .await
+            .unwrap();
+
+        let ttl = Duration::from_secs(30 * day_secs as u64);
+        let expired_splits =
+            mark_expired_splits_for_deletion(index_uid.clone(), metastore.clone(), ttl, true, None)
+                .await
+                .unwrap();
+        assert_eq!(expired_splits.len(), 1);
+        assert_eq!(expired_splits[0].split_id, "test-mark-expired-splits--old");
+
+        let query =
+            ListSplitsQuery::for_index(index_uid.clone()).with_split_state(SplitState::Published);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query).unwrap();
+        assert_eq!(
+            metastore
+                .list_splits(list_splits_request)
+                .await
+                .unwrap()
+                .collect_splits()
+                .await
+                .unwrap()
+                .len(),
+            3
+        );
+
+        let expired_splits = mark_expired_splits_for_deletion(
+            index_uid.clone(),
+            metastore.clone(),
+            ttl,
+            false,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(expired_splits.len(), 1);
+
+        let query =
+            ListSplitsQuery::for_index(index_uid).with_split_state(SplitState::MarkedForDeletion);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query).unwrap();
+        let marked_splits = metastore
+            .list_splits(list_splits_request)
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap();
+        assert_eq!(marked_splits.len(), 1);
+        assert_eq!(marked_splits[0].split_id(), "test-mark-expired-splits--old");
+    }
+
     #[tokio::test]
     async fn test_run_gc_deletes_splits_with_no_split() {
-        // Test that we make only 2 calls to the metastore.
+        // Test that we make only 3 calls to the metastore.
         let storage = storage_for_test();
         let mut metastore = MetastoreServiceClient::mock();
         metastore
             .expect_list_splits()
             .times(2)
             .returning(|_| Ok(ServiceStream::empty()));
+        metastore
+            .expect_list_index_snapshots()
+            .times(1)
+            .returning(|_| Ok(ListIndexSnapshotsResponse::default()));
         run_garbage_collect(
             IndexUid::new_with_random_ulid("index-test-gc-deletes"),
             storage.clone(),
@@ -821,4 +1313,79 @@ mod tests {
         assert_eq!(error.storage_failures.len(), 1);
         assert_eq!(error.metastore_failures.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_delete_splits_marked_for_deletion_skips_protected_splits() {
+        let index_uid = IndexUid::new_with_random_ulid("test-delete-splits-protected--index");
+        // A whole batch of splits protected by a snapshot must not prevent the deletion of the
+        // splits listed after them.
+        let protected_split_ids: Vec<SplitId> = (0..DELETE_SPLITS_BATCH_SIZE)
+            .map(|split_ord| format!("protected-{split_ord:04}"))
+            .collect();
+        let snapshot = IndexSnapshot {
+            index_uid: index_uid.to_string(),
+            split_ids: protected_split_ids.clone(),
+            ..Default::default()
+        };
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_index_snapshots()
+            .return_once(move |_| {
+                Ok(ListIndexSnapshotsResponse {
+                    snapshots: vec![snapshot],
+                })
+            });
+        let index_uid_clone = index_uid.clone();
+        mock_metastore
+            .expect_list_splits()
+            .times(2)
+            .returning(move |list_splits_request| {
+                let query = list_splits_request.deserialize_list_splits_query().unwrap();
+                let split_ids = match query.offset {
+                    Some(0) => protected_split_ids.clone(),
+                    Some(DELETE_SPLITS_BATCH_SIZE) => vec!["unprotected".to_string()],
+                    offset_opt => panic!("unexpected offset `{offset_opt:?}`"),
+                };
+                let splits = split_ids
+                    .into_iter()
+                    .map(|split_id| Split {
+                        split_state: SplitState::MarkedForDeletion,
+                        update_timestamp: 0,
+                        publish_timestamp: None,
+                        split_metadata: SplitMetadata {
+                            split_id,
+                            index_uid: index_uid_clone.clone(),
+                            ..Default::default()
+                        },
+                    })
+                    .collect();
+                let list_splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(list_splits_response)]))
+            });
+        mock_metastore
+            .expect_delete_splits()
+            .return_once(|delete_splits_request| {
+                assert_eq!(delete_splits_request.split_ids, ["unprotected"]);
+                Ok(EmptyResponse {})
+            });
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_bulk_delete()
+            .return_once(|_split_paths| Ok(()));
+
+        let split_removal_info = delete_splits_marked_for_deletion(
+            index_uid,
+            OffsetDateTime::now_utc().unix_timestamp(),
+            Arc::new(mock_storage),
+            MetastoreServiceClient::from(mock_metastore),
+            None,
+        )
+        .await;
+        assert_eq!(split_removal_info.removed_split_entries.len(), 1);
+        assert_eq!(
+            split_removal_info.removed_split_entries[0].split_id,
+            "unprotected"
+        );
+        assert!(split_removal_info.failed_splits.is_empty());
+    }
 }
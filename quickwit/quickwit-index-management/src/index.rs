@@ -17,31 +17,40 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::path::Path;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 use std::time::Duration;
 
+use anyhow::Context;
 use quickwit_common::fs::{empty_dir, get_cache_directory_path};
-use quickwit_config::{validate_identifier, IndexConfig, SourceConfig};
+use quickwit_config::{build_doc_mapper, validate_identifier, IndexConfig, SourceConfig};
+use quickwit_directories::{CachingDirectory, HotDirectory, StorageDirectory};
+use quickwit_doc_mapper::DocMapper;
 use quickwit_indexing::check_source_connectivity;
 use quickwit_metastore::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
     ListSplitsQuery, ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, SplitInfo,
-    SplitMetadata, SplitState,
+    SplitMaturity, SplitMetadata, SplitState, StageSplitsRequestExt,
 };
 use quickwit_proto::metastore::{
-    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, EntityKind, IndexMetadataRequest,
-    ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreService,
-    MetastoreServiceClient, ResetSourceCheckpointRequest,
+    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, DeleteSplitsRequest, EntityKind,
+    IndexMetadataRequest, ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError,
+    MetastoreService, MetastoreServiceClient, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    StageSplitsRequest,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use quickwit_proto::{ServiceError, ServiceErrorCode};
-use quickwit_storage::{StorageResolver, StorageResolverError};
+use quickwit_storage::{BundleStorage, Storage, StorageResolver, StorageResolverError};
+use tantivy::directory::FileSlice;
+use tantivy::{Index, ReloadPolicy};
 use thiserror::Error;
+use time::OffsetDateTime;
 use tracing::{error, info};
 
 use crate::garbage_collection::{
-    delete_splits_from_storage_and_metastore, run_garbage_collect, DeleteSplitsError,
-    SplitRemovalInfo,
+    delete_splits_from_storage_and_metastore, mark_expired_splits_for_deletion,
+    run_garbage_collect, DeleteSplitsError, SplitRemovalInfo,
 };
 
 #[derive(Error, Debug)]
@@ -231,12 +240,17 @@ impl IndexService {
     /// Detect all dangling splits and associated files from the index and removes them.
     ///
     /// * `index_id` - The target index Id.
-    /// * `grace_period` -  Threshold period after which a staged split can be garbage collected.
+    /// * `grace_period` -  Threshold period after which a staged split can be marked for deletion,
+    ///   and a split marked for deletion can be deleted. It leaves running search queries the time
+    ///   to complete.
+    /// * `ttl_opt` - If set, published splits whose time range ended before `now - ttl` are also
+    ///   marked for deletion.
     /// * `dry_run` - Should this only return a list of affected files without performing deletion.
     pub async fn garbage_collect_index(
         &mut self,
         index_id: &str,
         grace_period: Duration,
+        ttl_opt: Option<Duration>,
         dry_run: bool,
     ) -> anyhow::Result<SplitRemovalInfo> {
         let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
@@ -252,20 +266,28 @@ impl IndexService {
             .resolve(&index_config.index_uri)
             .await?;
 
-        let deleted_entries = run_garbage_collect(
-            index_uid,
+        let mut removal_info = run_garbage_collect(
+            index_uid.clone(),
             storage,
             self.metastore.clone(),
             grace_period,
-            // deletion_grace_period of zero, so that a cli call directly deletes splits after
-            // marking to be deleted.
-            Duration::ZERO,
+            grace_period,
             dry_run,
             None,
         )
         .await?;
 
-        Ok(deleted_entries)
+        if let Some(ttl) = ttl_opt {
+            removal_info.expired_splits = mark_expired_splits_for_deletion(
+                index_uid,
+                self.metastore.clone(),
+                ttl,
+                dry_run,
+                None,
+            )
+            .await?;
+        }
+        Ok(removal_info)
     }
 
     /// Clears the index by applying the following actions:
@@ -329,6 +351,104 @@ impl IndexService {
         Ok(())
     }
 
+    /// Registers and publishes a split whose files are present in the index storage but which is
+    /// unknown to the metastore, for instance after the metastore was restored from a backup.
+    ///
+    /// The split metadata (number of documents, time range, footer offsets) is reconstructed from
+    /// the split file itself. Unless `force` is set, the operation is rejected if the metastore
+    /// already holds an entry for the split. With `force`, a staged entry or an entry marked for
+    /// deletion is replaced. Published splits are never overwritten.
+    ///
+    /// * `index_id` - The target index Id.
+    /// * `split_id` - The Id of the split to publish.
+    /// * `force` - Replaces a stale staged or marked for deletion entry for the split.
+    pub async fn publish_split_from_storage(
+        &mut self,
+        index_id: &str,
+        split_id: &str,
+        force: bool,
+    ) -> Result<SplitMetadata, IndexServiceError> {
+        let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+        let index_metadata = self
+            .metastore
+            .index_metadata(index_metadata_request)
+            .await?
+            .deserialize_index_metadata()?;
+        let index_uid = index_metadata.index_uid.clone();
+        let list_splits_query =
+            ListSplitsQuery::for_index(index_uid.clone()).with_split_ids([split_id.to_string()]);
+        let list_splits_request = ListSplitsRequest::try_from_list_splits_query(list_splits_query)?;
+        let existing_split_opt = self
+            .metastore
+            .list_splits(list_splits_request)
+            .await?
+            .collect_splits()
+            .await?
+            .into_iter()
+            .find(|split| split.split_id() == split_id);
+
+        let mut should_delete_split_entry = false;
+        if let Some(existing_split) = existing_split_opt {
+            if existing_split.split_state == SplitState::Published {
+                return Err(IndexServiceError::OperationNotAllowed(format!(
+                    "split `{split_id}` is already published"
+                )));
+            }
+            if !force {
+                return Err(IndexServiceError::OperationNotAllowed(format!(
+                    "split `{split_id}` is already registered in the metastore with state `{}`, \
+                     use `--force` to replace it",
+                    existing_split.split_state.as_str()
+                )));
+            }
+            should_delete_split_entry = existing_split.split_state == SplitState::MarkedForDeletion;
+        }
+        let index_config = index_metadata.into_index_config();
+        let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
+            .map_err(IndexServiceError::InvalidConfig)?;
+        let storage = self
+            .storage_resolver
+            .resolve(&index_config.index_uri)
+            .await?;
+        let split_metadata =
+            reconstruct_split_metadata(index_uid.clone(), split_id, storage, doc_mapper)
+                .await
+                .map_err(|error| {
+                    IndexServiceError::Internal(format!(
+                        "failed to reconstruct metadata of split `{split_id}`: {error:#}"
+                    ))
+                })?;
+        // Splits marked for deletion cannot be staged again, so their entry is removed first.
+        if should_delete_split_entry {
+            let delete_splits_request = DeleteSplitsRequest {
+                index_uid: index_uid.to_string(),
+                split_ids: vec![split_id.to_string()],
+            };
+            self.metastore.delete_splits(delete_splits_request).await?;
+        }
+        let stage_splits_request =
+            StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata.clone())?;
+        self.metastore.stage_splits(stage_splits_request).await?;
+
+        let publish_splits_request = PublishSplitsRequest {
+            index_uid: index_uid.to_string(),
+            staged_split_ids: vec![split_id.to_string()],
+            replaced_split_ids: Vec::new(),
+            index_checkpoint_delta_json_opt: None,
+            publish_token_opt: None,
+        };
+        self.metastore
+            .publish_splits(publish_splits_request)
+            .await?;
+        info!(
+            index_id=%index_id,
+            split_id=%split_id,
+            num_docs=split_metadata.num_docs,
+            "published split from storage"
+        );
+        Ok(split_metadata)
+    }
+
     /// Creates a source config for index `index_id`.
     pub async fn create_source(
         &mut self,
@@ -396,6 +516,109 @@ impl IndexService {
     }
 }
 
+/// Reconstructs the metadata of a split from the content of its split file.
+///
+/// Only the footer of the split file and the timestamp fast field are fetched from storage. The
+/// uncompressed size of the documents is not recorded in the split, so the size of the split file
+/// is used instead. Tags are not reconstructed, which only disables tag pruning for this split.
+async fn reconstruct_split_metadata(
+    index_uid: IndexUid,
+    split_id: &str,
+    storage: Arc<dyn Storage>,
+    doc_mapper: Arc<dyn DocMapper>,
+) -> anyhow::Result<SplitMetadata> {
+    let split_file = PathBuf::from(format!("{split_id}.split"));
+    let split_num_bytes = storage.file_num_bytes(&split_file).await?;
+    let footer_offsets =
+        fetch_split_footer_offsets(&*storage, &split_file, split_num_bytes).await?;
+    let footer_data = storage
+        .get_slice(
+            &split_file,
+            footer_offsets.start as usize..footer_offsets.end as usize,
+        )
+        .await?;
+    let (hotcache_bytes, bundle_storage) = BundleStorage::open_from_split_data(
+        storage,
+        split_file,
+        FileSlice::new(Arc::new(footer_data)),
+    )?;
+    // The hotcache holds what is needed to open the index, and the caching directory retains the
+    // fast field data fetched below so that it can then be read synchronously.
+    let storage_directory = StorageDirectory::new(Arc::new(bundle_storage));
+    let caching_directory = CachingDirectory::new_unbounded(Arc::new(storage_directory));
+    let hot_directory = HotDirectory::open(caching_directory, hotcache_bytes.read_bytes()?)?;
+    let index = Index::open(hot_directory)?;
+    let index_reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = index_reader.searcher();
+    let num_docs = searcher.num_docs() as usize;
+
+    let mut time_range = None;
+    if let Some(timestamp_field_name) = doc_mapper.timestamp_field_name() {
+        for segment_reader in searcher.segment_readers() {
+            if segment_reader.num_docs() == 0 {
+                continue;
+            }
+            let fast_fields = segment_reader.fast_fields();
+            for column_handle in fast_fields
+                .list_dynamic_column_handles(timestamp_field_name)
+                .await?
+            {
+                column_handle.file_slice().read_bytes_async().await?;
+            }
+            let timestamp_column = fast_fields.date(timestamp_field_name)?;
+            let start = timestamp_column.min_value().into_timestamp_secs();
+            let end = timestamp_column.max_value().into_timestamp_secs();
+            time_range = match time_range {
+                Some(range) => Some(start.min(*range.start())..=end.max(*range.end())),
+                None => Some(start..=end),
+            };
+        }
+    }
+    let split_metadata = SplitMetadata {
+        split_id: split_id.to_string(),
+        index_uid,
+        num_docs,
+        uncompressed_docs_size_in_bytes: split_num_bytes,
+        time_range,
+        create_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+        maturity: SplitMaturity::Mature,
+        footer_offsets,
+        ..Default::default()
+    };
+    Ok(split_metadata)
+}
+
+/// Returns the byte range of the footer of a split file, reading only the lengths it ends with.
+///
+/// Split Format:
+/// `[Files][FilesMetadata][FilesMetadata length 4 bytes][Hotcache][Hotcache length 4 bytes]`
+async fn fetch_split_footer_offsets(
+    storage: &dyn Storage,
+    split_file: &Path,
+    split_num_bytes: u64,
+) -> anyhow::Result<Range<u64>> {
+    let read_len = |end: u64| async move {
+        let start = end.checked_sub(4).context("split file is truncated")?;
+        let len_bytes = storage
+            .get_slice(split_file, start as usize..end as usize)
+            .await?;
+        let len_bytes: [u8; 4] = len_bytes.as_slice().try_into()?;
+        anyhow::Ok(u32::from_le_bytes(len_bytes) as u64)
+    };
+    let hotcache_len = read_len(split_num_bytes).await?;
+    let files_metadata_end = (split_num_bytes - 4)
+        .checked_sub(hotcache_len)
+        .context("split file is truncated")?;
+    let files_metadata_len = read_len(files_metadata_end).await?;
+    let footer_start = (files_metadata_end - 4)
+        .checked_sub(files_metadata_len)
+        .context("split file is truncated")?;
+    Ok(footer_start..split_num_bytes)
+}
+
 /// Clears the cache directory of a given source.
 ///
 /// * `data_dir_path` - Path to directory where data (tmp data, splits kept for caching purpose) is
@@ -421,11 +644,13 @@ mod tests {
 
     use quickwit_common::uri::Uri;
     use quickwit_config::IndexConfig;
+    use quickwit_indexing::TestSandbox;
     use quickwit_metastore::{
         metastore_for_test, MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt,
     };
     use quickwit_proto::metastore::StageSplitsRequest;
     use quickwit_storage::PutPayload;
+    use serde_json::json;
 
     use super::*;
 
@@ -528,4 +753,91 @@ mod tests {
         assert!(splits.is_empty());
         assert!(!storage.exists(split_path).await.unwrap());
     }
+
+    #[tokio::test]
+    async fn test_publish_split_from_storage() {
+        let index_id = "test-publish-split-from-storage";
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: ts
+                type: datetime
+                fast: true
+            timestamp_field: ts
+        "#;
+        let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"])
+            .await
+            .unwrap();
+        let docs = vec![
+            json!({"body": "foo", "ts": 1_700_000_000}),
+            json!({"body": "bar", "ts": 1_700_000_100}),
+        ];
+        test_sandbox.add_documents(docs).await.unwrap();
+
+        let mut metastore = test_sandbox.metastore();
+        let index_uid = test_sandbox.index_uid();
+        let splits = metastore
+            .list_splits(ListSplitsRequest::try_from_index_uid(index_uid.clone()).unwrap())
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap();
+        assert_eq!(splits.len(), 1);
+        let original_split_metadata = splits[0].split_metadata.clone();
+        let split_id = original_split_metadata.split_id.clone();
+
+        // The metastore loses track of the split while its file remains in storage.
+        let mark_splits_for_deletion_request =
+            MarkSplitsForDeletionRequest::new(index_uid.clone(), vec![split_id.clone()]);
+        metastore
+            .mark_splits_for_deletion(mark_splits_for_deletion_request)
+            .await
+            .unwrap();
+
+        let mut index_service =
+            IndexService::new(metastore.clone(), test_sandbox.storage_resolver());
+        let error = index_service
+            .publish_split_from_storage(index_id, &split_id, false)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::OperationNotAllowed(_)));
+
+        let split_metadata = index_service
+            .publish_split_from_storage(index_id, &split_id, true)
+            .await
+            .unwrap();
+        assert_eq!(split_metadata.split_id, split_id);
+        assert_eq!(split_metadata.num_docs, 2);
+        assert_eq!(
+            split_metadata.time_range,
+            Some(1_700_000_000..=1_700_000_100)
+        );
+        assert_eq!(
+            split_metadata.footer_offsets,
+            original_split_metadata.footer_offsets
+        );
+
+        let query =
+            ListSplitsQuery::for_index(index_uid.clone()).with_split_state(SplitState::Published);
+        let published_splits = metastore
+            .list_splits(ListSplitsRequest::try_from_list_splits_query(query).unwrap())
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap();
+        assert_eq!(published_splits.len(), 1);
+        assert_eq!(published_splits[0].split_id(), split_id);
+        assert_eq!(published_splits[0].split_metadata.num_docs, 2);
+
+        let error = index_service
+            .publish_split_from_storage(index_id, &split_id, true)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, IndexServiceError::OperationNotAllowed(_)));
+
+        test_sandbox.assert_quit().await;
+    }
 }
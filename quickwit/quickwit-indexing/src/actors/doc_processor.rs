@@ -25,9 +25,12 @@ use anyhow::{bail, Context};
 use async_trait::async_trait;
 use bytes::Bytes;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
+use quickwit_common::rate_limited_warn;
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_config::{SourceInputFormat, TransformConfig};
-use quickwit_doc_mapper::{DocMapper, DocParsingError, JsonObject};
+use quickwit_doc_mapper::{
+    parse_json_obj, DocMapper, DocParsingError, JsonObject, JsonParsingOptions,
+};
 use quickwit_opentelemetry::otlp::{
     parse_otlp_spans_json, parse_otlp_spans_protobuf, JsonSpanIterator, OtlpTraceError,
 };
@@ -50,6 +53,8 @@ const PLAIN_TEXT: &str = "plain_text";
 pub(super) struct JsonDoc {
     json_obj: JsonObject,
     num_bytes: usize,
+    /// Number of duplicate keys resolved while parsing the document.
+    num_duplicate_keys: usize,
 }
 
 impl JsonDoc {
@@ -57,6 +62,7 @@ impl JsonDoc {
         Self {
             json_obj,
             num_bytes,
+            num_duplicate_keys: 0,
         }
     }
 
@@ -91,6 +97,12 @@ impl From<serde_json::Error> for DocProcessorError {
     }
 }
 
+impl From<DocParsingError> for DocProcessorError {
+    fn from(_error: DocParsingError) -> Self {
+        DocProcessorError::Parse
+    }
+}
+
 impl From<FromUtf8Error> for DocProcessorError {
     fn from(_error: FromUtf8Error) -> Self {
         DocProcessorError::Parse
@@ -124,11 +136,24 @@ fn try_into_json_docs(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    json_parsing_options: &JsonParsingOptions,
 ) -> JsonDocIterator {
     match input_format {
         SourceInputFormat::Json => {
-            let json_doc_result = serde_json::from_slice::<JsonObject>(&raw_doc)
-                .map(|json_obj| JsonDoc::new(json_obj, num_bytes));
+            let json_doc_result = parse_json_obj(&raw_doc, json_parsing_options)
+                .map(|(json_obj, num_duplicate_keys)| JsonDoc {
+                    json_obj,
+                    num_bytes,
+                    num_duplicate_keys,
+                })
+                .map_err(|error| {
+                    rate_limited_warn!(
+                        limit_per_min = 10,
+                        error=%error,
+                        "failed to parse JSON document"
+                    );
+                    DocProcessorError::from(error)
+                });
             JsonDocIterator::from(json_doc_result)
         }
         SourceInputFormat::OtlpTraceJson => {
@@ -156,14 +181,29 @@ fn parse_raw_doc(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    json_parsing_options: &JsonParsingOptions,
     vrl_program_opt: Option<&mut VrlProgram>,
 ) -> JsonDocIterator {
     let Some(vrl_program) = vrl_program_opt else {
-        return try_into_json_docs(input_format, raw_doc, num_bytes);
+        return try_into_json_docs(input_format, raw_doc, num_bytes, json_parsing_options);
     };
+    // Duplicate keys are resolved by the VRL value parser before we get a chance to see them,
+    // so only the depth and number of fields limits apply here.
     let json_doc_result = try_into_vrl_doc(input_format, raw_doc, num_bytes)
         .and_then(|vrl_doc| vrl_program.transform_doc(vrl_doc))
-        .and_then(JsonDoc::try_from_vrl_doc);
+        .and_then(JsonDoc::try_from_vrl_doc)
+        .and_then(|json_doc| {
+            quickwit_doc_mapper::check_json_obj_limits(&json_doc.json_obj, json_parsing_options)
+                .map_err(|error| {
+                    rate_limited_warn!(
+                        limit_per_min = 10,
+                        error=%error,
+                        "failed to parse JSON document"
+                    );
+                    DocProcessorError::from(error)
+                })?;
+            Ok(json_doc)
+        });
 
     JsonDocIterator::from(json_doc_result)
 }
@@ -173,9 +213,10 @@ fn parse_raw_doc(
     input_format: SourceInputFormat,
     raw_doc: Bytes,
     num_bytes: usize,
+    json_parsing_options: &JsonParsingOptions,
     _vrl_program_opt: Option<&mut VrlProgram>,
 ) -> JsonDocIterator {
-    try_into_json_docs(input_format, raw_doc, num_bytes)
+    try_into_json_docs(input_format, raw_doc, num_bytes, json_parsing_options)
 }
 
 enum JsonDocIterator {
@@ -231,6 +272,10 @@ pub struct DocProcessorCounters {
     pub num_schema_errors: AtomicU64,
     pub num_valid_docs: AtomicU64,
 
+    /// Number of duplicate keys resolved according to the duplicate keys policy
+    /// of the doc mapping.
+    pub num_duplicate_keys: AtomicU64,
+
     /// Number of bytes that went through the indexer
     /// during its entire lifetime.
     ///
@@ -247,6 +292,7 @@ impl DocProcessorCounters {
             num_transform_errors: Default::default(),
             num_schema_errors: Default::default(),
             num_valid_docs: Default::default(),
+            num_duplicate_keys: Default::default(),
             num_bytes_total: Default::default(),
         }
     }
@@ -316,6 +362,7 @@ pub struct DocProcessor {
     doc_mapper: Arc<dyn DocMapper>,
     indexer_mailbox: Mailbox<Indexer>,
     timestamp_field_opt: Option<Field>,
+    json_parsing_options: JsonParsingOptions,
     counters: Arc<DocProcessorCounters>,
     publish_lock: PublishLock,
     #[cfg(feature = "vrl")]
@@ -333,6 +380,7 @@ impl DocProcessor {
         input_format: SourceInputFormat,
     ) -> anyhow::Result<Self> {
         let timestamp_field_opt = extract_timestamp_field(&*doc_mapper)?;
+        let json_parsing_options = doc_mapper.json_parsing_options();
         if cfg!(not(feature = "vrl")) && transform_config_opt.is_some() {
             bail!("VRL is not enabled. please recompile with the `vrl` feature")
         }
@@ -340,6 +388,7 @@ impl DocProcessor {
             doc_mapper,
             indexer_mailbox,
             timestamp_field_opt,
+            json_parsing_options,
             counters: Arc::new(DocProcessorCounters::new(index_id, source_id)),
             publish_lock: PublishLock::default(),
             #[cfg(feature = "vrl")]
@@ -377,7 +426,14 @@ impl DocProcessor {
         #[cfg(not(feature = "vrl"))]
         let transform_opt: Option<&mut VrlProgram> = None;
 
-        for json_doc_result in parse_raw_doc(self.input_format, raw_doc, num_bytes, transform_opt) {
+        let json_doc_iter = parse_raw_doc(
+            self.input_format,
+            raw_doc,
+            num_bytes,
+            &self.json_parsing_options,
+            transform_opt,
+        );
+        for json_doc_result in json_doc_iter {
             let processed_doc_result =
                 json_doc_result.and_then(|json_doc| self.process_json_doc(json_doc));
 
@@ -396,6 +452,19 @@ impl DocProcessor {
     fn process_json_doc(&self, json_doc: JsonDoc) -> Result<ProcessedDoc, DocProcessorError> {
         let num_bytes = json_doc.num_bytes;
 
+        if json_doc.num_duplicate_keys > 0 {
+            rate_limited_warn!(
+                limit_per_min = 10,
+                index_id = self.counters.index_id,
+                source_id = self.counters.source_id,
+                num_duplicate_keys = json_doc.num_duplicate_keys,
+                "document contains duplicate keys"
+            );
+            self.counters
+                .num_duplicate_keys
+                .fetch_add(json_doc.num_duplicate_keys as u64, Ordering::Relaxed);
+        }
+
         let (partition, doc) = self
             .doc_mapper
             .doc_from_json_obj(json_doc.json_obj)
@@ -458,6 +527,7 @@ impl Actor for DocProcessor {
     ) -> anyhow::Result<()> {
         match exit_status {
             ActorExitStatus::DownstreamClosed
+            | ActorExitStatus::DownstreamTimeout
             | ActorExitStatus::Killed
             | ActorExitStatus::Failure(_)
             | ActorExitStatus::Panicked => return Ok(()),
@@ -694,6 +764,74 @@ mod tests {
         Ok(())
     }
 
+    const DOCMAPPER_WITH_JSON_PARSING_JSON: &str = r#"
+        {
+            "json_parsing": {
+                "max_depth": 8,
+                "max_num_fields": 16
+            },
+            "field_mappings": [
+                { "name": "body", "type": "text" }
+            ]
+        }"#;
+
+    #[tokio::test]
+    async fn test_doc_processor_json_parsing_limits() -> anyhow::Result<()> {
+        let doc_mapper: Arc<dyn DocMapper> = Arc::new(
+            serde_json::from_str::<DefaultDocMapper>(DOCMAPPER_WITH_JSON_PARSING_JSON).unwrap(),
+        );
+        let universe = Universe::with_accelerated_time();
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox();
+        let doc_processor = DocProcessor::try_new(
+            "my-index".to_string(),
+            "my-source".to_string(),
+            doc_mapper,
+            indexer_mailbox,
+            None,
+            SourceInputFormat::Json,
+        )
+        .unwrap();
+        let (doc_processor_mailbox, doc_processor_handle) =
+            universe.spawn_builder().spawn(doc_processor);
+
+        let deeply_nested_doc = format!(
+            r#"{{"body": "happy", "attributes": {}{}}}"#,
+            "[".repeat(10_000),
+            "]".repeat(10_000)
+        );
+        let wide_doc = format!(
+            r#"{{"body": "happy", {}}}"#,
+            (0..1_000)
+                .map(|i| format!(r#""field_{i}": {i}"#))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+        doc_processor_mailbox
+            .send_message(RawDocBatch::for_test(
+                &[
+                    r#"{"body": "happy", "attributes": {"tags": [["a"], ["b"]]}}"#, // ok
+                    deeply_nested_doc.as_str(),                                     // too deep
+                    wide_doc.as_str(),                        // too many fields
+                    r#"{"body": "happy", "body": "happy2"}"#, // ok, duplicate key
+                ],
+                0..4,
+            ))
+            .await?;
+        let counters = doc_processor_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_parse_errors.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.num_valid_docs.load(Ordering::Relaxed), 2);
+        assert_eq!(counters.num_duplicate_keys.load(Ordering::Relaxed), 1);
+
+        let output_messages: Vec<ProcessedDocBatch> = indexer_inbox.drain_for_test_typed();
+        assert_eq!(output_messages.len(), 1);
+        assert_eq!(output_messages[0].docs.len(), 2);
+        universe.assert_quit().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_doc_processor_forward_publish_lock() {
         let doc_mapper = Arc::new(default_doc_mapper_for_test());
@@ -65,6 +65,10 @@ use crate::models::{
 // Random partition ID used to gather partitions exceeding the maximum number of partitions.
 const OTHER_PARTITION_ID: u64 = 3264326757911759461u64;
 
+/// How long the indexer waits for the index serializer to accept a split batch. Past this
+/// timeout, the index serializer is considered wedged and the indexer exits.
+const INDEX_SERIALIZER_SEND_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
 #[derive(Debug)]
 struct CommitTimeout {
     workbench_id: Ulid,
@@ -292,6 +296,9 @@ impl IndexerState {
             .extend(batch.checkpoint_delta)
             .context("batch delta does not follow indexer checkpoint")?;
         let mut memory_usage_delta: u64 = 0;
+        // Adding a document can block while tantivy's indexing queue is full, so the whole batch
+        // is indexed in a protected zone.
+        let protect_guard = ctx.protect_zone();
         for doc in batch.docs {
             let ProcessedDoc {
                 doc,
@@ -314,15 +321,15 @@ impl IndexerState {
             if let Some(timestamp) = timestamp_opt {
                 record_timestamp(timestamp, &mut indexed_split.split_attrs.time_range);
             }
-            let _protect_guard = ctx.protect_zone();
             indexed_split
                 .index_writer
                 .add_document(doc)
                 .context("failed to add document")?;
             let mem_usage_after = indexed_split.index_writer.mem_usage() as u64;
             memory_usage_delta += mem_usage_after - mem_usage_before;
-            ctx.record_progress();
         }
+        drop(protect_guard);
+        ctx.record_progress();
         *memory_usage = ByteSize(memory_usage.as_u64() + memory_usage_delta);
         Ok(())
     }
@@ -425,6 +432,7 @@ impl Actor for Indexer {
     ) -> anyhow::Result<()> {
         match exit_status {
             ActorExitStatus::DownstreamClosed
+            | ActorExitStatus::DownstreamTimeout
             | ActorExitStatus::Killed
             | ActorExitStatus::Failure(_)
             | ActorExitStatus::Panicked => return Ok(()),
@@ -636,7 +644,7 @@ impl Indexer {
         // reprocessing the same faulty documents.
         if splits.is_empty() {
             if !checkpoint_delta.is_empty() {
-                ctx.send_message(
+                ctx.send_message_with_timeout(
                     &self.index_serializer_mailbox,
                     EmptySplit {
                         index_uid: self.indexer_state.pipeline_id.index_uid.clone(),
@@ -645,6 +653,7 @@ impl Indexer {
                         publish_token_opt,
                         batch_parent_span,
                     },
+                    INDEX_SERIALIZER_SEND_TIMEOUT,
                 )
                 .await?;
             }
@@ -653,7 +662,9 @@ impl Indexer {
         let num_splits = splits.len() as u64;
         let split_ids = splits.iter().map(|split| split.split_id()).join(",");
         info!(commit_trigger=?commit_trigger, split_ids=%split_ids, num_docs=self.counters.num_docs_in_workbench, "send-to-index-serializer");
-        ctx.send_message(
+        // A wedged index serializer makes the send time out, and the error makes the indexer
+        // exit and activate the kill switch of the pipeline instead of hanging.
+        ctx.send_message_with_timeout(
             &self.index_serializer_mailbox,
             IndexedSplitBatchBuilder {
                 splits,
@@ -663,6 +674,7 @@ impl Indexer {
                 commit_trigger,
                 batch_parent_span,
             },
+            INDEX_SERIALIZER_SEND_TIMEOUT,
         )
         .await?;
         self.counters.num_docs_in_workbench = 0;
@@ -674,14 +686,17 @@ impl Indexer {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use std::fmt::Write;
     use std::sync::Arc;
     use std::time::Duration;
 
     use quickwit_actors::Universe;
+    use quickwit_common::HumanDuration;
     use quickwit_doc_mapper::{default_doc_mapper_for_test, DefaultDocMapper};
     use quickwit_metastore::checkpoint::SourceCheckpointDelta;
-    use quickwit_proto::metastore::{EmptyResponse, LastDeleteOpstampResponse};
+    use quickwit_metastore::{ListSplitsRequestExt, MetastoreServiceStreamSplitsExt};
+    use quickwit_proto::metastore::{EmptyResponse, LastDeleteOpstampResponse, ListSplitsRequest};
     use quickwit_proto::types::{IndexUid, PipelineUid};
     use tantivy::{doc, DateTime};
 
@@ -853,6 +868,92 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_indexer_rotates_splits_on_target_num_docs() -> anyhow::Result<()> {
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::default(),
+        };
+        let doc_mapper = Arc::new(default_doc_mapper_for_test());
+        let last_delete_opstamp = 10;
+        let schema = doc_mapper.schema();
+        let body_field = schema.get_field("body").unwrap();
+        let timestamp_field = schema.get_field("timestamp").unwrap();
+        let indexing_directory = TempDirectory::for_test();
+        let mut indexing_settings = IndexingSettings::for_test();
+        indexing_settings.split_num_docs_target = 250;
+        let universe = Universe::with_accelerated_time();
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore
+            .expect_last_delete_opstamp()
+            .times(4)
+            .returning(move |_| Ok(LastDeleteOpstampResponse::new(last_delete_opstamp)));
+        metastore.expect_publish_splits().never();
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            MetastoreServiceClient::from(metastore),
+            indexing_directory,
+            indexing_settings,
+            None,
+            index_serializer_mailbox,
+        );
+        let (indexer_mailbox, indexer_handle) = universe.spawn_builder().spawn(indexer);
+
+        // 10 batches of 100 docs. The target number of docs is checked after each batch, so
+        // splits are rotated every 3 batches.
+        for batch_id in 0..10u64 {
+            let docs = (0..100)
+                .map(|_| ProcessedDoc {
+                    doc: doc!(
+                        body_field=>"this is a test document",
+                        timestamp_field=>DateTime::from_timestamp_secs(1_662_529_435)
+                    ),
+                    timestamp_opt: Some(DateTime::from_timestamp_secs(1_662_529_435)),
+                    partition: 1,
+                    num_bytes: 30,
+                })
+                .collect();
+            indexer_mailbox
+                .send_message(ProcessedDocBatch {
+                    docs,
+                    checkpoint_delta: SourceCheckpointDelta::from_range(
+                        batch_id * 100..(batch_id + 1) * 100,
+                    ),
+                    force_commit: false,
+                })
+                .await?;
+        }
+        let indexer_counters = indexer_handle.process_pending_and_observe().await.state;
+        assert_eq!(indexer_counters.num_splits_emitted, 3);
+        assert_eq!(indexer_counters.num_docs_in_workbench, 100);
+
+        let messages: Vec<IndexedSplitBatchBuilder> = index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(messages.len(), 3);
+        for batch in &messages {
+            assert_eq!(batch.commit_trigger, CommitTrigger::NumDocsLimit);
+            assert_eq!(batch.splits.len(), 1);
+            assert_eq!(batch.splits[0].split_attrs.num_docs, 300);
+        }
+        // The remaining docs are flushed into a fourth split upon exit.
+        universe.send_exit_with_success(&indexer_mailbox).await?;
+        let (exit_status, indexer_counters) = indexer_handle.join().await;
+        assert!(exit_status.is_success());
+        assert_eq!(indexer_counters.num_splits_emitted, 4);
+        assert_eq!(indexer_counters.num_docs_in_workbench, 0);
+
+        let messages: Vec<IndexedSplitBatchBuilder> = index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].commit_trigger, CommitTrigger::NoMoreDocs);
+        assert_eq!(messages[0].splits[0].split_attrs.num_docs, 100);
+        universe.assert_quit().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_indexer_triggers_commit_on_memory_limit() -> anyhow::Result<()> {
         let universe = Universe::new();
@@ -947,7 +1048,7 @@ mod tests {
         let timestamp_field = schema.get_field("timestamp").unwrap();
         let indexing_directory = TempDirectory::for_test();
         let mut indexing_settings = IndexingSettings::for_test();
-        indexing_settings.commit_timeout_secs = 1;
+        indexing_settings.commit_timeout = HumanDuration::from_secs(1);
         let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
         let mut metastore = MetastoreServiceClient::mock();
         metastore.expect_publish_splits().never();
@@ -1614,4 +1715,60 @@ mod tests {
         universe.assert_quit().await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_indexer_timestamp_partitioning() -> anyhow::Result<()> {
+        let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+              - name: timestamp
+                type: datetime
+                input_formats: [unix_timestamp]
+                fast: true
+            timestamp_field: timestamp
+            timestamp_partition_period: 1h
+        "#;
+        let test_sandbox =
+            crate::TestSandbox::create("test-index", doc_mapping_yaml, "", &["body"]).await?;
+        // 2023-11-14T21:00:00Z
+        let start_timestamp = 1_699_995_600;
+        let docs = [10, 20, 3_605, 3_700, 7_201, 1_000]
+            .into_iter()
+            .map(|offset| {
+                serde_json::json!({
+                    "body": "info",
+                    "timestamp": start_timestamp + offset,
+                })
+            })
+            .collect::<Vec<_>>();
+        let indexing_statistics = test_sandbox.add_documents(docs).await?;
+        assert_eq!(indexing_statistics.num_uploaded_splits, 3);
+        assert_eq!(indexing_statistics.num_splits_in_flight, 0);
+
+        let mut splits = test_sandbox
+            .metastore()
+            .list_splits(ListSplitsRequest::try_from_index_uid(test_sandbox.index_uid()).unwrap())
+            .await?
+            .collect_splits_metadata()
+            .await?;
+        splits.sort_by_key(|split| *split.time_range.as_ref().unwrap().start());
+
+        let time_ranges: Vec<_> = splits
+            .iter()
+            .map(|split| split.time_range.clone().unwrap())
+            .collect();
+        assert_eq!(
+            time_ranges,
+            [
+                start_timestamp + 10..=start_timestamp + 1_000,
+                start_timestamp + 3_605..=start_timestamp + 3_700,
+                start_timestamp + 7_201..=start_timestamp + 7_201,
+            ]
+        );
+        let partition_ids: HashSet<u64> = splits.iter().map(|split| split.partition_id).collect();
+        assert_eq!(partition_ids.len(), 3);
+        test_sandbox.assert_quit().await;
+        Ok(())
+    }
 }
@@ -40,7 +40,8 @@ use quickwit_proto::metastore::{
 };
 use quickwit_proto::types::ShardId;
 use quickwit_storage::{Storage, StorageResolver};
-use tokio::sync::Semaphore;
+use tokio::sync::{watch, Semaphore};
+use tokio::task::JoinHandle;
 use tracing::{debug, error, info, instrument};
 
 use super::MergePlanner;
@@ -116,6 +117,11 @@ pub struct Spawn {
     retry_count: usize,
 }
 
+/// Asks the pipeline to stop reading from its source and to exit successfully once the documents
+/// read so far have been indexed and published.
+#[derive(Clone, Copy, Debug)]
+pub struct DrainPipeline;
+
 pub struct IndexingPipeline {
     params: IndexingPipelineParams,
     previous_generations_statistics: IndexingStatistics,
@@ -127,6 +133,9 @@ pub struct IndexingPipeline {
     // requiring a respawn of the pipeline.
     // We keep the list of shards here however, to reassign them after a respawn.
     shard_ids: BTreeSet<ShardId>,
+    // Set when a `DrainPipeline` message is received, until the doc processor is asked to exit
+    // after the source has stopped.
+    drain_requested: bool,
 }
 
 #[async_trait]
@@ -168,6 +177,7 @@ impl IndexingPipeline {
             kill_switch: KillSwitch::default(),
             statistics: IndexingStatistics::default(),
             shard_ids: Default::default(),
+            drain_requested: false,
         }
     }
 
@@ -250,6 +260,7 @@ impl IndexingPipeline {
         let Some(handles) = &self.handles_opt else {
             return;
         };
+        handles.source_handle.refresh_observe();
         handles.doc_processor.refresh_observe();
         handles.indexer.refresh_observe();
         handles.uploader.refresh_observe();
@@ -268,6 +279,13 @@ impl IndexingPipeline {
         let pipeline_metrics_opt = handles.indexer.last_observation().pipeline_metrics_opt;
         self.statistics.pipeline_metrics_opt = pipeline_metrics_opt;
         self.statistics.shard_ids = self.shard_ids.clone();
+        // Only the file source stops at a maximum number of bytes.
+        self.statistics.reached_max_num_bytes |= handles
+            .source_handle
+            .last_observation()
+            .get("reached_max_num_bytes")
+            .and_then(serde_json::Value::as_bool)
+            .unwrap_or(false);
         ctx.observe(self);
     }
 
@@ -279,6 +297,14 @@ impl IndexingPipeline {
         let Some(handles) = self.handles_opt.as_mut() else {
             return Ok(());
         };
+        if self.drain_requested && handles.source_handle.state().is_exit() {
+            // The source no longer emits batches, so the rest of the pipeline can be flushed.
+            // Each actor asks its downstream actor to exit upon success.
+            let _ = ctx
+                .send_exit_with_success(handles.doc_processor.mailbox())
+                .await;
+            self.drain_requested = false;
+        }
 
         // While we check if the actor has terminated or not, we do not check for progress
         // at every single loop. Instead, we wait for the `HEARTBEAT` duration to have elapsed,
@@ -378,7 +404,13 @@ impl IndexingPipeline {
 
         // Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("Packager", tag_fields, uploader_mailbox);
+        let geo_point_fields = self
+            .params
+            .doc_mapper
+            .geo_point_field_names()
+            .into_iter()
+            .collect();
+        let packager = Packager::new("Packager", tag_fields, geo_point_fields, uploader_mailbox);
         let (packager_mailbox, packager_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -516,6 +548,25 @@ impl Handler<SuperviseLoop> for IndexingPipeline {
     }
 }
 
+#[async_trait]
+impl Handler<DrainPipeline> for IndexingPipeline {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _message: DrainPipeline,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        let Some(handles) = &self.handles_opt else {
+            // The pipeline has not been spawned yet, there is nothing to drain.
+            return Err(ActorExitStatus::Success);
+        };
+        ctx.send_exit_with_success(&handles.source_mailbox).await?;
+        self.drain_requested = true;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Handler<Spawn> for IndexingPipeline {
     type Reply = ();
@@ -605,12 +656,49 @@ pub struct IndexingPipelineParams {
     pub event_broker: EventBroker,
 }
 
+/// Publishes the statistics of an indexing pipeline to a watch channel until the pipeline exits.
+///
+/// The pipeline is observed every `refresh_interval`. Publishing never waits on the receivers, so
+/// a slow consumer cannot hold back indexing: it only ever sees the latest statistics. The last
+/// statistics published are the final ones, which the returned task also yields along with the
+/// pipeline exit status.
+pub fn watch_indexing_progress(
+    pipeline_handle: ActorHandle<IndexingPipeline>,
+    refresh_interval: Duration,
+) -> (
+    watch::Receiver<IndexingStatistics>,
+    JoinHandle<(ActorExitStatus, IndexingStatistics)>,
+) {
+    let initial_statistics = pipeline_handle.last_observation().clone();
+    let (progress_tx, progress_rx) = watch::channel(initial_statistics);
+
+    let join_handle = tokio::spawn(async move {
+        let mut refresh_interval = tokio::time::interval(refresh_interval);
+        loop {
+            refresh_interval.tick().await;
+            pipeline_handle.refresh_observe();
+
+            let statistics = pipeline_handle.last_observation().clone();
+            progress_tx.send_replace(statistics);
+
+            if pipeline_handle.is_terminated() {
+                break;
+            }
+        }
+        let (exit_status, statistics) = pipeline_handle.join().await;
+        progress_tx.send_replace(statistics.clone());
+        (exit_status, statistics)
+    });
+    (progress_rx, join_handle)
+}
+
 #[cfg(test)]
 mod tests {
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
     use std::sync::Arc;
 
+    use itertools::Itertools;
     use quickwit_actors::{Command, Universe};
     use quickwit_common::ServiceStream;
     use quickwit_config::{IndexingSettings, SourceInputFormat, SourceParams, VoidSourceParams};
@@ -836,6 +924,187 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_watch_indexing_progress() {
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore
+            .expect_index_metadata()
+            .withf(|index_metadata_request| {
+                index_metadata_request.index_id.as_ref().unwrap() == "test-index"
+            })
+            .returning(|_| {
+                let index_metadata =
+                    IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+                Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata).unwrap())
+            });
+        metastore
+            .expect_last_delete_opstamp()
+            .withf(|last_delete_opstamp| {
+                last_delete_opstamp.index_uid == "test-index:11111111111111111111111111"
+            })
+            .returning(move |_| Ok(LastDeleteOpstampResponse::new(10)));
+        metastore
+            .expect_stage_splits()
+            .withf(|stage_splits_request| {
+                stage_splits_request.index_uid == "test-index:11111111111111111111111111"
+            })
+            .returning(|_| Ok(EmptyResponse {}));
+        metastore
+            .expect_publish_splits()
+            .withf(|publish_splits_request| -> bool {
+                let checkpoint_delta: IndexCheckpointDelta = publish_splits_request
+                    .deserialize_index_checkpoint()
+                    .unwrap()
+                    .unwrap();
+                publish_splits_request.index_uid == "test-index:11111111111111111111111111"
+                    && publish_splits_request.staged_split_ids.len() == 1
+                    && publish_splits_request.replaced_split_ids.is_empty()
+                    && checkpoint_delta.source_id == "test-source"
+                    && format!("{:?}", checkpoint_delta.source_delta)
+                        .ends_with(":(00000000000000000000..00000000000000001030])")
+            })
+            .returning(|_| Ok(EmptyResponse {}));
+        let universe = Universe::new();
+        let node_id = "test-node";
+        let pipeline_id = IndexingPipelineId {
+            index_uid: "test-index:11111111111111111111111111".to_string().into(),
+            source_id: "test-source".to_string(),
+            node_id: node_id.to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        let source_config = SourceConfig {
+            source_id: "test-source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::file(PathBuf::from("data/test_corpus.json")),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        let storage = Arc::new(RamStorage::default());
+        let split_store = IndexingSplitStore::create_without_local_store_for_test(storage.clone());
+        let (merge_planner_mailbox, _) = universe.create_test_mailbox();
+        let pipeline_params = IndexingPipelineParams {
+            pipeline_id,
+            doc_mapper: Arc::new(default_doc_mapper_for_test()),
+            source_config,
+            source_storage_resolver: StorageResolver::for_test(),
+            indexing_directory: TempDirectory::for_test(),
+            indexing_settings: IndexingSettings::for_test(),
+            ingester_pool: IngesterPool::default(),
+            metastore: MetastoreServiceClient::from(metastore),
+            queues_dir_path: PathBuf::from("./queues"),
+            storage,
+            split_store,
+            merge_policy: default_merge_policy(),
+            max_concurrent_split_uploads_index: 4,
+            max_concurrent_split_uploads_merge: 5,
+            cooperative_indexing_permits: None,
+            merge_planner_mailbox,
+            event_broker: Default::default(),
+        };
+        let pipeline = IndexingPipeline::new(pipeline_params);
+        let (_pipeline_mailbox, pipeline_handle) = universe.spawn_builder().spawn(pipeline);
+        let (mut progress_rx, join_handle) =
+            watch_indexing_progress(pipeline_handle, Duration::from_millis(10));
+
+        let mut snapshots = vec![progress_rx.borrow_and_update().clone()];
+        while progress_rx.changed().await.is_ok() {
+            snapshots.push(progress_rx.borrow_and_update().clone());
+        }
+        let (pipeline_exit_status, pipeline_statistics) = join_handle.await.unwrap();
+        assert!(pipeline_exit_status.is_success());
+        assert_eq!(pipeline_statistics.num_published_splits, 1);
+
+        let distinct_snapshots: Vec<(u64, u64, u64)> = snapshots
+            .iter()
+            .map(|statistics| {
+                (
+                    statistics.num_docs,
+                    statistics.total_bytes_processed,
+                    statistics.num_published_splits,
+                )
+            })
+            .dedup()
+            .collect();
+        assert!(distinct_snapshots.len() >= 2);
+        assert_eq!(distinct_snapshots[0], (0, 0, 0));
+
+        let last_snapshot = snapshots.last().unwrap();
+        assert_eq!(last_snapshot.num_docs, pipeline_statistics.num_docs);
+        assert_eq!(last_snapshot.num_published_splits, 1);
+        universe.assert_quit().await;
+    }
+
+
+    #[tokio::test]
+    async fn test_indexing_pipeline_drain() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_index_metadata()
+            .withf(|index_metadata_request| {
+                index_metadata_request.index_id.as_ref().unwrap() == "test-index"
+            })
+            .returning(|_| {
+                let index_metadata =
+                    IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+                Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata).unwrap())
+            });
+        mock_metastore
+            .expect_last_delete_opstamp()
+            .returning(move |_| Ok(LastDeleteOpstampResponse::new(10)));
+        let universe = Universe::with_accelerated_time();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new_with_random_ulid("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        // The void source never exits on its own.
+        let source_config = SourceConfig {
+            source_id: "test-source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::Void(VoidSourceParams),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        let storage = Arc::new(RamStorage::default());
+        let split_store = IndexingSplitStore::create_without_local_store_for_test(storage.clone());
+        let (merge_planner_mailbox, _) = universe.create_test_mailbox();
+        let pipeline_params = IndexingPipelineParams {
+            pipeline_id,
+            doc_mapper: Arc::new(default_doc_mapper_for_test()),
+            source_config,
+            source_storage_resolver: StorageResolver::for_test(),
+            indexing_directory: TempDirectory::for_test(),
+            indexing_settings: IndexingSettings::for_test(),
+            ingester_pool: IngesterPool::default(),
+            metastore: MetastoreServiceClient::from(mock_metastore),
+            queues_dir_path: PathBuf::from("./queues"),
+            storage,
+            split_store,
+            merge_policy: default_merge_policy(),
+            max_concurrent_split_uploads_index: 4,
+            max_concurrent_split_uploads_merge: 5,
+            cooperative_indexing_permits: None,
+            merge_planner_mailbox,
+            event_broker: Default::default(),
+        };
+        let pipeline = IndexingPipeline::new(pipeline_params);
+        let (pipeline_mailbox, pipeline_handle) = universe.spawn_builder().spawn(pipeline);
+        let obs = pipeline_handle.process_pending_and_observe().await;
+        assert_eq!(obs.generation, 1);
+
+        pipeline_mailbox.send_message(DrainPipeline).await.unwrap();
+        let (pipeline_exit_status, pipeline_statistics) = pipeline_handle.join().await;
+        assert!(pipeline_exit_status.is_success());
+        assert_eq!(pipeline_statistics.generation, 1);
+        assert_eq!(pipeline_statistics.num_spawn_attempts, 1);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_merge_pipeline_does_not_stop_on_indexing_pipeline_failure() {
         let mut mock_metastore = MetastoreServiceClient::mock();
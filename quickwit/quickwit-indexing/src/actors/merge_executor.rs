@@ -565,6 +565,7 @@ mod tests {
     use quickwit_proto::metastore::{
         DeleteQuery, ListSplitsRequest, PublishSplitsRequest, StageSplitsRequest,
     };
+    use quickwit_proto::types::IndexUid;
     use serde_json::Value as JsonValue;
     use tantivy::{Document, Inventory, ReloadPolicy, TantivyDocument};
 
@@ -664,6 +665,81 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_merge_split_attrs() {
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        let splits = vec![
+            SplitMetadata {
+                split_id: "split-1".to_string(),
+                index_uid: index_uid.clone(),
+                num_docs: 10,
+                uncompressed_docs_size_in_bytes: 1_000,
+                time_range: Some(100..=200),
+                delete_opstamp: 5,
+                num_merge_ops: 0,
+                ..Default::default()
+            },
+            SplitMetadata {
+                split_id: "split-2".to_string(),
+                index_uid: index_uid.clone(),
+                num_docs: 20,
+                uncompressed_docs_size_in_bytes: 2_000,
+                time_range: Some(50..=150),
+                delete_opstamp: 3,
+                num_merge_ops: 2,
+                ..Default::default()
+            },
+            SplitMetadata {
+                split_id: "split-3".to_string(),
+                index_uid: index_uid.clone(),
+                num_docs: 30,
+                uncompressed_docs_size_in_bytes: 3_000,
+                time_range: None,
+                delete_opstamp: 7,
+                num_merge_ops: 1,
+                ..Default::default()
+            },
+        ];
+        let split_attrs = merge_split_attrs("merged-split".to_string(), &pipeline_id, &splits);
+        assert_eq!(split_attrs.split_id, "merged-split");
+        assert_eq!(
+            split_attrs.replaced_split_ids,
+            vec![
+                "split-1".to_string(),
+                "split-2".to_string(),
+                "split-3".to_string()
+            ]
+        );
+        assert_eq!(split_attrs.num_docs, 60);
+        assert_eq!(split_attrs.uncompressed_docs_size_in_bytes, 6_000);
+        assert_eq!(
+            split_attrs.time_range,
+            Some(DateTime::from_timestamp_secs(50)..=DateTime::from_timestamp_secs(200))
+        );
+        assert_eq!(split_attrs.delete_opstamp, 3);
+        assert_eq!(split_attrs.num_merge_ops, 3);
+    }
+
+    #[test]
+    fn test_merge_split_attrs_without_time_range() {
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new_with_random_ulid("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_uid: PipelineUid::from_u128(0u128),
+        };
+        let splits = vec![SplitMetadata::for_test("split-1".to_string())];
+        let split_attrs = merge_split_attrs("merged-split".to_string(), &pipeline_id, &splits);
+        assert!(split_attrs.time_range.is_none());
+        assert_eq!(split_attrs.num_merge_ops, 1);
+    }
+
     #[test]
     fn test_combine_partition_ids_singleton_unchanged() {
         assert_eq!(combine_partition_ids_aux([17]), 17);
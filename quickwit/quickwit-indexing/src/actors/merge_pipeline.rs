@@ -264,7 +264,18 @@ impl MergePipeline {
 
         // Merge Packager
         let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let merge_packager = Packager::new("MergePackager", tag_fields, merge_uploader_mailbox);
+        let geo_point_fields = self
+            .params
+            .doc_mapper
+            .geo_point_field_names()
+            .into_iter()
+            .collect();
+        let merge_packager = Packager::new(
+            "MergePackager",
+            tag_fields,
+            geo_point_fields,
+            merge_uploader_mailbox,
+        );
         let (merge_packager_mailbox, merge_packager_handler) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -24,6 +24,7 @@ use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, Qu
 use quickwit_common::io::IoControls;
 use quickwit_common::temp_dir::{self, TempDirectory};
 use quickwit_metastore::SplitMetadata;
+use quickwit_storage::{with_io_class, IoClass};
 use tantivy::{Directory, TrackedObject};
 use tracing::{debug, info, instrument};
 
@@ -117,9 +118,12 @@ impl MergeSplitDownloader {
                 .set_progress(ctx.progress().clone())
                 .set_kill_switch(ctx.kill_switch().clone());
             let _protect_guard = ctx.protect_zone();
-            let tantivy_dir = self
-                .split_store
-                .fetch_and_open_split(split.split_id(), download_directory, &io_controls)
+            let fetch_and_open_split_future = self.split_store.fetch_and_open_split(
+                split.split_id(),
+                download_directory,
+                &io_controls,
+            );
+            let tantivy_dir = with_io_class(IoClass::Background, fetch_and_open_split_future)
                 .await
                 .map_err(|error| {
                     let split_id = split.split_id();
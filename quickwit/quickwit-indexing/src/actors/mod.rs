@@ -36,7 +36,9 @@ mod vrl_processing;
 pub use doc_processor::{DocProcessor, DocProcessorCounters};
 pub use index_serializer::IndexSerializer;
 pub use indexer::{Indexer, IndexerCounters};
-pub use indexing_pipeline::{IndexingPipeline, IndexingPipelineParams};
+pub use indexing_pipeline::{
+    watch_indexing_progress, DrainPipeline, IndexingPipeline, IndexingPipelineParams,
+};
 pub use indexing_service::{
     IndexingService, IndexingServiceCounters, MergePipelineId, INDEXING_DIR_NAME,
 };
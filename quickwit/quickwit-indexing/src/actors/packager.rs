@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -27,6 +27,7 @@ use async_trait::async_trait;
 use fail::fail_point;
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
+use quickwit_common::geo::{GeoBoundingBox, GeoPoint};
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_common::temp_dir::TempDirectory;
 use quickwit_directories::write_hotcache;
@@ -36,7 +37,7 @@ use quickwit_proto::search::{
     serialize_split_fields, ListFieldType, ListFields, ListFieldsEntryResponse,
 };
 use tantivy::schema::{FieldType, Type};
-use tantivy::{FieldMetadata, InvertedIndexReader, ReloadPolicy, SegmentMeta};
+use tantivy::{FieldMetadata, InvertedIndexReader, ReloadPolicy, SegmentMeta, SegmentReader};
 use tokio::runtime::Handle;
 use tracing::{debug, info, instrument, warn};
 
@@ -58,6 +59,7 @@ use crate::models::{
 /// This includes the following steps:
 /// - commit: this step is CPU heavy
 /// - identifying the list of tags for the splits, and labelling it accordingly
+/// - computing the bounding box of the points of each `geo_point` field
 /// - creating a bundle file
 /// - computing the hotcache
 /// - appending it to the split file.
@@ -69,18 +71,22 @@ pub struct Packager {
     uploader_mailbox: Mailbox<Uploader>,
     /// List of tag fields ([`Vec<NamedField>`]) defined in the index config.
     tag_fields: Vec<NamedField>,
+    /// Names of the `geo_point` fields defined in the index config.
+    geo_point_fields: Vec<String>,
 }
 
 impl Packager {
     pub fn new(
         actor_name: &'static str,
         tag_fields: Vec<NamedField>,
+        geo_point_fields: Vec<String>,
         uploader_mailbox: Mailbox<Uploader>,
     ) -> Packager {
         Packager {
             actor_name,
             uploader_mailbox,
             tag_fields,
+            geo_point_fields,
         }
     }
 
@@ -91,8 +97,13 @@ impl Packager {
     ) -> anyhow::Result<PackagedSplit> {
         let segment_metas = split.index.searchable_segment_metas()?;
         assert_eq!(segment_metas.len(), 1);
-        let packaged_split =
-            create_packaged_split(&segment_metas[..], split, &self.tag_fields, ctx)?;
+        let packaged_split = create_packaged_split(
+            &segment_metas[..],
+            split,
+            &self.tag_fields,
+            &self.geo_point_fields,
+            ctx,
+        )?;
         Ok(packaged_split)
     }
 }
@@ -272,10 +283,40 @@ fn try_extract_terms(
     Ok(terms)
 }
 
+/// Computes the bounding box of the points of a `geo_point` field.
+///
+/// Returns None if the field does not hold any point.
+fn compute_geo_bounding_box(
+    geo_point_field: &str,
+    segment_readers: &[SegmentReader],
+) -> anyhow::Result<Option<GeoBoundingBox>> {
+    let mut bounding_box_opt: Option<GeoBoundingBox> = None;
+    for segment_reader in segment_readers {
+        let Some(column) = segment_reader
+            .fast_fields()
+            .column_opt::<u64>(geo_point_field)?
+        else {
+            continue;
+        };
+        for doc_id in segment_reader.doc_ids_alive() {
+            for geo_point_u64 in column.values_for_doc(doc_id) {
+                let geo_point = GeoPoint::from_u64(geo_point_u64);
+                if let Some(bounding_box) = bounding_box_opt.as_mut() {
+                    bounding_box.extend(&geo_point);
+                } else {
+                    bounding_box_opt = Some(GeoBoundingBox::from_point(geo_point));
+                }
+            }
+        }
+    }
+    Ok(bounding_box_opt)
+}
+
 fn create_packaged_split(
     segment_metas: &[SegmentMeta],
     split: IndexedSplit,
     tag_fields: &[NamedField],
+    geo_point_fields: &[String],
     ctx: &ActorContext<Packager>,
 ) -> anyhow::Result<PackagedSplit> {
     info!(split_id = split.split_id(), "create-packaged-split");
@@ -313,6 +354,23 @@ fn create_packaged_split(
 
     ctx.record_progress();
 
+    debug!(
+        split_id = split.split_id(),
+        geo_point_fields =? geo_point_fields,
+        "compute-geo-bounding-boxes"
+    );
+    let mut geo_bounding_boxes = BTreeMap::default();
+    let searcher = index_reader.searcher();
+    for geo_point_field in geo_point_fields {
+        if let Some(bounding_box) =
+            compute_geo_bounding_box(geo_point_field, searcher.segment_readers())?
+        {
+            geo_bounding_boxes.insert(geo_point_field.clone(), bounding_box);
+        }
+    }
+
+    ctx.record_progress();
+
     debug!(split_id = split.split_id(), "build-hotcache");
     let mut hotcache_bytes = Vec::new();
     build_hotcache(split.split_scratch_directory.path(), &mut hotcache_bytes)?;
@@ -325,6 +383,7 @@ fn create_packaged_split(
         split_attrs: split.split_attrs,
         split_scratch_directory: split.split_scratch_directory,
         tags,
+        geo_bounding_boxes,
         split_files,
         hotcache_bytes,
     };
@@ -462,6 +521,7 @@ mod tests {
             schema_builder.add_f64_field("tag_f64", NumericOptions::default().set_indexed());
         let tag_bool =
             schema_builder.add_bool_field("tag_bool", NumericOptions::default().set_indexed());
+        let location_field = schema_builder.add_u64_field("location", FAST);
         let schema = schema_builder.build();
         let index_builder = IndexBuilder::new()
             .settings(IndexSettings::default())
@@ -492,6 +552,7 @@ mod tests {
                     tag_i64 => -42i64,
                     tag_f64 => -42.02f64,
                     tag_bool => true,
+                    location_field => GeoPoint::new(40.0 + num as f64, -(num as f64))?.to_u64(),
                 );
                 index_writer.add_document(doc)?;
                 num_docs += 1;
@@ -565,7 +626,8 @@ mod tests {
                 "tag_str", "tag_many", "tag_u64", "tag_i64", "tag_f64", "tag_bool",
             ],
         );
-        let packager = Packager::new("TestPackager", tag_fields, mailbox);
+        let geo_point_fields = vec!["location".to_string()];
+        let packager = Packager::new("TestPackager", tag_fields, geo_point_fields, mailbox);
         let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
         packager_mailbox
             .send_message(IndexedSplitBatch {
@@ -609,6 +671,12 @@ mod tests {
                     ..=DateTime::from_timestamp_secs(1628203640)
             )
         );
+        assert_eq!(split.geo_bounding_boxes.len(), 1);
+        let bounding_box = split.geo_bounding_boxes["location"];
+        assert!((bounding_box.top() - 49.0).abs() < 1e-6);
+        assert!((bounding_box.bottom() - 41.0).abs() < 1e-6);
+        assert!((bounding_box.left() + 9.0).abs() < 1e-6);
+        assert!((bounding_box.right() + 1.0).abs() < 1e-6);
         universe.assert_quit().await;
         Ok(())
     }
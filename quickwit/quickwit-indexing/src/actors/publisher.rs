@@ -20,12 +20,13 @@
 use anyhow::Context;
 use async_trait::async_trait;
 use fail::fail_point;
-use quickwit_actors::{Actor, ActorContext, Handler, Mailbox, QueueCapacity};
+use quickwit_actors::{Actor, ActorContext, Handler, Mailbox, QueueCapacity, HEARTBEAT};
 use quickwit_proto::metastore::{MetastoreService, MetastoreServiceClient, PublishSplitsRequest};
 use serde::Serialize;
 use tracing::{info, instrument, warn};
 
 use crate::actors::MergePlanner;
+use crate::metrics::INDEXER_METRICS;
 use crate::models::{NewSplits, SplitsUpdate};
 use crate::source::{SourceActor, SuggestTruncate};
 
@@ -136,9 +137,22 @@ impl Handler<SplitsUpdate> for Publisher {
                 index_checkpoint_delta_json_opt,
                 publish_token_opt: publish_token_opt.clone(),
             };
-            ctx.protect_future(self.metastore.publish_splits(publish_splits_request))
-                .await
-                .context("failed to publish splits")?;
+            let publish_result = ctx
+                .protect_future_for(
+                    self.metastore.publish_splits(publish_splits_request),
+                    *HEARTBEAT * 10,
+                )
+                .await;
+            let publish_status = if publish_result.is_ok() {
+                "success"
+            } else {
+                "error"
+            };
+            INDEXER_METRICS
+                .publish_operations_total
+                .with_label_values([index_uid.index_id(), publish_status])
+                .inc();
+            publish_result.context("failed to publish splits")?;
         } else {
             // TODO: Remove the junk right away?
             info!(
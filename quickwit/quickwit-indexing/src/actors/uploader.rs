@@ -22,6 +22,7 @@ use std::iter::FromIterator;
 use std::mem;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::Instant;
 
 use anyhow::{bail, Context};
 use async_trait::async_trait;
@@ -35,7 +36,7 @@ use quickwit_metastore::{SplitMetadata, StageSplitsRequestExt};
 use quickwit_proto::metastore::{MetastoreService, MetastoreServiceClient, StageSplitsRequest};
 use quickwit_proto::search::{ReportSplit, ReportSplitsRequest};
 use quickwit_proto::types::{IndexUid, PublishToken};
-use quickwit_storage::SplitPayloadBuilder;
+use quickwit_storage::{PutPayload, SplitPayloadBuilder};
 use serde::Serialize;
 use tantivy::TrackedObject;
 use tokio::sync::oneshot::Sender;
@@ -233,6 +234,7 @@ impl Uploader {
 pub struct UploaderCounters {
     pub num_staged_splits: Arc<AtomicU64>,
     pub num_uploaded_splits: Arc<AtomicU64>,
+    pub num_uploaded_bytes: Arc<AtomicU64>,
 }
 
 #[async_trait]
@@ -325,6 +327,7 @@ impl Handler<PackagedSplitBatch> for Uploader {
                         &merge_policy,
                         &packaged_split.split_attrs,
                         packaged_split.tags.clone(),
+                        packaged_split.geo_bounding_boxes.clone(),
                         split_streamer.footer_range.start..split_streamer.footer_range.end,
                     );
 
@@ -348,7 +351,11 @@ impl Handler<PackagedSplitBatch> for Uploader {
 
                 event_broker.publish(ReportSplitsRequest { report_splits });
 
+                let split_upload_duration_histogram = INDEXER_METRICS
+                    .split_upload_duration_seconds
+                    .with_label_values([index_uid.index_id()]);
                 for (packaged_split, metadata) in batch.splits.into_iter().zip(split_metadata_list) {
+                    let upload_start = Instant::now();
                     let upload_result = upload_split(
                         &packaged_split,
                         &metadata,
@@ -356,6 +363,7 @@ impl Handler<PackagedSplitBatch> for Uploader {
                         counters.clone(),
                     )
                     .await;
+                    split_upload_duration_histogram.observe(upload_start.elapsed().as_secs_f64());
 
                     if let Err(cause) = upload_result {
                         warn!(cause=?cause, split_id=packaged_split.split_id(), "Failed to upload split. Killing!");
@@ -469,6 +477,7 @@ async fn upload_split(
         &packaged_split.serialized_split_fields,
         &packaged_split.hotcache_bytes,
     )?;
+    let split_num_bytes = split_streamer.len();
 
     split_store
         .store_split(
@@ -478,6 +487,9 @@ async fn upload_split(
         )
         .await?;
     counters.num_uploaded_splits.fetch_add(1, Ordering::SeqCst);
+    counters
+        .num_uploaded_bytes
+        .fetch_add(split_num_bytes, Ordering::SeqCst);
     Ok(())
 }
 
@@ -566,6 +578,7 @@ mod tests {
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
                     tags: Default::default(),
+                    geo_bounding_boxes: Default::default(),
                     hotcache_bytes: Vec::new(),
                     split_files: Vec::new(),
                 }],
@@ -678,6 +691,7 @@ mod tests {
             serialized_split_fields: Vec::new(),
             split_scratch_directory: split_scratch_directory_1,
             tags: Default::default(),
+            geo_bounding_boxes: Default::default(),
             split_files: Vec::new(),
             hotcache_bytes: Vec::new(),
         };
@@ -702,6 +716,7 @@ mod tests {
             serialized_split_fields: Vec::new(),
             split_scratch_directory: split_scratch_directory_2,
             tags: Default::default(),
+            geo_bounding_boxes: Default::default(),
             split_files: Vec::new(),
             hotcache_bytes: Vec::new(),
         };
@@ -820,6 +835,7 @@ mod tests {
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
                     tags: Default::default(),
+                    geo_bounding_boxes: Default::default(),
                     hotcache_bytes: Vec::new(),
                     split_files: Vec::new(),
                 }],
@@ -999,6 +1015,7 @@ mod tests {
                     serialized_split_fields: Vec::new(),
                     split_scratch_directory,
                     tags: Default::default(),
+                    geo_bounding_boxes: Default::default(),
                     hotcache_bytes: Vec::new(),
                     split_files: Vec::new(),
                 }],
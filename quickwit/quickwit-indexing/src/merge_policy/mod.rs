@@ -163,7 +163,7 @@ fn splits_short_debug(splits: &[SplitMetadata]) -> Vec<SplitShortDebug> {
 pub mod tests {
 
     use std::collections::hash_map::DefaultHasher;
-    use std::collections::{BTreeSet, HashMap};
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
     use std::hash::Hasher;
     use std::ops::RangeInclusive;
 
@@ -338,7 +338,7 @@ pub mod tests {
             pipeline_uid: PipelineUid::from_u128(0u128),
         };
         let split_attrs = merge_split_attrs(merged_split_id, &pipeline_id, splits);
-        create_split_metadata(merge_policy, &split_attrs, tags, 0..0)
+        create_split_metadata(merge_policy, &split_attrs, tags, BTreeMap::new(), 0..0)
     }
 
     fn apply_merge(
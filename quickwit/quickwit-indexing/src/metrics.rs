@@ -18,7 +18,9 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use once_cell::sync::Lazy;
-use quickwit_common::metrics::{new_counter_vec, new_gauge_vec, IntCounterVec, IntGaugeVec};
+use quickwit_common::metrics::{
+    new_counter_vec, new_gauge_vec, new_histogram_vec, HistogramVec, IntCounterVec, IntGaugeVec,
+};
 
 pub struct IndexerMetrics {
     pub processed_docs_total: IntCounterVec<3>,
@@ -26,6 +28,8 @@ pub struct IndexerMetrics {
     pub backpressure_micros: IntCounterVec<2>,
     pub available_concurrent_upload_permits: IntGaugeVec<1>,
     pub ongoing_merge_operations: IntGaugeVec<2>,
+    pub split_upload_duration_seconds: HistogramVec<1>,
+    pub publish_operations_total: IntCounterVec<2>,
 }
 
 impl Default for IndexerMetrics {
@@ -64,6 +68,18 @@ impl Default for IndexerMetrics {
                 "quickwit_indexing",
                 ["index", "source"],
             ),
+            split_upload_duration_seconds: new_histogram_vec(
+                "split_upload_duration_seconds",
+                "Time spent uploading a split to the storage (in seconds)",
+                "quickwit_indexing",
+                ["index"],
+            ),
+            publish_operations_total: new_counter_vec(
+                "publish_operations_total",
+                "Number of publish operations by index and status in [success, error]",
+                "quickwit_indexing",
+                ["index", "status"],
+            ),
         }
     }
 }
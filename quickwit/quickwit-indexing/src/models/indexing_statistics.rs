@@ -33,6 +33,12 @@ pub struct IndexingStatistics {
     pub num_docs: u64,
     /// Number of document parse error, or missing timestamps
     pub num_invalid_docs: u64,
+    /// Number of documents that could not be parsed
+    pub num_parse_errors: u64,
+    /// Number of documents that did not match the doc mapping
+    pub num_schema_errors: u64,
+    /// Number of documents rejected by the VRL transform
+    pub num_transform_errors: u64,
     /// Number of created split
     pub num_local_splits: u64,
     /// Number of staged splits
@@ -41,12 +47,17 @@ pub struct IndexingStatistics {
     pub num_uploaded_splits: u64,
     /// Number of published splits
     pub num_published_splits: u64,
+    /// Number of splits emitted by the indexer and not uploaded yet
+    pub num_splits_in_flight: u64,
     /// Number of empty batches
     pub num_empty_splits: u64,
     /// Size in byte of document processed
     pub total_bytes_processed: u64,
-    /// Size in bytes of resulting split
+    /// Size in bytes of the splits uploaded to the storage
     pub total_size_splits: u64,
+    /// Whether the source stopped reading its input because it reached its maximum number of
+    /// bytes
+    pub reached_max_num_bytes: bool,
     /// Pipeline generation.
     pub generation: usize,
     /// Number of successive pipeline spawn attempts.
@@ -68,6 +79,15 @@ impl IndexingStatistics {
     ) -> Self {
         self.num_docs += doc_processor_counters.num_processed_docs();
         self.num_invalid_docs += doc_processor_counters.num_invalid_docs();
+        self.num_parse_errors += doc_processor_counters
+            .num_parse_errors
+            .load(Ordering::Relaxed);
+        self.num_schema_errors += doc_processor_counters
+            .num_schema_errors
+            .load(Ordering::Relaxed);
+        self.num_transform_errors += doc_processor_counters
+            .num_transform_errors
+            .load(Ordering::Relaxed);
         self.num_local_splits += indexer_counters.num_splits_emitted;
         self.total_bytes_processed += doc_processor_counters
             .num_bytes_total
@@ -76,7 +96,13 @@ impl IndexingStatistics {
         self.num_uploaded_splits += uploader_counters
             .num_uploaded_splits
             .load(Ordering::Relaxed);
+        self.total_size_splits += uploader_counters.num_uploaded_bytes.load(Ordering::Relaxed);
         self.num_published_splits += publisher_counters.num_published_splits;
+        self.num_splits_in_flight += indexer_counters.num_splits_emitted.saturating_sub(
+            uploader_counters
+                .num_uploaded_splits
+                .load(Ordering::Relaxed),
+        );
         self.num_empty_splits += publisher_counters.num_empty_splits;
         self
     }
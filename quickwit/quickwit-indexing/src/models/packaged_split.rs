@@ -17,10 +17,11 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 
 use itertools::Itertools;
+use quickwit_common::geo::GeoBoundingBox;
 use quickwit_common::temp_dir::TempDirectory;
 use quickwit_metastore::checkpoint::IndexCheckpointDelta;
 use quickwit_proto::types::{IndexUid, PublishToken, SplitId};
@@ -35,6 +36,7 @@ pub struct PackagedSplit {
     pub split_attrs: SplitAttrs,
     pub split_scratch_directory: TempDirectory,
     pub tags: BTreeSet<String>,
+    pub geo_bounding_boxes: BTreeMap<String, GeoBoundingBox>,
     pub split_files: Vec<std::path::PathBuf>,
     pub hotcache_bytes: Vec<u8>,
 }
@@ -55,6 +57,7 @@ impl fmt::Debug for PackagedSplit {
             .field("split_attrs", &self.split_attrs)
             .field("split_scratch_directory", &self.split_scratch_directory)
             .field("tags", &self.tags)
+            .field("geo_bounding_boxes", &self.geo_bounding_boxes)
             .field("split_files", &self.split_files)
             .finish()
     }
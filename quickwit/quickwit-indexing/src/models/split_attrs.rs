@@ -17,11 +17,12 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::ops::{Range, RangeInclusive};
 use std::sync::Arc;
 
+use quickwit_common::geo::GeoBoundingBox;
 use quickwit_metastore::SplitMetadata;
 use quickwit_proto::indexing::IndexingPipelineId;
 use tantivy::DateTime;
@@ -86,6 +87,7 @@ pub fn create_split_metadata(
     merge_policy: &Arc<dyn MergePolicy>,
     split_attrs: &SplitAttrs,
     tags: BTreeSet<String>,
+    geo_bounding_boxes: BTreeMap<String, GeoBoundingBox>,
     footer_offsets: Range<u64>,
 ) -> SplitMetadata {
     let create_timestamp = OffsetDateTime::now_utc().unix_timestamp();
@@ -106,6 +108,7 @@ pub fn create_split_metadata(
         create_timestamp,
         maturity,
         tags,
+        geo_bounding_boxes,
         footer_offsets,
         delete_opstamp: split_attrs.delete_opstamp,
         num_merge_ops: split_attrs.num_merge_ops,
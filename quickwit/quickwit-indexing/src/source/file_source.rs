@@ -24,15 +24,17 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
+use async_compression::tokio::bufread::GzipDecoder;
 use async_trait::async_trait;
 use bytes::Bytes;
 use quickwit_actors::{ActorExitStatus, Mailbox};
+use quickwit_common::is_false;
 use quickwit_common::uri::Uri;
-use quickwit_config::FileSourceParams;
+use quickwit_config::{FileSourceCompression, FileSourceParams};
 use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
 use quickwit_proto::types::Position;
 use serde::Serialize;
-use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader};
 use tracing::info;
 
 use crate::actors::DocProcessor;
@@ -47,6 +49,10 @@ pub struct FileSourceCounters {
     pub previous_offset: u64,
     pub current_offset: u64,
     pub num_lines_processed: u64,
+    /// Whether the source stopped before the end of the input because it read the maximum number
+    /// of bytes set in its params.
+    #[serde(skip_serializing_if = "is_false")]
+    pub reached_max_num_bytes: bool,
 }
 
 pub struct FileSource {
@@ -54,6 +60,8 @@ pub struct FileSource {
     params: FileSourceParams,
     counters: FileSourceCounters,
     reader: BufReader<Box<dyn AsyncRead + Send + Unpin>>,
+    /// Offset at which the source stops reading, if `max_num_bytes` is set.
+    max_offset_opt: Option<u64>,
 }
 
 impl fmt::Debug for FileSource {
@@ -74,6 +82,16 @@ impl Source for FileSource {
         let mut reached_eof = false;
         let mut doc_batch = RawDocBatch::default();
         while self.counters.current_offset < limit_num_bytes {
+            if let Some(max_offset) = self.max_offset_opt {
+                if self.counters.current_offset >= max_offset {
+                    info!("reached maximum number of bytes to read");
+                    self.counters.reached_max_num_bytes = true;
+                    // The source then exits as if it had reached the end of the file, so that
+                    // the documents read so far are committed.
+                    reached_eof = true;
+                    break;
+                }
+            }
             let mut doc_line = String::new();
             // guard the zone in case of slow read, such as reading from someone
             // typing to stdin
@@ -139,6 +157,7 @@ impl TypedSourceFactory for FileSourceFactory {
         checkpoint: SourceCheckpoint,
     ) -> anyhow::Result<FileSource> {
         let mut offset = 0;
+        let compression_opt = params.effective_compression();
         let reader: Box<dyn AsyncRead + Send + Unpin> = if let Some(filepath) = &params.filepath {
             let partition_id = PartitionId::from(filepath.to_string_lossy().to_string());
             offset = checkpoint
@@ -152,11 +171,14 @@ impl TypedSourceFactory for FileSourceFactory {
             let (dir_uri, file_name) = dir_and_filename(filepath)?;
             let storage = ctx.storage_resolver.resolve(&dir_uri).await?;
             let file_size = storage.file_num_bytes(file_name).await?.try_into().unwrap();
+            // The offsets of a compressed file are expressed in decompressed bytes, so we cannot
+            // seek and have to read the file from the start.
+            let start = if compression_opt.is_some() { 0 } else { offset };
             storage
                 .get_slice_stream(
                     file_name,
                     Range {
-                        start: offset,
+                        start,
                         end: file_size,
                     },
                 )
@@ -165,14 +187,32 @@ impl TypedSourceFactory for FileSourceFactory {
             // We cannot use the checkpoint.
             Box::new(tokio::io::stdin())
         };
+        let reader = match compression_opt {
+            Some(FileSourceCompression::Gzip) => {
+                let mut gzip_decoder = GzipDecoder::new(BufReader::new(reader));
+                gzip_decoder.multiple_members(true);
+                let mut reader: Box<dyn AsyncRead + Send + Unpin> = Box::new(gzip_decoder);
+                tokio::io::copy(
+                    &mut (&mut reader).take(offset as u64),
+                    &mut tokio::io::sink(),
+                )
+                .await?;
+                reader
+            }
+            None => reader,
+        };
         let file_source = FileSource {
             source_id: ctx.source_id().to_string(),
             counters: FileSourceCounters {
                 previous_offset: offset as u64,
                 current_offset: offset as u64,
                 num_lines_processed: 0,
+                reached_max_num_bytes: false,
             },
             reader: BufReader::new(reader),
+            max_offset_opt: params
+                .max_num_bytes
+                .map(|max_num_bytes| offset as u64 + max_num_bytes.as_u64()),
             params,
         };
         Ok(file_source)
@@ -411,4 +451,159 @@ mod tests {
         let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
         assert!(&indexer_messages[0].docs[0].starts_with(b"2\n"));
     }
+
+    #[tokio::test]
+    async fn test_file_source_stops_at_max_num_bytes() {
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        use tempfile::NamedTempFile;
+        let mut temp_file = NamedTempFile::new().unwrap();
+        for i in 0..100 {
+            temp_file.write_all(format!("{i}\n").as_bytes()).unwrap();
+        }
+        temp_file.flush().unwrap();
+        let temp_file_path = temp_file.path().canonicalize().unwrap();
+        let params = FileSourceParams::file(&temp_file_path)
+            .with_max_num_bytes(Some(bytesize::ByteSize::b(10)));
+        // The limit applies to the bytes read by this run, not to the bytes read since the
+        // beginning of the file.
+        let mut checkpoint = SourceCheckpoint::default();
+        let partition_id = PartitionId::from(temp_file_path.to_string_lossy().to_string());
+        let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            Position::offset(0u64),
+            Position::offset(4u64),
+        )
+        .unwrap();
+        checkpoint.try_apply_delta(checkpoint_delta).unwrap();
+
+        let source_config = SourceConfig {
+            source_id: "test-file-source".to_string(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::File(params.clone()),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        let metastore = metastore_for_test();
+        let source = FileSourceFactory::typed_create_source(
+            SourceRuntimeArgs::for_test(
+                IndexUid::new_with_random_ulid("test-index"),
+                source_config,
+                metastore,
+                PathBuf::from("./queues"),
+            ),
+            params,
+            checkpoint,
+        )
+        .await
+        .unwrap();
+        let file_source_actor = SourceActor {
+            source: Box::new(source),
+            doc_processor_mailbox,
+        };
+        let (_file_source_mailbox, file_source_handle) =
+            universe.spawn_builder().spawn(file_source_actor);
+        let (actor_termination, counters) = file_source_handle.join().await;
+        assert!(actor_termination.is_success());
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "previous_offset": 14u64,
+                "current_offset": 14u64,
+                "num_lines_processed": 5u64,
+                "reached_max_num_bytes": true,
+            })
+        );
+        let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        assert_eq!(indexer_messages.len(), 1);
+        assert_eq!(
+            &indexer_messages[0].docs,
+            &["2\n", "3\n", "4\n", "5\n", "6\n"]
+        );
+        assert_eq!(
+            &extract_position_delta(&indexer_messages[0].checkpoint_delta).unwrap(),
+            "00000000000000000004..00000000000000000014"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_file_source_gzip_resume_from_checkpoint() {
+        use async_compression::tokio::write::GzipEncoder;
+        use tokio::io::AsyncWriteExt;
+
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let mut gzip_encoder = GzipEncoder::new(Vec::new());
+        for i in 0..100 {
+            gzip_encoder
+                .write_all(format!("{i}\n").as_bytes())
+                .await
+                .unwrap();
+        }
+        gzip_encoder.shutdown().await.unwrap();
+        let mut temp_file = tempfile::Builder::new()
+            .suffix(".json.gz")
+            .tempfile()
+            .unwrap();
+        temp_file.write_all(&gzip_encoder.into_inner()).unwrap();
+        temp_file.flush().unwrap();
+        let temp_file_path = temp_file.path().canonicalize().unwrap();
+        // The compression is inferred from the `.gz` extension.
+        let params = FileSourceParams::file(&temp_file_path);
+        let mut checkpoint = SourceCheckpoint::default();
+        let partition_id = PartitionId::from(temp_file_path.to_string_lossy().to_string());
+        let checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            Position::offset(0u64),
+            Position::offset(4u64),
+        )
+        .unwrap();
+        checkpoint.try_apply_delta(checkpoint_delta).unwrap();
+
+        let source_config = SourceConfig {
+            source_id: "test-file-source".to_string(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::File(params.clone()),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        let metastore = metastore_for_test();
+        let source = FileSourceFactory::typed_create_source(
+            SourceRuntimeArgs::for_test(
+                IndexUid::new_with_random_ulid("test-index"),
+                source_config,
+                metastore,
+                PathBuf::from("./queues"),
+            ),
+            params,
+            checkpoint,
+        )
+        .await
+        .unwrap();
+        let file_source_actor = SourceActor {
+            source: Box::new(source),
+            doc_processor_mailbox,
+        };
+        let (_file_source_mailbox, file_source_handle) =
+            universe.spawn_builder().spawn(file_source_actor);
+        let (actor_termination, counters) = file_source_handle.join().await;
+        assert!(actor_termination.is_success());
+        // Offsets are expressed in decompressed bytes.
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "previous_offset": 290u64,
+                "current_offset": 290u64,
+                "num_lines_processed": 98u64
+            })
+        );
+        let indexer_messages: Vec<RawDocBatch> = doc_processor_inbox.drain_for_test_typed();
+        assert!(&indexer_messages[0].docs[0].starts_with(b"2\n"));
+    }
 }
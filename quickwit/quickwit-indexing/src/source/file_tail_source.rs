@@ -0,0 +1,784 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::io::{self, SeekFrom};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use bytes::Bytes;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use quickwit_actors::{ActorExitStatus, Mailbox};
+use quickwit_config::FileTailSourceParams;
+use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
+use quickwit_proto::types::Position;
+use serde::Serialize;
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncSeekExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::time;
+use tracing::{info, warn};
+
+use crate::actors::DocProcessor;
+use crate::source::{
+    BatchBuilder, Source, SourceContext, SourceRuntimeArgs, TypedSourceFactory,
+    EMIT_BATCHES_TIMEOUT,
+};
+
+/// Identifies a file independently of its path, so that we can detect that the tailed file was
+/// replaced by a new one. This is the device and inode numbers of the file.
+type FileId = (u64, u64);
+
+#[cfg(unix)]
+fn file_id(metadata: &std::fs::Metadata) -> Option<FileId> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_id(_metadata: &std::fs::Metadata) -> Option<FileId> {
+    None
+}
+
+/// Position of the source in the tailed file.
+///
+/// The generation is incremented every time the file is rotated, so that positions keep
+/// increasing when the source starts over from the beginning of a new file.
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+struct TailPosition {
+    generation: u64,
+    offset: u64,
+    /// Identifies the file read at this position, so that a rotation that happened while the
+    /// source was not running is detected when it restarts. Missing from the positions
+    /// checkpointed by older versions, and on platforms without inodes.
+    file_id_opt: Option<FileId>,
+}
+
+impl TailPosition {
+    fn from_position(position: &Position) -> anyhow::Result<Self> {
+        let Position::Offset(offset) = position else {
+            if *position == Position::Beginning {
+                return Ok(Self::default());
+            }
+            bail!("unexpected file tail position `{position:?}`");
+        };
+        let parts: Vec<&str> = offset.as_str().split(':').collect();
+        let tail_position = match parts[..] {
+            [generation_str, offset_str] => TailPosition {
+                generation: generation_str.parse()?,
+                offset: offset_str.parse()?,
+                file_id_opt: None,
+            },
+            [generation_str, offset_str, device_str, inode_str] => TailPosition {
+                generation: generation_str.parse()?,
+                offset: offset_str.parse()?,
+                file_id_opt: Some((device_str.parse()?, inode_str.parse()?)),
+            },
+            _ => bail!("invalid file tail position `{position:?}`"),
+        };
+        Ok(tail_position)
+    }
+
+    fn to_position(self) -> Position {
+        if self == Self::default() {
+            return Position::Beginning;
+        }
+        // The generation and the offset are padded so that the lexicographical order of positions
+        // matches the order of `TailPosition`.
+        let position_str = if let Some((device, inode)) = self.file_id_opt {
+            format!(
+                "{:0>20}:{:0>20}:{device}:{inode}",
+                self.generation, self.offset
+            )
+        } else {
+            format!("{:0>20}:{:0>20}", self.generation, self.offset)
+        };
+        Position::offset(position_str.as_str())
+    }
+}
+
+#[derive(Default, Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct FileTailSourceCounters {
+    pub generation: u64,
+    pub current_offset: u64,
+    pub num_lines_processed: u64,
+    pub num_rotations: u64,
+    pub num_oversized_lines: u64,
+}
+
+struct TailReader {
+    reader: BufReader<File>,
+    file_id_opt: Option<FileId>,
+}
+
+/// Source tailing a local file: it reads the lines appended to the file as they are written,
+/// waking up on file system notifications.
+///
+/// When the file is rotated, that is replaced by a new file or truncated, the source starts over
+/// from the beginning of the new file.
+pub struct FileTailSource {
+    source_id: String,
+    params: FileTailSourceParams,
+    partition_id: PartitionId,
+    counters: FileTailSourceCounters,
+    // Position up to which lines were sent to the doc processor.
+    previous_position: TailPosition,
+    // `None` as long as the file does not exist.
+    tail_reader_opt: Option<TailReader>,
+    // Beginning of a line whose end has not been written yet.
+    partial_line: Vec<u8>,
+    // Number of bytes of the current line dropped so far because the line exceeds
+    // `max_line_bytes`.
+    num_dropped_line_bytes: u64,
+    fs_event_rx: mpsc::UnboundedReceiver<notify::Result<notify::Event>>,
+    _watcher: RecommendedWatcher,
+}
+
+impl fmt::Debug for FileTailSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FileTailSource {{ source_id: {} }}", self.source_id)
+    }
+}
+
+impl FileTailSource {
+    fn current_position(&self) -> TailPosition {
+        TailPosition {
+            generation: self.counters.generation,
+            offset: self.counters.current_offset,
+            file_id_opt: self
+                .tail_reader_opt
+                .as_ref()
+                .and_then(|tail_reader| tail_reader.file_id_opt),
+        }
+    }
+
+    /// Reads the complete lines available in the file until the batch reaches
+    /// `max_batch_bytes`. Returns true if it reached the end of the file.
+    ///
+    /// Lines longer than `max_line_bytes` are dropped, without ever being held in memory.
+    async fn read_lines(
+        &mut self,
+        batch_builder: &mut BatchBuilder,
+        ctx: &SourceContext,
+    ) -> anyhow::Result<bool> {
+        let Some(tail_reader) = &mut self.tail_reader_opt else {
+            return Ok(true);
+        };
+        let max_line_bytes = self.params.max_line_bytes.as_u64() as usize;
+
+        while batch_builder.num_bytes < self.params.max_batch_bytes.as_u64() {
+            // We read one byte more than the maximum line length to detect oversized lines.
+            let max_num_bytes_to_read = (max_line_bytes + 1 - self.partial_line.len()) as u64;
+            let num_bytes = ctx
+                .protect_future(
+                    (&mut tail_reader.reader)
+                        .take(max_num_bytes_to_read)
+                        .read_until(b'\n', &mut self.partial_line),
+                )
+                .await?;
+            if num_bytes == 0 {
+                return Ok(true);
+            }
+            if self.partial_line.last() != Some(&b'\n') {
+                if self.partial_line.len() <= max_line_bytes {
+                    // The writer has not finished writing the line.
+                    return Ok(true);
+                }
+                self.num_dropped_line_bytes += self.partial_line.len() as u64;
+                self.partial_line.clear();
+                continue;
+            }
+            let line = std::mem::take(&mut self.partial_line);
+
+            if self.num_dropped_line_bytes > 0 {
+                let line_num_bytes = self.num_dropped_line_bytes + line.len() as u64;
+                warn!(
+                    path=%self.params.path.display(),
+                    offset=self.counters.current_offset,
+                    "dropping line of {line_num_bytes} bytes exceeding `max_line_bytes` \
+                     ({max_line_bytes} bytes)"
+                );
+                self.counters.current_offset += line_num_bytes;
+                self.counters.num_oversized_lines += 1;
+                self.num_dropped_line_bytes = 0;
+                continue;
+            }
+            self.counters.current_offset += line.len() as u64;
+            self.counters.num_lines_processed += 1;
+            batch_builder.add_doc(Bytes::from(line));
+        }
+        Ok(false)
+    }
+
+    /// Opens the file if it was not opened yet, or reopens it if it was rotated. Returns true if
+    /// a file was (re)opened.
+    ///
+    /// A rotation is detected when the file found at the tailed path is not the file being read
+    /// anymore, or when its size is lower than the number of bytes read so far. When the source
+    /// starts, the file being read is the one recorded in the checkpoint.
+    async fn open_file_if_needed(&mut self) -> anyhow::Result<bool> {
+        let mut file = match File::open(&self.params.path).await {
+            Ok(file) => file,
+            Err(io_error) if io_error.kind() == io::ErrorKind::NotFound => {
+                // The file was not created yet or it was moved away by a rotation. In the latter
+                // case, we wait for the new file to show up.
+                return Ok(false);
+            }
+            Err(io_error) => {
+                return Err(io_error).with_context(|| {
+                    format!("failed to open file `{}`", self.params.path.display())
+                });
+            }
+        };
+        let metadata = file.metadata().await?;
+        let file_id_opt = file_id(&metadata);
+        let num_bytes_read = self.counters.current_offset
+            + self.num_dropped_line_bytes
+            + self.partial_line.len() as u64;
+
+        let previous_file_id_opt = match &self.tail_reader_opt {
+            Some(tail_reader) => tail_reader.file_id_opt,
+            None => self.previous_position.file_id_opt,
+        };
+        let is_replaced = previous_file_id_opt.is_some() && previous_file_id_opt != file_id_opt;
+        let is_rotated = is_replaced || metadata.len() < num_bytes_read;
+        if self.tail_reader_opt.is_some() && !is_rotated {
+            return Ok(false);
+        }
+        if is_rotated {
+            info!(path=%self.params.path.display(), "file-rotated");
+            self.counters.generation += 1;
+            self.counters.current_offset = 0;
+            self.counters.num_rotations += 1;
+            self.partial_line.clear();
+            self.num_dropped_line_bytes = 0;
+        }
+        file.seek(SeekFrom::Start(self.counters.current_offset))
+            .await?;
+        self.tail_reader_opt = Some(TailReader {
+            reader: BufReader::new(file),
+            file_id_opt,
+        });
+        Ok(true)
+    }
+}
+
+#[async_trait]
+impl Source for FileTailSource {
+    async fn emit_batches(
+        &mut self,
+        doc_processor_mailbox: &Mailbox<DocProcessor>,
+        ctx: &SourceContext,
+    ) -> Result<Duration, ActorExitStatus> {
+        let mut batch_builder = BatchBuilder::default();
+        let deadline = time::sleep(EMIT_BATCHES_TIMEOUT);
+        tokio::pin!(deadline);
+
+        loop {
+            let reached_eof = self.read_lines(&mut batch_builder, ctx).await?;
+
+            if !reached_eof || !batch_builder.docs.is_empty() {
+                break;
+            }
+            if self.open_file_if_needed().await? {
+                continue;
+            }
+            // We are all caught up: let's wait for the file to change.
+            tokio::select! {
+                fs_event_opt = self.fs_event_rx.recv() => {
+                    if let Some(Err(error)) = fs_event_opt {
+                        warn!(error=?error, path=%self.params.path.display(), "file-watch-error");
+                    }
+                    // Several events are usually emitted for a single write.
+                    while self.fs_event_rx.try_recv().is_ok() {}
+                }
+                _ = &mut deadline => {
+                    break;
+                }
+            }
+            ctx.record_progress();
+        }
+        if !batch_builder.docs.is_empty() {
+            let current_position = self.current_position();
+            batch_builder
+                .checkpoint_delta
+                .record_partition_delta(
+                    self.partition_id.clone(),
+                    self.previous_position.to_position(),
+                    current_position.to_position(),
+                )
+                .context("failed to record partition delta")?;
+            self.previous_position = current_position;
+            ctx.send_message(doc_processor_mailbox, batch_builder.build())
+                .await?;
+        }
+        Ok(Duration::default())
+    }
+
+    fn name(&self) -> String {
+        format!("FileTailSource{{source_id={}}}", self.source_id)
+    }
+
+    fn observable_state(&self) -> serde_json::Value {
+        serde_json::to_value(&self.counters).unwrap()
+    }
+}
+
+pub struct FileTailSourceFactory;
+
+#[async_trait]
+impl TypedSourceFactory for FileTailSourceFactory {
+    type Source = FileTailSource;
+    type Params = FileTailSourceParams;
+
+    async fn typed_create_source(
+        ctx: Arc<SourceRuntimeArgs>,
+        params: FileTailSourceParams,
+        checkpoint: SourceCheckpoint,
+    ) -> anyhow::Result<FileTailSource> {
+        let partition_id = PartitionId::from(params.path.to_string_lossy().to_string());
+        let checkpoint_position = checkpoint
+            .position_for_partition(&partition_id)
+            .map(TailPosition::from_position)
+            .transpose()?
+            .unwrap_or_default();
+
+        // We watch the parent directory rather than the file itself, to be notified when the
+        // file is created or replaced.
+        let watched_dir = params
+            .path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let (fs_event_tx, fs_event_rx) = mpsc::unbounded_channel();
+        let mut watcher = notify::recommended_watcher(move |fs_event| {
+            let _ = fs_event_tx.send(fs_event);
+        })?;
+        watcher
+            .watch(watched_dir, RecursiveMode::NonRecursive)
+            .with_context(|| format!("failed to watch directory `{}`", watched_dir.display()))?;
+
+        let mut file_tail_source = FileTailSource {
+            source_id: ctx.source_id().to_string(),
+            params,
+            partition_id,
+            counters: FileTailSourceCounters {
+                generation: checkpoint_position.generation,
+                current_offset: checkpoint_position.offset,
+                num_lines_processed: 0,
+                num_rotations: 0,
+                num_oversized_lines: 0,
+            },
+            previous_position: checkpoint_position,
+            tail_reader_opt: None,
+            partial_line: Vec::new(),
+            num_dropped_line_bytes: 0,
+            fs_event_rx,
+            _watcher: watcher,
+        };
+        file_tail_source.open_file_if_needed().await?;
+        Ok(file_tail_source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::num::NonZeroUsize;
+    use std::path::PathBuf;
+
+    use bytesize::ByteSize;
+    use quickwit_actors::{Inbox, Universe};
+    use quickwit_config::{SourceConfig, SourceInputFormat, SourceParams};
+    use quickwit_metastore::checkpoint::SourceCheckpointDelta;
+    use quickwit_metastore::metastore_for_test;
+    use quickwit_proto::types::IndexUid;
+
+    use super::*;
+    use crate::models::RawDocBatch;
+    use crate::source::SourceActor;
+
+    async fn create_file_tail_source(
+        params: FileTailSourceParams,
+        checkpoint: SourceCheckpoint,
+    ) -> FileTailSource {
+        let source_config = SourceConfig {
+            source_id: "test-file-tail-source".to_string(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::FileTail(params.clone()),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        FileTailSourceFactory::typed_create_source(
+            SourceRuntimeArgs::for_test(
+                IndexUid::new_with_random_ulid("test-index"),
+                source_config,
+                metastore_for_test(),
+                PathBuf::from("./queues"),
+            ),
+            params,
+            checkpoint,
+        )
+        .await
+        .unwrap()
+    }
+
+    /// Receives the batches emitted by the source until it has emitted `num_docs` documents.
+    async fn wait_for_docs(
+        doc_processor_inbox: &Inbox<DocProcessor>,
+        batches: &mut Vec<RawDocBatch>,
+        num_docs: usize,
+    ) {
+        let mut num_emitted_docs: usize = batches.iter().map(|batch| batch.docs.len()).sum();
+
+        while num_emitted_docs < num_docs {
+            let batch = doc_processor_inbox
+                .recv_typed_message::<RawDocBatch>()
+                .await
+                .unwrap();
+            num_emitted_docs += batch.docs.len();
+            batches.push(batch);
+        }
+        assert_eq!(num_emitted_docs, num_docs);
+    }
+
+    fn file_id_for_test(path: &Path) -> Option<FileId> {
+        file_id(&std::fs::metadata(path).unwrap())
+    }
+
+    fn emitted_lines(batches: &[RawDocBatch]) -> Vec<String> {
+        batches
+            .iter()
+            .flat_map(|batch| batch.docs.iter())
+            .map(|doc| String::from_utf8(doc.to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_tail_position_order() {
+        let positions = [
+            TailPosition::default(),
+            TailPosition {
+                generation: 0,
+                offset: 9,
+                file_id_opt: Some((1, 100)),
+            },
+            TailPosition {
+                generation: 0,
+                offset: 10,
+                file_id_opt: None,
+            },
+            TailPosition {
+                generation: 0,
+                offset: 10,
+                file_id_opt: Some((1, 100)),
+            },
+            TailPosition {
+                generation: 1,
+                offset: 0,
+                file_id_opt: Some((1, 2)),
+            },
+        ];
+        for window in positions.windows(2) {
+            assert!(window[0].to_position() < window[1].to_position());
+        }
+        for position in positions {
+            assert_eq!(
+                TailPosition::from_position(&position.to_position()).unwrap(),
+                position
+            );
+        }
+        assert_eq!(
+            TailPosition::from_position(&Position::Beginning).unwrap(),
+            TailPosition::default()
+        );
+        TailPosition::from_position(&Position::offset(42u64)).unwrap_err();
+        TailPosition::from_position(&Position::offset("1:2:3")).unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_file_tail_source_catches_up_with_writer() {
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "line-0\nline-1\n").unwrap();
+
+        let params = FileTailSourceParams::new(&path);
+        let file_tail_source = create_file_tail_source(params, SourceCheckpoint::default()).await;
+        let file_tail_source_actor = SourceActor {
+            source: Box::new(file_tail_source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) =
+            universe.spawn_builder().spawn(file_tail_source_actor);
+
+        let mut batches = Vec::new();
+        wait_for_docs(&doc_processor_inbox, &mut batches, 2).await;
+
+        let writer_path = path.clone();
+        let writer_thread = std::thread::spawn(move || {
+            let mut file = std::fs::OpenOptions::new()
+                .append(true)
+                .open(writer_path)
+                .unwrap();
+            for i in 2..10 {
+                writeln!(file, "line-{i}").unwrap();
+                std::thread::sleep(Duration::from_millis(10));
+            }
+            // The source must not emit a line until its end is written.
+            write!(file, "line-").unwrap();
+            file.flush().unwrap();
+            std::thread::sleep(Duration::from_millis(200));
+            writeln!(file, "10").unwrap();
+        });
+        wait_for_docs(&doc_processor_inbox, &mut batches, 11).await;
+        writer_thread.join().unwrap();
+
+        let expected_lines: Vec<String> = (0..11).map(|i| format!("line-{i}\n")).collect();
+        assert_eq!(emitted_lines(&batches), expected_lines);
+
+        let file_len = std::fs::metadata(&path).unwrap().len();
+        let mut checkpoint_delta = SourceCheckpointDelta::default();
+        for batch in &batches {
+            checkpoint_delta
+                .extend(batch.checkpoint_delta.clone())
+                .unwrap();
+        }
+        let partition_id = PartitionId::from(path.to_string_lossy().to_string());
+        let expected_checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            Position::Beginning,
+            TailPosition {
+                generation: 0,
+                offset: file_len,
+                file_id_opt: file_id_for_test(&path),
+            }
+            .to_position(),
+        )
+        .unwrap();
+        assert_eq!(checkpoint_delta, expected_checkpoint_delta);
+
+        let counters = source_handle.process_pending_and_observe().await.state;
+        assert_eq!(
+            counters,
+            serde_json::json!({
+                "generation": 0u64,
+                "current_offset": file_len,
+                "num_lines_processed": 11u64,
+                "num_rotations": 0u64,
+                "num_oversized_lines": 0u64,
+            })
+        );
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_file_tail_source_resets_on_rotation() {
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "first-line\nsecond-line\n").unwrap();
+
+        let params = FileTailSourceParams::new(&path);
+        let file_tail_source = create_file_tail_source(params, SourceCheckpoint::default()).await;
+        let file_tail_source_actor = SourceActor {
+            source: Box::new(file_tail_source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) =
+            universe.spawn_builder().spawn(file_tail_source_actor);
+
+        let mut batches = Vec::new();
+        wait_for_docs(&doc_processor_inbox, &mut batches, 2).await;
+
+        // Truncation, as done by `copytruncate` rotations.
+        std::fs::write(&path, "third\n").unwrap();
+        wait_for_docs(&doc_processor_inbox, &mut batches, 3).await;
+
+        // Replacement of the file by a new one.
+        let rotated_path = temp_dir.path().join("app.log.1");
+        std::fs::rename(&path, rotated_path).unwrap();
+        std::fs::write(&path, "fourth\n").unwrap();
+        wait_for_docs(&doc_processor_inbox, &mut batches, 4).await;
+
+        assert_eq!(
+            emitted_lines(&batches),
+            ["first-line\n", "second-line\n", "third\n", "fourth\n"]
+        );
+        let mut checkpoint_delta = SourceCheckpointDelta::default();
+        for batch in &batches {
+            checkpoint_delta
+                .extend(batch.checkpoint_delta.clone())
+                .unwrap();
+        }
+        let partition_id = PartitionId::from(path.to_string_lossy().to_string());
+        let expected_checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            Position::Beginning,
+            TailPosition {
+                generation: 2,
+                offset: 7,
+                file_id_opt: file_id_for_test(&path),
+            }
+            .to_position(),
+        )
+        .unwrap();
+        assert_eq!(checkpoint_delta, expected_checkpoint_delta);
+        let counters = source_handle.process_pending_and_observe().await.state;
+        assert_eq!(counters["generation"], 2);
+        assert_eq!(counters["num_rotations"], 2);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_file_tail_source_resumes_from_checkpoint() {
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "a\nb\nc\n").unwrap();
+
+        let partition_id = PartitionId::from(path.to_string_lossy().to_string());
+        // Checkpoints written by older versions do not identify the file.
+        let checkpoint_position = TailPosition {
+            generation: 3,
+            offset: 2,
+            file_id_opt: None,
+        };
+        let checkpoint = SourceCheckpoint::from_iter(vec![(
+            partition_id.clone(),
+            checkpoint_position.to_position(),
+        )]);
+        let params = FileTailSourceParams::new(&path);
+        let file_tail_source = create_file_tail_source(params, checkpoint).await;
+        let file_tail_source_actor = SourceActor {
+            source: Box::new(file_tail_source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) =
+            universe.spawn_builder().spawn(file_tail_source_actor);
+
+        let mut batches = Vec::new();
+        wait_for_docs(&doc_processor_inbox, &mut batches, 2).await;
+        assert_eq!(emitted_lines(&batches), ["b\n", "c\n"]);
+
+        let expected_checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            checkpoint_position.to_position(),
+            TailPosition {
+                generation: 3,
+                offset: 6,
+                file_id_opt: file_id_for_test(&path),
+            }
+            .to_position(),
+        )
+        .unwrap();
+        assert_eq!(batches[0].checkpoint_delta, expected_checkpoint_delta);
+
+        let counters = source_handle.process_pending_and_observe().await.state;
+        assert_eq!(counters["num_rotations"], 0);
+        universe.assert_quit().await;
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_file_tail_source_detects_rotation_while_stopped() {
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("app.log");
+        std::fs::write(&path, "new-file-line-0\nnew-file-line-1\n").unwrap();
+
+        // The checkpointed file is not the one found at the tailed path anymore, although the new
+        // file is larger than the checkpointed offset.
+        let partition_id = PartitionId::from(path.to_string_lossy().to_string());
+        let (device, inode) = file_id_for_test(&path).unwrap();
+        let checkpoint_position = TailPosition {
+            generation: 3,
+            offset: 4,
+            file_id_opt: Some((device, inode + 1)),
+        };
+        let checkpoint =
+            SourceCheckpoint::from_iter(vec![(partition_id, checkpoint_position.to_position())]);
+        let params = FileTailSourceParams::new(&path);
+        let file_tail_source = create_file_tail_source(params, checkpoint).await;
+        let file_tail_source_actor = SourceActor {
+            source: Box::new(file_tail_source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) =
+            universe.spawn_builder().spawn(file_tail_source_actor);
+
+        let mut batches = Vec::new();
+        wait_for_docs(&doc_processor_inbox, &mut batches, 2).await;
+        assert_eq!(
+            emitted_lines(&batches),
+            ["new-file-line-0\n", "new-file-line-1\n"]
+        );
+        let counters = source_handle.process_pending_and_observe().await.state;
+        assert_eq!(counters["generation"], 4);
+        assert_eq!(counters["num_rotations"], 1);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_file_tail_source_drops_oversized_lines() {
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let temp_dir = tempfile::tempdir().unwrap();
+        let path = temp_dir.path().join("app.log");
+        let oversized_line = "x".repeat(25);
+        std::fs::write(&path, format!("short\n{oversized_line}\nlast\n")).unwrap();
+
+        let mut params = FileTailSourceParams::new(&path);
+        params.max_line_bytes = ByteSize(10);
+        let file_tail_source = create_file_tail_source(params, SourceCheckpoint::default()).await;
+        let file_tail_source_actor = SourceActor {
+            source: Box::new(file_tail_source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) =
+            universe.spawn_builder().spawn(file_tail_source_actor);
+
+        let mut batches = Vec::new();
+        wait_for_docs(&doc_processor_inbox, &mut batches, 2).await;
+        assert_eq!(emitted_lines(&batches), ["short\n", "last\n"]);
+
+        let counters = source_handle.process_pending_and_observe().await.state;
+        assert_eq!(counters["num_oversized_lines"], 1);
+        // The offset covers the dropped line, so that it is not read again after a restart.
+        assert_eq!(
+            counters["current_offset"],
+            std::fs::metadata(&path).unwrap().len()
+        );
+        universe.assert_quit().await;
+    }
+}
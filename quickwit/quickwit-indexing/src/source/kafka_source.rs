@@ -192,6 +192,53 @@ fn collect_partitions(tpl: &TopicPartitionList, topic: &str) -> Vec<i32> {
 
 type RdKafkaConsumer = BaseConsumer<RdKafkaContext>;
 
+/// The subset of the Kafka consumer API used by the source. It allows testing the partition and
+/// offset bookkeeping of the source without a Kafka broker.
+///
+/// Rebalances are not part of the trait: as `RdKafkaContext` does, the consumer must notify the
+/// source of partition assignments and revocations by sending the corresponding events from
+/// within `poll`.
+trait KafkaConsumer: Send + 'static {
+    fn subscribe(&self, topic: &str) -> anyhow::Result<()>;
+
+    /// Polls the consumer for a message. Returns `None` if nothing was received before `timeout`.
+    fn poll(&self, timeout: Duration) -> Option<KafkaEvent>;
+
+    /// Commits, for each partition, the offset of the next message to consume.
+    fn commit(&self, topic: &str, next_offsets: &[(i32, i64)]) -> anyhow::Result<()>;
+
+    fn unsubscribe(&self);
+}
+
+impl KafkaConsumer for RdKafkaConsumer {
+    fn subscribe(&self, topic: &str) -> anyhow::Result<()> {
+        Consumer::subscribe(self, &[topic])?;
+        Ok(())
+    }
+
+    fn poll(&self, timeout: Duration) -> Option<KafkaEvent> {
+        let event = match BaseConsumer::poll(self, timeout)? {
+            Ok(message) => KafkaEvent::Message(message.into()),
+            Err(KafkaError::PartitionEOF(partition)) => KafkaEvent::PartitionEOF(partition),
+            Err(error) => KafkaEvent::Error(anyhow!(error)),
+        };
+        Some(event)
+    }
+
+    fn commit(&self, topic: &str, next_offsets: &[(i32, i64)]) -> anyhow::Result<()> {
+        let mut tpl = TopicPartitionList::new();
+        for &(partition, next_offset) in next_offsets {
+            tpl.add_partition_offset(topic, partition, Offset::Offset(next_offset))?;
+        }
+        Consumer::commit(self, &tpl, CommitMode::Async)?;
+        Ok(())
+    }
+
+    fn unsubscribe(&self) {
+        Consumer::unsubscribe(self)
+    }
+}
+
 #[derive(Default)]
 pub struct KafkaSourceState {
     /// Partitions IDs assigned to the source.
@@ -245,7 +292,6 @@ impl KafkaSource {
         let backfill_mode_enabled = params.enable_backfill_mode;
 
         let (events_tx, events_rx) = mpsc::channel(100);
-        let (truncate_tx, truncate_rx) = watch::channel(SourceCheckpoint::default());
         let (client_config, consumer, group_id) =
             create_consumer(ctx.index_uid(), ctx.source_id(), params, events_tx.clone())?;
         let native_client_config = client_config.create_native_config()?;
@@ -256,10 +302,6 @@ impl KafkaSource {
             .get("max.poll.interval.ms")?
             .parse::<u64>()?;
 
-        let poll_loop_jh =
-            spawn_consumer_poll_loop(consumer, topic.clone(), events_tx, truncate_rx);
-        let publish_lock = PublishLock::default();
-
         info!(
             index_id=%ctx.index_id(),
             source_id=%ctx.source_id(),
@@ -276,7 +318,33 @@ impl KafkaSource {
                  `300000` (5 minutes)."
             );
         }
-        Ok(KafkaSource {
+        let kafka_source = Self::with_consumer(
+            ctx,
+            topic,
+            group_id,
+            backfill_mode_enabled,
+            consumer,
+            events_tx,
+            events_rx,
+        );
+        Ok(kafka_source)
+    }
+
+    /// Instantiates a new `KafkaSource` polling `consumer` from a blocking task. The consumer
+    /// sends its events to the source through `events_tx`.
+    fn with_consumer<C: KafkaConsumer>(
+        ctx: Arc<SourceRuntimeArgs>,
+        topic: String,
+        group_id: GroupId,
+        backfill_mode_enabled: bool,
+        consumer: C,
+        events_tx: mpsc::Sender<KafkaEvent>,
+        events_rx: mpsc::Receiver<KafkaEvent>,
+    ) -> Self {
+        let (truncate_tx, truncate_rx) = watch::channel(SourceCheckpoint::default());
+        let poll_loop_jh =
+            spawn_consumer_poll_loop(consumer, topic.clone(), events_tx, truncate_rx);
+        KafkaSource {
             ctx,
             topic,
             group_id,
@@ -285,8 +353,8 @@ impl KafkaSource {
             events_rx,
             truncate_tx,
             poll_loop_jh,
-            publish_lock,
-        })
+            publish_lock: PublishLock::default(),
+        }
     }
 
     async fn process_message(
@@ -569,8 +637,8 @@ impl Source for KafkaSource {
 // callback is sync. Until `rust-rdkafka` offers a fully asynchronous API, we poll the consumer in a
 // blocking tokio task and handle the rebalance events via message passing between the rebalance
 // callback and the source.
-fn spawn_consumer_poll_loop(
-    consumer: RdKafkaConsumer,
+fn spawn_consumer_poll_loop<C: KafkaConsumer>(
+    consumer: C,
     topic: String,
     events_tx: mpsc::Sender<KafkaEvent>,
     mut truncate_rx: watch::Receiver<SourceCheckpoint>,
@@ -583,17 +651,12 @@ fn spawn_consumer_poll_loop(
         // `subscribe()` is an asynchronous method which returns immediately: background threads
         // will (re)join the group, wait for group rebalance, issue any registered rebalance_cb,
         // assign() the assigned partitions, and then start fetching messages.
-        if let Err(error) = consumer.subscribe(&[&topic]) {
+        if let Err(error) = consumer.subscribe(&topic) {
             let _ = events_tx.blocking_send(KafkaEvent::Error(anyhow!(error)));
             return;
         }
         while !events_tx.is_closed() {
-            if let Some(message_res) = consumer.poll(Some(Duration::from_secs(1))) {
-                let event = match message_res {
-                    Ok(message) => KafkaEvent::Message(message.into()),
-                    Err(KafkaError::PartitionEOF(partition)) => KafkaEvent::PartitionEOF(partition),
-                    Err(error) => KafkaEvent::Error(anyhow!(error)),
-                };
+            if let Some(event) = consumer.poll(Duration::from_secs(1)) {
                 // When the source experiences backpressure, this channel becomes full and the
                 // consumer might not call `poll()` for a duration that exceeds
                 // `max.poll.interval.ms`. When that happens the consumer is kicked out of the group
@@ -605,25 +668,9 @@ fn spawn_consumer_poll_loop(
                 }
             }
             if let Ok(true) = truncate_rx.has_changed() {
-                let checkpoint = truncate_rx.borrow_and_update();
+                let next_offsets = next_offsets_to_commit(&truncate_rx.borrow_and_update());
 
-                let mut tpl = TopicPartitionList::new();
-                for (partition_id, position) in checkpoint.iter() {
-                    let partition = partition_id
-                        .as_i64()
-                        .expect("Kafka partition should be stored as i64.")
-                        as i32;
-                    // Quickwit positions are inclusive whereas Kafka offsets are exclusive, hence
-                    // the increment by 1.
-                    let next_position = position
-                        .as_i64()
-                        .expect("Kafka offset should be stored as i64.")
-                        + 1;
-                    let offset = Offset::Offset(next_position);
-                    tpl.add_partition_offset(&topic, partition, offset)
-                        .expect("The offset should be valid.");
-                }
-                if let Err(error) = consumer.commit(&tpl, CommitMode::Async) {
+                if let Err(error) = consumer.commit(&topic, &next_offsets) {
                     warn!(error=?error, "failed to commit offsets");
                 }
             }
@@ -633,6 +680,26 @@ fn spawn_consumer_poll_loop(
     })
 }
 
+/// Returns, for each partition of the checkpoint, the offset of the next message to consume.
+fn next_offsets_to_commit(checkpoint: &SourceCheckpoint) -> Vec<(i32, i64)> {
+    checkpoint
+        .iter()
+        .map(|(partition_id, position)| {
+            let partition = partition_id
+                .as_i64()
+                .expect("Kafka partition should be stored as i64.")
+                as i32;
+            // Quickwit positions are inclusive whereas Kafka offsets are exclusive, hence the
+            // increment by 1.
+            let next_offset = position
+                .as_i64()
+                .expect("Kafka offset should be stored as i64.")
+                + 1;
+            (partition, next_offset)
+        })
+        .collect()
+}
+
 /// Returns the preceding `Position` for the offset.
 fn previous_position_for_offset(offset: i64) -> Position {
     if offset == 0 {
@@ -775,6 +842,269 @@ fn message_payload_to_doc(message: &BorrowedMessage) -> Option<Bytes> {
     None
 }
 
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, VecDeque};
+    use std::num::NonZeroUsize;
+    use std::path::PathBuf;
+    use std::sync::Mutex;
+
+    use quickwit_actors::Universe;
+    use quickwit_config::{SourceConfig, SourceInputFormat, SourceParams};
+    use quickwit_metastore::checkpoint::{IndexCheckpoint, SourceCheckpointDelta};
+    use quickwit_metastore::IndexMetadata;
+    use quickwit_proto::metastore::{IndexMetadataResponse, MetastoreServiceClient};
+    use tokio::sync::Notify;
+
+    use super::*;
+    use crate::models::RawDocBatch;
+    use crate::source::{SourceActor, SuggestTruncate};
+
+    #[derive(Default)]
+    struct MockKafkaConsumerState {
+        // Offsets from which the source asked to resume consuming the partitions.
+        assignment_opt: Option<Vec<(i32, Offset)>>,
+        events: VecDeque<KafkaEvent>,
+        commits: Vec<Vec<(i32, i64)>>,
+        // Notified every time the source commits offsets.
+        commit_notify: Arc<Notify>,
+    }
+
+    /// Consumer that is assigned `partitions` on its first poll and then replays the queued
+    /// events.
+    struct MockKafkaConsumer {
+        partitions: Vec<i32>,
+        events_tx: mpsc::Sender<KafkaEvent>,
+        state: Arc<Mutex<MockKafkaConsumerState>>,
+    }
+
+    impl KafkaConsumer for MockKafkaConsumer {
+        fn subscribe(&self, _topic: &str) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        fn poll(&self, timeout: Duration) -> Option<KafkaEvent> {
+            if self.state.lock().unwrap().assignment_opt.is_none() {
+                // Just like with `RdKafkaContext`, the rebalance happens while polling.
+                let (assignment_tx, assignment_rx) = oneshot::channel();
+                let assign_event = KafkaEvent::AssignPartitions {
+                    partitions: self.partitions.clone(),
+                    assignment_tx,
+                };
+                self.events_tx.blocking_send(assign_event).ok()?;
+                let assignment = assignment_rx.recv().ok()?;
+                self.state.lock().unwrap().assignment_opt = Some(assignment);
+            }
+            let event_opt = self.state.lock().unwrap().events.pop_front();
+            if event_opt.is_none() {
+                std::thread::sleep(timeout.min(Duration::from_millis(10)));
+            }
+            event_opt
+        }
+
+        fn commit(&self, _topic: &str, next_offsets: &[(i32, i64)]) -> anyhow::Result<()> {
+            let mut state_guard = self.state.lock().unwrap();
+            state_guard.commits.push(next_offsets.to_vec());
+            state_guard.commit_notify.notify_one();
+            Ok(())
+        }
+
+        fn unsubscribe(&self) {}
+    }
+
+    fn message_event(partition: i32, offset: i64, doc_opt: Option<&'static str>) -> KafkaEvent {
+        KafkaEvent::Message(KafkaMessage {
+            doc_opt: doc_opt.map(Bytes::from_static),
+            payload_len: doc_opt.map(str::len).unwrap_or_default() as u64,
+            partition,
+            offset,
+        })
+    }
+
+    fn create_kafka_source(
+        partitions: Vec<i32>,
+        events: Vec<KafkaEvent>,
+        published_checkpoint: SourceCheckpoint,
+        backfill_mode_enabled: bool,
+    ) -> (KafkaSource, Arc<Mutex<MockKafkaConsumerState>>) {
+        let source_id = "test-kafka-source".to_string();
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+        index_metadata.checkpoint =
+            IndexCheckpoint::from(BTreeMap::from([(source_id.clone(), published_checkpoint)]));
+        let index_uid = index_metadata.index_uid.clone();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore.expect_index_metadata().returning(move |_| {
+            Ok(IndexMetadataResponse::try_from_index_metadata(index_metadata.clone()).unwrap())
+        });
+        let source_config = SourceConfig {
+            source_id,
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::Kafka(KafkaSourceParams {
+                topic: "test-topic".to_string(),
+                client_log_level: None,
+                client_params: JsonValue::Null,
+                enable_backfill_mode: backfill_mode_enabled,
+            }),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        let ctx = SourceRuntimeArgs::for_test(
+            index_uid,
+            source_config,
+            MetastoreServiceClient::from(mock_metastore),
+            PathBuf::from("./queues"),
+        );
+        let (events_tx, events_rx) = mpsc::channel(100);
+        let state = Arc::new(Mutex::new(MockKafkaConsumerState {
+            events: events.into(),
+            ..Default::default()
+        }));
+        let consumer = MockKafkaConsumer {
+            partitions,
+            events_tx: events_tx.clone(),
+            state: state.clone(),
+        };
+        let kafka_source = KafkaSource::with_consumer(
+            ctx,
+            "test-topic".to_string(),
+            "test-group".to_string(),
+            backfill_mode_enabled,
+            consumer,
+            events_tx,
+            events_rx,
+        );
+        (kafka_source, state)
+    }
+
+    #[test]
+    fn test_next_offsets_to_commit() {
+        let checkpoint = SourceCheckpoint::from_iter([
+            (PartitionId::from(0i64), Position::offset(41u64)),
+            (PartitionId::from(1i64), Position::offset(0u64)),
+        ]);
+        assert_eq!(next_offsets_to_commit(&checkpoint), [(0, 42), (1, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_kafka_source_with_mock_consumer() {
+        let published_checkpoint =
+            SourceCheckpoint::from_iter([(PartitionId::from(0i64), Position::offset(9u64))]);
+        let events = vec![
+            message_event(0, 10, Some("doc-0")),
+            message_event(1, 0, Some("doc-1")),
+            message_event(1, 1, None),
+            message_event(0, 11, Some("doc-2")),
+            KafkaEvent::PartitionEOF(0),
+            KafkaEvent::PartitionEOF(1),
+        ];
+        let (kafka_source, consumer_state) =
+            create_kafka_source(vec![0, 1], events, published_checkpoint, true);
+
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let source_actor = SourceActor {
+            source: Box::new(kafka_source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) = universe.spawn_builder().spawn(source_actor);
+        let (exit_status, exit_state) = source_handle.join().await;
+        assert!(exit_status.is_success());
+
+        // Partition 1 is absent from the checkpoint, so the consumer falls back to
+        // `auto.offset.reset` for it.
+        assert_eq!(
+            consumer_state.lock().unwrap().assignment_opt,
+            Some(vec![(0, Offset::Offset(10))])
+        );
+        let batches = doc_processor_inbox.drain_for_test_typed::<RawDocBatch>();
+        let docs: Vec<Bytes> = batches
+            .iter()
+            .flat_map(|batch| batch.docs.iter().cloned())
+            .collect();
+        assert_eq!(docs, ["doc-0", "doc-1", "doc-2"]);
+
+        let mut checkpoint_delta = SourceCheckpointDelta::default();
+        for batch in batches {
+            checkpoint_delta.extend(batch.checkpoint_delta).unwrap();
+        }
+        let mut expected_checkpoint_delta = SourceCheckpointDelta::default();
+        expected_checkpoint_delta
+            .record_partition_delta(
+                PartitionId::from(0i64),
+                Position::offset(9u64),
+                Position::offset(11u64),
+            )
+            .unwrap();
+        expected_checkpoint_delta
+            .record_partition_delta(
+                PartitionId::from(1i64),
+                Position::Beginning,
+                Position::offset(1u64),
+            )
+            .unwrap();
+        assert_eq!(checkpoint_delta, expected_checkpoint_delta);
+
+        assert_eq!(exit_state["assigned_partitions"], json!([0, 1]));
+        assert_eq!(exit_state["num_messages_processed"], 4);
+        assert_eq!(exit_state["num_invalid_messages"], 1);
+        assert_eq!(exit_state["num_inactive_partitions"], 2);
+        // Nothing is committed until the splits are published.
+        assert!(consumer_state.lock().unwrap().commits.is_empty());
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_kafka_source_commits_offsets_on_suggest_truncate() {
+        let events = vec![
+            message_event(0, 0, Some("doc-0")),
+            message_event(0, 1, Some("doc-1")),
+        ];
+        let (kafka_source, consumer_state) =
+            create_kafka_source(vec![0], events, SourceCheckpoint::default(), false);
+
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let source_actor = SourceActor {
+            source: Box::new(kafka_source),
+            doc_processor_mailbox,
+        };
+        let (source_mailbox, _source_handle) = universe.spawn_builder().spawn(source_actor);
+
+        let mut num_docs = 0;
+        while num_docs < 2 {
+            num_docs += doc_processor_inbox
+                .recv_typed_message::<RawDocBatch>()
+                .await
+                .unwrap()
+                .docs
+                .len();
+        }
+        assert_eq!(num_docs, 2);
+        assert_eq!(
+            consumer_state.lock().unwrap().assignment_opt,
+            Some(Vec::new())
+        );
+        assert!(consumer_state.lock().unwrap().commits.is_empty());
+
+        let commit_notify = consumer_state.lock().unwrap().commit_notify.clone();
+
+        // The indexing pipeline suggests truncating once the split holding the docs is published.
+        let published_checkpoint =
+            SourceCheckpoint::from_iter([(PartitionId::from(0i64), Position::offset(1u64))]);
+        source_mailbox
+            .send_message(SuggestTruncate(published_checkpoint))
+            .await
+            .unwrap();
+
+        commit_notify.notified().await;
+        assert_eq!(consumer_state.lock().unwrap().commits, [vec![(0, 2)]]);
+        universe.assert_quit().await;
+    }
+}
+
 #[cfg(all(test, feature = "kafka-broker-tests"))]
 mod kafka_broker_tests {
     use std::num::NonZeroUsize;
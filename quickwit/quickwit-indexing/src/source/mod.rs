@@ -58,6 +58,7 @@
 //! - the kafka source: the partition id is a kafka topic partition id, and the position is a kafka
 //!   offset.
 mod file_source;
+mod file_tail_source;
 #[cfg(feature = "gcp-pubsub")]
 mod gcp_pubsub_source;
 mod ingest;
@@ -68,6 +69,7 @@ mod kafka_source;
 mod kinesis;
 #[cfg(feature = "pulsar")]
 mod pulsar_source;
+mod push_source;
 mod source_factory;
 mod vec_source;
 mod void_source;
@@ -80,6 +82,7 @@ use async_trait::async_trait;
 use bytes::Bytes;
 use bytesize::ByteSize;
 pub use file_source::{FileSource, FileSourceFactory};
+pub use file_tail_source::{FileTailSource, FileTailSourceFactory};
 #[cfg(feature = "gcp-pubsub")]
 pub use gcp_pubsub_source::{GcpPubSubSource, GcpPubSubSourceFactory};
 #[cfg(feature = "kafka")]
@@ -89,6 +92,7 @@ pub use kinesis::kinesis_source::{KinesisSource, KinesisSourceFactory};
 use once_cell::sync::OnceCell;
 #[cfg(feature = "pulsar")]
 pub use pulsar_source::{PulsarSource, PulsarSourceFactory};
+pub use push_source::{PushSource, PushSourceFactory};
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox};
 use quickwit_common::pubsub::EventBroker;
 use quickwit_common::runtimes::RuntimeType;
@@ -384,6 +388,7 @@ pub fn quickwit_supported_sources() -> &'static SourceLoader {
     SOURCE_LOADER.get_or_init(|| {
         let mut source_factory = SourceLoader::default();
         source_factory.add_source("file", FileSourceFactory);
+        source_factory.add_source("file_tail", FileTailSourceFactory);
         #[cfg(feature = "gcp-pubsub")]
         source_factory.add_source("gcp_pubsub", GcpPubSubSourceFactory);
         source_factory.add_source("ingest-api", IngestApiSourceFactory);
@@ -394,6 +399,7 @@ pub fn quickwit_supported_sources() -> &'static SourceLoader {
         source_factory.add_source("kinesis", KinesisSourceFactory);
         #[cfg(feature = "pulsar")]
         source_factory.add_source("pulsar", PulsarSourceFactory);
+        source_factory.add_source("push", PushSourceFactory);
         source_factory.add_source("vec", VecSourceFactory);
         source_factory.add_source("void", VoidSourceFactory);
         source_factory
@@ -0,0 +1,494 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{bail, Context};
+use async_trait::async_trait;
+use bytes::Bytes;
+use bytesize::ByteSize;
+use quickwit_actors::{ActorExitStatus, Mailbox};
+use quickwit_config::PushSourceParams;
+use quickwit_doc_mapper::JsonObject;
+use quickwit_metastore::checkpoint::{PartitionId, SourceCheckpoint};
+use quickwit_proto::types::Position;
+use serde::Serialize;
+use serde_json::json;
+use thiserror::Error;
+use tokio::sync::{mpsc, oneshot};
+use tokio::time;
+use tracing::info;
+use warp::http::StatusCode;
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+use crate::actors::DocProcessor;
+use crate::source::{
+    BatchBuilder, Source, SourceContext, SourceRuntimeArgs, TypedSourceFactory,
+    BATCH_NUM_BYTES_LIMIT, EMIT_BATCHES_TIMEOUT,
+};
+
+/// Content type of the requests pushing documents to the source.
+const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+/// Maximum size of the body of a request.
+const CONTENT_LENGTH_LIMIT: u64 = ByteSize::mib(10).as_u64();
+
+/// Number of requests accepted but not yet picked up by the source. Requests are rejected with a
+/// 429 status code beyond that, which happens when the indexing pipeline cannot keep up.
+const DOCS_CHANNEL_CAPACITY: usize = 10;
+
+#[derive(Debug, Error)]
+enum PushError {
+    #[error("index `{0}` is not served by this source")]
+    IndexNotFound(String),
+    #[error("{0}")]
+    InvalidArgument(String),
+    #[error("the indexing pipeline is busy, retry later")]
+    RateLimited,
+    #[error("the indexing pipeline is shutting down")]
+    Unavailable,
+}
+
+impl PushError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            PushError::IndexNotFound(_) => StatusCode::NOT_FOUND,
+            PushError::InvalidArgument(_) => StatusCode::BAD_REQUEST,
+            PushError::RateLimited => StatusCode::TOO_MANY_REQUESTS,
+            PushError::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct PushHandlerContext {
+    index_id: String,
+    max_batch_size_in_docs_opt: Option<NonZeroUsize>,
+    docs_tx: mpsc::Sender<Vec<Bytes>>,
+}
+
+/// Accepts the documents pushed as NDJSON on `POST /api/v1/{index_id}/ingest`.
+fn push_filter(
+    handler_ctx: PushHandlerContext,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("api" / "v1" / String / "ingest")
+        .and(warp::post())
+        .and(warp::header::exact_ignore_case(
+            "content-type",
+            NDJSON_CONTENT_TYPE,
+        ))
+        .and(warp::body::content_length_limit(CONTENT_LENGTH_LIMIT))
+        .and(warp::body::bytes())
+        .map(move |index_id: String, body: Bytes| {
+            let (status_code, response_json) = match push_docs(&handler_ctx, &index_id, body) {
+                Ok(num_docs) => (
+                    StatusCode::OK,
+                    json!({ "num_docs_for_processing": num_docs }),
+                ),
+                Err(error) => (error.status_code(), json!({ "message": error.to_string() })),
+            };
+            warp::reply::with_status(warp::reply::json(&response_json), status_code).into_response()
+        })
+}
+
+/// Checks that each line of the body holds a JSON object and hands the documents over to the
+/// source. Returns the number of documents.
+fn push_docs(
+    handler_ctx: &PushHandlerContext,
+    index_id: &str,
+    body: Bytes,
+) -> Result<usize, PushError> {
+    if index_id != handler_ctx.index_id {
+        return Err(PushError::IndexNotFound(index_id.to_string()));
+    }
+    let lines: Vec<&[u8]> = body
+        .split(|byte| *byte == b'\n')
+        .filter(|line| !line.iter().all(u8::is_ascii_whitespace))
+        .collect();
+    let num_docs = lines.len();
+
+    if let Some(max_batch_size_in_docs) = handler_ctx.max_batch_size_in_docs_opt {
+        if num_docs > max_batch_size_in_docs.get() {
+            return Err(PushError::InvalidArgument(format!(
+                "request contains {num_docs} documents, exceeding the limit of \
+                 {max_batch_size_in_docs} documents per request"
+            )));
+        }
+    }
+    let mut docs = Vec::with_capacity(num_docs);
+
+    for (line_idx, line) in lines.into_iter().enumerate() {
+        if serde_json::from_slice::<JsonObject>(line).is_err() {
+            return Err(PushError::InvalidArgument(format!(
+                "document #{line_idx} is not a valid JSON object"
+            )));
+        }
+        docs.push(body.slice_ref(line));
+    }
+    if docs.is_empty() {
+        return Ok(0);
+    }
+    handler_ctx
+        .docs_tx
+        .try_send(docs)
+        .map_err(|error| match error {
+            mpsc::error::TrySendError::Full(_) => PushError::RateLimited,
+            mpsc::error::TrySendError::Closed(_) => PushError::Unavailable,
+        })?;
+    Ok(num_docs)
+}
+
+/// Converts the number of documents received so far into a position, and back.
+fn position_from_offset(offset: u64) -> Position {
+    if offset == 0 {
+        return Position::Beginning;
+    }
+    Position::offset(offset)
+}
+
+fn offset_from_position(position: &Position) -> anyhow::Result<u64> {
+    if *position == Position::Beginning {
+        return Ok(0);
+    }
+    let Some(offset) = position.as_u64() else {
+        bail!("unexpected push source position `{position:?}`");
+    };
+    Ok(offset)
+}
+
+#[derive(Default, Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct PushSourceCounters {
+    /// Number of documents received since the source was created, including the documents
+    /// indexed by previous runs of the source.
+    pub current_offset: u64,
+    pub num_docs_processed: u64,
+}
+
+/// Source indexing the documents pushed over HTTP to the address it listens on.
+///
+/// The documents are not persisted: the documents received but not yet published when the
+/// indexing pipeline restarts are lost. The clients should use the ingest API when they need
+/// durability.
+pub struct PushSource {
+    source_id: String,
+    partition_id: PartitionId,
+    local_addr: SocketAddr,
+    counters: PushSourceCounters,
+    docs_rx: mpsc::Receiver<Vec<Bytes>>,
+    // Dropping the sender shuts the HTTP server down.
+    _shutdown_tx: oneshot::Sender<()>,
+}
+
+impl fmt::Debug for PushSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PushSource {{ source_id: {} }}", self.source_id)
+    }
+}
+
+impl PushSource {
+    /// Address the HTTP server of the source is bound to.
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+#[async_trait]
+impl Source for PushSource {
+    async fn emit_batches(
+        &mut self,
+        doc_processor_mailbox: &Mailbox<DocProcessor>,
+        ctx: &SourceContext,
+    ) -> Result<Duration, ActorExitStatus> {
+        let mut batch_builder = BatchBuilder::default();
+        let deadline = time::sleep(EMIT_BATCHES_TIMEOUT);
+        tokio::pin!(deadline);
+
+        while batch_builder.num_bytes < BATCH_NUM_BYTES_LIMIT {
+            tokio::select! {
+                docs_opt = self.docs_rx.recv() => {
+                    let Some(docs) = docs_opt else {
+                        return Err(anyhow::anyhow!("push source server stopped").into());
+                    };
+                    for doc in docs {
+                        batch_builder.add_doc(doc);
+                    }
+                }
+                _ = &mut deadline => {
+                    break;
+                }
+            }
+            ctx.record_progress();
+        }
+        if !batch_builder.docs.is_empty() {
+            let previous_offset = self.counters.current_offset;
+            let num_docs = batch_builder.docs.len() as u64;
+            self.counters.current_offset += num_docs;
+            self.counters.num_docs_processed += num_docs;
+            batch_builder
+                .checkpoint_delta
+                .record_partition_delta(
+                    self.partition_id.clone(),
+                    position_from_offset(previous_offset),
+                    position_from_offset(self.counters.current_offset),
+                )
+                .context("failed to record partition delta")?;
+            ctx.send_message(doc_processor_mailbox, batch_builder.build())
+                .await?;
+        }
+        Ok(Duration::default())
+    }
+
+    fn name(&self) -> String {
+        format!("PushSource{{source_id={}}}", self.source_id)
+    }
+
+    fn observable_state(&self) -> serde_json::Value {
+        serde_json::to_value(&self.counters).unwrap()
+    }
+}
+
+pub struct PushSourceFactory;
+
+#[async_trait]
+impl TypedSourceFactory for PushSourceFactory {
+    type Source = PushSource;
+    type Params = PushSourceParams;
+
+    async fn typed_create_source(
+        ctx: Arc<SourceRuntimeArgs>,
+        params: PushSourceParams,
+        checkpoint: SourceCheckpoint,
+    ) -> anyhow::Result<PushSource> {
+        let partition_id = PartitionId::from(ctx.source_id());
+        let current_offset = checkpoint
+            .position_for_partition(&partition_id)
+            .map(offset_from_position)
+            .transpose()?
+            .unwrap_or_default();
+
+        let (docs_tx, docs_rx) = mpsc::channel(DOCS_CHANNEL_CAPACITY);
+        let handler_ctx = PushHandlerContext {
+            index_id: ctx.index_id().to_string(),
+            max_batch_size_in_docs_opt: params.max_batch_size_in_docs,
+            docs_tx,
+        };
+        let (shutdown_tx, shutdown_rx) = oneshot::channel::<()>();
+        let shutdown_signal = async move {
+            let _ = shutdown_rx.await;
+        };
+        let (local_addr, server) = warp::serve(push_filter(handler_ctx))
+            .try_bind_with_graceful_shutdown(params.bind_addr, shutdown_signal)
+            .with_context(|| {
+                format!(
+                    "failed to bind push source `{}` to `{}`",
+                    ctx.source_id(),
+                    params.bind_addr
+                )
+            })?;
+        tokio::spawn(server);
+        info!(source_id=%ctx.source_id(), local_addr=%local_addr, "push-source-listening");
+
+        Ok(PushSource {
+            source_id: ctx.source_id().to_string(),
+            partition_id,
+            local_addr,
+            counters: PushSourceCounters {
+                current_offset,
+                num_docs_processed: 0,
+            },
+            docs_rx,
+            _shutdown_tx: shutdown_tx,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use quickwit_actors::Universe;
+    use quickwit_config::{SourceConfig, SourceInputFormat, SourceParams};
+    use quickwit_metastore::checkpoint::SourceCheckpointDelta;
+    use quickwit_metastore::metastore_for_test;
+    use quickwit_proto::types::IndexUid;
+
+    use super::*;
+    use crate::models::RawDocBatch;
+    use crate::source::SourceActor;
+
+    fn handler_ctx_for_test(
+        max_batch_size_in_docs_opt: Option<NonZeroUsize>,
+        channel_capacity: usize,
+    ) -> (PushHandlerContext, mpsc::Receiver<Vec<Bytes>>) {
+        let (docs_tx, docs_rx) = mpsc::channel(channel_capacity);
+        let handler_ctx = PushHandlerContext {
+            index_id: "test-index".to_string(),
+            max_batch_size_in_docs_opt,
+            docs_tx,
+        };
+        (handler_ctx, docs_rx)
+    }
+
+    fn push_request(index_id: &str, body: &str) -> warp::test::RequestBuilder {
+        warp::test::request()
+            .path(&format!("/api/v1/{index_id}/ingest"))
+            .method("POST")
+            .header("content-type", NDJSON_CONTENT_TYPE)
+            .body(body.to_string())
+    }
+
+    #[tokio::test]
+    async fn test_push_filter() {
+        let (handler_ctx, mut docs_rx) = handler_ctx_for_test(NonZeroUsize::new(2), 1);
+        let push_filter = push_filter(handler_ctx);
+
+        let resp = push_request("test-index", "{\"id\": 1}\n\n{\"id\": 2}\n")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json["num_docs_for_processing"], 2);
+
+        // The channel is full as long as the source does not pick up the documents.
+        let resp = push_request("test-index", "{\"id\": 3}")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::TOO_MANY_REQUESTS);
+
+        let docs = docs_rx.recv().await.unwrap();
+        assert_eq!(docs, ["{\"id\": 1}", "{\"id\": 2}"]);
+
+        let resp = push_request("test-index", "{\"id\": 3}")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp = push_request("test-index", "{\"id\": 4}\n{\"id\": 5}\n{\"id\": 6}")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert!(resp_json["message"]
+            .as_str()
+            .unwrap()
+            .contains("exceeding the limit of 2 documents per request"));
+
+        let resp = push_request("test-index", "{\"id\": 4}\n[5]")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        let resp = push_request("other-index", "{\"id\": 4}")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+        let resp = warp::test::request()
+            .path("/api/v1/test-index/ingest")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body("{\"id\": 4}")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+
+        drop(docs_rx);
+
+        let resp = push_request("test-index", "{\"id\": 4}")
+            .reply(&push_filter)
+            .await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    }
+
+    #[tokio::test]
+    async fn test_push_source() {
+        quickwit_common::setup_logging_for_tests();
+        let universe = Universe::with_accelerated_time();
+        let (doc_processor_mailbox, doc_processor_inbox) = universe.create_test_mailbox();
+        let params = PushSourceParams::new("127.0.0.1:0".parse().unwrap());
+        let source_config = SourceConfig {
+            source_id: "test-push-source".to_string(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::Push(params.clone()),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+        };
+        let partition_id = PartitionId::from("test-push-source");
+        let checkpoint =
+            SourceCheckpoint::from_iter(vec![(partition_id.clone(), Position::offset(3u64))]);
+        let push_source = PushSourceFactory::typed_create_source(
+            SourceRuntimeArgs::for_test(
+                IndexUid::new_with_random_ulid("test-index"),
+                source_config,
+                metastore_for_test(),
+                PathBuf::from("./queues"),
+            ),
+            params,
+            checkpoint,
+        )
+        .await
+        .unwrap();
+        let local_addr = push_source.local_addr();
+        let push_source_actor = SourceActor {
+            source: Box::new(push_source),
+            doc_processor_mailbox,
+        };
+        let (_source_mailbox, source_handle) = universe.spawn_builder().spawn(push_source_actor);
+
+        let response = reqwest::Client::new()
+            .post(format!("http://{local_addr}/api/v1/test-index/ingest"))
+            .header("content-type", NDJSON_CONTENT_TYPE)
+            .body("{\"id\": 1}\n{\"id\": 2}\n")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+        let batch = doc_processor_inbox
+            .recv_typed_message::<RawDocBatch>()
+            .await
+            .unwrap();
+        assert_eq!(batch.docs, ["{\"id\": 1}", "{\"id\": 2}"]);
+
+        let expected_checkpoint_delta = SourceCheckpointDelta::from_partition_delta(
+            partition_id,
+            Position::offset(3u64),
+            Position::offset(5u64),
+        )
+        .unwrap();
+        assert_eq!(batch.checkpoint_delta, expected_checkpoint_delta);
+
+        let counters = source_handle.process_pending_and_observe().await.state;
+        assert_eq!(
+            counters,
+            json!({
+                "current_offset": 5u64,
+                "num_docs_processed": 2u64,
+            })
+        );
+        universe.assert_quit().await;
+    }
+}
@@ -161,6 +161,8 @@ impl ClusterSandbox {
                         metastore_resolver,
                         storage_resolver,
                         shutdown_signal,
+                        Duration::from_secs(1),
+                        None,
                     )
                     .await?;
                     Result::<_, anyhow::Error>::Ok(result)
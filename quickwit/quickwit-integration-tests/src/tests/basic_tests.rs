@@ -176,7 +176,7 @@ async fn test_multi_nodes_cluster() {
               - name: body
                 type: text
             indexing_settings:
-              commit_timeout_secs: 1
+              commit_timeout: 1s
             "#,
             quickwit_config::ConfigFormat::Yaml,
             false,
@@ -48,7 +48,7 @@ async fn test_restarting_standalone_server() {
                 - name: body
                   type: text
             indexing_settings:
-                commit_timeout_secs: 1
+                commit_timeout: 1s
                 merge_policy:
                     type: stable_log
                     merge_factor: 3
@@ -220,7 +220,7 @@ const TEST_INDEX_CONFIG: &str = r#"
       - name: body
         type: text
     indexing_settings:
-      commit_timeout_secs: 1
+      commit_timeout: 1s
       merge_policy:
         type: stable_log
         merge_factor: 4
@@ -564,7 +564,7 @@ async fn test_shutdown() {
               - name: body
                 type: text
             indexing_settings:
-              commit_timeout_secs: 1
+              commit_timeout: 1s
             "#,
             ConfigFormat::Yaml,
             false,
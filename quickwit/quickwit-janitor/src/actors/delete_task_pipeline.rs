@@ -186,7 +186,13 @@ impl DeleteTaskPipeline {
         let doc_mapper =
             build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
         let tag_fields = doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("MergePackager", tag_fields, uploader_mailbox);
+        let geo_point_fields = doc_mapper.geo_point_field_names().into_iter().collect();
+        let packager = Packager::new(
+            "MergePackager",
+            tag_fields,
+            geo_point_fields,
+            uploader_mailbox,
+        );
         let (packager_mailbox, packager_supervisor_handler) = ctx.spawn_actor().supervise(packager);
         let index_pipeline_id = IndexingPipelineId {
             index_uid: self.index_uid.clone(),
@@ -321,6 +321,7 @@ impl DeleteTaskPlanner {
                 IndexMetasForLeafSearch {
                     doc_mapper_str: doc_mapper_str.to_string(),
                     index_uri,
+                    query_ast_opt: None,
                 },
             );
             let leaf_search_request = jobs_to_leaf_requests(
@@ -218,7 +218,8 @@ mod tests {
         SplitState,
     };
     use quickwit_proto::metastore::{
-        EmptyResponse, ListIndexesMetadataResponse, ListSplitsResponse, MetastoreError,
+        EmptyResponse, ListIndexSnapshotsResponse, ListIndexesMetadataResponse, ListSplitsResponse,
+        MetastoreError,
     };
     use quickwit_proto::types::IndexUid;
     use quickwit_storage::MockStorage;
@@ -296,6 +297,10 @@ mod tests {
                 let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
                 Ok(ServiceStream::from(vec![Ok(splits)]))
             });
+        mock_metastore
+            .expect_list_index_snapshots()
+            .times(1)
+            .returning(|_| Ok(ListIndexSnapshotsResponse::default()));
         mock_metastore
             .expect_mark_splits_for_deletion()
             .times(1)
@@ -373,6 +378,10 @@ mod tests {
                 let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
                 Ok(ServiceStream::from(vec![Ok(splits)]))
             });
+        mock_metastore
+            .expect_list_index_snapshots()
+            .times(1)
+            .returning(|_| Ok(ListIndexSnapshotsResponse::default()));
         mock_metastore
             .expect_mark_splits_for_deletion()
             .times(1)
@@ -449,6 +458,10 @@ mod tests {
                 let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
                 Ok(ServiceStream::from(vec![Ok(splits)]))
             });
+        mock_metastore
+            .expect_list_index_snapshots()
+            .times(3)
+            .returning(|_| Ok(ListIndexSnapshotsResponse::default()));
         mock_metastore
             .expect_mark_splits_for_deletion()
             .times(3)
@@ -625,6 +638,10 @@ mod tests {
                 let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
                 Ok(ServiceStream::from(vec![Ok(splits)]))
             });
+        mock_metastore
+            .expect_list_index_snapshots()
+            .once()
+            .returning(|_| Ok(ListIndexSnapshotsResponse::default()));
         mock_metastore
             .expect_mark_splits_for_deletion()
             .once()
@@ -701,6 +718,10 @@ mod tests {
                 let splits = ListSplitsResponse::try_from_splits(splits).unwrap();
                 Ok(ServiceStream::from(vec![Ok(splits)]))
             });
+        mock_metastore
+            .expect_list_index_snapshots()
+            .times(2)
+            .returning(|_| Ok(ListIndexSnapshotsResponse::default()));
         mock_metastore
             .expect_mark_splits_for_deletion()
             .times(2)
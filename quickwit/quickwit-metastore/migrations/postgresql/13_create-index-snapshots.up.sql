@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS index_snapshots (
+    index_uid VARCHAR(282) NOT NULL,
+    snapshot_id VARCHAR(50) NOT NULL,
+    create_timestamp TIMESTAMP NOT NULL DEFAULT (CURRENT_TIMESTAMP AT TIME ZONE 'UTC'),
+    split_ids TEXT[] NOT NULL,
+    index_checkpoint_json TEXT NOT NULL,
+    PRIMARY KEY (index_uid, snapshot_id),
+    FOREIGN KEY (index_uid) REFERENCES indexes (index_uid) ON DELETE CASCADE
+);
@@ -43,6 +43,8 @@ use std::ops::Range;
 
 pub use error::MetastoreResolverError;
 pub use metastore::control_plane_metastore::ControlPlaneMetastore;
+#[cfg(feature = "dynamodb")]
+pub use metastore::dynamodb_metastore::DynamoDbMetastore;
 pub use metastore::file_backed_metastore::FileBackedMetastore;
 pub(crate) use metastore::index_metadata::serialize::{IndexMetadataV0_6, VersionedIndexMetadata};
 #[cfg(feature = "postgres")]
@@ -51,7 +53,8 @@ pub use metastore::{
     file_backed_metastore, AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata,
     IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsQuery,
     ListSplitsRequestExt, ListSplitsResponseExt, MetastoreServiceExt,
-    MetastoreServiceStreamSplitsExt, PublishSplitsRequestExt, StageSplitsRequestExt,
+    MetastoreServiceStreamSplitsExt, PublishSplitsRequestExt, SplitMetadataPatch,
+    StageSplitsRequestExt, UpdateSplitMetadataRequestExt,
 };
 pub use metastore_factory::{MetastoreFactory, UnsupportedMetastore};
 pub use metastore_resolver::MetastoreResolver;
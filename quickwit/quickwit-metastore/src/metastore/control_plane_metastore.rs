@@ -24,15 +24,17 @@ use quickwit_common::uri::Uri;
 use quickwit_proto::control_plane::{ControlPlaneService, ControlPlaneServiceClient};
 use quickwit_proto::metastore::{
     AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
-    DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
-    IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
-    LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
-    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse,
-    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
-    MetastoreResult, MetastoreService, MetastoreServiceClient, MetastoreServiceStream,
-    OpenShardsRequest, OpenShardsResponse, PublishSplitsRequest, ResetSourceCheckpointRequest,
-    StageSplitsRequest, ToggleSourceRequest, UpdateSplitsDeleteOpstampRequest,
+    CreateIndexResponse, CreateIndexSnapshotRequest, DeleteIndexRequest,
+    DeleteIndexSnapshotRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
+    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse, IndexMetadataRequest,
+    IndexMetadataResponse, IndexSnapshot, LastDeleteOpstampRequest, LastDeleteOpstampResponse,
+    ListDeleteTasksRequest, ListDeleteTasksResponse, ListIndexSnapshotsRequest,
+    ListIndexSnapshotsResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
+    ListShardsRequest, ListShardsResponse, ListSplitsRequest, ListSplitsResponse,
+    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreResult, MetastoreService,
+    MetastoreServiceClient, MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse,
+    PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
+    UpdateSplitMetadataRequest, UpdateSplitsDeleteOpstampRequest,
     UpdateSplitsDeleteOpstampResponse,
 };
 
@@ -170,6 +172,13 @@ impl MetastoreService for ControlPlaneMetastore {
         self.metastore.delete_splits(request).await
     }
 
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.update_split_metadata(request).await
+    }
+
     async fn reset_source_checkpoint(
         &mut self,
         request: ResetSourceCheckpointRequest,
@@ -207,6 +216,29 @@ impl MetastoreService for ControlPlaneMetastore {
         self.metastore.list_delete_tasks(request).await
     }
 
+    // Index snapshot API
+
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> MetastoreResult<IndexSnapshot> {
+        self.metastore.create_index_snapshot(request).await
+    }
+
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> MetastoreResult<ListIndexSnapshotsResponse> {
+        self.metastore.list_index_snapshots(request).await
+    }
+
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        self.metastore.delete_index_snapshot(request).await
+    }
+
     // Shard API
 
     async fn open_shards(
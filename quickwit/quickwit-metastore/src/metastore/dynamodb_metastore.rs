@@ -0,0 +1,1764 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use aws_sdk_dynamodb::config::Region;
+use aws_sdk_dynamodb::error::{DisplayErrorContext, SdkError};
+use aws_sdk_dynamodb::operation::transact_write_items::TransactWriteItemsError;
+use aws_sdk_dynamodb::types::{
+    AttributeDefinition, AttributeValue, BillingMode, ConditionCheck, Delete, DeleteRequest,
+    KeySchemaElement, KeyType, KeysAndAttributes, Put, ScalarAttributeType, TableStatus,
+    TransactWriteItem, Update, WriteRequest,
+};
+use aws_sdk_dynamodb::{Client, Config};
+use itertools::Itertools;
+use quickwit_aws::{get_aws_config, DEFAULT_AWS_REGION};
+use quickwit_common::uri::Uri;
+use quickwit_common::{PrettySample, ServiceStream};
+use quickwit_config::{DynamoDbMetastoreConfig, MetastoreBackend, MetastoreConfig};
+use quickwit_proto::metastore::{
+    serde_utils, AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
+    CreateIndexResponse, CreateIndexSnapshotRequest, DeleteIndexRequest,
+    DeleteIndexSnapshotRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
+    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse, EntityKind,
+    IndexMetadataRequest, IndexMetadataResponse, IndexSnapshot, LastDeleteOpstampRequest,
+    LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
+    ListIndexSnapshotsRequest, ListIndexSnapshotsResponse, ListIndexesMetadataRequest,
+    ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse, ListSplitsRequest,
+    ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError,
+    MetastoreResult, MetastoreService, MetastoreServiceClient, MetastoreServiceStream,
+    OpenShardsRequest, OpenShardsResponse, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    StageSplitsRequest, ToggleSourceRequest, UpdateSplitMetadataRequest,
+    UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
+};
+use quickwit_proto::types::IndexUid;
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use tracing::{debug, info, instrument, warn};
+use ulid::Ulid;
+
+use super::STREAM_SPLITS_CHUNK_SIZE;
+use crate::checkpoint::IndexCheckpointDelta;
+use crate::metastore::dynamodb_model::*;
+use crate::metastore::file_backed_metastore::build_regex_set_from_patterns;
+use crate::metastore::file_backed_metastore::file_backed_index::split_query_predicate;
+use crate::metastore::{instrument_metastore, PublishSplitsRequestExt};
+use crate::{
+    AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
+    ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt, ListSplitsResponseExt,
+    MetastoreFactory, MetastoreResolverError, MetastoreServiceExt, Split, SplitState,
+    StageSplitsRequestExt, UpdateSplitMetadataRequestExt,
+};
+
+/// Maximum number of actions in a single `TransactWriteItems` request.
+const MAX_TRANSACTION_ITEMS: usize = 100;
+
+/// Maximum number of keys in a single `BatchGetItem` request.
+const MAX_BATCH_GET_ITEMS: usize = 100;
+
+/// Maximum number of requests in a single `BatchWriteItem` request.
+const MAX_BATCH_WRITE_ITEMS: usize = 25;
+
+/// Number of times an operation is attempted when its transaction is cancelled because of
+/// concurrent writes.
+const MAX_TRANSACTION_ATTEMPTS: usize = 5;
+
+/// Number of times the table status is polled while waiting for the table to become active.
+const MAX_TABLE_STATUS_POLLS: usize = 60;
+
+fn convert_sdk_error<E>(error: SdkError<E>) -> MetastoreError
+where E: std::error::Error + Send + Sync + 'static {
+    let message = DisplayErrorContext(&error).to_string();
+    match error {
+        SdkError::DispatchFailure(_) | SdkError::TimeoutError(_) => {
+            MetastoreError::Connection { message }
+        }
+        _ => MetastoreError::Db { message },
+    }
+}
+
+fn too_many_conflicts_error() -> MetastoreError {
+    MetastoreError::Db {
+        message: format!(
+            "transaction was cancelled {MAX_TRANSACTION_ATTEMPTS} times in a row because of \
+             concurrent writes"
+        ),
+    }
+}
+
+fn index_not_found_error(index_uid: &IndexUid) -> MetastoreError {
+    MetastoreError::NotFound(EntityKind::Index {
+        index_id: index_uid.index_id().to_string(),
+    })
+}
+
+fn now_timestamp() -> i64 {
+    OffsetDateTime::now_utc().unix_timestamp()
+}
+
+/// Extracts the table name from a URI of the form `dynamodb://table-name`.
+fn parse_table_name(uri: &Uri) -> MetastoreResult<String> {
+    let table_name = uri
+        .as_str()
+        .strip_prefix("dynamodb://")
+        .unwrap_or_default()
+        .trim_end_matches('/');
+    if table_name.is_empty() || table_name.contains('/') {
+        return Err(MetastoreError::InvalidArgument {
+            message: format!(
+                "invalid DynamoDB metastore URI `{uri}`: expected `dynamodb://table-name`"
+            ),
+        });
+    }
+    Ok(table_name.to_string())
+}
+
+async fn get_dynamodb_client(dynamodb_metastore_config: &DynamoDbMetastoreConfig) -> Client {
+    let aws_config = get_aws_config().await;
+
+    let mut dynamodb_config = Config::builder();
+    dynamodb_config.set_retry_config(aws_config.retry_config().cloned());
+    dynamodb_config.set_credentials_provider(aws_config.credentials_provider().cloned());
+    dynamodb_config.set_http_connector(aws_config.http_connector().cloned());
+    dynamodb_config.set_timeout_config(aws_config.timeout_config().cloned());
+    dynamodb_config.set_credentials_cache(aws_config.credentials_cache().cloned());
+    dynamodb_config.set_sleep_impl(Some(Arc::new(quickwit_aws::TokioSleep::default())));
+
+    let region = dynamodb_metastore_config
+        .region
+        .clone()
+        .map(Region::new)
+        .or_else(|| aws_config.region().cloned())
+        .unwrap_or(DEFAULT_AWS_REGION);
+    dynamodb_config = dynamodb_config.region(Some(region));
+
+    if let Some(endpoint) = &dynamodb_metastore_config.endpoint {
+        dynamodb_config = dynamodb_config.endpoint_url(endpoint);
+    }
+    Client::from_conf(dynamodb_config.build())
+}
+
+/// Creates the metastore table if it does not exist and waits for it to become active.
+#[instrument(skip(client))]
+async fn create_table_if_not_exists(client: &Client, table_name: &str) -> MetastoreResult<()> {
+    match client.describe_table().table_name(table_name).send().await {
+        Ok(_) => {}
+        Err(SdkError::ServiceError(service_error))
+            if service_error.err().is_resource_not_found_exception() =>
+        {
+            info!("creating DynamoDB metastore table");
+            let create_table_res = client
+                .create_table()
+                .table_name(table_name)
+                .key_schema(
+                    KeySchemaElement::builder()
+                        .attribute_name(PARTITION_KEY)
+                        .key_type(KeyType::Hash)
+                        .build(),
+                )
+                .key_schema(
+                    KeySchemaElement::builder()
+                        .attribute_name(SORT_KEY)
+                        .key_type(KeyType::Range)
+                        .build(),
+                )
+                .attribute_definitions(
+                    AttributeDefinition::builder()
+                        .attribute_name(PARTITION_KEY)
+                        .attribute_type(ScalarAttributeType::S)
+                        .build(),
+                )
+                .attribute_definitions(
+                    AttributeDefinition::builder()
+                        .attribute_name(SORT_KEY)
+                        .attribute_type(ScalarAttributeType::S)
+                        .build(),
+                )
+                .billing_mode(BillingMode::PayPerRequest)
+                .send()
+                .await;
+            match create_table_res {
+                Ok(_) => {}
+                // Another node created the table concurrently.
+                Err(SdkError::ServiceError(service_error))
+                    if service_error.err().is_resource_in_use_exception() => {}
+                Err(error) => return Err(convert_sdk_error(error)),
+            }
+        }
+        Err(error) => return Err(convert_sdk_error(error)),
+    }
+    for _ in 0..MAX_TABLE_STATUS_POLLS {
+        let describe_table_output = client
+            .describe_table()
+            .table_name(table_name)
+            .send()
+            .await
+            .map_err(convert_sdk_error)?;
+        let table_status_opt = describe_table_output
+            .table()
+            .and_then(|table| table.table_status());
+        if table_status_opt == Some(&TableStatus::Active) {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    Err(MetastoreError::Unavailable(format!(
+        "DynamoDB table `{table_name}` did not become active"
+    )))
+}
+
+/// DynamoDB metastore implementation.
+///
+/// All the items of an index are stored in the same partition, keyed by the index ID. Writes that
+/// touch several items are performed with `TransactWriteItems` and guarded by condition
+/// expressions, so that, for instance, a published split can never be overwritten by a staged one.
+/// Writes to the index metadata item are serialized with a version attribute.
+#[derive(Clone)]
+pub struct DynamoDbMetastore {
+    uri: Uri,
+    table_name: String,
+    client: Client,
+}
+
+impl fmt::Debug for DynamoDbMetastore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DynamoDbMetastore")
+            .field("uri", &self.uri)
+            .finish()
+    }
+}
+
+impl DynamoDbMetastore {
+    /// Creates a metastore given a `dynamodb://table-name` URI. The table is created if it does
+    /// not exist.
+    pub async fn new(
+        dynamodb_metastore_config: &DynamoDbMetastoreConfig,
+        uri: &Uri,
+    ) -> MetastoreResult<Self> {
+        let table_name = parse_table_name(uri)?;
+        let client = get_dynamodb_client(dynamodb_metastore_config).await;
+        create_table_if_not_exists(&client, &table_name).await?;
+        Ok(DynamoDbMetastore {
+            uri: uri.clone(),
+            table_name,
+            client,
+        })
+    }
+
+    async fn get_item(&self, index_id: &str, sort_key: String) -> MetastoreResult<Option<Item>> {
+        let get_item_output = self
+            .client
+            .get_item()
+            .table_name(&self.table_name)
+            .set_key(Some(primary_key(index_id, sort_key)))
+            .consistent_read(true)
+            .send()
+            .await
+            .map_err(convert_sdk_error)?;
+        Ok(get_item_output.item)
+    }
+
+    /// Returns the index item for `index_id` or `None` if it does not exist.
+    async fn index_opt(&self, index_id: &str) -> MetastoreResult<Option<DynamoDbIndex>> {
+        self.get_item(index_id, INDEX_METADATA_SORT_KEY.to_string())
+            .await?
+            .as_ref()
+            .map(DynamoDbIndex::try_from)
+            .transpose()
+    }
+
+    /// Returns the index item for `index_uid` or `None` if it does not exist.
+    async fn index_opt_for_uid(
+        &self,
+        index_uid: &IndexUid,
+    ) -> MetastoreResult<Option<DynamoDbIndex>> {
+        let index_opt = self
+            .index_opt(index_uid.index_id())
+            .await?
+            .filter(|index| index.index_uid == *index_uid);
+        Ok(index_opt)
+    }
+
+    /// Returns the index item for `index_uid` or a `NotFound` error if it does not exist.
+    async fn index_for_uid(&self, index_uid: &IndexUid) -> MetastoreResult<DynamoDbIndex> {
+        self.index_opt_for_uid(index_uid)
+            .await?
+            .ok_or_else(|| index_not_found_error(index_uid))
+    }
+
+    /// Returns the items of the index `index_uid` whose sort key starts with `sort_key_prefix`.
+    async fn query_items(
+        &self,
+        index_uid: &IndexUid,
+        sort_key_prefix_opt: Option<&str>,
+    ) -> MetastoreResult<Vec<Item>> {
+        let key_condition_expression = if sort_key_prefix_opt.is_some() {
+            "pk = :pk AND begins_with(sk, :sort_key_prefix)"
+        } else {
+            "pk = :pk"
+        };
+        let mut items = Vec::new();
+        let mut exclusive_start_key_opt: Option<Item> = None;
+
+        loop {
+            let mut query = self
+                .client
+                .query()
+                .table_name(&self.table_name)
+                .key_condition_expression(key_condition_expression)
+                .filter_expression("index_uid = :index_uid")
+                .expression_attribute_values(":pk", attr_s(index_uid.index_id()))
+                .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+                .consistent_read(true)
+                .set_exclusive_start_key(exclusive_start_key_opt);
+            if let Some(sort_key_prefix) = sort_key_prefix_opt {
+                query =
+                    query.expression_attribute_values(":sort_key_prefix", attr_s(sort_key_prefix));
+            }
+            let query_output = query.send().await.map_err(convert_sdk_error)?;
+            items.extend(query_output.items.unwrap_or_default());
+
+            exclusive_start_key_opt = query_output.last_evaluated_key;
+            if exclusive_start_key_opt.is_none() {
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Returns all the splits of the index `index_uid`, ordered by split ID.
+    async fn query_splits(&self, index_uid: &IndexUid) -> MetastoreResult<Vec<Split>> {
+        self.query_items(index_uid, None)
+            .await?
+            .iter()
+            .filter(|item| is_split_item(item))
+            .map(split_from_item)
+            .collect()
+    }
+
+    /// Fetches the split items `split_ids` of the index `index_id`, regardless of the index
+    /// incarnation they belong to.
+    async fn batch_get_split_items(
+        &self,
+        index_id: &str,
+        split_ids: &[String],
+    ) -> MetastoreResult<Vec<Item>> {
+        let mut items = Vec::with_capacity(split_ids.len());
+
+        for split_ids_chunk in &split_ids.iter().unique().chunks(MAX_BATCH_GET_ITEMS) {
+            let keys: Vec<Item> = split_ids_chunk
+                .map(|split_id| primary_key(index_id, split_id.clone()))
+                .collect();
+            let keys_and_attributes = KeysAndAttributes::builder()
+                .set_keys(Some(keys))
+                .consistent_read(true)
+                .build();
+            let mut request_items =
+                HashMap::from_iter([(self.table_name.clone(), keys_and_attributes)]);
+
+            while !request_items.is_empty() {
+                let batch_get_item_output = self
+                    .client
+                    .batch_get_item()
+                    .set_request_items(Some(request_items))
+                    .send()
+                    .await
+                    .map_err(convert_sdk_error)?;
+
+                if let Some(mut responses) = batch_get_item_output.responses {
+                    items.extend(responses.remove(&self.table_name).unwrap_or_default());
+                }
+                request_items = batch_get_item_output.unprocessed_keys.unwrap_or_default();
+                request_items.retain(|_, keys_and_attributes| {
+                    keys_and_attributes
+                        .keys()
+                        .map_or(false, |keys| !keys.is_empty())
+                });
+                if !request_items.is_empty() {
+                    // DynamoDB returns unprocessed keys when the request is throttled.
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+        Ok(items)
+    }
+
+    /// Fetches the splits `split_ids` of the index `index_uid`. Splits that do not exist are
+    /// missing from the returned map.
+    async fn batch_get_splits(
+        &self,
+        index_uid: &IndexUid,
+        split_ids: &[String],
+    ) -> MetastoreResult<HashMap<String, Split>> {
+        let index_uid_str = index_uid.to_string();
+        let mut splits = HashMap::with_capacity(split_ids.len());
+
+        for item in self
+            .batch_get_split_items(index_uid.index_id(), split_ids)
+            .await?
+        {
+            if get_string(&item, INDEX_UID)? != index_uid_str {
+                continue;
+            }
+            let split = split_from_item(&item)?;
+            splits.insert(split.split_id().to_string(), split);
+        }
+        Ok(splits)
+    }
+
+    /// Deletes the items with the given primary keys.
+    async fn batch_delete_items(&self, keys: Vec<Item>) -> MetastoreResult<()> {
+        for keys_chunk in &keys.into_iter().chunks(MAX_BATCH_WRITE_ITEMS) {
+            let write_requests: Vec<WriteRequest> = keys_chunk
+                .map(|key| {
+                    WriteRequest::builder()
+                        .delete_request(DeleteRequest::builder().set_key(Some(key)).build())
+                        .build()
+                })
+                .collect();
+            let mut request_items = HashMap::from_iter([(self.table_name.clone(), write_requests)]);
+
+            while !request_items.is_empty() {
+                let batch_write_item_output = self
+                    .client
+                    .batch_write_item()
+                    .set_request_items(Some(request_items))
+                    .send()
+                    .await
+                    .map_err(convert_sdk_error)?;
+
+                request_items = batch_write_item_output
+                    .unprocessed_items
+                    .unwrap_or_default();
+                request_items.retain(|_, write_requests| !write_requests.is_empty());
+
+                if !request_items.is_empty() {
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Executes the write actions `items` in a single transaction. Returns `false` if the
+    /// transaction was cancelled because a condition failed or a concurrent transaction touched
+    /// the same items. In that case, callers re-read the state they depend on and try again.
+    async fn transact_write_items(&self, items: Vec<TransactWriteItem>) -> MetastoreResult<bool> {
+        debug_assert!(items.len() <= MAX_TRANSACTION_ITEMS);
+
+        let error = match self
+            .client
+            .transact_write_items()
+            .set_transact_items(Some(items))
+            .send()
+            .await
+        {
+            Ok(_) => return Ok(true),
+            Err(error) => error,
+        };
+        if let SdkError::ServiceError(service_error) = &error {
+            if let TransactWriteItemsError::TransactionCanceledException(exception) =
+                service_error.err()
+            {
+                let is_retryable = exception
+                    .cancellation_reasons()
+                    .unwrap_or_default()
+                    .iter()
+                    .all(|reason| {
+                        matches!(
+                            reason.code(),
+                            None | Some("None")
+                                | Some("ConditionalCheckFailed")
+                                | Some("TransactionConflict")
+                        )
+                    });
+                if is_retryable {
+                    debug!("transaction cancelled");
+                    return Ok(false);
+                }
+            }
+        }
+        Err(convert_sdk_error(error))
+    }
+
+    /// Returns a write action that checks that the index `index_uid` exists.
+    fn index_exists_condition_check(&self, index_uid: &IndexUid) -> TransactWriteItem {
+        let condition_check = ConditionCheck::builder()
+            .table_name(&self.table_name)
+            .set_key(Some(primary_key(
+                index_uid.index_id(),
+                INDEX_METADATA_SORT_KEY,
+            )))
+            .condition_expression("index_uid = :index_uid")
+            .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+            .build();
+        TransactWriteItem::builder()
+            .condition_check(condition_check)
+            .build()
+    }
+
+    /// Returns a write action that checks that the index item has not been modified since
+    /// `index` was read.
+    fn index_unchanged_condition_check(&self, index: &DynamoDbIndex) -> TransactWriteItem {
+        let condition_check = ConditionCheck::builder()
+            .table_name(&self.table_name)
+            .set_key(Some(primary_key(
+                index.index_uid.index_id(),
+                INDEX_METADATA_SORT_KEY,
+            )))
+            .condition_expression("metadata_version = :version")
+            .expression_attribute_values(":version", attr_n(index.version))
+            .build();
+        TransactWriteItem::builder()
+            .condition_check(condition_check)
+            .build()
+    }
+
+    /// Returns a write action that replaces the metadata of the index read as `index` with
+    /// `index_metadata`, provided the index item has not been modified in the meantime.
+    fn update_index_metadata_item(
+        &self,
+        index: &DynamoDbIndex,
+        index_metadata: &IndexMetadata,
+        now: i64,
+    ) -> MetastoreResult<TransactWriteItem> {
+        let index_metadata_json = serde_json::to_string(index_metadata).map_err(|error| {
+            MetastoreError::JsonSerializeError {
+                struct_name: "IndexMetadata".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        let update = Update::builder()
+            .table_name(&self.table_name)
+            .set_key(Some(primary_key(
+                index.index_uid.index_id(),
+                INDEX_METADATA_SORT_KEY,
+            )))
+            .update_expression(
+                "SET index_metadata_json = :index_metadata_json, update_timestamp = :now, \
+                 metadata_version = :next_version",
+            )
+            .condition_expression("metadata_version = :version")
+            .expression_attribute_values(":index_metadata_json", attr_s(index_metadata_json))
+            .expression_attribute_values(":now", attr_n(now))
+            .expression_attribute_values(":version", attr_n(index.version))
+            .expression_attribute_values(":next_version", attr_n(index.version + 1))
+            .build();
+        Ok(TransactWriteItem::builder().update(update).build())
+    }
+
+    /// Applies `mutate_fn` to the metadata of the index `index_uid` and writes it back. Returns
+    /// whether a mutation occurred.
+    async fn mutate_index_metadata<E, M>(
+        &self,
+        index_uid: &IndexUid,
+        mutate_fn: M,
+    ) -> MetastoreResult<bool>
+    where
+        MetastoreError: From<E>,
+        M: Fn(&mut IndexMetadata) -> Result<bool, E>,
+    {
+        for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let index = self.index_for_uid(index_uid).await?;
+            let mut index_metadata = index.index_metadata()?;
+
+            if !mutate_fn(&mut index_metadata)? {
+                return Ok(false);
+            }
+            let items =
+                vec![self.update_index_metadata_item(&index, &index_metadata, now_timestamp())?];
+
+            if self.transact_write_items(items).await? {
+                return Ok(true);
+            }
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    /// Lists the splits matching `query`.
+    async fn inner_list_splits(&self, query: &ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
+        let mut splits = Vec::new();
+
+        for index_uid in &query.index_uids {
+            let index_splits = self.query_splits(index_uid).await?;
+            splits.extend(
+                index_splits
+                    .into_iter()
+                    .filter(|split| split_query_predicate(&split, query)),
+            );
+        }
+        if query.sort_by_staleness {
+            splits.sort_unstable_by(|left_split, right_split| {
+                left_split
+                    .split_metadata
+                    .delete_opstamp
+                    .cmp(&right_split.split_metadata.delete_opstamp)
+                    .then_with(|| {
+                        left_split
+                            .publish_timestamp
+                            .cmp(&right_split.publish_timestamp)
+                    })
+            });
+        } else if query.offset.is_some() {
+            splits.sort_unstable_by(|left_split, right_split| {
+                left_split.split_id().cmp(right_split.split_id())
+            });
+        }
+        let offset = query.offset.unwrap_or_default();
+        let limit = query.limit.unwrap_or(usize::MAX);
+
+        let splits = splits.into_iter().skip(offset).take(limit).collect();
+        Ok(splits)
+    }
+}
+
+#[async_trait]
+impl MetastoreService for DynamoDbMetastore {
+    async fn check_connectivity(&mut self) -> anyhow::Result<()> {
+        self.client
+            .describe_table()
+            .table_name(&self.table_name)
+            .send()
+            .await?;
+        Ok(())
+    }
+
+    fn endpoints(&self) -> Vec<quickwit_common::uri::Uri> {
+        vec![self.uri.clone()]
+    }
+
+    /// Lists the indexes matching the patterns. This scans the whole table, which is acceptable
+    /// because indexes are listed infrequently.
+    #[instrument(skip(self))]
+    async fn list_indexes_metadata(
+        &mut self,
+        request: ListIndexesMetadataRequest,
+    ) -> MetastoreResult<ListIndexesMetadataResponse> {
+        let index_matcher =
+            build_regex_set_from_patterns(request.index_id_patterns).map_err(|error| {
+                MetastoreError::Internal {
+                    message: "failed to build RegexSet from index patterns`".to_string(),
+                    cause: error.to_string(),
+                }
+            })?;
+        let mut indexes_metadata = Vec::new();
+        let mut exclusive_start_key_opt: Option<Item> = None;
+
+        loop {
+            let scan_output = self
+                .client
+                .scan()
+                .table_name(&self.table_name)
+                .filter_expression("sk = :sk")
+                .expression_attribute_values(":sk", attr_s(INDEX_METADATA_SORT_KEY))
+                .consistent_read(true)
+                .set_exclusive_start_key(exclusive_start_key_opt)
+                .send()
+                .await
+                .map_err(convert_sdk_error)?;
+
+            for item in scan_output.items.unwrap_or_default() {
+                if !index_matcher.is_match(get_string(&item, PARTITION_KEY)?) {
+                    continue;
+                }
+                let index_metadata = DynamoDbIndex::try_from(&item)?.index_metadata()?;
+                indexes_metadata.push(index_metadata);
+            }
+            exclusive_start_key_opt = scan_output.last_evaluated_key;
+            if exclusive_start_key_opt.is_none() {
+                break;
+            }
+        }
+        let response = ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self))]
+    async fn create_index(
+        &mut self,
+        request: CreateIndexRequest,
+    ) -> MetastoreResult<CreateIndexResponse> {
+        let index_config = request.deserialize_index_config()?;
+        let index_metadata = IndexMetadata::new(index_config);
+        let index_metadata_json = serde_json::to_string(&index_metadata).map_err(|error| {
+            MetastoreError::JsonSerializeError {
+                struct_name: "IndexMetadata".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        let now = now_timestamp();
+        let mut item = primary_key(index_metadata.index_id(), INDEX_METADATA_SORT_KEY);
+        item.insert(
+            INDEX_UID.to_string(),
+            attr_s(index_metadata.index_uid.to_string()),
+        );
+        item.insert(INDEX_METADATA_JSON.to_string(), attr_s(index_metadata_json));
+        item.insert(CREATE_TIMESTAMP.to_string(), attr_n(now));
+        item.insert(UPDATE_TIMESTAMP.to_string(), attr_n(now));
+        item.insert(METADATA_VERSION.to_string(), attr_n(0));
+        item.insert(LAST_DELETE_OPSTAMP.to_string(), attr_n(0));
+
+        let put_item_res = self
+            .client
+            .put_item()
+            .table_name(&self.table_name)
+            .set_item(Some(item))
+            .condition_expression("attribute_not_exists(pk)")
+            .send()
+            .await;
+        match put_item_res {
+            Ok(_) => {}
+            Err(SdkError::ServiceError(service_error))
+                if service_error.err().is_conditional_check_failed_exception() =>
+            {
+                return Err(MetastoreError::AlreadyExists(EntityKind::Index {
+                    index_id: index_metadata.index_id().to_string(),
+                }));
+            }
+            Err(error) => return Err(convert_sdk_error(error)),
+        }
+        Ok(CreateIndexResponse {
+            index_uid: index_metadata.index_uid.to_string(),
+        })
+    }
+
+    #[instrument(skip_all, fields(index_id=request.index_uid))]
+    async fn delete_index(
+        &mut self,
+        request: DeleteIndexRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let delete_item_res = self
+            .client
+            .delete_item()
+            .table_name(&self.table_name)
+            .set_key(Some(primary_key(
+                index_uid.index_id(),
+                INDEX_METADATA_SORT_KEY,
+            )))
+            .condition_expression("index_uid = :index_uid")
+            .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+            .send()
+            .await;
+        match delete_item_res {
+            Ok(_) => {}
+            Err(SdkError::ServiceError(service_error))
+                if service_error.err().is_conditional_check_failed_exception() =>
+            {
+                return Err(index_not_found_error(&index_uid));
+            }
+            Err(error) => return Err(convert_sdk_error(error)),
+        }
+        // The index is gone: purge the splits, delete tasks, and snapshots it left
+        // behind. Items of a partially purged index are ignored because they carry a stale index
+        // UID.
+        let keys: Vec<Item> = self
+            .query_items(&index_uid, None)
+            .await?
+            .into_iter()
+            .filter_map(|mut item| {
+                let partition_key = item.remove(PARTITION_KEY)?;
+                let sort_key = item.remove(SORT_KEY)?;
+                Some(HashMap::from_iter([
+                    (PARTITION_KEY.to_string(), partition_key),
+                    (SORT_KEY.to_string(), sort_key),
+                ]))
+            })
+            .collect();
+        self.batch_delete_items(keys).await?;
+        Ok(EmptyResponse {})
+    }
+
+    #[instrument(skip_all, fields(split_ids))]
+    async fn stage_splits(
+        &mut self,
+        request: StageSplitsRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let split_metadata_list = request.deserialize_splits_metadata()?;
+        let index_uid: IndexUid = request.index_uid.into();
+        let split_ids: Vec<String> = split_metadata_list
+            .iter()
+            .map(|split_metadata| split_metadata.split_id.clone())
+            .collect();
+        tracing::Span::current().record("split_ids", format!("{split_ids:?}"));
+
+        // Staged splits are written in chunks, so staging a large number of splits is not atomic.
+        // This is harmless because staging is idempotent and staged splits are eventually
+        // garbage collected.
+        'attempts: for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            self.index_for_uid(&index_uid).await?;
+
+            let mut not_staged_split_ids = Vec::new();
+
+            for item in self
+                .batch_get_split_items(index_uid.index_id(), &split_ids)
+                .await?
+            {
+                if split_state(&item)? != SplitState::Staged {
+                    not_staged_split_ids.push(get_string(&item, SORT_KEY)?.to_string());
+                }
+            }
+            if !not_staged_split_ids.is_empty() {
+                let entity = EntityKind::Splits {
+                    split_ids: not_staged_split_ids,
+                };
+                let message = "splits are not staged".to_string();
+                return Err(MetastoreError::FailedPrecondition { entity, message });
+            }
+            let now = now_timestamp();
+
+            for split_metadata_chunk in split_metadata_list.chunks(MAX_TRANSACTION_ITEMS - 1) {
+                let mut items = Vec::with_capacity(split_metadata_chunk.len() + 1);
+                items.push(self.index_exists_condition_check(&index_uid));
+
+                for split_metadata in split_metadata_chunk {
+                    let mut item =
+                        primary_key(index_uid.index_id(), split_metadata.split_id.clone());
+                    item.insert(INDEX_UID.to_string(), attr_s(index_uid.to_string()));
+                    item.insert(SPLIT_STATE.to_string(), attr_s(SplitState::Staged.as_str()));
+                    item.insert(
+                        SPLIT_METADATA_JSON.to_string(),
+                        attr_s(split_metadata_json(split_metadata)?),
+                    );
+                    item.insert(CREATE_TIMESTAMP.to_string(), attr_n(now));
+                    item.insert(UPDATE_TIMESTAMP.to_string(), attr_n(now));
+                    item.insert(
+                        DELETE_OPSTAMP.to_string(),
+                        attr_n(split_metadata.delete_opstamp),
+                    );
+                    // A split can only be (re)staged if it does not exist or is still staged.
+                    let put = Put::builder()
+                        .table_name(&self.table_name)
+                        .set_item(Some(item))
+                        .condition_expression("attribute_not_exists(sk) OR split_state = :staged")
+                        .expression_attribute_values(":staged", attr_s(SplitState::Staged.as_str()))
+                        .build();
+                    items.push(TransactWriteItem::builder().put(put).build());
+                }
+                if !self.transact_write_items(items).await? {
+                    continue 'attempts;
+                }
+            }
+            debug!(index_id=%index_uid.index_id(), num_splits=split_ids.len(), "splits successfully staged");
+            return Ok(EmptyResponse {});
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    #[instrument(skip(self))]
+    async fn publish_splits(
+        &mut self,
+        request: PublishSplitsRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let checkpoint_delta_opt: Option<IndexCheckpointDelta> =
+            request.deserialize_index_checkpoint()?;
+        let index_uid: IndexUid = request.index_uid.into();
+        let staged_split_ids = request.staged_split_ids;
+        let replaced_split_ids = request.replaced_split_ids;
+
+        if 1 + staged_split_ids.len() + replaced_split_ids.len() > MAX_TRANSACTION_ITEMS {
+            return Err(MetastoreError::InvalidArgument {
+                message: format!(
+                    "cannot publish and replace more than {} splits at once",
+                    MAX_TRANSACTION_ITEMS - 1
+                ),
+            });
+        }
+        let split_ids: Vec<String> = staged_split_ids
+            .iter()
+            .chain(replaced_split_ids.iter())
+            .cloned()
+            .collect();
+
+        for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let index = self.index_for_uid(&index_uid).await?;
+            let mut index_metadata = index.index_metadata()?;
+
+            if let Some(checkpoint_delta) = checkpoint_delta_opt.clone() {
+                let source_id = checkpoint_delta.source_id.clone();
+                index_metadata
+                    .checkpoint
+                    .try_apply_delta(checkpoint_delta)
+                    .map_err(|error| {
+                        let entity = EntityKind::CheckpointDelta {
+                            index_id: index_uid.index_id().to_string(),
+                            source_id,
+                        };
+                        let message = error.to_string();
+                        MetastoreError::FailedPrecondition { entity, message }
+                    })?;
+            }
+            let splits = self.batch_get_splits(&index_uid, &split_ids).await?;
+
+            let not_found_split_ids: Vec<String> = split_ids
+                .iter()
+                .filter(|split_id| !splits.contains_key(*split_id))
+                .cloned()
+                .collect();
+            if !not_found_split_ids.is_empty() {
+                return Err(MetastoreError::NotFound(EntityKind::Splits {
+                    split_ids: not_found_split_ids,
+                }));
+            }
+            let not_staged_split_ids: Vec<String> = staged_split_ids
+                .iter()
+                .filter(|split_id| splits[*split_id].split_state != SplitState::Staged)
+                .cloned()
+                .collect();
+            if !not_staged_split_ids.is_empty() {
+                let entity = EntityKind::Splits {
+                    split_ids: not_staged_split_ids,
+                };
+                let message = "splits are not staged".to_string();
+                return Err(MetastoreError::FailedPrecondition { entity, message });
+            }
+            let not_marked_split_ids: Vec<String> = replaced_split_ids
+                .iter()
+                .filter(|split_id| splits[*split_id].split_state != SplitState::Published)
+                .cloned()
+                .collect();
+            if !not_marked_split_ids.is_empty() {
+                let entity = EntityKind::Splits {
+                    split_ids: not_marked_split_ids,
+                };
+                let message = "splits are not marked for deletion".to_string();
+                return Err(MetastoreError::FailedPrecondition { entity, message });
+            }
+            let now = now_timestamp();
+            let mut items = Vec::with_capacity(1 + split_ids.len());
+            items.push(self.update_index_metadata_item(&index, &index_metadata, now)?);
+
+            for split_id in &staged_split_ids {
+                let update = Update::builder()
+                    .table_name(&self.table_name)
+                    .set_key(Some(primary_key(index_uid.index_id(), split_id.clone())))
+                    .update_expression(
+                        "SET split_state = :published, update_timestamp = :now, publish_timestamp \
+                         = :now",
+                    )
+                    .condition_expression("index_uid = :index_uid AND split_state = :staged")
+                    .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+                    .expression_attribute_values(":staged", attr_s(SplitState::Staged.as_str()))
+                    .expression_attribute_values(
+                        ":published",
+                        attr_s(SplitState::Published.as_str()),
+                    )
+                    .expression_attribute_values(":now", attr_n(now))
+                    .build();
+                items.push(TransactWriteItem::builder().update(update).build());
+            }
+            for split_id in &replaced_split_ids {
+                let update = Update::builder()
+                    .table_name(&self.table_name)
+                    .set_key(Some(primary_key(index_uid.index_id(), split_id.clone())))
+                    .update_expression("SET split_state = :marked, update_timestamp = :now")
+                    .condition_expression("index_uid = :index_uid AND split_state = :published")
+                    .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+                    .expression_attribute_values(
+                        ":published",
+                        attr_s(SplitState::Published.as_str()),
+                    )
+                    .expression_attribute_values(
+                        ":marked",
+                        attr_s(SplitState::MarkedForDeletion.as_str()),
+                    )
+                    .expression_attribute_values(":now", attr_n(now))
+                    .build();
+                items.push(TransactWriteItem::builder().update(update).build());
+            }
+            if self.transact_write_items(items).await? {
+                info!(
+                    index_id=%index_uid.index_id(),
+                    "Published {} splits and marked {} splits for deletion successfully.",
+                    staged_split_ids.len(), replaced_split_ids.len()
+                );
+                return Ok(EmptyResponse {});
+            }
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_splits(
+        &mut self,
+        request: ListSplitsRequest,
+    ) -> MetastoreResult<MetastoreServiceStream<ListSplitsResponse>> {
+        let query = request.deserialize_list_splits_query()?;
+        let splits = self.inner_list_splits(&query).await?;
+        let splits_responses: Vec<MetastoreResult<ListSplitsResponse>> = splits
+            .chunks(STREAM_SPLITS_CHUNK_SIZE)
+            .map(|chunk| ListSplitsResponse::try_from_splits(chunk.to_vec()))
+            .collect();
+        let splits_responses_stream = Box::pin(futures::stream::iter(splits_responses));
+        Ok(ServiceStream::new(splits_responses_stream))
+    }
+
+    #[instrument(skip(self))]
+    async fn mark_splits_for_deletion(
+        &mut self,
+        request: MarkSplitsForDeletionRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let split_ids = request.split_ids;
+
+        'attempts: for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let splits = self.batch_get_splits(&index_uid, &split_ids).await?;
+
+            if splits.is_empty() && self.index_opt_for_uid(&index_uid).await?.is_none() {
+                return Err(index_not_found_error(&index_uid));
+            }
+            let split_ids_to_mark: Vec<&String> = splits
+                .iter()
+                .filter(|(_, split)| {
+                    matches!(
+                        split.split_state,
+                        SplitState::Staged | SplitState::Published
+                    )
+                })
+                .map(|(split_id, _)| split_id)
+                .collect();
+            let now = now_timestamp();
+
+            for split_ids_chunk in split_ids_to_mark.chunks(MAX_TRANSACTION_ITEMS) {
+                let items: Vec<TransactWriteItem> = split_ids_chunk
+                    .iter()
+                    .map(|split_id| {
+                        let update = Update::builder()
+                            .table_name(&self.table_name)
+                            .set_key(Some(primary_key(
+                                index_uid.index_id(),
+                                split_id.to_string(),
+                            )))
+                            .update_expression("SET split_state = :marked, update_timestamp = :now")
+                            .condition_expression(
+                                "index_uid = :index_uid AND split_state IN (:staged, :published)",
+                            )
+                            .expression_attribute_values(
+                                ":index_uid",
+                                attr_s(index_uid.to_string()),
+                            )
+                            .expression_attribute_values(
+                                ":staged",
+                                attr_s(SplitState::Staged.as_str()),
+                            )
+                            .expression_attribute_values(
+                                ":published",
+                                attr_s(SplitState::Published.as_str()),
+                            )
+                            .expression_attribute_values(
+                                ":marked",
+                                attr_s(SplitState::MarkedForDeletion.as_str()),
+                            )
+                            .expression_attribute_values(":now", attr_n(now))
+                            .build();
+                        TransactWriteItem::builder().update(update).build()
+                    })
+                    .collect();
+                if !self.transact_write_items(items).await? {
+                    continue 'attempts;
+                }
+            }
+            let not_found_split_ids: Vec<String> = split_ids
+                .iter()
+                .filter(|split_id| !splits.contains_key(*split_id))
+                .cloned()
+                .collect();
+            info!(
+                index_id=%index_uid.index_id(),
+                "Marked {} splits for deletion, among which {} were newly marked.",
+                split_ids.len() - not_found_split_ids.len(),
+                split_ids_to_mark.len()
+            );
+            if !not_found_split_ids.is_empty() {
+                warn!(
+                    index_id=%index_uid.index_id(),
+                    split_ids=?PrettySample::new(&not_found_split_ids, 5),
+                    "{} splits were not found and could not be marked for deletion.",
+                    not_found_split_ids.len()
+                );
+            }
+            return Ok(EmptyResponse {});
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_splits(
+        &mut self,
+        request: DeleteSplitsRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let split_ids = request.split_ids;
+
+        'attempts: for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let splits = self.batch_get_splits(&index_uid, &split_ids).await?;
+
+            if splits.is_empty() && self.index_opt_for_uid(&index_uid).await?.is_none() {
+                return Err(index_not_found_error(&index_uid));
+            }
+            let not_deletable_split_ids: Vec<String> = split_ids
+                .iter()
+                .filter(|split_id| {
+                    splits.get(*split_id).map_or(false, |split| {
+                        matches!(
+                            split.split_state,
+                            SplitState::Staged | SplitState::Published
+                        )
+                    })
+                })
+                .cloned()
+                .collect();
+            if !not_deletable_split_ids.is_empty() {
+                let message = format!(
+                    "splits `{}` are not deletable",
+                    not_deletable_split_ids.join(", ")
+                );
+                let entity = EntityKind::Splits {
+                    split_ids: not_deletable_split_ids,
+                };
+                return Err(MetastoreError::FailedPrecondition { entity, message });
+            }
+            let split_ids_to_delete: Vec<&String> = splits.keys().collect();
+
+            for split_ids_chunk in split_ids_to_delete.chunks(MAX_TRANSACTION_ITEMS) {
+                let items: Vec<TransactWriteItem> = split_ids_chunk
+                    .iter()
+                    .map(|split_id| {
+                        let delete = Delete::builder()
+                            .table_name(&self.table_name)
+                            .set_key(Some(primary_key(
+                                index_uid.index_id(),
+                                split_id.to_string(),
+                            )))
+                            .condition_expression(
+                                "index_uid = :index_uid AND split_state = :marked",
+                            )
+                            .expression_attribute_values(
+                                ":index_uid",
+                                attr_s(index_uid.to_string()),
+                            )
+                            .expression_attribute_values(
+                                ":marked",
+                                attr_s(SplitState::MarkedForDeletion.as_str()),
+                            )
+                            .build();
+                        TransactWriteItem::builder().delete(delete).build()
+                    })
+                    .collect();
+                if !self.transact_write_items(items).await? {
+                    continue 'attempts;
+                }
+            }
+            info!(index_id=%index_uid.index_id(), "Deleted {} splits from index.", split_ids_to_delete.len());
+
+            let not_found_split_ids: Vec<String> = split_ids
+                .iter()
+                .filter(|split_id| !splits.contains_key(*split_id))
+                .cloned()
+                .collect();
+            if !not_found_split_ids.is_empty() {
+                warn!(
+                    index_id=%index_uid.index_id(),
+                    split_ids=?PrettySample::new(&not_found_split_ids, 5),
+                    "{} splits were not found and could not be deleted.",
+                    not_found_split_ids.len()
+                );
+            }
+            return Ok(EmptyResponse {});
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    #[instrument(skip(self))]
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let patch = request.patch();
+        let index_uid: IndexUid = request.index_uid.into();
+        let split_ids = [request.split_id];
+
+        for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let mut splits = self.batch_get_splits(&index_uid, &split_ids).await?;
+
+            let Some(mut split) = splits.remove(&split_ids[0]) else {
+                if self.index_opt_for_uid(&index_uid).await?.is_none() {
+                    return Err(index_not_found_error(&index_uid));
+                }
+                let split_id = split_ids[0].clone();
+                return Err(MetastoreError::NotFound(EntityKind::Split { split_id }));
+            };
+            let current_split_metadata_json = split_metadata_json(&split.split_metadata)?;
+            patch.apply(&mut split)?;
+
+            // The update only goes through if the split has not been modified concurrently.
+            let update = Update::builder()
+                .table_name(&self.table_name)
+                .set_key(Some(primary_key(
+                    index_uid.index_id(),
+                    split_ids[0].clone(),
+                )))
+                .update_expression(
+                    "SET split_metadata_json = :split_metadata_json, update_timestamp = :now",
+                )
+                .condition_expression(
+                    "index_uid = :index_uid AND split_state = :published AND split_metadata_json \
+                     = :current_split_metadata_json",
+                )
+                .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+                .expression_attribute_values(":published", attr_s(SplitState::Published.as_str()))
+                .expression_attribute_values(
+                    ":current_split_metadata_json",
+                    attr_s(current_split_metadata_json),
+                )
+                .expression_attribute_values(
+                    ":split_metadata_json",
+                    attr_s(split_metadata_json(&split.split_metadata)?),
+                )
+                .expression_attribute_values(":now", attr_n(split.update_timestamp))
+                .build();
+            let items = vec![TransactWriteItem::builder().update(update).build()];
+
+            if self.transact_write_items(items).await? {
+                info!(index_id=%index_uid.index_id(), split_id=%split_ids[0], "Updated split metadata.");
+                return Ok(EmptyResponse {});
+            }
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    #[instrument(skip(self))]
+    async fn index_metadata(
+        &mut self,
+        request: IndexMetadataRequest,
+    ) -> MetastoreResult<IndexMetadataResponse> {
+        let index_opt = if let Some(index_uid) = &request.index_uid {
+            let index_uid: IndexUid = index_uid.to_string().into();
+            self.index_opt_for_uid(&index_uid).await?
+        } else if let Some(index_id) = &request.index_id {
+            self.index_opt(index_id).await?
+        } else {
+            return Err(MetastoreError::Internal {
+                message: "either `index_id` or `index_uid` must be set".to_string(),
+                cause: "missing index identifier".to_string(),
+            });
+        };
+        let index_metadata = index_opt
+            .ok_or({
+                MetastoreError::NotFound(EntityKind::Index {
+                    index_id: request.get_index_id().expect("index_id is set").to_string(),
+                })
+            })?
+            .index_metadata()?;
+        let response = IndexMetadataResponse::try_from_index_metadata(index_metadata)?;
+        Ok(response)
+    }
+
+    #[instrument(skip(self))]
+    async fn add_source(&mut self, request: AddSourceRequest) -> MetastoreResult<EmptyResponse> {
+        let source_config = request.deserialize_source_config()?;
+        let index_uid: IndexUid = request.index_uid.into();
+        self.mutate_index_metadata::<MetastoreError, _>(
+            &index_uid,
+            |index_metadata: &mut IndexMetadata| {
+                index_metadata.add_source(source_config.clone())?;
+                Ok(true)
+            },
+        )
+        .await?;
+        Ok(EmptyResponse {})
+    }
+
+    #[instrument(skip(self))]
+    async fn toggle_source(
+        &mut self,
+        request: ToggleSourceRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        self.mutate_index_metadata(&index_uid, |index_metadata| {
+            index_metadata.toggle_source(&request.source_id, request.enable)
+        })
+        .await?;
+        Ok(EmptyResponse {})
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_source(
+        &mut self,
+        request: DeleteSourceRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        self.mutate_index_metadata(&index_uid, |index_metadata| {
+            index_metadata.delete_source(&request.source_id)
+        })
+        .await?;
+        Ok(EmptyResponse {})
+    }
+
+    #[instrument(skip(self))]
+    async fn reset_source_checkpoint(
+        &mut self,
+        request: ResetSourceCheckpointRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        self.mutate_index_metadata(&index_uid, |index_metadata| {
+            Ok::<_, MetastoreError>(index_metadata.checkpoint.reset_source(&request.source_id))
+        })
+        .await?;
+        Ok(EmptyResponse {})
+    }
+
+    /// Retrieves the last delete opstamp for a given `index_id`.
+    #[instrument(skip(self))]
+    async fn last_delete_opstamp(
+        &mut self,
+        request: LastDeleteOpstampRequest,
+    ) -> MetastoreResult<LastDeleteOpstampResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let last_delete_opstamp = self
+            .index_opt_for_uid(&index_uid)
+            .await?
+            .map(|index| index.last_delete_opstamp)
+            .unwrap_or_default();
+        Ok(LastDeleteOpstampResponse::new(last_delete_opstamp))
+    }
+
+    /// Creates a delete task from a delete query.
+    #[instrument(skip(self))]
+    async fn create_delete_task(
+        &mut self,
+        delete_query: DeleteQuery,
+    ) -> MetastoreResult<DeleteTask> {
+        let delete_query_json = serde_json::to_string(&delete_query).map_err(|error| {
+            MetastoreError::JsonSerializeError {
+                struct_name: "DeleteQuery".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        let index_uid: IndexUid = delete_query.index_uid.clone().into();
+
+        for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let index = self.index_for_uid(&index_uid).await?;
+            let opstamp = index.last_delete_opstamp + 1;
+            let create_timestamp = now_timestamp();
+
+            // Bumping the opstamp counter of the index and inserting the delete task in the same
+            // transaction keeps opstamps unique and gapless.
+            let update = Update::builder()
+                .table_name(&self.table_name)
+                .set_key(Some(primary_key(
+                    index_uid.index_id(),
+                    INDEX_METADATA_SORT_KEY,
+                )))
+                .update_expression("SET last_delete_opstamp = :opstamp")
+                .condition_expression(
+                    "index_uid = :index_uid AND last_delete_opstamp = :last_delete_opstamp",
+                )
+                .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+                .expression_attribute_values(
+                    ":last_delete_opstamp",
+                    attr_n(index.last_delete_opstamp),
+                )
+                .expression_attribute_values(":opstamp", attr_n(opstamp))
+                .build();
+
+            let mut item = primary_key(index_uid.index_id(), delete_task_sort_key(opstamp));
+            item.insert(INDEX_UID.to_string(), attr_s(index_uid.to_string()));
+            item.insert(OPSTAMP.to_string(), attr_n(opstamp));
+            item.insert(CREATE_TIMESTAMP.to_string(), attr_n(create_timestamp));
+            item.insert(DELETE_QUERY_JSON.to_string(), attr_s(&delete_query_json));
+            let put = Put::builder()
+                .table_name(&self.table_name)
+                .set_item(Some(item))
+                .build();
+
+            let items = vec![
+                TransactWriteItem::builder().update(update).build(),
+                TransactWriteItem::builder().put(put).build(),
+            ];
+            if self.transact_write_items(items).await? {
+                return Ok(DeleteTask {
+                    create_timestamp,
+                    opstamp,
+                    delete_query: Some(delete_query),
+                });
+            }
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    /// Update splits delete opstamps.
+    #[instrument(skip(self))]
+    async fn update_splits_delete_opstamp(
+        &mut self,
+        request: UpdateSplitsDeleteOpstampRequest,
+    ) -> MetastoreResult<UpdateSplitsDeleteOpstampResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let split_ids = request.split_ids;
+        if split_ids.is_empty() {
+            return Ok(UpdateSplitsDeleteOpstampResponse {});
+        }
+        'attempts: for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let splits = self.batch_get_splits(&index_uid, &split_ids).await?;
+
+            // If no splits were found, maybe the index does not exist in the first place?
+            if splits.is_empty() && self.index_opt_for_uid(&index_uid).await?.is_none() {
+                return Err(index_not_found_error(&index_uid));
+            }
+            let split_ids_to_update: Vec<&String> = splits
+                .iter()
+                .filter(|(_, split)| split.split_metadata.delete_opstamp != request.delete_opstamp)
+                .map(|(split_id, _)| split_id)
+                .collect();
+            let now = now_timestamp();
+
+            for split_ids_chunk in split_ids_to_update.chunks(MAX_TRANSACTION_ITEMS) {
+                let items: Vec<TransactWriteItem> = split_ids_chunk
+                    .iter()
+                    .map(|split_id| {
+                        let update = Update::builder()
+                            .table_name(&self.table_name)
+                            .set_key(Some(primary_key(
+                                index_uid.index_id(),
+                                split_id.to_string(),
+                            )))
+                            .update_expression(
+                                "SET delete_opstamp = :delete_opstamp, update_timestamp = :now",
+                            )
+                            .condition_expression("index_uid = :index_uid")
+                            .expression_attribute_values(
+                                ":index_uid",
+                                attr_s(index_uid.to_string()),
+                            )
+                            .expression_attribute_values(
+                                ":delete_opstamp",
+                                attr_n(request.delete_opstamp),
+                            )
+                            .expression_attribute_values(":now", attr_n(now))
+                            .build();
+                        TransactWriteItem::builder().update(update).build()
+                    })
+                    .collect();
+                if !self.transact_write_items(items).await? {
+                    continue 'attempts;
+                }
+            }
+            return Ok(UpdateSplitsDeleteOpstampResponse {});
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    /// Lists the delete tasks with opstamp > `opstamp_start`.
+    #[instrument(skip(self))]
+    async fn list_delete_tasks(
+        &mut self,
+        request: ListDeleteTasksRequest,
+    ) -> MetastoreResult<ListDeleteTasksResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let delete_tasks = self
+            .query_items(&index_uid, Some(DELETE_TASK_SORT_KEY_PREFIX))
+            .await?
+            .iter()
+            .map(delete_task_from_item)
+            .filter_ok(|delete_task| delete_task.opstamp > request.opstamp_start)
+            .collect::<MetastoreResult<Vec<DeleteTask>>>()?;
+        Ok(ListDeleteTasksResponse { delete_tasks })
+    }
+
+    /// Returns `num_splits` published splits with `split.delete_opstamp` < `delete_opstamp`.
+    /// Results are ordered by ascending `split.delete_opstamp` and `split.publish_timestamp`
+    /// values.
+    #[instrument(skip(self))]
+    async fn list_stale_splits(
+        &mut self,
+        request: ListStaleSplitsRequest,
+    ) -> MetastoreResult<ListSplitsResponse> {
+        let list_splits_query = ListSplitsQuery::for_index(request.index_uid.into())
+            .with_delete_opstamp_lt(request.delete_opstamp)
+            .with_split_state(SplitState::Published)
+            .retain_mature(OffsetDateTime::now_utc())
+            .sort_by_staleness()
+            .with_limit(request.num_splits as usize);
+        let splits = self.inner_list_splits(&list_splits_query).await?;
+        ListSplitsResponse::try_from_splits(splits)
+    }
+
+    #[instrument(skip(self))]
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> MetastoreResult<IndexSnapshot> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let snapshot_id = Ulid::new().to_string();
+
+        for _ in 0..MAX_TRANSACTION_ATTEMPTS {
+            let index = self.index_for_uid(&index_uid).await?;
+            let index_metadata = index.index_metadata()?;
+            let index_checkpoint_json = serde_utils::to_json_str(&index_metadata.checkpoint)?;
+            let split_ids: Vec<String> = self
+                .query_splits(&index_uid)
+                .await?
+                .into_iter()
+                .filter(|split| split.split_state == SplitState::Published)
+                .map(|split| split.split_metadata.split_id)
+                .sorted()
+                .collect();
+            let snapshot = IndexSnapshot {
+                snapshot_id: snapshot_id.clone(),
+                index_uid: index_uid.to_string(),
+                create_timestamp: now_timestamp(),
+                split_ids,
+                index_checkpoint_json,
+            };
+            let mut item = primary_key(index_uid.index_id(), index_snapshot_sort_key(&snapshot_id));
+            item.insert(INDEX_UID.to_string(), attr_s(&snapshot.index_uid));
+            item.insert(SNAPSHOT_ID.to_string(), attr_s(&snapshot.snapshot_id));
+            item.insert(
+                CREATE_TIMESTAMP.to_string(),
+                attr_n(snapshot.create_timestamp),
+            );
+            item.insert(
+                SPLIT_IDS.to_string(),
+                AttributeValue::L(snapshot.split_ids.iter().map(attr_s).collect()),
+            );
+            item.insert(
+                INDEX_CHECKPOINT_JSON.to_string(),
+                attr_s(&snapshot.index_checkpoint_json),
+            );
+            let put = Put::builder()
+                .table_name(&self.table_name)
+                .set_item(Some(item))
+                .build();
+
+            // The snapshot is only written if no split was published in the meantime.
+            let items = vec![
+                self.index_unchanged_condition_check(&index),
+                TransactWriteItem::builder().put(put).build(),
+            ];
+            if self.transact_write_items(items).await? {
+                return Ok(snapshot);
+            }
+        }
+        Err(too_many_conflicts_error())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> MetastoreResult<ListIndexSnapshotsResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let mut snapshots = self
+            .query_items(&index_uid, Some(INDEX_SNAPSHOT_SORT_KEY_PREFIX))
+            .await?
+            .iter()
+            .map(index_snapshot_from_item)
+            .collect::<MetastoreResult<Vec<IndexSnapshot>>>()?;
+        snapshots.sort_by(|left, right| {
+            left.create_timestamp
+                .cmp(&right.create_timestamp)
+                .then_with(|| left.snapshot_id.cmp(&right.snapshot_id))
+        });
+        Ok(ListIndexSnapshotsResponse { snapshots })
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let delete_item_res = self
+            .client
+            .delete_item()
+            .table_name(&self.table_name)
+            .set_key(Some(primary_key(
+                index_uid.index_id(),
+                index_snapshot_sort_key(&request.snapshot_id),
+            )))
+            .condition_expression("index_uid = :index_uid")
+            .expression_attribute_values(":index_uid", attr_s(index_uid.to_string()))
+            .send()
+            .await;
+        match delete_item_res {
+            Ok(_) => Ok(EmptyResponse {}),
+            Err(SdkError::ServiceError(service_error))
+                if service_error.err().is_conditional_check_failed_exception() =>
+            {
+                Err(MetastoreError::NotFound(EntityKind::IndexSnapshot {
+                    index_id: index_uid.index_id().to_string(),
+                    snapshot_id: request.snapshot_id,
+                }))
+            }
+            Err(error) => Err(convert_sdk_error(error)),
+        }
+    }
+
+    async fn open_shards(
+        &mut self,
+        _request: OpenShardsRequest,
+    ) -> MetastoreResult<OpenShardsResponse> {
+        unimplemented!("`open_shards` is not implemented for DynamoDB metastore")
+    }
+
+    async fn acquire_shards(
+        &mut self,
+        _request: AcquireShardsRequest,
+    ) -> MetastoreResult<AcquireShardsResponse> {
+        unimplemented!("`acquire_shards` is not implemented for DynamoDB metastore")
+    }
+
+    async fn list_shards(
+        &mut self,
+        _request: ListShardsRequest,
+    ) -> MetastoreResult<ListShardsResponse> {
+        unimplemented!("`list_shards` is not implemented for DynamoDB metastore")
+    }
+
+    async fn delete_shards(
+        &mut self,
+        _request: DeleteShardsRequest,
+    ) -> MetastoreResult<DeleteShardsResponse> {
+        unimplemented!("`delete_shards` is not implemented for DynamoDB metastore")
+    }
+}
+
+impl MetastoreServiceExt for DynamoDbMetastore {}
+
+/// A DynamoDB metastore factory
+#[derive(Clone, Default)]
+pub struct DynamoDbMetastoreFactory {
+    // In a normal run, this cache will contain a single Metastore.
+    cache: Arc<Mutex<HashMap<Uri, MetastoreServiceClient>>>,
+}
+
+impl DynamoDbMetastoreFactory {
+    async fn get_from_cache(&self, uri: &Uri) -> Option<MetastoreServiceClient> {
+        let cache_lock = self.cache.lock().await;
+        cache_lock.get(uri).map(MetastoreServiceClient::clone)
+    }
+
+    /// If there is a valid entry in the cache to begin with, we trash the new
+    /// one and return the old one.
+    ///
+    /// This way we make sure that we keep only one instance associated
+    /// to the key `uri` outside of this struct.
+    async fn cache_metastore(
+        &self,
+        uri: Uri,
+        metastore: MetastoreServiceClient,
+    ) -> MetastoreServiceClient {
+        let mut cache_lock = self.cache.lock().await;
+        if let Some(metastore) = cache_lock.get(&uri) {
+            return metastore.clone();
+        }
+        cache_lock.insert(uri, metastore.clone());
+        metastore
+    }
+}
+
+#[async_trait]
+impl MetastoreFactory for DynamoDbMetastoreFactory {
+    fn backend(&self) -> MetastoreBackend {
+        MetastoreBackend::DynamoDB
+    }
+
+    async fn resolve(
+        &self,
+        metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> Result<MetastoreServiceClient, MetastoreResolverError> {
+        if let Some(metastore) = self.get_from_cache(uri).await {
+            debug!("using metastore from cache");
+            return Ok(metastore);
+        }
+        debug!("metastore not found in cache");
+        let dynamodb_metastore_config = metastore_config.as_dynamodb().ok_or_else(|| {
+            let message = format!(
+                "expected DynamoDB metastore config, got `{:?}`",
+                metastore_config.backend()
+            );
+            MetastoreResolverError::InvalidConfig(message)
+        })?;
+        let dynamodb_metastore = DynamoDbMetastore::new(dynamodb_metastore_config, uri)
+            .await
+            .map_err(MetastoreResolverError::Initialization)?;
+        let instrumented_metastore = instrument_metastore(dynamodb_metastore);
+        let unique_metastore_for_uri = self
+            .cache_metastore(uri.clone(), instrumented_metastore)
+            .await;
+        Ok(unique_metastore_for_uri)
+    }
+}
+
+#[cfg(test)]
+#[async_trait]
+impl crate::tests::DefaultForTest for DynamoDbMetastore {
+    async fn default_for_test() -> Self {
+        // The tests run against the localstack instance started by `make docker-compose-up`.
+        let endpoint = std::env::var("QW_TEST_DYNAMODB_ENDPOINT")
+            .unwrap_or_else(|_| "http://localhost:4566".to_string());
+        let dynamodb_metastore_config = DynamoDbMetastoreConfig {
+            region: None,
+            endpoint: Some(endpoint),
+        };
+        let uri = Uri::for_test("dynamodb://quickwit-metastore-test");
+        DynamoDbMetastore::new(&dynamodb_metastore_config, &uri)
+            .await
+            .expect("Failed to initialize test DynamoDB metastore.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::uri::{Protocol, Uri};
+    use quickwit_proto::metastore::MetastoreService;
+
+    use super::{parse_table_name, DynamoDbMetastore};
+    use crate::metastore_test_suite;
+    use crate::tests::DefaultForTest;
+
+    metastore_test_suite!(crate::DynamoDbMetastore);
+
+    #[tokio::test]
+    async fn test_metastore_connectivity_and_endpoints() {
+        let mut metastore = DynamoDbMetastore::default_for_test().await;
+        metastore.check_connectivity().await.unwrap();
+        assert_eq!(metastore.endpoints()[0].protocol(), Protocol::DynamoDB);
+    }
+
+    #[test]
+    fn test_parse_table_name() {
+        assert_eq!(
+            parse_table_name(&Uri::for_test("dynamodb://quickwit-metastore")).unwrap(),
+            "quickwit-metastore"
+        );
+        assert_eq!(
+            parse_table_name(&Uri::for_test("dynamodb://quickwit-metastore/")).unwrap(),
+            "quickwit-metastore"
+        );
+        parse_table_name(&Uri::for_test("dynamodb://")).unwrap_err();
+        parse_table_name(&Uri::for_test("dynamodb://table/key")).unwrap_err();
+    }
+}
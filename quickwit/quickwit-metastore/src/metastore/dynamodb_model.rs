@@ -0,0 +1,318 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Layout of the items stored in the DynamoDB metastore table.
+//!
+//! All the items of an index live in the same partition, keyed by the index ID:
+//!
+//! | `pk`       | `sk`                               | item                |
+//! |------------|------------------------------------|---------------------|
+//! | `index_id` | `#INDEX_METADATA`                  | index metadata      |
+//! | `index_id` | `split_id`                         | split               |
+//! | `index_id` | `#DELETE_TASK#{opstamp:020}`       | delete task         |
+//! | `index_id` | `#INDEX_SNAPSHOT#{snapshot_id}`    | index snapshot      |
+//!
+//! Every item also carries the `index_uid` of the index incarnation it belongs to, so that items
+//! left behind by a deleted index are never confused with the items of a newer index sharing the
+//! same ID.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use aws_sdk_dynamodb::types::AttributeValue;
+use quickwit_proto::metastore::{
+    DeleteQuery, DeleteTask, IndexSnapshot, MetastoreError, MetastoreResult,
+};
+use quickwit_proto::types::IndexUid;
+use tracing::error;
+
+use crate::{IndexMetadata, Split, SplitMetadata, SplitState};
+
+pub(super) type Item = HashMap<String, AttributeValue>;
+
+/// Name of the partition key attribute.
+pub(super) const PARTITION_KEY: &str = "pk";
+/// Name of the sort key attribute.
+pub(super) const SORT_KEY: &str = "sk";
+
+pub(super) const INDEX_METADATA_SORT_KEY: &str = "#INDEX_METADATA";
+pub(super) const DELETE_TASK_SORT_KEY_PREFIX: &str = "#DELETE_TASK#";
+pub(super) const INDEX_SNAPSHOT_SORT_KEY_PREFIX: &str = "#INDEX_SNAPSHOT#";
+
+pub(super) const INDEX_UID: &str = "index_uid";
+pub(super) const INDEX_METADATA_JSON: &str = "index_metadata_json";
+pub(super) const CREATE_TIMESTAMP: &str = "create_timestamp";
+pub(super) const UPDATE_TIMESTAMP: &str = "update_timestamp";
+pub(super) const PUBLISH_TIMESTAMP: &str = "publish_timestamp";
+/// Version of the index metadata item, incremented on every write. Used for optimistic
+/// concurrency control.
+pub(super) const METADATA_VERSION: &str = "metadata_version";
+/// Opstamp of the last delete task created for the index.
+pub(super) const LAST_DELETE_OPSTAMP: &str = "last_delete_opstamp";
+pub(super) const SPLIT_STATE: &str = "split_state";
+pub(super) const SPLIT_METADATA_JSON: &str = "split_metadata_json";
+pub(super) const DELETE_OPSTAMP: &str = "delete_opstamp";
+pub(super) const OPSTAMP: &str = "opstamp";
+pub(super) const DELETE_QUERY_JSON: &str = "delete_query_json";
+pub(super) const SNAPSHOT_ID: &str = "snapshot_id";
+pub(super) const SPLIT_IDS: &str = "split_ids";
+pub(super) const INDEX_CHECKPOINT_JSON: &str = "index_checkpoint_json";
+
+pub(super) fn delete_task_sort_key(opstamp: u64) -> String {
+    format!("{DELETE_TASK_SORT_KEY_PREFIX}{opstamp:020}")
+}
+
+pub(super) fn index_snapshot_sort_key(snapshot_id: &str) -> String {
+    format!("{INDEX_SNAPSHOT_SORT_KEY_PREFIX}{snapshot_id}")
+}
+
+/// Returns the primary key of the item identified by `index_id` and `sort_key`.
+pub(super) fn primary_key(index_id: &str, sort_key: impl Into<String>) -> Item {
+    HashMap::from_iter([
+        (
+            PARTITION_KEY.to_string(),
+            AttributeValue::S(index_id.to_string()),
+        ),
+        (SORT_KEY.to_string(), AttributeValue::S(sort_key.into())),
+    ])
+}
+
+pub(super) fn attr_s(value: impl Into<String>) -> AttributeValue {
+    AttributeValue::S(value.into())
+}
+
+pub(super) fn attr_n(value: impl ToString) -> AttributeValue {
+    AttributeValue::N(value.to_string())
+}
+
+fn missing_attribute_error(attribute_name: &str) -> MetastoreError {
+    MetastoreError::Internal {
+        message: "failed to parse DynamoDB item".to_string(),
+        cause: format!("attribute `{attribute_name}` is missing or has an unexpected type"),
+    }
+}
+
+pub(super) fn get_string<'a>(item: &'a Item, attribute_name: &str) -> MetastoreResult<&'a str> {
+    match item.get(attribute_name) {
+        Some(AttributeValue::S(value)) => Ok(value),
+        _ => Err(missing_attribute_error(attribute_name)),
+    }
+}
+
+pub(super) fn get_number<T: FromStr>(item: &Item, attribute_name: &str) -> MetastoreResult<T> {
+    get_number_opt(item, attribute_name)?.ok_or_else(|| missing_attribute_error(attribute_name))
+}
+
+pub(super) fn get_number_opt<T: FromStr>(
+    item: &Item,
+    attribute_name: &str,
+) -> MetastoreResult<Option<T>> {
+    match item.get(attribute_name) {
+        Some(AttributeValue::N(value)) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| missing_attribute_error(attribute_name)),
+        None => Ok(None),
+        _ => Err(missing_attribute_error(attribute_name)),
+    }
+}
+
+/// Index metadata item.
+pub(super) struct DynamoDbIndex {
+    pub index_uid: IndexUid,
+    pub index_metadata_json: String,
+    pub create_timestamp: i64,
+    pub version: u64,
+    pub last_delete_opstamp: u64,
+}
+
+impl DynamoDbIndex {
+    /// Deserializes index metadata from the JSON string stored in the item and sets the
+    /// appropriate timestamps.
+    pub fn index_metadata(&self) -> MetastoreResult<IndexMetadata> {
+        let mut index_metadata = serde_json::from_str::<IndexMetadata>(&self.index_metadata_json)
+            .map_err(|error| {
+            error!(index_uid=%self.index_uid, error=?error, "failed to deserialize index metadata");
+
+            MetastoreError::JsonDeserializeError {
+                struct_name: "IndexMetadata".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        index_metadata.create_timestamp = self.create_timestamp;
+        Ok(index_metadata)
+    }
+}
+
+impl TryFrom<&Item> for DynamoDbIndex {
+    type Error = MetastoreError;
+
+    fn try_from(item: &Item) -> MetastoreResult<Self> {
+        Ok(DynamoDbIndex {
+            index_uid: get_string(item, INDEX_UID)?.to_string().into(),
+            index_metadata_json: get_string(item, INDEX_METADATA_JSON)?.to_string(),
+            create_timestamp: get_number(item, CREATE_TIMESTAMP)?,
+            version: get_number(item, METADATA_VERSION)?,
+            last_delete_opstamp: get_number_opt(item, LAST_DELETE_OPSTAMP)?.unwrap_or_default(),
+        })
+    }
+}
+
+/// Returns whether `item` is a split item. Split items are the only items whose sort key does not
+/// start with `#`.
+pub(super) fn is_split_item(item: &Item) -> bool {
+    matches!(item.get(SORT_KEY), Some(AttributeValue::S(sort_key)) if !sort_key.starts_with('#'))
+}
+
+pub(super) fn split_state(item: &Item) -> MetastoreResult<SplitState> {
+    let split_state_str = get_string(item, SPLIT_STATE)?;
+    SplitState::from_str(split_state_str).map_err(|error| {
+        error!(split_state=%split_state_str, error=?error, "failed to deserialize split state");
+        MetastoreError::JsonDeserializeError {
+            struct_name: "SplitState".to_string(),
+            message: error,
+        }
+    })
+}
+
+pub(super) fn split_metadata_json(split_metadata: &SplitMetadata) -> MetastoreResult<String> {
+    serde_json::to_string(split_metadata).map_err(|error| MetastoreError::JsonSerializeError {
+        struct_name: "SplitMetadata".to_string(),
+        message: error.to_string(),
+    })
+}
+
+/// Converts a split item into a [`Split`].
+pub(super) fn split_from_item(item: &Item) -> MetastoreResult<Split> {
+    let mut split_metadata: SplitMetadata =
+        serde_json::from_str(get_string(item, SPLIT_METADATA_JSON)?).map_err(|error| {
+            MetastoreError::JsonDeserializeError {
+                struct_name: "SplitMetadata".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+    // `create_timestamp`, `delete_opstamp`, and `index_uid` are duplicated in `SplitMetadata` and
+    // need to be overridden with the "true" values stored in dedicated attributes.
+    split_metadata.create_timestamp = get_number(item, CREATE_TIMESTAMP)?;
+    split_metadata.delete_opstamp = get_number(item, DELETE_OPSTAMP)?;
+    split_metadata.index_uid = get_string(item, INDEX_UID)?.to_string().into();
+    Ok(Split {
+        split_metadata,
+        split_state: split_state(item)?,
+        update_timestamp: get_number(item, UPDATE_TIMESTAMP)?,
+        publish_timestamp: get_number_opt(item, PUBLISH_TIMESTAMP)?,
+    })
+}
+
+/// Converts a delete task item into a [`DeleteTask`].
+pub(super) fn delete_task_from_item(item: &Item) -> MetastoreResult<DeleteTask> {
+    let delete_query: DeleteQuery = serde_json::from_str(get_string(item, DELETE_QUERY_JSON)?)
+        .map_err(|error| MetastoreError::JsonDeserializeError {
+            struct_name: "DeleteQuery".to_string(),
+            message: error.to_string(),
+        })?;
+    Ok(DeleteTask {
+        create_timestamp: get_number(item, CREATE_TIMESTAMP)?,
+        opstamp: get_number(item, OPSTAMP)?,
+        delete_query: Some(delete_query),
+    })
+}
+
+/// Converts an index snapshot item into an [`IndexSnapshot`].
+pub(super) fn index_snapshot_from_item(item: &Item) -> MetastoreResult<IndexSnapshot> {
+    let split_ids = match item.get(SPLIT_IDS) {
+        Some(AttributeValue::L(split_ids)) => split_ids
+            .iter()
+            .map(|split_id| match split_id {
+                AttributeValue::S(split_id) => Ok(split_id.clone()),
+                _ => Err(missing_attribute_error(SPLIT_IDS)),
+            })
+            .collect::<MetastoreResult<Vec<String>>>()?,
+        _ => return Err(missing_attribute_error(SPLIT_IDS)),
+    };
+    Ok(IndexSnapshot {
+        snapshot_id: get_string(item, SNAPSHOT_ID)?.to_string(),
+        index_uid: get_string(item, INDEX_UID)?.to_string(),
+        create_timestamp: get_number(item, CREATE_TIMESTAMP)?,
+        split_ids,
+        index_checkpoint_json: get_string(item, INDEX_CHECKPOINT_JSON)?.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dynamodb_sort_keys() {
+        assert_eq!(
+            delete_task_sort_key(42),
+            "#DELETE_TASK#00000000000000000042"
+        );
+        assert!(delete_task_sort_key(9) < delete_task_sort_key(10));
+        assert_eq!(index_snapshot_sort_key("01H9Z"), "#INDEX_SNAPSHOT#01H9Z");
+        let mut item = primary_key("my-index", "my-split");
+        assert!(is_split_item(&item));
+
+        item = primary_key("my-index", INDEX_METADATA_SORT_KEY);
+        assert!(!is_split_item(&item));
+    }
+
+    #[test]
+    fn test_dynamodb_split_from_item() {
+        let split_metadata = SplitMetadata {
+            split_id: "my-split".to_string(),
+            index_uid: "my-index:00000000000000000000000000".to_string().into(),
+            create_timestamp: 1,
+            delete_opstamp: 2,
+            ..Default::default()
+        };
+        let mut item = primary_key("my-index", "my-split");
+        item.insert(
+            INDEX_UID.to_string(),
+            attr_s("my-index:11111111111111111111111111"),
+        );
+        item.insert(
+            SPLIT_METADATA_JSON.to_string(),
+            attr_s(split_metadata_json(&split_metadata).unwrap()),
+        );
+        item.insert(
+            SPLIT_STATE.to_string(),
+            attr_s(SplitState::Published.as_str()),
+        );
+        item.insert(CREATE_TIMESTAMP.to_string(), attr_n(10));
+        item.insert(UPDATE_TIMESTAMP.to_string(), attr_n(20));
+        item.insert(PUBLISH_TIMESTAMP.to_string(), attr_n(30));
+        item.insert(DELETE_OPSTAMP.to_string(), attr_n(40));
+
+        let split = split_from_item(&item).unwrap();
+        assert_eq!(split.split_state, SplitState::Published);
+        assert_eq!(split.update_timestamp, 20);
+        assert_eq!(split.publish_timestamp, Some(30));
+        assert_eq!(split.split_metadata.create_timestamp, 10);
+        assert_eq!(split.split_metadata.delete_opstamp, 40);
+        assert_eq!(
+            split.split_metadata.index_uid.to_string(),
+            "my-index:11111111111111111111111111"
+        );
+
+        item.remove(SPLIT_STATE);
+        split_from_item(&item).unwrap_err();
+    }
+}
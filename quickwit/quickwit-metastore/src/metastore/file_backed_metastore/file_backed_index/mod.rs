@@ -32,9 +32,10 @@ use itertools::Itertools;
 use quickwit_common::PrettySample;
 use quickwit_config::{SourceConfig, INGEST_SOURCE_ID};
 use quickwit_proto::metastore::{
-    AcquireShardsSubrequest, AcquireShardsSubresponse, DeleteQuery, DeleteShardsSubrequest,
-    DeleteTask, EntityKind, ListShardsSubrequest, ListShardsSubresponse, MetastoreError,
-    MetastoreResult, OpenShardsSubrequest, OpenShardsSubresponse,
+    serde_utils, AcquireShardsSubrequest, AcquireShardsSubresponse, DeleteQuery,
+    DeleteShardsSubrequest, DeleteTask, EntityKind, IndexSnapshot, ListShardsSubrequest,
+    ListShardsSubresponse, MetastoreError, MetastoreResult, OpenShardsSubrequest,
+    OpenShardsSubresponse,
 };
 use quickwit_proto::types::{IndexUid, PublishToken, SourceId, SplitId};
 use serde::{Deserialize, Serialize};
@@ -42,10 +43,14 @@ use serialize::VersionedFileBackedIndex;
 use shards::Shards;
 use time::OffsetDateTime;
 use tracing::{info, warn};
+use ulid::Ulid;
 
 use super::MutationOccurred;
 use crate::checkpoint::IndexCheckpointDelta;
-use crate::{split_tag_filter, IndexMetadata, ListSplitsQuery, Split, SplitMetadata, SplitState};
+use crate::{
+    split_tag_filter, IndexMetadata, ListSplitsQuery, Split, SplitMetadata, SplitMetadataPatch,
+    SplitState,
+};
 
 /// A `FileBackedIndex` object carries an index metadata and its split metadata.
 // This struct is meant to be used only within the [`FileBackedMetastore`]. The public visibility is
@@ -61,6 +66,8 @@ pub(crate) struct FileBackedIndex {
     per_source_shards: HashMap<SourceId, Shards>,
     /// Delete tasks.
     delete_tasks: Vec<DeleteTask>,
+    /// Snapshots of the published splits.
+    snapshots: Vec<IndexSnapshot>,
     /// Stamper.
     stamper: Stamper,
     /// Flag used to avoid polling the metastore if
@@ -131,6 +138,7 @@ impl quickwit_config::TestableForRegression for FileBackedIndex {
         assert_eq!(self.splits, other.splits);
         assert_eq!(self.per_source_shards, other.per_source_shards);
         assert_eq!(self.delete_tasks, other.delete_tasks);
+        assert_eq!(self.snapshots, other.snapshots);
     }
 }
 
@@ -141,6 +149,7 @@ impl From<IndexMetadata> for FileBackedIndex {
             splits: Default::default(),
             per_source_shards: Default::default(),
             delete_tasks: Default::default(),
+            snapshots: Default::default(),
             stamper: Default::default(),
             recently_modified: false,
             discarded: false,
@@ -177,6 +186,7 @@ impl FileBackedIndex {
             splits,
             per_source_shards,
             delete_tasks,
+            snapshots: Default::default(),
             stamper: Stamper::new(last_opstamp),
             recently_modified: false,
             discarded: false,
@@ -404,6 +414,18 @@ impl FileBackedIndex {
                 .take(limit)
                 .cloned()
                 .collect()
+        } else if query.offset.is_some() {
+            // Paginated listings need a stable order, like the other metastores.
+            self.splits
+                .values()
+                .filter(|split| split_query_predicate(split, query))
+                .sorted_unstable_by(|left_split, right_split| {
+                    left_split.split_id().cmp(right_split.split_id())
+                })
+                .skip(offset)
+                .take(limit)
+                .cloned()
+                .collect()
         } else {
             self.splits
                 .values()
@@ -469,6 +491,21 @@ impl FileBackedIndex {
         Ok(())
     }
 
+    /// Updates the metadata of a published split.
+    pub(crate) fn update_split_metadata(
+        &mut self,
+        split_id: &str,
+        patch: &SplitMetadataPatch,
+    ) -> MetastoreResult<()> {
+        let Some(split) = self.splits.get_mut(split_id) else {
+            let entity = EntityKind::Split {
+                split_id: split_id.to_string(),
+            };
+            return Err(MetastoreError::NotFound(entity));
+        };
+        patch.apply(split)
+    }
+
     /// Adds a source.
     pub(crate) fn add_source(&mut self, source_config: SourceConfig) -> MetastoreResult<()> {
         let index_uid = self.index_uid().clone();
@@ -548,6 +585,48 @@ impl FileBackedIndex {
         Ok(delete_tasks)
     }
 
+    /// Creates a snapshot of the splits currently published.
+    pub(crate) fn create_snapshot(&mut self) -> MetastoreResult<IndexSnapshot> {
+        let split_ids: Vec<SplitId> = self
+            .splits
+            .values()
+            .filter(|split| split.split_state == SplitState::Published)
+            .map(|split| split.split_id().to_string())
+            .sorted()
+            .collect();
+        let index_checkpoint_json = serde_utils::to_json_str(&self.metadata.checkpoint)?;
+        let snapshot = IndexSnapshot {
+            snapshot_id: Ulid::new().to_string(),
+            index_uid: self.index_uid().to_string(),
+            create_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+            split_ids,
+            index_checkpoint_json,
+        };
+        self.snapshots.push(snapshot.clone());
+        Ok(snapshot)
+    }
+
+    /// Lists the snapshots of the index.
+    pub(crate) fn list_snapshots(&self) -> Vec<IndexSnapshot> {
+        self.snapshots.clone()
+    }
+
+    /// Deletes a snapshot.
+    pub(crate) fn delete_snapshot(&mut self, snapshot_id: &str) -> MetastoreResult<()> {
+        let Some(position) = self
+            .snapshots
+            .iter()
+            .position(|snapshot| snapshot.snapshot_id == snapshot_id)
+        else {
+            return Err(MetastoreError::NotFound(EntityKind::IndexSnapshot {
+                index_id: self.index_id().to_string(),
+                snapshot_id: snapshot_id.to_string(),
+            }));
+        };
+        self.snapshots.remove(position);
+        Ok(())
+    }
+
     // Shard API
 
     fn get_shards_for_source(&self, source_id: &str) -> MetastoreResult<&Shards> {
@@ -679,7 +758,7 @@ impl Debug for Stamper {
     }
 }
 
-fn split_query_predicate(split: &&Split, query: &ListSplitsQuery) -> bool {
+pub(crate) fn split_query_predicate(split: &&Split, query: &ListSplitsQuery) -> bool {
     if !split_tag_filter(&split.split_metadata, query.tags.as_ref()) {
         return false;
     }
@@ -688,6 +767,10 @@ fn split_query_predicate(split: &&Split, query: &ListSplitsQuery) -> bool {
         return false;
     }
 
+    if !query.split_ids.is_empty() && !query.split_ids.contains(&split.split_metadata.split_id) {
+        return false;
+    }
+
     if !query
         .delete_opstamp
         .contains(&split.split_metadata.delete_opstamp)
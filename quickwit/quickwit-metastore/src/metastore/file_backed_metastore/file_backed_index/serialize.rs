@@ -20,6 +20,7 @@
 use std::collections::HashMap;
 
 use itertools::Itertools;
+use quickwit_proto::metastore::IndexSnapshot;
 use quickwit_proto::types::SourceId;
 use serde::{Deserialize, Serialize};
 
@@ -61,6 +62,8 @@ pub(crate) struct FileBackedIndexV0_6 {
     shards: HashMap<SourceId, SerdeShards>,
     #[serde(default)]
     delete_tasks: Vec<DeleteTask>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    snapshots: Vec<IndexSnapshot>,
 }
 
 impl From<FileBackedIndex> for FileBackedIndexV0_6 {
@@ -85,6 +88,7 @@ impl From<FileBackedIndex> for FileBackedIndexV0_6 {
             splits,
             shards,
             delete_tasks,
+            snapshots: index.snapshots,
         }
     }
 }
@@ -108,6 +112,9 @@ impl From<FileBackedIndexV0_6> for FileBackedIndex {
                 )
             })
             .collect();
-        Self::new(index.metadata, index.splits, shards, index.delete_tasks)
+        let mut file_backed_index =
+            Self::new(index.metadata, index.splits, shards, index.delete_tasks);
+        file_backed_index.snapshots = index.snapshots;
+        file_backed_index
     }
 }
@@ -22,7 +22,7 @@ use std::time::Duration;
 
 use quickwit_proto::metastore::MetastoreResult;
 use quickwit_storage::Storage;
-use tokio::sync::{Mutex, OnceCell};
+use tokio::sync::{OnceCell, RwLock};
 use tracing::error;
 
 use super::file_backed_index::FileBackedIndex;
@@ -35,7 +35,7 @@ pub(crate) struct LazyFileBackedIndex {
     index_id: String,
     storage: Arc<dyn Storage>,
     polling_interval_opt: Option<Duration>,
-    lazy_index: OnceCell<Arc<Mutex<FileBackedIndex>>>,
+    lazy_index: OnceCell<Arc<RwLock<FileBackedIndex>>>,
 }
 
 impl LazyFileBackedIndex {
@@ -46,15 +46,15 @@ impl LazyFileBackedIndex {
         polling_interval_opt: Option<Duration>,
         file_backed_index: Option<FileBackedIndex>,
     ) -> Self {
-        let index_mutex_opt = file_backed_index.map(|index| Arc::new(Mutex::new(index)));
+        let index_rwlock_opt = file_backed_index.map(|index| Arc::new(RwLock::new(index)));
         // If an index is given and a polling interval is given,
         // spawn immediately the polling task.
-        if let Some(index_mutex) = index_mutex_opt.as_ref() {
+        if let Some(index_rwlock) = index_rwlock_opt.as_ref() {
             if let Some(polling_interval) = polling_interval_opt {
                 spawn_index_metadata_polling_task(
                     storage.clone(),
                     index_id.clone(),
-                    Arc::downgrade(index_mutex),
+                    Arc::downgrade(index_rwlock),
                     polling_interval,
                 );
             }
@@ -63,12 +63,12 @@ impl LazyFileBackedIndex {
             index_id,
             storage,
             polling_interval_opt,
-            lazy_index: OnceCell::new_with(index_mutex_opt),
+            lazy_index: OnceCell::new_with(index_rwlock_opt),
         }
     }
 
     /// Get `FileBackedIndex`.
-    pub async fn get(&self) -> MetastoreResult<Arc<Mutex<FileBackedIndex>>> {
+    pub async fn get(&self) -> MetastoreResult<Arc<RwLock<FileBackedIndex>>> {
         self.lazy_index
             .get_or_try_init(|| {
                 load_file_backed_index(
@@ -85,15 +85,22 @@ impl LazyFileBackedIndex {
 async fn poll_index_metadata_once(
     storage: &dyn Storage,
     index_id: &str,
-    metadata_mutex: &Mutex<FileBackedIndex>,
+    metadata_rwlock: &RwLock<FileBackedIndex>,
 ) {
-    let mut metadata_lock = metadata_mutex.lock().await;
-    if metadata_lock.flip_recently_modified_down() {
+    if metadata_rwlock.write().await.flip_recently_modified_down() {
         return;
     }
+    // The index is fetched without holding the lock so that readers are not blocked while the
+    // storage is being polled.
     let index_fetch_res = fetch_index(storage, index_id).await;
     match index_fetch_res {
         Ok(index) => {
+            let mut metadata_lock = metadata_rwlock.write().await;
+            // The metastore wrote a value while we were fetching the index: what we fetched might
+            // be stale.
+            if metadata_lock.flip_recently_modified_down() {
+                return;
+            }
             *metadata_lock = index;
         }
         Err(fetch_error) => {
@@ -105,15 +112,15 @@ async fn poll_index_metadata_once(
 fn spawn_index_metadata_polling_task(
     storage: Arc<dyn Storage>,
     index_id: String,
-    metastore_weak: Weak<Mutex<FileBackedIndex>>,
+    metastore_weak: Weak<RwLock<FileBackedIndex>>,
     polling_interval: Duration,
 ) {
     tokio::task::spawn(async move {
         let mut interval = tokio::time::interval(polling_interval);
         interval.tick().await; //< this is to prevent fetch right after the first population of the data.
-        while let Some(metadata_mutex) = metastore_weak.upgrade() {
+        while let Some(metadata_rwlock) = metastore_weak.upgrade() {
             interval.tick().await;
-            poll_index_metadata_once(&*storage, &index_id, &metadata_mutex).await;
+            poll_index_metadata_once(&*storage, &index_id, &metadata_rwlock).await;
         }
     });
 }
@@ -122,16 +129,16 @@ async fn load_file_backed_index(
     storage: Arc<dyn Storage>,
     index_id: String,
     polling_interval_opt: Option<Duration>,
-) -> MetastoreResult<Arc<Mutex<FileBackedIndex>>> {
+) -> MetastoreResult<Arc<RwLock<FileBackedIndex>>> {
     let index = fetch_index(&*storage, &index_id).await?;
-    let index_mutex = Arc::new(Mutex::new(index));
+    let index_rwlock = Arc::new(RwLock::new(index));
     if let Some(polling_interval) = polling_interval_opt {
         spawn_index_metadata_polling_task(
             storage.clone(),
             index_id.clone(),
-            Arc::downgrade(&index_mutex),
+            Arc::downgrade(&index_rwlock),
             polling_interval,
         );
     }
-    Ok(index_mutex)
+    Ok(index_rwlock)
 }
@@ -38,22 +38,24 @@ use quickwit_common::ServiceStream;
 use quickwit_config::validate_index_id_pattern;
 use quickwit_proto::metastore::{
     AcquireShardsRequest, AcquireShardsResponse, AcquireShardsSubrequest, AddSourceRequest,
-    CreateIndexRequest, CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
-    DeleteShardsResponse, DeleteShardsSubrequest, DeleteSourceRequest, DeleteSplitsRequest,
-    DeleteTask, EmptyResponse, EntityKind, IndexMetadataRequest, IndexMetadataResponse,
+    CreateIndexRequest, CreateIndexResponse, CreateIndexSnapshotRequest, DeleteIndexRequest,
+    DeleteIndexSnapshotRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
+    DeleteShardsSubrequest, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
+    EntityKind, IndexMetadataRequest, IndexMetadataResponse, IndexSnapshot,
     LastDeleteOpstampRequest, LastDeleteOpstampResponse, ListDeleteTasksRequest,
-    ListDeleteTasksResponse, ListIndexesMetadataRequest, ListIndexesMetadataResponse,
-    ListShardsRequest, ListShardsResponse, ListSplitsRequest, ListSplitsResponse,
-    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
-    MetastoreService, MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse,
-    OpenShardsSubrequest, PublishSplitsRequest, ResetSourceCheckpointRequest, StageSplitsRequest,
-    ToggleSourceRequest, UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
+    ListDeleteTasksResponse, ListIndexSnapshotsRequest, ListIndexSnapshotsResponse,
+    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse,
+    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
+    MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceStream, OpenShardsRequest,
+    OpenShardsResponse, OpenShardsSubrequest, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    StageSplitsRequest, ToggleSourceRequest, UpdateSplitMetadataRequest,
+    UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::IndexUid;
 use quickwit_storage::Storage;
 use regex::RegexSet;
 use time::OffsetDateTime;
-use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
+use tokio::sync::{OwnedRwLockReadGuard, OwnedRwLockWriteGuard, RwLock};
 
 use self::file_backed_index::FileBackedIndex;
 pub use self::file_backed_metastore_factory::FileBackedMetastoreFactory;
@@ -65,7 +67,8 @@ use self::store_operations::{
 use super::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadataResponseExt,
     ListIndexesMetadataResponseExt, ListSplitsRequestExt, ListSplitsResponseExt,
-    PublishSplitsRequestExt, StageSplitsRequestExt, STREAM_SPLITS_CHUNK_SIZE,
+    PublishSplitsRequestExt, StageSplitsRequestExt, UpdateSplitMetadataRequestExt,
+    STREAM_SPLITS_CHUNK_SIZE,
 };
 use crate::checkpoint::IndexCheckpointDelta;
 use crate::{IndexMetadata, ListSplitsQuery, MetastoreServiceExt, Split, SplitState};
@@ -190,7 +193,7 @@ impl FileBackedMetastore {
         mutate_fn: impl FnOnce(&mut FileBackedIndex) -> MetastoreResult<MutationOccurred<T>>,
     ) -> MetastoreResult<T> {
         let index_id = index_uid.index_id();
-        let mut locked_index = self.get_locked_index(index_id).await?;
+        let mut locked_index = self.get_write_locked_index(index_id).await?;
         if *locked_index.index_uid() != index_uid {
             return Err(MetastoreError::NotFound(EntityKind::Index {
                 index_id: index_id.to_string(),
@@ -203,7 +206,9 @@ impl FileBackedMetastore {
                 return Ok(value);
             }
         };
-        locked_index.set_recently_modified();
+        // The flag must be set on the new version of the index, which replaces the locked one once
+        // it is written.
+        index.set_recently_modified();
         let put_result = put_index(&*self.storage, &index).await;
         match put_result {
             Ok(()) => {
@@ -237,7 +242,7 @@ impl FileBackedMetastore {
     async fn read<T, F>(&self, index_uid: IndexUid, view: F) -> MetastoreResult<T>
     where F: FnOnce(&FileBackedIndex) -> MetastoreResult<T> {
         let index_id = index_uid.index_id();
-        let locked_index = self.get_locked_index(index_id).await?;
+        let locked_index = self.get_read_locked_index(index_id).await?;
         if *locked_index.index_uid() == index_uid {
             view(&locked_index)
         } else {
@@ -249,23 +254,41 @@ impl FileBackedMetastore {
 
     async fn read_any<T, F>(&self, index_id: &str, view: F) -> MetastoreResult<T>
     where F: FnOnce(&FileBackedIndex) -> MetastoreResult<T> {
-        let locked_index = self.get_locked_index(index_id).await?;
+        let locked_index = self.get_read_locked_index(index_id).await?;
         view(&locked_index)
     }
 
-    /// Returns a valid metadataset that is locked.
+    /// Returns a valid metadataset that is write-locked.
     ///
     /// This function guarantees that the metadataset has not been
     /// marked as discarded.
-    async fn get_locked_index(
+    async fn get_write_locked_index(
         &self,
         index_id: &str,
-    ) -> MetastoreResult<OwnedMutexGuard<FileBackedIndex>> {
+    ) -> MetastoreResult<OwnedRwLockWriteGuard<FileBackedIndex>> {
         loop {
-            let index_mutex = self.index(index_id).await?;
-            let index_lock = index_mutex.lock_owned().await;
-            if !index_lock.discarded {
-                return Ok(index_lock);
+            let index_rwlock = self.index(index_id).await?;
+            let index_guard = index_rwlock.write_owned().await;
+            if !index_guard.discarded {
+                return Ok(index_guard);
+            }
+        }
+    }
+
+    /// Returns a valid metadataset that is read-locked. Concurrent readers of the same index do
+    /// not block each other.
+    ///
+    /// This function guarantees that the metadataset has not been
+    /// marked as discarded.
+    async fn get_read_locked_index(
+        &self,
+        index_id: &str,
+    ) -> MetastoreResult<OwnedRwLockReadGuard<FileBackedIndex>> {
+        loop {
+            let index_rwlock = self.index(index_id).await?;
+            let index_guard = index_rwlock.read_owned().await;
+            if !index_guard.discarded {
+                return Ok(index_guard);
             }
         }
     }
@@ -278,13 +301,13 @@ impl FileBackedMetastore {
     /// a fetch to the storage will be initiated and might trigger an error.
     ///
     /// For a given index_id, only copies of the same index_view are returned.
-    async fn index(&self, index_id: &str) -> MetastoreResult<Arc<Mutex<FileBackedIndex>>> {
+    async fn index(&self, index_id: &str) -> MetastoreResult<Arc<RwLock<FileBackedIndex>>> {
         {
             // Happy path!
             // If the object is already in our cache then we just return a copy
             let per_index_metastores_r = self.per_index_metastores.read().await;
             if let Some(index_state) = per_index_metastores_r.get(index_id) {
-                return get_index_mutex(index_id, index_state).await;
+                return get_index_rwlock(index_id, index_state).await;
             }
         }
         // At this point we do not hold our mutex, so we need to do a little dance
@@ -302,10 +325,10 @@ impl FileBackedMetastore {
         // the map. We want to avoid two copies to exist in the application, so we keep only
         // one.
         if let Some(index_state) = per_index_metastores_wlock.get(index_id) {
-            return get_index_mutex(index_id, index_state).await;
+            return get_index_rwlock(index_id, index_state).await;
         }
 
-        // We need to instantiate a `LazyFileBackedIndex` that will hold the mutex
+        // We need to instantiate a `LazyFileBackedIndex` that will hold the lock
         // and take care of spawning the polling if needed.
         let index = index_result?;
         let lazy_index = LazyFileBackedIndex::new(
@@ -314,9 +337,9 @@ impl FileBackedMetastore {
             self.polling_interval_opt,
             Some(index),
         );
-        let index_mutex = lazy_index.get().await?;
+        let index_rwlock = lazy_index.get().await?;
         per_index_metastores_wlock.insert(index_id.to_string(), IndexState::Alive(lazy_index));
-        Ok(index_mutex)
+        Ok(index_rwlock)
     }
 
     /// Returns the list of splits for the given request.
@@ -575,6 +598,21 @@ impl MetastoreService for FileBackedMetastore {
         Ok(EmptyResponse {})
     }
 
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let patch = request.patch();
+        let index_uid: IndexUid = request.index_uid.into();
+
+        self.mutate(index_uid, |index| {
+            index.update_split_metadata(&request.split_id, &patch)?;
+            Ok(MutationOccurred::Yes(EmptyResponse {}))
+        })
+        .await?;
+        Ok(EmptyResponse {})
+    }
+
     async fn add_source(&mut self, request: AddSourceRequest) -> MetastoreResult<EmptyResponse> {
         let source_config = request.deserialize_source_config()?;
         let index_uid = request.index_uid.into();
@@ -881,14 +919,56 @@ impl MetastoreService for FileBackedMetastore {
         let response = ListDeleteTasksResponse { delete_tasks };
         Ok(response)
     }
+
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> MetastoreResult<IndexSnapshot> {
+        let index_uid: IndexUid = request.index_uid.into();
+
+        let snapshot = self
+            .mutate(index_uid, |index| {
+                index.create_snapshot().map(MutationOccurred::Yes)
+            })
+            .await?;
+        Ok(snapshot)
+    }
+
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> MetastoreResult<ListIndexSnapshotsResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+
+        let snapshots = self
+            .read(index_uid, |index| Ok(index.list_snapshots()))
+            .await?;
+        let response = ListIndexSnapshotsResponse { snapshots };
+        Ok(response)
+    }
+
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+
+        self.mutate(index_uid, |index| {
+            index
+                .delete_snapshot(&request.snapshot_id)
+                .map(MutationOccurred::Yes)
+        })
+        .await?;
+        Ok(EmptyResponse {})
+    }
 }
 
 impl MetastoreServiceExt for FileBackedMetastore {}
 
-async fn get_index_mutex(
+async fn get_index_rwlock(
     index_id: &str,
     index_state: &IndexState,
-) -> MetastoreResult<Arc<Mutex<FileBackedIndex>>> {
+) -> MetastoreResult<Arc<RwLock<FileBackedIndex>>> {
     match index_state {
         IndexState::Alive(lazy_index) => lazy_index.get().await,
         IndexState::Creating => Err(MetastoreError::Internal {
@@ -935,7 +1015,7 @@ async fn get_index_metadata(
 /// - If the given pattern does not contain a `*` char, it matches the exact pattern.
 /// - If the given pattern contains one or more `*`, it matches the regex built from a regex where
 ///   `*` is replaced by `.*`. All other regular expression meta characters are escaped.
-fn build_regex_set_from_patterns(patterns: Vec<String>) -> anyhow::Result<RegexSet> {
+pub(crate) fn build_regex_set_from_patterns(patterns: Vec<String>) -> anyhow::Result<RegexSet> {
     // If there is a match all pattern, no need to go further.
     if patterns.iter().any(|pattern| pattern == "*") {
         return Ok(RegexSet::new([".*".to_string()]).expect("regex compilation shouldn't fail"));
@@ -1261,6 +1341,50 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_file_backed_metastore_concurrent_readers_do_not_block() {
+        let mut metastore = FileBackedMetastore::default_for_test().await;
+
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+        let create_index_response = metastore.create_index(create_index_request).await.unwrap();
+        let index_uid: IndexUid = create_index_response.index_uid.into();
+
+        // Hold a read lock on the index for the duration of the test.
+        let _read_locked_index = metastore
+            .get_read_locked_index(index_uid.index_id())
+            .await
+            .unwrap();
+
+        let index_metadata_request = IndexMetadataRequest::for_index_uid(index_uid.clone());
+        let index_metadata_response = tokio::time::timeout(
+            Duration::from_secs(1),
+            metastore.index_metadata(index_metadata_request),
+        )
+        .await
+        .expect("readers should not block each other")
+        .unwrap();
+        assert_eq!(
+            index_metadata_response
+                .deserialize_index_metadata()
+                .unwrap()
+                .index_uid,
+            index_uid
+        );
+
+        let splits = tokio::time::timeout(
+            Duration::from_secs(1),
+            metastore.list_splits(ListSplitsRequest::try_from_index_uid(index_uid).unwrap()),
+        )
+        .await
+        .expect("readers should not block each other")
+        .unwrap()
+        .collect_splits()
+        .await
+        .unwrap();
+        assert!(splits.is_empty());
+    }
+
     #[tokio::test]
     async fn test_file_backed_metastore_polling() -> MetastoreResult<()> {
         let storage = Arc::new(RamStorage::default());
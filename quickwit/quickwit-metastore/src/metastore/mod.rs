@@ -17,6 +17,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+#[cfg(feature = "dynamodb")]
+pub mod dynamodb_metastore;
+#[cfg(feature = "dynamodb")]
+mod dynamodb_model;
 pub mod file_backed_metastore;
 pub(crate) mod index_metadata;
 #[cfg(feature = "postgres")]
@@ -37,15 +41,16 @@ use quickwit_common::tower::PrometheusMetricsLayer;
 use quickwit_config::{IndexConfig, SourceConfig};
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore::{
-    serde_utils, AddSourceRequest, CreateIndexRequest, DeleteTask, IndexMetadataRequest,
-    IndexMetadataResponse, ListIndexesMetadataResponse, ListSplitsRequest, ListSplitsResponse,
-    MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
-    MetastoreServiceStream, PublishSplitsRequest, StageSplitsRequest,
+    serde_utils, AddSourceRequest, CreateIndexRequest, DeleteTask, EntityKind,
+    IndexMetadataRequest, IndexMetadataResponse, ListIndexesMetadataResponse, ListSplitsRequest,
+    ListSplitsResponse, MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
+    MetastoreServiceStream, PublishSplitsRequest, StageSplitsRequest, UpdateSplitMetadataRequest,
 };
 use quickwit_proto::types::{IndexUid, SplitId};
 use time::OffsetDateTime;
 
 use crate::checkpoint::IndexCheckpointDelta;
+use crate::split_metadata::utc_now_timestamp;
 use crate::{Split, SplitMetadata, SplitState};
 
 /// Splits batch size returned by the stream splits API
@@ -373,6 +378,112 @@ impl PublishSplitsRequestExt for PublishSplitsRequest {
     }
 }
 
+/// Attributes of a published split to correct with
+/// [`MetastoreService::update_split_metadata`]. Only the attributes set are updated.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SplitMetadataPatch {
+    /// Number of documents of the split.
+    pub num_docs: Option<usize>,
+    /// Uncompressed size of the documents of the split.
+    pub uncompressed_docs_size_in_bytes: Option<u64>,
+    /// Lower bound of the time range of the split.
+    pub time_range_start: Option<i64>,
+    /// Upper bound of the time range of the split.
+    pub time_range_end: Option<i64>,
+}
+
+impl SplitMetadataPatch {
+    /// Applies the patch to a split, which must be published, and bumps its update timestamp.
+    pub(crate) fn apply(&self, split: &mut Split) -> MetastoreResult<()> {
+        let split_metadata = &mut split.split_metadata;
+
+        if split.split_state != SplitState::Published {
+            let message = format!(
+                "split `{}` is {:?}: only published splits can be updated",
+                split_metadata.split_id, split.split_state
+            );
+            let entity = EntityKind::Split {
+                split_id: split_metadata.split_id.clone(),
+            };
+            return Err(MetastoreError::FailedPrecondition { entity, message });
+        }
+        if self.time_range_start.is_some() || self.time_range_end.is_some() {
+            let time_range_start_opt = self.time_range_start.or(split_metadata
+                .time_range
+                .as_ref()
+                .map(|time_range| *time_range.start()));
+            let time_range_end_opt = self.time_range_end.or(split_metadata
+                .time_range
+                .as_ref()
+                .map(|time_range| *time_range.end()));
+            let (Some(time_range_start), Some(time_range_end)) =
+                (time_range_start_opt, time_range_end_opt)
+            else {
+                let message = format!(
+                    "split `{}` has no time range: both its start and end must be set",
+                    split_metadata.split_id
+                );
+                return Err(MetastoreError::InvalidArgument { message });
+            };
+            if time_range_start > time_range_end {
+                let message = format!(
+                    "invalid time range `[{time_range_start}, {time_range_end}]` for split `{}`",
+                    split_metadata.split_id
+                );
+                return Err(MetastoreError::InvalidArgument { message });
+            }
+            split_metadata.time_range = Some(time_range_start..=time_range_end);
+        }
+        if let Some(num_docs) = self.num_docs {
+            split_metadata.num_docs = num_docs;
+        }
+        if let Some(uncompressed_docs_size_in_bytes) = self.uncompressed_docs_size_in_bytes {
+            split_metadata.uncompressed_docs_size_in_bytes = uncompressed_docs_size_in_bytes;
+        }
+        split.update_timestamp = utc_now_timestamp();
+        Ok(())
+    }
+}
+
+/// Helper trait to build an [`UpdateSplitMetadataRequest`] and extract its patch.
+pub trait UpdateSplitMetadataRequestExt {
+    /// Creates a new [`UpdateSplitMetadataRequest`] from a [`SplitMetadataPatch`].
+    fn from_patch(
+        index_uid: impl Into<IndexUid>,
+        split_id: impl Into<SplitId>,
+        patch: SplitMetadataPatch,
+    ) -> UpdateSplitMetadataRequest;
+
+    /// Returns the [`SplitMetadataPatch`] carried by an [`UpdateSplitMetadataRequest`].
+    fn patch(&self) -> SplitMetadataPatch;
+}
+
+impl UpdateSplitMetadataRequestExt for UpdateSplitMetadataRequest {
+    fn from_patch(
+        index_uid: impl Into<IndexUid>,
+        split_id: impl Into<SplitId>,
+        patch: SplitMetadataPatch,
+    ) -> UpdateSplitMetadataRequest {
+        Self {
+            index_uid: index_uid.into().into(),
+            split_id: split_id.into(),
+            num_docs: patch.num_docs.map(|num_docs| num_docs as u64),
+            uncompressed_docs_size_in_bytes: patch.uncompressed_docs_size_in_bytes,
+            time_range_start: patch.time_range_start,
+            time_range_end: patch.time_range_end,
+        }
+    }
+
+    fn patch(&self) -> SplitMetadataPatch {
+        SplitMetadataPatch {
+            num_docs: self.num_docs.map(|num_docs| num_docs as usize),
+            uncompressed_docs_size_in_bytes: self.uncompressed_docs_size_in_bytes,
+            time_range_start: self.time_range_start,
+            time_range_end: self.time_range_end,
+        }
+    }
+}
+
 impl ListSplitsResponseExt for ListSplitsResponse {
     fn empty() -> Self {
         Self {
@@ -408,6 +519,10 @@ pub struct ListSplitsQuery {
     /// A specific split state(s) to filter by.
     pub split_states: Vec<SplitState>,
 
+    /// A specific set of split ID(s) to filter by.
+    #[serde(default)]
+    pub split_ids: Vec<SplitId>,
+
     /// A specific set of tag(s) to filter by.
     pub tags: Option<TagFilterAst>,
 
@@ -440,6 +555,7 @@ impl ListSplitsQuery {
             limit: None,
             offset: None,
             split_states: Vec::new(),
+            split_ids: Vec::new(),
             tags: None,
             time_range: Default::default(),
             delete_opstamp: Default::default(),
@@ -464,6 +580,7 @@ impl ListSplitsQuery {
             limit: None,
             offset: None,
             split_states: Vec::new(),
+            split_ids: Vec::new(),
             tags: None,
             time_range: Default::default(),
             delete_opstamp: Default::default(),
@@ -498,6 +615,12 @@ impl ListSplitsQuery {
         self
     }
 
+    /// Select splits which have any of the given split IDs.
+    pub fn with_split_ids(mut self, split_ids: impl IntoIterator<Item = SplitId>) -> Self {
+        self.split_ids.extend(split_ids);
+        self
+    }
+
     /// Select splits which match the given tag filter.
     pub fn with_tags_filter(mut self, tags: TagFilterAst) -> Self {
         self.tags = Some(tags);
@@ -808,4 +931,57 @@ mod tests {
         let response = ListIndexesMetadataResponse::empty();
         assert_eq!(response.deserialize_indexes_metadata().unwrap(), vec![]);
     }
+
+    #[test]
+    fn test_split_metadata_patch_apply() {
+        let mut split = Split {
+            split_state: SplitState::Published,
+            update_timestamp: 0,
+            publish_timestamp: Some(0),
+            split_metadata: SplitMetadata {
+                split_id: "test-split".to_string(),
+                num_docs: 10,
+                ..Default::default()
+            },
+        };
+        let patch = SplitMetadataPatch {
+            time_range_end: Some(100),
+            ..Default::default()
+        };
+        let error = patch.apply(&mut split).unwrap_err();
+        assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+        assert_eq!(split.split_metadata.time_range, None);
+
+        let patch = SplitMetadataPatch {
+            num_docs: Some(20),
+            time_range_start: Some(10),
+            time_range_end: Some(100),
+            ..Default::default()
+        };
+        patch.apply(&mut split).unwrap();
+        assert_eq!(split.split_metadata.num_docs, 20);
+        assert_eq!(split.split_metadata.time_range, Some(10..=100));
+        assert!(split.update_timestamp > 0);
+
+        split.split_state = SplitState::MarkedForDeletion;
+        let error = patch.apply(&mut split).unwrap_err();
+        assert!(matches!(error, MetastoreError::FailedPrecondition { .. }));
+    }
+
+    #[test]
+    fn test_update_split_metadata_request_patch() {
+        let patch = SplitMetadataPatch {
+            num_docs: Some(42),
+            uncompressed_docs_size_in_bytes: None,
+            time_range_start: Some(-1),
+            time_range_end: Some(1),
+        };
+        let request = UpdateSplitMetadataRequest::from_patch(
+            IndexUid::from_parts("test-index", 0),
+            "test-split",
+            patch.clone(),
+        );
+        assert_eq!(request.split_id, "test-split");
+        assert_eq!(request.patch(), patch);
+    }
 }
@@ -35,16 +35,18 @@ use quickwit_config::{
 };
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore::{
-    AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
-    CreateIndexResponse, DeleteIndexRequest, DeleteQuery, DeleteShardsRequest,
-    DeleteShardsResponse, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse,
-    EntityKind, IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
+    serde_utils, AcquireShardsRequest, AcquireShardsResponse, AddSourceRequest, CreateIndexRequest,
+    CreateIndexResponse, CreateIndexSnapshotRequest, DeleteIndexRequest,
+    DeleteIndexSnapshotRequest, DeleteQuery, DeleteShardsRequest, DeleteShardsResponse,
+    DeleteSourceRequest, DeleteSplitsRequest, DeleteTask, EmptyResponse, EntityKind,
+    IndexMetadataRequest, IndexMetadataResponse, IndexSnapshot, LastDeleteOpstampRequest,
     LastDeleteOpstampResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
-    ListIndexesMetadataRequest, ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse,
-    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
-    MetastoreError, MetastoreResult, MetastoreService, MetastoreServiceClient,
-    MetastoreServiceStream, OpenShardsRequest, OpenShardsResponse, PublishSplitsRequest,
-    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
+    ListIndexSnapshotsRequest, ListIndexSnapshotsResponse, ListIndexesMetadataRequest,
+    ListIndexesMetadataResponse, ListShardsRequest, ListShardsResponse, ListSplitsRequest,
+    ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError,
+    MetastoreResult, MetastoreService, MetastoreServiceClient, MetastoreServiceStream,
+    OpenShardsRequest, OpenShardsResponse, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    StageSplitsRequest, ToggleSourceRequest, UpdateSplitMetadataRequest,
     UpdateSplitsDeleteOpstampRequest, UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::types::IndexUid;
@@ -59,16 +61,19 @@ use tokio::sync::Mutex;
 use tokio_stream::Stream;
 use tracing::log::LevelFilter;
 use tracing::{debug, error, info, instrument, warn};
+use ulid::Ulid;
 
 use super::STREAM_SPLITS_CHUNK_SIZE;
 use crate::checkpoint::IndexCheckpointDelta;
-use crate::metastore::postgresql_model::{PgDeleteTask, PgIndex, PgSplit, Splits, ToTimestampFunc};
+use crate::metastore::postgresql_model::{
+    PgDeleteTask, PgIndex, PgIndexSnapshot, PgSplit, Splits, ToTimestampFunc,
+};
 use crate::metastore::{instrument_metastore, FilterRange, PublishSplitsRequestExt};
 use crate::{
     AddSourceRequestExt, CreateIndexRequestExt, IndexMetadata, IndexMetadataResponseExt,
     ListIndexesMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt, ListSplitsResponseExt,
     MetastoreFactory, MetastoreResolverError, MetastoreServiceExt, Split, SplitMaturity,
-    SplitMetadata, SplitState, StageSplitsRequestExt,
+    SplitMetadata, SplitState, StageSplitsRequestExt, UpdateSplitMetadataRequestExt,
 };
 
 static MIGRATOR: Migrator = sqlx::migrate!("migrations/postgresql");
@@ -271,6 +276,13 @@ fn append_query_filters(sql: &mut SelectStatement, query: &ListSplitsQuery) {
         );
     };
 
+    if !query.split_ids.is_empty() {
+        sql.cond_where(
+            Expr::col(Splits::SplitId)
+                .is_in(query.split_ids.iter().map(|split_id| split_id.as_str())),
+        );
+    };
+
     if let Some(tags) = query.tags.as_ref() {
         sql.cond_where(tags_filter_expression_helper(tags));
     };
@@ -1020,6 +1032,86 @@ impl MetastoreService for PostgresqlMetastore {
         Ok(EmptyResponse {})
     }
 
+    #[instrument(skip(self))]
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let patch = request.patch();
+        let index_uid: IndexUid = request.index_uid.into();
+        let split_id = request.split_id;
+
+        run_with_tx!(self.connection_pool, tx, {
+            let pg_split_opt: Option<PgSplit> = sqlx::query_as::<_, PgSplit>(
+                r#"
+                SELECT *
+                FROM splits
+                WHERE index_uid = $1 AND split_id = $2
+                FOR UPDATE
+                "#,
+            )
+            .bind(index_uid.to_string())
+            .bind(&split_id)
+            .fetch_optional(tx.as_mut())
+            .await
+            .map_err(|error| convert_sqlx_err(index_uid.index_id(), error))?;
+
+            let Some(pg_split) = pg_split_opt else {
+                if index_opt_for_uid(tx.as_mut(), index_uid.clone())
+                    .await?
+                    .is_none()
+                {
+                    return Err(MetastoreError::NotFound(EntityKind::Index {
+                        index_id: index_uid.index_id().to_string(),
+                    }));
+                }
+                return Err(MetastoreError::NotFound(EntityKind::Split { split_id }));
+            };
+            let mut split: Split = pg_split.try_into()?;
+            patch.apply(&mut split)?;
+
+            let split_metadata = &split.split_metadata;
+            let split_metadata_json = serde_json::to_string(split_metadata).map_err(|error| {
+                MetastoreError::JsonSerializeError {
+                    struct_name: "SplitMetadata".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+            let time_range_start = split_metadata
+                .time_range
+                .as_ref()
+                .map(|time_range| *time_range.start());
+            let time_range_end = split_metadata
+                .time_range
+                .as_ref()
+                .map(|time_range| *time_range.end());
+
+            sqlx::query(
+                r#"
+                UPDATE splits
+                SET
+                    split_metadata_json = $3,
+                    time_range_start = $4,
+                    time_range_end = $5,
+                    update_timestamp = (CURRENT_TIMESTAMP AT TIME ZONE 'UTC')
+                WHERE index_uid = $1 AND split_id = $2
+                "#,
+            )
+            .bind(index_uid.to_string())
+            .bind(&split_id)
+            .bind(split_metadata_json)
+            .bind(time_range_start)
+            .bind(time_range_end)
+            .execute(tx.as_mut())
+            .await
+            .map_err(|error| convert_sqlx_err(index_uid.index_id(), error))?;
+
+            info!(index_id=%index_uid.index_id(), split_id=%split_id, "Updated split metadata.");
+            Ok(())
+        })?;
+        Ok(EmptyResponse {})
+    }
+
     #[instrument(skip(self))]
     async fn index_metadata(
         &mut self,
@@ -1282,6 +1374,97 @@ impl MetastoreService for PostgresqlMetastore {
         Ok(response)
     }
 
+    #[instrument(skip(self))]
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> MetastoreResult<IndexSnapshot> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let snapshot_id = Ulid::new().to_string();
+        run_with_tx!(self.connection_pool, tx, {
+            let index_id = index_uid.index_id();
+            // Locks the index row so that no split is published while the snapshot is created.
+            let index_metadata = index_metadata(tx, index_id).await?;
+            if index_metadata.index_uid != index_uid {
+                return Err(MetastoreError::NotFound(EntityKind::Index {
+                    index_id: index_id.to_string(),
+                }));
+            }
+            let index_checkpoint_json = serde_utils::to_json_str(&index_metadata.checkpoint)?;
+            let split_ids: Vec<String> = sqlx::query_scalar(
+                r#"
+                SELECT split_id
+                FROM splits
+                WHERE index_uid = $1 AND split_state = $2
+                ORDER BY split_id
+                "#,
+            )
+            .bind(index_uid.to_string())
+            .bind(SplitState::Published.as_str())
+            .fetch_all(tx.as_mut())
+            .await?;
+            let pg_snapshot: PgIndexSnapshot = sqlx::query_as::<_, PgIndexSnapshot>(
+                r#"
+                INSERT INTO index_snapshots (index_uid, snapshot_id, split_ids, index_checkpoint_json)
+                VALUES ($1, $2, $3, $4)
+                RETURNING *
+                "#,
+            )
+            .bind(index_uid.to_string())
+            .bind(&snapshot_id)
+            .bind(&split_ids)
+            .bind(&index_checkpoint_json)
+            .fetch_one(tx.as_mut())
+            .await?;
+            Ok(pg_snapshot.into())
+        })
+    }
+
+    #[instrument(skip(self))]
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> MetastoreResult<ListIndexSnapshotsResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let pg_snapshots: Vec<PgIndexSnapshot> = sqlx::query_as::<_, PgIndexSnapshot>(
+            r#"
+                SELECT * FROM index_snapshots
+                WHERE index_uid = $1
+                ORDER BY create_timestamp ASC, snapshot_id ASC
+                "#,
+        )
+        .bind(index_uid.to_string())
+        .fetch_all(&self.connection_pool)
+        .await?;
+        let snapshots = pg_snapshots.into_iter().map(IndexSnapshot::from).collect();
+        Ok(ListIndexSnapshotsResponse { snapshots })
+    }
+
+    #[instrument(skip(self))]
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> MetastoreResult<EmptyResponse> {
+        let index_uid: IndexUid = request.index_uid.into();
+        let query_result = sqlx::query(
+            r#"
+                DELETE FROM index_snapshots
+                WHERE index_uid = $1 AND snapshot_id = $2
+                "#,
+        )
+        .bind(index_uid.to_string())
+        .bind(&request.snapshot_id)
+        .execute(&self.connection_pool)
+        .await?;
+        if query_result.rows_affected() == 0 {
+            return Err(MetastoreError::NotFound(EntityKind::IndexSnapshot {
+                index_id: index_uid.index_id().to_string(),
+                snapshot_id: request.snapshot_id,
+            }));
+        }
+        Ok(EmptyResponse {})
+    }
+
     async fn open_shards(
         &mut self,
         _request: OpenShardsRequest,
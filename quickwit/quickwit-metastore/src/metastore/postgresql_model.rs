@@ -20,7 +20,9 @@
 use std::convert::TryInto;
 use std::str::FromStr;
 
-use quickwit_proto::metastore::{DeleteQuery, DeleteTask, MetastoreError, MetastoreResult};
+use quickwit_proto::metastore::{
+    DeleteQuery, DeleteTask, IndexSnapshot, MetastoreError, MetastoreResult,
+};
 use quickwit_proto::types::IndexUid;
 use sea_query::{Iden, Write};
 use tracing::error;
@@ -210,3 +212,31 @@ impl TryInto<DeleteTask> for PgDeleteTask {
         })
     }
 }
+
+/// A model structure for handling index snapshots in a database.
+#[derive(sqlx::FromRow)]
+pub struct PgIndexSnapshot {
+    /// Index UID.
+    #[sqlx(try_from = "String")]
+    pub index_uid: IndexUid,
+    /// Snapshot ID.
+    pub snapshot_id: String,
+    /// Create timestamp.
+    pub create_timestamp: sqlx::types::time::PrimitiveDateTime,
+    /// IDs of the splits published when the snapshot was created.
+    pub split_ids: Vec<String>,
+    /// Index checkpoint serialized as a JSON string.
+    pub index_checkpoint_json: String,
+}
+
+impl From<PgIndexSnapshot> for IndexSnapshot {
+    fn from(pg_snapshot: PgIndexSnapshot) -> Self {
+        IndexSnapshot {
+            snapshot_id: pg_snapshot.snapshot_id,
+            index_uid: pg_snapshot.index_uid.into(),
+            create_timestamp: pg_snapshot.create_timestamp.assume_utc().unix_timestamp(),
+            split_ids: pg_snapshot.split_ids,
+            index_checkpoint_json: pg_snapshot.index_checkpoint_json,
+        }
+    }
+}
@@ -28,6 +28,8 @@ use quickwit_config::{MetastoreBackend, MetastoreConfig, MetastoreConfigs};
 use quickwit_proto::metastore::MetastoreServiceClient;
 use quickwit_storage::StorageResolver;
 
+#[cfg(feature = "dynamodb")]
+use crate::metastore::dynamodb_metastore::DynamoDbMetastoreFactory;
 use crate::metastore::file_backed_metastore::FileBackedMetastoreFactory;
 #[cfg(feature = "postgres")]
 use crate::metastore::postgresql_metastore::PostgresqlMetastoreFactory;
@@ -62,6 +64,7 @@ impl MetastoreResolver {
     ) -> Result<MetastoreServiceClient, MetastoreResolverError> {
         let backend = match uri.protocol() {
             Protocol::Azure => MetastoreBackend::File,
+            Protocol::DynamoDB => MetastoreBackend::DynamoDB,
             Protocol::File => MetastoreBackend::File,
             Protocol::Ram => MetastoreBackend::File,
             Protocol::S3 => MetastoreBackend::File,
@@ -134,6 +137,31 @@ impl MetastoreResolver {
                 PostgresMetastoreConfig::default().into(),
             );
         }
+        #[cfg(feature = "dynamodb")]
+        {
+            builder = builder.register(
+                DynamoDbMetastoreFactory::default(),
+                metastore_configs
+                    .find_dynamodb()
+                    .cloned()
+                    .unwrap_or_default()
+                    .into(),
+            );
+        }
+        #[cfg(not(feature = "dynamodb"))]
+        {
+            use quickwit_config::DynamoDbMetastoreConfig;
+
+            use crate::UnsupportedMetastore;
+
+            builder = builder.register(
+                UnsupportedMetastore::new(
+                    MetastoreBackend::DynamoDB,
+                    "Quickwit was compiled without the `dynamodb` feature.",
+                ),
+                DynamoDbMetastoreConfig::default().into(),
+            );
+        }
         builder
             .build()
             .expect("Metastore factory and config backends should match.")
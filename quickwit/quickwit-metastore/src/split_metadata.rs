@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::ops::{Range, RangeInclusive};
 use std::path::PathBuf;
@@ -25,6 +25,7 @@ use std::str::FromStr;
 use std::time::Duration;
 
 use bytesize::ByteSize;
+use quickwit_common::geo::GeoBoundingBox;
 use quickwit_proto::types::IndexUid;
 use serde::{Deserialize, Serialize};
 use serde_with::{serde_as, DurationMilliSeconds};
@@ -119,6 +120,11 @@ pub struct SplitMetadata {
     /// [`MAX_VALUES_PER_TAG_FIELD`]: https://github.com/quickwit-oss/quickwit/blob/main/quickwit-indexing/src/actors/packager.rs#L36
     pub tags: BTreeSet<String>,
 
+    /// Bounding box of the points of each `geo_point` field of the split. Fields without any
+    /// point are absent, and so are all fields for splits created before these boxes were
+    /// recorded.
+    pub geo_bounding_boxes: BTreeMap<String, GeoBoundingBox>,
+
     /// Contains the range of bytes of the footer that needs to be downloaded
     /// in order to open a split.
     ///
@@ -172,6 +178,9 @@ impl fmt::Debug for SplitMetadata {
             tags_str.push('}');
             debug_struct.field("tags", &tags_str);
         }
+        if !self.geo_bounding_boxes.is_empty() {
+            debug_struct.field("geo_bounding_boxes", &self.geo_bounding_boxes);
+        }
         debug_struct.field("footer_offsets", &self.footer_offsets);
         debug_struct.field("delete_opstamp", &self.delete_opstamp);
         debug_struct.field("num_merge_ops", &self.num_merge_ops);
@@ -17,9 +17,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::{Range, RangeInclusive};
 
+use quickwit_common::geo::GeoBoundingBox;
 use quickwit_proto::types::IndexUid;
 use serde::{Deserialize, Serialize};
 
@@ -77,6 +78,12 @@ pub(crate) struct SplitMetadataV0_6 {
     /// A set of tags for categorizing and searching group of splits.
     pub tags: BTreeSet<String>,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = Object)]
+    /// Bounding box of the points of each `geo_point` field of the split.
+    pub geo_bounding_boxes: BTreeMap<String, GeoBoundingBox>,
+
     #[schema(value_type = Object)]
     /// Contains the range of bytes of the footer that needs to be downloaded
     /// in order to open a split.
@@ -124,6 +131,7 @@ impl From<SplitMetadataV0_6> for SplitMetadata {
             create_timestamp: v6.create_timestamp,
             maturity: v6.maturity,
             tags: v6.tags,
+            geo_bounding_boxes: v6.geo_bounding_boxes,
             footer_offsets: v6.footer_offsets,
             num_merge_ops: v6.num_merge_ops,
         }
@@ -145,6 +153,7 @@ impl From<SplitMetadata> for SplitMetadataV0_6 {
             create_timestamp: split.create_timestamp,
             maturity: split.maturity,
             tags: split.tags,
+            geo_bounding_boxes: split.geo_bounding_boxes,
             footer_offsets: split.footer_offsets,
             num_merge_ops: split.num_merge_ops,
         }
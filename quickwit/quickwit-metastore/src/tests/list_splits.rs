@@ -894,6 +894,22 @@ pub async fn test_metastore_list_splits<MetastoreToTest: MetastoreServiceExt + D
         let split_ids = collect_split_ids(&splits);
         assert_eq!(split_ids, &[&split_id_2, &split_id_3]);
 
+        // Test split IDs filter
+        let query = ListSplitsQuery::for_index(index_uid.clone()).with_split_ids([
+            split_id_2.clone(),
+            split_id_5.clone(),
+            "unknown".to_string(),
+        ]);
+        let splits = metastore
+            .list_splits(ListSplitsRequest::try_from_list_splits_query(query).unwrap())
+            .await
+            .unwrap()
+            .collect_splits()
+            .await
+            .unwrap();
+        let split_ids = collect_split_ids(&splits);
+        assert_eq!(split_ids, &[&split_id_2, &split_id_5]);
+
         cleanup_index(&mut metastore, index_uid).await;
     }
 }
@@ -33,6 +33,7 @@ pub(crate) mod delete_task;
 pub(crate) mod index;
 pub(crate) mod list_splits;
 pub(crate) mod shard;
+pub(crate) mod snapshot;
 pub(crate) mod source;
 pub(crate) mod split;
 
@@ -271,6 +272,12 @@ macro_rules! metastore_test_suite {
                 $crate::tests::split::test_metastore_delete_splits::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_update_split_metadata() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::split::test_metastore_update_split_metadata::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             async fn test_metastore_stream_splits() {
                 let _ = tracing_subscriber::fmt::try_init();
@@ -344,6 +351,18 @@ macro_rules! metastore_test_suite {
                 $crate::tests::delete_task::test_metastore_list_delete_tasks::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_create_index_snapshot() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::snapshot::test_metastore_create_index_snapshot::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_delete_index_snapshot() {
+                let _ = tracing_subscriber::fmt::try_init();
+                $crate::tests::snapshot::test_metastore_delete_index_snapshot::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             async fn test_metastore_list_stale_splits() {
                 let _ = tracing_subscriber::fmt::try_init();
@@ -29,7 +29,7 @@ use super::DefaultForTest;
 use crate::tests::cleanup_index;
 use crate::{AddSourceRequestExt, CreateIndexRequestExt, FileBackedMetastore, MetastoreServiceExt};
 
-// TODO: Remove when `PostgresqlMetastore` implements Shard API.
+// TODO: Remove when `PostgresqlMetastore` and `DynamoDbMetastore` implement Shard API.
 pub trait RunTests {
     fn run_open_shards_test() -> bool {
         true
@@ -42,6 +42,17 @@ pub trait RunTests {
 
 impl RunTests for FileBackedMetastore {}
 
+#[cfg(feature = "dynamodb")]
+impl RunTests for crate::DynamoDbMetastore {
+    fn run_other_tests() -> bool {
+        false
+    }
+
+    fn run_open_shards_test() -> bool {
+        false
+    }
+}
+
 #[cfg(feature = "postgres")]
 impl RunTests for crate::PostgresqlMetastore {
     fn run_other_tests() -> bool {
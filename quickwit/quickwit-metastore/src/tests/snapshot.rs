@@ -0,0 +1,197 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use quickwit_common::rand::append_random_suffix;
+use quickwit_config::IndexConfig;
+use quickwit_proto::metastore::{
+    CreateIndexRequest, CreateIndexSnapshotRequest, DeleteIndexSnapshotRequest, EntityKind,
+    ListIndexSnapshotsRequest, MetastoreError, PublishSplitsRequest, StageSplitsRequest,
+};
+use quickwit_proto::types::IndexUid;
+
+use super::DefaultForTest;
+use crate::tests::cleanup_index;
+use crate::{CreateIndexRequestExt, MetastoreServiceExt, SplitMetadata, StageSplitsRequestExt};
+
+async fn stage_and_publish_split(
+    metastore: &mut dyn MetastoreServiceExt,
+    index_uid: &IndexUid,
+    split_id: &str,
+) {
+    let split_metadata = SplitMetadata {
+        split_id: split_id.to_string(),
+        index_uid: index_uid.clone(),
+        ..Default::default()
+    };
+    let stage_splits_request =
+        StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata).unwrap();
+    metastore.stage_splits(stage_splits_request).await.unwrap();
+
+    let publish_splits_request = PublishSplitsRequest {
+        index_uid: index_uid.to_string(),
+        staged_split_ids: vec![split_id.to_string()],
+        ..Default::default()
+    };
+    metastore
+        .publish_splits(publish_splits_request)
+        .await
+        .unwrap();
+}
+
+pub async fn test_metastore_create_index_snapshot<
+    MetastoreToTest: MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-create-index-snapshot");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    // Create a snapshot of a non-existent index.
+    let create_snapshot_request = CreateIndexSnapshotRequest {
+        index_uid: IndexUid::new_with_random_ulid(&index_id).to_string(),
+    };
+    let error = metastore
+        .create_index_snapshot(create_snapshot_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let split_id_1 = format!("{index_id}--split-1");
+    stage_and_publish_split(&mut metastore, &index_uid, &split_id_1).await;
+
+    // A staged split is not part of the snapshot.
+    let split_id_2 = format!("{index_id}--split-2");
+    let split_metadata_2 = SplitMetadata {
+        split_id: split_id_2.clone(),
+        index_uid: index_uid.clone(),
+        ..Default::default()
+    };
+    let stage_splits_request =
+        StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata_2).unwrap();
+    metastore.stage_splits(stage_splits_request).await.unwrap();
+
+    let create_snapshot_request = CreateIndexSnapshotRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let snapshot_1 = metastore
+        .create_index_snapshot(create_snapshot_request.clone())
+        .await
+        .unwrap();
+    assert!(!snapshot_1.snapshot_id.is_empty());
+    assert_eq!(snapshot_1.index_uid, index_uid.to_string());
+    assert_eq!(snapshot_1.split_ids, vec![split_id_1.clone()]);
+    assert!(!snapshot_1.index_checkpoint_json.is_empty());
+
+    let split_id_3 = format!("{index_id}--split-3");
+    stage_and_publish_split(&mut metastore, &index_uid, &split_id_3).await;
+
+    let snapshot_2 = metastore
+        .create_index_snapshot(create_snapshot_request)
+        .await
+        .unwrap();
+    assert_ne!(snapshot_2.snapshot_id, snapshot_1.snapshot_id);
+    assert_eq!(snapshot_2.split_ids, vec![split_id_1, split_id_3]);
+
+    let list_snapshots_request = ListIndexSnapshotsRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let mut snapshots = metastore
+        .list_index_snapshots(list_snapshots_request)
+        .await
+        .unwrap()
+        .snapshots;
+    snapshots.sort_by(|left, right| left.snapshot_id.cmp(&right.snapshot_id));
+
+    let mut expected_snapshots = vec![snapshot_1, snapshot_2];
+    expected_snapshots.sort_by(|left, right| left.snapshot_id.cmp(&right.snapshot_id));
+    assert_eq!(snapshots, expected_snapshots);
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
+pub async fn test_metastore_delete_index_snapshot<
+    MetastoreToTest: MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-delete-index-snapshot");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request = CreateIndexRequest::try_from_index_config(index_config).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let split_id = format!("{index_id}--split");
+    stage_and_publish_split(&mut metastore, &index_uid, &split_id).await;
+
+    let create_snapshot_request = CreateIndexSnapshotRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let snapshot = metastore
+        .create_index_snapshot(create_snapshot_request)
+        .await
+        .unwrap();
+
+    let delete_snapshot_request = DeleteIndexSnapshotRequest {
+        index_uid: index_uid.to_string(),
+        snapshot_id: snapshot.snapshot_id,
+    };
+    metastore
+        .delete_index_snapshot(delete_snapshot_request.clone())
+        .await
+        .unwrap();
+
+    let list_snapshots_request = ListIndexSnapshotsRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let snapshots = metastore
+        .list_index_snapshots(list_snapshots_request)
+        .await
+        .unwrap()
+        .snapshots;
+    assert!(snapshots.is_empty());
+
+    // Delete a non-existent snapshot.
+    let error = metastore
+        .delete_index_snapshot(delete_snapshot_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::IndexSnapshot { .. })
+    ));
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
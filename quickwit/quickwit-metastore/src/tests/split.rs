@@ -25,7 +25,7 @@ use quickwit_config::IndexConfig;
 use quickwit_proto::metastore::{
     CreateIndexRequest, DeleteSplitsRequest, EntityKind, IndexMetadataRequest, ListSplitsRequest,
     ListStaleSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, PublishSplitsRequest,
-    StageSplitsRequest, UpdateSplitsDeleteOpstampRequest,
+    StageSplitsRequest, UpdateSplitMetadataRequest, UpdateSplitsDeleteOpstampRequest,
 };
 use quickwit_proto::types::{IndexUid, Position};
 use time::OffsetDateTime;
@@ -38,7 +38,8 @@ use crate::metastore::MetastoreServiceStreamSplitsExt;
 use crate::tests::{cleanup_index, collect_split_ids};
 use crate::{
     CreateIndexRequestExt, IndexMetadataResponseExt, ListSplitsQuery, ListSplitsRequestExt,
-    ListSplitsResponseExt, MetastoreServiceExt, SplitMetadata, SplitState, StageSplitsRequestExt,
+    ListSplitsResponseExt, MetastoreServiceExt, SplitMetadata, SplitMetadataPatch, SplitState,
+    StageSplitsRequestExt, UpdateSplitMetadataRequestExt,
 };
 
 pub async fn test_metastore_publish_splits_empty_splits_array_is_allowed<
@@ -1451,6 +1452,146 @@ pub async fn test_metastore_delete_splits<MetastoreToTest: MetastoreServiceExt +
     cleanup_index(&mut metastore, index_uid).await;
 }
 
+pub async fn test_metastore_update_split_metadata<
+    MetastoreToTest: MetastoreServiceExt + DefaultForTest,
+>() {
+    let mut metastore = MetastoreToTest::default_for_test().await;
+
+    let index_id = append_random_suffix("test-update-split-metadata");
+    let index_uri = format!("ram:///indexes/{index_id}");
+    let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+    let create_index_request =
+        CreateIndexRequest::try_from_index_config(index_config.clone()).unwrap();
+    let index_uid: IndexUid = metastore
+        .create_index(create_index_request)
+        .await
+        .unwrap()
+        .index_uid
+        .into();
+
+    let patch = SplitMetadataPatch {
+        num_docs: Some(42),
+        uncompressed_docs_size_in_bytes: Some(4_200),
+        time_range_start: Some(5),
+        time_range_end: None,
+    };
+
+    // Check error if index does not exist.
+    let update_split_metadata_request = UpdateSplitMetadataRequest::from_patch(
+        IndexUid::new_with_random_ulid(&index_id),
+        "split-not-found",
+        patch.clone(),
+    );
+    let error = metastore
+        .update_split_metadata(update_split_metadata_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Index { .. })
+    ));
+
+    // Check error if split does not exist.
+    let update_split_metadata_request =
+        UpdateSplitMetadataRequest::from_patch(index_uid.clone(), "split-not-found", patch.clone());
+    let error = metastore
+        .update_split_metadata(update_split_metadata_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::NotFound(EntityKind::Split { .. })
+    ));
+
+    let split_id = format!("{index_id}--split");
+    let split_metadata = SplitMetadata {
+        split_id: split_id.clone(),
+        index_uid: index_uid.clone(),
+        num_docs: 10,
+        uncompressed_docs_size_in_bytes: 1_000,
+        time_range: Some(0..=99),
+        ..Default::default()
+    };
+    let stage_splits_request =
+        StageSplitsRequest::try_from_split_metadata(index_uid.clone(), split_metadata.clone())
+            .unwrap();
+    metastore.stage_splits(stage_splits_request).await.unwrap();
+
+    // Check error if split is not published.
+    let update_split_metadata_request =
+        UpdateSplitMetadataRequest::from_patch(index_uid.clone(), &split_id, patch.clone());
+    let error = metastore
+        .update_split_metadata(update_split_metadata_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        MetastoreError::FailedPrecondition {
+            entity: EntityKind::Split { .. },
+            ..
+        }
+    ));
+
+    let publish_splits_request = PublishSplitsRequest {
+        index_uid: index_uid.clone().to_string(),
+        staged_split_ids: vec![split_id.clone()],
+        ..Default::default()
+    };
+    metastore
+        .publish_splits(publish_splits_request)
+        .await
+        .unwrap();
+
+    // Check error if the time range becomes invalid.
+    let invalid_patch = SplitMetadataPatch {
+        time_range_start: Some(100),
+        ..Default::default()
+    };
+    let update_split_metadata_request =
+        UpdateSplitMetadataRequest::from_patch(index_uid.clone(), &split_id, invalid_patch);
+    let error = metastore
+        .update_split_metadata(update_split_metadata_request)
+        .await
+        .unwrap_err();
+    assert!(matches!(error, MetastoreError::InvalidArgument { .. }));
+
+    let update_split_metadata_request =
+        UpdateSplitMetadataRequest::from_patch(index_uid.clone(), &split_id, patch);
+    metastore
+        .update_split_metadata(update_split_metadata_request)
+        .await
+        .unwrap();
+
+    let splits = metastore
+        .list_splits(ListSplitsRequest::try_from_index_uid(index_uid.clone()).unwrap())
+        .await
+        .unwrap()
+        .collect_splits()
+        .await
+        .unwrap();
+    assert_eq!(splits.len(), 1);
+
+    let split = &splits[0];
+    assert_eq!(split.split_state, SplitState::Published);
+    assert_eq!(split.split_metadata.num_docs, 42);
+    assert_eq!(split.split_metadata.uncompressed_docs_size_in_bytes, 4_200);
+    assert_eq!(split.split_metadata.time_range, Some(5..=99));
+
+    // Check that the time range filter takes the new bounds into account.
+    let query = ListSplitsQuery::for_index(index_uid.clone()).with_time_range_end_lt(5);
+    let splits = metastore
+        .list_splits(ListSplitsRequest::try_from_list_splits_query(query).unwrap())
+        .await
+        .unwrap()
+        .collect_splits()
+        .await
+        .unwrap();
+    assert!(splits.is_empty());
+
+    cleanup_index(&mut metastore, index_uid).await;
+}
+
 pub async fn test_metastore_split_update_timestamp<
     MetastoreToTest: MetastoreServiceExt + DefaultForTest,
 >() {
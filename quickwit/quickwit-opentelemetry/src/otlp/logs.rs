@@ -114,7 +114,7 @@ doc_mapping:
   # tag_fields: [service_name]
 
 indexing_settings:
-  commit_timeout_secs: 5
+  commit_timeout: 5s
 
 search_settings:
   default_search_fields: [body.message]
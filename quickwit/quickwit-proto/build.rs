@@ -134,6 +134,11 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .out_dir("src/codegen/quickwit")
         .compile_with_config(prost_config, &["protos/quickwit/search.proto"], &["protos"])?;
 
+    // Index changes service.
+    tonic_build::configure()
+        .out_dir("src/codegen/quickwit")
+        .compile(&["protos/quickwit/index_changes.proto"], &["protos"])?;
+
     // Jaeger proto
     let protos = find_protos("protos/third-party/jaeger");
 
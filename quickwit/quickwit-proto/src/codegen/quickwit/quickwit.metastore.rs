@@ -122,6 +122,27 @@ pub struct DeleteSplitsRequest {
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateSplitMetadataRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub split_id: ::prost::alloc::string::String,
+    /// If set, replaces the number of documents of the split.
+    #[prost(uint64, optional, tag = "3")]
+    pub num_docs: ::core::option::Option<u64>,
+    /// If set, replaces the uncompressed size of the documents of the split.
+    #[prost(uint64, optional, tag = "4")]
+    pub uncompressed_docs_size_in_bytes: ::core::option::Option<u64>,
+    /// If set, replaces the lower bound of the time range of the split.
+    #[prost(int64, optional, tag = "5")]
+    pub time_range_start: ::core::option::Option<i64>,
+    /// If set, replaces the upper bound of the time range of the split.
+    #[prost(int64, optional, tag = "6")]
+    pub time_range_end: ::core::option::Option<i64>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct AddSourceRequest {
     #[prost(string, tag = "1")]
     pub index_uid: ::prost::alloc::string::String,
@@ -249,6 +270,53 @@ pub struct ListDeleteTasksResponse {
 #[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IndexSnapshot {
+    #[prost(string, tag = "1")]
+    pub snapshot_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(int64, tag = "3")]
+    pub create_timestamp: i64,
+    /// IDs of the splits published when the snapshot was created.
+    #[prost(string, repeated, tag = "4")]
+    pub split_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// JSON serialized `IndexCheckpoint` of the index when the snapshot was created.
+    #[prost(string, tag = "5")]
+    pub index_checkpoint_json: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CreateIndexSnapshotRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexSnapshotsRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListIndexSnapshotsResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub snapshots: ::prost::alloc::vec::Vec<IndexSnapshot>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteIndexSnapshotRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub snapshot_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct OpenShardsRequest {
     #[prost(message, repeated, tag = "1")]
     pub subrequests: ::prost::alloc::vec::Vec<OpenShardsSubrequest>,
@@ -410,6 +478,8 @@ pub enum SourceType {
     Pulsar = 9,
     Vec = 10,
     Void = 11,
+    Push = 12,
+    FileTail = 13,
 }
 impl SourceType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -430,6 +500,8 @@ impl SourceType {
             SourceType::Pulsar => "SOURCE_TYPE_PULSAR",
             SourceType::Vec => "SOURCE_TYPE_VEC",
             SourceType::Void => "SOURCE_TYPE_VOID",
+            SourceType::Push => "SOURCE_TYPE_PUSH",
+            SourceType::FileTail => "SOURCE_TYPE_FILE_TAIL",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -447,6 +519,8 @@ impl SourceType {
             "SOURCE_TYPE_PULSAR" => Some(Self::Pulsar),
             "SOURCE_TYPE_VEC" => Some(Self::Vec),
             "SOURCE_TYPE_VOID" => Some(Self::Void),
+            "SOURCE_TYPE_PUSH" => Some(Self::Push),
+            "SOURCE_TYPE_FILE_TAIL" => Some(Self::FileTail),
             _ => None,
         }
     }
@@ -503,6 +577,11 @@ impl PrometheusLabels<1> for DeleteSplitsRequest {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("delete_splits")])
     }
 }
+impl PrometheusLabels<1> for UpdateSplitMetadataRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("update_split_metadata")])
+    }
+}
 impl PrometheusLabels<1> for AddSourceRequest {
     fn labels(&self) -> OwnedPrometheusLabels<1usize> {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("add_source")])
@@ -552,6 +631,21 @@ impl PrometheusLabels<1> for ListStaleSplitsRequest {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("list_stale_splits")])
     }
 }
+impl PrometheusLabels<1> for CreateIndexSnapshotRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("create_index_snapshot")])
+    }
+}
+impl PrometheusLabels<1> for ListIndexSnapshotsRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("list_index_snapshots")])
+    }
+}
+impl PrometheusLabels<1> for DeleteIndexSnapshotRequest {
+    fn labels(&self) -> OwnedPrometheusLabels<1usize> {
+        OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("delete_index_snapshot")])
+    }
+}
 impl PrometheusLabels<1> for OpenShardsRequest {
     fn labels(&self) -> OwnedPrometheusLabels<1usize> {
         OwnedPrometheusLabels::new([std::borrow::Cow::Borrowed("open_shards")])
@@ -626,6 +720,13 @@ pub trait MetastoreService: std::fmt::Debug + dyn_clone::DynClone + Send + Sync
         &mut self,
         request: DeleteSplitsRequest,
     ) -> crate::metastore::MetastoreResult<EmptyResponse>;
+    /// Updates the metadata of a published split.
+    ///
+    /// Only the fields set in the request are updated.
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
     /// Adds source.
     async fn add_source(
         &mut self,
@@ -671,6 +772,24 @@ pub trait MetastoreService: std::fmt::Debug + dyn_clone::DynClone + Send + Sync
         &mut self,
         request: ListStaleSplitsRequest,
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse>;
+    /// Creates a snapshot of the splits published in an index.
+    ///
+    /// The snapshot only records the split IDs and the index checkpoint. Until the snapshot is deleted,
+    /// the splits it references are not garbage collected.
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<IndexSnapshot>;
+    /// Lists the snapshots of an index.
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexSnapshotsResponse>;
+    /// Deletes a snapshot, allowing its splits to be garbage collected.
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse>;
     /// Shard API
     ///
     /// Note that for the file-backed metastore implementation, the requests are not processed atomically.
@@ -834,6 +953,12 @@ impl MetastoreService for MetastoreServiceClient {
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.inner.delete_splits(request).await
     }
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.update_split_metadata(request).await
+    }
     async fn add_source(
         &mut self,
         request: AddSourceRequest,
@@ -888,6 +1013,24 @@ impl MetastoreService for MetastoreServiceClient {
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse> {
         self.inner.list_stale_splits(request).await
     }
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<IndexSnapshot> {
+        self.inner.create_index_snapshot(request).await
+    }
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexSnapshotsResponse> {
+        self.inner.list_index_snapshots(request).await
+    }
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner.delete_index_snapshot(request).await
+    }
     async fn open_shards(
         &mut self,
         request: OpenShardsRequest,
@@ -984,6 +1127,12 @@ pub mod metastore_service_mock {
         ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
             self.inner.lock().await.delete_splits(request).await
         }
+        async fn update_split_metadata(
+            &mut self,
+            request: super::UpdateSplitMetadataRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.update_split_metadata(request).await
+        }
         async fn add_source(
             &mut self,
             request: super::AddSourceRequest,
@@ -1040,6 +1189,24 @@ pub mod metastore_service_mock {
         ) -> crate::metastore::MetastoreResult<super::ListSplitsResponse> {
             self.inner.lock().await.list_stale_splits(request).await
         }
+        async fn create_index_snapshot(
+            &mut self,
+            request: super::CreateIndexSnapshotRequest,
+        ) -> crate::metastore::MetastoreResult<super::IndexSnapshot> {
+            self.inner.lock().await.create_index_snapshot(request).await
+        }
+        async fn list_index_snapshots(
+            &mut self,
+            request: super::ListIndexSnapshotsRequest,
+        ) -> crate::metastore::MetastoreResult<super::ListIndexSnapshotsResponse> {
+            self.inner.lock().await.list_index_snapshots(request).await
+        }
+        async fn delete_index_snapshot(
+            &mut self,
+            request: super::DeleteIndexSnapshotRequest,
+        ) -> crate::metastore::MetastoreResult<super::EmptyResponse> {
+            self.inner.lock().await.delete_index_snapshot(request).await
+        }
         async fn open_shards(
             &mut self,
             request: super::OpenShardsRequest,
@@ -1227,6 +1394,22 @@ impl tower::Service<DeleteSplitsRequest> for Box<dyn MetastoreService> {
         Box::pin(fut)
     }
 }
+impl tower::Service<UpdateSplitMetadataRequest> for Box<dyn MetastoreService> {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: UpdateSplitMetadataRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.update_split_metadata(request).await };
+        Box::pin(fut)
+    }
+}
 impl tower::Service<AddSourceRequest> for Box<dyn MetastoreService> {
     type Response = EmptyResponse;
     type Error = crate::metastore::MetastoreError;
@@ -1371,6 +1554,54 @@ impl tower::Service<ListStaleSplitsRequest> for Box<dyn MetastoreService> {
         Box::pin(fut)
     }
 }
+impl tower::Service<CreateIndexSnapshotRequest> for Box<dyn MetastoreService> {
+    type Response = IndexSnapshot;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: CreateIndexSnapshotRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.create_index_snapshot(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<ListIndexSnapshotsRequest> for Box<dyn MetastoreService> {
+    type Response = ListIndexSnapshotsResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: ListIndexSnapshotsRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.list_index_snapshots(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<DeleteIndexSnapshotRequest> for Box<dyn MetastoreService> {
+    type Response = EmptyResponse;
+    type Error = crate::metastore::MetastoreError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: DeleteIndexSnapshotRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.delete_index_snapshot(request).await };
+        Box::pin(fut)
+    }
+}
 impl tower::Service<OpenShardsRequest> for Box<dyn MetastoreService> {
     type Response = OpenShardsResponse;
     type Error = crate::metastore::MetastoreError;
@@ -1484,6 +1715,11 @@ struct MetastoreServiceTowerBlock {
         EmptyResponse,
         crate::metastore::MetastoreError,
     >,
+    update_split_metadata_svc: quickwit_common::tower::BoxService<
+        UpdateSplitMetadataRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
     add_source_svc: quickwit_common::tower::BoxService<
         AddSourceRequest,
         EmptyResponse,
@@ -1529,6 +1765,21 @@ struct MetastoreServiceTowerBlock {
         ListSplitsResponse,
         crate::metastore::MetastoreError,
     >,
+    create_index_snapshot_svc: quickwit_common::tower::BoxService<
+        CreateIndexSnapshotRequest,
+        IndexSnapshot,
+        crate::metastore::MetastoreError,
+    >,
+    list_index_snapshots_svc: quickwit_common::tower::BoxService<
+        ListIndexSnapshotsRequest,
+        ListIndexSnapshotsResponse,
+        crate::metastore::MetastoreError,
+    >,
+    delete_index_snapshot_svc: quickwit_common::tower::BoxService<
+        DeleteIndexSnapshotRequest,
+        EmptyResponse,
+        crate::metastore::MetastoreError,
+    >,
     open_shards_svc: quickwit_common::tower::BoxService<
         OpenShardsRequest,
         OpenShardsResponse,
@@ -1563,6 +1814,7 @@ impl Clone for MetastoreServiceTowerBlock {
             publish_splits_svc: self.publish_splits_svc.clone(),
             mark_splits_for_deletion_svc: self.mark_splits_for_deletion_svc.clone(),
             delete_splits_svc: self.delete_splits_svc.clone(),
+            update_split_metadata_svc: self.update_split_metadata_svc.clone(),
             add_source_svc: self.add_source_svc.clone(),
             toggle_source_svc: self.toggle_source_svc.clone(),
             delete_source_svc: self.delete_source_svc.clone(),
@@ -1574,6 +1826,9 @@ impl Clone for MetastoreServiceTowerBlock {
                 .clone(),
             list_delete_tasks_svc: self.list_delete_tasks_svc.clone(),
             list_stale_splits_svc: self.list_stale_splits_svc.clone(),
+            create_index_snapshot_svc: self.create_index_snapshot_svc.clone(),
+            list_index_snapshots_svc: self.list_index_snapshots_svc.clone(),
+            delete_index_snapshot_svc: self.delete_index_snapshot_svc.clone(),
             open_shards_svc: self.open_shards_svc.clone(),
             acquire_shards_svc: self.acquire_shards_svc.clone(),
             delete_shards_svc: self.delete_shards_svc.clone(),
@@ -1637,6 +1892,12 @@ impl MetastoreService for MetastoreServiceTowerBlock {
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.delete_splits_svc.ready().await?.call(request).await
     }
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.update_split_metadata_svc.ready().await?.call(request).await
+    }
     async fn add_source(
         &mut self,
         request: AddSourceRequest,
@@ -1691,6 +1952,24 @@ impl MetastoreService for MetastoreServiceTowerBlock {
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse> {
         self.list_stale_splits_svc.ready().await?.call(request).await
     }
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<IndexSnapshot> {
+        self.create_index_snapshot_svc.ready().await?.call(request).await
+    }
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexSnapshotsResponse> {
+        self.list_index_snapshots_svc.ready().await?.call(request).await
+    }
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.delete_index_snapshot_svc.ready().await?.call(request).await
+    }
     async fn open_shards(
         &mut self,
         request: OpenShardsRequest,
@@ -1806,6 +2085,15 @@ pub struct MetastoreServiceTowerBlockBuilder {
         >,
     >,
     #[allow(clippy::type_complexity)]
+    update_split_metadata_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            UpdateSplitMetadataRequest,
+            EmptyResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
     add_source_layer: Option<
         quickwit_common::tower::BoxLayer<
             Box<dyn MetastoreService>,
@@ -1887,6 +2175,33 @@ pub struct MetastoreServiceTowerBlockBuilder {
         >,
     >,
     #[allow(clippy::type_complexity)]
+    create_index_snapshot_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            CreateIndexSnapshotRequest,
+            IndexSnapshot,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    list_index_snapshots_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            ListIndexSnapshotsRequest,
+            ListIndexSnapshotsResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    delete_index_snapshot_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn MetastoreService>,
+            DeleteIndexSnapshotRequest,
+            EmptyResponse,
+            crate::metastore::MetastoreError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
     open_shards_layer: Option<
         quickwit_common::tower::BoxLayer<
             Box<dyn MetastoreService>,
@@ -1985,6 +2300,12 @@ impl MetastoreServiceTowerBlockBuilder {
                 Error = crate::metastore::MetastoreError,
             > + Clone + Send + Sync + 'static,
         <L::Service as tower::Service<DeleteSplitsRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                UpdateSplitMetadataRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<UpdateSplitMetadataRequest>>::Future: Send + 'static,
         L::Service: tower::Service<
                 AddSourceRequest,
                 Response = EmptyResponse,
@@ -2043,6 +2364,24 @@ impl MetastoreServiceTowerBlockBuilder {
                 Error = crate::metastore::MetastoreError,
             > + Clone + Send + Sync + 'static,
         <L::Service as tower::Service<ListStaleSplitsRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                CreateIndexSnapshotRequest,
+                Response = IndexSnapshot,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<CreateIndexSnapshotRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                ListIndexSnapshotsRequest,
+                Response = ListIndexSnapshotsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListIndexSnapshotsRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                DeleteIndexSnapshotRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<DeleteIndexSnapshotRequest>>::Future: Send + 'static,
         L::Service: tower::Service<
                 OpenShardsRequest,
                 Response = OpenShardsResponse,
@@ -2104,6 +2443,10 @@ impl MetastoreServiceTowerBlockBuilder {
             .delete_splits_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
         );
+        self
+            .update_split_metadata_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
         self
             .add_source_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
@@ -2140,6 +2483,18 @@ impl MetastoreServiceTowerBlockBuilder {
             .list_stale_splits_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
         );
+        self
+            .create_index_snapshot_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .list_index_snapshots_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .delete_index_snapshot_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
         self
             .open_shards_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer.clone()),
@@ -2282,6 +2637,19 @@ impl MetastoreServiceTowerBlockBuilder {
         self.delete_splits_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
         self
     }
+    pub fn update_split_metadata_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                UpdateSplitMetadataRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<UpdateSplitMetadataRequest>>::Future: Send + 'static,
+    {
+        self.update_split_metadata_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
     pub fn add_source_layer<L>(mut self, layer: L) -> Self
     where
         L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
@@ -2421,6 +2789,54 @@ impl MetastoreServiceTowerBlockBuilder {
         );
         self
     }
+    pub fn create_index_snapshot_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                CreateIndexSnapshotRequest,
+                Response = IndexSnapshot,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<CreateIndexSnapshotRequest>>::Future: Send + 'static,
+    {
+        self
+            .create_index_snapshot_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer),
+        );
+        self
+    }
+    pub fn list_index_snapshots_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                ListIndexSnapshotsRequest,
+                Response = ListIndexSnapshotsResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<ListIndexSnapshotsRequest>>::Future: Send + 'static,
+    {
+        self
+            .list_index_snapshots_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer),
+        );
+        self
+    }
+    pub fn delete_index_snapshot_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                DeleteIndexSnapshotRequest,
+                Response = EmptyResponse,
+                Error = crate::metastore::MetastoreError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<DeleteIndexSnapshotRequest>>::Future: Send + 'static,
+    {
+        self
+            .delete_index_snapshot_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer),
+        );
+        self
+    }
     pub fn open_shards_layer<L>(mut self, layer: L) -> Self
     where
         L: tower::Layer<Box<dyn MetastoreService>> + Send + Sync + 'static,
@@ -2559,6 +2975,11 @@ impl MetastoreServiceTowerBlockBuilder {
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
+        let update_split_metadata_svc = if let Some(layer) = self.update_split_metadata_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
         let add_source_svc = if let Some(layer) = self.add_source_layer {
             layer.layer(boxed_instance.clone())
         } else {
@@ -2609,22 +3030,40 @@ impl MetastoreServiceTowerBlockBuilder {
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
-        let open_shards_svc = if let Some(layer) = self.open_shards_layer {
+        let create_index_snapshot_svc = if let Some(layer) = self.create_index_snapshot_layer
+        {
             layer.layer(boxed_instance.clone())
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
-        let acquire_shards_svc = if let Some(layer) = self.acquire_shards_layer {
+        let list_index_snapshots_svc = if let Some(layer) = self.list_index_snapshots_layer
+        {
             layer.layer(boxed_instance.clone())
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
-        let delete_shards_svc = if let Some(layer) = self.delete_shards_layer {
+        let delete_index_snapshot_svc = if let Some(layer) = self.delete_index_snapshot_layer
+        {
             layer.layer(boxed_instance.clone())
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
-        let list_shards_svc = if let Some(layer) = self.list_shards_layer {
+        let open_shards_svc = if let Some(layer) = self.open_shards_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let acquire_shards_svc = if let Some(layer) = self.acquire_shards_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let delete_shards_svc = if let Some(layer) = self.delete_shards_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let list_shards_svc = if let Some(layer) = self.list_shards_layer {
             layer.layer(boxed_instance.clone())
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
@@ -2640,6 +3079,7 @@ impl MetastoreServiceTowerBlockBuilder {
             publish_splits_svc,
             mark_splits_for_deletion_svc,
             delete_splits_svc,
+            update_split_metadata_svc,
             add_source_svc,
             toggle_source_svc,
             delete_source_svc,
@@ -2649,6 +3089,9 @@ impl MetastoreServiceTowerBlockBuilder {
             update_splits_delete_opstamp_svc,
             list_delete_tasks_svc,
             list_stale_splits_svc,
+            create_index_snapshot_svc,
+            list_index_snapshots_svc,
+            delete_index_snapshot_svc,
             open_shards_svc,
             acquire_shards_svc,
             delete_shards_svc,
@@ -2789,6 +3232,12 @@ where
             Error = crate::metastore::MetastoreError,
             Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
         >
+        + tower::Service<
+            UpdateSplitMetadataRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<EmptyResponse, crate::metastore::MetastoreError>,
+        >
         + tower::Service<
             AddSourceRequest,
             Response = EmptyResponse,
@@ -2849,6 +3298,33 @@ where
             Error = crate::metastore::MetastoreError,
             Future = BoxFuture<ListSplitsResponse, crate::metastore::MetastoreError>,
         >
+        + tower::Service<
+            CreateIndexSnapshotRequest,
+            Response = IndexSnapshot,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<
+                IndexSnapshot,
+                crate::metastore::MetastoreError,
+            >,
+        >
+        + tower::Service<
+            ListIndexSnapshotsRequest,
+            Response = ListIndexSnapshotsResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<
+                ListIndexSnapshotsResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >
+        + tower::Service<
+            DeleteIndexSnapshotRequest,
+            Response = EmptyResponse,
+            Error = crate::metastore::MetastoreError,
+            Future = BoxFuture<
+                EmptyResponse,
+                crate::metastore::MetastoreError,
+            >,
+        >
         + tower::Service<
             OpenShardsRequest,
             Response = OpenShardsResponse,
@@ -2928,6 +3404,12 @@ where
     ) -> crate::metastore::MetastoreResult<EmptyResponse> {
         self.call(request).await
     }
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.call(request).await
+    }
     async fn add_source(
         &mut self,
         request: AddSourceRequest,
@@ -2982,6 +3464,24 @@ where
     ) -> crate::metastore::MetastoreResult<ListSplitsResponse> {
         self.call(request).await
     }
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<IndexSnapshot> {
+        self.call(request).await
+    }
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexSnapshotsResponse> {
+        self.call(request).await
+    }
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.call(request).await
+    }
     async fn open_shards(
         &mut self,
         request: OpenShardsRequest,
@@ -3147,6 +3647,16 @@ where
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
     }
+    async fn update_split_metadata(
+        &mut self,
+        request: UpdateSplitMetadataRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .update_split_metadata(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
     async fn add_source(
         &mut self,
         request: AddSourceRequest,
@@ -3237,6 +3747,36 @@ where
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
     }
+    async fn create_index_snapshot(
+        &mut self,
+        request: CreateIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<IndexSnapshot> {
+        self.inner
+            .create_index_snapshot(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn list_index_snapshots(
+        &mut self,
+        request: ListIndexSnapshotsRequest,
+    ) -> crate::metastore::MetastoreResult<ListIndexSnapshotsResponse> {
+        self.inner
+            .list_index_snapshots(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn delete_index_snapshot(
+        &mut self,
+        request: DeleteIndexSnapshotRequest,
+    ) -> crate::metastore::MetastoreResult<EmptyResponse> {
+        self.inner
+            .delete_index_snapshot(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
     async fn open_shards(
         &mut self,
         request: OpenShardsRequest,
@@ -3410,6 +3950,17 @@ for MetastoreServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(|error| error.into())
     }
+    async fn update_split_metadata(
+        &self,
+        request: tonic::Request<UpdateSplitMetadataRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .update_split_metadata(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
     async fn add_source(
         &self,
         request: tonic::Request<AddSourceRequest>,
@@ -3509,6 +4060,39 @@ for MetastoreServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(|error| error.into())
     }
+    async fn create_index_snapshot(
+        &self,
+        request: tonic::Request<CreateIndexSnapshotRequest>,
+    ) -> Result<tonic::Response<IndexSnapshot>, tonic::Status> {
+        self.inner
+            .clone()
+            .create_index_snapshot(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn list_index_snapshots(
+        &self,
+        request: tonic::Request<ListIndexSnapshotsRequest>,
+    ) -> Result<tonic::Response<ListIndexSnapshotsResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .list_index_snapshots(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn delete_index_snapshot(
+        &self,
+        request: tonic::Request<DeleteIndexSnapshotRequest>,
+    ) -> Result<tonic::Response<EmptyResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .delete_index_snapshot(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
     async fn open_shards(
         &self,
         request: tonic::Request<OpenShardsRequest>,
@@ -3939,6 +4523,35 @@ pub mod metastore_service_grpc_client {
                     ),
                 );
             self.inner.unary(req, path, codec).await
+        /// Updates the metadata of a published split.
+        ///
+        /// Only the fields set in the request are updated.
+        pub async fn update_split_metadata(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateSplitMetadataRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/UpdateSplitMetadata",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "UpdateSplitMetadata",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
         }
         /// Adds source.
         pub async fn add_source(
@@ -4201,6 +4814,102 @@ pub mod metastore_service_grpc_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Creates a snapshot of the splits published in an index.
+        ///
+        /// The snapshot only records the split IDs and the index checkpoint. Until the snapshot is deleted,
+        /// the splits it references are not garbage collected.
+        pub async fn create_index_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CreateIndexSnapshotRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexSnapshot>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/CreateIndexSnapshot",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "CreateIndexSnapshot",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Lists the snapshots of an index.
+        pub async fn list_index_snapshots(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListIndexSnapshotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIndexSnapshotsResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/ListIndexSnapshots",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "ListIndexSnapshots",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
+        /// Deletes a snapshot, allowing its splits to be garbage collected.
+        pub async fn delete_index_snapshot(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteIndexSnapshotRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EmptyResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.metastore.MetastoreService/DeleteIndexSnapshot",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new(
+                        "quickwit.metastore.MetastoreService",
+                        "DeleteIndexSnapshot",
+                    ),
+                );
+            self.inner.unary(req, path, codec).await
+        }
         /// Shard API
         ///
         /// Note that for the file-backed metastore implementation, the requests are not processed atomically.
@@ -4398,6 +5107,13 @@ pub mod metastore_service_grpc_server {
             &self,
             request: tonic::Request<super::DeleteSplitsRequest>,
         ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
+        /// Updates the metadata of a published split.
+        ///
+        /// Only the fields set in the request are updated.
+        async fn update_split_metadata(
+            &self,
+            request: tonic::Request<super::UpdateSplitMetadataRequest>,
+        ) -> std::result::Result<tonic::Response<super::EmptyResponse>, tonic::Status>;
         /// Adds source.
         async fn add_source(
             &self,
@@ -4455,6 +5171,33 @@ pub mod metastore_service_grpc_server {
             tonic::Response<super::ListSplitsResponse>,
             tonic::Status,
         >;
+        /// Creates a snapshot of the splits published in an index.
+        ///
+        /// The snapshot only records the split IDs and the index checkpoint. Until the snapshot is deleted,
+        /// the splits it references are not garbage collected.
+        async fn create_index_snapshot(
+            &self,
+            request: tonic::Request<super::CreateIndexSnapshotRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::IndexSnapshot>,
+            tonic::Status,
+        >;
+        /// Lists the snapshots of an index.
+        async fn list_index_snapshots(
+            &self,
+            request: tonic::Request<super::ListIndexSnapshotsRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::ListIndexSnapshotsResponse>,
+            tonic::Status,
+        >;
+        /// Deletes a snapshot, allowing its splits to be garbage collected.
+        async fn delete_index_snapshot(
+            &self,
+            request: tonic::Request<super::DeleteIndexSnapshotRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::EmptyResponse>,
+            tonic::Status,
+        >;
         /// Shard API
         ///
         /// Note that for the file-backed metastore implementation, the requests are not processed atomically.
@@ -5034,6 +5777,52 @@ pub mod metastore_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.metastore.MetastoreService/UpdateSplitMetadata" => {
+                    #[allow(non_camel_case_types)]
+                    struct UpdateSplitMetadataSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::UpdateSplitMetadataRequest>
+                    for UpdateSplitMetadataSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateSplitMetadataRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).update_split_metadata(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = UpdateSplitMetadataSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit.metastore.MetastoreService/AddSource" => {
                     #[allow(non_camel_case_types)]
                     struct AddSourceSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
@@ -5451,6 +6240,144 @@ pub mod metastore_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.metastore.MetastoreService/CreateIndexSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct CreateIndexSnapshotSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::CreateIndexSnapshotRequest>
+                    for CreateIndexSnapshotSvc<T> {
+                        type Response = super::IndexSnapshot;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CreateIndexSnapshotRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).create_index_snapshot(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CreateIndexSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/ListIndexSnapshots" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListIndexSnapshotsSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::ListIndexSnapshotsRequest>
+                    for ListIndexSnapshotsSvc<T> {
+                        type Response = super::ListIndexSnapshotsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListIndexSnapshotsRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).list_index_snapshots(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListIndexSnapshotsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit.metastore.MetastoreService/DeleteIndexSnapshot" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteIndexSnapshotSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: MetastoreServiceGrpc,
+                    > tonic::server::UnaryService<super::DeleteIndexSnapshotRequest>
+                    for DeleteIndexSnapshotSvc<T> {
+                        type Response = super::EmptyResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteIndexSnapshotRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).delete_index_snapshot(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteIndexSnapshotSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit.metastore.MetastoreService/OpenShards" => {
                     #[allow(non_camel_case_types)]
                     struct OpenShardsSvc<T: MetastoreServiceGrpc>(pub Arc<T>);
@@ -63,6 +63,22 @@ pub struct ReportSplitsResponse {}
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthCheckRequest {}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct HealthCheckResponse {
+    /// Number of seconds elapsed since the search service was started.
+    #[prost(uint64, tag = "1")]
+    pub uptime_secs: u64,
+    /// Number of indexes registered in the metastore, i.e. searchable by the node. The count is
+    /// refreshed in the background every 30 seconds, so health checks do not call the metastore.
+    #[prost(uint64, tag = "2")]
+    pub num_indexes: u64,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct ListFieldsRequest {
     /// Optional limit query to a set of indexes.
     #[prost(string, repeated, tag = "1")]
@@ -184,6 +200,32 @@ pub struct SearchRequest {
     pub search_after: ::core::option::Option<PartialHit>,
     #[prost(enumeration = "CountHits", tag = "17")]
     pub count_hits: i32,
+    /// If not empty, only splits carrying at least one of these tags are searched.
+    #[prost(string, repeated, tag = "18")]
+    pub tags: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Disables the expansion of the query with the synonyms configured on the indexes.
+    #[prost(bool, tag = "19")]
+    pub disable_synonyms: bool,
+    /// If set, the search runs against the splits recorded in this index snapshot instead of the
+    /// splits currently published.
+    #[prost(string, optional, tag = "20")]
+    pub snapshot_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// Json serialized filters on `geo_point` fields (bounding boxes or distances from a point).
+    /// Documents must match all of them.
+    #[prost(string, repeated, tag = "21")]
+    pub geo_filters: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Maximum time allotted to the search of the splits and the fetch of the documents. Once it has
+    /// elapsed, the results collected so far are returned and `timed_out` is set in the response.
+    #[prost(uint64, optional, tag = "22")]
+    pub timeout_millis: ::core::option::Option<u64>,
+    /// If set, the terms of the query match the terms of indexed text fields within this
+    /// Levenshtein distance (0 to 2).
+    #[prost(uint32, optional, tag = "23")]
+    pub fuzzy_distance: ::core::option::Option<u32>,
+    /// Number of searchers running concurrently on the segments of each split on the leaves.
+    /// Defaults to 1, in which case the segments of a split are searched sequentially.
+    #[prost(uint32, optional, tag = "24")]
+    pub num_searchers: ::core::option::Option<u32>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[derive(Eq, Hash)]
@@ -223,6 +265,66 @@ pub struct SearchResponse {
     /// Scroll Id (only set if scroll_secs was set in the request)
     #[prost(string, optional, tag = "6")]
     pub scroll_id: ::core::option::Option<::prost::alloc::string::String>,
+    /// Splits that could not be searched, even after retries.
+    /// When not empty, the hits and aggregations only cover the other splits.
+    #[prost(message, repeated, tag = "7")]
+    pub failed_splits: ::prost::alloc::vec::Vec<SplitSearchError>,
+    /// Cursor pointing after the last hit of this page, to pass as `search_after` to fetch the next
+    /// page (only set if the page is full and the request is not a scroll request).
+    #[prost(string, optional, tag = "8")]
+    pub next_page_token: ::core::option::Option<::prost::alloc::string::String>,
+    /// Statistics about the splits searched to answer the request (not set if the response was
+    /// served from the search response cache).
+    #[prost(message, optional, tag = "9")]
+    pub stats: ::core::option::Option<SearchStats>,
+    /// Indexes that could not be searched. When not empty, the hits and aggregations only cover the
+    /// other indexes.
+    #[prost(message, repeated, tag = "10")]
+    pub failed_indexes: ::prost::alloc::vec::Vec<IndexSearchError>,
+    /// Whether `num_hits` is exact or only a lower bound of the number of matching documents.
+    #[prost(enumeration = "HitCountAccuracy", tag = "11")]
+    pub num_hits_accuracy: i32,
+    /// Whether the search exceeded the `timeout_millis` of the request. When true, the response only
+    /// covers the documents scanned before the timeout, and may have no hits if the documents could
+    /// not be fetched in time.
+    #[prost(bool, tag = "12")]
+    pub timed_out: bool,
+    /// Conditions that did not prevent the search from succeeding but may affect its results, e.g.
+    /// the metastore being unreachable.
+    #[prost(message, repeated, tag = "13")]
+    pub warnings: ::prost::alloc::vec::Vec<SearchWarning>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct IndexSearchError {
+    /// ID of the index that could not be searched.
+    #[prost(string, tag = "1")]
+    pub index_id: ::prost::alloc::string::String,
+    /// Code of the error, e.g. `bad_request` or `timeout`.
+    #[prost(string, tag = "2")]
+    pub error_code: ::prost::alloc::string::String,
+    /// The error that occurred formatted as string.
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchWarning {
+    /// Code of the warning, e.g. `stale_metastore`.
+    #[prost(string, tag = "1")]
+    pub code: ::prost::alloc::string::String,
+    /// ID of the index the warning relates to.
+    #[prost(string, tag = "2")]
+    pub index_id: ::prost::alloc::string::String,
+    /// The warning formatted as string.
+    #[prost(string, tag = "3")]
+    pub message: ::prost::alloc::string::String,
+    /// Age in seconds of the metastore data the index was searched with (only set for
+    /// `stale_metastore` warnings).
+    #[prost(uint64, optional, tag = "4")]
+    pub metastore_data_age_secs: ::core::option::Option<u64>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -241,6 +343,94 @@ pub struct SplitSearchError {
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchStats {
+    /// Total number of splits targeted by the request, including the failed ones.
+    #[prost(uint64, tag = "1")]
+    pub num_attempted_splits: u64,
+    /// Statistics of the splits that were searched successfully, slowest first.
+    #[prost(message, repeated, tag = "2")]
+    pub split_stats: ::prost::alloc::vec::Vec<SplitSearchStats>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SplitSearchStats {
+    /// Split id.
+    #[prost(string, tag = "1")]
+    pub split_id: ::prost::alloc::string::String,
+    /// gRPC address of the searcher node that searched the split. It is set by the root.
+    #[prost(string, tag = "2")]
+    pub searcher_addr: ::prost::alloc::string::String,
+    /// Time spent by the leaf searching the split, expressed in microseconds.
+    #[prost(uint64, tag = "3")]
+    pub search_duration_micros: u64,
+    /// Number of documents of the split that matched the query and were scanned by the collector.
+    #[prost(uint64, tag = "4")]
+    pub num_docs_scanned: u64,
+    /// Statistics of the ephemeral cache of the split (not set if the response was served from the
+    /// leaf search cache).
+    #[prost(message, optional, tag = "5")]
+    pub cache_stats: ::core::option::Option<DirectoryCacheStats>,
+    /// Whether the search of the split was interrupted because it exceeded its time budget.
+    #[prost(bool, tag = "6")]
+    pub partial: bool,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DirectoryCacheStats {
+    /// Number of reads served from the cache.
+    #[prost(uint64, tag = "1")]
+    pub num_hits: u64,
+    /// Number of reads served by the underlying storage.
+    #[prost(uint64, tag = "2")]
+    pub num_misses: u64,
+    /// Number of slices evicted from the cache to make room for new ones.
+    #[prost(uint64, tag = "3")]
+    pub num_evictions: u64,
+    /// Number of bytes held by the cache at the end of the search.
+    #[prost(uint64, tag = "4")]
+    pub num_bytes: u64,
+    /// Number of slices held by the cache at the end of the search.
+    #[prost(uint64, tag = "5")]
+    pub num_items: u64,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExplainResult {
+    /// One explanation per split of the targeted indexes.
+    #[prost(message, repeated, tag = "1")]
+    pub splits: ::prost::alloc::vec::Vec<SplitExplanation>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SplitExplanation {
+    #[prost(string, tag = "1")]
+    pub split_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub index_id: ::prost::alloc::string::String,
+    /// True if the split was discarded because it does not overlap the
+    /// requested time range. Pruned splits are not searched.
+    #[prost(bool, tag = "3")]
+    pub pruned_by_time_range: bool,
+    /// Number of documents matching the query in this split.
+    #[prost(uint64, tag = "4")]
+    pub num_candidate_docs: u64,
+    /// Top hits of the split, with their raw sort values.
+    #[prost(message, repeated, tag = "5")]
+    pub top_hits: ::prost::alloc::vec::Vec<PartialHit>,
+    /// Address of the searcher the split was assigned to.
+    #[prost(string, tag = "6")]
+    pub searcher_addr: ::prost::alloc::string::String,
+    /// Error raised while searching the split, if any.
+    #[prost(string, optional, tag = "7")]
+    pub error: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct LeafSearchRequest {
     /// Search request. This is a perfect copy of the original search request,
     /// that was sent to root apart from the start_offset & max_hits params.
@@ -257,6 +447,17 @@ pub struct LeafSearchRequest {
     /// split files.
     #[prost(string, tag = "6")]
     pub index_uri: ::prost::alloc::string::String,
+    /// Time allotted to the leaf to answer the request, derived from the cost of its splits.
+    /// The leaf uses it to bound the time spent on each split.
+    #[prost(uint64, optional, tag = "7")]
+    pub timeout_millis: ::core::option::Option<u64>,
+    /// ID of the index the splits belong to. It is used to label the search metrics of the leaf.
+    #[prost(string, tag = "8")]
+    pub index_id: ::prost::alloc::string::String,
+    /// Number of searchers running concurrently on the segments of each split. Defaults to 1, in
+    /// which case the segments of a split are searched sequentially.
+    #[prost(uint32, optional, tag = "9")]
+    pub num_searchers: ::core::option::Option<u32>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -278,6 +479,9 @@ pub struct SplitIdAndFooterOffsets {
     /// The highest timestamp appearing in the split
     #[prost(int64, optional, tag = "5")]
     pub timestamp_end: ::core::option::Option<i64>,
+    /// Number of documents in the split. It is used to estimate the cost of searching the split.
+    #[prost(uint64, tag = "6")]
+    pub num_docs: u64,
 }
 /// Hits returned by a FetchDocRequest.
 ///
@@ -404,6 +608,14 @@ pub struct LeafSearchResponse {
     pub intermediate_aggregation_result: ::core::option::Option<
         ::prost::alloc::vec::Vec<u8>,
     >,
+    /// Statistics of the splits that were searched successfully.
+    #[prost(message, repeated, tag = "7")]
+    pub split_stats: ::prost::alloc::vec::Vec<SplitSearchStats>,
+    /// The list of splits whose search was interrupted because it exceeded its time budget. Unlike
+    /// failed splits, their results are included in the response, but they only cover the documents
+    /// scanned before the interruption.
+    #[prost(string, repeated, tag = "8")]
+    pub partial_splits: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -435,6 +647,9 @@ pub struct FetchDocsRequest {
     /// `DocMapper` as json serialized trait.
     #[prost(string, tag = "6")]
     pub doc_mapper: ::prost::alloc::string::String,
+    /// ID of the index the splits belong to. It is used to label the search metrics of the leaf.
+    #[prost(string, tag = "8")]
+    pub index_id: ::prost::alloc::string::String,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -726,6 +941,37 @@ impl SortDatetimeFormat {
 #[serde(rename_all = "snake_case")]
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
 #[repr(i32)]
+pub enum HitCountAccuracy {
+    /// `num_hits` is the exact number of matching documents.
+    Exact = 0,
+    /// Some indexes could not be searched or the search of some splits was interrupted, so `num_hits`
+    /// only counts the matching documents that were scanned.
+    LowerBound = 1,
+}
+impl HitCountAccuracy {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            HitCountAccuracy::Exact => "EXACT",
+            HitCountAccuracy::LowerBound => "LOWER_BOUND",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "EXACT" => Some(Self::Exact),
+            "LOWER_BOUND" => Some(Self::LowerBound),
+            _ => None,
+        }
+    }
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
 pub enum OutputFormat {
     /// Comma Separated Values format (<https://datatracker.ietf.org/doc/html/rfc4180>).
     /// The delimiter is `,`.
@@ -868,6 +1114,34 @@ pub mod search_service_client {
                 .insert(GrpcMethod::new("quickwit.search.SearchService", "RootSearch"));
             self.inner.unary(req, path, codec).await
         }
+        /// Root explain API.
+        /// This RPC runs the same split selection as `RootSearch` and reports, for
+        /// every split of the targeted indexes, whether it was pruned and how it
+        /// scored the query.
+        pub async fn explain_search(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SearchRequest>,
+        ) -> std::result::Result<tonic::Response<super::ExplainResult>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.search.SearchService/ExplainSearch",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(
+                    GrpcMethod::new("quickwit.search.SearchService", "ExplainSearch"),
+                );
+            self.inner.unary(req, path, codec).await
+        }
         /// Perform a leaf search on a given set of splits.
         ///
         /// It is like a regular search except that:
@@ -1170,6 +1444,33 @@ pub mod search_service_client {
                 );
             self.inner.unary(req, path, codec).await
         }
+        /// Reports whether the node is up, along with its uptime and the number of
+        /// indexes it can search.
+        pub async fn health_check(
+            &mut self,
+            request: impl tonic::IntoRequest<super::HealthCheckRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HealthCheckResponse>,
+            tonic::Status,
+        > {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit.search.SearchService/HealthCheck",
+            );
+            let mut req = request.into_request();
+            req.extensions_mut()
+                .insert(GrpcMethod::new("quickwit.search.SearchService", "HealthCheck"));
+            self.inner.unary(req, path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -1188,6 +1489,14 @@ pub mod search_service_server {
             &self,
             request: tonic::Request<super::SearchRequest>,
         ) -> std::result::Result<tonic::Response<super::SearchResponse>, tonic::Status>;
+        /// Root explain API.
+        /// This RPC runs the same split selection as `RootSearch` and reports, for
+        /// every split of the targeted indexes, whether it was pruned and how it
+        /// scored the query.
+        async fn explain_search(
+            &self,
+            request: tonic::Request<super::SearchRequest>,
+        ) -> std::result::Result<tonic::Response<super::ExplainResult>, tonic::Status>;
         /// Perform a leaf search on a given set of splits.
         ///
         /// It is like a regular search except that:
@@ -1291,6 +1600,15 @@ pub mod search_service_server {
             tonic::Response<super::ListFieldsResponse>,
             tonic::Status,
         >;
+        /// Reports whether the node is up, along with its uptime and the number of
+        /// indexes it can search.
+        async fn health_check(
+            &self,
+            request: tonic::Request<super::HealthCheckRequest>,
+        ) -> std::result::Result<
+            tonic::Response<super::HealthCheckResponse>,
+            tonic::Status,
+        >;
     }
     #[derive(Debug)]
     pub struct SearchServiceServer<T: SearchService> {
@@ -1415,6 +1733,52 @@ pub mod search_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.search.SearchService/ExplainSearch" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExplainSearchSvc<T: SearchService>(pub Arc<T>);
+                    impl<
+                        T: SearchService,
+                    > tonic::server::UnaryService<super::SearchRequest>
+                    for ExplainSearchSvc<T> {
+                        type Response = super::ExplainResult;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SearchRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).explain_search(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExplainSearchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit.search.SearchService/LeafSearch" => {
                     #[allow(non_camel_case_types)]
                     struct LeafSearchSvc<T: SearchService>(pub Arc<T>);
@@ -1909,6 +2273,52 @@ pub mod search_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit.search.SearchService/HealthCheck" => {
+                    #[allow(non_camel_case_types)]
+                    struct HealthCheckSvc<T: SearchService>(pub Arc<T>);
+                    impl<
+                        T: SearchService,
+                    > tonic::server::UnaryService<super::HealthCheckRequest>
+                    for HealthCheckSvc<T> {
+                        type Response = super::HealthCheckResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::HealthCheckRequest>,
+                        ) -> Self::Future {
+                            let inner = Arc::clone(&self.0);
+                            let fut = async move {
+                                (*inner).health_check(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let max_decoding_message_size = self.max_decoding_message_size;
+                    let max_encoding_message_size = self.max_encoding_message_size;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = HealthCheckSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            )
+                            .apply_max_message_size_config(
+                                max_decoding_message_size,
+                                max_encoding_message_size,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
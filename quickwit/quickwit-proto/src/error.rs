@@ -29,6 +29,7 @@ pub enum ServiceErrorCode {
     BadRequest,
     Internal,
     MethodNotAllowed,
+    NotAcceptable,
     NotFound,
     // Used for APIs that are available in Elasticsearch but not available yet in Quickwit.
     NotSupportedYet,
@@ -45,6 +46,7 @@ impl ServiceErrorCode {
             ServiceErrorCode::BadRequest => tonic::Code::InvalidArgument,
             ServiceErrorCode::Internal => tonic::Code::Internal,
             ServiceErrorCode::MethodNotAllowed => tonic::Code::InvalidArgument,
+            ServiceErrorCode::NotAcceptable => tonic::Code::InvalidArgument,
             ServiceErrorCode::NotFound => tonic::Code::NotFound,
             ServiceErrorCode::NotSupportedYet => tonic::Code::Unimplemented,
             ServiceErrorCode::RateLimited => tonic::Code::ResourceExhausted,
@@ -59,6 +61,7 @@ impl ServiceErrorCode {
             ServiceErrorCode::BadRequest => http::StatusCode::BAD_REQUEST,
             ServiceErrorCode::Internal => http::StatusCode::INTERNAL_SERVER_ERROR,
             ServiceErrorCode::MethodNotAllowed => http::StatusCode::METHOD_NOT_ALLOWED,
+            ServiceErrorCode::NotAcceptable => http::StatusCode::NOT_ACCEPTABLE,
             ServiceErrorCode::NotFound => http::StatusCode::NOT_FOUND,
             ServiceErrorCode::NotSupportedYet => http::StatusCode::NOT_IMPLEMENTED,
             ServiceErrorCode::RateLimited => http::StatusCode::TOO_MANY_REQUESTS,
@@ -67,6 +70,21 @@ impl ServiceErrorCode {
             ServiceErrorCode::Timeout => http::StatusCode::REQUEST_TIMEOUT,
         }
     }
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ServiceErrorCode::AlreadyExists => "already_exists",
+            ServiceErrorCode::BadRequest => "bad_request",
+            ServiceErrorCode::Internal => "internal",
+            ServiceErrorCode::MethodNotAllowed => "method_not_allowed",
+            ServiceErrorCode::NotAcceptable => "not_acceptable",
+            ServiceErrorCode::NotFound => "not_found",
+            ServiceErrorCode::NotSupportedYet => "not_supported_yet",
+            ServiceErrorCode::RateLimited => "rate_limited",
+            ServiceErrorCode::Timeout => "timeout",
+            ServiceErrorCode::Unavailable => "unavailable",
+            ServiceErrorCode::UnsupportedMediaType => "unsupported_media_type",
+        }
+    }
 }
 
 pub trait ServiceError: ToString {
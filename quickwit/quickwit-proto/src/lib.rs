@@ -34,6 +34,7 @@ use tracing_opentelemetry::OpenTelemetrySpanExt;
 pub mod control_plane;
 pub use {bytes, tonic};
 pub mod error;
+pub mod index_changes;
 pub mod indexing;
 pub mod ingest;
 pub mod metastore;
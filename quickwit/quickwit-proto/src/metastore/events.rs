@@ -22,7 +22,8 @@
 use quickwit_common::pubsub::Event;
 
 use super::{
-    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, DeleteSourceRequest, SourceType,
+    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, DeleteSourceRequest,
+    DeleteSplitsRequest, MarkSplitsForDeletionRequest, PublishSplitsRequest, SourceType,
     ToggleSourceRequest,
 };
 use crate::types::{IndexUid, SourceId};
@@ -69,4 +70,7 @@ impl Event for AddSourceRequest {}
 impl Event for CreateIndexRequest {}
 impl Event for DeleteIndexRequest {}
 impl Event for DeleteSourceRequest {}
+impl Event for DeleteSplitsRequest {}
+impl Event for MarkSplitsForDeletionRequest {}
+impl Event for PublishSplitsRequest {}
 impl Event for ToggleSourceRequest {}
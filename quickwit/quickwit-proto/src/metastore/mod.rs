@@ -46,6 +46,13 @@ pub enum EntityKind {
         /// Index ID.
         index_id: IndexId,
     },
+    /// An index snapshot.
+    IndexSnapshot {
+        /// Index ID.
+        index_id: IndexId,
+        /// Snapshot ID.
+        snapshot_id: String,
+    },
     /// A set of indexes.
     Indexes {
         /// Index IDs.
@@ -83,6 +90,10 @@ impl fmt::Display for EntityKind {
                 source_id,
             } => write!(f, "checkpoint delta `{index_id}/{source_id}`"),
             EntityKind::Index { index_id } => write!(f, "index `{}`", index_id),
+            EntityKind::IndexSnapshot {
+                index_id,
+                snapshot_id,
+            } => write!(f, "snapshot `{index_id}/{snapshot_id}`"),
             EntityKind::Indexes { index_ids } => write!(f, "indexes `{}`", index_ids.join(", ")),
             EntityKind::Shard { queue_id } => write!(f, "shard `{queue_id}`"),
             EntityKind::Source {
@@ -207,6 +218,7 @@ impl SourceType {
         match self {
             SourceType::Cli => "ingest-cli",
             SourceType::File => "file",
+            SourceType::FileTail => "file_tail",
             SourceType::GcpPubsub => "gcp_pubsub",
             SourceType::IngestV1 => "ingest-api",
             SourceType::IngestV2 => "ingest",
@@ -214,6 +226,7 @@ impl SourceType {
             SourceType::Kinesis => "kinesis",
             SourceType::Nats => "nats",
             SourceType::Pulsar => "pulsar",
+            SourceType::Push => "push",
             SourceType::Unspecified => "unspecified",
             SourceType::Vec => "vec",
             SourceType::Void => "void",
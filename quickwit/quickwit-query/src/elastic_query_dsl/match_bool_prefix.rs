@@ -43,6 +43,7 @@ impl ConvertableToQueryAst for MatchBoolPrefixQuery {
                 max_expansions: default_max_expansions(),
             },
             zero_terms_query: self.params.zero_terms_query,
+            fuzzy_distance: None,
         };
         Ok(QueryAst::FullText(FullTextQuery {
             field: self.field,
@@ -52,6 +52,7 @@ impl ConvertableToQueryAst for MatchPhraseQuery {
                 slop: self.params.slop,
             },
             zero_terms_query: self.params.zero_terms_query,
+            fuzzy_distance: None,
         };
         Ok(QueryAst::FullText(FullTextQuery {
             field: self.field,
@@ -55,6 +55,7 @@ impl ConvertableToQueryAst for MatchQuery {
             tokenizer: None,
             mode: self.params.operator.into(),
             zero_terms_query: self.params.zero_terms_query,
+            fuzzy_distance: None,
         };
         Ok(QueryAst::FullText(FullTextQuery {
             field: self.field,
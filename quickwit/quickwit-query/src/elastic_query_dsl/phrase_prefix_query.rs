@@ -61,6 +61,7 @@ impl ConvertableToQueryAst for MatchPhrasePrefixQuery {
             tokenizer: analyzer,
             mode: FullTextMode::Phrase { slop },
             zero_terms_query,
+            fuzzy_distance: None,
         };
         let phrase_prefix_query_ast = query_ast::PhrasePrefixQuery {
             field: self.field,
@@ -28,6 +28,10 @@ use crate::query_ast::{BuildTantivyAst, QueryAst};
 use crate::tokenizers::TokenizerManager;
 use crate::{find_field_or_hit_dynamic, InvalidQuery};
 
+/// Matches the documents in which a value is present for the given field.
+///
+/// For multivalued fields, a document matches as soon as one of the values is present. For fields
+/// nested in objects, a document matches if the leaf field is present in any of its objects.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct FieldPresenceQuery {
     pub field: String,
@@ -81,6 +85,13 @@ impl BuildTantivyAst for FieldPresenceQuery {
             InvalidQuery::SchemaError("field presence is not available for this split".to_string())
         })?;
         let (field, field_entry, path) = find_field_or_hit_dynamic(&self.field, schema)?;
+        if !field_entry.is_indexed() && !field_entry.is_fast() {
+            return Err(InvalidQuery::SchemaError(format!(
+                "exists queries are only supported for indexed or fast fields. (`{}` is neither \
+                 indexed nor a fast field)",
+                self.field
+            )));
+        }
         if field_entry.is_fast() {
             let full_path = if path.is_empty() {
                 field_entry.name().to_string()
@@ -103,8 +114,68 @@ impl BuildTantivyAst for FieldPresenceQuery {
 
 #[cfg(test)]
 mod tests {
+    use tantivy::schema::{FAST, INDEXED, STORED, TEXT};
 
     use super::*;
+    use crate::create_default_quickwit_tokenizer_manager;
+
+    fn make_schema() -> TantivySchema {
+        let mut schema_builder = TantivySchema::builder();
+        schema_builder.add_u64_field("fast_field", FAST);
+        schema_builder.add_text_field("text_field", TEXT);
+        schema_builder.add_text_field("stored_field", STORED);
+        schema_builder.add_u64_field(FIELD_PRESENCE_FIELD_NAME, INDEXED);
+        schema_builder.build()
+    }
+
+    fn build_field_presence_ast(
+        field: &str,
+        with_validation: bool,
+    ) -> Result<TantivyQueryAst, InvalidQuery> {
+        FieldPresenceQuery {
+            field: field.to_string(),
+        }
+        .build_tantivy_ast_call(
+            &make_schema(),
+            &create_default_quickwit_tokenizer_manager(),
+            &[],
+            with_validation,
+        )
+    }
+
+    #[test]
+    fn test_field_presence_query_fast_field() {
+        let tantivy_ast = build_field_presence_ast("fast_field", true).unwrap();
+        let leaf = tantivy_ast.as_leaf().unwrap();
+        assert!(format!("{leaf:?}").starts_with("ExistsQuery"));
+    }
+
+    #[test]
+    fn test_field_presence_query_indexed_field() {
+        let tantivy_ast = build_field_presence_ast("text_field", true).unwrap();
+        let leaf = tantivy_ast.as_leaf().unwrap();
+        let expected_hash = compute_field_presence_hash(Field::from_field_id(1u32), "");
+        assert_eq!(
+            format!("{leaf:?}"),
+            format!("TermQuery(Term(field=3, type=U64, {expected_hash}))")
+        );
+    }
+
+    #[test]
+    fn test_field_presence_query_stored_only_field() {
+        let invalid_query = build_field_presence_ast("stored_field", true).unwrap_err();
+        let InvalidQuery::SchemaError(error_msg) = invalid_query else {
+            panic!("expected a schema error, got `{invalid_query:?}`");
+        };
+        assert!(error_msg.contains("stored_field"));
+        // Without validation, the query does not match any document.
+        assert_eq!(
+            build_field_presence_ast("stored_field", false)
+                .unwrap()
+                .const_predicate(),
+            Some(crate::MatchAllOrNone::MatchNone)
+        );
+    }
 
     #[test]
     fn test_field_presence_single() {
@@ -21,8 +21,8 @@ use anyhow::Context;
 use serde::{Deserialize, Serialize};
 use tantivy::json_utils::JsonTermWriter;
 use tantivy::query::{
-    PhrasePrefixQuery as TantivyPhrasePrefixQuery, PhraseQuery as TantivyPhraseQuery,
-    TermQuery as TantivyTermQuery,
+    FuzzyTermQuery as TantivyFuzzyTermQuery, PhrasePrefixQuery as TantivyPhrasePrefixQuery,
+    PhraseQuery as TantivyPhraseQuery, TermQuery as TantivyTermQuery,
 };
 use tantivy::schema::{
     Field, FieldType, IndexRecordOption, JsonObjectOptions, Schema as TantivySchema,
@@ -47,6 +47,10 @@ pub struct FullTextParams {
     // By default we match no documents.
     #[serde(default, skip_serializing_if = "MatchAllOrNone::is_none")]
     pub zero_terms_query: MatchAllOrNone,
+    // Maximum Levenshtein distance between the terms of the query and the terms they match.
+    // By default, terms must match exactly. Phrase queries always match exactly.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fuzzy_distance: Option<u8>,
 }
 
 impl FullTextParams {
@@ -111,6 +115,19 @@ impl FullTextParams {
         Ok(tokens)
     }
 
+    fn make_term_query(
+        &self,
+        term: Term,
+        index_record_option: IndexRecordOption,
+    ) -> TantivyQueryAst {
+        if let Some(fuzzy_distance) = self.fuzzy_distance {
+            // Transpositions count as a single edit.
+            TantivyFuzzyTermQuery::new(term, fuzzy_distance, true).into()
+        } else {
+            TantivyTermQuery::new(term, index_record_option).into()
+        }
+    }
+
     pub(crate) fn make_query(
         &self,
         mut terms: Vec<(usize, Term)>,
@@ -121,13 +138,16 @@ impl FullTextParams {
         }
         if terms.len() == 1 {
             let term = terms.pop().unwrap().1;
-            return Ok(TantivyTermQuery::new(term, IndexRecordOption::WithFreqs).into());
+            if let FullTextMode::Phrase { .. } = self.mode {
+                return Ok(TantivyTermQuery::new(term, IndexRecordOption::WithFreqs).into());
+            }
+            return Ok(self.make_term_query(term, IndexRecordOption::WithFreqs));
         }
         match self.mode {
             FullTextMode::Bool { operator } => {
                 let leaf_queries: Vec<TantivyQueryAst> = terms
                     .into_iter()
-                    .map(|(_, term)| TantivyTermQuery::new(term, index_record_option).into())
+                    .map(|(_, term)| self.make_term_query(term, index_record_option))
                     .collect();
                 Ok(TantivyBoolQuery::build_clause(operator, leaf_queries).into())
             }
@@ -138,7 +158,7 @@ impl FullTextParams {
                 let term_with_prefix = terms.pop();
                 let mut leaf_queries: Vec<TantivyQueryAst> = terms
                     .into_iter()
-                    .map(|(_, term)| TantivyTermQuery::new(term, index_record_option).into())
+                    .map(|(_, term)| self.make_term_query(term, index_record_option))
                     .collect();
                 if let Some(term_with_prefix) = term_with_prefix {
                     let mut phrase_prefix_query =
@@ -154,12 +174,13 @@ impl FullTextParams {
                 Ok(phrase_query.into())
             }
             FullTextMode::PhraseFallbackToIntersection => {
-                if index_record_option.has_positions() {
+                // Fuzzy terms cannot be part of a phrase, so we fall back to the intersection.
+                if index_record_option.has_positions() && self.fuzzy_distance.is_none() {
                     Ok(TantivyPhraseQuery::new_with_offset(terms).into())
                 } else {
                     let term_query: Vec<TantivyQueryAst> = terms
                         .into_iter()
-                        .map(|(_, term)| TantivyTermQuery::new(term, index_record_option).into())
+                        .map(|(_, term)| self.make_term_query(term, index_record_option))
                         .collect();
                     Ok(TantivyBoolQuery::build_clause(BooleanOperand::And, term_query).into())
                 }
@@ -302,11 +323,52 @@ impl FullTextQuery {
             _ => None,
         }
     }
+
+    /// Returns the terms of the query that are matched approximately, i.e. within
+    /// `params.fuzzy_distance` edits, assuming the query is targetting a string field.
+    ///
+    /// The terms they match are only known once the term dictionary has been searched, so the
+    /// warmup has to handle them separately.
+    pub fn get_fuzzy_terms(
+        &self,
+        schema: &TantivySchema,
+        tokenizer_manager: &TokenizerManager,
+    ) -> Vec<Term> {
+        if self.params.fuzzy_distance.is_none()
+            || matches!(self.params.mode, FullTextMode::Phrase { .. })
+        {
+            return Vec::new();
+        }
+        let Ok((field, field_entry, _json_path)) = find_field_or_hit_dynamic(&self.field, schema)
+        else {
+            return Vec::new();
+        };
+        let FieldType::Str(text_options) = field_entry.field_type() else {
+            return Vec::new();
+        };
+        let Some(text_field_indexing) = text_options.get_indexing_options() else {
+            return Vec::new();
+        };
+        let Ok(mut terms) = self.params.tokenize_text_into_terms(
+            field,
+            &self.text,
+            text_field_indexing,
+            tokenizer_manager,
+        ) else {
+            return Vec::new();
+        };
+        // The last term of a bool prefix query with several terms is a prefix, see `make_query`.
+        if matches!(self.params.mode, FullTextMode::BoolPrefix { .. }) && terms.len() > 1 {
+            terms.pop();
+        }
+        terms.into_iter().map(|(_position, term)| term).collect()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use tantivy::schema::{Schema, TEXT};
+    use tantivy::Term;
 
     use crate::query_ast::tantivy_query_ast::TantivyQueryAst;
     use crate::query_ast::{BuildTantivyAst, FullTextMode, FullTextQuery};
@@ -321,6 +383,7 @@ mod tests {
                 tokenizer: None,
                 mode: BooleanOperand::And.into(),
                 zero_terms_query: crate::MatchAllOrNone::MatchAll,
+                fuzzy_distance: None,
             },
         };
         let mut schema_builder = Schema::builder();
@@ -346,6 +409,7 @@ mod tests {
                 tokenizer: None,
                 mode: FullTextMode::Phrase { slop: 1 },
                 zero_terms_query: crate::MatchAllOrNone::MatchAll,
+                fuzzy_distance: None,
             },
         };
         let mut schema_builder = Schema::builder();
@@ -376,6 +440,7 @@ mod tests {
                 tokenizer: Some("raw".to_string()),
                 mode: FullTextMode::Phrase { slop: 1 },
                 zero_terms_query: crate::MatchAllOrNone::MatchAll,
+                fuzzy_distance: None,
             },
         };
         let mut schema_builder = Schema::builder();
@@ -405,6 +470,7 @@ mod tests {
                 tokenizer: None,
                 mode: BooleanOperand::And.into(),
                 zero_terms_query: crate::MatchAllOrNone::MatchAll,
+                fuzzy_distance: None,
             },
         };
         let mut schema_builder = Schema::builder();
@@ -421,4 +487,95 @@ mod tests {
         let bool_query = ast.as_bool_query().unwrap();
         assert_eq!(bool_query.must.len(), 2);
     }
+
+    #[test]
+    fn test_full_text_fuzzy_distance() {
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_text_field("body", TEXT);
+        let schema = schema_builder.build();
+        let build_leaf_queries = |mode: FullTextMode| -> Vec<String> {
+            let full_text_query = FullTextQuery {
+                field: "body".to_string(),
+                text: "Helo wrld".to_string(),
+                params: super::FullTextParams {
+                    tokenizer: None,
+                    mode,
+                    zero_terms_query: crate::MatchAllOrNone::MatchNone,
+                    fuzzy_distance: Some(1),
+                },
+            };
+            let ast: TantivyQueryAst = full_text_query
+                .build_tantivy_ast_call(
+                    &schema,
+                    &create_default_quickwit_tokenizer_manager(),
+                    &[],
+                    true,
+                )
+                .unwrap();
+            if let Some(leaf) = ast.as_leaf() {
+                return vec![format!("{leaf:?}")];
+            }
+            let bool_query = ast.as_bool_query().unwrap();
+            bool_query
+                .must
+                .iter()
+                .map(|leaf| format!("{:?}", leaf.as_leaf().unwrap()))
+                .collect()
+        };
+        let leaf_queries = build_leaf_queries(BooleanOperand::And.into());
+        assert_eq!(leaf_queries.len(), 2);
+        assert!(leaf_queries
+            .iter()
+            .all(|leaf_query| leaf_query.starts_with("FuzzyTermQuery")));
+
+        // Fuzzy terms cannot be part of a phrase.
+        let leaf_queries = build_leaf_queries(FullTextMode::PhraseFallbackToIntersection);
+        assert_eq!(leaf_queries.len(), 2);
+        assert!(leaf_queries
+            .iter()
+            .all(|leaf_query| leaf_query.starts_with("FuzzyTermQuery")));
+
+        let leaf_queries = build_leaf_queries(FullTextMode::Phrase { slop: 0 });
+        assert_eq!(leaf_queries.len(), 1);
+        assert!(leaf_queries[0].starts_with("PhraseQuery"));
+    }
+
+    #[test]
+    fn test_full_text_get_fuzzy_terms() {
+        let mut schema_builder = Schema::builder();
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        schema_builder.add_json_field("attributes", TEXT);
+        let schema = schema_builder.build();
+        let get_fuzzy_terms = |field: &str, mode: FullTextMode, fuzzy_distance: Option<u8>| {
+            let full_text_query = FullTextQuery {
+                field: field.to_string(),
+                text: "Helo wrld".to_string(),
+                params: super::FullTextParams {
+                    tokenizer: None,
+                    mode,
+                    zero_terms_query: crate::MatchAllOrNone::MatchNone,
+                    fuzzy_distance,
+                },
+            };
+            full_text_query.get_fuzzy_terms(&schema, &create_default_quickwit_tokenizer_manager())
+        };
+        assert_eq!(
+            get_fuzzy_terms("body", BooleanOperand::Or.into(), Some(1)),
+            vec![
+                Term::from_field_text(body_field, "helo"),
+                Term::from_field_text(body_field, "wrld")
+            ]
+        );
+        let bool_prefix_mode = FullTextMode::BoolPrefix {
+            operator: BooleanOperand::Or,
+            max_expansions: 50,
+        };
+        assert_eq!(
+            get_fuzzy_terms("body", bool_prefix_mode, Some(1)),
+            vec![Term::from_field_text(body_field, "helo")]
+        );
+        assert!(get_fuzzy_terms("body", BooleanOperand::Or.into(), None).is_empty());
+        assert!(get_fuzzy_terms("body", FullTextMode::Phrase { slop: 0 }, Some(1)).is_empty());
+        assert!(get_fuzzy_terms("attributes.msg", BooleanOperand::Or.into(), Some(1)).is_empty());
+    }
 }
@@ -0,0 +1,148 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use crate::query_ast::{BoolQuery, FullTextParams, FullTextQuery, QueryAst, TermQuery};
+use crate::BooleanOperand;
+
+/// Maximum Levenshtein distance supported by fuzzy queries.
+pub const MAX_FUZZY_DISTANCE: u8 = 2;
+
+/// Rewrites the term and full-text queries of `query_ast` so that their terms match any term
+/// within `fuzzy_distance` edits.
+///
+/// Only the terms of indexed text fields are matched approximately. Phrase queries, phrase prefix
+/// queries and the terms of other field types keep matching exactly.
+///
+/// The query AST is expected to be resolved, i.e. free of user input queries.
+pub fn apply_fuzzy_distance(query_ast: QueryAst, fuzzy_distance: u8) -> QueryAst {
+    if fuzzy_distance == 0 {
+        return query_ast;
+    }
+    let apply = |asts: Vec<QueryAst>| -> Vec<QueryAst> {
+        asts.into_iter()
+            .map(|ast| apply_fuzzy_distance(ast, fuzzy_distance))
+            .collect()
+    };
+    match query_ast {
+        QueryAst::Bool(BoolQuery {
+            must,
+            must_not,
+            should,
+            filter,
+        }) => BoolQuery {
+            must: apply(must),
+            must_not: apply(must_not),
+            should: apply(should),
+            filter: apply(filter),
+        }
+        .into(),
+        QueryAst::Boost { underlying, boost } => QueryAst::Boost {
+            underlying: Box::new(apply_fuzzy_distance(*underlying, fuzzy_distance)),
+            boost,
+        },
+        QueryAst::FullText(mut full_text_query) => {
+            full_text_query.params.fuzzy_distance = Some(fuzzy_distance);
+            full_text_query.into()
+        }
+        // A term query acts like a full-text query with a raw tokenizer.
+        QueryAst::Term(TermQuery { field, value }) => FullTextQuery {
+            field,
+            text: value,
+            params: FullTextParams {
+                tokenizer: Some("raw".to_string()),
+                mode: BooleanOperand::Or.into(),
+                zero_terms_query: Default::default(),
+                fuzzy_distance: Some(fuzzy_distance),
+            },
+        }
+        .into(),
+        ast @ QueryAst::TermSet(_)
+        | ast @ QueryAst::FieldPresence(_)
+        | ast @ QueryAst::PhrasePrefix(_)
+        | ast @ QueryAst::Range(_)
+        | ast @ QueryAst::UserInput(_)
+        | ast @ QueryAst::Wildcard(_)
+        | ast @ QueryAst::MatchAll
+        | ast @ QueryAst::MatchNone => ast,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::not_nan_f32::NotNaNf32;
+    use crate::query_ast::FullTextMode;
+
+    #[test]
+    fn test_apply_fuzzy_distance() {
+        let full_text_params = FullTextParams {
+            tokenizer: None,
+            mode: FullTextMode::PhraseFallbackToIntersection,
+            zero_terms_query: Default::default(),
+            fuzzy_distance: None,
+        };
+        let query_ast: QueryAst = BoolQuery {
+            must: vec![FullTextQuery {
+                field: "body".to_string(),
+                text: "helo".to_string(),
+                params: full_text_params.clone(),
+            }
+            .into()],
+            should: vec![QueryAst::Boost {
+                underlying: Box::new(TermQuery::from_field_value("title", "wrld").into()),
+                boost: NotNaNf32::try_from(2.0f32).unwrap(),
+            }],
+            filter: vec![QueryAst::MatchAll],
+            ..Default::default()
+        }
+        .into();
+        let expected_ast: QueryAst = BoolQuery {
+            must: vec![FullTextQuery {
+                field: "body".to_string(),
+                text: "helo".to_string(),
+                params: FullTextParams {
+                    fuzzy_distance: Some(1),
+                    ..full_text_params
+                },
+            }
+            .into()],
+            should: vec![QueryAst::Boost {
+                underlying: Box::new(
+                    FullTextQuery {
+                        field: "title".to_string(),
+                        text: "wrld".to_string(),
+                        params: FullTextParams {
+                            tokenizer: Some("raw".to_string()),
+                            mode: BooleanOperand::Or.into(),
+                            zero_terms_query: Default::default(),
+                            fuzzy_distance: Some(1),
+                        },
+                    }
+                    .into(),
+                ),
+                boost: NotNaNf32::try_from(2.0f32).unwrap(),
+            }],
+            filter: vec![QueryAst::MatchAll],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(apply_fuzzy_distance(query_ast.clone(), 1), expected_ast);
+        assert_eq!(apply_fuzzy_distance(query_ast.clone(), 0), query_ast);
+    }
+}
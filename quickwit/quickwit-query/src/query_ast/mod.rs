@@ -26,8 +26,10 @@ use crate::tokenizers::TokenizerManager;
 mod bool_query;
 mod field_presence;
 mod full_text_query;
+mod fuzzy;
 mod phrase_prefix_query;
 mod range_query;
+mod synonyms;
 mod tantivy_query_ast;
 mod term_query;
 mod term_set_query;
@@ -39,8 +41,10 @@ mod wildcard_query;
 pub use bool_query::BoolQuery;
 pub use field_presence::FieldPresenceQuery;
 pub use full_text_query::{FullTextMode, FullTextParams, FullTextQuery};
+pub use fuzzy::{apply_fuzzy_distance, MAX_FUZZY_DISTANCE};
 pub use phrase_prefix_query::PhrasePrefixQuery;
 pub use range_query::RangeQuery;
+pub use synonyms::{expand_synonyms, AppliedSynonym, SynonymTable};
 use tantivy_query_ast::TantivyQueryAst;
 pub use term_query::TermQuery;
 pub use term_set_query::TermSetQuery;
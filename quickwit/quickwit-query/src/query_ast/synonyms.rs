@@ -0,0 +1,375 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use anyhow::bail;
+
+use crate::query_ast::{BoolQuery, FullTextMode, FullTextParams, FullTextQuery, QueryAst};
+
+/// A table of synonym rules used to expand full-text queries at search time.
+///
+/// Rules follow the Solr/Elasticsearch synonym file syntax:
+/// - `k8s, kubernetes`: equivalence group, each term expands into all the others.
+/// - `lb => load balancer, loadbalancer`: one-way rule, only the left-hand side is expanded.
+///
+/// Empty lines and lines starting with `#` are ignored. Terms are matched case-insensitively
+/// against the whole text of a full-text query, so multi-word terms only match phrases.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SynonymTable {
+    expansions: HashMap<String, Vec<String>>,
+}
+
+/// A synonym expansion applied while rewriting a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedSynonym {
+    pub field: String,
+    pub text: String,
+    pub expansions: Vec<String>,
+    /// Whether some expansions were dropped to honor the expansion limit.
+    pub truncated: bool,
+}
+
+impl SynonymTable {
+    /// Parses a list of synonym rules.
+    pub fn parse<'a>(rules: impl IntoIterator<Item = &'a str>) -> anyhow::Result<SynonymTable> {
+        let mut synonym_table = SynonymTable::default();
+        for rule in rules {
+            let rule = rule.trim();
+            if rule.is_empty() || rule.starts_with('#') {
+                continue;
+            }
+            if let Some((sources, targets)) = rule.split_once("=>") {
+                let sources = parse_terms(sources);
+                let targets = parse_terms(targets);
+                if sources.is_empty() || targets.is_empty() {
+                    bail!("invalid synonym rule `{rule}`: both sides of `=>` must be non-empty");
+                }
+                for source in &sources {
+                    synonym_table.add_expansions(source, &targets);
+                }
+            } else {
+                let terms = parse_terms(rule);
+                if terms.len() < 2 {
+                    bail!("invalid synonym rule `{rule}`: expected at least two terms");
+                }
+                for term in &terms {
+                    synonym_table.add_expansions(term, &terms);
+                }
+            }
+        }
+        Ok(synonym_table)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.expansions.is_empty()
+    }
+
+    /// Returns the synonyms of `text`, if any.
+    pub fn expansions(&self, text: &str) -> Option<&[String]> {
+        self.expansions
+            .get(&normalize_term(text))
+            .map(|expansions| expansions.as_slice())
+    }
+
+    fn add_expansions(&mut self, source: &str, targets: &[String]) {
+        let expansions = self.expansions.entry(source.to_string()).or_default();
+        for target in targets {
+            if target != source && !expansions.contains(target) {
+                expansions.push(target.clone());
+            }
+        }
+    }
+}
+
+fn normalize_term(term: &str) -> String {
+    term.split_whitespace()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+fn parse_terms(terms: &str) -> Vec<String> {
+    terms
+        .split(',')
+        .map(normalize_term)
+        .filter(|term| !term.is_empty())
+        .collect()
+}
+
+/// Rewrites the full-text queries of `query_ast` matching an entry of `synonym_table` into a
+/// disjunction of the original query and its synonyms. Multi-word synonyms are searched as
+/// phrases. At most `max_expansions` synonyms are added per query.
+///
+/// The query AST is expected to be resolved, i.e. free of user input queries.
+pub fn expand_synonyms(
+    query_ast: QueryAst,
+    synonym_table: &SynonymTable,
+    max_expansions: usize,
+) -> (QueryAst, Vec<AppliedSynonym>) {
+    let mut applied_synonyms = Vec::new();
+    if synonym_table.is_empty() {
+        return (query_ast, applied_synonyms);
+    }
+    let query_ast = expand_synonyms_aux(
+        query_ast,
+        synonym_table,
+        max_expansions,
+        &mut applied_synonyms,
+    );
+    (query_ast, applied_synonyms)
+}
+
+fn expand_synonyms_aux(
+    query_ast: QueryAst,
+    synonym_table: &SynonymTable,
+    max_expansions: usize,
+    applied_synonyms: &mut Vec<AppliedSynonym>,
+) -> QueryAst {
+    let mut expand = |asts: Vec<QueryAst>| -> Vec<QueryAst> {
+        asts.into_iter()
+            .map(|ast| expand_synonyms_aux(ast, synonym_table, max_expansions, applied_synonyms))
+            .collect()
+    };
+    match query_ast {
+        QueryAst::Bool(BoolQuery {
+            must,
+            must_not,
+            should,
+            filter,
+        }) => BoolQuery {
+            must: expand(must),
+            must_not: expand(must_not),
+            should: expand(should),
+            filter: expand(filter),
+        }
+        .into(),
+        QueryAst::Boost { underlying, boost } => QueryAst::Boost {
+            underlying: Box::new(expand_synonyms_aux(
+                *underlying,
+                synonym_table,
+                max_expansions,
+                applied_synonyms,
+            )),
+            boost,
+        },
+        QueryAst::FullText(full_text_query) => {
+            let Some(expansions) = synonym_table.expansions(&full_text_query.text) else {
+                return full_text_query.into();
+            };
+            let truncated = expansions.len() > max_expansions;
+            let expansions = &expansions[..expansions.len().min(max_expansions)];
+            if expansions.is_empty() {
+                return full_text_query.into();
+            }
+            let mut should: Vec<QueryAst> = Vec::with_capacity(expansions.len() + 1);
+            for expansion in expansions {
+                let params = if expansion.contains(' ') {
+                    FullTextParams {
+                        mode: FullTextMode::Phrase { slop: 0 },
+                        ..full_text_query.params.clone()
+                    }
+                } else {
+                    full_text_query.params.clone()
+                };
+                should.push(
+                    FullTextQuery {
+                        field: full_text_query.field.clone(),
+                        text: expansion.clone(),
+                        params,
+                    }
+                    .into(),
+                );
+            }
+            applied_synonyms.push(AppliedSynonym {
+                field: full_text_query.field.clone(),
+                text: full_text_query.text.clone(),
+                expansions: expansions.to_vec(),
+                truncated,
+            });
+            should.insert(0, full_text_query.into());
+            BoolQuery {
+                should,
+                ..Default::default()
+            }
+            .into()
+        }
+        ast @ QueryAst::Term(_)
+        | ast @ QueryAst::TermSet(_)
+        | ast @ QueryAst::FieldPresence(_)
+        | ast @ QueryAst::PhrasePrefix(_)
+        | ast @ QueryAst::Range(_)
+        | ast @ QueryAst::UserInput(_)
+        | ast @ QueryAst::Wildcard(_)
+        | ast @ QueryAst::MatchAll
+        | ast @ QueryAst::MatchNone => ast,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BooleanOperand;
+
+    fn full_text(field: &str, text: &str, mode: FullTextMode) -> QueryAst {
+        FullTextQuery {
+            field: field.to_string(),
+            text: text.to_string(),
+            params: FullTextParams {
+                tokenizer: None,
+                mode,
+                zero_terms_query: Default::default(),
+                fuzzy_distance: None,
+            },
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_synonym_table_parse() {
+        let synonym_table = SynonymTable::parse([
+            "# comment",
+            "",
+            "k8s, Kubernetes",
+            "lb => load  balancer, loadbalancer",
+        ])
+        .unwrap();
+        assert_eq!(synonym_table.expansions("k8s").unwrap(), ["kubernetes"]);
+        assert_eq!(synonym_table.expansions("KUBERNETES").unwrap(), ["k8s"]);
+        assert_eq!(
+            synonym_table.expansions("lb").unwrap(),
+            ["load balancer", "loadbalancer"]
+        );
+        assert!(synonym_table.expansions("loadbalancer").is_none());
+
+        SynonymTable::parse(["k8s"]).unwrap_err();
+        SynonymTable::parse(["lb =>"]).unwrap_err();
+    }
+
+    #[test]
+    fn test_expand_synonyms_single_term() {
+        let synonym_table = SynonymTable::parse(["k8s, kubernetes"]).unwrap();
+        let mode = FullTextMode::PhraseFallbackToIntersection;
+        let query_ast = BoolQuery {
+            must: vec![full_text("body", "k8s", mode.clone())],
+            must_not: vec![full_text("body", "docker", mode.clone())],
+            ..Default::default()
+        }
+        .into();
+        let (expanded_ast, applied_synonyms) = expand_synonyms(query_ast, &synonym_table, 10);
+        let expected_ast: QueryAst = BoolQuery {
+            must: vec![BoolQuery {
+                should: vec![
+                    full_text("body", "k8s", mode.clone()),
+                    full_text("body", "kubernetes", mode.clone()),
+                ],
+                ..Default::default()
+            }
+            .into()],
+            must_not: vec![full_text("body", "docker", mode)],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(expanded_ast, expected_ast);
+        assert_eq!(
+            applied_synonyms,
+            [AppliedSynonym {
+                field: "body".to_string(),
+                text: "k8s".to_string(),
+                expansions: vec!["kubernetes".to_string()],
+                truncated: false,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_expand_synonyms_multi_word() {
+        let synonym_table =
+            SynonymTable::parse(["lb => load balancer", "error rate => failure ratio"]).unwrap();
+        let bool_mode = FullTextMode::Bool {
+            operator: BooleanOperand::And,
+        };
+        let phrase_mode = FullTextMode::Phrase { slop: 0 };
+
+        let (expanded_ast, _) = expand_synonyms(
+            full_text("body", "lb", bool_mode.clone()),
+            &synonym_table,
+            10,
+        );
+        let expected_ast: QueryAst = BoolQuery {
+            should: vec![
+                full_text("body", "lb", bool_mode.clone()),
+                full_text("body", "load balancer", phrase_mode.clone()),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(expanded_ast, expected_ast);
+
+        let (expanded_ast, applied_synonyms) = expand_synonyms(
+            full_text("body", "Error Rate", phrase_mode.clone()),
+            &synonym_table,
+            10,
+        );
+        assert_eq!(applied_synonyms.len(), 1);
+        assert_eq!(applied_synonyms[0].expansions, ["failure ratio"]);
+        let QueryAst::Bool(bool_query) = expanded_ast else {
+            panic!("expected a bool query");
+        };
+        assert_eq!(bool_query.should.len(), 2);
+
+        // Unquoted words are matched individually.
+        let query_ast: QueryAst = BoolQuery {
+            must: vec![
+                full_text("body", "error", bool_mode.clone()),
+                full_text("body", "rate", bool_mode),
+            ],
+            ..Default::default()
+        }
+        .into();
+        let (expanded_ast, applied_synonyms) =
+            expand_synonyms(query_ast.clone(), &synonym_table, 10);
+        assert_eq!(expanded_ast, query_ast);
+        assert!(applied_synonyms.is_empty());
+    }
+
+    #[test]
+    fn test_expand_synonyms_max_expansions() {
+        let synonym_table = SynonymTable::parse(["a, b, c, d"]).unwrap();
+        let mode = FullTextMode::PhraseFallbackToIntersection;
+        let (expanded_ast, applied_synonyms) =
+            expand_synonyms(full_text("body", "a", mode.clone()), &synonym_table, 2);
+        let expected_ast: QueryAst = BoolQuery {
+            should: vec![
+                full_text("body", "a", mode.clone()),
+                full_text("body", "b", mode.clone()),
+                full_text("body", "c", mode.clone()),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(expanded_ast, expected_ast);
+        assert!(applied_synonyms[0].truncated);
+
+        let (expanded_ast, applied_synonyms) =
+            expand_synonyms(full_text("body", "a", mode.clone()), &synonym_table, 0);
+        assert_eq!(expanded_ast, full_text("body", "a", mode));
+        assert!(applied_synonyms.is_empty());
+    }
+}
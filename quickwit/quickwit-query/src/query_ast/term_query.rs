@@ -64,6 +64,7 @@ impl BuildTantivyAst for TermQuery {
             // The parameter below won't matter, since we will have only one term
             mode: BooleanOperand::Or.into(),
             zero_terms_query: Default::default(),
+            fuzzy_distance: None,
         };
         crate::query_ast::utils::full_text_query(
             &self.field,
@@ -37,6 +37,10 @@ use crate::{BooleanOperand, InvalidQuery, JsonLiteral};
 
 const DEFAULT_PHRASE_QUERY_MAX_EXPANSION: u32 = 50;
 
+/// Pseudo field used to express field presence queries: `_exists_:my_field` is equivalent to
+/// `my_field:*`.
+const EXISTS_PSEUDO_FIELD_NAME: &str = "_exists_";
+
 /// A query expressed in the tantivy query grammar DSL.
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct UserInputQuery {
@@ -218,6 +222,12 @@ fn convert_user_input_literal(
         delimiter,
         slop,
     } = user_input_literal;
+    if field_name.as_deref() == Some(EXISTS_PSEUDO_FIELD_NAME) {
+        if prefix || delimiter != Delimiter::None {
+            anyhow::bail!("`{EXISTS_PSEUDO_FIELD_NAME}` expects a field name");
+        }
+        return Ok(FieldPresenceQuery { field: phrase }.into());
+    }
     let field_names: Vec<String> = if let Some(field_name) = field_name {
         vec![field_name]
     } else {
@@ -240,6 +250,7 @@ fn convert_user_input_literal(
         tokenizer: None,
         mode,
         zero_terms_query: crate::MatchAllOrNone::MatchNone,
+        fuzzy_distance: None,
     };
     let wildcard = delimiter == Delimiter::None && is_wildcard(&phrase);
     let mut phrase_queries: Vec<QueryAst> = field_names
@@ -285,7 +296,8 @@ fn convert_user_input_literal(
 #[cfg(test)]
 mod tests {
     use crate::query_ast::{
-        BoolQuery, BuildTantivyAst, FullTextMode, FullTextQuery, QueryAst, UserInputQuery,
+        BoolQuery, BuildTantivyAst, FieldPresenceQuery, FullTextMode, FullTextQuery, QueryAst,
+        UserInputQuery,
     };
     use crate::{create_default_quickwit_tokenizer_manager, BooleanOperand, InvalidQuery};
 
@@ -505,4 +517,60 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_user_input_query_exists() {
+        let parse_user_query_util = |query: &str| {
+            UserInputQuery {
+                user_text: query.to_string(),
+                default_fields: None,
+                default_operator: BooleanOperand::And,
+            }
+            .parse_user_query(&["body".to_string()])
+            .unwrap()
+        };
+        let expected_field_presence_query = |field: &str| -> QueryAst {
+            FieldPresenceQuery {
+                field: field.to_string(),
+            }
+            .into()
+        };
+        assert_eq!(
+            parse_user_query_util("_exists_:error.stack"),
+            expected_field_presence_query("error.stack")
+        );
+        assert_eq!(
+            parse_user_query_util("user_id:*"),
+            expected_field_presence_query("user_id")
+        );
+        {
+            let QueryAst::Bool(bool_query) = parse_user_query_util("-_exists_:user_id") else {
+                panic!()
+            };
+            assert!(bool_query.must.is_empty());
+            assert_eq!(
+                bool_query.must_not,
+                [expected_field_presence_query("user_id")]
+            );
+        }
+        {
+            let QueryAst::Bool(bool_query) =
+                parse_user_query_util("level:error AND NOT _exists_:user_id")
+            else {
+                panic!()
+            };
+            assert_eq!(bool_query.must.len(), 1);
+            assert_eq!(
+                bool_query.must_not,
+                [expected_field_presence_query("user_id")]
+            );
+        }
+        UserInputQuery {
+            user_text: "_exists_:\"user_id\"".to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::And,
+        }
+        .parse_user_query(&[])
+        .unwrap_err();
+    }
 }
@@ -185,6 +185,11 @@ fn compute_tantivy_ast_query_for_json(
     json_options: &JsonObjectOptions,
     tokenizer_manager: &TokenizerManager,
 ) -> Result<TantivyQueryAst, InvalidQuery> {
+    // Fuzzy term queries only support text fields, so the terms of json fields match exactly.
+    let full_text_params = FullTextParams {
+        fuzzy_distance: None,
+        ..full_text_params.clone()
+    };
     let mut bool_query = TantivyBoolQuery::default();
     let mut term = Term::with_capacity(100);
     let mut json_term_writer = JsonTermWriter::from_field_and_json_path(
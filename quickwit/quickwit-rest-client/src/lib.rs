@@ -25,9 +25,12 @@ use tokio::fs::File;
 use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
 use tracing::warn;
 
+use crate::models::IngestSource;
+
 pub mod error;
 pub mod models;
 pub mod rest_client;
+pub mod routing;
 
 // re-exports
 pub use quickwit_config::ConfigFormat;
@@ -48,6 +51,19 @@ impl BatchLineReader {
         Ok(Self::new(Box::new(file), max_batch_num_bytes))
     }
 
+    pub async fn from_ingest_source(
+        ingest_source: IngestSource,
+        max_batch_num_bytes: usize,
+    ) -> io::Result<Self> {
+        match ingest_source {
+            IngestSource::File(filepath) => Self::from_file(&filepath, max_batch_num_bytes).await,
+            IngestSource::Stdin => Ok(Self::from_stdin(max_batch_num_bytes)),
+            IngestSource::Str(ingest_payload) => {
+                Ok(Self::from_string(ingest_payload, max_batch_num_bytes))
+            }
+        }
+    }
+
     pub fn from_stdin(max_batch_num_bytes: usize) -> Self {
         Self::new(Box::new(tokio::io::stdin()), max_batch_num_bytes)
     }
@@ -17,16 +17,23 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
+use std::mem;
 use std::time::Duration;
 
 use bytes::Bytes;
-use quickwit_cluster::ClusterSnapshot;
+use quickwit_cluster::{ClusterMemberInfo, ClusterSnapshot};
 use quickwit_config::{ConfigFormat, SourceConfig};
 use quickwit_indexing::actors::IndexingServiceCounters;
 pub use quickwit_ingest::CommitType;
 use quickwit_metastore::{IndexMetadata, Split, SplitInfo};
+use quickwit_proto::metastore::IndexSnapshot;
+use quickwit_proto::search::ExplainResult;
 use quickwit_search::SearchResponseRest;
-use quickwit_serve::{ListSplitsQueryParams, ListSplitsResponse, SearchRequestQueryString};
+use quickwit_serve::{
+    IndexSummary, ListSplitsQueryParams, ListSplitsResponse, SearchRequestQueryString,
+    SplitMetadataUpdate,
+};
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use reqwest::{Client, ClientBuilder, Method, StatusCode, Url};
 use serde::Serialize;
@@ -35,11 +42,14 @@ use tracing::warn;
 
 use crate::error::Error;
 use crate::models::{ApiResponse, IngestSource, Timeout};
+use crate::routing::{DocRouter, RoutedIngestStats};
 use crate::BatchLineReader;
 
 pub const DEFAULT_BASE_URL: &str = "http://127.0.0.1:7280";
 pub const DEFAULT_CONTENT_TYPE: &str = "application/json";
 pub const INGEST_CONTENT_LENGTH_LIMIT: usize = 10 * 1024 * 1024; // 10MiB
+/// Maximum number of bytes buffered across the pending batches of a routed ingestion.
+const ROUTED_INGEST_PENDING_BYTES_LIMIT: usize = 64 * 1024 * 1024; // 64MiB
 pub const DEFAULT_CLIENT_CONNECT_TIMEOUT: Timeout = Timeout::from_secs(5);
 pub const DEFAULT_CLIENT_TIMEOUT: Timeout = Timeout::from_secs(10);
 pub const DEFAULT_CLIENT_SEARCH_TIMEOUT: Timeout = Timeout::from_mins(1);
@@ -228,6 +238,32 @@ impl QuickwitClient {
         Ok(search_response)
     }
 
+    pub async fn search_explain(
+        &self,
+        index_id: &str,
+        search_query: SearchRequestQueryString,
+    ) -> Result<ExplainResult, Error> {
+        let path = format!("{index_id}/search/explain");
+        let bytes = serde_json::to_string(&search_query)
+            .unwrap()
+            .as_bytes()
+            .to_vec();
+        let body = Bytes::from(bytes);
+        let response = self
+            .transport
+            .send::<()>(
+                Method::POST,
+                &path,
+                None,
+                None,
+                Some(body),
+                self.search_timeout,
+            )
+            .await?;
+        let explain_result = response.deserialize().await?;
+        Ok(explain_result)
+    }
+
     pub fn indexes(&self) -> IndexClient {
         IndexClient::new(&self.transport, self.timeout)
     }
@@ -236,6 +272,10 @@ impl QuickwitClient {
         SplitClient::new(&self.transport, self.timeout, index_id)
     }
 
+    pub fn snapshots<'a>(&'a self, index_id: &'a str) -> SnapshotClient {
+        SnapshotClient::new(&self.transport, self.timeout, index_id)
+    }
+
     pub fn sources<'a>(&'a self, index_id: &'a str) -> SourceClient {
         SourceClient::new(&self.transport, self.timeout, index_id)
     }
@@ -259,56 +299,203 @@ impl QuickwitClient {
         batch_size_limit_opt: Option<usize>,
         on_ingest_event: Option<&(dyn Fn(IngestEvent) + Sync)>,
         last_block_commit: CommitType,
+    ) -> Result<(), Error> {
+        let batch_size_limit = batch_size_limit_opt.unwrap_or(INGEST_CONTENT_LENGTH_LIMIT);
+        let mut batch_reader =
+            BatchLineReader::from_ingest_source(ingest_source, batch_size_limit).await?;
+        while let Some(batch) = batch_reader.next_batch().await? {
+            let commit_type = if batch_reader.has_next() {
+                CommitType::Auto
+            } else {
+                last_block_commit
+            };
+            self.ingest_batch(index_id, batch, commit_type, on_ingest_event)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Ingests NDJSON documents into the indexes selected by `doc_router`. Documents are
+    /// buffered per target index and sent in batches of at most `batch_size_limit_opt` bytes.
+    /// `last_block_commit` applies to the last batch sent to each index.
+    ///
+    /// Documents that cannot be routed are skipped and counted in the returned statistics. When
+    /// the pending batches exceed [`ROUTED_INGEST_PENDING_BYTES_LIMIT`] bytes, the largest one is
+    /// sent early, minus its last document. A failure to ingest into one index is recorded in
+    /// the returned statistics and does not stop the ingestion into the other indexes.
+    pub async fn ingest_routed(
+        &self,
+        doc_router: &DocRouter,
+        ingest_source: IngestSource,
+        batch_size_limit_opt: Option<usize>,
+        on_ingest_event: Option<&(dyn Fn(IngestEvent) + Sync)>,
+        last_block_commit: CommitType,
+    ) -> Result<RoutedIngestStats, Error> {
+        let batch_size_limit = batch_size_limit_opt.unwrap_or(INGEST_CONTENT_LENGTH_LIMIT);
+        let mut batch_reader =
+            BatchLineReader::from_ingest_source(ingest_source, batch_size_limit).await?;
+        let pending_bytes_limit = ROUTED_INGEST_PENDING_BYTES_LIMIT.max(batch_size_limit);
+        let mut pending_batches: BTreeMap<String, Vec<u8>> = BTreeMap::new();
+        let mut num_pending_bytes = 0;
+        let mut routed_ingest_stats = RoutedIngestStats::default();
+
+        while let Some(batch) = batch_reader.next_batch().await? {
+            for doc in batch.split_inclusive(|byte| *byte == b'\n') {
+                if doc.iter().all(u8::is_ascii_whitespace) {
+                    continue;
+                }
+                let Some(index_id) = doc_router.route(doc) else {
+                    routed_ingest_stats.num_unroutable_docs += 1;
+                    continue;
+                };
+                *routed_ingest_stats
+                    .num_docs_per_index
+                    .entry(index_id.clone())
+                    .or_default() += 1;
+
+                if routed_ingest_stats.failed_indexes.contains_key(&index_id) {
+                    continue;
+                }
+                let pending_batch = pending_batches.entry(index_id.clone()).or_default();
+
+                if !pending_batch.is_empty() && pending_batch.len() + doc.len() > batch_size_limit {
+                    let batch = Bytes::from(mem::take(pending_batch));
+                    num_pending_bytes -= batch.len();
+
+                    if let Err(error) = self
+                        .ingest_batch(&index_id, batch, CommitType::Auto, on_ingest_event)
+                        .await
+                    {
+                        pending_batches.remove(&index_id);
+                        routed_ingest_stats
+                            .failed_indexes
+                            .insert(index_id, error.to_string());
+                        continue;
+                    }
+                }
+                pending_batch.extend_from_slice(doc);
+                num_pending_bytes += doc.len();
+
+                if !doc.ends_with(b"\n") {
+                    pending_batch.push(b'\n');
+                    num_pending_bytes += 1;
+                }
+                if num_pending_bytes > pending_bytes_limit {
+                    num_pending_bytes -= self
+                        .ingest_largest_pending_batch(
+                            &mut pending_batches,
+                            &mut routed_ingest_stats,
+                            on_ingest_event,
+                        )
+                        .await;
+                }
+            }
+        }
+        // Every index that received documents and did not fail has a non-empty pending batch at
+        // this point, so each of them gets a last batch carrying `last_block_commit`.
+        for (index_id, pending_batch) in pending_batches {
+            let batch = Bytes::from(pending_batch);
+
+            if let Err(error) = self
+                .ingest_batch(&index_id, batch, last_block_commit, on_ingest_event)
+                .await
+            {
+                routed_ingest_stats
+                    .failed_indexes
+                    .insert(index_id, error.to_string());
+            }
+        }
+        Ok(routed_ingest_stats)
+    }
+
+    /// Sends the largest pending batch of a routed ingestion, except for its last document, so
+    /// that the last batch sent to the index still carries the requested commit type. Returns
+    /// the number of bytes removed from the pending batches.
+    async fn ingest_largest_pending_batch(
+        &self,
+        pending_batches: &mut BTreeMap<String, Vec<u8>>,
+        routed_ingest_stats: &mut RoutedIngestStats,
+        on_ingest_event: Option<&(dyn Fn(IngestEvent) + Sync)>,
+    ) -> usize {
+        let Some((index_id, pending_batch)) = pending_batches
+            .iter_mut()
+            .max_by_key(|(_, pending_batch)| pending_batch.len())
+        else {
+            return 0;
+        };
+        // Pending batches always end with a newline, so the last document starts right after the
+        // second to last one.
+        let Some(last_doc_start) = pending_batch[..pending_batch.len() - 1]
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map(|position| position + 1)
+        else {
+            // Every pending batch holds a single document.
+            return 0;
+        };
+        let last_doc = pending_batch.split_off(last_doc_start);
+        let batch = Bytes::from(mem::replace(pending_batch, last_doc));
+        let num_bytes = batch.len();
+        let index_id = index_id.clone();
+
+        if let Err(error) = self
+            .ingest_batch(&index_id, batch, CommitType::Auto, on_ingest_event)
+            .await
+        {
+            let num_dropped_bytes = pending_batches
+                .remove(&index_id)
+                .map(|pending_batch| pending_batch.len())
+                .unwrap_or_default();
+            routed_ingest_stats
+                .failed_indexes
+                .insert(index_id, error.to_string());
+            return num_bytes + num_dropped_bytes;
+        }
+        num_bytes
+    }
+
+    async fn ingest_batch(
+        &self,
+        index_id: &str,
+        batch: Bytes,
+        commit_type: CommitType,
+        on_ingest_event: Option<&(dyn Fn(IngestEvent) + Sync)>,
     ) -> Result<(), Error> {
         let ingest_path = if self.ingest_v2 {
             format!("{index_id}/ingest-v2")
         } else {
             format!("{index_id}/ingest")
         };
-        let batch_size_limit = batch_size_limit_opt.unwrap_or(INGEST_CONTENT_LENGTH_LIMIT);
-        let mut batch_reader = match ingest_source {
-            IngestSource::File(filepath) => {
-                BatchLineReader::from_file(&filepath, batch_size_limit).await?
-            }
-            IngestSource::Stdin => BatchLineReader::from_stdin(batch_size_limit),
-            IngestSource::Str(ingest_payload) => {
-                BatchLineReader::from_string(ingest_payload, batch_size_limit)
-            }
+        let (query_params, timeout) = if commit_type != CommitType::Auto {
+            (commit_type.to_query_parameter(), self.commit_timeout)
+        } else {
+            (None, self.ingest_timeout)
         };
-        while let Some(batch) = batch_reader.next_batch().await? {
-            loop {
-                let (query_params, timeout) =
-                    if !batch_reader.has_next() && last_block_commit != CommitType::Auto {
-                        (last_block_commit.to_query_parameter(), self.commit_timeout)
-                    } else {
-                        (None, self.ingest_timeout)
-                    };
-                let response = self
-                    .transport
-                    .send(
-                        Method::POST,
-                        &ingest_path,
-                        None,
-                        query_params,
-                        Some(batch.clone()),
-                        timeout,
-                    )
-                    .await?;
-                if response.status_code() == StatusCode::TOO_MANY_REQUESTS {
-                    if let Some(event_fn) = &on_ingest_event {
-                        event_fn(IngestEvent::Sleep)
-                    }
-                    tokio::time::sleep(Duration::from_secs(1)).await;
-                } else {
-                    response.check().await?;
-                    break;
+        loop {
+            let response = self
+                .transport
+                .send(
+                    Method::POST,
+                    &ingest_path,
+                    None,
+                    query_params,
+                    Some(batch.clone()),
+                    timeout,
+                )
+                .await?;
+            if response.status_code() == StatusCode::TOO_MANY_REQUESTS {
+                if let Some(event_fn) = &on_ingest_event {
+                    event_fn(IngestEvent::Sleep)
                 }
-            }
-            if let Some(event_fn) = on_ingest_event.as_ref() {
-                event_fn(IngestEvent::IngestedDocBatch(batch.len()))
+                tokio::time::sleep(Duration::from_secs(1)).await;
+            } else {
+                response.check().await?;
+                break;
             }
         }
-
+        if let Some(event_fn) = on_ingest_event.as_ref() {
+            event_fn(IngestEvent::IngestedDocBatch(batch.len()))
+        }
         Ok(())
     }
 }
@@ -361,6 +548,16 @@ impl<'a> IndexClient<'a> {
         Ok(indexes_metadatas)
     }
 
+    /// Lists the summaries of all indexes, including their numbers of published documents.
+    pub async fn summaries(&self) -> Result<Vec<IndexSummary>, Error> {
+        let response = self
+            .transport
+            .send::<()>(Method::GET, "indexes/_summary", None, None, None, self.timeout)
+            .await?;
+        let indexes_summaries = response.deserialize().await?;
+        Ok(indexes_summaries)
+    }
+
     pub async fn get(&self, index_id: &str) -> Result<IndexMetadata, Error> {
         let path = format!("indexes/{index_id}");
         let response = self
@@ -449,6 +646,72 @@ impl<'a, 'b> SplitClient<'a, 'b> {
         response.check().await?;
         Ok(())
     }
+
+    pub async fn update_metadata(
+        &self,
+        split_id: &str,
+        split_metadata_update: SplitMetadataUpdate,
+    ) -> Result<(), Error> {
+        let path = format!("{}/{split_id}/metadata", self.splits_root_url());
+        let body = Bytes::from(serde_json::to_vec(&split_metadata_update)?);
+        let response = self
+            .transport
+            .send::<()>(Method::PUT, &path, None, None, Some(body), self.timeout)
+            .await?;
+        response.check().await?;
+        Ok(())
+    }
+}
+
+/// Client for snapshot APIs.
+pub struct SnapshotClient<'a> {
+    transport: &'a Transport,
+    timeout: Timeout,
+    index_id: &'a str,
+}
+
+impl<'a> SnapshotClient<'a> {
+    fn new(transport: &'a Transport, timeout: Timeout, index_id: &'a str) -> Self {
+        Self {
+            transport,
+            timeout,
+            index_id,
+        }
+    }
+
+    fn snapshots_root_url(&self) -> String {
+        format!("indexes/{}/snapshots", self.index_id)
+    }
+
+    pub async fn create(&self) -> Result<IndexSnapshot, Error> {
+        let path = self.snapshots_root_url();
+        let response = self
+            .transport
+            .send::<()>(Method::POST, &path, None, None, None, self.timeout)
+            .await?;
+        let snapshot = response.deserialize().await?;
+        Ok(snapshot)
+    }
+
+    pub async fn list(&self) -> Result<Vec<IndexSnapshot>, Error> {
+        let path = self.snapshots_root_url();
+        let response = self
+            .transport
+            .send::<()>(Method::GET, &path, None, None, None, self.timeout)
+            .await?;
+        let snapshots = response.deserialize().await?;
+        Ok(snapshots)
+    }
+
+    pub async fn delete(&self, snapshot_id: &str) -> Result<(), Error> {
+        let path = format!("{}/{snapshot_id}", self.snapshots_root_url());
+        let response = self
+            .transport
+            .send::<()>(Method::DELETE, &path, None, None, None, self.timeout)
+            .await?;
+        response.check().await?;
+        Ok(())
+    }
 }
 
 /// Client for source APIs.
@@ -578,6 +841,22 @@ impl<'a> ClusterClient<'a> {
         let cluster_snapshot = response.deserialize().await?;
         Ok(cluster_snapshot)
     }
+
+    pub async fn members(&self) -> Result<Vec<ClusterMemberInfo>, Error> {
+        let response = self
+            .transport
+            .send::<()>(
+                Method::GET,
+                "cluster/members",
+                None,
+                None,
+                None,
+                self.timeout,
+            )
+            .await?;
+        let members_info = response.deserialize().await?;
+        Ok(members_info)
+    }
 }
 
 /// Client for Node-level Stats APIs.
@@ -652,15 +931,20 @@ fn header_from_config_format(config_format: ConfigFormat) -> HeaderMap {
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
     use std::path::PathBuf;
     use std::str::FromStr;
 
+    use quickwit_common::uri::Uri;
     use quickwit_config::{ConfigFormat, SourceConfig};
     use quickwit_indexing::mock_split;
     use quickwit_ingest::CommitType;
     use quickwit_metastore::IndexMetadata;
     use quickwit_search::SearchResponseRest;
-    use quickwit_serve::{ListSplitsQueryParams, ListSplitsResponse, SearchRequestQueryString};
+    use quickwit_serve::{
+        IndexSummary, ListSplitsQueryParams, ListSplitsResponse, SearchRequestQueryString,
+        SplitMetadataUpdate,
+    };
     use reqwest::header::CONTENT_TYPE;
     use reqwest::{StatusCode, Url};
     use serde_json::json;
@@ -674,6 +958,7 @@ mod test {
     use crate::error::Error;
     use crate::models::IngestSource;
     use crate::rest_client::QuickwitClientBuilder;
+    use crate::routing::{DocRouter, RoutedIngestStats};
 
     #[tokio::test]
     async fn test_client_no_server() {
@@ -699,9 +984,16 @@ mod test {
             num_hits: 0,
             hits: Vec::new(),
             snippets: None,
+            hit_index_ids: None,
             aggregations: None,
             elapsed_time_micros: 100,
             errors: Vec::new(),
+            next_page_token: None,
+            stats: None,
+            failed_indexes: Vec::new(),
+            num_hits_accuracy: None,
+            timed_out: false,
+            warnings: Vec::new(),
         };
         Mock::given(method("POST"))
             .and(path("/api/v1/my-index/search"))
@@ -720,6 +1012,39 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_search_explain_endpoint() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let search_query_params = SearchRequestQueryString {
+            query: "severity:ERROR".to_string(),
+            ..Default::default()
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/v1/my-index/search/explain"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(json!({"splits": [{
+                    "split_id": "split-1",
+                    "index_id": "my-index",
+                    "pruned_by_time_range": true,
+                    "num_candidate_docs": 0,
+                    "top_hits": [],
+                    "searcher_addr": "",
+                }]})),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let explain_result = qw_client
+            .search_explain("my-index", search_query_params)
+            .await
+            .unwrap();
+        assert_eq!(explain_result.splits.len(), 1);
+        assert_eq!(explain_result.splits[0].split_id, "split-1");
+        assert!(explain_result.splits[0].pruned_by_time_range);
+    }
+
     fn get_ndjson_filepath(ndjson_dataset_filename: &str) -> String {
         format!(
             "{}/resources/tests/{}",
@@ -821,6 +1146,191 @@ mod test {
             .unwrap();
     }
 
+    #[tokio::test]
+    async fn test_ingest_routed() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let ingest_payload = concat!(
+            r#"{"tenant": "acme", "body": "foo"}"#,
+            "\n",
+            r#"{"tenant": "globex", "body": "bar"}"#,
+            "\n",
+            r#"{"body": "baz"}"#,
+            "\n",
+            r#"{"tenant": "acme", "body": "qux"}"#,
+        );
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-acme/ingest"))
+            .and(query_param("commit", "wait_for"))
+            .and(body_bytes(concat!(
+                r#"{"tenant": "acme", "body": "foo"}"#,
+                "\n",
+                r#"{"tenant": "acme", "body": "qux"}"#,
+                "\n",
+            )))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-globex/ingest"))
+            .and(query_param("commit", "wait_for"))
+            .and(body_bytes(
+                b"{\"tenant\": \"globex\", \"body\": \"bar\"}\n".to_vec(),
+            ))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let doc_router = DocRouter::new("tenant", "logs-{tenant}", None).unwrap();
+        let ingest_source = IngestSource::Str(ingest_payload.to_string());
+        let routed_ingest_stats = qw_client
+            .ingest_routed(&doc_router, ingest_source, None, None, CommitType::WaitFor)
+            .await
+            .unwrap();
+        assert_eq!(routed_ingest_stats.num_docs_per_index.len(), 2);
+        assert_eq!(routed_ingest_stats.num_docs_per_index["logs-acme"], 2);
+        assert_eq!(routed_ingest_stats.num_docs_per_index["logs-globex"], 1);
+        assert_eq!(routed_ingest_stats.num_unroutable_docs, 1);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_routed_commits_last_batch_of_each_index() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let ingest_payload = concat!(
+            r#"{"tenant": "acme", "seq": 1}"#,
+            "\n",
+            r#"{"tenant": "acme", "seq": 2}"#,
+            "\n",
+            r#"{"body": "no tenant"}"#,
+            "\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-acme/ingest"))
+            .and(query_param_is_missing("commit"))
+            .and(body_bytes(b"{\"tenant\": \"acme\", \"seq\": 1}\n".to_vec()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-acme/ingest"))
+            .and(query_param("commit", "force"))
+            .and(body_bytes(b"{\"tenant\": \"acme\", \"seq\": 2}\n".to_vec()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-default/ingest"))
+            .and(query_param("commit", "force"))
+            .and(body_bytes(b"{\"body\": \"no tenant\"}\n".to_vec()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let doc_router =
+            DocRouter::new("tenant", "logs-{tenant}", Some("logs-default".to_string())).unwrap();
+        let ingest_source = IngestSource::Str(ingest_payload.to_string());
+        // Each document is 29 bytes long, so two documents do not fit in one batch.
+        let routed_ingest_stats = qw_client
+            .ingest_routed(
+                &doc_router,
+                ingest_source,
+                Some(40),
+                None,
+                CommitType::Force,
+            )
+            .await
+            .unwrap();
+        assert_eq!(routed_ingest_stats.num_docs_per_index["logs-acme"], 2);
+        assert_eq!(routed_ingest_stats.num_docs_per_index["logs-default"], 1);
+        assert_eq!(routed_ingest_stats.num_unroutable_docs, 0);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_routed_reports_failed_indexes() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let ingest_payload = concat!(
+            r#"{"tenant": "acme", "seq": 1}"#,
+            "\n",
+            r#"{"tenant": "globex", "seq": 1}"#,
+            "\n",
+            r#"{"tenant": "acme", "seq": 2}"#,
+            "\n",
+        );
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-acme/ingest"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(json!({"message": "index not found"})),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-globex/ingest"))
+            .and(body_bytes(
+                b"{\"tenant\": \"globex\", \"seq\": 1}\n".to_vec(),
+            ))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let doc_router = DocRouter::new("tenant", "logs-{tenant}", None).unwrap();
+        let ingest_source = IngestSource::Str(ingest_payload.to_string());
+        // The first acme document fills a batch on its own, so the failure is detected before
+        // the second one is routed.
+        let routed_ingest_stats = qw_client
+            .ingest_routed(&doc_router, ingest_source, Some(40), None, CommitType::Auto)
+            .await
+            .unwrap();
+        assert_eq!(routed_ingest_stats.num_docs_per_index["logs-acme"], 2);
+        assert_eq!(routed_ingest_stats.num_docs_per_index["logs-globex"], 1);
+        assert_eq!(routed_ingest_stats.failed_indexes.len(), 1);
+        assert!(routed_ingest_stats.failed_indexes["logs-acme"].contains("index not found"));
+    }
+
+    #[tokio::test]
+    async fn test_ingest_largest_pending_batch_keeps_last_doc() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        Mock::given(method("POST"))
+            .and(path("/api/v1/logs-acme/ingest"))
+            .and(query_param_is_missing("commit"))
+            .and(body_bytes(b"{\"seq\": 1}\n{\"seq\": 2}\n".to_vec()))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        let mut pending_batches = BTreeMap::from_iter([
+            (
+                "logs-acme".to_string(),
+                b"{\"seq\": 1}\n{\"seq\": 2}\n{\"seq\": 3}\n".to_vec(),
+            ),
+            ("logs-globex".to_string(), b"{\"seq\": 1}\n".to_vec()),
+        ]);
+        let mut routed_ingest_stats = RoutedIngestStats::default();
+        let num_bytes = qw_client
+            .ingest_largest_pending_batch(&mut pending_batches, &mut routed_ingest_stats, None)
+            .await;
+        assert_eq!(num_bytes, 22);
+        assert_eq!(pending_batches["logs-acme"], b"{\"seq\": 3}\n");
+        assert_eq!(pending_batches["logs-globex"], b"{\"seq\": 1}\n");
+        assert!(routed_ingest_stats.failed_indexes.is_empty());
+
+        // Every pending batch holds a single document: nothing can be sent early.
+        let num_bytes = qw_client
+            .ingest_largest_pending_batch(&mut pending_batches, &mut routed_ingest_stats, None)
+            .await;
+        assert_eq!(num_bytes, 0);
+    }
+
     #[tokio::test]
     async fn test_ingest_endpoint_should_return_api_error() {
         let mock_server = MockServer::start().await;
@@ -878,6 +1388,26 @@ mod test {
             vec![index_metadata.clone()]
         );
 
+        // GET indexes summaries
+        let index_summary = IndexSummary {
+            index_id: "test-index".to_string(),
+            index_uri: Uri::for_test("ram:///indexes/test-index"),
+            num_published_splits: 1,
+            num_published_docs: 10,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/indexes/_summary"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(vec![&index_summary]),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let indexes_summaries = qw_client.indexes().summaries().await.unwrap();
+        assert_eq!(indexes_summaries.len(), 1);
+        assert_eq!(indexes_summaries[0].index_id, "test-index");
+        assert_eq!(indexes_summaries[0].num_published_docs, 10);
+
         // POST create index
         let index_config_to_create = index_metadata.index_config.clone();
         Mock::given(method("POST"))
@@ -1026,6 +1556,77 @@ mod test {
             .mark_for_deletion(vec!["split-1".to_string()])
             .await
             .unwrap_err();
+
+        // Update metadata
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/my-index/splits/split-1/metadata"))
+            .and(body_json(json!({"num_docs": 42, "time_range_start": 1})))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let split_metadata_update = SplitMetadataUpdate {
+            num_docs: Some(42),
+            time_range_start: Some(1),
+            ..Default::default()
+        };
+        qw_client
+            .splits("my-index")
+            .update_metadata("split-1", split_metadata_update)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_endpoints() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClientBuilder::new(server_url).build();
+        let snapshot = IndexSnapshot {
+            snapshot_id: "snapshot-1".to_string(),
+            index_uid: "my-index:11111111111111111111111111".to_string(),
+            create_timestamp: 1,
+            split_ids: vec!["split-1".to_string()],
+            index_checkpoint_json: "{}".to_string(),
+        };
+        // POST create snapshot
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexes/my-index/snapshots"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(snapshot.clone()))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.snapshots("my-index").create().await.unwrap(),
+            snapshot
+        );
+
+        // GET snapshots
+        Mock::given(method("GET"))
+            .and(path("/api/v1/indexes/my-index/snapshots"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(vec![snapshot.clone()]),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.snapshots("my-index").list().await.unwrap(),
+            vec![snapshot]
+        );
+
+        // DELETE snapshot
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/indexes/my-index/snapshots/snapshot-1"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!({})))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        qw_client
+            .snapshots("my-index")
+            .delete("snapshot-1")
+            .await
+            .unwrap();
     }
 
     #[tokio::test]
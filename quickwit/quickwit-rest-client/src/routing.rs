@@ -0,0 +1,174 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use anyhow::bail;
+use quickwit_config::validate_identifier;
+use serde_json::Value as JsonValue;
+
+/// Routes NDJSON documents to an index derived from the value of one of their fields.
+///
+/// The target index ID is obtained by substituting the `{<routing field>}` placeholder of the
+/// index ID template with the value of the routing field, for instance documents with a `tenant`
+/// field equal to `acme` are routed to `logs-acme` by the template `logs-{tenant}`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DocRouter {
+    routing_field: String,
+    json_pointer: String,
+    index_id_template: String,
+    default_index_id_opt: Option<String>,
+}
+
+impl DocRouter {
+    /// Creates a new router. Nested routing fields are expressed with dots, e.g.
+    /// `resource.tenant`. Documents without a routing field value are routed to
+    /// `default_index_id_opt`, if any.
+    pub fn new(
+        routing_field: impl Into<String>,
+        index_id_template: impl Into<String>,
+        default_index_id_opt: Option<String>,
+    ) -> anyhow::Result<Self> {
+        let routing_field = routing_field.into();
+        let index_id_template = index_id_template.into();
+        if routing_field.is_empty() {
+            bail!("routing field must not be empty");
+        }
+        let placeholder = format!("{{{routing_field}}}");
+        if !index_id_template.contains(&placeholder) {
+            bail!(
+                "index ID template `{index_id_template}` must contain the placeholder \
+                 `{placeholder}`"
+            );
+        }
+        let json_pointer = format!("/{}", routing_field.replace('.', "/"));
+        Ok(Self {
+            routing_field,
+            json_pointer,
+            index_id_template,
+            default_index_id_opt,
+        })
+    }
+
+    pub fn routing_field(&self) -> &str {
+        &self.routing_field
+    }
+
+    /// Returns the ID of the index the document should be sent to, or `None` if the document is
+    /// not a JSON object with a string, number, or boolean routing field value and no default
+    /// index is configured. Documents whose routing field value yields an invalid index ID are
+    /// unroutable as well.
+    pub fn route(&self, doc: &[u8]) -> Option<String> {
+        let Some(routing_value) = self.routing_value(doc) else {
+            return self.default_index_id_opt.clone();
+        };
+        let placeholder = format!("{{{}}}", self.routing_field);
+        let index_id = self.index_id_template.replace(&placeholder, &routing_value);
+        validate_identifier("index", &index_id).ok()?;
+        Some(index_id)
+    }
+
+    fn routing_value(&self, doc: &[u8]) -> Option<String> {
+        let doc_json: JsonValue = serde_json::from_slice(doc).ok()?;
+        match doc_json.pointer(&self.json_pointer)? {
+            JsonValue::String(value) if !value.is_empty() => Some(value.clone()),
+            JsonValue::Number(value) => Some(value.to_string()),
+            JsonValue::Bool(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Statistics of an ingestion routed by a [`DocRouter`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RoutedIngestStats {
+    /// Number of documents routed to each index. Some of the documents routed to an index listed
+    /// in `failed_indexes` may not have been ingested.
+    pub num_docs_per_index: BTreeMap<String, u64>,
+    /// Number of documents that could not be routed and were not sent.
+    pub num_unroutable_docs: u64,
+    /// Error that stopped the ingestion into each index that failed. The documents routed to
+    /// those indexes after the failure are dropped.
+    pub failed_indexes: BTreeMap<String, String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_doc_router_new() {
+        DocRouter::new("tenant", "logs-{tenant}", None).unwrap();
+
+        let error = DocRouter::new("tenant", "logs", None).unwrap_err();
+        assert!(error.to_string().contains("`{tenant}`"));
+
+        DocRouter::new("", "logs-{}", None).unwrap_err();
+    }
+
+    #[test]
+    fn test_doc_router_route() {
+        let doc_router = DocRouter::new("tenant", "logs-{tenant}", None).unwrap();
+        assert_eq!(
+            doc_router.route(br#"{"tenant": "acme", "body": "foo"}"#),
+            Some("logs-acme".to_string())
+        );
+        assert_eq!(
+            doc_router.route(br#"{"tenant": 42}"#),
+            Some("logs-42".to_string())
+        );
+        assert_eq!(doc_router.route(br#"{"body": "foo"}"#), None);
+        assert_eq!(doc_router.route(br#"{"tenant": ""}"#), None);
+        assert_eq!(doc_router.route(br#"{"tenant": ["acme"]}"#), None);
+        assert_eq!(doc_router.route(b"not json"), None);
+    }
+
+    #[test]
+    fn test_doc_router_route_invalid_index_id() {
+        let doc_router = DocRouter::new("tenant", "logs-{tenant}", None).unwrap();
+        assert_eq!(doc_router.route(br#"{"tenant": "../acme"}"#), None);
+        assert_eq!(doc_router.route(br#"{"tenant": "acme/ingest"}"#), None);
+        assert_eq!(doc_router.route(br#"{"tenant": "ac me"}"#), None);
+
+        let doc_router = DocRouter::new("tenant", "{tenant}", Some("logs".to_string())).unwrap();
+        assert_eq!(doc_router.route(br#"{"tenant": "42"}"#), None);
+        assert_eq!(
+            doc_router.route(br#"{"tenant": "acme"}"#),
+            Some("acme".to_string())
+        );
+    }
+
+    #[test]
+    fn test_doc_router_route_nested_field_with_default_index() {
+        let doc_router = DocRouter::new(
+            "resource.tenant",
+            "{resource.tenant}-logs",
+            Some("unrouted-logs".to_string()),
+        )
+        .unwrap();
+        assert_eq!(
+            doc_router.route(br#"{"resource": {"tenant": "acme"}}"#),
+            Some("acme-logs".to_string())
+        );
+        assert_eq!(
+            doc_router.route(br#"{"resource": {}}"#),
+            Some("unrouted-logs".to_string())
+        );
+    }
+}
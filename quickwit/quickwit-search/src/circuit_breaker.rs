@@ -0,0 +1,257 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Configuration of the circuit breakers protecting the root from degraded searcher nodes.
+#[derive(Clone, Copy, Debug)]
+pub struct CircuitBreakerConfig {
+    /// Number of most recent requests considered to compute the error rate of a node.
+    pub window_size: usize,
+    /// Error rate, between 0 and 1, at or above which the circuit breaker trips.
+    pub error_rate_threshold: f32,
+    /// How long a tripped circuit breaker stays open before letting a probe request through.
+    pub recovery_timeout: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            window_size: 10,
+            error_rate_threshold: 0.5,
+            recovery_timeout: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a circuit breaker.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitBreakerState {
+    /// Requests are sent to the node.
+    Closed,
+    /// The node is excluded until the recovery timeout elapses.
+    Open,
+    /// A single probe request is sent to the node to decide whether it recovered.
+    HalfOpen,
+}
+
+/// Circuit breaker tracking the outcome of the last requests sent to a node.
+///
+/// The circuit breaker trips, i.e. opens, when the error rate over the last `window_size`
+/// requests reaches `error_rate_threshold`. Once `recovery_timeout` has elapsed, it becomes
+/// half-open and lets a single probe request through: a success closes it, a failure opens it
+/// again. If the outcome of the probe is never recorded, another probe is let through after
+/// another `recovery_timeout`.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: CircuitBreakerState,
+    /// Outcomes of the last requests, `true` standing for a failure.
+    window: VecDeque<bool>,
+    num_failures: usize,
+    /// When the circuit breaker opened or, if half-open, when the last probe was let through.
+    last_transition_at: Instant,
+}
+
+impl CircuitBreaker {
+    /// Creates a closed circuit breaker.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: CircuitBreakerState::Closed,
+            window: VecDeque::with_capacity(config.window_size),
+            num_failures: 0,
+            last_transition_at: Instant::now(),
+        }
+    }
+
+    /// Returns the current state of the circuit breaker.
+    pub fn state(&self) -> CircuitBreakerState {
+        self.state
+    }
+
+    /// Error rate over the requests of the current window.
+    pub fn error_rate(&self) -> f32 {
+        if self.window.is_empty() {
+            return 0.0;
+        }
+        self.num_failures as f32 / self.window.len() as f32
+    }
+
+    /// Returns whether a request could be sent to the node, without acquiring the probe of an open
+    /// circuit breaker.
+    pub fn is_available(&self, now: Instant) -> bool {
+        match self.state {
+            CircuitBreakerState::Closed => true,
+            CircuitBreakerState::Open | CircuitBreakerState::HalfOpen => {
+                now.duration_since(self.last_transition_at) >= self.config.recovery_timeout
+            }
+        }
+    }
+
+    /// Returns whether a request can be sent to the node, transitioning from open to half-open
+    /// once the recovery timeout has elapsed.
+    pub fn try_acquire(&mut self, now: Instant) -> bool {
+        if !self.is_available(now) {
+            return false;
+        }
+        if self.state != CircuitBreakerState::Closed {
+            self.state = CircuitBreakerState::HalfOpen;
+            self.last_transition_at = now;
+        }
+        true
+    }
+
+    /// Records the outcome of a request sent to the node.
+    pub fn record(&mut self, is_success: bool, now: Instant) {
+        match self.state {
+            CircuitBreakerState::Closed => {
+                if self.window.len() == self.config.window_size {
+                    if let Some(true) = self.window.pop_front() {
+                        self.num_failures -= 1;
+                    }
+                }
+                self.window.push_back(!is_success);
+                if !is_success {
+                    self.num_failures += 1;
+                }
+                if self.window.len() == self.config.window_size
+                    && self.error_rate() >= self.config.error_rate_threshold
+                {
+                    self.open(now);
+                }
+            }
+            CircuitBreakerState::HalfOpen if is_success => self.close(),
+            CircuitBreakerState::HalfOpen => self.open(now),
+            // A request sent before the circuit breaker tripped.
+            CircuitBreakerState::Open => {}
+        }
+    }
+
+    fn open(&mut self, now: Instant) {
+        self.state = CircuitBreakerState::Open;
+        self.last_transition_at = now;
+    }
+
+    fn close(&mut self) {
+        self.state = CircuitBreakerState::Closed;
+        self.window.clear();
+        self.num_failures = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn circuit_breaker_for_test() -> CircuitBreaker {
+        CircuitBreaker::new(CircuitBreakerConfig {
+            window_size: 4,
+            error_rate_threshold: 0.5,
+            recovery_timeout: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn test_circuit_breaker_trips_on_error_rate() {
+        let mut circuit_breaker = circuit_breaker_for_test();
+        let now = Instant::now();
+
+        // The window is not full yet.
+        circuit_breaker.record(false, now);
+        circuit_breaker.record(false, now);
+        circuit_breaker.record(true, now);
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::Closed);
+        assert!(circuit_breaker.try_acquire(now));
+
+        circuit_breaker.record(true, now);
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::Open);
+        assert_eq!(circuit_breaker.error_rate(), 0.5);
+        assert!(!circuit_breaker.try_acquire(now));
+    }
+
+    #[test]
+    fn test_circuit_breaker_sliding_window() {
+        let mut circuit_breaker = circuit_breaker_for_test();
+        let now = Instant::now();
+
+        circuit_breaker.record(false, now);
+        for _ in 0..4 {
+            circuit_breaker.record(true, now);
+        }
+        assert_eq!(circuit_breaker.error_rate(), 0.0);
+
+        circuit_breaker.record(false, now);
+        assert_eq!(circuit_breaker.error_rate(), 0.25);
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::Closed);
+    }
+
+    #[test]
+    fn test_circuit_breaker_half_open_probe() {
+        let mut circuit_breaker = circuit_breaker_for_test();
+        let now = Instant::now();
+
+        for _ in 0..4 {
+            circuit_breaker.record(false, now);
+        }
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::Open);
+
+        let after_timeout = now + Duration::from_secs(30);
+        // Checking the availability does not acquire the probe.
+        assert!(circuit_breaker.is_available(after_timeout));
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::Open);
+
+        assert!(circuit_breaker.try_acquire(after_timeout));
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::HalfOpen);
+        // Only one probe is let through.
+        assert!(!circuit_breaker.is_available(after_timeout));
+        assert!(!circuit_breaker.try_acquire(after_timeout));
+
+        circuit_breaker.record(false, after_timeout);
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::Open);
+        assert!(!circuit_breaker.try_acquire(after_timeout + Duration::from_secs(1)));
+
+        let after_second_timeout = after_timeout + Duration::from_secs(30);
+        assert!(circuit_breaker.try_acquire(after_second_timeout));
+        circuit_breaker.record(true, after_second_timeout);
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::Closed);
+        assert_eq!(circuit_breaker.error_rate(), 0.0);
+        assert!(circuit_breaker.try_acquire(after_second_timeout));
+    }
+
+    #[test]
+    fn test_circuit_breaker_lost_probe() {
+        let mut circuit_breaker = circuit_breaker_for_test();
+        let now = Instant::now();
+
+        for _ in 0..4 {
+            circuit_breaker.record(false, now);
+        }
+        let after_timeout = now + Duration::from_secs(30);
+        assert!(circuit_breaker.try_acquire(after_timeout));
+        // The outcome of the probe is never recorded.
+        assert!(!circuit_breaker.try_acquire(after_timeout + Duration::from_secs(29)));
+        assert!(circuit_breaker.try_acquire(after_timeout + Duration::from_secs(30)));
+        assert_eq!(circuit_breaker.state(), CircuitBreakerState::HalfOpen);
+    }
+}
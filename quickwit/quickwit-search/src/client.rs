@@ -122,6 +122,42 @@ impl SearchServiceClient {
         }
     }
 
+    /// Perform root explain search.
+    pub async fn explain_search(
+        &mut self,
+        request: quickwit_proto::search::SearchRequest,
+    ) -> crate::Result<quickwit_proto::search::ExplainResult> {
+        match &mut self.client_impl {
+            SearchServiceClientImpl::Grpc(grpc_client) => {
+                let tonic_request = Request::new(request);
+                let tonic_response = grpc_client
+                    .explain_search(tonic_request)
+                    .await
+                    .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+                Ok(tonic_response.into_inner())
+            }
+            SearchServiceClientImpl::Local(service) => service.explain_search(request).await,
+        }
+    }
+
+    /// Checks the health of the node.
+    pub async fn health_check(
+        &mut self,
+        request: quickwit_proto::search::HealthCheckRequest,
+    ) -> crate::Result<quickwit_proto::search::HealthCheckResponse> {
+        match &mut self.client_impl {
+            SearchServiceClientImpl::Grpc(grpc_client) => {
+                let tonic_request = Request::new(request);
+                let tonic_response = grpc_client
+                    .health_check(tonic_request)
+                    .await
+                    .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+                Ok(tonic_response.into_inner())
+            }
+            SearchServiceClientImpl::Local(service) => service.health_check(request).await,
+        }
+    }
+
     /// Perform leaf search.
     pub async fn leaf_search(
         &mut self,
@@ -129,7 +165,11 @@ impl SearchServiceClient {
     ) -> crate::Result<quickwit_proto::search::LeafSearchResponse> {
         match &mut self.client_impl {
             SearchServiceClientImpl::Grpc(grpc_client) => {
-                let tonic_request = Request::new(request);
+                let timeout_opt = request.timeout_millis.map(Duration::from_millis);
+                let mut tonic_request = Request::new(request);
+                if let Some(timeout) = timeout_opt {
+                    tonic_request.set_timeout(timeout);
+                }
                 let tonic_response = grpc_client
                     .leaf_search(tonic_request)
                     .await
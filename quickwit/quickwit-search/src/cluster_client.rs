@@ -17,11 +17,17 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 
 use base64::Engine;
 use futures::future::ready;
 use futures::{Future, StreamExt};
+use quickwit_common::retry_budget::{
+    global_retry_budget, RetryBudget, RetryLayer, RETRY_BUDGET_EXHAUSTED,
+};
 use quickwit_proto::search::{
     FetchDocsRequest, FetchDocsResponse, GetKvRequest, LeafListFieldsRequest, LeafListTermsRequest,
     LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse, LeafSearchStreamRequest,
@@ -47,18 +53,40 @@ const MAX_GET_KV_ATTEMPTS: usize = 6;
 /// We attempt to store our KVs on two nodes.
 const TARGET_NUM_REPLICATION: usize = 2;
 
+/// Default maximum number of retries of a leaf search request.
+const DEFAULT_MAX_LEAF_SEARCH_RETRIES: usize = 1;
+
 /// Client that executes placed requests (Request, `SearchServiceClient`) and provides
 /// retry policies for `FetchDocsRequest`, `LeafSearchRequest` and `LeafSearchStreamRequest`
 /// to retry on other `SearchServiceClient`.
 #[derive(Clone)]
 pub struct ClusterClient {
     pub(crate) search_job_placer: SearchJobPlacer,
+    max_leaf_search_retries: usize,
+    retry_budget: Arc<RetryBudget>,
 }
 
 impl ClusterClient {
     /// Instantiates [`ClusterClient`].
     pub fn new(search_job_placer: SearchJobPlacer) -> Self {
-        Self { search_job_placer }
+        Self {
+            search_job_placer,
+            max_leaf_search_retries: DEFAULT_MAX_LEAF_SEARCH_RETRIES,
+            retry_budget: global_retry_budget(),
+        }
+    }
+
+    /// Sets the maximum number of times a leaf search request is retried on another node.
+    pub fn with_max_leaf_search_retries(mut self, max_leaf_search_retries: usize) -> Self {
+        self.max_leaf_search_retries = max_leaf_search_retries;
+        self
+    }
+
+    /// Sets the retry budget consulted before retrying a request on another node. Defaults to
+    /// the retry budget of the node.
+    pub fn with_retry_budget(mut self, retry_budget: Arc<RetryBudget>) -> Self {
+        self.retry_budget = retry_budget;
+        self
     }
 
     /// Fetches docs with retry on another node client.
@@ -68,12 +96,21 @@ impl ClusterClient {
         mut client: SearchServiceClient,
     ) -> crate::Result<FetchDocsResponse> {
         let mut response_res = client.fetch_docs(request.clone()).await;
+        self.record_search_result(client.grpc_addr(), &response_res);
         let retry_policy = DefaultRetryPolicy {};
-        if let Some(retry_request) = retry_policy.retry_request(request, &response_res) {
+        let Some(retry_request) = retry_policy.retry_request(request, &response_res) else {
+            self.retry_budget.record_primary_success();
+            return response_res;
+        };
+        if self
+            .retry_budget
+            .try_acquire_retry(RetryLayer::ClusterClient)
+        {
             assert!(!retry_request.split_offsets.is_empty());
+            let excluded_addrs = HashSet::from_iter([client.grpc_addr()]);
             client = retry_client(
                 &self.search_job_placer,
-                client.grpc_addr(),
+                &excluded_addrs,
                 &retry_request.split_offsets[0].split_id,
             )
             .await?;
@@ -82,36 +119,88 @@ impl ClusterClient {
                 response_res, retry_request, client
             );
             response_res = client.fetch_docs(retry_request).await;
+            self.record_search_result(client.grpc_addr(), &response_res);
         }
         response_res
     }
 
-    /// Leaf search with retry on another node client.
+    /// Leaf search with retries on other node clients.
+    ///
+    /// The failing splits are retried up to `max_leaf_search_retries` times, each time on a node
+    /// that has not failed yet. Retries stop early once every node has failed. Splits that still
+    /// fail are reported in the `failed_splits` of the response.
     pub async fn leaf_search(
         &self,
         request: LeafSearchRequest,
         mut client: SearchServiceClient,
     ) -> crate::Result<LeafSearchResponse> {
         let mut response_res = client.leaf_search(request.clone()).await;
+        self.record_search_result(client.grpc_addr(), &response_res);
+        set_split_stats_searcher_addr(&mut response_res, client.grpc_addr());
         let retry_policy = LeafSearchRetryPolicy {};
-        if let Some(retry_request) = retry_policy.retry_request(request, &response_res) {
+        let mut retry_request_opt = retry_policy.retry_request(request, &response_res);
+        // Refill the budget even when retries are disabled: it is shared with the other layers.
+        if retry_request_opt.is_none() {
+            self.retry_budget.record_primary_success();
+        }
+        let mut excluded_addrs: HashSet<SocketAddr> = HashSet::new();
+
+        for num_retries in 1..=self.max_leaf_search_retries {
+            let Some(retry_request) = retry_request_opt else {
+                break;
+            };
+            excluded_addrs.insert(client.grpc_addr());
+            // Every node already failed: retrying would hit one of them again.
+            if !self.search_job_placer.has_node_outside(&excluded_addrs) {
+                break;
+            }
+            if !self
+                .retry_budget
+                .try_acquire_retry(RetryLayer::ClusterClient)
+            {
+                tag_failed_splits_retry_budget_exhausted(&mut response_res);
+                break;
+            }
             assert!(!retry_request.split_offsets.is_empty());
-            client = retry_client(
+            client = match retry_client(
                 &self.search_job_placer,
-                client.grpc_addr(),
+                &excluded_addrs,
                 &retry_request.split_offsets[0].split_id,
             )
-            .await?;
+            .await
+            {
+                Ok(client) => client,
+                Err(error) => {
+                    // The failed splits of the partial response report what was not searched.
+                    warn!(error=?error, "failed to find a node to retry the leaf search on");
+                    break;
+                }
+            };
             debug!(
-                "Leaf search response error: `{:?}`. Retry once to execute {:?} with {:?}",
-                response_res, retry_request, client
+                "Leaf search response error: `{:?}`. Retry #{} to execute {:?} with {:?}",
+                response_res, num_retries, retry_request, client
             );
-            let retry_result = client.leaf_search(retry_request).await;
+            let mut retry_result = client.leaf_search(retry_request.clone()).await;
+            self.record_search_result(client.grpc_addr(), &retry_result);
+            set_split_stats_searcher_addr(&mut retry_result, client.grpc_addr());
             response_res = merge_leaf_search_results(response_res, retry_result);
+            retry_request_opt = retry_policy.retry_request(retry_request, &response_res);
         }
         response_res
     }
 
+    /// Records the outcome of a request in the circuit breaker of the node that served it. Only
+    /// internal errors and timeouts count as failures of the node: the other errors are caused
+    /// by the request itself.
+    fn record_search_result<T>(&self, grpc_addr: SocketAddr, response_res: &crate::Result<T>) {
+        let is_node_failure = matches!(
+            response_res,
+            Err(SearchError::Internal(_)) | Err(SearchError::Timeout(_))
+        );
+        self.search_job_placer
+            .record_search_result(grpc_addr, !is_node_failure);
+    }
+
     /// Leaf search with retry on another node client.
     pub async fn leaf_list_fields(
         &self,
@@ -132,6 +221,7 @@ impl ClusterClient {
         // in this case we send all results.
         let (result_sender, result_receiver) = unbounded_channel();
         let client_pool = self.search_job_placer.clone();
+        let retry_budget = self.retry_budget.clone();
         let retry_policy = LeafSearchStreamRetryPolicy {};
         tokio::spawn(async move {
             let result_stream = client.leaf_search_stream(request.clone()).await;
@@ -139,33 +229,48 @@ impl ClusterClient {
             // errors.
             let forward_result =
                 forward_leaf_search_stream(result_stream, result_sender.clone(), false).await;
-            if let Some(retry_request) = retry_policy.retry_request(request, &forward_result) {
-                assert!(!retry_request.split_offsets.is_empty());
-                let retry_client_res = retry_client(
-                    &client_pool,
-                    client.grpc_addr(),
-                    &retry_request.split_offsets[0].split_id,
-                )
-                .await;
-                let mut retry_client = match retry_client_res {
-                    Ok(retry_client) => retry_client,
-                    Err(error) => {
-                        // Propagates the error if we cannot get a new client and stops the task.
-                        let _ = result_sender.send(Err(SearchError::from(error)));
-                        return;
-                    }
-                };
-                debug!(
-                    "Leaf search stream response error. Retry once to execute {:?} with {:?}",
-                    retry_request, client
-                );
-                let retry_results_stream = retry_client.leaf_search_stream(retry_request).await;
-                // Forward all results to the result_sender as we won't do another retry.
-                // It is ok to ignore send errors, there is nothing else to do.
-                let _ =
-                    forward_leaf_search_stream(retry_results_stream, result_sender.clone(), true)
-                        .await;
+            let Some(retry_request) = retry_policy.retry_request(request, &forward_result) else {
+                if forward_result.is_ok() {
+                    retry_budget.record_primary_success();
+                }
+                return;
+            };
+            if !retry_budget.try_acquire_retry(RetryLayer::ClusterClient) {
+                let failed_split_ids = retry_request
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect::<Vec<_>>();
+                let _ = result_sender.send(Err(SearchError::Internal(format!(
+                    "{RETRY_BUDGET_EXHAUSTED}: failed to search splits {failed_split_ids:?}"
+                ))));
+                return;
             }
+            assert!(!retry_request.split_offsets.is_empty());
+            let excluded_addrs = HashSet::from_iter([client.grpc_addr()]);
+            let retry_client_res = retry_client(
+                &client_pool,
+                &excluded_addrs,
+                &retry_request.split_offsets[0].split_id,
+            )
+            .await;
+            let mut retry_client = match retry_client_res {
+                Ok(retry_client) => retry_client,
+                Err(error) => {
+                    // Propagates the error if we cannot get a new client and stops the task.
+                    let _ = result_sender.send(Err(SearchError::from(error)));
+                    return;
+                }
+            };
+            debug!(
+                "Leaf search stream response error. Retry once to execute {:?} with {:?}",
+                retry_request, client
+            );
+            let retry_results_stream = retry_client.leaf_search_stream(retry_request).await;
+            // Forward all results to the result_sender as we won't do another retry.
+            // It is ok to ignore send errors, there is nothing else to do.
+            let _ =
+                forward_leaf_search_stream(retry_results_stream, result_sender.clone(), true).await;
         });
 
         UnboundedReceiverStream::new(result_receiver)
@@ -274,6 +379,28 @@ fn merge_intermediate_aggregation(left: &[u8], right: &[u8]) -> crate::Result<Ve
     Ok(serialized)
 }
 
+// Tags the splits that are not retried because the retry budget is exhausted.
+fn tag_failed_splits_retry_budget_exhausted(response_res: &mut crate::Result<LeafSearchResponse>) {
+    if let Ok(response) = response_res {
+        for failed_split in &mut response.failed_splits {
+            failed_split.error = format!("{RETRY_BUDGET_EXHAUSTED}: {}", failed_split.error);
+        }
+    }
+}
+
+/// Records the address of the searcher that answered a leaf search request in the split stats
+/// of its response.
+fn set_split_stats_searcher_addr(
+    response_res: &mut crate::Result<LeafSearchResponse>,
+    searcher_addr: SocketAddr,
+) {
+    if let Ok(response) = response_res {
+        for split_stats in &mut response.split_stats {
+            split_stats.searcher_addr = searcher_addr.to_string();
+        }
+    }
+}
+
 fn merge_leaf_search_response(
     mut left_response: LeafSearchResponse,
     right_response: LeafSearchResponse,
@@ -281,6 +408,10 @@ fn merge_leaf_search_response(
     left_response
         .partial_hits
         .extend(right_response.partial_hits);
+    left_response.split_stats.extend(right_response.split_stats);
+    left_response
+        .partial_splits
+        .extend(right_response.partial_splits);
     let intermediate_aggregation_result: Option<Vec<u8>> = match (
         left_response.intermediate_aggregation_result,
         right_response.intermediate_aggregation_result,
@@ -301,6 +432,8 @@ fn merge_leaf_search_response(
             + right_response.num_attempted_splits,
         failed_splits: right_response.failed_splits,
         partial_hits: left_response.partial_hits,
+        split_stats: left_response.split_stats,
+        partial_splits: left_response.partial_splits,
     })
 }
 
@@ -349,6 +482,7 @@ async fn forward_leaf_search_stream(
 mod tests {
     use std::collections::HashSet;
     use std::net::SocketAddr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     use quickwit_proto::search::{
         PartialHit, SearchRequest, SearchStreamRequest, SortValue, SplitIdAndFooterOffsets,
@@ -380,6 +514,7 @@ mod tests {
                 split_footer_start: 0,
                 timestamp_start: None,
                 timestamp_end: None,
+                num_docs: 0,
             }],
             ..Default::default()
         }
@@ -396,6 +531,9 @@ mod tests {
             search_request: Some(search_request),
             doc_mapper: "doc_mapper".to_string(),
             index_uri: "uri".to_string(),
+            timeout_millis: None,
+            index_id: "test-idx".to_string(),
+            num_searchers: None,
             split_offsets: vec![
                 SplitIdAndFooterOffsets {
                     split_id: "split_1".to_string(),
@@ -403,6 +541,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    num_docs: 0,
                 },
                 SplitIdAndFooterOffsets {
                     split_id: "split_2".to_string(),
@@ -410,6 +549,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    num_docs: 0,
                 },
             ],
         }
@@ -437,6 +577,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    num_docs: 0,
                 },
                 SplitIdAndFooterOffsets {
                     split_id: "split_2".to_string(),
@@ -444,6 +585,7 @@ mod tests {
                     split_footer_end: 100,
                     timestamp_start: None,
                     timestamp_end: None,
+                    num_docs: 0,
                 },
             ],
         }
@@ -552,8 +694,8 @@ mod tests {
     #[tokio::test]
     async fn test_cluster_client_leaf_search_retry_on_failing_splits() {
         let request = mock_leaf_search_request();
-        let mut mock_search_service = MockSearchService::new();
-        mock_search_service
+        let mut mock_search_service_1 = MockSearchService::new();
+        mock_search_service_1
             .expect_leaf_search()
             .withf(|request| request.split_offsets[0].split_id == "split_1")
             .return_once(|_: LeafSearchRequest| {
@@ -568,7 +710,8 @@ mod tests {
                     ..Default::default()
                 })
             });
-        mock_search_service
+        let mut mock_search_service_2 = MockSearchService::new();
+        mock_search_service_2
             .expect_leaf_search()
             .withf(|request| request.split_offsets[0].split_id == "split_2")
             .return_once(|_: LeafSearchRequest| {
@@ -584,18 +727,279 @@ mod tests {
                     ..Default::default()
                 })
             });
-        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", mock_search_service_1),
+            ("127.0.0.1:1002", mock_search_service_2),
+        ]);
+        let first_client_addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let first_client = searcher_pool.get(&first_client_addr).unwrap();
         let search_job_placer = SearchJobPlacer::new(searcher_pool);
-        let first_client = search_job_placer
-            .assign_job(SearchJob::for_test("split_1", 0), &HashSet::new())
-            .await
-            .unwrap();
         let cluster_client = ClusterClient::new(search_job_placer);
         let result = cluster_client.leaf_search(request, first_client).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().num_hits, 2);
     }
 
+    #[tokio::test]
+    async fn test_cluster_client_leaf_search_retries_on_other_nodes() {
+        let request = mock_leaf_search_request();
+        let mut mock_search_service_1 = MockSearchService::new();
+        mock_search_service_1
+            .expect_leaf_search()
+            .return_once(|_: LeafSearchRequest| {
+                Ok(LeafSearchResponse {
+                    num_hits: 1,
+                    failed_splits: vec![SplitSearchError {
+                        error: "mock_error".to_string(),
+                        split_id: "split_2".to_string(),
+                        retryable_error: true,
+                    }],
+                    num_attempted_splits: 2,
+                    ..Default::default()
+                })
+            });
+        let mut mock_search_service_2 = MockSearchService::new();
+        mock_search_service_2
+            .expect_leaf_search()
+            .withf(|request| request.split_offsets.len() == 1)
+            .return_once(|_: LeafSearchRequest| {
+                Ok(LeafSearchResponse {
+                    failed_splits: vec![SplitSearchError {
+                        error: "mock_error".to_string(),
+                        split_id: "split_2".to_string(),
+                        retryable_error: true,
+                    }],
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            });
+        let mut mock_search_service_3 = MockSearchService::new();
+        mock_search_service_3
+            .expect_leaf_search()
+            .withf(|request| request.split_offsets.len() == 1)
+            .return_once(|_: LeafSearchRequest| {
+                Ok(LeafSearchResponse {
+                    num_hits: 1,
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", mock_search_service_1),
+            ("127.0.0.1:1002", mock_search_service_2),
+            ("127.0.0.1:1003", mock_search_service_3),
+        ]);
+        let first_client_addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let first_client = searcher_pool.get(&first_client_addr).unwrap();
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer).with_max_leaf_search_retries(2);
+        let leaf_search_response = cluster_client
+            .leaf_search(request, first_client)
+            .await
+            .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 2);
+        assert!(leaf_search_response.failed_splits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cluster_client_leaf_search_max_retries() {
+        let request = mock_leaf_search_request();
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(1)
+            .returning(|_: LeafSearchRequest| {
+                Ok(LeafSearchResponse {
+                    num_hits: 1,
+                    failed_splits: vec![SplitSearchError {
+                        error: "mock_error".to_string(),
+                        split_id: "split_2".to_string(),
+                        retryable_error: true,
+                    }],
+                    num_attempted_splits: 2,
+                    ..Default::default()
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let first_client_addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let first_client = searcher_pool.get(&first_client_addr).unwrap();
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer).with_max_leaf_search_retries(0);
+        let leaf_search_response = cluster_client
+            .leaf_search(request, first_client)
+            .await
+            .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 1);
+        assert_eq!(leaf_search_response.failed_splits.len(), 1);
+        assert_eq!(leaf_search_response.failed_splits[0].split_id, "split_2");
+    }
+
+    #[tokio::test]
+    async fn test_cluster_client_leaf_search_does_not_retry_on_failed_nodes() {
+        let request = mock_leaf_search_request();
+        let mut mock_search_service_1 = MockSearchService::new();
+        mock_search_service_1
+            .expect_leaf_search()
+            .times(1)
+            .returning(|_: LeafSearchRequest| Ok(mock_leaf_search_response_with_failed_split()));
+        let mut mock_search_service_2 = MockSearchService::new();
+        mock_search_service_2
+            .expect_leaf_search()
+            .times(1)
+            .returning(|_: LeafSearchRequest| Ok(mock_leaf_search_response_with_failed_split()));
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", mock_search_service_1),
+            ("127.0.0.1:1002", mock_search_service_2),
+        ]);
+        let first_client_addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let first_client = searcher_pool.get(&first_client_addr).unwrap();
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer)
+            .with_max_leaf_search_retries(5)
+            .with_retry_budget(Arc::new(RetryBudget::unlimited()));
+        let leaf_search_response = cluster_client
+            .leaf_search(request, first_client)
+            .await
+            .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 2);
+        assert_eq!(leaf_search_response.failed_splits.len(), 1);
+        assert_eq!(leaf_search_response.failed_splits[0].split_id, "split_2");
+    }
+
+    #[tokio::test]
+    async fn test_cluster_client_leaf_search_no_retries_records_primary_success() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(1)
+            .returning(|_: LeafSearchRequest| {
+                Ok(LeafSearchResponse {
+                    num_hits: 2,
+                    num_attempted_splits: 2,
+                    ..Default::default()
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let first_client_addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let first_client = searcher_pool.get(&first_client_addr).unwrap();
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let retry_budget = Arc::new(RetryBudget::new(100, 1));
+        assert!(retry_budget.try_acquire_retry(RetryLayer::ClusterClient));
+        assert!(!retry_budget.try_acquire_retry(RetryLayer::ClusterClient));
+
+        let cluster_client = ClusterClient::new(search_job_placer)
+            .with_max_leaf_search_retries(0)
+            .with_retry_budget(retry_budget.clone());
+        let leaf_search_response = cluster_client
+            .leaf_search(mock_leaf_search_request(), first_client)
+            .await
+            .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 2);
+        assert!(retry_budget.try_acquire_retry(RetryLayer::ClusterClient));
+    }
+
+    fn mock_leaf_search_response_with_failed_split() -> LeafSearchResponse {
+        LeafSearchResponse {
+            num_hits: 1,
+            failed_splits: vec![SplitSearchError {
+                error: "mock_error".to_string(),
+                split_id: "split_2".to_string(),
+                retryable_error: true,
+            }],
+            num_attempted_splits: 2,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cluster_client_leaf_search_retry_budget_exhausted() {
+        let mut mock_search_service_1 = MockSearchService::new();
+        mock_search_service_1
+            .expect_leaf_search()
+            .times(10)
+            .returning(|_: LeafSearchRequest| Ok(mock_leaf_search_response_with_failed_split()));
+        let mut mock_search_service_2 = MockSearchService::new();
+        // Only the first failure is retried: the budget allows a single retry and is not
+        // refilled since no request succeeds.
+        mock_search_service_2
+            .expect_leaf_search()
+            .times(1)
+            .returning(|_: LeafSearchRequest| Ok(mock_leaf_search_response_with_failed_split()));
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", mock_search_service_1),
+            ("127.0.0.1:1002", mock_search_service_2),
+        ]);
+        let first_client_addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
+        let cluster_client = ClusterClient::new(search_job_placer)
+            .with_retry_budget(Arc::new(RetryBudget::new(10, 1)));
+
+        for request_num in 0..10 {
+            let first_client = searcher_pool.get(&first_client_addr).unwrap();
+            let leaf_search_response = cluster_client
+                .leaf_search(mock_leaf_search_request(), first_client)
+                .await
+                .unwrap();
+            assert_eq!(leaf_search_response.failed_splits.len(), 1);
+            let error = &leaf_search_response.failed_splits[0].error;
+            if request_num == 0 {
+                assert_eq!(error, "mock_error");
+            } else {
+                assert_eq!(error, "retry_budget_exhausted: mock_error");
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cluster_client_leaf_search_retry_budget_low_failure_rate() {
+        let num_requests = Arc::new(AtomicUsize::new(0));
+        let mut mock_search_service_1 = MockSearchService::new();
+        // One request out of 20 fails.
+        mock_search_service_1
+            .expect_leaf_search()
+            .times(100)
+            .returning(move |_: LeafSearchRequest| {
+                if num_requests.fetch_add(1, Ordering::Relaxed) % 20 == 19 {
+                    Ok(mock_leaf_search_response_with_failed_split())
+                } else {
+                    Ok(LeafSearchResponse {
+                        num_hits: 2,
+                        num_attempted_splits: 2,
+                        ..Default::default()
+                    })
+                }
+            });
+        let mut mock_search_service_2 = MockSearchService::new();
+        mock_search_service_2
+            .expect_leaf_search()
+            .times(5)
+            .returning(|_: LeafSearchRequest| {
+                Ok(LeafSearchResponse {
+                    num_hits: 1,
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", mock_search_service_1),
+            ("127.0.0.1:1002", mock_search_service_2),
+        ]);
+        let first_client_addr: SocketAddr = "127.0.0.1:1001".parse().unwrap();
+        let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
+        let cluster_client = ClusterClient::new(search_job_placer)
+            .with_retry_budget(Arc::new(RetryBudget::new(10, 1)));
+
+        for _ in 0..100 {
+            let first_client = searcher_pool.get(&first_client_addr).unwrap();
+            let leaf_search_response = cluster_client
+                .leaf_search(mock_leaf_search_request(), first_client)
+                .await
+                .unwrap();
+            assert_eq!(leaf_search_response.num_hits, 2);
+            assert!(leaf_search_response.failed_splits.is_empty());
+        }
+    }
+
     #[test]
     fn test_merge_leaf_search_retry_on_partial_success() -> anyhow::Result<()> {
         let split_error = SplitSearchError {
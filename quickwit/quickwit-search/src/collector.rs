@@ -20,14 +20,18 @@
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+use std::time::Instant;
 
 use itertools::Itertools;
 use quickwit_common::binary_heap::{SortKeyMapper, TopK};
 use quickwit_doc_mapper::{DocMapper, WarmupInfo};
 use quickwit_proto::search::{
     LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortOrder, SortValue,
-    SplitSearchError,
+    SplitSearchError, SplitSearchStats,
 };
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use serde::Deserialize;
 use tantivy::aggregation::agg_req::{get_fast_field_names, Aggregations};
 use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResults;
@@ -35,9 +39,13 @@ use tantivy::aggregation::{AggregationLimits, AggregationSegmentCollector};
 use tantivy::collector::{Collector, SegmentCollector};
 use tantivy::columnar::{ColumnType, MonotonicallyMappableToU64};
 use tantivy::fastfield::Column;
-use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+use tantivy::query::{EnableScoring, Query};
+use tantivy::{DocId, Score, Searcher, SegmentOrdinal, SegmentReader, TantivyError};
 
-use crate::filters::{create_timestamp_filter_builder, TimestampFilter, TimestampFilterBuilder};
+use crate::filters::{
+    create_geo_point_filter_builders, create_timestamp_filter_builder, GeoPointFilter,
+    GeoPointFilterBuilder, TimestampFilter, TimestampFilterBuilder,
+};
 use crate::find_trace_ids_collector::{FindTraceIdsCollector, FindTraceIdsSegmentCollector, Span};
 use crate::GlobalDocAddress;
 
@@ -293,6 +301,53 @@ enum AggregationSegmentCollectors {
     TantivyAggregationSegmentCollector(AggregationSegmentCollector),
 }
 
+// Measure the cost associated to searching in a split with the given number of documents.
+//
+// Every split has a fixed cost (opening it, warming up its hotcache...), on top of which we count
+// one unit per million documents.
+pub(crate) fn compute_split_cost(num_docs: u64) -> usize {
+    1 + (num_docs / 1_000_000) as usize
+}
+
+/// Number of documents a segment collector collects between two checks of its deadline.
+const DEADLINE_CHECK_INTERVAL: u32 = 1_024;
+
+/// Deadline after which the collection of a split is interrupted.
+///
+/// The deadline is checked cooperatively by the collector. Once it has passed, it stays expired,
+/// and the collector stops collecting documents.
+#[derive(Clone, Debug)]
+pub(crate) struct CollectionDeadline {
+    deadline: Instant,
+    expired: Arc<AtomicBool>,
+}
+
+impl CollectionDeadline {
+    pub fn new(deadline: Instant) -> Self {
+        CollectionDeadline {
+            deadline,
+            expired: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Checks the clock and returns true if the deadline has passed.
+    fn check_expired(&self) -> bool {
+        if self.is_expired() {
+            return true;
+        }
+        if Instant::now() < self.deadline {
+            return false;
+        }
+        self.expired.store(true, AtomicOrdering::Relaxed);
+        true
+    }
+
+    /// Returns true if the deadline was found expired by a previous check.
+    fn is_expired(&self) -> bool {
+        self.expired.load(AtomicOrdering::Relaxed)
+    }
+}
+
 /// Quickwit collector working at the scale of the segment.
 pub struct QuickwitSegmentCollector {
     num_hits: u64,
@@ -302,12 +357,38 @@ pub struct QuickwitSegmentCollector {
     top_k_hits: TopK<SegmentPartialHit, SegmentPartialHitSortingKey, HitSortingMapper>,
     segment_ord: u32,
     timestamp_filter_opt: Option<TimestampFilter>,
+    geo_point_filters: Vec<GeoPointFilter>,
     aggregation: Option<AggregationSegmentCollectors>,
     search_after: Option<PartialHit>,
     split_search_after_order: Ordering,
+    deadline_opt: Option<CollectionDeadline>,
+    num_docs_until_deadline_check: u32,
+    interrupted: bool,
 }
 
 impl QuickwitSegmentCollector {
+    /// Returns true if the deadline has passed, in which case the remaining documents of the
+    /// segment are skipped. The deadline is only checked every [`DEADLINE_CHECK_INTERVAL`]
+    /// documents.
+    #[inline]
+    fn is_interrupted(&mut self) -> bool {
+        if self.interrupted {
+            return true;
+        }
+        let Some(deadline) = &self.deadline_opt else {
+            return false;
+        };
+        if self.num_docs_until_deadline_check == 0 {
+            self.num_docs_until_deadline_check = DEADLINE_CHECK_INTERVAL;
+            if deadline.check_expired() {
+                self.interrupted = true;
+                return true;
+            }
+        }
+        self.num_docs_until_deadline_check -= 1;
+        false
+    }
+
     #[inline]
     fn collect_top_k(&mut self, doc_id: DocId, score: Score) {
         let (sort_value, sort_value2) =
@@ -351,9 +432,13 @@ impl QuickwitSegmentCollector {
     #[inline]
     fn accept_document(&self, doc_id: DocId) -> bool {
         if let Some(ref timestamp_filter) = self.timestamp_filter_opt {
-            return timestamp_filter.is_within_range(doc_id);
+            if !timestamp_filter.is_within_range(doc_id) {
+                return false;
+            }
         }
-        true
+        self.geo_point_filters
+            .iter()
+            .all(|geo_point_filter| geo_point_filter.is_match(doc_id))
     }
 }
 
@@ -385,7 +470,7 @@ impl SegmentCollector for QuickwitSegmentCollector {
 
     #[inline]
     fn collect(&mut self, doc_id: DocId, score: Score) {
-        if !self.accept_document(doc_id) {
+        if self.is_interrupted() || !self.accept_document(doc_id) {
             return;
         }
 
@@ -433,6 +518,8 @@ impl SegmentCollector for QuickwitSegmentCollector {
             partial_hits,
             failed_splits: Vec::new(),
             num_attempted_splits: 1,
+            split_stats: Vec::new(),
+            partial_splits: Vec::new(),
         })
     }
 }
@@ -558,9 +645,13 @@ pub(crate) struct QuickwitCollector {
     pub max_hits: usize,
     pub sort_by: SortByPair,
     timestamp_filter_builder_opt: Option<TimestampFilterBuilder>,
+    geo_point_filter_builders: Vec<GeoPointFilterBuilder>,
     pub aggregation: Option<QuickwitAggregations>,
     pub aggregation_limits: AggregationLimits,
     search_after: Option<PartialHit>,
+    pub deadline_opt: Option<CollectionDeadline>,
+    /// Number of searchers running concurrently on the segments of the split.
+    pub num_searchers: usize,
 }
 
 impl QuickwitCollector {
@@ -576,6 +667,9 @@ impl QuickwitCollector {
         if let Some(timestamp_filter_builder) = &self.timestamp_filter_builder_opt {
             fast_field_names.insert(timestamp_filter_builder.timestamp_field_name.clone());
         }
+        for geo_point_filter_builder in &self.geo_point_filter_builders {
+            fast_field_names.insert(geo_point_filter_builder.geo_point_field_name().to_string());
+        }
         fast_field_names
     }
 
@@ -586,6 +680,75 @@ impl QuickwitCollector {
             ..WarmupInfo::default()
         }
     }
+
+    /// Searches the segments of a split in the given order.
+    ///
+    /// If the collector has a deadline and it passes, the remaining documents and segments are
+    /// skipped. The hits and aggregations collected so far are returned and the split is reported
+    /// in `partial_splits`. The first segment is always searched, so that a split that spent its
+    /// budget warming up still returns some results.
+    ///
+    /// With more than one searcher, the segments are searched concurrently on the search thread
+    /// pool. Each searcher picks the next segment to search in the given order.
+    pub fn search_segments(
+        &self,
+        searcher: &Searcher,
+        query: &dyn Query,
+        segment_ords: &[SegmentOrdinal],
+    ) -> tantivy::Result<LeafSearchResponse> {
+        let enable_scoring = if self.requires_scoring() {
+            EnableScoring::enabled_from_searcher(searcher)
+        } else {
+            EnableScoring::disabled_from_searcher(searcher)
+        };
+        let weight = query.weight(enable_scoring)?;
+        let next_segment_idx = AtomicUsize::new(0);
+
+        let run_searcher = || -> tantivy::Result<Vec<tantivy::Result<LeafSearchResponse>>> {
+            let mut segment_fruits = Vec::new();
+            loop {
+                let segment_idx = next_segment_idx.fetch_add(1, AtomicOrdering::Relaxed);
+                if segment_idx >= segment_ords.len() {
+                    break;
+                }
+                if segment_idx > 0
+                    && self
+                        .deadline_opt
+                        .as_ref()
+                        .map(CollectionDeadline::check_expired)
+                        .unwrap_or(false)
+                {
+                    break;
+                }
+                let segment_ord = segment_ords[segment_idx];
+                let segment_reader = searcher.segment_reader(segment_ord);
+                let segment_fruit =
+                    self.collect_segment(weight.as_ref(), segment_ord, segment_reader)?;
+                segment_fruits.push(segment_fruit);
+            }
+            Ok(segment_fruits)
+        };
+        let num_searchers = self.num_searchers.clamp(1, segment_ords.len().max(1));
+        let segment_fruits = if num_searchers == 1 {
+            run_searcher()?
+        } else {
+            (0..num_searchers)
+                .into_par_iter()
+                .map(|_| run_searcher())
+                .collect::<tantivy::Result<Vec<_>>>()?
+                .into_iter()
+                .flatten()
+                .collect()
+        };
+        let mut leaf_search_response = self.merge_fruits(segment_fruits)?;
+
+        if let Some(deadline) = &self.deadline_opt {
+            if deadline.is_expired() {
+                leaf_search_response.partial_splits = vec![self.split_id.clone()];
+            }
+        }
+        Ok(leaf_search_response)
+    }
 }
 
 impl Collector for QuickwitCollector {
@@ -605,6 +768,11 @@ impl Collector for QuickwitCollector {
             Some(timestamp_filter_builder) => timestamp_filter_builder.build(segment_reader)?,
             None => None,
         };
+        let geo_point_filters = self
+            .geo_point_filter_builders
+            .iter()
+            .map(|geo_point_filter_builder| geo_point_filter_builder.build(segment_reader))
+            .collect::<tantivy::Result<Vec<_>>>()?;
         let aggregation = match &self.aggregation {
             Some(QuickwitAggregations::FindTraceIdsAggregation(collector)) => {
                 Some(AggregationSegmentCollectors::FindTraceIdsSegmentCollector(
@@ -644,9 +812,13 @@ impl Collector for QuickwitCollector {
             top_k_hits: TopK::new(leaf_max_hits, sort_key_mapper),
             segment_ord,
             timestamp_filter_opt,
+            geo_point_filters,
             aggregation,
             search_after: self.search_after.clone(),
             split_search_after_order,
+            deadline_opt: self.deadline_opt.clone(),
+            num_docs_until_deadline_check: DEADLINE_CHECK_INTERVAL,
+            interrupted: false,
         })
     }
 
@@ -777,6 +949,16 @@ fn merge_leaf_responses(
         .flat_map(|leaf_response| leaf_response.failed_splits.iter())
         .cloned()
         .collect_vec();
+    let split_stats = leaf_responses
+        .iter()
+        .flat_map(|leaf_response| leaf_response.split_stats.iter())
+        .cloned()
+        .collect_vec();
+    let partial_splits = leaf_responses
+        .iter()
+        .flat_map(|leaf_response| leaf_response.partial_splits.iter())
+        .cloned()
+        .collect_vec();
     let all_partial_hits: Vec<PartialHit> = leaf_responses
         .into_iter()
         .flat_map(|leaf_response| leaf_response.partial_hits)
@@ -793,6 +975,8 @@ fn merge_leaf_responses(
         partial_hits: top_k_partial_hits,
         failed_splits,
         num_attempted_splits,
+        split_stats,
+        partial_splits,
     })
 }
 
@@ -868,6 +1052,7 @@ pub(crate) fn make_collector_for_split(
         search_request.start_timestamp,
         search_request.end_timestamp,
     );
+    let geo_point_filter_builders = create_geo_point_filter_builders(&search_request.geo_filters)?;
     let sort_by = sort_by_from_request(search_request);
     Ok(QuickwitCollector {
         split_id,
@@ -875,9 +1060,12 @@ pub(crate) fn make_collector_for_split(
         max_hits: search_request.max_hits as usize,
         sort_by,
         timestamp_filter_builder_opt,
+        geo_point_filter_builders,
         aggregation,
         aggregation_limits,
         search_after: search_request.search_after.clone(),
+        deadline_opt: None,
+        num_searchers: 1,
     })
 }
 
@@ -897,9 +1085,12 @@ pub(crate) fn make_merge_collector(
         max_hits: search_request.max_hits as usize,
         sort_by,
         timestamp_filter_builder_opt: None,
+        geo_point_filter_builders: Vec::new(),
         aggregation,
         aggregation_limits: aggregation_limits.clone(),
         search_after: search_request.search_after.clone(),
+        deadline_opt: None,
+        num_searchers: 1,
     })
 }
 
@@ -1023,6 +1214,8 @@ pub(crate) struct IncrementalCollector {
     num_hits: u64,
     failed_splits: Vec<SplitSearchError>,
     num_attempted_splits: u64,
+    split_stats: Vec<SplitSearchStats>,
+    partial_splits: Vec<String>,
 }
 
 impl IncrementalCollector {
@@ -1042,6 +1235,8 @@ impl IncrementalCollector {
             num_hits: 0,
             failed_splits: Vec::new(),
             num_attempted_splits: 0,
+            split_stats: Vec::new(),
+            partial_splits: Vec::new(),
         }
     }
 
@@ -1052,13 +1247,17 @@ impl IncrementalCollector {
             partial_hits,
             failed_splits,
             num_attempted_splits,
+            split_stats,
             intermediate_aggregation_result,
+            partial_splits,
         } = leaf_response;
 
         self.num_hits += num_hits;
         self.top_k_hits.add_entries(partial_hits.into_iter());
         self.failed_splits.extend(failed_splits);
         self.num_attempted_splits += num_attempted_splits;
+        self.split_stats.extend(split_stats);
+        self.partial_splits.extend(partial_splits);
         if let Some(intermediate_aggregation_result) = intermediate_aggregation_result {
             self.incremental_aggregation
                 .add(intermediate_aggregation_result)?;
@@ -1101,7 +1300,9 @@ impl IncrementalCollector {
             partial_hits,
             failed_splits: self.failed_splits,
             num_attempted_splits: self.num_attempted_splits,
+            split_stats: self.split_stats,
             intermediate_aggregation_result,
+            partial_splits: self.partial_splits,
         })
     }
 }
@@ -1109,6 +1310,7 @@ impl IncrementalCollector {
 #[cfg(test)]
 mod tests {
     use std::cmp::Ordering;
+    use std::time::{Duration, Instant};
 
     use quickwit_proto::search::{
         LeafSearchResponse, PartialHit, SearchRequest, SortByValue, SortField, SortOrder,
@@ -1117,7 +1319,10 @@ mod tests {
     use tantivy::collector::Collector;
     use tantivy::TantivyDocument;
 
-    use super::{make_merge_collector, IncrementalCollector, PartialHitHeapItem};
+    use super::{
+        make_merge_collector, CollectionDeadline, IncrementalCollector, PartialHitHeapItem,
+        DEADLINE_CHECK_INTERVAL,
+    };
     use crate::collector::top_k_partial_hits;
 
     #[test]
@@ -1498,6 +1703,112 @@ mod tests {
         }
     }
 
+    fn make_multi_segment_index(num_segments: u64, num_docs_per_segment: u64) -> tantivy::Index {
+        use tantivy::indexer::NoMergePolicy;
+        use tantivy::schema::{NumericOptions, Schema};
+        use tantivy::Index;
+
+        let mut schema_builder = Schema::builder();
+        let field = schema_builder.add_u64_field("sort1", NumericOptions::default().set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+
+        for segment_idx in 0..num_segments {
+            for doc_idx in 0..num_docs_per_segment {
+                let mut doc = TantivyDocument::new();
+                doc.add_u64(field, segment_idx * num_docs_per_segment + doc_idx);
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        index
+    }
+
+    #[test]
+    fn test_search_segments_interrupted_by_deadline() {
+        let index = make_multi_segment_index(3, 2_000);
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+        let segment_ords = [2, 0, 1];
+
+        let mut collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &MockDocMapper,
+            &make_request(10, "sort1"),
+            Default::default(),
+        )
+        .unwrap();
+        let leaf_search_response = collector
+            .search_segments(&searcher, &tantivy::query::AllQuery, &segment_ords)
+            .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 6_000);
+        assert_eq!(leaf_search_response.partial_hits.len(), 10);
+        assert!(leaf_search_response.partial_splits.is_empty());
+
+        // The deadline has already passed: the search stops after the first batch of documents of
+        // the first segment visited and returns what it collected.
+        collector.deadline_opt = Some(CollectionDeadline::new(Instant::now()));
+        let leaf_search_response = collector
+            .search_segments(&searcher, &tantivy::query::AllQuery, &segment_ords)
+            .unwrap();
+        assert_eq!(
+            leaf_search_response.num_hits,
+            DEADLINE_CHECK_INTERVAL as u64
+        );
+        assert_eq!(leaf_search_response.partial_hits.len(), 10);
+        assert!(leaf_search_response
+            .partial_hits
+            .iter()
+            .all(|partial_hit| partial_hit.segment_ord == 2));
+        assert!(leaf_search_response.failed_splits.is_empty());
+        assert_eq!(
+            leaf_search_response.partial_splits,
+            vec!["fake_split_id".to_string()]
+        );
+
+        // A deadline that does not pass leaves the results untouched.
+        collector.deadline_opt = Some(CollectionDeadline::new(
+            Instant::now() + Duration::from_secs(3_600),
+        ));
+        let leaf_search_response = collector
+            .search_segments(&searcher, &tantivy::query::AllQuery, &segment_ords)
+            .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 6_000);
+        assert!(leaf_search_response.partial_splits.is_empty());
+    }
+
+    #[test]
+    fn test_search_segments_with_several_searchers() {
+        let index = make_multi_segment_index(5, 1_000);
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 5);
+        let segment_ords = [4, 3, 2, 1, 0];
+
+        let mut collector = super::make_collector_for_split(
+            "fake_split_id".to_string(),
+            &MockDocMapper,
+            &make_request(10, "sort1"),
+            Default::default(),
+        )
+        .unwrap();
+        let sequential_leaf_search_response = collector
+            .search_segments(&searcher, &tantivy::query::AllQuery, &segment_ords)
+            .unwrap();
+        assert_eq!(sequential_leaf_search_response.num_hits, 5_000);
+
+        // More searchers than segments are capped to the number of segments.
+        for num_searchers in [2, 3, 5, 8] {
+            collector.num_searchers = num_searchers;
+            let leaf_search_response = collector
+                .search_segments(&searcher, &tantivy::query::AllQuery, &segment_ords)
+                .unwrap();
+            assert_eq!(leaf_search_response, sequential_leaf_search_response);
+        }
+    }
+
     #[test]
     fn test_search_after() {
         let index = make_index();
@@ -1681,6 +1992,8 @@ mod tests {
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
+                split_stats: Vec::new(),
+                partial_splits: Vec::new(),
                 intermediate_aggregation_result: None,
             }],
         );
@@ -1698,6 +2011,8 @@ mod tests {
                 }],
                 failed_splits: Vec::new(),
                 num_attempted_splits: 3,
+                split_stats: Vec::new(),
+                partial_splits: Vec::new(),
                 intermediate_aggregation_result: None
             }
         );
@@ -1735,6 +2050,8 @@ mod tests {
                     ],
                     failed_splits: Vec::new(),
                     num_attempted_splits: 3,
+                    split_stats: Vec::new(),
+                    partial_splits: Vec::new(),
                     intermediate_aggregation_result: None,
                 },
                 LeafSearchResponse {
@@ -1752,6 +2069,8 @@ mod tests {
                         retryable_error: true,
                     }],
                     num_attempted_splits: 2,
+                    split_stats: Vec::new(),
+                    partial_splits: Vec::new(),
                     intermediate_aggregation_result: None,
                 },
             ],
@@ -1783,6 +2102,8 @@ mod tests {
                     retryable_error: true,
                 }],
                 num_attempted_splits: 5,
+                split_stats: Vec::new(),
+                partial_splits: Vec::new(),
                 intermediate_aggregation_result: None
             }
         );
@@ -1821,6 +2142,8 @@ mod tests {
                     ],
                     failed_splits: Vec::new(),
                     num_attempted_splits: 3,
+                    split_stats: Vec::new(),
+                    partial_splits: Vec::new(),
                     intermediate_aggregation_result: None,
                 },
                 LeafSearchResponse {
@@ -1838,6 +2161,8 @@ mod tests {
                         retryable_error: true,
                     }],
                     num_attempted_splits: 2,
+                    split_stats: Vec::new(),
+                    partial_splits: Vec::new(),
                     intermediate_aggregation_result: None,
                 },
             ],
@@ -1869,6 +2194,8 @@ mod tests {
                     retryable_error: true,
                 }],
                 num_attempted_splits: 5,
+                split_stats: Vec::new(),
+                partial_splits: Vec::new(),
                 intermediate_aggregation_result: None
             }
         );
@@ -42,6 +42,8 @@ pub enum SearchError {
     InvalidQuery(String),
     #[error("storage not found: `{0}`)")]
     StorageResolver(#[from] StorageResolverError),
+    #[error("timeout: {0}")]
+    Timeout(String),
 }
 
 impl ServiceError for SearchError {
@@ -53,6 +55,7 @@ impl ServiceError for SearchError {
             SearchError::InvalidArgument(_) => ServiceErrorCode::BadRequest,
             SearchError::InvalidQuery(_) => ServiceErrorCode::BadRequest,
             SearchError::StorageResolver(_) => ServiceErrorCode::BadRequest,
+            SearchError::Timeout(_) => ServiceErrorCode::Timeout,
         }
     }
 }
@@ -19,6 +19,7 @@
 
 use std::ops::{Bound, RangeBounds, RangeInclusive};
 
+use quickwit_common::geo::{GeoFilter, GeoPoint};
 use tantivy::columnar::Cardinality;
 use tantivy::fastfield::Column;
 use tantivy::{DateTime, DocId, SegmentReader};
@@ -118,6 +119,61 @@ impl TimestampFilterBuilder {
     }
 }
 
+/// A filter that only retains docs whose geo point matches a bounding box or distance filter.
+#[derive(Clone)]
+pub struct GeoPointFilter {
+    geo_filter: GeoFilter,
+    geo_point_column: Column<u64>,
+}
+
+impl GeoPointFilter {
+    #[inline]
+    pub fn is_match(&self, doc_id: DocId) -> bool {
+        self.geo_point_column
+            .first(doc_id)
+            .map(|geo_point_u64| self.geo_filter.contains(&GeoPoint::from_u64(geo_point_u64)))
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GeoPointFilterBuilder {
+    geo_filter: GeoFilter,
+}
+
+impl GeoPointFilterBuilder {
+    pub fn new(geo_filter: GeoFilter) -> GeoPointFilterBuilder {
+        GeoPointFilterBuilder { geo_filter }
+    }
+
+    pub fn geo_point_field_name(&self) -> &str {
+        self.geo_filter.field()
+    }
+
+    pub fn build(&self, segment_reader: &SegmentReader) -> tantivy::Result<GeoPointFilter> {
+        let geo_point_column = segment_reader
+            .fast_fields()
+            .column_opt::<u64>(self.geo_filter.field())?
+            .unwrap_or_else(|| Column::build_empty_column(segment_reader.max_doc()));
+        Ok(GeoPointFilter {
+            geo_filter: self.geo_filter.clone(),
+            geo_point_column,
+        })
+    }
+}
+
+/// Parses the JSON serialized geo filters of a search request.
+pub fn create_geo_point_filter_builders(
+    geo_filters_json: &[String],
+) -> serde_json::Result<Vec<GeoPointFilterBuilder>> {
+    geo_filters_json
+        .iter()
+        .map(|geo_filter_json| {
+            serde_json::from_str::<GeoFilter>(geo_filter_json).map(GeoPointFilterBuilder::new)
+        })
+        .collect()
+}
+
 /// Determine if all docs of a segment always satisfy the requested timestamp range.
 ///
 /// Note:
@@ -17,20 +17,26 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::ops::Bound;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 use anyhow::Context;
 use futures::future::try_join_all;
 use itertools::{Either, Itertools};
+use levenshtein_automata::{Distance, LevenshteinAutomatonBuilder, DFA, SINK_STATE};
 use quickwit_common::PrettySample;
-use quickwit_directories::{CachingDirectory, HotDirectory, StorageDirectory};
+use quickwit_config::LeafSearchTimeoutConfig;
+use quickwit_directories::{CacheStats, CachingDirectory, HotDirectory, StorageDirectory};
 use quickwit_doc_mapper::{DocMapper, TermRange, WarmupInfo};
 use quickwit_proto::search::{
-    CountHits, LeafListTermsResponse, LeafSearchResponse, ListTermsRequest, PartialHit,
-    SearchRequest, SortOrder, SortValue, SplitIdAndFooterOffsets, SplitSearchError,
+    CountHits, DirectoryCacheStats, LeafListTermsResponse, LeafSearchResponse, ListTermsRequest,
+    PartialHit, SearchRequest, SortOrder, SortValue, SplitIdAndFooterOffsets, SplitSearchError,
+    SplitSearchStats,
 };
 use quickwit_query::query_ast::QueryAst;
 use quickwit_query::tokenizers::TokenizerManager;
@@ -40,10 +46,15 @@ use quickwit_storage::{
 use tantivy::directory::FileSlice;
 use tantivy::fastfield::FastFieldReaders;
 use tantivy::schema::{Field, FieldType};
-use tantivy::{Index, ReloadPolicy, Searcher, Term};
+use tantivy::{DateTime, Index, ReloadPolicy, Searcher, SegmentOrdinal, Term};
+use tantivy_fst::Automaton;
+use tokio::time::Instant;
 use tracing::*;
 
-use crate::collector::{make_collector_for_split, make_merge_collector, IncrementalCollector};
+use crate::collector::{
+    compute_split_cost, make_collector_for_split, make_merge_collector, CollectionDeadline,
+    IncrementalCollector,
+};
 use crate::service::SearcherContext;
 use crate::SearchError;
 
@@ -129,6 +140,26 @@ pub(crate) async fn open_index_with_caches(
     tokenizer_manager: Option<&TokenizerManager>,
     ephemeral_unbounded_cache: bool,
 ) -> anyhow::Result<Index> {
+    let (index, _caching_directory_opt) = open_index_with_caching_directory(
+        searcher_context,
+        index_storage,
+        split_and_footer_offsets,
+        tokenizer_manager,
+        ephemeral_unbounded_cache,
+    )
+    .await?;
+    Ok(index)
+}
+
+/// Same as [`open_index_with_caches`], but also returns the ephemeral cache directory, if any, so
+/// that the statistics of its cache can be reported.
+async fn open_index_with_caching_directory(
+    searcher_context: &SearcherContext,
+    index_storage: Arc<dyn Storage>,
+    split_and_footer_offsets: &SplitIdAndFooterOffsets,
+    tokenizer_manager: Option<&TokenizerManager>,
+    ephemeral_unbounded_cache: bool,
+) -> anyhow::Result<(Index, Option<CachingDirectory>)> {
     let (hotcache_bytes, bundle_storage) =
         open_split_bundle(searcher_context, index_storage, split_and_footer_offsets).await?;
 
@@ -138,11 +169,14 @@ pub(crate) async fn open_index_with_caches(
     );
     let directory = StorageDirectory::new(bundle_storage_with_cache);
 
-    let hot_directory = if ephemeral_unbounded_cache {
+    let (hot_directory, caching_directory_opt) = if ephemeral_unbounded_cache {
         let caching_directory = CachingDirectory::new_unbounded(Arc::new(directory));
-        HotDirectory::open(caching_directory, hotcache_bytes.read_bytes()?)?
+        let hot_directory =
+            HotDirectory::open(caching_directory.clone(), hotcache_bytes.read_bytes()?)?;
+        (hot_directory, Some(caching_directory))
     } else {
-        HotDirectory::open(directory, hotcache_bytes.read_bytes()?)?
+        let hot_directory = HotDirectory::open(directory, hotcache_bytes.read_bytes()?)?;
+        (hot_directory, None)
     };
 
     let mut index = Index::open(hot_directory)?;
@@ -154,7 +188,7 @@ pub(crate) async fn open_index_with_caches(
             .tantivy_manager()
             .clone(),
     );
-    Ok(index)
+    Ok((index, caching_directory_opt))
 }
 
 /// Tantivy search does not make it possible to fetch data asynchronously during
@@ -183,6 +217,9 @@ pub(crate) async fn warmup(searcher: &Searcher, warmup_info: &WarmupInfo) -> any
     let warm_up_term_dict_future =
         warm_up_term_dict_fields(searcher, &warmup_info.term_dict_fields)
             .instrument(debug_span!("warm_up_term_dicts"));
+    let warm_up_fuzzy_terms_future =
+        warm_up_fuzzy_terms(searcher, &warmup_info.fuzzy_terms_grouped_by_field)
+            .instrument(debug_span!("warm_up_fuzzy_terms"));
     let warm_up_fastfields_future = warm_up_fastfields(searcher, &warmup_info.fast_field_names)
         .instrument(debug_span!("warm_up_fastfields"));
     let warm_up_fieldnorms_future = warm_up_fieldnorms(searcher, warmup_info.field_norms)
@@ -196,6 +233,7 @@ pub(crate) async fn warmup(searcher: &Searcher, warmup_info: &WarmupInfo) -> any
         warm_up_term_ranges_future,
         warm_up_fastfields_future,
         warm_up_term_dict_future,
+        warm_up_fuzzy_terms_future,
         warm_up_fieldnorms_future,
         warm_up_postings_future,
     )?;
@@ -221,6 +259,80 @@ async fn warm_up_term_dict_fields(
     Ok(())
 }
 
+/// Wraps a Levenshtein DFA so that it can search a term dictionary.
+struct LevenshteinDfa(DFA);
+
+impl Automaton for LevenshteinDfa {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(self.0.distance(*state), Distance::Exact(_))
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
+
+/// Warms up the term dictionary of the fields targeted by fuzzy terms, then the postings of the
+/// terms they match. Unlike `warm_up_postings`, the postings of the other terms are not fetched.
+async fn warm_up_fuzzy_terms(
+    searcher: &Searcher,
+    fuzzy_terms_grouped_by_field: &HashMap<Field, HashMap<Term, u8>>,
+) -> anyhow::Result<()> {
+    let mut warm_up_futures = Vec::new();
+    for (field, fuzzy_terms) in fuzzy_terms_grouped_by_field {
+        // Transpositions count as a single edit, like in the fuzzy term queries.
+        let dfas: Vec<LevenshteinDfa> = fuzzy_terms
+            .iter()
+            .filter_map(|(term, fuzzy_distance)| {
+                let text = term.value().as_str()?;
+                let dfa = LevenshteinAutomatonBuilder::new(*fuzzy_distance, true).build_dfa(text);
+                Some(LevenshteinDfa(dfa))
+            })
+            .collect();
+        let dfas = Arc::new(dfas);
+
+        for segment_reader in searcher.segment_readers() {
+            let inverted_index = segment_reader.inverted_index(*field)?.clone();
+            let dfas = dfas.clone();
+            let field = *field;
+            warm_up_futures.push(async move {
+                let term_dict = inverted_index.terms();
+                term_dict.warm_up_dictionary().await?;
+
+                // The term dictionary is now in cache, so it can be searched synchronously.
+                let mut matched_terms = HashSet::new();
+                for dfa in dfas.iter() {
+                    let mut term_stream = term_dict.search(dfa).into_stream()?;
+                    while term_stream.advance() {
+                        if let Ok(text) = std::str::from_utf8(term_stream.key()) {
+                            matched_terms.insert(Term::from_field_text(field, text));
+                        }
+                    }
+                }
+                try_join_all(
+                    matched_terms
+                        .iter()
+                        .map(|term| inverted_index.warm_postings(term, false)),
+                )
+                .await?;
+                anyhow::Result::<()>::Ok(())
+            });
+        }
+    }
+    try_join_all(warm_up_futures).await?;
+    Ok(())
+}
+
 async fn warm_up_postings(searcher: &Searcher, fields: &HashSet<Field>) -> anyhow::Result<()> {
     let mut warm_up_futures = Vec::new();
     for field in fields {
@@ -327,7 +439,61 @@ async fn warm_up_fieldnorms(searcher: &Searcher, requires_scoring: bool) -> anyh
     Ok(())
 }
 
+/// Returns the order in which the segments of a split are searched.
+///
+/// When the hits are sorted by timestamp, the segments holding the best hits are searched first,
+/// so that a search interrupted by its deadline still returns the most relevant documents.
+/// Segments without any timestamp are searched last.
+fn segment_visit_order(
+    searcher: &Searcher,
+    search_request: &SearchRequest,
+    timestamp_field_name_opt: Option<&str>,
+) -> tantivy::Result<Vec<SegmentOrdinal>> {
+    let segment_readers = searcher.segment_readers();
+    let mut segment_ords: Vec<SegmentOrdinal> =
+        (0..segment_readers.len() as SegmentOrdinal).collect();
+    let Some(timestamp_field_name) = timestamp_field_name_opt else {
+        return Ok(segment_ords);
+    };
+    let newest_first =
+        match CanSplitDoBetter::from_request(search_request, timestamp_field_name_opt) {
+            CanSplitDoBetter::SplitTimestampHigher(_) => true,
+            CanSplitDoBetter::SplitTimestampLower(_) => false,
+            _ => return Ok(segment_ords),
+        };
+    let mut timestamp_ranges: Vec<Option<(DateTime, DateTime)>> =
+        Vec::with_capacity(segment_readers.len());
+    for segment_reader in segment_readers {
+        let timestamp_range_opt = segment_reader
+            .fast_fields()
+            .column_opt::<DateTime>(timestamp_field_name)?
+            .map(|timestamp_column| (timestamp_column.min_value(), timestamp_column.max_value()));
+        timestamp_ranges.push(timestamp_range_opt);
+    }
+    if newest_first {
+        segment_ords.sort_by_key(|&segment_ord| {
+            let timestamp_range_opt = timestamp_ranges[segment_ord as usize];
+            (
+                timestamp_range_opt.is_none(),
+                Reverse(timestamp_range_opt.map(|(_, max_timestamp)| max_timestamp)),
+            )
+        });
+    } else {
+        segment_ords.sort_by_key(|&segment_ord| {
+            let timestamp_range_opt = timestamp_ranges[segment_ord as usize];
+            (
+                timestamp_range_opt.is_none(),
+                timestamp_range_opt.map(|(min_timestamp, _)| min_timestamp),
+            )
+        });
+    }
+    Ok(segment_ords)
+}
+
 /// Apply a leaf search on a single split.
+///
+/// Opening the split and warming it up are abandoned if they do not complete before the deadline.
+/// The collection itself is interrupted cooperatively and returns the results collected so far.
 #[instrument(skip_all, fields(split_id = split.split_id))]
 async fn leaf_search_single_split(
     searcher_context: &SearcherContext,
@@ -335,60 +501,129 @@ async fn leaf_search_single_split(
     storage: Arc<dyn Storage>,
     split: SplitIdAndFooterOffsets,
     doc_mapper: Arc<dyn DocMapper>,
+    split_deadline_opt: Option<Instant>,
+    num_searchers: usize,
 ) -> crate::Result<LeafSearchResponse> {
+    let start = Instant::now();
     rewrite_request(&mut search_request, &split);
     if let Some(cached_answer) = searcher_context
         .leaf_search_cache
         .get(split.clone(), search_request.clone())
     {
-        return Ok(cached_answer);
+        return Ok(with_split_stats(
+            cached_answer,
+            &split.split_id,
+            start,
+            None,
+        ));
     }
 
     let split_id = split.split_id.to_string();
-    let index = open_index_with_caches(
-        searcher_context,
-        storage,
-        &split,
-        Some(doc_mapper.tokenizer_manager()),
-        true,
-    )
-    .await?;
-    let split_schema = index.schema();
+    let open_and_warmup_fut = async {
+        let (index, caching_directory_opt) = open_index_with_caching_directory(
+            searcher_context,
+            storage,
+            &split,
+            Some(doc_mapper.tokenizer_manager()),
+            true,
+        )
+        .await?;
+        let split_schema = index.schema();
+
+        let mut quickwit_collector = make_collector_for_split(
+            split_id.clone(),
+            doc_mapper.as_ref(),
+            &search_request,
+            searcher_context.get_aggregation_limits(),
+        )?;
+        quickwit_collector.deadline_opt = split_deadline_opt
+            .map(|split_deadline| CollectionDeadline::new(split_deadline.into_std()));
+        quickwit_collector.num_searchers = num_searchers;
+        let query_ast: QueryAst = serde_json::from_str(search_request.query_ast.as_str())
+            .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+        let (query, mut warmup_info) = doc_mapper.query(split_schema, &query_ast, false)?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()?;
+        let searcher = reader.searcher();
+
+        let collector_warmup_info = quickwit_collector.warmup_info();
+        warmup_info.merge(collector_warmup_info);
+        warmup_info.simplify();
+
+        warmup(&searcher, &warmup_info).await?;
+        Ok::<_, SearchError>((searcher, query, quickwit_collector, caching_directory_opt))
+    };
+    let (searcher, query, quickwit_collector, caching_directory_opt) =
+        run_with_split_deadline(&split_id, open_and_warmup_fut, split_deadline_opt).await?;
 
-    let quickwit_collector = make_collector_for_split(
-        split_id.clone(),
-        doc_mapper.as_ref(),
+    // The timestamp fast field was warmed up by the collector if the hits are sorted by timestamp.
+    let segment_ords = segment_visit_order(
+        &searcher,
         &search_request,
-        searcher_context.get_aggregation_limits(),
+        doc_mapper.timestamp_field_name(),
     )?;
-    let query_ast: QueryAst = serde_json::from_str(search_request.query_ast.as_str())
-        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
-    let (query, mut warmup_info) = doc_mapper.query(split_schema, &query_ast, false)?;
-    let reader = index
-        .reader_builder()
-        .reload_policy(ReloadPolicy::Manual)
-        .try_into()?;
-    let searcher = reader.searcher();
-
-    let collector_warmup_info = quickwit_collector.warmup_info();
-    warmup_info.merge(collector_warmup_info);
-    warmup_info.simplify();
-
-    warmup(&searcher, &warmup_info).await?;
     let span = info_span!("tantivy_search");
     let leaf_search_response = crate::run_cpu_intensive(move || {
         let _span_guard = span.enter();
-        searcher.search(&query, &quickwit_collector)
+        quickwit_collector.search_segments(&searcher, query.as_ref(), &segment_ords)
     })
     .await
     .map_err(|_| {
         crate::SearchError::Internal(format!("leaf search panicked. split={split_id}"))
     })??;
 
-    searcher_context
-        .leaf_search_cache
-        .put(split, search_request, leaf_search_response.clone());
-    Ok(leaf_search_response)
+    // Partial responses are not cached so that the split gets a chance to be searched entirely by
+    // the next request.
+    if leaf_search_response.partial_splits.is_empty() {
+        searcher_context
+            .leaf_search_cache
+            .put(split, search_request, leaf_search_response.clone());
+    } else {
+        warn!(
+            split_id=%split_id,
+            "search of split was interrupted after exceeding its time budget"
+        );
+    }
+    let cache_stats_opt = caching_directory_opt.map(|caching_directory| {
+        let cache_stats = caching_directory.stats();
+        debug!(split_id=%split_id, cache_stats=?cache_stats, "ephemeral cache stats");
+        cache_stats
+    });
+    Ok(with_split_stats(
+        leaf_search_response,
+        &split_id,
+        start,
+        cache_stats_opt,
+    ))
+}
+
+/// Attaches the statistics of the search of a single split to its response.
+///
+/// The statistics are not stored in the leaf search cache: a cache hit reports the time it took
+/// to get the response from the cache and no cache statistics.
+fn with_split_stats(
+    mut leaf_search_response: LeafSearchResponse,
+    split_id: &str,
+    start: Instant,
+    cache_stats_opt: Option<CacheStats>,
+) -> LeafSearchResponse {
+    leaf_search_response.split_stats = vec![SplitSearchStats {
+        split_id: split_id.to_string(),
+        searcher_addr: String::new(),
+        search_duration_micros: start.elapsed().as_micros() as u64,
+        num_docs_scanned: leaf_search_response.num_hits,
+        partial: !leaf_search_response.partial_splits.is_empty(),
+        cache_stats: cache_stats_opt.map(|cache_stats| DirectoryCacheStats {
+            num_hits: cache_stats.num_hits,
+            num_misses: cache_stats.num_misses,
+            num_evictions: cache_stats.num_evictions,
+            num_bytes: cache_stats.num_bytes,
+            num_items: cache_stats.num_items,
+        }),
+    }];
+    leaf_search_response
 }
 
 /// Rewrite a request removing parts which incure additional download or computation with no
@@ -557,9 +792,16 @@ pub async fn leaf_search(
     index_storage: Arc<dyn Storage>,
     mut splits: Vec<SplitIdAndFooterOffsets>,
     doc_mapper: Arc<dyn DocMapper>,
+    timeout_opt: Option<Duration>,
+    num_searchers: usize,
 ) -> Result<LeafSearchResponse, SearchError> {
     info!(splits_num = splits.len(), split_offsets = ?PrettySample::new(&splits, 5));
 
+    // We keep a tenth of the time allotted by the root to merge the split results and send the
+    // response back before the root gives up on the request.
+    let request_deadline_opt =
+        timeout_opt.map(|timeout| Instant::now() + timeout.saturating_sub(timeout / 10));
+
     let split_filter = CanSplitDoBetter::from_request(&request, doc_mapper.timestamp_field_name());
     split_filter.optimize_split_order(&mut splits);
 
@@ -578,6 +820,7 @@ pub async fn leaf_search(
     let incremental_merge_collector = Arc::new(Mutex::new(incremental_merge_collector));
 
     let mut leaf_search_single_split_futures: Vec<_> = Vec::with_capacity(splits.len());
+    let mut searched_split_ids: Vec<String> = Vec::with_capacity(splits.len());
 
     for split in splits {
         let leaf_split_search_permit = searcher_context.leaf_search_split_semaphore
@@ -597,6 +840,12 @@ pub async fn leaf_search(
             request.sort_fields.clear();
         }
 
+        let split_deadline_opt = compute_split_deadline(
+            &searcher_context.searcher_config.leaf_search_timeout,
+            &split,
+            request_deadline_opt,
+        );
+        searched_split_ids.push(split.split_id.clone());
         leaf_search_single_split_futures.push(tokio::spawn(
             leaf_search_single_split_wrapper(
                 request,
@@ -604,6 +853,8 @@ pub async fn leaf_search(
                 index_storage.clone(),
                 doc_mapper.clone(),
                 split,
+                split_deadline_opt,
+                num_searchers,
                 split_filter.clone(),
                 incremental_merge_collector.clone(),
                 leaf_split_search_permit,
@@ -623,14 +874,12 @@ pub async fn leaf_search(
         Err(filter_merger) => filter_merger.lock().unwrap().clone(),
     };
 
-    for result in split_search_results {
+    for (split_id, result) in searched_split_ids.into_iter().zip(split_search_results) {
         // splits that did not panic were already added to the collector
-        if let Err(e) = result {
+        if let Err(join_error) = result {
             incremental_merge_collector.add_failed_split(SplitSearchError {
-                // we could reasonably add a wrapper to the JoinHandle to give us the
-                // split_id anyway
-                split_id: "unknown".to_string(),
-                error: format!("{}", SearchError::from(e)),
+                split_id,
+                error: format!("{}", SearchError::from(join_error)),
                 retryable_error: true,
             })
         }
@@ -649,6 +898,8 @@ async fn leaf_search_single_split_wrapper(
     index_storage: Arc<dyn Storage>,
     doc_mapper: Arc<dyn DocMapper>,
     split: SplitIdAndFooterOffsets,
+    split_deadline_opt: Option<Instant>,
+    num_searchers: usize,
     split_filter: Arc<Mutex<CanSplitDoBetter>>,
     incremental_merge_collector: Arc<Mutex<IncrementalCollector>>,
     leaf_split_search_permit: tokio::sync::OwnedSemaphorePermit,
@@ -663,6 +914,8 @@ async fn leaf_search_single_split_wrapper(
         index_storage,
         split.clone(),
         doc_mapper,
+        split_deadline_opt,
+        num_searchers,
     )
     .await;
 
@@ -687,7 +940,7 @@ async fn leaf_search_single_split_wrapper(
         Err(err) => locked_incremental_merge_collector.add_failed_split(SplitSearchError {
             split_id: split.split_id.clone(),
             error: format!("{err}"),
-            retryable_error: true,
+            retryable_error: !matches!(err, SearchError::Timeout(_)),
         }),
     }
     if let Some(last_hit) = locked_incremental_merge_collector.peek_worst_hit() {
@@ -698,6 +951,41 @@ async fn leaf_search_single_split_wrapper(
     }
 }
 
+/// Computes the instant after which the search of a split is interrupted.
+///
+/// Each split gets a budget derived from its own cost, so that a single pathological split cannot
+/// consume the time allotted to the whole leaf request. The split budget never exceeds the request
+/// deadline. Splits are not bounded in time when the root did not set a deadline.
+fn compute_split_deadline(
+    leaf_search_timeout_config: &LeafSearchTimeoutConfig,
+    split: &SplitIdAndFooterOffsets,
+    request_deadline_opt: Option<Instant>,
+) -> Option<Instant> {
+    let request_deadline = request_deadline_opt?;
+    let split_timeout =
+        leaf_search_timeout_config.timeout_for_cost(compute_split_cost(split.num_docs));
+    Some(request_deadline.min(Instant::now() + split_timeout))
+}
+
+/// Runs a step of the search of a single split, abandoning it if it does not complete before the
+/// deadline.
+async fn run_with_split_deadline<T>(
+    split_id: &str,
+    leaf_search_single_split_fut: impl Future<Output = crate::Result<T>>,
+    split_deadline_opt: Option<Instant>,
+) -> crate::Result<T> {
+    let Some(split_deadline) = split_deadline_opt else {
+        return leaf_search_single_split_fut.await;
+    };
+    tokio::time::timeout_at(split_deadline, leaf_search_single_split_fut)
+        .await
+        .map_err(|_| {
+            SearchError::Timeout(format!(
+                "search of split `{split_id}` was abandoned after exceeding its time budget"
+            ))
+        })?
+}
+
 /// Apply a leaf list terms on a single split.
 #[instrument(skip_all, fields(split_id = split.split_id))]
 async fn leaf_list_terms_single_split(
@@ -881,3 +1169,194 @@ pub async fn leaf_list_terms(
 
     Ok(merged_search_response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::root::compute_leaf_search_timeout;
+
+    fn split_offsets_for_test(split_id: &str, num_docs: u64) -> SplitIdAndFooterOffsets {
+        SplitIdAndFooterOffsets {
+            split_id: split_id.to_string(),
+            num_docs,
+            ..Default::default()
+        }
+    }
+
+    async fn mock_leaf_search_single_split(
+        search_duration: Duration,
+    ) -> crate::Result<LeafSearchResponse> {
+        tokio::time::sleep(search_duration).await;
+        Ok(LeafSearchResponse {
+            num_hits: 1,
+            num_attempted_splits: 1,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_compute_split_deadline() {
+        // 1s per cost unit, with a 5s floor and a 30s ceiling.
+        let leaf_search_timeout_config = LeafSearchTimeoutConfig::default();
+        let big_split = split_offsets_for_test("big-split", 20_000_000);
+        let small_split = split_offsets_for_test("small-split", 1_000);
+        let now = Instant::now();
+
+        assert!(compute_split_deadline(&leaf_search_timeout_config, &big_split, None).is_none());
+
+        let request_deadline = now + Duration::from_secs(30);
+        assert_eq!(
+            compute_split_deadline(
+                &leaf_search_timeout_config,
+                &big_split,
+                Some(request_deadline)
+            ),
+            Some(now + Duration::from_secs(21))
+        );
+        assert_eq!(
+            compute_split_deadline(
+                &leaf_search_timeout_config,
+                &small_split,
+                Some(request_deadline)
+            ),
+            Some(now + Duration::from_secs(5))
+        );
+        // The split budget never exceeds the request deadline.
+        let request_deadline = now + Duration::from_secs(10);
+        assert_eq!(
+            compute_split_deadline(
+                &leaf_search_timeout_config,
+                &big_split,
+                Some(request_deadline)
+            ),
+            Some(request_deadline)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_slow_small_split_is_abandoned() {
+        let leaf_search_timeout_config = LeafSearchTimeoutConfig::default();
+        let big_split = split_offsets_for_test("big-split", 20_000_000);
+        let small_split = split_offsets_for_test("small-split", 1_000);
+
+        // The big split legitimately takes 15s while the small one is stuck for a minute.
+        let big_split_duration = Duration::from_secs(15);
+        let small_split_duration = Duration::from_secs(60);
+
+        // Flat timeout baseline: every split gets the same 30s budget.
+        let start = Instant::now();
+        let flat_deadline = start + Duration::from_secs(30);
+        let (big_split_res, small_split_res) = tokio::join!(
+            run_with_split_deadline(
+                &big_split.split_id,
+                mock_leaf_search_single_split(big_split_duration),
+                Some(flat_deadline),
+            ),
+            run_with_split_deadline(
+                &small_split.split_id,
+                mock_leaf_search_single_split(small_split_duration),
+                Some(flat_deadline),
+            ),
+        );
+        let flat_timeout_latency = start.elapsed();
+        assert!(big_split_res.is_ok());
+        assert!(matches!(small_split_res, Err(SearchError::Timeout(_))));
+        assert!(flat_timeout_latency >= Duration::from_secs(30));
+
+        // Cost-aware budgets: the root grants 22s to the leaf, which subdivides it across splits.
+        let start = Instant::now();
+        let leaf_search_timeout = compute_leaf_search_timeout(
+            &leaf_search_timeout_config,
+            &[big_split.clone(), small_split.clone()],
+        );
+        assert_eq!(leaf_search_timeout, Duration::from_secs(22));
+        let request_deadline = start + leaf_search_timeout - leaf_search_timeout / 10;
+        let (big_split_res, small_split_res) = tokio::join!(
+            run_with_split_deadline(
+                &big_split.split_id,
+                mock_leaf_search_single_split(big_split_duration),
+                compute_split_deadline(
+                    &leaf_search_timeout_config,
+                    &big_split,
+                    Some(request_deadline)
+                ),
+            ),
+            run_with_split_deadline(
+                &small_split.split_id,
+                mock_leaf_search_single_split(small_split_duration),
+                compute_split_deadline(
+                    &leaf_search_timeout_config,
+                    &small_split,
+                    Some(request_deadline)
+                ),
+            ),
+        );
+        let cost_aware_latency = start.elapsed();
+        assert_eq!(big_split_res.unwrap().num_hits, 1);
+        let SearchError::Timeout(error_message) = small_split_res.unwrap_err() else {
+            panic!("expected a timeout error");
+        };
+        assert!(error_message.contains("small-split"));
+        // The leaf no longer waits for the pathological split past the completion of the big one.
+        assert!(cost_aware_latency >= big_split_duration);
+        assert!(cost_aware_latency < big_split_duration + Duration::from_secs(1));
+        assert!(cost_aware_latency < flat_timeout_latency);
+    }
+
+    #[test]
+    fn test_segment_visit_order() {
+        use quickwit_proto::search::SortField;
+        use tantivy::indexer::NoMergePolicy;
+        use tantivy::schema::{DateOptions, Schema};
+        use tantivy::TantivyDocument;
+
+        let mut schema_builder = Schema::builder();
+        let timestamp_field =
+            schema_builder.add_date_field("timestamp", DateOptions::default().set_fast());
+        let index = Index::create_in_ram(schema_builder.build());
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        index_writer.set_merge_policy(Box::new(NoMergePolicy));
+        // Segment 0 holds the middle time range, segment 1 the oldest and segment 2 the newest.
+        for timestamps in [[20, 30], [10, 15], [40, 50]] {
+            for timestamp in timestamps {
+                let mut doc = TantivyDocument::new();
+                doc.add_date(timestamp_field, DateTime::from_timestamp_secs(timestamp));
+                index_writer.add_document(doc).unwrap();
+            }
+            index_writer.commit().unwrap();
+        }
+        let reader = index.reader().unwrap();
+        let searcher = reader.searcher();
+        assert_eq!(searcher.segment_readers().len(), 3);
+
+        let make_request = |field_name: &str, sort_order: SortOrder| SearchRequest {
+            max_hits: 10,
+            sort_fields: vec![SortField {
+                field_name: field_name.to_string(),
+                sort_order: sort_order as i32,
+                sort_datetime_format: None,
+            }],
+            ..Default::default()
+        };
+        let newest_first_request = make_request("timestamp", SortOrder::Desc);
+        assert_eq!(
+            segment_visit_order(&searcher, &newest_first_request, Some("timestamp")).unwrap(),
+            vec![2, 0, 1]
+        );
+        let oldest_first_request = make_request("timestamp", SortOrder::Asc);
+        assert_eq!(
+            segment_visit_order(&searcher, &oldest_first_request, Some("timestamp")).unwrap(),
+            vec![1, 0, 2]
+        );
+        // Segments are visited in their natural order when the hits are not sorted by timestamp.
+        assert_eq!(
+            segment_visit_order(&searcher, &newest_first_request, None).unwrap(),
+            vec![0, 1, 2]
+        );
+        let other_field_request = make_request("body", SortOrder::Desc);
+        assert_eq!(
+            segment_visit_order(&searcher, &other_field_request, Some("timestamp")).unwrap(),
+            vec![0, 1, 2]
+        );
+    }
+}
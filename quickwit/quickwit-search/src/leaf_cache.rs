@@ -206,6 +206,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: None,
             timestamp_end: None,
+            num_docs: 0,
         };
 
         let split_2 = SplitIdAndFooterOffsets {
@@ -214,6 +215,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: None,
             timestamp_end: None,
+            num_docs: 0,
         };
 
         let query_1 = SearchRequest {
@@ -240,6 +242,8 @@ mod tests {
             failed_splits: Vec::new(),
             intermediate_aggregation_result: None,
             num_attempted_splits: 0,
+            split_stats: Vec::new(),
+            partial_splits: Vec::new(),
             num_hits: 1234,
             partial_hits: vec![PartialHit {
                 doc_id: 1,
@@ -268,6 +272,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: Some(100),
             timestamp_end: Some(199),
+            num_docs: 0,
         };
         let split_2 = SplitIdAndFooterOffsets {
             split_id: "split_2".to_string(),
@@ -275,6 +280,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: Some(150),
             timestamp_end: Some(249),
+            num_docs: 0,
         };
         let split_3 = SplitIdAndFooterOffsets {
             split_id: "split_3".to_string(),
@@ -282,6 +288,7 @@ mod tests {
             split_footer_end: 100,
             timestamp_start: Some(150),
             timestamp_end: Some(249),
+            num_docs: 0,
         };
 
         let query_1 = SearchRequest {
@@ -326,6 +333,8 @@ mod tests {
             failed_splits: Vec::new(),
             intermediate_aggregation_result: None,
             num_attempted_splits: 0,
+            split_stats: Vec::new(),
+            partial_splits: Vec::new(),
             num_hits: 1234,
             partial_hits: vec![PartialHit {
                 doc_id: 1,
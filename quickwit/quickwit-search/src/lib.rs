@@ -22,6 +22,7 @@
 #![allow(clippy::bool_assert_comparison)]
 #![deny(clippy::disallowed_methods)]
 
+mod circuit_breaker;
 mod client;
 mod cluster_client;
 mod collector;
@@ -33,13 +34,17 @@ mod leaf;
 mod leaf_cache;
 mod list_fields;
 mod list_fields_cache;
+mod metastore_fallback_cache;
+mod page_token;
 mod retry;
 mod root;
 mod scroll_context;
 mod search_job_placer;
+mod search_response_cache;
 mod search_response_rest;
 mod search_stream;
 mod service;
+mod synonym_table_cache;
 mod thread_pool;
 
 mod metrics;
@@ -51,16 +56,20 @@ pub use collector::QuickwitAggregations;
 use metrics::SEARCH_METRICS;
 use quickwit_common::tower::Pool;
 use quickwit_doc_mapper::DocMapper;
-use quickwit_proto::metastore::{ListSplitsRequest, MetastoreService, MetastoreServiceClient};
+use quickwit_proto::metastore::{
+    ListIndexSnapshotsRequest, ListSplitsRequest, MetastoreService, MetastoreServiceClient,
+};
 use tantivy::schema::NamedFieldDocument;
 
 /// Refer to this as `crate::Result<T>`.
 pub type Result<T> = std::result::Result<T, SearchError>;
 
+use std::collections::HashSet;
 use std::net::{Ipv4Addr, SocketAddr};
 use std::sync::Arc;
 
 pub use find_trace_ids_collector::FindTraceIdsCollector;
+pub use metastore_fallback_cache::MetastoreFallbackCache;
 use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_metastore::{
@@ -71,8 +80,10 @@ use quickwit_proto::search::{PartialHit, SearchRequest, SearchResponse, SplitIdA
 use quickwit_proto::types::IndexUid;
 use quickwit_storage::StorageResolver;
 pub use service::SearcherContext;
+pub use synonym_table_cache::SynonymTableCache;
 use tantivy::DocAddress;
 
+pub use crate::circuit_breaker::{CircuitBreakerConfig, CircuitBreakerState};
 pub use crate::client::{
     create_search_client_from_channel, create_search_client_from_grpc_addr, SearchServiceClient,
 };
@@ -80,10 +91,12 @@ pub use crate::cluster_client::ClusterClient;
 pub use crate::error::{parse_grpc_error, SearchError};
 use crate::fetch_docs::fetch_docs;
 use crate::leaf::{leaf_list_terms, leaf_search};
+pub use crate::page_token::{decode_page_token, encode_page_token};
 pub use crate::root::{
-    jobs_to_leaf_requests, root_list_terms, root_search, IndexMetasForLeafSearch, SearchJob,
+    jobs_to_leaf_requests, root_list_terms, root_search, root_search_hits_stream,
+    IndexMetasForLeafSearch, SearchJob,
 };
-pub use crate::search_job_placer::{Job, SearchJobPlacer};
+pub use crate::search_job_placer::{Job, NodeCircuitBreakerInfo, SearchJobPlacer};
 pub use crate::search_response_rest::SearchResponseRest;
 pub use crate::search_stream::root_search_stream;
 pub use crate::service::{MockSearchService, SearchService, SearchServiceImpl};
@@ -165,6 +178,7 @@ fn extract_split_and_footer_offsets(split_metadata: &SplitMetadata) -> SplitIdAn
             .time_range
             .as_ref()
             .map(|time_range| *time_range.end()),
+        num_docs: split_metadata.num_docs as u64,
     }
 }
 
@@ -176,9 +190,68 @@ async fn list_relevant_splits(
     tags_filter_opt: Option<TagFilterAst>,
     metastore: &mut MetastoreServiceClient,
 ) -> crate::Result<Vec<SplitMetadata>> {
-    let mut query =
+    let query =
         ListSplitsQuery::try_from_index_uids(index_uids)?.with_split_state(SplitState::Published);
+    list_splits_matching(
+        query,
+        start_timestamp,
+        end_timestamp,
+        tags_filter_opt,
+        metastore,
+    )
+    .await
+}
 
+/// Extract the list of splits of an index snapshot relevant for a given request.
+///
+/// The splits of a snapshot may have been marked for deletion since the snapshot was created. The
+/// garbage collector keeps them around until the snapshot is deleted, so they remain searchable.
+async fn list_relevant_snapshot_splits(
+    index_uid: IndexUid,
+    snapshot_id: &str,
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
+    tags_filter_opt: Option<TagFilterAst>,
+    metastore: &mut MetastoreServiceClient,
+) -> crate::Result<Vec<SplitMetadata>> {
+    let list_snapshots_request = ListIndexSnapshotsRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let snapshot = metastore
+        .list_index_snapshots(list_snapshots_request)
+        .await?
+        .snapshots
+        .into_iter()
+        .find(|snapshot| snapshot.snapshot_id == snapshot_id)
+        .ok_or_else(|| {
+            SearchError::InvalidArgument(format!(
+                "snapshot `{snapshot_id}` does not exist for index `{}`",
+                index_uid.index_id()
+            ))
+        })?;
+    let snapshot_split_ids: HashSet<String> = snapshot.split_ids.into_iter().collect();
+
+    let query = ListSplitsQuery::for_index(index_uid)
+        .with_split_states([SplitState::Published, SplitState::MarkedForDeletion]);
+    let mut splits_metadata = list_splits_matching(
+        query,
+        start_timestamp,
+        end_timestamp,
+        tags_filter_opt,
+        metastore,
+    )
+    .await?;
+    splits_metadata.retain(|split_metadata| snapshot_split_ids.contains(&split_metadata.split_id));
+    Ok(splits_metadata)
+}
+
+async fn list_splits_matching(
+    mut query: ListSplitsQuery,
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
+    tags_filter_opt: Option<TagFilterAst>,
+    metastore: &mut MetastoreServiceClient,
+) -> crate::Result<Vec<SplitMetadata>> {
     if let Some(start_ts) = start_timestamp {
         query = query.with_time_range_start_gte(start_ts);
     }
@@ -220,13 +293,15 @@ pub async fn start_searcher_service(
     search_job_placer: SearchJobPlacer,
     searcher_context: Arc<SearcherContext>,
 ) -> anyhow::Result<Arc<dyn SearchService>> {
-    let cluster_client = ClusterClient::new(search_job_placer);
+    let cluster_client = ClusterClient::new(search_job_placer)
+        .with_max_leaf_search_retries(searcher_context.searcher_config.max_leaf_search_retries);
     let search_service = Arc::new(SearchServiceImpl::new(
         metastore,
         storage_resolver,
         cluster_client,
         searcher_context,
     ));
+    search_service.spawn_num_indexes_refresh_loop();
     Ok(search_service)
 }
 
@@ -242,7 +317,9 @@ pub async fn single_node_search(
     let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
     let cluster_client = ClusterClient::new(search_job_placer);
     let searcher_config = SearcherConfig::default();
-    let searcher_context = Arc::new(SearcherContext::new(searcher_config, None));
+    let mut searcher_context = SearcherContext::new(searcher_config, None);
+    searcher_context.synonym_table_cache = SynonymTableCache::new(storage_resolver.clone());
+    let searcher_context = Arc::new(searcher_context);
     let search_service = Arc::new(SearchServiceImpl::new(
         metastore.clone(),
         storage_resolver,
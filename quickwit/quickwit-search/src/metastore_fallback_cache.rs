@@ -0,0 +1,415 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use quickwit_doc_mapper::tag_pruning::TagFilterAst;
+use quickwit_metastore::{IndexMetadata, SplitMetadata};
+use quickwit_proto::types::IndexUid;
+use tokio::time::Instant;
+
+use crate::SearchError;
+
+/// Maximum number of split lists kept per index, one per time range and tag filter. When full,
+/// the oldest list is evicted.
+const MAX_CACHED_SPLIT_LISTS_PER_INDEX: usize = 4;
+
+/// The last index metadata and split lists fetched from the metastore by the root searcher.
+///
+/// When the metastore is unreachable, they are served instead for up to `max_staleness`, so that
+/// searches keep working at the cost of possibly missing the most recent splits. Entries older
+/// than `max_staleness` are evicted on the next write.
+pub struct MetastoreFallbackCache {
+    max_staleness: Duration,
+    indexes_metadata: Mutex<HashMap<Vec<String>, CachedIndexesMetadata>>,
+    /// Split lists of each index, from the oldest to the most recent.
+    splits: Mutex<HashMap<IndexUid, Vec<CachedSplits>>>,
+}
+
+struct CachedIndexesMetadata {
+    indexes_metadata: Vec<IndexMetadata>,
+    fetched_at: Instant,
+}
+
+struct CachedSplits {
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
+    tag_filter_ast: Option<TagFilterAst>,
+    split_metadatas: Arc<[SplitMetadata]>,
+    fetched_at: Instant,
+    fetched_at_timestamp: i64,
+}
+
+impl CachedSplits {
+    /// Returns whether this entry was listed with the given time range and tag filter.
+    fn has_filters(
+        &self,
+        start_timestamp: Option<i64>,
+        end_timestamp: Option<i64>,
+        tag_filter_ast: &Option<TagFilterAst>,
+    ) -> bool {
+        self.start_timestamp == start_timestamp
+            && self.end_timestamp == end_timestamp
+            && self.tag_filter_ast == *tag_filter_ast
+    }
+
+    /// Returns whether the splits listed for this entry include all the splits relevant for the
+    /// given time range and tag filter, as of the time the entry was fetched.
+    fn covers(
+        &self,
+        start_timestamp: Option<i64>,
+        end_timestamp: Option<i64>,
+        tag_filter_ast: &Option<TagFilterAst>,
+    ) -> bool {
+        let covers_start = match (self.start_timestamp, start_timestamp) {
+            (None, _) => true,
+            (Some(cached_start), Some(start)) => cached_start <= start,
+            (Some(_), None) => false,
+        };
+        // No split could hold documents more recent than the time the entry was fetched.
+        let covers_end = match self.end_timestamp {
+            None => true,
+            Some(cached_end) => {
+                cached_end
+                    >= end_timestamp
+                        .unwrap_or(i64::MAX)
+                        .min(self.fetched_at_timestamp)
+            }
+        };
+        let covers_tags = self.tag_filter_ast.is_none() || self.tag_filter_ast == *tag_filter_ast;
+        covers_start && covers_end && covers_tags
+    }
+}
+
+impl MetastoreFallbackCache {
+    /// Creates a new cache. A `max_staleness` of zero disables the cache.
+    pub fn new(max_staleness: Duration) -> Self {
+        MetastoreFallbackCache {
+            max_staleness,
+            indexes_metadata: Mutex::default(),
+            splits: Mutex::default(),
+        }
+    }
+
+    fn is_enabled(&self) -> bool {
+        !self.max_staleness.is_zero()
+    }
+
+    /// Records the index metadata returned by the metastore for the given index ID patterns.
+    pub fn put_indexes_metadata(
+        &self,
+        index_id_patterns: &[String],
+        indexes_metadata: &[IndexMetadata],
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let cached = CachedIndexesMetadata {
+            indexes_metadata: indexes_metadata.to_vec(),
+            fetched_at: Instant::now(),
+        };
+        let mut indexes_metadata_lock = self.indexes_metadata.lock().unwrap();
+        indexes_metadata_lock.retain(|_, cached| cached.fetched_at.elapsed() <= self.max_staleness);
+        indexes_metadata_lock.insert(index_id_patterns.to_vec(), cached);
+    }
+
+    /// Returns the last index metadata fetched for the given index ID patterns, along with its
+    /// age, if it is not older than `max_staleness`.
+    pub fn get_indexes_metadata(
+        &self,
+        index_id_patterns: &[String],
+    ) -> Option<(Vec<IndexMetadata>, Duration)> {
+        let indexes_metadata_lock = self.indexes_metadata.lock().unwrap();
+        let cached = indexes_metadata_lock.get(index_id_patterns)?;
+        let age = cached.fetched_at.elapsed();
+        if age > self.max_staleness {
+            return None;
+        }
+        Some((cached.indexes_metadata.clone(), age))
+    }
+
+    /// Records the splits returned by the metastore for the given index, time range, and tag
+    /// filter. It replaces the splits previously recorded for the same filters only, so that the
+    /// splits of a narrow query do not replace the ones of a broad query.
+    pub fn put_splits(
+        &self,
+        index_uid: &IndexUid,
+        start_timestamp: Option<i64>,
+        end_timestamp: Option<i64>,
+        tag_filter_ast: &Option<TagFilterAst>,
+        split_metadatas: &[SplitMetadata],
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+        let fetched_at_timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() as i64)
+            .unwrap_or_default();
+        let cached = CachedSplits {
+            start_timestamp,
+            end_timestamp,
+            tag_filter_ast: tag_filter_ast.clone(),
+            split_metadatas: Arc::from(split_metadatas),
+            fetched_at: Instant::now(),
+            fetched_at_timestamp,
+        };
+        let mut splits_lock = self.splits.lock().unwrap();
+        splits_lock.retain(|_, cached_splits| {
+            cached_splits.retain(|cached| cached.fetched_at.elapsed() <= self.max_staleness);
+            !cached_splits.is_empty()
+        });
+        let cached_splits = splits_lock.entry(index_uid.clone()).or_default();
+        cached_splits
+            .retain(|cached| !cached.has_filters(start_timestamp, end_timestamp, tag_filter_ast));
+
+        if cached_splits.len() >= MAX_CACHED_SPLIT_LISTS_PER_INDEX {
+            cached_splits.remove(0);
+        }
+        cached_splits.push(cached);
+    }
+
+    /// Returns the splits of the index relevant for the given time range and tag filter, along
+    /// with their age, from the most recent split list of the index that covers them and is not
+    /// older than `max_staleness`.
+    pub fn get_splits(
+        &self,
+        index_uid: &IndexUid,
+        start_timestamp: Option<i64>,
+        end_timestamp: Option<i64>,
+        tag_filter_ast: &Option<TagFilterAst>,
+    ) -> Option<(Vec<SplitMetadata>, Duration)> {
+        let splits_lock = self.splits.lock().unwrap();
+        let cached = splits_lock.get(index_uid)?.iter().rev().find(|cached| {
+            cached.fetched_at.elapsed() <= self.max_staleness
+                && cached.covers(start_timestamp, end_timestamp, tag_filter_ast)
+        })?;
+        let age = cached.fetched_at.elapsed();
+        let split_metadatas = cached
+            .split_metadatas
+            .iter()
+            .filter(|split_metadata| {
+                split_matches(
+                    split_metadata,
+                    start_timestamp,
+                    end_timestamp,
+                    tag_filter_ast,
+                )
+            })
+            .cloned()
+            .collect();
+        Some((split_metadatas, age))
+    }
+}
+
+/// Applies the time range and tag filter of a list splits query to a split.
+pub(crate) fn split_matches(
+    split_metadata: &SplitMetadata,
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
+    tag_filter_ast: &Option<TagFilterAst>,
+) -> bool {
+    if let Some(time_range) = &split_metadata.time_range {
+        if start_timestamp.is_some_and(|start| *time_range.end() < start) {
+            return false;
+        }
+        if end_timestamp.is_some_and(|end| *time_range.start() >= end) {
+            return false;
+        }
+    }
+    tag_filter_ast.as_ref().map_or(true, |tag_filter_ast| {
+        tag_filter_ast.evaluate(&split_metadata.tags)
+    })
+}
+
+/// Returns whether a failed metastore request may succeed later, in which case the last data
+/// fetched from the metastore can be served instead. Missing indexes and invalid requests are
+/// never masked.
+pub(crate) fn is_transient_metastore_error(search_error: &SearchError) -> bool {
+    matches!(
+        search_error,
+        SearchError::Internal(_) | SearchError::Timeout(_)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split_metadata(split_id: &str, time_range: (i64, i64), tag: &str) -> SplitMetadata {
+        SplitMetadata {
+            split_id: split_id.to_string(),
+            time_range: Some(time_range.0..=time_range.1),
+            tags: [tag.to_string()].into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    fn tag_filter_ast(tag: &str) -> Option<TagFilterAst> {
+        Some(TagFilterAst::Tag {
+            is_present: true,
+            tag: tag.to_string(),
+        })
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_metastore_fallback_cache_indexes_metadata() {
+        let cache = MetastoreFallbackCache::new(Duration::from_secs(60));
+        let index_id_patterns = vec!["test-index-*".to_string()];
+        assert!(cache.get_indexes_metadata(&index_id_patterns).is_none());
+
+        let index_metadata = IndexMetadata::for_test("test-index-1", "ram:///test-index-1");
+        cache.put_indexes_metadata(&index_id_patterns, &[index_metadata]);
+        tokio::time::advance(Duration::from_secs(30)).await;
+
+        let (indexes_metadata, age) = cache.get_indexes_metadata(&index_id_patterns).unwrap();
+        assert_eq!(indexes_metadata.len(), 1);
+        assert_eq!(indexes_metadata[0].index_id(), "test-index-1");
+        assert_eq!(age, Duration::from_secs(30));
+        assert!(cache
+            .get_indexes_metadata(&["test-index-1".to_string()])
+            .is_none());
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        assert!(cache.get_indexes_metadata(&index_id_patterns).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_metastore_fallback_cache_splits() {
+        let cache = MetastoreFallbackCache::new(Duration::from_secs(60));
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let split_metadatas = vec![
+            split_metadata("split-1", (0, 99), "tenant:a"),
+            split_metadata("split-2", (100, 199), "tenant:a"),
+            split_metadata("split-3", (100, 199), "tenant:b"),
+        ];
+        cache.put_splits(&index_uid, Some(0), Some(200), &None, &split_metadatas);
+
+        let (split_metadatas, age) = cache
+            .get_splits(
+                &index_uid,
+                Some(100),
+                Some(150),
+                &tag_filter_ast("tenant:a"),
+            )
+            .unwrap();
+        assert_eq!(split_metadatas.len(), 1);
+        assert_eq!(split_metadatas[0].split_id, "split-2");
+        assert_eq!(age, Duration::ZERO);
+
+        // The cached splits do not cover the time range.
+        assert!(cache
+            .get_splits(&index_uid, None, Some(150), &None)
+            .is_none());
+        assert!(cache
+            .get_splits(&index_uid, Some(0), Some(300), &None)
+            .is_none());
+        assert!(cache
+            .get_splits(
+                &IndexUid::new_with_random_ulid("other-index"),
+                None,
+                None,
+                &None
+            )
+            .is_none());
+
+        tokio::time::advance(Duration::from_secs(61)).await;
+        assert!(cache
+            .get_splits(&index_uid, Some(100), Some(150), &None)
+            .is_none());
+    }
+
+    #[test]
+    fn test_metastore_fallback_cache_covers_end_up_to_fetch_time() {
+        let cache = MetastoreFallbackCache::new(Duration::from_secs(60));
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        cache.put_splits(&index_uid, Some(now - 900), Some(now + 1), &None, &[]);
+        // A relative time range moving forward is still covered: no split can hold documents
+        // more recent than the time the splits were listed.
+        assert!(cache
+            .get_splits(&index_uid, Some(now - 890), Some(now + 10), &None)
+            .is_some());
+        // A filtered listing does not cover another filter.
+        cache.put_splits(&index_uid, None, None, &tag_filter_ast("tenant:a"), &[]);
+        assert!(cache
+            .get_splits(&index_uid, None, None, &tag_filter_ast("tenant:b"))
+            .is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_metastore_fallback_cache_keeps_split_lists_per_filters() {
+        let cache = MetastoreFallbackCache::new(Duration::from_secs(60));
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        let split_metadatas = vec![
+            split_metadata("split-1", (0, 99), "tenant:a"),
+            split_metadata("split-2", (100, 199), "tenant:b"),
+        ];
+        cache.put_splits(&index_uid, None, None, &None, &split_metadatas);
+        tokio::time::advance(Duration::from_secs(10)).await;
+
+        // A narrow query does not replace the splits of the broad one.
+        cache.put_splits(
+            &index_uid,
+            Some(100),
+            Some(150),
+            &None,
+            &split_metadatas[1..],
+        );
+        let (split_metadatas, age) = cache.get_splits(&index_uid, None, None, &None).unwrap();
+        assert_eq!(split_metadatas.len(), 2);
+        assert_eq!(age, Duration::from_secs(10));
+
+        // The most recent list covering the query is served.
+        let (split_metadatas, age) = cache
+            .get_splits(&index_uid, Some(100), Some(150), &None)
+            .unwrap();
+        assert_eq!(split_metadatas.len(), 1);
+        assert_eq!(age, Duration::ZERO);
+
+        // The oldest lists are evicted once the index has too many of them.
+        for start_timestamp in 0..MAX_CACHED_SPLIT_LISTS_PER_INDEX as i64 {
+            cache.put_splits(&index_uid, Some(start_timestamp), None, &None, &[]);
+        }
+        assert_eq!(
+            cache.splits.lock().unwrap()[&index_uid].len(),
+            MAX_CACHED_SPLIT_LISTS_PER_INDEX
+        );
+        assert!(cache.get_splits(&index_uid, None, None, &None).is_none());
+
+        // Expired lists are evicted on the next write.
+        tokio::time::advance(Duration::from_secs(61)).await;
+        let other_index_uid = IndexUid::new_with_random_ulid("other-index");
+        cache.put_splits(&other_index_uid, None, None, &None, &[]);
+        assert!(!cache.splits.lock().unwrap().contains_key(&index_uid));
+    }
+
+    #[test]
+    fn test_metastore_fallback_cache_disabled() {
+        let cache = MetastoreFallbackCache::new(Duration::ZERO);
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+        cache.put_splits(&index_uid, None, None, &None, &[]);
+        assert!(cache.get_splits(&index_uid, None, None, &None).is_none());
+    }
+}
@@ -21,10 +21,20 @@
 
 use once_cell::sync::Lazy;
 use quickwit_common::metrics::{
-    new_counter, new_gauge, new_histogram, Histogram, IntCounter, IntGauge,
+    new_counter, new_counter_vec, new_gauge, new_gauge_vec, new_histogram, new_histogram_vec,
+    Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
 };
 
 pub struct SearchMetrics {
+    pub requests_total: IntCounterVec<2>,
+    pub request_errors_total: IntCounterVec<2>,
+    pub request_duration_seconds: HistogramVec<3>,
+    pub requests_in_flight: IntGaugeVec<1>,
+    pub root_search_splits_total: IntCounterVec<1>,
+    pub root_search_stale_metastore_total: IntCounterVec<1>,
+    pub leaf_search_failed_splits_total: IntCounterVec<1>,
+    pub leaf_search_split_cache_hits_total: IntCounterVec<1>,
+    pub leaf_search_split_cache_misses_total: IntCounterVec<1>,
     pub leaf_searches_splits_total: IntCounter,
     pub leaf_search_split_duration_secs: Histogram,
     pub active_search_threads_count: IntGauge,
@@ -33,6 +43,62 @@ pub struct SearchMetrics {
 impl Default for SearchMetrics {
     fn default() -> Self {
         SearchMetrics {
+            requests_total: new_counter_vec(
+                "requests_total",
+                "Number of search service requests (root_search, leaf_search, fetch_docs).",
+                "quickwit_search",
+                ["operation", "index"],
+            ),
+            request_errors_total: new_counter_vec(
+                "request_errors_total",
+                "Number of failed search service requests.",
+                "quickwit_search",
+                ["operation", "index"],
+            ),
+            request_duration_seconds: new_histogram_vec(
+                "request_duration_seconds",
+                "Duration of search service requests.",
+                "quickwit_search",
+                ["operation", "index", "error"],
+            ),
+            requests_in_flight: new_gauge_vec(
+                "requests_in_flight",
+                "Number of search service requests being processed.",
+                "quickwit_search",
+                ["operation"],
+            ),
+            root_search_splits_total: new_counter_vec(
+                "root_search_splits_total",
+                "Number of splits targeted by root searches. Divided by the number of root_search \
+                 requests, it gives the number of splits searched per query.",
+                "quickwit_search",
+                ["index"],
+            ),
+            root_search_stale_metastore_total: new_counter_vec(
+                "root_search_stale_metastore_total",
+                "Number of root searches served from the last index metadata or split list \
+                 fetched from the metastore because the metastore was unreachable.",
+                "quickwit_search",
+                ["index"],
+            ),
+            leaf_search_failed_splits_total: new_counter_vec(
+                "leaf_search_failed_splits_total",
+                "Number of splits that could not be searched by leaf searches.",
+                "quickwit_search",
+                ["index"],
+            ),
+            leaf_search_split_cache_hits_total: new_counter_vec(
+                "leaf_search_split_cache_hits_total",
+                "Number of reads of split files served by the ephemeral cache of leaf searches.",
+                "quickwit_search",
+                ["index"],
+            ),
+            leaf_search_split_cache_misses_total: new_counter_vec(
+                "leaf_search_split_cache_misses_total",
+                "Number of reads of split files served by the storage during leaf searches.",
+                "quickwit_search",
+                ["index"],
+            ),
             leaf_searches_splits_total: new_counter(
                 "leaf_searches_splits_total",
                 "Number of leaf searches (count of splits) started.",
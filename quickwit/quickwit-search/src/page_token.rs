@@ -0,0 +1,77 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use base64::prelude::BASE64_URL_SAFE_NO_PAD;
+use base64::Engine;
+use quickwit_proto::search::PartialHit;
+
+use crate::SearchError;
+
+/// Encodes the partial hit of the last hit of a page into the opaque `next_page_token` returned
+/// with search responses. Passing this token back as `search_after` returns the next page without
+/// requiring the caller to know the types of the sort fields.
+pub fn encode_page_token(partial_hit: &PartialHit) -> String {
+    let partial_hit_json =
+        serde_json::to_vec(partial_hit).expect("`PartialHit` should be JSON serializable");
+    BASE64_URL_SAFE_NO_PAD.encode(partial_hit_json)
+}
+
+/// Decodes a token produced by [`encode_page_token`] back into a `search_after` partial hit.
+pub fn decode_page_token(page_token: &str) -> crate::Result<PartialHit> {
+    let partial_hit_json = BASE64_URL_SAFE_NO_PAD
+        .decode(page_token)
+        .map_err(|_| SearchError::InvalidArgument(format!("invalid page token `{page_token}`")))?;
+    let partial_hit = serde_json::from_slice(&partial_hit_json)
+        .map_err(|_| SearchError::InvalidArgument(format!("invalid page token `{page_token}`")))?;
+    Ok(partial_hit)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::search::{SortByValue, SortValue};
+
+    use super::*;
+
+    #[test]
+    fn test_page_token_round_trip() {
+        let partial_hit = PartialHit {
+            sort_value: Some(SortByValue {
+                sort_value: Some(SortValue::I64(-42)),
+            }),
+            sort_value2: Some(SortByValue {
+                sort_value: Some(SortValue::F64(1.5)),
+            }),
+            split_id: "split-1".to_string(),
+            segment_ord: 1,
+            doc_id: 2,
+        };
+        let page_token = encode_page_token(&partial_hit);
+        assert_eq!(decode_page_token(&page_token).unwrap(), partial_hit);
+    }
+
+    #[test]
+    fn test_decode_invalid_page_token() {
+        let error = decode_page_token("not a token").unwrap_err();
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
+
+        let page_token = BASE64_URL_SAFE_NO_PAD.encode("{\"split_id\": 1}");
+        let error = decode_page_token(&page_token).unwrap_err();
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
+    }
+}
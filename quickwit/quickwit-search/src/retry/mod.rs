@@ -67,21 +67,19 @@ impl<'a> Job for &'a str {
 
 // Select a new client from the client pool by the following oversimplified policy:
 // 1. Take the first split_id of the request
-// 2. Ask for a relevant client for that split while excluding the failing identified by its socket
-// addr.
+// 2. Ask for a relevant client for that split while excluding the failing nodes identified by
+// their socket addr.
 pub async fn retry_client(
     search_job_placer: &SearchJobPlacer,
-    excluded_addr: SocketAddr,
+    excluded_addrs: &HashSet<SocketAddr>,
     split_id: &str,
 ) -> anyhow::Result<SearchServiceClient> {
-    let excluded_addrs = HashSet::from_iter([excluded_addr]);
-    search_job_placer
-        .assign_job(split_id, &excluded_addrs)
-        .await
+    search_job_placer.assign_job(split_id, excluded_addrs).await
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashSet;
     use std::net::SocketAddr;
     use std::sync::Arc;
 
@@ -133,10 +131,11 @@ mod tests {
             split_footer_start: 0,
             timestamp_start: None,
             timestamp_end: None,
+            num_docs: 0,
         };
         let client_for_retry = retry_client(
             &search_job_placer,
-            searcher_grpc_addr_1,
+            &HashSet::from_iter([searcher_grpc_addr_1]),
             &split_id_and_footer_offsets.split_id,
         )
         .await
@@ -45,6 +45,9 @@ impl RetryPolicy<LeafSearchRequest, LeafSearchResponse, SearchError> for LeafSea
                         .iter()
                         .any(|failed_split| failed_split.split_id == split_metadata.split_id)
                 });
+                if request.split_offsets.is_empty() {
+                    return None;
+                }
                 Some(request)
             }
             Err(_) => Some(request),
@@ -74,6 +77,9 @@ mod tests {
             }),
             doc_mapper: "doc_mapper".to_string(),
             index_uri: "uri".to_string(),
+            timeout_millis: None,
+            index_id: "test-idx".to_string(),
+            num_searchers: None,
             split_offsets: vec![
                 SplitIdAndFooterOffsets {
                     split_id: "split_1".to_string(),
@@ -81,6 +87,7 @@ mod tests {
                     split_footer_start: 0,
                     timestamp_start: None,
                     timestamp_end: None,
+                    num_docs: 0,
                 },
                 SplitIdAndFooterOffsets {
                     split_id: "split_2".to_string(),
@@ -88,6 +95,7 @@ mod tests {
                     split_footer_start: 0,
                     timestamp_start: None,
                     timestamp_end: None,
+                    num_docs: 0,
                 },
             ],
         }
@@ -138,4 +146,21 @@ mod tests {
         let retry_request = retry_policy.retry_request(request, &response_res).unwrap();
         assert_eq!(retry_request, expected_retry_request);
     }
+
+    #[test]
+    fn test_should_not_retry_on_failed_splits_absent_from_request() {
+        let retry_policy = LeafSearchRetryPolicy {};
+        let request = mock_leaf_search_request();
+        let split_error = SplitSearchError {
+            error: "error".to_string(),
+            split_id: "split_3".to_string(),
+            retryable_error: true,
+        };
+        let response_res = Ok(LeafSearchResponse {
+            failed_splits: vec![split_error],
+            num_attempted_splits: 1,
+            ..Default::default()
+        });
+        assert!(retry_policy.retry_request(request, &response_res).is_none());
+    }
 }
@@ -17,17 +17,23 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashMap, HashSet};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::future::Future;
+use std::sync::Arc;
 use std::time::Duration;
 
 use anyhow::Context;
-use futures::future::try_join_all;
+use futures::future::{try_join_all, BoxFuture};
+use futures::stream::{self, BoxStream, FuturesUnordered};
+use futures::{FutureExt, StreamExt};
 use itertools::Itertools;
+use quickwit_common::geo::{GeoBoundingBox, GeoFilter};
 use quickwit_common::shared_consts::{DELETION_GRACE_PERIOD, SCROLL_BATCH_LEN};
 use quickwit_common::uri::Uri;
 use quickwit_common::PrettySample;
-use quickwit_config::{build_doc_mapper, IndexConfig};
-use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
+use quickwit_config::{build_doc_mapper, IndexConfig, LeafSearchTimeoutConfig};
+use quickwit_doc_mapper::tag_pruning::{extract_tags_from_query, TagFilterAst};
 use quickwit_doc_mapper::{DocMapper, DYNAMIC_FIELD_NAME};
 use quickwit_metastore::{
     IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsRequestExt,
@@ -38,14 +44,17 @@ use quickwit_proto::metastore::{
     MetastoreServiceClient,
 };
 use quickwit_proto::search::{
-    FetchDocsRequest, FetchDocsResponse, Hit, LeafHit, LeafListTermsRequest, LeafListTermsResponse,
-    LeafSearchRequest, LeafSearchResponse, ListTermsRequest, ListTermsResponse, PartialHit,
-    SearchRequest, SearchResponse, SnippetRequest, SortDatetimeFormat, SortField, SortValue,
-    SplitIdAndFooterOffsets,
+    ExplainResult, FetchDocsRequest, FetchDocsResponse, Hit, HitCountAccuracy, IndexSearchError,
+    LeafHit, LeafListTermsRequest, LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse,
+    ListTermsRequest, ListTermsResponse, PartialHit, SearchRequest, SearchResponse, SearchStats,
+    SearchWarning, SnippetRequest, SortDatetimeFormat, SortField, SortValue, SplitExplanation,
+    SplitIdAndFooterOffsets, SplitSearchError,
 };
-use quickwit_proto::types::{IndexUid, SplitId};
+use quickwit_proto::types::{IndexId, IndexUid, SplitId};
+use quickwit_proto::ServiceError;
 use quickwit_query::query_ast::{
-    BoolQuery, QueryAst, QueryAstVisitor, RangeQuery, TermQuery, TermSetQuery,
+    apply_fuzzy_distance, expand_synonyms, BoolQuery, QueryAst, QueryAstVisitor, RangeQuery,
+    SynonymTable, TermQuery, TermSetQuery, MAX_FUZZY_DISTANCE,
 };
 use serde::{Deserialize, Serialize};
 use tantivy::aggregation::agg_result::AggregationResults;
@@ -53,17 +62,20 @@ use tantivy::aggregation::intermediate_agg_result::IntermediateAggregationResult
 use tantivy::collector::Collector;
 use tantivy::schema::{FieldType, Schema};
 use tantivy::TantivyError;
-use tracing::{debug, error, info, info_span, instrument};
+use tokio::time::Instant;
+use tracing::{debug, error, info, info_span, instrument, warn};
 
 use crate::cluster_client::ClusterClient;
-use crate::collector::{make_merge_collector, QuickwitAggregations};
+use crate::collector::{compute_split_cost, make_merge_collector, QuickwitAggregations};
 use crate::find_trace_ids_collector::Span;
+use crate::metastore_fallback_cache::{is_transient_metastore_error, split_matches};
 use crate::scroll_context::{ScrollContext, ScrollKeyAndStartOffset};
 use crate::search_job_placer::Job;
+use crate::search_response_cache::CacheKey as SearchResponseCacheKey;
 use crate::service::SearcherContext;
 use crate::{
-    extract_split_and_footer_offsets, list_relevant_splits, SearchError, SearchJobPlacer,
-    SearchServiceClient,
+    encode_page_token, extract_split_and_footer_offsets, list_relevant_snapshot_splits,
+    list_relevant_splits, SearchError, SearchJobPlacer, SearchServiceClient, SEARCH_METRICS,
 };
 
 /// Maximum accepted scroll TTL.
@@ -103,7 +115,7 @@ impl<'a> From<&'a SplitMetadata> for SearchJob {
     fn from(split_metadata: &'a SplitMetadata) -> Self {
         SearchJob {
             index_uid: split_metadata.index_uid.clone(),
-            cost: compute_split_cost(split_metadata),
+            cost: compute_split_cost(split_metadata.num_docs as u64),
             offsets: extract_split_and_footer_offsets(split_metadata),
         }
     }
@@ -148,44 +160,102 @@ pub struct IndexMetasForLeafSearch {
     pub index_uri: Uri,
     /// Doc mapper json string.
     pub doc_mapper_str: String,
+    /// Query AST json string to run on the index instead of the query AST of the search request,
+    /// when the index resolves the query differently, e.g. because it expands it with synonyms.
+    #[serde(default)]
+    pub query_ast_opt: Option<String>,
 }
 
 pub(crate) type IndexesMetasForLeafSearch = HashMap<IndexUid, IndexMetasForLeafSearch>;
 type TimestampFieldOpt = Option<String>;
+/// Synonym table and maximum number of expansions per term of each index.
+type SynonymTables = HashMap<IndexUid, (Arc<SynonymTable>, usize)>;
+
+/// Loads the synonym tables of the indexes configuring synonyms, unless the request opts out.
+async fn load_synonym_tables(
+    searcher_context: &SearcherContext,
+    indexes_metadata: &[IndexMetadata],
+    search_request: &SearchRequest,
+) -> crate::Result<SynonymTables> {
+    let mut synonym_tables = SynonymTables::new();
+    if search_request.disable_synonyms {
+        return Ok(synonym_tables);
+    }
+    for index_metadata in indexes_metadata {
+        let Some(synonyms_config) = &index_metadata.index_config.search_settings.synonyms else {
+            continue;
+        };
+        let synonym_table = searcher_context
+            .synonym_table_cache
+            .get(&index_metadata.index_uid, synonyms_config)
+            .await
+            .map_err(|err| {
+                SearchError::Internal(format!(
+                    "failed to load synonyms of index `{}`. cause: {err:#}",
+                    index_metadata.index_id()
+                ))
+            })?;
+        synonym_tables.insert(
+            index_metadata.index_uid.clone(),
+            (synonym_table, synonyms_config.max_expansions),
+        );
+    }
+    Ok(synonym_tables)
+}
 
 /// Validates request against each index's doc mapper and ensures that:
 /// - timestamp fields (if any) are equal across indexes.
-/// - resolved query ASTs are the same across indexes.
+/// - resolved query ASTs, before synonym expansion, are the same across indexes.
 /// - if a sort field has a datetime format specified, it must be a datetime field on all indexes.
-/// Returns the timestamp field, the resolved query AST and the indexes metadatas
-/// needed for leaf search requests.
+/// Indexes the request cannot be run against are returned as failed indexes, unless all of them
+/// fail, in which case the error of the first index is returned.
+/// Returns the timestamp field, the resolved query AST, the indexes metadatas
+/// needed for leaf search requests and the failed indexes. The metadatas of the indexes that
+/// expand the query with their synonyms carry their own query AST.
 /// Note: the requirements on timestamp fields and resolved query ASTs can be lifted
 /// but it adds complexity that does not seem needed right now.
 fn validate_request_and_build_metadatas(
     indexes_metadata: &[IndexMetadata],
     search_request: &SearchRequest,
-) -> crate::Result<(TimestampFieldOpt, QueryAst, IndexesMetasForLeafSearch)> {
+    synonym_tables: &SynonymTables,
+) -> crate::Result<(
+    TimestampFieldOpt,
+    QueryAst,
+    IndexesMetasForLeafSearch,
+    Vec<IndexSearchError>,
+)> {
     let mut metadatas_for_leaf: HashMap<IndexUid, IndexMetasForLeafSearch> = HashMap::new();
     let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast)
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
     let mut query_ast_resolved_opt: Option<QueryAst> = None;
     let mut timestamp_field_opt: Option<String> = None;
+    let mut failed_indexes: Vec<IndexSearchError> = Vec::new();
+    let mut first_error_opt: Option<SearchError> = None;
 
     for index_metadata in indexes_metadata {
-        let doc_mapper = build_doc_mapper(
-            &index_metadata.index_config.doc_mapping,
-            &index_metadata.index_config.search_settings,
-        )
-        .map_err(|err| {
-            SearchError::Internal(format!("failed to build doc mapper. cause: {err}"))
-        })?;
-        let query_ast_resolved_for_index = query_ast
-            .clone()
-            .parse_user_query(doc_mapper.default_search_fields())
-            // We convert the error to return a 400 to the user (and not a 500).
-            .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+        let (query_ast_resolved_for_index, timestamp_field_for_index_opt, index_metas_for_leaf) =
+            match validate_request_for_index(
+                index_metadata,
+                &query_ast,
+                search_request,
+                synonym_tables,
+            ) {
+                Ok(validated_request) => validated_request,
+                Err(search_error) => {
+                    warn!(
+                        index_id=%index_metadata.index_id(),
+                        error=%search_error,
+                        "failed to validate search request for index"
+                    );
+                    failed_indexes
+                        .push(index_search_error(index_metadata.index_id(), &search_error));
+                    first_error_opt.get_or_insert(search_error);
+                    continue;
+                }
+            };
 
-        // Validate uniqueness of resolved query AST.
+        // Validate uniqueness of resolved query AST. Synonyms are configured per index, so the
+        // query ASTs expanded with them may differ.
         if let Some(query_ast_resolved) = &query_ast_resolved_opt {
             if query_ast_resolved != &query_ast_resolved_for_index {
                 return Err(SearchError::InvalidQuery(
@@ -195,49 +265,146 @@ fn validate_request_and_build_metadatas(
                 ));
             }
         } else {
-            query_ast_resolved_opt = Some(query_ast_resolved_for_index.clone());
+            query_ast_resolved_opt = Some(query_ast_resolved_for_index);
         }
 
         // Validate uniqueness of timestamp field if any.
-        if let Some(timestamp_field_for_index) = doc_mapper.timestamp_field_name() {
-            match timestamp_field_opt {
-                Some(timestamp_field) if timestamp_field != timestamp_field_for_index => {
+        if let Some(timestamp_field_for_index) = timestamp_field_for_index_opt {
+            match &timestamp_field_opt {
+                Some(timestamp_field) if timestamp_field != &timestamp_field_for_index => {
                     return Err(SearchError::InvalidQuery(
                         "the timestamp field (if present) must be the same for all indexes"
                             .to_string(),
                     ));
                 }
                 None => {
-                    timestamp_field_opt = Some(timestamp_field_for_index.to_string());
+                    timestamp_field_opt = Some(timestamp_field_for_index);
                 }
                 _ => {}
             }
         }
-
-        validate_request(&*doc_mapper, search_request)?;
-
-        // Validates the query by effectively building it against the current schema.
-        doc_mapper.query(doc_mapper.schema(), &query_ast_resolved_for_index, true)?;
-
-        let index_metadata_for_leaf_search = IndexMetasForLeafSearch {
-            index_uri: index_metadata.index_uri().clone(),
-            doc_mapper_str: serde_json::to_string(&doc_mapper).map_err(|err| {
-                SearchError::Internal(format!("failed to serialize doc mapper. cause: {err}"))
-            })?,
-        };
-        metadatas_for_leaf.insert(
-            index_metadata.index_uid.clone(),
-            index_metadata_for_leaf_search,
-        );
+        metadatas_for_leaf.insert(index_metadata.index_uid.clone(), index_metas_for_leaf);
+    }
+    if metadatas_for_leaf.is_empty() {
+        if let Some(first_error) = first_error_opt {
+            return Err(first_error);
+        }
     }
-
     let query_ast_resolved = query_ast_resolved_opt.ok_or_else(|| {
         SearchError::Internal(
             "resolved query AST must be present. this should never happen".to_string(),
         )
     })?;
 
-    Ok((timestamp_field_opt, query_ast_resolved, metadatas_for_leaf))
+    Ok((
+        timestamp_field_opt,
+        query_ast_resolved,
+        metadatas_for_leaf,
+        failed_indexes,
+    ))
+}
+
+/// Validates the request against the doc mapper of an index.
+/// Returns the query AST resolved for the index, the timestamp field of the index (if any) and
+/// the index metadata needed for leaf search requests, which carries the query AST expanded with
+/// the synonyms of the index if it has any.
+fn validate_request_for_index(
+    index_metadata: &IndexMetadata,
+    query_ast: &QueryAst,
+    search_request: &SearchRequest,
+    synonym_tables: &SynonymTables,
+) -> crate::Result<(QueryAst, TimestampFieldOpt, IndexMetasForLeafSearch)> {
+    let doc_mapper = build_doc_mapper(
+        &index_metadata.index_config.doc_mapping,
+        &index_metadata.index_config.search_settings,
+    )
+    .map_err(|err| SearchError::Internal(format!("failed to build doc mapper. cause: {err}")))?;
+    let mut query_ast_resolved_for_index = query_ast
+        .clone()
+        .parse_user_query(doc_mapper.default_search_fields())
+        // We convert the error to return a 400 to the user (and not a 500).
+        .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
+
+    validate_timestamp_range(
+        &*doc_mapper,
+        index_metadata.index_id(),
+        search_request.start_timestamp,
+        search_request.end_timestamp,
+    )?;
+    validate_request(&*doc_mapper, search_request)?;
+
+    let mut query_ast_expanded_opt: Option<QueryAst> = None;
+
+    if let Some((synonym_table, max_expansions)) = synonym_tables.get(&index_metadata.index_uid) {
+        let (query_ast_expanded, applied_synonyms) = expand_synonyms(
+            query_ast_resolved_for_index.clone(),
+            synonym_table,
+            *max_expansions,
+        );
+        for applied_synonym in applied_synonyms {
+            if applied_synonym.truncated {
+                warn!(
+                    index_id=%index_metadata.index_id(),
+                    text=%applied_synonym.text,
+                    max_expansions=%max_expansions,
+                    "synonym expansion truncated"
+                );
+            }
+            debug!(
+                index_id=%index_metadata.index_id(),
+                field=%applied_synonym.field,
+                text=%applied_synonym.text,
+                expansions=?applied_synonym.expansions,
+                "expanded query with synonyms"
+            );
+        }
+        if query_ast_expanded != query_ast_resolved_for_index {
+            query_ast_expanded_opt = Some(query_ast_expanded);
+        }
+    }
+    if let Some(fuzzy_distance) = search_request.fuzzy_distance {
+        // The fuzzy distance is bounded by `validate_request`.
+        query_ast_resolved_for_index =
+            apply_fuzzy_distance(query_ast_resolved_for_index, fuzzy_distance as u8);
+        query_ast_expanded_opt = query_ast_expanded_opt.map(|query_ast_expanded| {
+            apply_fuzzy_distance(query_ast_expanded, fuzzy_distance as u8)
+        });
+    }
+    // Validates the query by effectively building it against the current schema.
+    doc_mapper.query(
+        doc_mapper.schema(),
+        query_ast_expanded_opt
+            .as_ref()
+            .unwrap_or(&query_ast_resolved_for_index),
+        true,
+    )?;
+
+    let query_ast_opt = query_ast_expanded_opt
+        .map(|query_ast_expanded| serde_json::to_string(&query_ast_expanded))
+        .transpose()?;
+    let index_metadata_for_leaf_search = IndexMetasForLeafSearch {
+        index_uri: index_metadata.index_uri().clone(),
+        doc_mapper_str: serde_json::to_string(&doc_mapper).map_err(|err| {
+            SearchError::Internal(format!("failed to serialize doc mapper. cause: {err}"))
+        })?,
+        query_ast_opt,
+    };
+    let timestamp_field_opt = doc_mapper
+        .timestamp_field_name()
+        .map(|timestamp_field| timestamp_field.to_string());
+    Ok((
+        query_ast_resolved_for_index,
+        timestamp_field_opt,
+        index_metadata_for_leaf_search,
+    ))
+}
+
+fn index_search_error(index_id: &str, search_error: &SearchError) -> IndexSearchError {
+    IndexSearchError {
+        index_id: index_id.to_string(),
+        error_code: search_error.error_code().as_str().to_string(),
+        message: search_error.to_string(),
+    }
 }
 
 fn validate_requested_snippet_fields(
@@ -294,6 +461,15 @@ fn simplify_search_request_for_scroll_api(req: &SearchRequest) -> crate::Result<
         scroll_ttl_secs: None,
         search_after: None,
         count_hits: req.count_hits,
+        tags: req.tags.clone(),
+        disable_synonyms: req.disable_synonyms,
+        snapshot_id: req.snapshot_id.clone(),
+        geo_filters: req.geo_filters.clone(),
+        // Scroll pages are fetched from the scroll cache, the timeout only applies to the first
+        // page.
+        timeout_millis: None,
+        fuzzy_distance: req.fuzzy_distance,
+        num_searchers: req.num_searchers,
     })
 }
 
@@ -385,21 +561,31 @@ fn validate_sort_by_field(
     Ok(())
 }
 
-fn validate_request(
+/// Checks that no time range is requested on an index without timestamp field. The splits of
+/// such an index cannot be pruned, nor its documents filtered, by timestamp, so the time range
+/// would otherwise be silently ignored.
+pub(crate) fn validate_timestamp_range(
     doc_mapper: &dyn DocMapper,
-    search_request: &SearchRequest,
+    index_id: &str,
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
 ) -> crate::Result<()> {
-    let schema = doc_mapper.schema();
     if doc_mapper.timestamp_field_name().is_none()
-        && (search_request.start_timestamp.is_some() || search_request.end_timestamp.is_some())
+        && (start_timestamp.is_some() || end_timestamp.is_some())
     {
         return Err(SearchError::InvalidQuery(format!(
-            "the timestamp field is not set in index: {:?} definition but start-timestamp or \
-             end-timestamp are set in the query",
-            search_request.index_id_patterns
+            "timestamp filtering is not supported for index `{index_id}`: its doc mapping has no \
+             timestamp field but a start or end timestamp is set in the request"
         )));
     }
+    Ok(())
+}
 
+fn validate_request(
+    doc_mapper: &dyn DocMapper,
+    search_request: &SearchRequest,
+) -> crate::Result<()> {
+    let schema = doc_mapper.schema();
     validate_requested_snippet_fields(&schema, &search_request.snippet_fields)?;
 
     validate_sort_by_fields_and_search_after(
@@ -416,6 +602,16 @@ fn validate_request(
         })?;
     };
 
+    let geo_point_field_names = doc_mapper.geo_point_field_names();
+    for (geo_point_field_name, _) in parse_geo_filter_bounding_boxes(&search_request.geo_filters)? {
+        if !geo_point_field_names.contains(&geo_point_field_name) {
+            return Err(SearchError::InvalidArgument(format!(
+                "geo filters can only target `geo_point` fields, but `{geo_point_field_name}` is \
+                 not a `geo_point` field"
+            )));
+        }
+    }
+
     if search_request.start_offset > 10_000 {
         return Err(SearchError::InvalidArgument(format!(
             "max value for start_offset is 10_000, but got {}",
@@ -430,9 +626,55 @@ fn validate_request(
         )));
     }
 
+    if let Some(fuzzy_distance) = search_request.fuzzy_distance {
+        if fuzzy_distance > MAX_FUZZY_DISTANCE as u32 {
+            return Err(SearchError::InvalidArgument(format!(
+                "max value for fuzzy_distance is {MAX_FUZZY_DISTANCE}, but got {fuzzy_distance}"
+            )));
+        }
+    }
+
     Ok(())
 }
 
+/// Parses the JSON serialized geo filters of a search request and returns the field targeted by
+/// each filter along with a bounding box containing all the points it matches.
+fn parse_geo_filter_bounding_boxes(
+    geo_filters: &[String],
+) -> crate::Result<Vec<(String, GeoBoundingBox)>> {
+    geo_filters
+        .iter()
+        .map(|geo_filter_json| {
+            let geo_filter: GeoFilter = serde_json::from_str(geo_filter_json).map_err(|err| {
+                SearchError::InvalidArgument(format!("invalid geo filter: {err}"))
+            })?;
+            let bounding_box = geo_filter.bounding_box().map_err(|err| {
+                SearchError::InvalidArgument(format!("invalid geo filter: {err}"))
+            })?;
+            Ok((geo_filter.field().to_string(), bounding_box))
+        })
+        .collect()
+}
+
+/// Returns false if the points of the split cannot match one of the geo filters.
+///
+/// Splits without a bounding box for the filtered field, such as splits created before bounding
+/// boxes were recorded, are always kept.
+fn split_may_match_geo_filters(
+    split_metadata: &SplitMetadata,
+    geo_filter_bounding_boxes: &[(String, GeoBoundingBox)],
+) -> bool {
+    geo_filter_bounding_boxes
+        .iter()
+        .all(|(geo_point_field_name, bounding_box)| {
+            split_metadata
+                .geo_bounding_boxes
+                .get(geo_point_field_name)
+                .map(|split_bounding_box| split_bounding_box.intersects(bounding_box))
+                .unwrap_or(true)
+        })
+}
+
 fn get_scroll_ttl_duration(search_request: &SearchRequest) -> crate::Result<Option<Duration>> {
     let Some(scroll_ttl_secs) = search_request.scroll_ttl_secs else {
         return Ok(None);
@@ -447,6 +689,31 @@ fn get_scroll_ttl_duration(search_request: &SearchRequest) -> crate::Result<Opti
     Ok(Some(scroll_ttl))
 }
 
+/// Share of the search timeout allotted to the leaf searches. The rest of the timeout is left for
+/// fetching the documents.
+const LEAF_SEARCH_TIMEOUT_SHARE: f64 = 0.8;
+
+/// Deadlines of the phases of a search request with a timeout.
+#[derive(Clone, Copy, Debug)]
+struct SearchDeadlines {
+    /// The leaves interrupt their search at this instant, and the root stops waiting for them.
+    leaf_search: Instant,
+    /// The root stops waiting for the documents at this instant and returns no hits.
+    fetch_docs: Instant,
+}
+
+impl SearchDeadlines {
+    /// Returns the deadlines of a search started at `search_start`, or `None` if the request has
+    /// no timeout.
+    fn for_search_request(search_start: Instant, search_request: &SearchRequest) -> Option<Self> {
+        let search_timeout = Duration::from_millis(search_request.timeout_millis?);
+        Some(Self {
+            leaf_search: search_start + search_timeout.mul_f64(LEAF_SEARCH_TIMEOUT_SHARE),
+            fetch_docs: search_start + search_timeout,
+        })
+    }
+}
+
 #[instrument(level = "debug", skip_all)]
 async fn search_partial_hits_phase_with_scroll(
     searcher_context: &SearcherContext,
@@ -454,6 +721,7 @@ async fn search_partial_hits_phase_with_scroll(
     mut search_request: SearchRequest,
     split_metadatas: &[SplitMetadata],
     cluster_client: &ClusterClient,
+    leaf_search_deadline_opt: Option<Instant>,
 ) -> crate::Result<(LeafSearchResponse, Option<ScrollKeyAndStartOffset>)> {
     let scroll_ttl_opt = get_scroll_ttl_duration(&search_request)?;
 
@@ -470,6 +738,7 @@ async fn search_partial_hits_phase_with_scroll(
             &search_request,
             split_metadatas,
             cluster_client,
+            leaf_search_deadline_opt,
         )
         .await?;
         let cached_partial_hits = leaf_search_resp.partial_hits.clone();
@@ -506,12 +775,17 @@ async fn search_partial_hits_phase_with_scroll(
             &search_request,
             split_metadatas,
             cluster_client,
+            leaf_search_deadline_opt,
         )
         .await?;
         Ok((leaf_search_resp, None))
     }
 }
 
+/// Searches the splits on the leaves and merges their responses.
+///
+/// If a `leaf_search_deadline_opt` is given, the leaves interrupt their search at this deadline
+/// and the root stops waiting for them.
 #[instrument(level = "debug", skip_all)]
 pub(crate) async fn search_partial_hits_phase(
     searcher_context: &SearcherContext,
@@ -519,6 +793,7 @@ pub(crate) async fn search_partial_hits_phase(
     search_request: &SearchRequest,
     split_metadatas: &[SplitMetadata],
     cluster_client: &ClusterClient,
+    leaf_search_deadline_opt: Option<Instant>,
 ) -> crate::Result<LeafSearchResponse> {
     let jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
     let assigned_leaf_search_jobs = cluster_client
@@ -529,8 +804,31 @@ pub(crate) async fn search_partial_hits_phase(
     for (client, client_jobs) in assigned_leaf_search_jobs {
         let leaf_requests =
             jobs_to_leaf_requests(search_request, indexes_metas_for_leaf_search, client_jobs)?;
-        for leaf_request in leaf_requests {
-            leaf_request_tasks.push(cluster_client.leaf_search(leaf_request, client.clone()));
+        for mut leaf_request in leaf_requests {
+            let mut timeout_opt = compute_leaf_search_timeout(
+                searcher_context
+                    .searcher_config
+                    .leaf_search_timeout
+                    .as_ref(),
+                &leaf_request.split_offsets,
+            );
+            if let Some(leaf_search_deadline) = leaf_search_deadline_opt {
+                let remaining_time = leaf_search_deadline.saturating_duration_since(Instant::now());
+                timeout_opt =
+                    Some(timeout_opt.map_or(remaining_time, |timeout| timeout.min(remaining_time)));
+            }
+            leaf_request.timeout_millis = timeout_opt.map(|timeout| timeout.as_millis() as u64);
+            let split_ids: Vec<SplitId> = leaf_request
+                .split_offsets
+                .iter()
+                .map(|split_offsets| split_offsets.split_id.clone())
+                .collect();
+            let leaf_search_fut = cluster_client.leaf_search(leaf_request, client.clone());
+            leaf_request_tasks.push(leaf_search_with_timeout(
+                leaf_search_fut,
+                split_ids,
+                leaf_search_deadline_opt,
+            ));
         }
     }
     let leaf_search_responses: Vec<LeafSearchResponse> = try_join_all(leaf_request_tasks).await?;
@@ -556,18 +854,55 @@ pub(crate) async fn search_partial_hits_phase(
     debug!(
         num_hits = leaf_search_response.num_hits,
         failed_splits = ?leaf_search_response.failed_splits,
+        partial_splits = ?leaf_search_response.partial_splits,
         num_attempted_splits = leaf_search_response.num_attempted_splits,
         has_intermediate_aggregation_result = leaf_search_response.intermediate_aggregation_result.is_some(),
         "Merged leaf search response."
     );
     if !leaf_search_response.failed_splits.is_empty() {
         error!(failed_splits = ?leaf_search_response.failed_splits, "leaf search response contains at least one failed split");
-        let errors: String = leaf_search_response.failed_splits.iter().join(", ");
-        return Err(SearchError::Internal(errors));
+        // We only fail the whole request if none of the splits could be searched. Otherwise, the
+        // failed splits are reported alongside the partial results.
+        if leaf_search_response.failed_splits.len() >= split_metadatas.len() {
+            let errors: String = leaf_search_response.failed_splits.iter().join(", ");
+            return Err(SearchError::Internal(errors));
+        }
+    }
+    if !leaf_search_response.partial_splits.is_empty() {
+        warn!(
+            partial_splits = ?leaf_search_response.partial_splits,
+            "the search of some splits was interrupted, returning their partial results"
+        );
     }
     Ok(leaf_search_response)
 }
 
+/// Waits for the response of a leaf until `deadline_opt` at most.
+///
+/// The leaf interrupts its search by itself at the deadline, so this only kicks in for leaves
+/// that are unresponsive. Their splits are then reported as partially searched, without any hit, so
+/// that the results of the other leaves are still returned.
+async fn leaf_search_with_timeout(
+    leaf_search_fut: impl Future<Output = crate::Result<LeafSearchResponse>>,
+    split_ids: Vec<SplitId>,
+    deadline_opt: Option<Instant>,
+) -> crate::Result<LeafSearchResponse> {
+    let Some(deadline) = deadline_opt else {
+        return leaf_search_fut.await;
+    };
+    match tokio::time::timeout_at(deadline, leaf_search_fut).await {
+        Ok(leaf_search_result) => leaf_search_result,
+        Err(_elapsed) => {
+            warn!(split_ids = ?PrettySample::new(&split_ids, 5), "leaf search timed out");
+            Ok(LeafSearchResponse {
+                num_attempted_splits: split_ids.len() as u64,
+                partial_splits: split_ids,
+                ..Default::default()
+            })
+        }
+    }
+}
+
 pub(crate) fn get_snippet_request(search_request: &SearchRequest) -> Option<SnippetRequest> {
     if search_request.snippet_fields.is_empty() {
         return None;
@@ -586,38 +921,18 @@ pub(crate) async fn fetch_docs_phase(
     search_request: &SearchRequest,
     cluster_client: &ClusterClient,
 ) -> crate::Result<Vec<Hit>> {
-    let snippet_request: Option<SnippetRequest> = get_snippet_request(search_request);
-    let hit_order: HashMap<(String, u32, u32), usize> = partial_hits
-        .iter()
-        .enumerate()
-        .map(|(position, partial_hit)| {
-            let key = (
-                partial_hit.split_id.clone(),
-                partial_hit.segment_ord,
-                partial_hit.doc_id,
-            );
-            (key, position)
-        })
-        .collect();
-
-    let assigned_fetch_docs_jobs = assign_client_fetch_docs_jobs(
+    let hit_order = build_hit_order(partial_hits);
+    let fetch_docs_requests = build_fetch_docs_requests(
+        indexes_metas_for_leaf_search,
         partial_hits,
         split_metadatas,
-        &cluster_client.search_job_placer,
+        search_request,
+        cluster_client,
     )
     .await?;
-
-    let mut fetch_docs_tasks = Vec::new();
-    for (client, client_jobs) in assigned_fetch_docs_jobs {
-        let fetch_jobs_requests = jobs_to_fetch_docs_requests(
-            snippet_request.clone(),
-            indexes_metas_for_leaf_search,
-            client_jobs,
-        )?;
-        for fetch_docs_request in fetch_jobs_requests {
-            fetch_docs_tasks.push(cluster_client.fetch_docs(fetch_docs_request, client.clone()));
-        }
-    }
+    let fetch_docs_tasks = fetch_docs_requests
+        .into_iter()
+        .map(|(client, fetch_docs_request)| cluster_client.fetch_docs(fetch_docs_request, client));
     let fetch_docs_responses: Vec<FetchDocsResponse> = try_join_all(fetch_docs_tasks).await?;
 
     // Merge the fetched docs.
@@ -625,17 +940,7 @@ pub(crate) async fn fetch_docs_phase(
         .into_iter()
         .flat_map(|response| response.hits.into_iter());
 
-    // Build map of Split ID > index ID to add the index ID to the hits.
-    // Used for ES compatibility.
-    let split_id_to_index_id_map: HashMap<&SplitId, &str> = split_metadatas
-        .iter()
-        .map(|split_metadata| {
-            (
-                &split_metadata.split_id,
-                split_metadata.index_uid.index_id(),
-            )
-        })
-        .collect();
+    let split_id_to_index_id_map = build_split_id_to_index_id_map(split_metadatas);
     let mut sort_field_iter = search_request.sort_fields.iter();
     let sort_field_1_datetime_format_opt: Option<SortDatetimeFormat> =
         get_sort_field_datetime_format(sort_field_iter.next())?;
@@ -662,9 +967,210 @@ pub(crate) async fn fetch_docs_phase(
     Ok(hits)
 }
 
+/// Fetches the documents of the partial hits and streams them in the order of the partial hits.
+///
+/// Unlike [`fetch_docs_phase`], a hit is yielded as soon as the hits ranked before it have been
+/// fetched, instead of once all the fetch docs responses have been received. The stream ends after
+/// the first error.
+pub(crate) async fn fetch_docs_phase_stream(
+    indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
+    partial_hits: &[PartialHit],
+    split_metadatas: &[SplitMetadata],
+    search_request: &SearchRequest,
+    cluster_client: &ClusterClient,
+) -> crate::Result<BoxStream<'static, crate::Result<Hit>>> {
+    let fetch_docs_requests = build_fetch_docs_requests(
+        indexes_metas_for_leaf_search,
+        partial_hits,
+        split_metadatas,
+        search_request,
+        cluster_client,
+    )
+    .await?;
+    let fetch_docs_futures: FuturesUnordered<_> = fetch_docs_requests
+        .into_iter()
+        .map(|(client, fetch_docs_request)| {
+            let cluster_client = cluster_client.clone();
+            async move { cluster_client.fetch_docs(fetch_docs_request, client).await }.boxed()
+        })
+        .collect();
+    let mut sort_field_iter = search_request.sort_fields.iter();
+    let hits_merger = FetchedHitsMerger {
+        fetch_docs_futures,
+        hits_heap: BinaryHeap::new(),
+        next_position: 0,
+        hit_order: build_hit_order(partial_hits),
+        split_id_to_index_id_map: build_split_id_to_index_id_map(split_metadatas),
+        sort_field_1_datetime_format_opt: get_sort_field_datetime_format(sort_field_iter.next())?,
+        sort_field_2_datetime_format_opt: get_sort_field_datetime_format(sort_field_iter.next())?,
+        is_terminated: false,
+    };
+    let hits_stream = stream::unfold(hits_merger, |mut hits_merger| async move {
+        let hit_res = hits_merger.next_hit().await?;
+        Some((hit_res, hits_merger))
+    });
+    Ok(hits_stream.boxed())
+}
+
+/// A hit along with its position in the list of hits of the response. Hits are ordered by
+/// position only.
+struct PositionedHit {
+    position: usize,
+    hit: Hit,
+}
+
+impl PartialEq for PositionedHit {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+    }
+}
+
+impl Eq for PositionedHit {}
+
+impl PartialOrd for PositionedHit {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PositionedHit {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.position.cmp(&other.position)
+    }
+}
+
+/// Merges the hits of the fetch docs responses, in whatever order the responses come back, into a
+/// sequence of hits ordered by position.
+///
+/// Each response is a run of hits of a node. The hits of all runs go through a min-heap keyed by
+/// position, which is popped as long as its top is the next expected position.
+struct FetchedHitsMerger {
+    fetch_docs_futures: FuturesUnordered<BoxFuture<'static, crate::Result<FetchDocsResponse>>>,
+    hits_heap: BinaryHeap<Reverse<PositionedHit>>,
+    next_position: usize,
+    hit_order: HashMap<(String, u32, u32), usize>,
+    split_id_to_index_id_map: HashMap<SplitId, IndexId>,
+    sort_field_1_datetime_format_opt: Option<SortDatetimeFormat>,
+    sort_field_2_datetime_format_opt: Option<SortDatetimeFormat>,
+    is_terminated: bool,
+}
+
+impl FetchedHitsMerger {
+    async fn next_hit(&mut self) -> Option<crate::Result<Hit>> {
+        if self.is_terminated {
+            return None;
+        }
+        loop {
+            let next_hit_is_ready = self
+                .hits_heap
+                .peek()
+                .map(|Reverse(positioned_hit)| positioned_hit.position <= self.next_position)
+                .unwrap_or(false);
+            if next_hit_is_ready {
+                return self.pop_hit().map(Ok);
+            }
+            match self.fetch_docs_futures.next().await {
+                Some(Ok(fetch_docs_response)) => {
+                    for leaf_hit in fetch_docs_response.hits {
+                        match build_hit_with_position(
+                            leaf_hit,
+                            &self.split_id_to_index_id_map,
+                            &self.hit_order,
+                            &self.sort_field_1_datetime_format_opt,
+                            &self.sort_field_2_datetime_format_opt,
+                        ) {
+                            Ok((position, hit)) => {
+                                self.hits_heap
+                                    .push(Reverse(PositionedHit { position, hit }));
+                            }
+                            Err(error) => {
+                                self.is_terminated = true;
+                                return Some(Err(error));
+                            }
+                        }
+                    }
+                }
+                Some(Err(error)) => {
+                    self.is_terminated = true;
+                    return Some(Err(error));
+                }
+                // All the documents have been fetched. Some of them may be missing, so we stop
+                // waiting for the next position and drain the heap.
+                None => return self.pop_hit().map(Ok),
+            }
+        }
+    }
+
+    fn pop_hit(&mut self) -> Option<Hit> {
+        let Reverse(positioned_hit) = self.hits_heap.pop()?;
+        self.next_position = positioned_hit.position + 1;
+        Some(positioned_hit.hit)
+    }
+}
+
+/// Maps each partial hit to its position.
+fn build_hit_order(partial_hits: &[PartialHit]) -> HashMap<(String, u32, u32), usize> {
+    partial_hits
+        .iter()
+        .enumerate()
+        .map(|(position, partial_hit)| {
+            let key = (
+                partial_hit.split_id.clone(),
+                partial_hit.segment_ord,
+                partial_hit.doc_id,
+            );
+            (key, position)
+        })
+        .collect()
+}
+
+/// Builds the map of split ID to index ID used to add the index ID to the hits.
+/// Used for ES compatibility.
+fn build_split_id_to_index_id_map(split_metadatas: &[SplitMetadata]) -> HashMap<SplitId, IndexId> {
+    split_metadatas
+        .iter()
+        .map(|split_metadata| {
+            (
+                split_metadata.split_id.clone(),
+                split_metadata.index_uid.index_id().to_string(),
+            )
+        })
+        .collect()
+}
+
+/// Assigns the fetch docs jobs of the partial hits to the searchers and builds their requests.
+async fn build_fetch_docs_requests(
+    indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
+    partial_hits: &[PartialHit],
+    split_metadatas: &[SplitMetadata],
+    search_request: &SearchRequest,
+    cluster_client: &ClusterClient,
+) -> crate::Result<Vec<(SearchServiceClient, FetchDocsRequest)>> {
+    let snippet_request: Option<SnippetRequest> = get_snippet_request(search_request);
+    let assigned_fetch_docs_jobs = assign_client_fetch_docs_jobs(
+        partial_hits,
+        split_metadatas,
+        &cluster_client.search_job_placer,
+    )
+    .await?;
+
+    let mut fetch_docs_requests = Vec::new();
+    for (client, client_jobs) in assigned_fetch_docs_jobs {
+        let fetch_jobs_requests = jobs_to_fetch_docs_requests(
+            snippet_request.clone(),
+            indexes_metas_for_leaf_search,
+            client_jobs,
+        )?;
+        for fetch_docs_request in fetch_jobs_requests {
+            fetch_docs_requests.push((client.clone(), fetch_docs_request));
+        }
+    }
+    Ok(fetch_docs_requests)
+}
+
 fn build_hit_with_position(
     mut leaf_hit: LeafHit,
-    split_id_to_index_id_map: &HashMap<&SplitId, &str>,
+    split_id_to_index_id_map: &HashMap<SplitId, IndexId>,
     hit_order: &HashMap<(String, u32, u32), usize>,
     sort_field_1_datetime_format_opt: &Option<SortDatetimeFormat>,
     sort_field_2_datetime_format_opt: &Option<SortDatetimeFormat>,
@@ -699,7 +1205,7 @@ fn build_hit_with_position(
     let position = *hit_order.get(&key).expect("hit order must be present");
     let index_id = split_id_to_index_id_map
         .get(&partial_hit_ref.split_id)
-        .map(|split_id| split_id.to_string())
+        .cloned()
         .unwrap_or_default();
 
     Result::<(usize, Hit), SearchError>::Ok((
@@ -739,6 +1245,7 @@ async fn root_search_aux(
     search_request: SearchRequest,
     split_metadatas: Vec<SplitMetadata>,
     cluster_client: &ClusterClient,
+    search_deadlines_opt: Option<SearchDeadlines>,
 ) -> crate::Result<SearchResponse> {
     debug!(split_metadatas = ?PrettySample::new(&split_metadatas, 5));
     let (first_phase_result, scroll_key_and_start_offset_opt): (
@@ -750,17 +1257,30 @@ async fn root_search_aux(
         search_request.clone(),
         &split_metadatas[..],
         cluster_client,
+        search_deadlines_opt.map(|search_deadlines| search_deadlines.leaf_search),
     )
     .await?;
 
-    let hits = fetch_docs_phase(
+    let fetch_docs_fut = fetch_docs_phase(
         indexes_metas_for_leaf_search,
         &first_phase_result.partial_hits,
         &split_metadatas[..],
         &search_request,
         cluster_client,
-    )
-    .await?;
+    );
+    let mut fetch_docs_timed_out = false;
+    let hits = if let Some(search_deadlines) = search_deadlines_opt {
+        match tokio::time::timeout_at(search_deadlines.fetch_docs, fetch_docs_fut).await {
+            Ok(fetch_docs_result) => fetch_docs_result?,
+            Err(_elapsed) => {
+                warn!("fetching the documents exceeded the search timeout, returning no hits");
+                fetch_docs_timed_out = true;
+                Vec::new()
+            }
+        }
+    } else {
+        fetch_docs_fut.await?
+    };
 
     let aggregation_result_json_opt = finalize_aggregation_if_any(
         &search_request,
@@ -768,6 +1288,37 @@ async fn root_search_aux(
         searcher_context,
     )?;
 
+    // A page that is not full is the last one. Scroll requests paginate with their scroll ID.
+    let next_page_token = if scroll_key_and_start_offset_opt.is_none()
+        && search_request.max_hits > 0
+        && hits.len() as u64 == search_request.max_hits
+    {
+        hits.last()
+            .and_then(|hit| hit.partial_hit.as_ref())
+            .map(encode_page_token)
+    } else {
+        None
+    };
+
+    let mut split_stats = first_phase_result.split_stats;
+    split_stats.sort_by(|left, right| {
+        right
+            .search_duration_micros
+            .cmp(&left.search_duration_micros)
+    });
+    let stats = SearchStats {
+        num_attempted_splits: first_phase_result.num_attempted_splits,
+        split_stats,
+    };
+    // Splits whose search was interrupted only counted part of their matching documents.
+    let num_hits_accuracy = if first_phase_result.partial_splits.is_empty() {
+        HitCountAccuracy::Exact
+    } else {
+        HitCountAccuracy::LowerBound
+    };
+    let timed_out = search_deadlines_opt.is_some()
+        && (fetch_docs_timed_out || !first_phase_result.partial_splits.is_empty());
+
     Ok(SearchResponse {
         aggregation: aggregation_result_json_opt,
         num_hits: first_phase_result.num_hits,
@@ -777,6 +1328,13 @@ async fn root_search_aux(
         scroll_id: scroll_key_and_start_offset_opt
             .as_ref()
             .map(ToString::to_string),
+        failed_splits: first_phase_result.failed_splits,
+        next_page_token,
+        stats: Some(stats),
+        failed_indexes: Vec::new(),
+        num_hits_accuracy: num_hits_accuracy as i32,
+        timed_out,
+        warnings: Vec::new(),
     })
 }
 
@@ -862,6 +1420,235 @@ pub fn check_all_index_metadata_found(
     Ok(())
 }
 
+/// The splits targeted by a root search request, along with the metadata of their indexes.
+struct RootSearchPlan {
+    indexes_metas_for_leaf_search: IndexesMetasForLeafSearch,
+    split_metadatas: Vec<SplitMetadata>,
+    /// Indexes that failed validation or whose splits could not be listed.
+    failed_indexes: Vec<IndexSearchError>,
+    /// Indexes whose splits were listed from the metastore fallback cache.
+    warnings: Vec<SearchWarning>,
+    /// Splits that do not overlap the time range of the request. Only listed when requested.
+    time_pruned_split_metadatas: Vec<SplitMetadata>,
+}
+
+/// Validates the search request against the targeted indexes, resolves its query, and lists the
+/// splits to search.
+///
+/// If `list_time_pruned_splits` is true, the splits are listed regardless of the time range of the
+/// request, and the ones that do not overlap it are returned apart instead of being dropped.
+async fn plan_root_search(
+    searcher_context: &SearcherContext,
+    indexes_metadata: &[IndexMetadata],
+    search_request: &mut SearchRequest,
+    metastore: &mut MetastoreServiceClient,
+    list_time_pruned_splits: bool,
+) -> crate::Result<RootSearchPlan> {
+    let synonym_tables =
+        load_synonym_tables(searcher_context, indexes_metadata, search_request).await?;
+    let (
+        timestamp_field_opt,
+        query_ast_resolved,
+        indexes_metas_for_leaf_search,
+        mut failed_indexes,
+    ) = validate_request_and_build_metadatas(indexes_metadata, search_request, &synonym_tables)?;
+    // The indexes that failed validation are not searched.
+    let index_uids = indexes_metadata
+        .iter()
+        .map(|index_metadata| index_metadata.index_uid.clone())
+        .filter(|index_uid| indexes_metas_for_leaf_search.contains_key(index_uid))
+        .collect_vec();
+    search_request.query_ast = serde_json::to_string(&query_ast_resolved)?;
+
+    // convert search_after datetime values from input datetime format to nanos.
+    convert_search_after_datetime_values(search_request)?;
+
+    // update_search_after_datetime_in_nanos(&mut search_request)?;
+    if let Some(timestamp_field) = &timestamp_field_opt {
+        refine_start_end_timestamp_from_ast(
+            &query_ast_resolved,
+            timestamp_field,
+            &mut search_request.start_timestamp,
+            &mut search_request.end_timestamp,
+        );
+    }
+    // The tags are extracted from the query AST run on each index, which is expanded with the
+    // synonyms of the index if it has any.
+    let mut index_uids_and_tag_filters: Vec<(IndexUid, Option<TagFilterAst>)> =
+        Vec::with_capacity(index_uids.len());
+    for index_uid in index_uids {
+        let query_ast_opt = indexes_metas_for_leaf_search
+            .get(&index_uid)
+            .and_then(|index_metas| index_metas.query_ast_opt.as_ref());
+        let tag_filter_ast = if let Some(query_ast) = query_ast_opt {
+            extract_tags_from_query(serde_json::from_str(query_ast)?)
+        } else {
+            extract_tags_from_query(query_ast_resolved.clone())
+        };
+        index_uids_and_tag_filters.push((index_uid, tag_filter_ast));
+    }
+
+    // TODO if search after is set, we sort by timestamp and we don't want to count all results,
+    // we can refine more here. Same if we sort by _shard_doc
+    let mut warnings = Vec::new();
+    let (list_start_timestamp, list_end_timestamp) = if list_time_pruned_splits {
+        (None, None)
+    } else {
+        (search_request.start_timestamp, search_request.end_timestamp)
+    };
+    let mut split_metadatas: Vec<SplitMetadata> =
+        if let Some(snapshot_id) = &search_request.snapshot_id {
+            let [(index_uid, tag_filter_ast)]: [(IndexUid, Option<TagFilterAst>); 1] =
+                index_uids_and_tag_filters.try_into().map_err(|_| {
+                    SearchError::InvalidArgument(
+                        "searching a snapshot requires targeting exactly one index".to_string(),
+                    )
+                })?;
+            list_relevant_snapshot_splits(
+                index_uid,
+                snapshot_id,
+                list_start_timestamp,
+                list_end_timestamp,
+                tag_filter_ast,
+                metastore,
+            )
+            .await?
+        } else {
+            let (split_metadatas, list_splits_failed_indexes, list_splits_warnings) =
+                list_relevant_splits_per_index(
+                    searcher_context,
+                    index_uids_and_tag_filters,
+                    list_start_timestamp,
+                    list_end_timestamp,
+                    metastore,
+                )
+                .await?;
+            failed_indexes.extend(list_splits_failed_indexes);
+            warnings.extend(list_splits_warnings);
+            split_metadatas
+        };
+    let mut time_pruned_split_metadatas = Vec::new();
+
+    if list_time_pruned_splits {
+        (split_metadatas, time_pruned_split_metadatas) =
+            split_metadatas.into_iter().partition(|split_metadata| {
+                split_matches(
+                    split_metadata,
+                    search_request.start_timestamp,
+                    search_request.end_timestamp,
+                    &None,
+                )
+            });
+    }
+    if !search_request.tags.is_empty() {
+        split_metadatas.retain(|split_metadata| {
+            search_request
+                .tags
+                .iter()
+                .any(|tag| split_metadata.tags.contains(tag))
+        });
+    }
+    if !search_request.geo_filters.is_empty() {
+        let geo_filter_bounding_boxes =
+            parse_geo_filter_bounding_boxes(&search_request.geo_filters)?;
+        split_metadatas.retain(|split_metadata| {
+            split_may_match_geo_filters(split_metadata, &geo_filter_bounding_boxes)
+        });
+    }
+    Ok(RootSearchPlan {
+        indexes_metas_for_leaf_search,
+        split_metadatas,
+        failed_indexes,
+        warnings,
+        time_pruned_split_metadatas,
+    })
+}
+
+/// Lists the metadata of the indexes matching the patterns.
+///
+/// If the metastore is unreachable, the metadata last fetched for the same patterns is returned
+/// instead, along with its age, as long as it is not older than the max staleness configured for
+/// the searcher.
+async fn list_indexes_metadata(
+    searcher_context: &SearcherContext,
+    index_id_patterns: &[String],
+    metastore: &mut MetastoreServiceClient,
+) -> crate::Result<(Vec<IndexMetadata>, Option<Duration>)> {
+    let list_indexes_metadatas_request = ListIndexesMetadataRequest {
+        index_id_patterns: index_id_patterns.to_vec(),
+    };
+    let list_indexes_metadata_result: crate::Result<Vec<IndexMetadata>> = async {
+        let indexes_metadata = metastore
+            .list_indexes_metadata(list_indexes_metadatas_request)
+            .await?
+            .deserialize_indexes_metadata()?;
+        Ok(indexes_metadata)
+    }
+    .await;
+    let metastore_fallback_cache = &searcher_context.metastore_fallback_cache;
+
+    match list_indexes_metadata_result {
+        Ok(indexes_metadata) => {
+            metastore_fallback_cache.put_indexes_metadata(index_id_patterns, &indexes_metadata);
+            Ok((indexes_metadata, None))
+        }
+        Err(search_error) if is_transient_metastore_error(&search_error) => {
+            let Some((indexes_metadata, age)) =
+                metastore_fallback_cache.get_indexes_metadata(index_id_patterns)
+            else {
+                return Err(search_error);
+            };
+            warn!(
+                index_id_patterns=?index_id_patterns,
+                age=?age,
+                error=%search_error,
+                "failed to list indexes metadata, using the metadata last fetched from the \
+                 metastore"
+            );
+            Ok((indexes_metadata, Some(age)))
+        }
+        Err(search_error) => Err(search_error),
+    }
+}
+
+fn stale_metastore_warning(index_id: &str, age: Duration) -> SearchWarning {
+    SearchWarning {
+        code: "stale_metastore".to_string(),
+        index_id: index_id.to_string(),
+        message: format!(
+            "the metastore is unreachable, the index was searched with the splits listed {}s ago \
+             and may miss the most recent documents",
+            age.as_secs()
+        ),
+        metastore_data_age_secs: Some(age.as_secs()),
+    }
+}
+
+/// Adds a `stale_metastore` warning for the indexes whose metadata was served from the metastore
+/// fallback cache, unless their splits already have one, and counts the indexes searched with
+/// stale metastore data.
+fn record_stale_metastore_warnings(
+    indexes_metadata: &[IndexMetadata],
+    indexes_metadata_age_opt: Option<Duration>,
+    warnings: &mut Vec<SearchWarning>,
+) {
+    let Some(indexes_metadata_age) = indexes_metadata_age_opt else {
+        return;
+    };
+    for index_metadata in indexes_metadata {
+        let index_id = index_metadata.index_id();
+        if !warnings.iter().any(|warning| warning.index_id == index_id) {
+            warnings.push(stale_metastore_warning(index_id, indexes_metadata_age));
+        }
+    }
+    for warning in warnings.iter() {
+        SEARCH_METRICS
+            .root_search_stale_metastore_total
+            .with_label_values([warning.index_id.as_str()])
+            .inc();
+    }
+}
+
 /// Performs a distributed search.
 /// 1. Sends leaf request over gRPC to multiple leaf nodes.
 /// 2. Merges the search results.
@@ -875,14 +1662,16 @@ pub async fn root_search(
     cluster_client: &ClusterClient,
 ) -> crate::Result<SearchResponse> {
     info!(searcher_context = ?searcher_context, search_request = ?search_request);
-    let start_instant = tokio::time::Instant::now();
-    let list_indexes_metadatas_request = ListIndexesMetadataRequest {
-        index_id_patterns: search_request.index_id_patterns.clone(),
-    };
-    let indexes_metadata: Vec<IndexMetadata> = metastore
-        .list_indexes_metadata(list_indexes_metadatas_request)
-        .await?
-        .deserialize_indexes_metadata()?;
+    let start_instant = Instant::now();
+    // The clock starts before planning, so listing the indexes and splits counts toward the
+    // search timeout.
+    let search_deadlines_opt = SearchDeadlines::for_search_request(start_instant, &search_request);
+    let (indexes_metadata, indexes_metadata_age_opt) = list_indexes_metadata(
+        searcher_context,
+        &search_request.index_id_patterns,
+        &mut metastore,
+    )
+    .await?;
 
     check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
 
@@ -896,161 +1685,556 @@ pub async fn root_search(
             search_request,
             Vec::new(),
             cluster_client,
+            search_deadlines_opt,
         )
         .await?;
         search_response.elapsed_time_micros = start_instant.elapsed().as_micros() as u64;
         return Ok(search_response);
     }
 
-    let index_uids = indexes_metadata
-        .iter()
-        .map(|index_metadata| index_metadata.index_uid.clone())
-        .collect_vec();
-    let (timestamp_field_opt, query_ast_resolved, indexes_metas_for_leaf_search) =
-        validate_request_and_build_metadatas(&indexes_metadata, &search_request)?;
-    search_request.query_ast = serde_json::to_string(&query_ast_resolved)?;
-
-    // convert search_after datetime values from input datetime format to nanos.
-    convert_search_after_datetime_values(&mut search_request)?;
-
-    // update_search_after_datetime_in_nanos(&mut search_request)?;
-    if let Some(timestamp_field) = &timestamp_field_opt {
-        refine_start_end_timestamp_from_ast(
-            &query_ast_resolved,
-            timestamp_field,
-            &mut search_request.start_timestamp,
-            &mut search_request.end_timestamp,
-        );
-    }
-    let tag_filter_ast = extract_tags_from_query(query_ast_resolved);
-
-    // TODO if search after is set, we sort by timestamp and we don't want to count all results,
-    // we can refine more here. Same if we sort by _shard_doc
-    let split_metadatas: Vec<SplitMetadata> = list_relevant_splits(
-        index_uids,
-        search_request.start_timestamp,
-        search_request.end_timestamp,
-        tag_filter_ast,
+    let RootSearchPlan {
+        indexes_metas_for_leaf_search,
+        split_metadatas,
+        failed_indexes,
+        mut warnings,
+        ..
+    } = plan_root_search(
+        searcher_context,
+        &indexes_metadata,
+        &mut search_request,
         &mut metastore,
+        false,
     )
     .await?;
+    record_stale_metastore_warnings(&indexes_metadata, indexes_metadata_age_opt, &mut warnings);
+
+    // Scroll requests must create a new scroll context, so they are never served from cache.
+    let search_response_cache_and_key_opt = searcher_context
+        .search_response_cache_opt
+        .as_ref()
+        .filter(|_| search_request.scroll_ttl_secs.is_none())
+        .map(|search_response_cache| {
+            let cache_key = SearchResponseCacheKey::new(&search_request, &split_metadatas);
+            (search_response_cache, cache_key)
+        });
 
+    if let Some((search_response_cache, cache_key)) = &search_response_cache_and_key_opt {
+        if let Some(mut search_response) = search_response_cache.get(cache_key) {
+            search_response.elapsed_time_micros = start_instant.elapsed().as_micros() as u64;
+            // No split was searched to answer this request.
+            search_response.stats = None;
+            if !warnings.is_empty() {
+                search_response.num_hits_accuracy = HitCountAccuracy::LowerBound as i32;
+                search_response.warnings = warnings;
+            }
+            return Ok(search_response);
+        }
+    }
     let mut search_response = root_search_aux(
         searcher_context,
         &indexes_metas_for_leaf_search,
         search_request,
         split_metadatas,
         cluster_client,
+        search_deadlines_opt,
     )
     .await?;
 
+    if let Some((search_response_cache, cache_key)) = search_response_cache_and_key_opt {
+        // Partial responses are not cached so that failed splits and indexes get a chance to be
+        // retried, and interrupted splits a chance to be searched entirely.
+        if search_response.failed_splits.is_empty()
+            && failed_indexes.is_empty()
+            && search_response.num_hits_accuracy == HitCountAccuracy::Exact as i32
+            && !search_response.timed_out
+        {
+            search_response_cache.put(cache_key, search_response.clone());
+        }
+    }
+    if !failed_indexes.is_empty() {
+        search_response.num_hits_accuracy = HitCountAccuracy::LowerBound as i32;
+        search_response.failed_indexes = failed_indexes;
+    }
+    // Splits created since the metastore became unreachable are not searched.
+    if !warnings.is_empty() {
+        search_response.num_hits_accuracy = HitCountAccuracy::LowerBound as i32;
+        search_response.warnings = warnings;
+    }
     search_response.elapsed_time_micros = start_instant.elapsed().as_micros() as u64;
     Ok(search_response)
 }
 
-/// Converts search after with datetime format to nanoseconds (representation in tantivy).
-fn convert_search_after_datetime_values(search_request: &mut SearchRequest) -> crate::Result<()> {
-    if let Some(partial_hit) = search_request.search_after.as_mut() {
-        let search_after_values = [
-            partial_hit.sort_value.as_mut(),
-            partial_hit.sort_value2.as_mut(),
-        ];
-        for (sort_field, search_after_value_opt) in
-            search_request.sort_fields.iter().zip(search_after_values)
-        {
-            let Some(search_after_sort_by_value) = search_after_value_opt else {
-                continue;
-            };
-            let Some(search_after_sort_value) = search_after_sort_by_value.sort_value.as_mut()
-            else {
-                continue;
-            };
-            let Some(datetime_format_int) = sort_field.sort_datetime_format else {
-                continue;
-            };
-            let input_datetime_format = SortDatetimeFormat::from_i32(datetime_format_int)
-                .context("invalid sort datetime format")?;
-            convert_sort_datetime_value_into_nanos(search_after_sort_value, input_datetime_format)?;
-        }
+/// Number of top hits reported for each split by [`root_explain_search`].
+const EXPLAIN_NUM_TOP_HITS_PER_SPLIT: u64 = 5;
+
+/// Maximum number of leaf requests sent concurrently by [`root_explain_search`], which sends one
+/// request per split.
+const EXPLAIN_MAX_CONCURRENT_LEAF_REQUESTS: usize = 32;
+
+/// Explains how a search request is executed, split by split.
+///
+/// The splits are selected as in [`root_search`], then each of them is searched on its own so that
+/// the number of matching documents and the top hits, along with their raw sort values, can be
+/// reported per split. The splits of the targeted indexes that do not overlap the requested time
+/// range are reported as pruned. Aggregations, scroll and pagination parameters are ignored.
+#[instrument(skip_all)]
+pub async fn root_explain_search(
+    searcher_context: &SearcherContext,
+    mut search_request: SearchRequest,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<ExplainResult> {
+    info!(searcher_context = ?searcher_context, search_request = ?search_request);
+    let (indexes_metadata, _indexes_metadata_age_opt) = list_indexes_metadata(
+        searcher_context,
+        &search_request.index_id_patterns,
+        &mut metastore,
+    )
+    .await?;
+
+    check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
+
+    if indexes_metadata.is_empty() {
+        return Ok(ExplainResult::default());
     }
-    Ok(())
-}
+    search_request.aggregation_request = None;
+    search_request.scroll_ttl_secs = None;
+    search_request.search_after = None;
+    search_request.start_offset = 0;
+    search_request.max_hits = EXPLAIN_NUM_TOP_HITS_PER_SPLIT;
 
-/// Convert sort values from input datetime format into nanoseconds.
-/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
-fn convert_sort_datetime_value_into_nanos(
-    sort_value: &mut SortValue,
-    input_format: SortDatetimeFormat,
-) -> crate::Result<()> {
-    match sort_value {
-        SortValue::U64(value) => match input_format {
-            SortDatetimeFormat::UnixTimestampMillis => {
-                *value = value.checked_mul(1_000_000).ok_or_else(|| {
-                    SearchError::Internal(format!(
-                        "sort value defined in milliseconds is too large and cannot be converted \
-                         into nanoseconds: {}",
-                        value
-                    ))
-                })?;
-            }
-        },
-        SortValue::I64(value) => match input_format {
-            SortDatetimeFormat::UnixTimestampMillis => {
-                *value = value.checked_mul(1_000_000).ok_or_else(|| {
-                    SearchError::Internal(format!(
-                        "sort value defined in milliseconds is too large and cannot be converted \
-                         into nanoseconds: {}",
-                        value
-                    ))
-                })?;
+    let RootSearchPlan {
+        indexes_metas_for_leaf_search,
+        split_metadatas,
+        time_pruned_split_metadatas,
+        ..
+    } = plan_root_search(
+        searcher_context,
+        &indexes_metadata,
+        &mut search_request,
+        &mut metastore,
+        true,
+    )
+    .await?;
+
+    let jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
+    let assigned_leaf_search_jobs = cluster_client
+        .search_job_placer
+        .assign_jobs(jobs, &HashSet::default())
+        .await?;
+    let mut split_explanation_futures = Vec::new();
+    for (client, client_jobs) in assigned_leaf_search_jobs {
+        for job in client_jobs {
+            let index_id = job.index_uid.index_id().to_string();
+            let split_id = job.offsets.split_id.clone();
+            // Searching the splits one by one gives us per-split hit counts and top hits.
+            let leaf_requests =
+                jobs_to_leaf_requests(&search_request, &indexes_metas_for_leaf_search, vec![job])?;
+            for mut leaf_request in leaf_requests {
+                let timeout_opt = compute_leaf_search_timeout(
+                    searcher_context
+                        .searcher_config
+                        .leaf_search_timeout
+                        .as_ref(),
+                    &leaf_request.split_offsets,
+                );
+                leaf_request.timeout_millis = timeout_opt.map(|timeout| timeout.as_millis() as u64);
+                let client = client.clone();
+                let index_id = index_id.clone();
+                let split_id = split_id.clone();
+                split_explanation_futures.push(async move {
+                    let searcher_addr = client.grpc_addr().to_string();
+                    let leaf_search_result = cluster_client.leaf_search(leaf_request, client).await;
+                    explain_split(index_id, split_id, searcher_addr, leaf_search_result)
+                });
             }
-        },
-        _ => {
-            return Err(SearchError::Internal(format!(
-                "datetime conversion are only support for u64 and i64 sort values, not `{:?}`",
-                sort_value
-            )));
         }
     }
-    Ok(())
+    let mut split_explanations: Vec<SplitExplanation> = stream::iter(split_explanation_futures)
+        .buffer_unordered(EXPLAIN_MAX_CONCURRENT_LEAF_REQUESTS)
+        .collect()
+        .await;
+
+    let pruned_split_explanations = time_pruned_split_metadatas
+        .into_iter()
+        .map(|split_metadata| SplitExplanation {
+            index_id: split_metadata.index_uid.index_id().to_string(),
+            split_id: split_metadata.split_id,
+            pruned_by_time_range: true,
+            ..Default::default()
+        });
+    split_explanations.extend(pruned_split_explanations);
+    split_explanations.sort_unstable_by(|left, right| {
+        (&left.index_id, &left.split_id).cmp(&(&right.index_id, &right.split_id))
+    });
+    Ok(ExplainResult {
+        splits: split_explanations,
+    })
 }
 
-/// Convert sort values from nanoseconds to the requested output format.
-/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
-fn convert_sort_datetime_value(
-    sort_value: &mut SortValue,
-    output_format: SortDatetimeFormat,
-) -> crate::Result<()> {
-    match sort_value {
-        SortValue::U64(value) => match output_format {
-            SortDatetimeFormat::UnixTimestampMillis => {
-                *value /= 1_000_000;
+/// Builds the explanation of a split from the response of the leaf that searched it.
+fn explain_split(
+    index_id: IndexId,
+    split_id: SplitId,
+    searcher_addr: String,
+    leaf_search_result: crate::Result<LeafSearchResponse>,
+) -> SplitExplanation {
+    let mut split_explanation = SplitExplanation {
+        split_id,
+        index_id,
+        searcher_addr,
+        ..Default::default()
+    };
+    match leaf_search_result {
+        Ok(leaf_search_response) => {
+            // The split may have been searched by another searcher after a retry.
+            if let Some(split_stats) = leaf_search_response.split_stats.first() {
+                split_explanation.searcher_addr = split_stats.searcher_addr.clone();
             }
-        },
-        SortValue::I64(value) => match output_format {
-            SortDatetimeFormat::UnixTimestampMillis => {
-                *value /= 1_000_000;
+            if let Some(split_search_error) = leaf_search_response.failed_splits.first() {
+                split_explanation.error = Some(split_search_error.error.clone());
             }
-        },
-        _ => {
-            return Err(SearchError::Internal(format!(
-                "datetime conversion are only support for u64 and i64 sort values, not `{:?}`",
-                sort_value
-            )));
+            split_explanation.num_candidate_docs = leaf_search_response.num_hits;
+            split_explanation.top_hits = leaf_search_response.partial_hits;
+        }
+        Err(search_error) => {
+            split_explanation.error = Some(search_error.to_string());
         }
     }
-    Ok(())
+    split_explanation
 }
 
-pub(crate) fn refine_start_end_timestamp_from_ast(
-    query_ast: &QueryAst,
-    timestamp_field: &str,
-    start_timestamp: &mut Option<i64>,
-    end_timestamp: &mut Option<i64>,
-) {
-    let mut timestamp_range_extractor = ExtractTimestampRange {
-        timestamp_field,
-        start_timestamp: *start_timestamp,
+/// Performs a distributed search and streams the hits.
+///
+/// The hits are the same, and in the same order, as the ones of [`root_search`]. However, they are
+/// yielded as the fetch docs responses of the leaf nodes come back instead of being collected in
+/// memory first. Scroll and aggregation requests are not supported, and the responses are not
+/// cached.
+///
+/// If some indexes or splits could not be searched, the stream yields the hits found in the others
+/// and ends with an error, so that consumers cannot mistake incomplete results for complete ones.
+#[instrument(skip_all)]
+pub async fn root_search_hits_stream(
+    searcher_context: &SearcherContext,
+    mut search_request: SearchRequest,
+    mut metastore: MetastoreServiceClient,
+    cluster_client: &ClusterClient,
+) -> crate::Result<BoxStream<'static, crate::Result<Hit>>> {
+    info!(searcher_context = ?searcher_context, search_request = ?search_request);
+    if search_request.scroll_ttl_secs.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "scroll is not supported by streamed searches".to_string(),
+        ));
+    }
+    if search_request.aggregation_request.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "aggregations are not supported by streamed searches".to_string(),
+        ));
+    }
+    let search_deadlines_opt = SearchDeadlines::for_search_request(Instant::now(), &search_request);
+    let (indexes_metadata, indexes_metadata_age_opt) = list_indexes_metadata(
+        searcher_context,
+        &search_request.index_id_patterns,
+        &mut metastore,
+    )
+    .await?;
+
+    check_all_index_metadata_found(&indexes_metadata[..], &search_request.index_id_patterns[..])?;
+
+    if indexes_metadata.is_empty() {
+        return Ok(stream::empty().boxed());
+    }
+    let RootSearchPlan {
+        indexes_metas_for_leaf_search,
+        split_metadatas,
+        failed_indexes,
+        mut warnings,
+        ..
+    } = plan_root_search(
+        searcher_context,
+        &indexes_metadata,
+        &mut search_request,
+        &mut metastore,
+        false,
+    )
+    .await?;
+    record_stale_metastore_warnings(&indexes_metadata, indexes_metadata_age_opt, &mut warnings);
+    if !warnings.is_empty() {
+        warn!(warnings=?warnings, "some indexes were searched with stale metastore data");
+    }
+    let first_phase_result = search_partial_hits_phase(
+        searcher_context,
+        &indexes_metas_for_leaf_search,
+        &search_request,
+        &split_metadatas[..],
+        cluster_client,
+        search_deadlines_opt.map(|search_deadlines| search_deadlines.leaf_search),
+    )
+    .await?;
+
+    let incomplete_search_error_opt =
+        incomplete_search_error(&failed_indexes, &first_phase_result.failed_splits);
+    let hits_stream = fetch_docs_phase_stream(
+        &indexes_metas_for_leaf_search,
+        &first_phase_result.partial_hits,
+        &split_metadatas[..],
+        &search_request,
+        cluster_client,
+    )
+    .await?;
+    let Some(incomplete_search_error) = incomplete_search_error_opt else {
+        return Ok(hits_stream);
+    };
+    warn!(error=%incomplete_search_error, "streamed search is incomplete");
+    let hits_stream = hits_stream
+        .chain(stream::once(async move { Err(incomplete_search_error) }))
+        .boxed();
+    Ok(hits_stream)
+}
+
+/// Returns the error ending the stream of hits of a search that could not search some indexes or
+/// splits, if any.
+fn incomplete_search_error(
+    failed_indexes: &[IndexSearchError],
+    failed_splits: &[SplitSearchError],
+) -> Option<SearchError> {
+    if failed_indexes.is_empty() && failed_splits.is_empty() {
+        return None;
+    }
+    let failed_index_ids = failed_indexes
+        .iter()
+        .map(|failed_index| failed_index.index_id.as_str())
+        .join(", ");
+    let error_message = format!(
+        "search is incomplete: {} index(es) could not be searched ({failed_index_ids}) and {} \
+         split(s) failed",
+        failed_indexes.len(),
+        failed_splits.len(),
+    );
+    Some(SearchError::Internal(error_message))
+}
+
+/// Lists the splits relevant for the request of each index concurrently, pruning them with the
+/// tag filter of the index. At most `max_num_concurrent_list_splits` indexes are listed at a time.
+///
+/// Indexes whose splits cannot be listed within the `list_splits_timeout` of the searcher are
+/// returned as failed indexes, unless all of them fail, in which case the error of the first index
+/// is returned. If the metastore is unreachable, the splits last listed for an index are used
+/// instead, as long as they are not older than the max staleness configured for the searcher, and
+/// the index gets a `stale_metastore` warning.
+async fn list_relevant_splits_per_index(
+    searcher_context: &SearcherContext,
+    index_uids_and_tag_filters: Vec<(IndexUid, Option<TagFilterAst>)>,
+    start_timestamp: Option<i64>,
+    end_timestamp: Option<i64>,
+    metastore: &MetastoreServiceClient,
+) -> crate::Result<(
+    Vec<SplitMetadata>,
+    Vec<IndexSearchError>,
+    Vec<SearchWarning>,
+)> {
+    let num_indexes = index_uids_and_tag_filters.len();
+    let list_splits_timeout = searcher_context.searcher_config.list_splits_timeout();
+    let max_num_concurrent_list_splits = searcher_context
+        .searcher_config
+        .max_num_concurrent_list_splits
+        .get();
+    let list_splits_futures =
+        index_uids_and_tag_filters
+            .into_iter()
+            .map(|(index_uid, tag_filter_ast)| {
+                let mut metastore = metastore.clone();
+                async move {
+                    let list_splits_result = tokio::time::timeout(
+                        list_splits_timeout,
+                        list_relevant_splits(
+                            vec![index_uid.clone()],
+                            start_timestamp,
+                            end_timestamp,
+                            tag_filter_ast.clone(),
+                            &mut metastore,
+                        ),
+                    )
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(SearchError::Timeout(format!(
+                            "listing the splits of index `{}` took more than {:?}",
+                            index_uid.index_id(),
+                            list_splits_timeout
+                        )))
+                    });
+                    (index_uid, tag_filter_ast, list_splits_result)
+                }
+            });
+    let metastore_fallback_cache = &searcher_context.metastore_fallback_cache;
+    let mut split_metadatas: Vec<SplitMetadata> = Vec::new();
+    let mut failed_indexes: Vec<IndexSearchError> = Vec::new();
+    let mut warnings: Vec<SearchWarning> = Vec::new();
+    let mut first_error_opt: Option<SearchError> = None;
+
+    let list_splits_results: Vec<_> = stream::iter(list_splits_futures)
+        .buffered(max_num_concurrent_list_splits)
+        .collect()
+        .await;
+
+    for (index_uid, tag_filter_ast, list_splits_result) in list_splits_results {
+        if let Err(search_error) = &list_splits_result {
+            let stale_split_metadatas_opt = if is_transient_metastore_error(search_error) {
+                metastore_fallback_cache.get_splits(
+                    &index_uid,
+                    start_timestamp,
+                    end_timestamp,
+                    &tag_filter_ast,
+                )
+            } else {
+                None
+            };
+            if let Some((stale_split_metadatas, age)) = stale_split_metadatas_opt {
+                warn!(
+                    index_id=%index_uid.index_id(),
+                    age=?age,
+                    error=%search_error,
+                    "failed to list splits of index, using the splits last listed from the \
+                     metastore"
+                );
+                warnings.push(stale_metastore_warning(index_uid.index_id(), age));
+                split_metadatas.extend(stale_split_metadatas);
+                continue;
+            }
+        }
+        match list_splits_result {
+            Ok(index_split_metadatas) => {
+                metastore_fallback_cache.put_splits(
+                    &index_uid,
+                    start_timestamp,
+                    end_timestamp,
+                    &tag_filter_ast,
+                    &index_split_metadatas,
+                );
+                split_metadatas.extend(index_split_metadatas);
+            }
+            Err(search_error) => {
+                warn!(
+                    index_id=%index_uid.index_id(),
+                    error=%search_error,
+                    "failed to list splits of index"
+                );
+                failed_indexes.push(index_search_error(index_uid.index_id(), &search_error));
+                first_error_opt.get_or_insert(search_error);
+            }
+        }
+    }
+    if failed_indexes.len() == num_indexes {
+        if let Some(first_error) = first_error_opt {
+            return Err(first_error);
+        }
+    }
+    Ok((split_metadatas, failed_indexes, warnings))
+}
+
+/// Converts search after with datetime format to nanoseconds (representation in tantivy).
+fn convert_search_after_datetime_values(search_request: &mut SearchRequest) -> crate::Result<()> {
+    if let Some(partial_hit) = search_request.search_after.as_mut() {
+        let search_after_values = [
+            partial_hit.sort_value.as_mut(),
+            partial_hit.sort_value2.as_mut(),
+        ];
+        for (sort_field, search_after_value_opt) in
+            search_request.sort_fields.iter().zip(search_after_values)
+        {
+            let Some(search_after_sort_by_value) = search_after_value_opt else {
+                continue;
+            };
+            let Some(search_after_sort_value) = search_after_sort_by_value.sort_value.as_mut()
+            else {
+                continue;
+            };
+            let Some(datetime_format_int) = sort_field.sort_datetime_format else {
+                continue;
+            };
+            let input_datetime_format = SortDatetimeFormat::from_i32(datetime_format_int)
+                .context("invalid sort datetime format")?;
+            convert_sort_datetime_value_into_nanos(search_after_sort_value, input_datetime_format)?;
+        }
+    }
+    Ok(())
+}
+
+/// Convert sort values from input datetime format into nanoseconds.
+/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
+fn convert_sort_datetime_value_into_nanos(
+    sort_value: &mut SortValue,
+    input_format: SortDatetimeFormat,
+) -> crate::Result<()> {
+    match sort_value {
+        SortValue::U64(value) => match input_format {
+            SortDatetimeFormat::UnixTimestampMillis => {
+                *value = value.checked_mul(1_000_000).ok_or_else(|| {
+                    SearchError::Internal(format!(
+                        "sort value defined in milliseconds is too large and cannot be converted \
+                         into nanoseconds: {}",
+                        value
+                    ))
+                })?;
+            }
+        },
+        SortValue::I64(value) => match input_format {
+            SortDatetimeFormat::UnixTimestampMillis => {
+                *value = value.checked_mul(1_000_000).ok_or_else(|| {
+                    SearchError::Internal(format!(
+                        "sort value defined in milliseconds is too large and cannot be converted \
+                         into nanoseconds: {}",
+                        value
+                    ))
+                })?;
+            }
+        },
+        _ => {
+            return Err(SearchError::Internal(format!(
+                "datetime conversion are only support for u64 and i64 sort values, not `{:?}`",
+                sort_value
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Convert sort values from nanoseconds to the requested output format.
+/// The conversion is done only for U64 and I64 sort values, an error is returned for other types.
+fn convert_sort_datetime_value(
+    sort_value: &mut SortValue,
+    output_format: SortDatetimeFormat,
+) -> crate::Result<()> {
+    match sort_value {
+        SortValue::U64(value) => match output_format {
+            SortDatetimeFormat::UnixTimestampMillis => {
+                *value /= 1_000_000;
+            }
+        },
+        SortValue::I64(value) => match output_format {
+            SortDatetimeFormat::UnixTimestampMillis => {
+                *value /= 1_000_000;
+            }
+        },
+        _ => {
+            return Err(SearchError::Internal(format!(
+                "datetime conversion are only support for u64 and i64 sort values, not `{:?}`",
+                sort_value
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn refine_start_end_timestamp_from_ast(
+    query_ast: &QueryAst,
+    timestamp_field: &str,
+    start_timestamp: &mut Option<i64>,
+    end_timestamp: &mut Option<i64>,
+) {
+    let mut timestamp_range_extractor = ExtractTimestampRange {
+        timestamp_field,
+        start_timestamp: *start_timestamp,
         end_timestamp: *end_timestamp,
     };
     timestamp_range_extractor
@@ -1181,7 +2365,7 @@ pub async fn root_list_terms(
     mut metastore: MetastoreServiceClient,
     cluster_client: &ClusterClient,
 ) -> crate::Result<ListTermsResponse> {
-    let start_instant = tokio::time::Instant::now();
+    let start_instant = Instant::now();
     let index_metadata_request =
         IndexMetadataRequest::for_index_id(list_terms_request.index_id.clone());
     let index_metadata = metastore
@@ -1196,6 +2380,13 @@ pub async fn root_list_terms(
             SearchError::Internal(format!("failed to build doc mapper. cause: {err}"))
         })?;
 
+    validate_timestamp_range(
+        &*doc_mapper,
+        &list_terms_request.index_id,
+        list_terms_request.start_timestamp,
+        list_terms_request.end_timestamp,
+    )?;
+
     let schema = doc_mapper.schema();
     let field = schema.get_field(&list_terms_request.field).map_err(|_| {
         SearchError::InvalidQuery(format!(
@@ -1345,10 +2536,18 @@ async fn assign_client_fetch_docs_jobs(
     Ok(assigned_jobs)
 }
 
-// Measure the cost associated to searching in a given split metadata.
-fn compute_split_cost(_split_metadata: &SplitMetadata) -> usize {
-    // TODO: Have a smarter cost, by smoothing the number of docs.
-    1
+/// Computes the time allotted to a leaf to search the given splits, from the sum of their costs.
+/// Returns `None` if leaf search timeouts are not configured.
+pub(crate) fn compute_leaf_search_timeout(
+    leaf_search_timeout_config_opt: Option<&LeafSearchTimeoutConfig>,
+    split_offsets: &[SplitIdAndFooterOffsets],
+) -> Option<Duration> {
+    let leaf_search_timeout_config = leaf_search_timeout_config_opt?;
+    let cost: usize = split_offsets
+        .iter()
+        .map(|split_offsets| compute_split_cost(split_offsets.num_docs))
+        .sum();
+    Some(leaf_search_timeout_config.timeout_for_cost(cost))
 }
 
 /// Builds a list of [`LeafSearchRequest`], one per index, from a list of [`SearchJob`].
@@ -1368,11 +2567,19 @@ pub fn jobs_to_leaf_requests(
                 "received search job for an unknown index {index_uid}. it should never happen"
             ))
         })?;
+        let mut search_request_for_index = search_request_for_leaf.clone();
+
+        if let Some(query_ast) = &search_index_meta.query_ast_opt {
+            search_request_for_index.query_ast = query_ast.clone();
+        }
         let leaf_search_request = LeafSearchRequest {
-            search_request: Some(search_request_for_leaf.clone()),
+            search_request: Some(search_request_for_index),
             split_offsets: job_group.into_iter().map(|job| job.offsets).collect(),
             doc_mapper: search_index_meta.doc_mapper_str.clone(),
             index_uri: search_index_meta.index_uri.to_string(),
+            timeout_millis: None,
+            index_id: index_uid.index_id().to_string(),
+            num_searchers: request.num_searchers,
         };
         leaf_search_requests.push(leaf_search_request);
     }
@@ -1380,13 +2587,18 @@ pub fn jobs_to_leaf_requests(
 }
 
 /// Builds a list of [`FetchDocsRequest`], one per index, from a list of [`FetchDocsJob`].
+///
+/// The jobs assigned to a node span several splits, so a single request carries the partial hits
+/// of all the splits of a given index.
 pub fn jobs_to_fetch_docs_requests(
     snippet_request_opt: Option<SnippetRequest>,
     indexes_metas_for_leaf_search: &IndexesMetasForLeafSearch,
-    jobs: Vec<FetchDocsJob>,
+    mut jobs: Vec<FetchDocsJob>,
 ) -> crate::Result<Vec<FetchDocsRequest>> {
     let mut fetch_docs_requests = Vec::new();
-    // Group jobs by index uid.
+    // Group jobs by index uid. `group_by` only groups consecutive elements and the jobs come
+    // sorted by cost, so we sort them by index uid first.
+    jobs.sort_by(|left, right| left.index_uid.cmp(&right.index_uid));
     for (index_uid, job_group) in &jobs.into_iter().group_by(|job| job.index_uid.clone()) {
         let index_meta = indexes_metas_for_leaf_search
             .get(&index_uid)
@@ -1404,12 +2616,21 @@ pub fn jobs_to_fetch_docs_requests(
             .into_iter()
             .map(|fetch_doc_job| fetch_doc_job.into())
             .collect();
+        let mut snippet_request_for_index_opt = snippet_request_opt.clone();
+
+        if let (Some(snippet_request), Some(query_ast)) = (
+            &mut snippet_request_for_index_opt,
+            &index_meta.query_ast_opt,
+        ) {
+            snippet_request.query_ast_resolved = query_ast.clone();
+        }
         let fetch_docs_req = FetchDocsRequest {
             partial_hits,
             split_offsets,
             index_uri: index_meta.index_uri.to_string(),
-            snippet_request: snippet_request_opt.clone(),
+            snippet_request: snippet_request_for_index_opt,
             doc_mapper: index_meta.doc_mapper_str.clone(),
+            index_id: index_uid.index_id().to_string(),
         };
         fetch_docs_requests.push(fetch_docs_req);
     }
@@ -1418,24 +2639,35 @@ pub fn jobs_to_fetch_docs_requests(
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+    use std::num::NonZeroUsize;
     use std::ops::Range;
     use std::str::FromStr;
     use std::sync::{Arc, RwLock};
 
+    use futures::TryStreamExt;
+    use quickwit_common::geo::GeoPoint;
+    use quickwit_common::retry_budget::RetryBudget;
     use quickwit_common::shared_consts::SCROLL_BATCH_LEN;
-    use quickwit_common::ServiceStream;
-    use quickwit_config::{DocMapping, IndexingSettings, SearchSettings};
+    use quickwit_common::{HumanDuration, ServiceStream};
+    use quickwit_config::{
+        DocMapping, IndexingSettings, SearchResponseCacheLimits, SearchSettings, SearcherConfig,
+        SynonymsConfig,
+    };
     use quickwit_indexing::MockSplitBuilder;
     use quickwit_metastore::{IndexMetadata, ListSplitsResponseExt};
-    use quickwit_proto::metastore::{ListIndexesMetadataResponse, ListSplitsResponse};
+    use quickwit_proto::metastore::{
+        EntityKind, IndexSnapshot, ListIndexSnapshotsResponse, ListIndexesMetadataResponse,
+        ListSplitsResponse, MetastoreError,
+    };
     use quickwit_proto::search::{
-        ScrollRequest, SortByValue, SortOrder, SortValue, SplitSearchError,
+        ScrollRequest, SortByValue, SortOrder, SortValue, SplitSearchError, SplitSearchStats,
     };
     use quickwit_query::query_ast::{qast_helper, qast_json_helper, query_ast_from_user_text};
     use tantivy::schema::{FAST, STORED, TEXT};
 
     use super::*;
-    use crate::{searcher_pool_for_test, MockSearchService};
+    use crate::{decode_page_token, searcher_pool_for_test, MockSearchService};
 
     #[track_caller]
     fn check_snippet_fields_validation(snippet_fields: &[String]) -> anyhow::Result<()> {
@@ -1493,6 +2725,7 @@ mod tests {
         let indexing_settings = IndexingSettings::default();
         let search_settings = SearchSettings {
             default_search_fields: vec!["body".to_string()],
+            synonyms: None,
         };
         IndexMetadata::new(IndexConfig {
             index_id: index_id.to_string(),
@@ -1504,6 +2737,63 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_jobs_to_fetch_docs_requests_groups_jobs_per_index() {
+        let index_uid_1 = IndexUid::from("test-index-1:0");
+        let index_uid_2 = IndexUid::from("test-index-2:0");
+        let indexes_metas_for_leaf_search: IndexesMetasForLeafSearch = [
+            (
+                index_uid_1.clone(),
+                IndexMetasForLeafSearch {
+                    index_uri: Uri::for_test("ram:///test-index-1"),
+                    doc_mapper_str: "doc-mapper-1".to_string(),
+                    query_ast_opt: None,
+                },
+            ),
+            (
+                index_uid_2.clone(),
+                IndexMetasForLeafSearch {
+                    index_uri: Uri::for_test("ram:///test-index-2"),
+                    doc_mapper_str: "doc-mapper-2".to_string(),
+                    query_ast_opt: None,
+                },
+            ),
+        ]
+        .into_iter()
+        .collect();
+        let fetch_docs_job = |index_uid: &IndexUid, split_id: &str| FetchDocsJob {
+            index_uid: index_uid.clone(),
+            offsets: SplitIdAndFooterOffsets {
+                split_id: split_id.to_string(),
+                ..Default::default()
+            },
+            partial_hits: vec![mock_partial_hit(split_id, 1, 1)],
+        };
+        let jobs = vec![
+            fetch_docs_job(&index_uid_1, "split1"),
+            fetch_docs_job(&index_uid_2, "split2"),
+            fetch_docs_job(&index_uid_1, "split3"),
+            fetch_docs_job(&index_uid_2, "split4"),
+        ];
+        let fetch_docs_requests =
+            jobs_to_fetch_docs_requests(None, &indexes_metas_for_leaf_search, jobs).unwrap();
+        assert_eq!(fetch_docs_requests.len(), 2);
+
+        let split_ids = |request: &FetchDocsRequest| -> Vec<String> {
+            request
+                .split_offsets
+                .iter()
+                .map(|split_offsets| split_offsets.split_id.clone())
+                .collect()
+        };
+        assert_eq!(fetch_docs_requests[0].index_uri, "ram:///test-index-1");
+        assert_eq!(split_ids(&fetch_docs_requests[0]), ["split1", "split3"]);
+        assert_eq!(fetch_docs_requests[0].partial_hits.len(), 2);
+        assert_eq!(fetch_docs_requests[1].index_uri, "ram:///test-index-2");
+        assert_eq!(split_ids(&fetch_docs_requests[1]), ["split2", "split4"]);
+        assert_eq!(fetch_docs_requests[1].partial_hits.len(), 2);
+    }
+
     #[test]
     fn test_validate_request_and_build_metadatas_ok() {
         let request_query_ast = qast_helper("body:test", &[]);
@@ -1523,7 +2813,7 @@ mod tests {
             .index_config
             .doc_mapping
             .timestamp_field = None;
-        let (timestamp_field, query_ast, indexes_metas_for_leaf_req) =
+        let (timestamp_field, query_ast, indexes_metas_for_leaf_req, failed_indexes) =
             validate_request_and_build_metadatas(
                 &[
                     index_metadata,
@@ -1531,11 +2821,13 @@ mod tests {
                     index_metadata_no_timestamp,
                 ],
                 &search_request,
+                &SynonymTables::new(),
             )
             .unwrap();
         assert_eq!(timestamp_field, Some("timestamp".to_string()));
         assert_eq!(query_ast, request_query_ast);
         assert_eq!(indexes_metas_for_leaf_req.len(), 3);
+        assert!(failed_indexes.is_empty());
     }
 
     #[test]
@@ -1574,6 +2866,7 @@ mod tests {
         let timestamp_field_different = validate_request_and_build_metadatas(
             &[index_metadata_1, index_metadata_2],
             &search_request,
+            &SynonymTables::new(),
         )
         .unwrap_err();
         assert_eq!(
@@ -1601,6 +2894,7 @@ mod tests {
         let timestamp_field_different = validate_request_and_build_metadatas(
             &[index_metadata_1, index_metadata_2],
             &search_request,
+            &SynonymTables::new(),
         )
         .unwrap_err();
         assert_eq!(
@@ -1611,40 +2905,315 @@ mod tests {
     }
 
     #[test]
-    fn test_convert_sort_datetime_value() {
-        let mut sort_value = SortValue::U64(1617000000000000000);
-        convert_sort_datetime_value(&mut sort_value, SortDatetimeFormat::UnixTimestampMillis)
-            .unwrap();
-        assert_eq!(sort_value, SortValue::U64(1617000000000));
-        let mut sort_value = SortValue::I64(1617000000000000000);
-        convert_sort_datetime_value(&mut sort_value, SortDatetimeFormat::UnixTimestampMillis)
-            .unwrap();
-        assert_eq!(sort_value, SortValue::I64(1617000000000));
-
-        // conversion with float values should fail.
-        let mut sort_value = SortValue::F64(1617000000000000000.0);
-        let error =
-            convert_sort_datetime_value(&mut sort_value, SortDatetimeFormat::UnixTimestampMillis)
-                .unwrap_err();
-        assert_eq!(
-            error.to_string(),
-            "internal error: `datetime conversion are only support for u64 and i64 sort values, \
-             not `F64(1.617e18)``"
-        );
+    fn test_validate_timestamp_range() {
+        // Same doc mapping as the wikipedia tutorial index, which has no timestamp field.
+        let doc_mapping_json = r#"{
+            "field_mappings": [
+                {"name": "title", "type": "text", "tokenizer": "default", "record": "position"},
+                {"name": "body", "type": "text", "tokenizer": "default", "record": "position"},
+                {"name": "url", "type": "text", "indexed": false}
+            ]
+        }"#;
+        let doc_mapping: DocMapping = serde_json::from_str(doc_mapping_json).unwrap();
+        let doc_mapper = build_doc_mapper(&doc_mapping, &SearchSettings::default()).unwrap();
+
+        validate_timestamp_range(&*doc_mapper, "wikipedia", None, None).unwrap();
+        for (start_timestamp, end_timestamp) in [(Some(10), None), (None, Some(20))] {
+            let search_error =
+                validate_timestamp_range(&*doc_mapper, "wikipedia", start_timestamp, end_timestamp)
+                    .unwrap_err();
+            assert!(matches!(search_error, SearchError::InvalidQuery(_)));
+            assert_eq!(
+                search_error.to_string(),
+                "timestamp filtering is not supported for index `wikipedia`: its doc mapping has \
+                 no timestamp field but a start or end timestamp is set in the request"
+            );
+        }
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let doc_mapper = build_doc_mapper(
+            &index_metadata.index_config.doc_mapping,
+            &index_metadata.index_config.search_settings,
+        )
+        .unwrap();
+        validate_timestamp_range(&*doc_mapper, "test-index", Some(10), Some(20)).unwrap();
     }
 
     #[test]
-    fn test_convert_sort_datetime_value_into_nanos() {
-        let mut sort_value = SortValue::U64(1617000000000);
-        convert_sort_datetime_value_into_nanos(
-            &mut sort_value,
-            SortDatetimeFormat::UnixTimestampMillis,
-        )
-        .unwrap();
-        assert_eq!(sort_value, SortValue::U64(1617000000000000000));
-        let mut sort_value = SortValue::I64(1617000000000);
-        convert_sort_datetime_value_into_nanos(
-            &mut sort_value,
+    fn test_validate_request_and_build_metadatas_isolates_failed_indexes() {
+        // The `owner` field only exists in the mapping of the first index.
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index-*".to_string()],
+            query_ast: qast_json_helper("foo", &["owner"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let index_metadata_1 = IndexMetadata::for_test("test-index-1", "ram:///test-index-1");
+        let index_metadata_2 =
+            index_metadata_for_multi_indexes_test("test-index-2", "ram:///test-index-2");
+        let (_, _, indexes_metas_for_leaf_req, failed_indexes) =
+            validate_request_and_build_metadatas(
+                &[index_metadata_1.clone(), index_metadata_2.clone()],
+                &search_request,
+                &SynonymTables::new(),
+            )
+            .unwrap();
+        assert_eq!(indexes_metas_for_leaf_req.len(), 1);
+        assert!(indexes_metas_for_leaf_req.contains_key(&index_metadata_1.index_uid));
+        assert_eq!(failed_indexes.len(), 1);
+        assert_eq!(failed_indexes[0].index_id, "test-index-2");
+        assert_eq!(failed_indexes[0].error_code, "bad_request");
+
+        // All the indexes failed: the error of the first one is returned.
+        let index_metadata_3 =
+            index_metadata_for_multi_indexes_test("test-index-3", "ram:///test-index-3");
+        let search_error = validate_request_and_build_metadatas(
+            &[index_metadata_2, index_metadata_3],
+            &search_request,
+            &SynonymTables::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::InvalidQuery(_)));
+    }
+
+    #[tokio::test]
+    async fn test_validate_request_and_build_metadatas_expands_synonyms() {
+        let mut search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("k8s", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        index_metadata.index_config.search_settings.synonyms = Some(SynonymsConfig {
+            rules: vec!["k8s, kubernetes".to_string()],
+            rules_uri: None,
+            refresh_interval: HumanDuration::from_secs(5 * 60),
+            max_expansions: 10,
+        });
+        let indexes_metadata = [index_metadata];
+        let searcher_context = SearcherContext::for_test();
+
+        let synonym_tables =
+            load_synonym_tables(&searcher_context, &indexes_metadata, &search_request)
+                .await
+                .unwrap();
+        let (_, query_ast, indexes_metas_for_leaf_search, _) =
+            validate_request_and_build_metadatas(
+                &indexes_metadata,
+                &search_request,
+                &synonym_tables,
+            )
+            .unwrap();
+        assert_eq!(query_ast, qast_helper("k8s", &["body"]));
+
+        let expected_query_ast: QueryAst = BoolQuery {
+            should: vec![
+                qast_helper("k8s", &["body"]),
+                qast_helper("kubernetes", &["body"]),
+            ],
+            ..Default::default()
+        }
+        .into();
+        let index_query_ast_json = indexes_metas_for_leaf_search[&indexes_metadata[0].index_uid]
+            .query_ast_opt
+            .as_ref()
+            .unwrap();
+        let index_query_ast: QueryAst = serde_json::from_str(index_query_ast_json).unwrap();
+        assert_eq!(index_query_ast, expected_query_ast);
+
+        search_request.disable_synonyms = true;
+        let synonym_tables =
+            load_synonym_tables(&searcher_context, &indexes_metadata, &search_request)
+                .await
+                .unwrap();
+        assert!(synonym_tables.is_empty());
+        let (_, query_ast, indexes_metas_for_leaf_search, _) =
+            validate_request_and_build_metadatas(
+                &indexes_metadata,
+                &search_request,
+                &synonym_tables,
+            )
+            .unwrap();
+        assert_eq!(query_ast, qast_helper("k8s", &["body"]));
+        assert!(
+            indexes_metas_for_leaf_search[&indexes_metadata[0].index_uid]
+                .query_ast_opt
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_validate_request_and_build_metadatas_with_synonyms_of_one_index() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index-*".to_string()],
+            query_ast: qast_json_helper("k8s", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut index_metadata_1 = IndexMetadata::for_test("test-index-1", "ram:///test-index-1");
+        index_metadata_1.index_config.search_settings.synonyms = Some(SynonymsConfig {
+            rules: vec!["k8s, kubernetes".to_string()],
+            rules_uri: None,
+            refresh_interval: HumanDuration::from_secs(5 * 60),
+            max_expansions: 10,
+        });
+        let index_metadata_2 = IndexMetadata::for_test("test-index-2", "ram:///test-index-2");
+        let indexes_metadata = [index_metadata_1, index_metadata_2];
+        let searcher_context = SearcherContext::for_test();
+
+        let synonym_tables =
+            load_synonym_tables(&searcher_context, &indexes_metadata, &search_request)
+                .await
+                .unwrap();
+        let (_, query_ast, indexes_metas_for_leaf_search, failed_indexes) =
+            validate_request_and_build_metadatas(
+                &indexes_metadata,
+                &search_request,
+                &synonym_tables,
+            )
+            .unwrap();
+        assert!(failed_indexes.is_empty());
+        assert_eq!(query_ast, qast_helper("k8s", &["body"]));
+        assert!(
+            indexes_metas_for_leaf_search[&indexes_metadata[0].index_uid]
+                .query_ast_opt
+                .is_some()
+        );
+        assert!(
+            indexes_metas_for_leaf_search[&indexes_metadata[1].index_uid]
+                .query_ast_opt
+                .is_none()
+        );
+
+        // Each index gets the leaf request carrying its own query AST.
+        let search_request = quickwit_proto::search::SearchRequest {
+            query_ast: serde_json::to_string(&query_ast).unwrap(),
+            ..search_request
+        };
+        let jobs = indexes_metadata
+            .iter()
+            .map(|index_metadata| SearchJob {
+                index_uid: index_metadata.index_uid.clone(),
+                ..SearchJob::for_test(index_metadata.index_id(), 10)
+            })
+            .collect();
+        let leaf_search_requests =
+            jobs_to_leaf_requests(&search_request, &indexes_metas_for_leaf_search, jobs).unwrap();
+        assert_eq!(leaf_search_requests.len(), 2);
+
+        for leaf_search_request in leaf_search_requests {
+            let index_uid = indexes_metadata
+                .iter()
+                .find(|index_metadata| index_metadata.index_id() == leaf_search_request.index_id)
+                .map(|index_metadata| index_metadata.index_uid.clone())
+                .unwrap();
+            let index_query_ast = leaf_search_request.search_request.unwrap().query_ast;
+            let expected_query_ast = indexes_metas_for_leaf_search[&index_uid]
+                .query_ast_opt
+                .clone()
+                .unwrap_or_else(|| search_request.query_ast.clone());
+            assert_eq!(index_query_ast, expected_query_ast);
+        }
+    }
+
+    #[test]
+    fn test_validate_request_and_build_metadatas_applies_fuzzy_distance() {
+        let mut search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("kubernets", &["body"]),
+            max_hits: 10,
+            fuzzy_distance: Some(1),
+            ..Default::default()
+        };
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let indexes_metadata = [index_metadata];
+
+        let (_, query_ast, _, _) = validate_request_and_build_metadatas(
+            &indexes_metadata,
+            &search_request,
+            &SynonymTables::new(),
+        )
+        .unwrap();
+        assert_eq!(
+            query_ast,
+            apply_fuzzy_distance(qast_helper("kubernets", &["body"]), 1)
+        );
+
+        search_request.fuzzy_distance = Some(3);
+        let search_error = validate_request_and_build_metadatas(
+            &indexes_metadata,
+            &search_request,
+            &SynonymTables::new(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            search_error.to_string(),
+            "Invalid argument: max value for fuzzy_distance is 2, but got 3"
+        );
+    }
+
+    #[test]
+    fn test_jobs_to_leaf_requests_forwards_num_searchers() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("kubernetes", &["body"]),
+            max_hits: 10,
+            num_searchers: Some(4),
+            ..Default::default()
+        };
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        let (_, _, indexes_metas_for_leaf_search, _) = validate_request_and_build_metadatas(
+            &[index_metadata],
+            &search_request,
+            &SynonymTables::new(),
+        )
+        .unwrap();
+        let jobs = vec![SearchJob {
+            index_uid,
+            ..SearchJob::for_test("split-1", 10)
+        }];
+        let leaf_search_requests =
+            jobs_to_leaf_requests(&search_request, &indexes_metas_for_leaf_search, jobs).unwrap();
+        assert_eq!(leaf_search_requests.len(), 1);
+        assert_eq!(leaf_search_requests[0].num_searchers, Some(4));
+    }
+
+    #[test]
+    fn test_convert_sort_datetime_value() {
+        let mut sort_value = SortValue::U64(1617000000000000000);
+        convert_sort_datetime_value(&mut sort_value, SortDatetimeFormat::UnixTimestampMillis)
+            .unwrap();
+        assert_eq!(sort_value, SortValue::U64(1617000000000));
+        let mut sort_value = SortValue::I64(1617000000000000000);
+        convert_sort_datetime_value(&mut sort_value, SortDatetimeFormat::UnixTimestampMillis)
+            .unwrap();
+        assert_eq!(sort_value, SortValue::I64(1617000000000));
+
+        // conversion with float values should fail.
+        let mut sort_value = SortValue::F64(1617000000000000000.0);
+        let error =
+            convert_sort_datetime_value(&mut sort_value, SortDatetimeFormat::UnixTimestampMillis)
+                .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "internal error: `datetime conversion are only support for u64 and i64 sort values, \
+             not `F64(1.617e18)``"
+        );
+    }
+
+    #[test]
+    fn test_convert_sort_datetime_value_into_nanos() {
+        let mut sort_value = SortValue::U64(1617000000000);
+        convert_sort_datetime_value_into_nanos(
+            &mut sort_value,
+            SortDatetimeFormat::UnixTimestampMillis,
+        )
+        .unwrap();
+        assert_eq!(sort_value, SortValue::U64(1617000000000000000));
+        let mut sort_value = SortValue::I64(1617000000000);
+        convert_sort_datetime_value_into_nanos(
+            &mut sort_value,
             SortDatetimeFormat::UnixTimestampMillis,
         )
         .unwrap();
@@ -1967,7 +3536,1091 @@ mod tests {
                 })
             },
         );
-        mock_search_service_1.expect_fetch_docs().returning(
+        mock_search_service_1.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", mock_search_service_1),
+            ("127.0.0.1:1002", mock_search_service_2),
+        ]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(mock_metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 5);
+        assert_eq!(search_response.hits.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_leaf_search_timeout() {
+        // 1s per cost unit, with a 5s floor and a 30s ceiling.
+        let leaf_search_timeout_config = LeafSearchTimeoutConfig::default();
+        let split_offsets = |num_docs: u64| SplitIdAndFooterOffsets {
+            num_docs,
+            ..Default::default()
+        };
+        assert_eq!(compute_split_cost(0), 1);
+        assert_eq!(compute_split_cost(999_999), 1);
+        assert_eq!(compute_split_cost(20_000_000), 21);
+
+        // A single tiny split gets the floor.
+        assert_eq!(
+            compute_leaf_search_timeout(Some(&leaf_search_timeout_config), &[split_offsets(1_000)]),
+            Some(Duration::from_secs(5))
+        );
+        // Five tiny splits are not granted more than the floor either.
+        assert_eq!(
+            compute_leaf_search_timeout(
+                Some(&leaf_search_timeout_config),
+                &vec![split_offsets(1_000); 5]
+            ),
+            Some(Duration::from_secs(5))
+        );
+        // A big split gets a budget proportional to its cost.
+        assert_eq!(
+            compute_leaf_search_timeout(
+                Some(&leaf_search_timeout_config),
+                &[split_offsets(20_000_000)]
+            ),
+            Some(Duration::from_secs(21))
+        );
+        assert_eq!(
+            compute_leaf_search_timeout(
+                Some(&leaf_search_timeout_config),
+                &[split_offsets(20_000_000), split_offsets(1_000)]
+            ),
+            Some(Duration::from_secs(22))
+        );
+        // A huge split is capped by the ceiling.
+        assert_eq!(
+            compute_leaf_search_timeout(
+                Some(&leaf_search_timeout_config),
+                &[split_offsets(100_000_000)]
+            ),
+            Some(Duration::from_secs(30))
+        );
+        // Without a configuration, leaf search requests get no timeout of their own.
+        assert_eq!(
+            compute_leaf_search_timeout(None, &[split_offsets(100_000_000)]),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_root_search_sets_leaf_search_timeout() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let mut big_split = MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build();
+                big_split.split_metadata.num_docs = 20_000_000;
+                let small_split = MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build();
+                let splits = vec![big_split, small_split];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                // split1 costs 21 units, split2 costs 1 unit.
+                assert_eq!(leaf_search_req.timeout_millis, Some(22_000));
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_attempted_splits: 2,
+                    ..Default::default()
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_config = SearcherConfig {
+            leaf_search_timeout: Some(LeafSearchTimeoutConfig::default()),
+            ..Default::default()
+        };
+        let search_response = root_search(
+            &SearcherContext::new(searcher_config, None),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 0);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_with_timeout_returns_partial_results() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 0,
+            timeout_millis: Some(5_000),
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let mut big_split = MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build();
+                big_split.split_metadata.num_docs = 20_000_000;
+                let splits = vec![big_split];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                // The cost-based timeout of split1 (21s) is capped by the share of the search
+                // timeout allotted to the leaves (4s), minus the time spent planning the search.
+                let leaf_timeout_millis = leaf_search_req.timeout_millis.unwrap();
+                assert!(leaf_timeout_millis <= 4_000);
+                assert!(leaf_timeout_millis > 3_000);
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 3,
+                    num_attempted_splits: 1,
+                    partial_splits: vec!["split1".to_string()],
+                    ..Default::default()
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_config = SearcherConfig {
+            leaf_search_timeout: Some(LeafSearchTimeoutConfig::default()),
+            ..Default::default()
+        };
+        let search_response = root_search(
+            &SearcherContext::new(searcher_config, None),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 3);
+        assert_eq!(
+            search_response.num_hits_accuracy,
+            HitCountAccuracy::LowerBound as i32
+        );
+        assert!(search_response.timed_out);
+        assert!(search_response.failed_splits.is_empty());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_leaf_search_with_timeout() {
+        let leaf_search_response = leaf_search_with_timeout(
+            futures::future::pending(),
+            vec!["split1".to_string(), "split2".to_string()],
+            Some(Instant::now() + Duration::from_millis(50)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 0);
+        assert_eq!(leaf_search_response.num_attempted_splits, 2);
+        assert_eq!(leaf_search_response.partial_splits, ["split1", "split2"]);
+
+        let leaf_search_response = leaf_search_with_timeout(
+            futures::future::ready(Ok(LeafSearchResponse {
+                num_hits: 1,
+                ..Default::default()
+            })),
+            vec!["split1".to_string()],
+            Some(Instant::now() + Duration::from_secs(5)),
+        )
+        .await
+        .unwrap();
+        assert_eq!(leaf_search_response.num_hits, 1);
+        assert!(leaf_search_response.partial_splits.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_root_search_single_split() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build()];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 3,
+                    partial_hits: vec![
+                        mock_partial_hit("split1", 3, 1),
+                        mock_partial_hit("split1", 2, 2),
+                        mock_partial_hit("split1", 1, 3),
+                    ],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_context = SearcherContext::for_test();
+        let search_response = root_search(
+            &searcher_context,
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 3);
+        assert_eq!(search_response.hits.len(), 3);
+        // The page is not full, so there is no next page.
+        assert!(search_response.next_page_token.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_explain_search() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 100,
+            start_offset: 10,
+            start_timestamp: Some(500),
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        // The splits pruned by the time range are reported without listing the splits again.
+        metastore
+            .expect_list_splits()
+            .times(1)
+            .returning(move |list_splits_request| {
+                let list_splits_query =
+                    list_splits_request.deserialize_list_splits_query().unwrap();
+                let mut split_1 = MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build();
+                split_1.split_metadata.time_range = Some(100..=200);
+                let mut split_2 = MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build();
+                split_2.split_metadata.time_range = Some(1_000..=2_000);
+                let splits: Vec<_> = [split_1, split_2]
+                    .into_iter()
+                    .filter(|split| {
+                        list_splits_query
+                            .time_range
+                            .overlaps_with(split.split_metadata.time_range.clone().unwrap())
+                    })
+                    .collect();
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().times(1).returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let search_req = leaf_search_req.search_request.unwrap();
+                assert_eq!(search_req.max_hits, 5);
+                assert_eq!(search_req.start_offset, 0);
+                assert_eq!(leaf_search_req.split_offsets.len(), 1);
+                assert_eq!(leaf_search_req.split_offsets[0].split_id, "split2");
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 7,
+                    partial_hits: vec![
+                        mock_partial_hit("split2", 3, 1),
+                        mock_partial_hit("split2", 2, 2),
+                    ],
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer);
+
+        let searcher_context = SearcherContext::for_test();
+        let explain_result = root_explain_search(
+            &searcher_context,
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(explain_result.splits.len(), 2);
+
+        let split_1_explanation = &explain_result.splits[0];
+        assert_eq!(split_1_explanation.split_id, "split1");
+        assert_eq!(split_1_explanation.index_id, "test-index");
+        assert!(split_1_explanation.pruned_by_time_range);
+        assert_eq!(split_1_explanation.num_candidate_docs, 0);
+
+        let split_2_explanation = &explain_result.splits[1];
+        assert_eq!(split_2_explanation.split_id, "split2");
+        assert!(!split_2_explanation.pruned_by_time_range);
+        assert_eq!(split_2_explanation.num_candidate_docs, 7);
+        assert_eq!(split_2_explanation.top_hits.len(), 2);
+        assert_eq!(split_2_explanation.searcher_addr, "127.0.0.1:1001");
+        assert!(split_2_explanation.error.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_root_search_next_page_token() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 2,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build()];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 3,
+                    partial_hits: vec![
+                        mock_partial_hit("split1", 3, 1),
+                        mock_partial_hit("split1", 2, 2),
+                        mock_partial_hit("split1", 1, 3),
+                    ],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_context = SearcherContext::for_test();
+        let search_response = root_search(
+            &searcher_context,
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 3);
+        assert_eq!(search_response.hits.len(), 2);
+        let next_page_token = search_response.next_page_token.unwrap();
+        let last_partial_hit = search_response.hits[1].partial_hit.clone().unwrap();
+        assert_eq!(decode_page_token(&next_page_token)?, last_partial_hit);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_snapshot() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            snapshot_id: Some("test-snapshot".to_string()),
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_index_snapshots()
+            .returning(|_list_snapshots_request| {
+                let snapshot = IndexSnapshot {
+                    snapshot_id: "test-snapshot".to_string(),
+                    split_ids: vec!["split1".to_string()],
+                    ..Default::default()
+                };
+                Ok(ListIndexSnapshotsResponse {
+                    snapshots: vec![snapshot],
+                })
+            });
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = vec![
+                    MockSplitBuilder::new("split1")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                    MockSplitBuilder::new("split2")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                // Only the split referenced by the snapshot is searched.
+                assert_eq!(leaf_search_req.split_offsets.len(), 1);
+                assert_eq!(leaf_search_req.split_offsets[0].split_id, "split1");
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 1,
+                    partial_hits: vec![mock_partial_hit("split1", 3, 1)],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+
+        let searcher_context = SearcherContext::for_test();
+        let search_response = root_search(
+            &searcher_context,
+            search_request.clone(),
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 1);
+        assert_eq!(search_response.hits.len(), 1);
+
+        let mut metastore = MetastoreServiceClient::mock();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(|_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    IndexMetadata::for_test("test-index", "ram:///test-index"),
+                ])
+                .unwrap())
+            });
+        metastore
+            .expect_list_index_snapshots()
+            .returning(|_list_snapshots_request| Ok(ListIndexSnapshotsResponse::default()));
+        let search_error = root_search(
+            &searcher_context,
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::InvalidArgument(_)));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_response_cache() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        let split_ids = Arc::new(RwLock::new(vec!["split1"]));
+        let split_ids_clone = split_ids.clone();
+        metastore
+            .expect_list_splits()
+            .returning(move |_list_splits_request| {
+                let splits = split_ids_clone
+                    .read()
+                    .unwrap()
+                    .iter()
+                    .map(|split_id| {
+                        MockSplitBuilder::new(split_id)
+                            .with_index_uid(&index_uid)
+                            .build()
+                    })
+                    .collect();
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().times(2).returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let partial_hits = leaf_search_req
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| mock_partial_hit(&split_offsets.split_id, 1, 1))
+                    .collect();
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: leaf_search_req.split_offsets.len() as u64,
+                    partial_hits,
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: leaf_search_req.split_offsets.len() as u64,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let metastore = MetastoreServiceClient::from(metastore);
+
+        let searcher_config = SearcherConfig {
+            search_response_cache: Some(SearchResponseCacheLimits::default()),
+            ..Default::default()
+        };
+        let searcher_context = SearcherContext::new(searcher_config, None);
+
+        for request_num in 0..2 {
+            // The second request is served from cache.
+            let search_response = root_search(
+                &searcher_context,
+                search_request.clone(),
+                metastore.clone(),
+                &cluster_client,
+            )
+            .await
+            .unwrap();
+            assert_eq!(search_response.num_hits, 1);
+            assert_eq!(search_response.stats.is_some(), request_num == 0);
+        }
+        // Adding a split invalidates the cached response.
+        split_ids.write().unwrap().push("split2");
+
+        let search_response = root_search(
+            &searcher_context,
+            search_request,
+            metastore.clone(),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_with_tags() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            tags: vec!["tenant:acme".to_string(), "region:us-east".to_string()],
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = [
+                ("split1", &["tenant:acme"][..]),
+                ("split2", &["tenant:globex", "region:us-east"][..]),
+                ("split3", &["tenant:globex"][..]),
+                ("split4", &[][..]),
+            ]
+            .into_iter()
+            .map(|(split_id, tags)| {
+                let mut split = MockSplitBuilder::new(split_id)
+                    .with_index_uid(&index_uid)
+                    .build();
+                split.split_metadata.tags = tags.iter().map(|tag| tag.to_string()).collect();
+                split
+            })
+            .collect();
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let split_ids: BTreeSet<&str> = leaf_search_req
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect();
+                assert_eq!(split_ids, BTreeSet::from(["split1", "split2"]));
+                let partial_hits = leaf_search_req
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| mock_partial_hit(&split_offsets.split_id, 1, 1))
+                    .collect();
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: leaf_search_req.split_offsets.len() as u64,
+                    partial_hits,
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: leaf_search_req.split_offsets.len() as u64,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let search_response = root_search(
+            &SearcherContext::new(SearcherConfig::default(), None),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 2);
+        Ok(())
+    }
+
+    fn index_metadata_with_geo_point_field_for_test() -> IndexMetadata {
+        let mut index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        index_metadata
+            .index_config
+            .doc_mapping
+            .field_mappings
+            .push(serde_json::from_str(r#"{"name": "location", "type": "geo_point"}"#).unwrap());
+        index_metadata
+    }
+
+    #[test]
+    fn test_validate_request_geo_filters() {
+        let index_metadata = index_metadata_with_geo_point_field_for_test();
+        let validate_geo_filter = |geo_filter: &str| {
+            let search_request = quickwit_proto::search::SearchRequest {
+                index_id_patterns: vec!["test-index".to_string()],
+                query_ast: qast_json_helper("test", &["body"]),
+                geo_filters: vec![geo_filter.to_string()],
+                ..Default::default()
+            };
+            validate_request_and_build_metadatas(
+                &[index_metadata.clone()],
+                &search_request,
+                &SynonymTables::new(),
+            )
+        };
+        validate_geo_filter(
+            r#"{"geo_distance": {"field": "location", "center": "48.85,2.35", "radius_m": 1000}}"#,
+        )
+        .unwrap();
+        let search_error = validate_geo_filter(
+            r#"{"geo_distance": {"field": "body", "center": "48.85,2.35", "radius_m": 1000}}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::InvalidArgument(_)));
+        let search_error = validate_geo_filter(
+            r#"{"geo_distance": {"field": "location", "center": "48.85,2.35", "radius_m": -1}}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::InvalidArgument(_)));
+        let search_error = validate_geo_filter(
+            r#"{"geo_bbox": {"field": "location", "top_left": "10,0", "bottom_right": "20,1"}}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::InvalidArgument(_)));
+    }
+
+    #[tokio::test]
+    async fn test_root_search_with_geo_filters() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            // Crosses the antimeridian, around Fiji.
+            geo_filters: vec![r#"{"geo_bbox": {
+                "field": "location",
+                "top_left": "-15,177",
+                "bottom_right": "-20,-178"
+            }}"#
+            .to_string()],
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = index_metadata_with_geo_point_field_for_test();
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = [
+                // West of the antimeridian.
+                ("split1", Some(((-16.0, 178.0), (-18.0, 179.0)))),
+                // East of the antimeridian.
+                ("split2", Some(((-16.0, -179.5), (-17.0, -179.0)))),
+                // Paris.
+                ("split3", Some(((48.9, 2.2), (48.8, 2.4)))),
+                // Created before geo bounding boxes were recorded.
+                ("split4", None),
+            ]
+            .into_iter()
+            .map(|(split_id, bounding_box_opt)| {
+                let mut split = MockSplitBuilder::new(split_id)
+                    .with_index_uid(&index_uid)
+                    .build();
+                if let Some(((top, left), (bottom, right))) = bounding_box_opt {
+                    let bounding_box = GeoBoundingBox::new(
+                        GeoPoint::new(top, left).unwrap(),
+                        GeoPoint::new(bottom, right).unwrap(),
+                    )
+                    .unwrap();
+                    split
+                        .split_metadata
+                        .geo_bounding_boxes
+                        .insert("location".to_string(), bounding_box);
+                }
+                split
+            })
+            .collect();
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let split_ids: BTreeSet<&str> = leaf_search_req
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| split_offsets.split_id.as_str())
+                    .collect();
+                assert_eq!(split_ids, BTreeSet::from(["split1", "split2", "split4"]));
+                let partial_hits = leaf_search_req
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| mock_partial_hit(&split_offsets.split_id, 1, 1))
+                    .collect();
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: leaf_search_req.split_offsets.len() as u64,
+                    partial_hits,
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: leaf_search_req.split_offsets.len() as u64,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let search_response = root_search(
+            &SearcherContext::new(SearcherConfig::default(), None),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_multiple_splits() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = vec![
+                MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build(),
+                MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+        let mut mock_search_service_1 = MockSearchService::new();
+        mock_search_service_1.expect_leaf_search().returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 2,
+                    partial_hits: vec![
+                        mock_partial_hit("split1", 3, 1),
+                        mock_partial_hit("split1", 1, 3),
+                    ],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service_1.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let mut mock_search_service_2 = MockSearchService::new();
+        mock_search_service_2.expect_leaf_search().returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 1,
+                    partial_hits: vec![mock_partial_hit("split2", 2, 2)],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service_2.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", mock_search_service_1),
+            ("127.0.0.1:1002", mock_search_service_2),
+        ]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 3);
+        assert_eq!(search_response.hits.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_hits_stream_multiple_splits() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = vec![
+                MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build(),
+                MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+        let mut mock_search_service_1 = MockSearchService::new();
+        mock_search_service_1.expect_leaf_search().returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 2,
+                    partial_hits: vec![
+                        mock_partial_hit("split1", 3, 1),
+                        mock_partial_hit("split1", 1, 3),
+                    ],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service_1.expect_fetch_docs().returning(
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            },
+        );
+        let mut mock_search_service_2 = MockSearchService::new();
+        mock_search_service_2.expect_leaf_search().returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 1,
+                    partial_hits: vec![mock_partial_hit("split2", 2, 2)],
+                    failed_splits: Vec::new(),
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service_2.expect_fetch_docs().returning(
             |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
                 Ok(quickwit_proto::search::FetchDocsResponse {
                     hits: get_doc_for_fetch_req(fetch_docs_req),
@@ -1980,22 +4633,28 @@ mod tests {
         ]);
         let search_job_placer = SearchJobPlacer::new(searcher_pool);
         let cluster_client = ClusterClient::new(search_job_placer.clone());
-
-        let search_response = root_search(
+        let hits: Vec<Hit> = root_search_hits_stream(
             &SearcherContext::for_test(),
             search_request,
-            MetastoreServiceClient::from(mock_metastore),
+            MetastoreServiceClient::from(metastore),
             &cluster_client,
         )
         .await
+        .unwrap()
+        .try_collect()
+        .await
         .unwrap();
-        assert_eq!(search_response.num_hits, 5);
-        assert_eq!(search_response.hits.len(), 0);
+        let hit_doc_ids: Vec<u32> = hits
+            .iter()
+            .map(|hit| hit.partial_hit.as_ref().unwrap().doc_id)
+            .collect();
+        assert_eq!(hit_doc_ids, [1, 2, 3]);
+        assert!(hits.iter().all(|hit| hit.index_id == "test-index"));
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_root_search_single_split() -> anyhow::Result<()> {
+    async fn test_root_search_hits_stream_ends_with_error_on_failed_splits() {
         let search_request = quickwit_proto::search::SearchRequest {
             index_id_patterns: vec!["test-index".to_string()],
             query_ast: qast_json_helper("test", &["body"]),
@@ -2013,27 +4672,30 @@ mod tests {
                 ])
                 .unwrap())
             });
-        metastore
-            .expect_list_splits()
-            .returning(move |_list_splits_request| {
-                let splits = vec![MockSplitBuilder::new("split1")
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = vec![
+                MockSplitBuilder::new("split1")
                     .with_index_uid(&index_uid)
-                    .build()];
-                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
-                Ok(ServiceStream::from(vec![Ok(splits_response)]))
-            });
+                    .build(),
+                MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
         let mut mock_search_service = MockSearchService::new();
         mock_search_service.expect_leaf_search().returning(
             |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
                 Ok(quickwit_proto::search::LeafSearchResponse {
-                    num_hits: 3,
-                    partial_hits: vec![
-                        mock_partial_hit("split1", 3, 1),
-                        mock_partial_hit("split1", 2, 2),
-                        mock_partial_hit("split1", 1, 3),
-                    ],
-                    failed_splits: Vec::new(),
-                    num_attempted_splits: 1,
+                    num_hits: 1,
+                    partial_hits: vec![mock_partial_hit("split1", 2, 1)],
+                    failed_splits: vec![SplitSearchError {
+                        error: "mock error".to_string(),
+                        split_id: "split2".to_string(),
+                        retryable_error: false,
+                    }],
+                    num_attempted_splits: 2,
                     ..Default::default()
                 })
             },
@@ -2047,24 +4709,102 @@ mod tests {
         );
         let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
         let search_job_placer = SearchJobPlacer::new(searcher_pool);
-        let cluster_client = ClusterClient::new(search_job_placer.clone());
-
-        let searcher_context = SearcherContext::for_test();
-        let search_response = root_search(
-            &searcher_context,
+        let cluster_client = ClusterClient::new(search_job_placer);
+        let hit_results: Vec<crate::Result<Hit>> = root_search_hits_stream(
+            &SearcherContext::for_test(),
             search_request,
             MetastoreServiceClient::from(metastore),
             &cluster_client,
         )
         .await
+        .unwrap()
+        .collect()
+        .await;
+        assert_eq!(hit_results.len(), 2);
+        assert!(hit_results[0].is_ok());
+
+        let SearchError::Internal(error_message) = hit_results[1].as_ref().unwrap_err() else {
+            panic!("expected an internal error");
+        };
+        assert!(error_message.contains("1 split(s) failed"));
+    }
+
+    #[tokio::test]
+    async fn test_root_search_hits_stream_rejects_aggregations() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            aggregation_request: Some(
+                r#"{"count": {"value_count": {"field": "body"}}}"#.to_string(),
+            ),
+            ..Default::default()
+        };
+        let cluster_client = ClusterClient::new(SearchJobPlacer::default());
+        let error = root_search_hits_stream(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(MetastoreServiceClient::mock()),
+            &cluster_client,
+        )
+        .await
+        .err()
         .unwrap();
-        assert_eq!(search_response.num_hits, 3);
-        assert_eq!(search_response.hits.len(), 3);
-        Ok(())
+        assert!(matches!(error, SearchError::InvalidArgument(_)));
     }
 
     #[tokio::test]
-    async fn test_root_search_multiple_splits() -> anyhow::Result<()> {
+    async fn test_fetched_hits_merger_yields_hits_before_all_responses() {
+        let partial_hits = vec![
+            mock_partial_hit("split1", 3, 1),
+            mock_partial_hit("split2", 2, 2),
+            mock_partial_hit("split1", 1, 3),
+        ];
+        let leaf_hit = |partial_hit: &PartialHit| LeafHit {
+            leaf_json: format!(r#"{{"doc_id": {}}}"#, partial_hit.doc_id),
+            partial_hit: Some(partial_hit.clone()),
+            leaf_snippet_json: None,
+        };
+        let split1_response = FetchDocsResponse {
+            hits: vec![leaf_hit(&partial_hits[2]), leaf_hit(&partial_hits[0])],
+        };
+        let split2_response = FetchDocsResponse {
+            hits: vec![leaf_hit(&partial_hits[1])],
+        };
+        let (split2_response_tx, split2_response_rx) = tokio::sync::oneshot::channel();
+        let fetch_docs_futures: FuturesUnordered<
+            BoxFuture<'static, crate::Result<FetchDocsResponse>>,
+        > = [
+            async move { Ok(split2_response_rx.await.unwrap()) }.boxed(),
+            async move { Ok(split1_response) }.boxed(),
+        ]
+        .into_iter()
+        .collect();
+        let mut hits_merger = FetchedHitsMerger {
+            fetch_docs_futures,
+            hits_heap: BinaryHeap::new(),
+            next_position: 0,
+            hit_order: build_hit_order(&partial_hits),
+            split_id_to_index_id_map: HashMap::new(),
+            sort_field_1_datetime_format_opt: None,
+            sort_field_2_datetime_format_opt: None,
+            is_terminated: false,
+        };
+        let next_doc_id = |hit: Hit| hit.partial_hit.unwrap().doc_id;
+
+        // The first hit is yielded while the response of split2 is still pending.
+        let hit = hits_merger.next_hit().await.unwrap().unwrap();
+        assert_eq!(next_doc_id(hit), 1);
+
+        split2_response_tx.send(split2_response).unwrap();
+        let hit = hits_merger.next_hit().await.unwrap().unwrap();
+        assert_eq!(next_doc_id(hit), 2);
+        let hit = hits_merger.next_hit().await.unwrap().unwrap();
+        assert_eq!(next_doc_id(hit), 3);
+        assert!(hits_merger.next_hit().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_root_search_stats() -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
             index_id_patterns: vec!["test-index".to_string()],
             query_ast: qast_json_helper("test", &["body"]),
@@ -2094,51 +4834,127 @@ mod tests {
             let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
             Ok(ServiceStream::from(vec![Ok(splits_response)]))
         });
-        let mut mock_search_service_1 = MockSearchService::new();
-        mock_search_service_1.expect_leaf_search().returning(
-            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let split_stats = leaf_search_req
+                    .split_offsets
+                    .iter()
+                    .map(|split_offsets| SplitSearchStats {
+                        split_id: split_offsets.split_id.clone(),
+                        searcher_addr: String::new(),
+                        search_duration_micros: if split_offsets.split_id == "split2" {
+                            2_000
+                        } else {
+                            1_000
+                        },
+                        num_docs_scanned: 1,
+                        cache_stats: None,
+                        partial: false,
+                    })
+                    .collect();
                 Ok(quickwit_proto::search::LeafSearchResponse {
                     num_hits: 2,
                     partial_hits: vec![
-                        mock_partial_hit("split1", 3, 1),
-                        mock_partial_hit("split1", 1, 3),
+                        mock_partial_hit("split1", 2, 1),
+                        mock_partial_hit("split2", 1, 1),
                     ],
-                    failed_splits: Vec::new(),
-                    num_attempted_splits: 1,
+                    num_attempted_splits: 2,
+                    split_stats,
                     ..Default::default()
                 })
             },
         );
-        mock_search_service_1.expect_fetch_docs().returning(
+        mock_search_service.expect_fetch_docs().returning(
             |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
                 Ok(quickwit_proto::search::FetchDocsResponse {
                     hits: get_doc_for_fetch_req(fetch_docs_req),
                 })
             },
         );
-        let mut mock_search_service_2 = MockSearchService::new();
-        mock_search_service_2.expect_leaf_search().returning(
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            MetastoreServiceClient::from(metastore),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        let stats = search_response.stats.unwrap();
+        assert_eq!(stats.num_attempted_splits, 2);
+        assert_eq!(stats.split_stats.len(), 2);
+        // The slowest split comes first.
+        assert_eq!(stats.split_stats[0].split_id, "split2");
+        assert_eq!(stats.split_stats[0].search_duration_micros, 2_000);
+        assert_eq!(stats.split_stats[1].split_id, "split1");
+        for split_stats in &stats.split_stats {
+            assert_eq!(split_stats.searcher_addr, "127.0.0.1:1001");
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_with_partial_split() -> anyhow::Result<()> {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_index_ids_query| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = vec![
+                MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build(),
+                MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().returning(
             |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
                 Ok(quickwit_proto::search::LeafSearchResponse {
-                    num_hits: 1,
-                    partial_hits: vec![mock_partial_hit("split2", 2, 2)],
-                    failed_splits: Vec::new(),
-                    num_attempted_splits: 1,
+                    num_hits: 2,
+                    partial_hits: vec![
+                        mock_partial_hit("split1", 2, 1),
+                        mock_partial_hit("split2", 1, 1),
+                    ],
+                    num_attempted_splits: 2,
+                    split_stats: vec![SplitSearchStats {
+                        split_id: "split2".to_string(),
+                        partial: true,
+                        ..Default::default()
+                    }],
+                    partial_splits: vec!["split2".to_string()],
                     ..Default::default()
                 })
             },
         );
-        mock_search_service_2.expect_fetch_docs().returning(
+        mock_search_service.expect_fetch_docs().returning(
             |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
                 Ok(quickwit_proto::search::FetchDocsResponse {
                     hits: get_doc_for_fetch_req(fetch_docs_req),
                 })
             },
         );
-        let searcher_pool = searcher_pool_for_test([
-            ("127.0.0.1:1001", mock_search_service_1),
-            ("127.0.0.1:1002", mock_search_service_2),
-        ]);
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
         let search_job_placer = SearchJobPlacer::new(searcher_pool);
         let cluster_client = ClusterClient::new(search_job_placer.clone());
         let search_response = root_search(
@@ -2149,8 +4965,17 @@ mod tests {
         )
         .await
         .unwrap();
-        assert_eq!(search_response.num_hits, 3);
-        assert_eq!(search_response.hits.len(), 3);
+        // The hits of the interrupted split are returned, but the hit count is only a lower bound.
+        assert_eq!(search_response.num_hits, 2);
+        assert_eq!(search_response.hits.len(), 2);
+        assert!(search_response.failed_splits.is_empty());
+        assert_eq!(
+            search_response.num_hits_accuracy,
+            HitCountAccuracy::LowerBound as i32
+        );
+        let stats = search_response.stats.unwrap();
+        assert_eq!(stats.split_stats.len(), 1);
+        assert!(stats.split_stats[0].partial);
         Ok(())
     }
 
@@ -2769,7 +5594,7 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn test_root_search_single_split_retry_single_node() -> anyhow::Result<()> {
+    async fn test_root_search_single_split_no_retry_on_single_node() -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
             index_id_patterns: vec!["test-index".to_string()],
             query_ast: qast_json_helper("test", &["body"]),
@@ -2787,49 +5612,34 @@ mod tests {
                 ])
                 .unwrap())
             });
-        metastore
-            .expect_list_splits()
-            .returning(move |_list_splits_request| {
-                let splits = vec![MockSplitBuilder::new("split1")
-                    .with_index_uid(&index_uid)
-                    .build()];
-                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
-                Ok(ServiceStream::from(vec![Ok(splits_response)]))
-            });
-        let mut first_call = true;
+        metastore.expect_list_splits().returning(move |_filter| {
+            let splits = vec![MockSplitBuilder::new("split1")
+                .with_index_uid(&index_uid)
+                .build()];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+
         let mut mock_search_service = MockSearchService::new();
-        mock_search_service.expect_leaf_search().times(2).returning(
+        // The only node already failed: the split is not retried.
+        mock_search_service.expect_leaf_search().times(1).returning(
             move |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
-                // requests from split 2 arrive here - simulate failure, then success
-                if first_call {
-                    first_call = false;
-                    Ok(quickwit_proto::search::LeafSearchResponse {
-                        num_hits: 0,
-                        partial_hits: Vec::new(),
-                        failed_splits: vec![SplitSearchError {
-                            error: "mock_error".to_string(),
-                            split_id: "split1".to_string(),
-                            retryable_error: true,
-                        }],
-                        num_attempted_splits: 1,
-                        ..Default::default()
-                    })
-                } else {
-                    Ok(quickwit_proto::search::LeafSearchResponse {
-                        num_hits: 1,
-                        partial_hits: vec![mock_partial_hit("split1", 2, 2)],
-                        failed_splits: Vec::new(),
-                        num_attempted_splits: 1,
-                        ..Default::default()
-                    })
-                }
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 0,
+                    partial_hits: Vec::new(),
+                    failed_splits: vec![SplitSearchError {
+                        error: "mock_error".to_string(),
+                        split_id: "split1".to_string(),
+                        retryable_error: true,
+                    }],
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
             },
         );
         mock_search_service.expect_fetch_docs().returning(
-            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
-                Ok(quickwit_proto::search::FetchDocsResponse {
-                    hits: get_doc_for_fetch_req(fetch_docs_req),
-                })
+            |_fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Err(SearchError::Internal("mockerr docs".to_string()))
             },
         );
         let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
@@ -2841,15 +5651,14 @@ mod tests {
             MetastoreServiceClient::from(metastore),
             &cluster_client,
         )
-        .await
-        .unwrap();
-        assert_eq!(search_response.num_hits, 1);
-        assert_eq!(search_response.hits.len(), 1);
+        .await;
+        assert!(search_response.is_err());
         Ok(())
     }
 
     #[tokio::test]
-    async fn test_root_search_single_split_retry_single_node_fails() -> anyhow::Result<()> {
+    async fn test_root_search_multiple_splits_returns_partial_results_on_failed_split(
+    ) -> anyhow::Result<()> {
         let search_request = quickwit_proto::search::SearchRequest {
             index_id_patterns: vec!["test-index".to_string()],
             query_ast: qast_json_helper("test", &["body"]),
@@ -2868,45 +5677,75 @@ mod tests {
                 .unwrap())
             });
         metastore.expect_list_splits().returning(move |_filter| {
-            let splits = vec![MockSplitBuilder::new("split1")
-                .with_index_uid(&index_uid)
-                .build()];
+            let splits = vec![
+                MockSplitBuilder::new("split1")
+                    .with_index_uid(&index_uid)
+                    .build(),
+                MockSplitBuilder::new("split2")
+                    .with_index_uid(&index_uid)
+                    .build(),
+            ];
             let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
             Ok(ServiceStream::from(vec![Ok(splits_response)]))
         });
-
+        let max_leaf_search_retries = 2;
         let mut mock_search_service = MockSearchService::new();
-        mock_search_service.expect_leaf_search().times(2).returning(
-            move |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
-                Ok(quickwit_proto::search::LeafSearchResponse {
-                    num_hits: 0,
-                    partial_hits: Vec::new(),
-                    failed_splits: vec![SplitSearchError {
-                        error: "mock_error".to_string(),
-                        split_id: "split1".to_string(),
-                        retryable_error: true,
-                    }],
-                    num_attempted_splits: 1,
-                    ..Default::default()
-                })
+        // The first request fails on split 2, which is not retried since the only node failed.
+        mock_search_service.expect_leaf_search().times(1).returning(
+            |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                let failed_splits = vec![SplitSearchError {
+                    error: "mock_error".to_string(),
+                    split_id: "split2".to_string(),
+                    retryable_error: true,
+                }];
+                let has_split1 = leaf_search_req
+                    .split_offsets
+                    .iter()
+                    .any(|split_offsets| split_offsets.split_id == "split1");
+                if has_split1 {
+                    Ok(quickwit_proto::search::LeafSearchResponse {
+                        num_hits: 2,
+                        partial_hits: vec![
+                            mock_partial_hit("split1", 3, 1),
+                            mock_partial_hit("split1", 1, 3),
+                        ],
+                        failed_splits,
+                        num_attempted_splits: 2,
+                        ..Default::default()
+                    })
+                } else {
+                    Ok(quickwit_proto::search::LeafSearchResponse {
+                        failed_splits,
+                        num_attempted_splits: 1,
+                        ..Default::default()
+                    })
+                }
             },
         );
         mock_search_service.expect_fetch_docs().returning(
-            |_fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
-                Err(SearchError::Internal("mockerr docs".to_string()))
+            |fetch_docs_req: quickwit_proto::search::FetchDocsRequest| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
             },
         );
         let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
         let search_job_placer = SearchJobPlacer::new(searcher_pool);
-        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let cluster_client = ClusterClient::new(search_job_placer.clone())
+            .with_max_leaf_search_retries(max_leaf_search_retries)
+            .with_retry_budget(Arc::new(RetryBudget::unlimited()));
         let search_response = root_search(
             &SearcherContext::for_test(),
             search_request,
             MetastoreServiceClient::from(metastore),
             &cluster_client,
         )
-        .await;
-        assert!(search_response.is_err());
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 2);
+        assert_eq!(search_response.hits.len(), 2);
+        assert_eq!(search_response.failed_splits.len(), 1);
+        assert_eq!(search_response.failed_splits[0].split_id, "split2");
         Ok(())
     }
 
@@ -3426,18 +6265,27 @@ mod tests {
                         .unwrap(),
                 )
             });
-        metastore.expect_list_splits().returning(move |_filter| {
-            let splits = vec![
-                MockSplitBuilder::new("split1")
-                    .with_index_uid(&index_uid)
-                    .build(),
-                MockSplitBuilder::new("split2")
-                    .with_index_uid(&index_uid_2)
-                    .build(),
-            ];
-            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
-            Ok(ServiceStream::from(vec![Ok(splits_response)]))
-        });
+        metastore
+            .expect_list_splits()
+            .returning(move |list_splits_request| {
+                let list_splits_query =
+                    list_splits_request.deserialize_list_splits_query().unwrap();
+                let mut splits = vec![
+                    MockSplitBuilder::new("split1")
+                        .with_index_uid(&index_uid)
+                        .build(),
+                    MockSplitBuilder::new("split2")
+                        .with_index_uid(&index_uid_2)
+                        .build(),
+                ];
+                splits.retain(|split| {
+                    list_splits_query
+                        .index_uids
+                        .contains(&split.split_metadata.index_uid)
+                });
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
         let mut mock_search_service = MockSearchService::new();
         mock_search_service.expect_leaf_search().times(2).returning(
             |req: quickwit_proto::search::LeafSearchRequest| {
@@ -3617,20 +6465,17 @@ mod tests {
                 .unwrap())
             },
         );
+        // The splits of each index are listed separately.
         metastore
             .expect_list_splits()
-            .return_once(move |list_splits_request| {
+            .times(3)
+            .returning(move |list_splits_request| {
                 let list_splits_query =
                     list_splits_request.deserialize_list_splits_query().unwrap();
-                assert!(
-                    list_splits_query.index_uids
-                        == vec![
-                            index_uid_1.clone(),
-                            index_uid_2.clone(),
-                            index_uid_3.clone()
-                        ]
-                );
-                let splits = vec![
+                assert_eq!(list_splits_query.index_uids.len(), 1);
+                assert!([&index_uid_1, &index_uid_2, &index_uid_3]
+                    .contains(&&list_splits_query.index_uids[0]));
+                let mut splits = vec![
                     MockSplitBuilder::new("index-1-split-1")
                         .with_index_uid(&index_uid_1)
                         .build(),
@@ -3641,6 +6486,11 @@ mod tests {
                         .with_index_uid(&index_uid_2)
                         .build(),
                 ];
+                splits.retain(|split| {
+                    list_splits_query
+                        .index_uids
+                        .contains(&split.split_metadata.index_uid)
+                });
                 let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
                 Ok(ServiceStream::from(vec![Ok(splits_response)]))
             });
@@ -3707,4 +6557,343 @@ mod tests {
         );
         Ok(())
     }
+
+    fn metastore_for_failed_indexes_test(
+        indexes_metadata: Vec<IndexMetadata>,
+        broken_index_uids: Vec<IndexUid>,
+    ) -> MetastoreServiceClient {
+        let mut metastore = MetastoreServiceClient::mock();
+        let index_uids = indexes_metadata
+            .iter()
+            .map(|index_metadata| index_metadata.index_uid.clone())
+            .collect_vec();
+        metastore
+            .expect_list_indexes_metadata()
+            .return_once(move |_| {
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        metastore
+            .expect_list_splits()
+            .times(index_uids.len())
+            .returning(move |list_splits_request| {
+                let list_splits_query =
+                    list_splits_request.deserialize_list_splits_query().unwrap();
+                let index_uid = &list_splits_query.index_uids[0];
+                if broken_index_uids.contains(index_uid) {
+                    return Err(MetastoreError::Unavailable(
+                        "metastore shard is down".to_string(),
+                    ));
+                }
+                let splits =
+                    vec![
+                        MockSplitBuilder::new(&format!("{}-split", index_uid.index_id()))
+                            .with_index_uid(index_uid)
+                            .build(),
+                    ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        MetastoreServiceClient::from(metastore)
+    }
+
+    #[tokio::test]
+    async fn test_root_search_multi_indices_with_failed_index() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index-*".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let index_metadata_1 = IndexMetadata::for_test("test-index-1", "ram:///test-index-1");
+        let index_metadata_2 =
+            index_metadata_for_multi_indexes_test("test-index-2", "ram:///test-index-2");
+        let index_metadata_3 =
+            index_metadata_for_multi_indexes_test("test-index-3", "ram:///test-index-3");
+        let broken_index_uid = index_metadata_3.index_uid.clone();
+        let metastore = metastore_for_failed_indexes_test(
+            vec![index_metadata_1, index_metadata_2, index_metadata_3],
+            vec![broken_index_uid],
+        );
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search()
+            .times(2)
+            .withf(|leaf_search_req| leaf_search_req.index_uri != "ram:///test-index-3")
+            .returning(
+                |leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                    let split_id = &leaf_search_req.split_offsets[0].split_id;
+                    Ok(quickwit_proto::search::LeafSearchResponse {
+                        num_hits: 1,
+                        partial_hits: vec![mock_partial_hit(split_id, 1, 1)],
+                        num_attempted_splits: 1,
+                        ..Default::default()
+                    })
+                },
+            );
+        mock_search_service
+            .expect_fetch_docs()
+            .returning(|fetch_docs_req| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let cluster_client = ClusterClient::new(SearchJobPlacer::new(searcher_pool));
+        let search_response = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            metastore,
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 2);
+        assert_eq!(
+            search_response
+                .hits
+                .iter()
+                .map(|hit| hit.index_id.as_str())
+                .sorted()
+                .collect_vec(),
+            ["test-index-1", "test-index-2"]
+        );
+        assert_eq!(search_response.failed_indexes.len(), 1);
+        let failed_index = &search_response.failed_indexes[0];
+        assert_eq!(failed_index.index_id, "test-index-3");
+        assert_eq!(failed_index.error_code, "internal");
+        assert!(failed_index.message.contains("metastore shard is down"));
+        assert_eq!(
+            search_response.num_hits_accuracy,
+            HitCountAccuracy::LowerBound as i32
+        );
+    }
+
+    #[tokio::test]
+    async fn test_root_search_multi_indices_all_failed() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index-*".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let index_metadata_1 =
+            index_metadata_for_multi_indexes_test("test-index-1", "ram:///test-index-1");
+        let index_metadata_2 =
+            index_metadata_for_multi_indexes_test("test-index-2", "ram:///test-index-2");
+        let broken_index_uids = vec![
+            index_metadata_1.index_uid.clone(),
+            index_metadata_2.index_uid.clone(),
+        ];
+        let metastore = metastore_for_failed_indexes_test(
+            vec![index_metadata_1, index_metadata_2],
+            broken_index_uids,
+        );
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", MockSearchService::new())]);
+        let cluster_client = ClusterClient::new(SearchJobPlacer::new(searcher_pool));
+        let search_error = root_search(
+            &SearcherContext::for_test(),
+            search_request,
+            metastore,
+            &cluster_client,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::Internal(_)));
+        assert!(search_error.to_string().contains("metastore shard is down"));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_list_relevant_splits_per_index_times_out_slow_indexes() {
+        let index_uids = ["test-index-1", "test-index-2", "test-index-3"]
+            .map(|index_id| IndexUid::from_parts(index_id, 0));
+        let slow_index_uid = index_uids[1].clone();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_list_splits()
+            .times(3)
+            .returning(move |list_splits_request| {
+                let list_splits_query =
+                    list_splits_request.deserialize_list_splits_query().unwrap();
+                let index_uid = &list_splits_query.index_uids[0];
+                if *index_uid == slow_index_uid {
+                    return Ok(ServiceStream::new(Box::pin(futures::stream::pending())));
+                }
+                let splits =
+                    vec![
+                        MockSplitBuilder::new(&format!("{}-split", index_uid.index_id()))
+                            .with_index_uid(index_uid)
+                            .build(),
+                    ];
+                let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits_response)]))
+            });
+        let metastore = MetastoreServiceClient::from(mock_metastore);
+
+        let searcher_config = SearcherConfig {
+            list_splits_timeout: HumanDuration::from_secs(1),
+            max_num_concurrent_list_splits: NonZeroUsize::new(1).unwrap(),
+            ..Default::default()
+        };
+        let searcher_context = SearcherContext::new(searcher_config, None);
+        let index_uids_and_tag_filters = index_uids
+            .iter()
+            .map(|index_uid| (index_uid.clone(), None))
+            .collect();
+
+        let (split_metadatas, failed_indexes, _warnings) = list_relevant_splits_per_index(
+            &searcher_context,
+            index_uids_and_tag_filters,
+            None,
+            None,
+            &metastore,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            split_metadatas
+                .iter()
+                .map(|split_metadata| split_metadata.split_id.as_str())
+                .collect_vec(),
+            ["test-index-1-split", "test-index-3-split"]
+        );
+        assert_eq!(failed_indexes.len(), 1);
+        assert_eq!(failed_indexes[0].index_id, "test-index-2");
+        assert_eq!(failed_indexes[0].error_code, "timeout");
+        assert!(failed_indexes[0].message.contains("took more than 1s"));
+    }
+
+    #[tokio::test]
+    async fn test_root_search_with_unreachable_metastore_serves_stale_splits() {
+        let search_request = quickwit_proto::search::SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            query_ast: qast_json_helper("test", &["body"]),
+            max_hits: 10,
+            ..Default::default()
+        };
+        let index_metadata = IndexMetadata::for_test("test-index", "ram:///test-index");
+        let index_uid = index_metadata.index_uid.clone();
+        let metastore_error_opt: Arc<RwLock<Option<MetastoreError>>> = Arc::default();
+
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        let metastore_error_opt_clone = metastore_error_opt.clone();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_| {
+                if let Some(metastore_error) = metastore_error_opt_clone.read().unwrap().clone() {
+                    return Err(metastore_error);
+                }
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata.clone()
+                ])
+                .unwrap())
+            });
+        let metastore_error_opt_clone = metastore_error_opt.clone();
+        mock_metastore.expect_list_splits().returning(move |_| {
+            if let Some(metastore_error) = metastore_error_opt_clone.read().unwrap().clone() {
+                return Err(metastore_error);
+            }
+            let splits = vec![MockSplitBuilder::new("split1")
+                .with_index_uid(&index_uid)
+                .build()];
+            let splits_response = ListSplitsResponse::try_from_splits(splits).unwrap();
+            Ok(ServiceStream::from(vec![Ok(splits_response)]))
+        });
+        let metastore = MetastoreServiceClient::from(mock_metastore);
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_leaf_search().times(2).returning(
+            |_leaf_search_req: quickwit_proto::search::LeafSearchRequest| {
+                Ok(quickwit_proto::search::LeafSearchResponse {
+                    num_hits: 1,
+                    partial_hits: vec![mock_partial_hit("split1", 1, 1)],
+                    num_attempted_splits: 1,
+                    ..Default::default()
+                })
+            },
+        );
+        mock_search_service
+            .expect_fetch_docs()
+            .returning(|fetch_docs_req| {
+                Ok(quickwit_proto::search::FetchDocsResponse {
+                    hits: get_doc_for_fetch_req(fetch_docs_req),
+                })
+            });
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", mock_search_service)]);
+        let cluster_client = ClusterClient::new(SearchJobPlacer::new(searcher_pool));
+        let searcher_context = SearcherContext::for_test();
+
+        let search_response = root_search(
+            &searcher_context,
+            search_request.clone(),
+            metastore.clone(),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 1);
+        assert!(search_response.warnings.is_empty());
+
+        *metastore_error_opt.write().unwrap() = Some(MetastoreError::Connection {
+            message: "connection refused".to_string(),
+        });
+        let search_response = root_search(
+            &searcher_context,
+            search_request.clone(),
+            metastore.clone(),
+            &cluster_client,
+        )
+        .await
+        .unwrap();
+        assert_eq!(search_response.num_hits, 1);
+        assert_eq!(search_response.hits.len(), 1);
+        assert_eq!(
+            search_response.num_hits_accuracy,
+            HitCountAccuracy::LowerBound as i32
+        );
+        assert_eq!(search_response.warnings.len(), 1);
+        let warning = &search_response.warnings[0];
+        assert_eq!(warning.code, "stale_metastore");
+        assert_eq!(warning.index_id, "test-index");
+        assert_eq!(warning.metastore_data_age_secs, Some(0));
+
+        // Missing indexes are never masked.
+        *metastore_error_opt.write().unwrap() = Some(MetastoreError::NotFound(EntityKind::Index {
+            index_id: "test-index".to_string(),
+        }));
+        let search_error = root_search(
+            &searcher_context,
+            search_request.clone(),
+            metastore.clone(),
+            &cluster_client,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::IndexesNotFound { .. }));
+
+        // Past the max staleness, the searches fail.
+        *metastore_error_opt.write().unwrap() = Some(MetastoreError::Connection {
+            message: "connection refused".to_string(),
+        });
+        tokio::time::pause();
+        tokio::time::advance(
+            searcher_context.searcher_config.metastore_max_staleness() + Duration::from_secs(1),
+        )
+        .await;
+        tokio::time::resume();
+
+        let search_error = root_search(
+            &searcher_context,
+            search_request,
+            metastore,
+            &cluster_client,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(search_error, SearchError::Internal(_)));
+        assert!(search_error.to_string().contains("connection refused"));
+    }
 }
@@ -26,6 +26,7 @@ use std::time::Duration;
 use anyhow::Context;
 use base64::prelude::BASE64_STANDARD;
 use base64::Engine;
+use itertools::Itertools;
 use quickwit_metastore::SplitMetadata;
 use quickwit_proto::search::{LeafSearchResponse, PartialHit, SearchRequest};
 use quickwit_proto::types::IndexUid;
@@ -36,7 +37,7 @@ use ulid::Ulid;
 
 use crate::root::IndexMetasForLeafSearch;
 use crate::service::SearcherContext;
-use crate::ClusterClient;
+use crate::{ClusterClient, SearchError};
 
 /// Maximum capacity of the search after cache.
 ///
@@ -115,8 +116,14 @@ impl ScrollContext {
             &self.search_request,
             &self.split_metadatas[..],
             cluster_client,
+            None,
         )
         .await?;
+        // Unlike the first page, scroll pages have no way to report failed splits.
+        if !leaf_search_response.failed_splits.is_empty() {
+            let errors: String = leaf_search_response.failed_splits.iter().join(", ");
+            return Err(SearchError::Internal(errors));
+        }
         self.cached_partial_hits_start_offset = start_offset;
         self.cached_partial_hits = leaf_search_response.partial_hits;
         Ok(true)
@@ -22,13 +22,17 @@ use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::hash::{Hash, Hasher};
 use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
 
 use anyhow::bail;
 use async_trait::async_trait;
 use quickwit_common::pubsub::EventSubscriber;
 use quickwit_common::rendezvous_hasher::{node_affinity, sort_by_rendez_vous_hash};
 use quickwit_proto::search::{ReportSplit, ReportSplitsRequest};
+use serde::Serialize;
 
+use crate::circuit_breaker::{CircuitBreaker, CircuitBreakerConfig, CircuitBreakerState};
 use crate::{SearchServiceClient, SearcherPool};
 
 /// Job.
@@ -61,6 +65,21 @@ pub trait Job {
 pub struct SearchJobPlacer {
     /// Search clients pool.
     searcher_pool: SearcherPool,
+    circuit_breaker_config: CircuitBreakerConfig,
+    /// Circuit breakers of the nodes of the pool that received requests, keyed by gRPC address.
+    circuit_breakers: Arc<Mutex<HashMap<SocketAddr, CircuitBreaker>>>,
+}
+
+/// Circuit breaker state of a searcher node, as exposed by the cluster API.
+#[derive(Clone, Debug, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct NodeCircuitBreakerInfo {
+    /// gRPC address of the node.
+    #[schema(value_type = String)]
+    pub grpc_advertise_addr: SocketAddr,
+    /// State of the circuit breaker of the node.
+    pub circuit_breaker_state: CircuitBreakerState,
+    /// Error rate over the last requests sent to the node, between 0 and 1.
+    pub error_rate: f32,
 }
 
 #[async_trait]
@@ -96,14 +115,117 @@ impl EventSubscriber<ReportSplitsRequest> for SearchJobPlacer {
 
 impl fmt::Debug for SearchJobPlacer {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.debug_struct("SearchJobPlacer").finish()
+        f.debug_struct("SearchJobPlacer")
+            .field("circuit_breaker_config", &self.circuit_breaker_config)
+            .finish()
     }
 }
 
 impl SearchJobPlacer {
     /// Returns an [`SearchJobPlacer`] from a search service client pool.
     pub fn new(searcher_pool: SearcherPool) -> Self {
-        Self { searcher_pool }
+        Self {
+            searcher_pool,
+            circuit_breaker_config: CircuitBreakerConfig::default(),
+            circuit_breakers: Arc::default(),
+        }
+    }
+
+    /// Sets the configuration of the circuit breakers of the nodes.
+    pub fn with_circuit_breaker_config(
+        mut self,
+        circuit_breaker_config: CircuitBreakerConfig,
+    ) -> Self {
+        self.circuit_breaker_config = circuit_breaker_config;
+        self
+    }
+
+    /// Records the outcome of a request sent to a node in its circuit breaker. Outcomes of nodes
+    /// that left the searcher pool are ignored.
+    pub fn record_search_result(&self, grpc_addr: SocketAddr, is_success: bool) {
+        if !self.searcher_pool.contains_key(&grpc_addr) {
+            return;
+        }
+        let now = Instant::now();
+        let mut circuit_breakers_lock = self.circuit_breakers.lock().unwrap();
+        circuit_breakers_lock
+            .entry(grpc_addr)
+            .or_insert_with(|| CircuitBreaker::new(self.circuit_breaker_config))
+            .record(is_success, now);
+    }
+
+    /// Returns the circuit breaker state of each node of the pool, ordered by address.
+    pub fn node_circuit_breaker_infos(&self) -> Vec<NodeCircuitBreakerInfo> {
+        let circuit_breakers_lock = self.circuit_breakers.lock().unwrap();
+        let mut node_infos: Vec<NodeCircuitBreakerInfo> = self
+            .searcher_pool
+            .keys()
+            .into_iter()
+            .map(|grpc_addr| {
+                let (circuit_breaker_state, error_rate) = circuit_breakers_lock
+                    .get(&grpc_addr)
+                    .map(|circuit_breaker| (circuit_breaker.state(), circuit_breaker.error_rate()))
+                    .unwrap_or((CircuitBreakerState::Closed, 0.0));
+                NodeCircuitBreakerInfo {
+                    grpc_advertise_addr: grpc_addr,
+                    circuit_breaker_state,
+                    error_rate,
+                }
+            })
+            .collect();
+        node_infos.sort_by_key(|node_info| node_info.grpc_advertise_addr);
+        node_infos
+    }
+
+    /// Returns whether the searcher pool contains at least one node that is not in
+    /// `excluded_addrs`.
+    pub fn has_node_outside(&self, excluded_addrs: &HashSet<SocketAddr>) -> bool {
+        self.searcher_pool
+            .keys()
+            .into_iter()
+            .any(|grpc_addr| !excluded_addrs.contains(&grpc_addr))
+    }
+
+    /// Drops the circuit breaker of a node that left the searcher pool.
+    pub fn remove_node(&self, grpc_addr: &SocketAddr) {
+        self.circuit_breakers.lock().unwrap().remove(grpc_addr);
+    }
+
+    /// Returns the addresses among `grpc_addrs` whose circuit breaker refuses requests. It does
+    /// not acquire the probes of the open circuit breakers whose recovery timeout elapsed.
+    fn addrs_refused_by_circuit_breakers(
+        &self,
+        grpc_addrs: impl Iterator<Item = SocketAddr>,
+        now: Instant,
+    ) -> HashSet<SocketAddr> {
+        let circuit_breakers_lock = self.circuit_breakers.lock().unwrap();
+        grpc_addrs
+            .filter(|grpc_addr| {
+                circuit_breakers_lock
+                    .get(grpc_addr)
+                    .map(|circuit_breaker| !circuit_breaker.is_available(now))
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Acquires the circuit breakers of the nodes that received jobs, so that open circuit
+    /// breakers whose recovery timeout elapsed become half-open and let these jobs through as
+    /// probes.
+    fn acquire_circuit_breakers<'a>(
+        &self,
+        grpc_addrs: impl Iterator<Item = &'a SocketAddr>,
+        now: Instant,
+    ) {
+        let mut circuit_breakers_lock = self.circuit_breakers.lock().unwrap();
+
+        for grpc_addr in grpc_addrs {
+            if let Some(circuit_breaker) = circuit_breakers_lock.get_mut(grpc_addr) {
+                // A concurrent request may have acquired the probe in the meantime, in which case
+                // the node receives one more request.
+                let _ = circuit_breaker.try_acquire(now);
+            }
+        }
     }
 }
 
@@ -143,7 +265,8 @@ impl SearchJobPlacer {
     /// Assign the given job to the clients
     /// Returns a list of pair (SocketAddr, `Vec<Job>`)
     ///
-    /// When exclude_addresses filters all clients it is ignored.
+    /// When exclude_addresses filters all clients it is ignored. Likewise, the nodes whose
+    /// circuit breaker is open are skipped, unless all the candidate nodes are.
     pub async fn assign_jobs<J: Job>(
         &self,
         mut jobs: Vec<J>,
@@ -167,6 +290,19 @@ impl SearchJobPlacer {
             })
             .collect();
 
+        let now = Instant::now();
+        let refused_addrs = self.addrs_refused_by_circuit_breakers(
+            candidate_nodes
+                .iter()
+                .map(|candidate_node| candidate_node.grpc_addr),
+            now,
+        );
+        let enforce_circuit_breakers = refused_addrs.len() < candidate_nodes.len();
+
+        if enforce_circuit_breakers {
+            candidate_nodes
+                .retain(|candidate_node| !refused_addrs.contains(&candidate_node.grpc_addr));
+        }
         if candidate_nodes.is_empty() {
             bail!(
                 "failed to assign search jobs. there are no available searcher nodes in the pool"
@@ -194,6 +330,9 @@ impl SearchJobPlacer {
                 .1
                 .push(job);
         }
+        if enforce_circuit_breakers {
+            self.acquire_circuit_breakers(job_assignments.keys(), now);
+        }
         Ok(job_assignments.into_values())
     }
 
@@ -236,6 +375,13 @@ impl Eq for CandidateNodes {}
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use quickwit_common::tower::Change;
+    use quickwit_metastore::SplitMetadata;
+    use tokio_stream::wrappers::UnboundedReceiverStream;
+
     use super::*;
     use crate::{searcher_pool_for_test, MockSearchService, SearchJob};
 
@@ -322,4 +468,350 @@ mod tests {
             assert_eq!(assigned_jobs, expected_assigned_jobs);
         }
     }
+
+    async fn split_id_to_node_for_test(
+        search_job_placer: &SearchJobPlacer,
+        jobs: Vec<SearchJob>,
+        excluded_addrs: &HashSet<SocketAddr>,
+    ) -> HashMap<String, SocketAddr> {
+        search_job_placer
+            .assign_jobs(jobs, excluded_addrs)
+            .await
+            .unwrap()
+            .flat_map(|(client, jobs)| {
+                let grpc_addr = client.grpc_addr();
+                jobs.into_iter()
+                    .map(move |job| (job.split_id().to_string(), grpc_addr))
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_assignment_is_stable() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+            ("127.0.0.1:1003", MockSearchService::new()),
+        ]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        let jobs: Vec<SearchJob> = (0..30)
+            .map(|split_idx| SearchJob::for_test(&format!("split{split_idx}"), split_idx % 5 + 1))
+            .collect();
+        let split_id_to_node =
+            split_id_to_node_for_test(&search_job_placer, jobs.clone(), &HashSet::new()).await;
+        assert_eq!(split_id_to_node.len(), 30);
+
+        // The assignment neither depends on previous calls nor on the order of the jobs.
+        let mut reversed_jobs = jobs;
+        reversed_jobs.reverse();
+        let split_id_to_node_reversed =
+            split_id_to_node_for_test(&search_job_placer, reversed_jobs, &HashSet::new()).await;
+        assert_eq!(split_id_to_node_reversed, split_id_to_node);
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_redistributes_jobs_of_removed_node() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+            ("127.0.0.1:1003", MockSearchService::new()),
+        ]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+        // With zero-cost jobs, the load never outweighs the affinity of the splits.
+        let jobs: Vec<SearchJob> = (0..30)
+            .map(|split_idx| SearchJob::for_test(&format!("split{split_idx}"), 0))
+            .collect();
+        let split_id_to_node =
+            split_id_to_node_for_test(&search_job_placer, jobs.clone(), &HashSet::new()).await;
+
+        let removed_addr: SocketAddr = ([127, 0, 0, 1], 1002).into();
+        assert!(split_id_to_node.values().any(|addr| *addr == removed_addr));
+
+        let searcher_pool_without_removed_node = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1003", MockSearchService::new()),
+        ]);
+        let search_job_placer_without_removed_node =
+            SearchJobPlacer::new(searcher_pool_without_removed_node);
+        let split_id_to_node_after_removal = split_id_to_node_for_test(
+            &search_job_placer_without_removed_node,
+            jobs.clone(),
+            &HashSet::new(),
+        )
+        .await;
+        assert_eq!(split_id_to_node_after_removal.len(), 30);
+
+        for (split_id, addr) in &split_id_to_node {
+            let addr_after_removal = split_id_to_node_after_removal[split_id];
+            assert_ne!(addr_after_removal, removed_addr);
+            // Only the jobs of the removed node move.
+            if *addr != removed_addr {
+                assert_eq!(addr_after_removal, *addr);
+            }
+        }
+        // Excluding a node, as the retry path does, is equivalent to removing it.
+        let split_id_to_node_with_excluded_node =
+            split_id_to_node_for_test(&search_job_placer, jobs, &HashSet::from([removed_addr]))
+                .await;
+        assert_eq!(
+            split_id_to_node_with_excluded_node,
+            split_id_to_node_after_removal
+        );
+    }
+
+    async fn wait_for_searcher_pool_len(searcher_pool: &SearcherPool, expected_len: usize) {
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while searcher_pool.len() != expected_len {
+                tokio::time::sleep(Duration::from_millis(1)).await;
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("the searcher pool should contain {expected_len} searchers"));
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_follows_searcher_pool_changes() {
+        let searcher_pool = searcher_pool_for_test([("127.0.0.1:1001", MockSearchService::new())]);
+        let (change_stream_tx, change_stream_rx) = tokio::sync::mpsc::unbounded_channel();
+        searcher_pool.listen_for_changes(UnboundedReceiverStream::new(change_stream_rx));
+        let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
+
+        let jobs: Vec<SearchJob> = (0..10)
+            .map(|split_idx| SearchJob::for_test(&format!("split{split_idx}"), 1))
+            .collect();
+        let first_addr: SocketAddr = ([127, 0, 0, 1], 1001).into();
+        let second_addr: SocketAddr = ([127, 0, 0, 1], 1002).into();
+
+        let split_id_to_node =
+            split_id_to_node_for_test(&search_job_placer, jobs.clone(), &HashSet::new()).await;
+        assert!(split_id_to_node.values().all(|addr| *addr == first_addr));
+
+        // A searcher joins the cluster.
+        let second_client =
+            SearchServiceClient::from_service(Arc::new(MockSearchService::new()), second_addr);
+        change_stream_tx
+            .send(Change::Insert(second_addr, second_client))
+            .unwrap();
+        wait_for_searcher_pool_len(&searcher_pool, 2).await;
+
+        let split_id_to_node =
+            split_id_to_node_for_test(&search_job_placer, jobs.clone(), &HashSet::new()).await;
+        assert!(split_id_to_node.values().any(|addr| *addr == second_addr));
+
+        // The searcher leaves the cluster.
+        change_stream_tx.send(Change::Remove(second_addr)).unwrap();
+        wait_for_searcher_pool_len(&searcher_pool, 1).await;
+
+        let split_id_to_node =
+            split_id_to_node_for_test(&search_job_placer, jobs, &HashSet::new()).await;
+        assert!(split_id_to_node.values().all(|addr| *addr == first_addr));
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_in_flight_assignments_survive_node_removal() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+        ]);
+        let (change_stream_tx, change_stream_rx) = tokio::sync::mpsc::unbounded_channel();
+        searcher_pool.listen_for_changes(UnboundedReceiverStream::new(change_stream_rx));
+        let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
+
+        let jobs: Vec<SearchJob> = (0..10)
+            .map(|split_idx| SearchJob::for_test(&format!("split{split_idx}"), 1))
+            .collect();
+        let removed_addr: SocketAddr = ([127, 0, 0, 1], 1002).into();
+
+        let in_flight_assignments = search_job_placer
+            .assign_jobs(jobs, &HashSet::new())
+            .await
+            .unwrap();
+
+        // The searcher leaves the cluster while the assignments are in flight.
+        change_stream_tx.send(Change::Remove(removed_addr)).unwrap();
+        wait_for_searcher_pool_len(&searcher_pool, 1).await;
+
+        let in_flight_assignments: Vec<(SearchServiceClient, Vec<SearchJob>)> =
+            in_flight_assignments.collect();
+        assert_eq!(in_flight_assignments.len(), 2);
+        assert!(in_flight_assignments
+            .iter()
+            .any(|(client, _)| client.grpc_addr() == removed_addr));
+
+        let num_in_flight_jobs: usize = in_flight_assignments
+            .iter()
+            .map(|(_, jobs)| jobs.len())
+            .sum();
+        assert_eq!(num_in_flight_jobs, 10);
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_balances_cost_rather_than_num_splits() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+        ]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool);
+
+        // One large split weighs as much as ten small ones.
+        let mut splits_metadata = vec![SplitMetadata {
+            split_id: "large-split".to_string(),
+            num_docs: 10_000_000,
+            ..Default::default()
+        }];
+        for split_idx in 0..10 {
+            splits_metadata.push(SplitMetadata {
+                split_id: format!("small-split-{split_idx}"),
+                num_docs: 1_000,
+                ..Default::default()
+            });
+        }
+        let jobs: Vec<SearchJob> = splits_metadata.iter().map(SearchJob::from).collect();
+        assert_eq!(jobs[0].cost(), 11);
+        assert!(jobs[1..].iter().all(|job| job.cost() == 1));
+
+        let mut assigned_jobs: Vec<Vec<SearchJob>> = search_job_placer
+            .assign_jobs(jobs, &HashSet::new())
+            .await
+            .unwrap()
+            .map(|(_, jobs)| jobs)
+            .collect();
+        assigned_jobs.sort_unstable_by_key(|jobs| jobs.len());
+        assert_eq!(assigned_jobs.len(), 2);
+
+        // The large split gets a searcher of its own, the small ones go to the other searcher.
+        assert_eq!(assigned_jobs[0].len(), 1);
+        assert_eq!(assigned_jobs[0][0].split_id(), "large-split");
+        assert_eq!(assigned_jobs[1].len(), 10);
+
+        let total_costs: Vec<usize> = assigned_jobs
+            .iter()
+            .map(|jobs| jobs.iter().map(Job::cost).sum())
+            .collect();
+        assert_eq!(total_costs, [11, 10]);
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_skips_nodes_with_open_circuit_breaker() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+        ]);
+        let search_job_placer =
+            SearchJobPlacer::new(searcher_pool).with_circuit_breaker_config(CircuitBreakerConfig {
+                window_size: 2,
+                error_rate_threshold: 0.5,
+                recovery_timeout: Duration::from_secs(60),
+            });
+        let degraded_addr: SocketAddr = ([127, 0, 0, 1], 1001).into();
+        let healthy_addr: SocketAddr = ([127, 0, 0, 1], 1002).into();
+        search_job_placer.record_search_result(degraded_addr, false);
+        search_job_placer.record_search_result(degraded_addr, false);
+        search_job_placer.record_search_result(healthy_addr, true);
+
+        let node_infos = search_job_placer.node_circuit_breaker_infos();
+        assert_eq!(
+            node_infos,
+            [
+                NodeCircuitBreakerInfo {
+                    grpc_advertise_addr: degraded_addr,
+                    circuit_breaker_state: CircuitBreakerState::Open,
+                    error_rate: 1.0,
+                },
+                NodeCircuitBreakerInfo {
+                    grpc_advertise_addr: healthy_addr,
+                    circuit_breaker_state: CircuitBreakerState::Closed,
+                    error_rate: 0.0,
+                },
+            ]
+        );
+        let jobs: Vec<SearchJob> = (0..10)
+            .map(|split_idx| SearchJob::for_test(&format!("split-{split_idx}"), 1))
+            .collect();
+        let assigned_addrs: Vec<SocketAddr> = search_job_placer
+            .assign_jobs(jobs, &HashSet::new())
+            .await
+            .unwrap()
+            .map(|(client, _)| client.grpc_addr())
+            .collect();
+        assert_eq!(assigned_addrs, [healthy_addr]);
+
+        // The circuit breaker is ignored when it would exclude all the candidate nodes.
+        let excluded_addrs = HashSet::from_iter([healthy_addr]);
+        let client = search_job_placer
+            .assign_job(SearchJob::for_test("split", 1), &excluded_addrs)
+            .await
+            .unwrap();
+        assert_eq!(client.grpc_addr(), degraded_addr);
+        // Ignored circuit breakers are not acquired.
+        assert_eq!(
+            search_job_placer.node_circuit_breaker_infos()[0].circuit_breaker_state,
+            CircuitBreakerState::Open
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_only_acquires_circuit_breakers_of_assigned_nodes() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+        ]);
+        let search_job_placer =
+            SearchJobPlacer::new(searcher_pool).with_circuit_breaker_config(CircuitBreakerConfig {
+                window_size: 1,
+                error_rate_threshold: 0.5,
+                recovery_timeout: Duration::ZERO,
+            });
+        let recovering_addr: SocketAddr = ([127, 0, 0, 1], 1001).into();
+        search_job_placer.record_search_result(recovering_addr, false);
+        assert_eq!(
+            search_job_placer.node_circuit_breaker_infos()[0].circuit_breaker_state,
+            CircuitBreakerState::Open
+        );
+        // The recovery timeout elapsed, so the node is a candidate, but its probe is only
+        // acquired if it receives the job.
+        let client = search_job_placer
+            .assign_job(SearchJob::for_test("split", 1), &HashSet::new())
+            .await
+            .unwrap();
+        let expected_circuit_breaker_state = if client.grpc_addr() == recovering_addr {
+            CircuitBreakerState::HalfOpen
+        } else {
+            CircuitBreakerState::Open
+        };
+        assert_eq!(
+            search_job_placer.node_circuit_breaker_infos()[0].circuit_breaker_state,
+            expected_circuit_breaker_state
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_job_placer_drops_circuit_breakers_of_removed_nodes() {
+        let searcher_pool = searcher_pool_for_test([
+            ("127.0.0.1:1001", MockSearchService::new()),
+            ("127.0.0.1:1002", MockSearchService::new()),
+        ]);
+        let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
+        let removed_addr: SocketAddr = ([127, 0, 0, 1], 1001).into();
+        let unknown_addr: SocketAddr = ([127, 0, 0, 1], 1003).into();
+        search_job_placer.record_search_result(removed_addr, false);
+        search_job_placer.record_search_result(unknown_addr, false);
+        assert_eq!(search_job_placer.circuit_breakers.lock().unwrap().len(), 1);
+
+        searcher_pool.remove(&removed_addr);
+        search_job_placer.remove_node(&removed_addr);
+        assert!(search_job_placer
+            .circuit_breakers
+            .lock()
+            .unwrap()
+            .is_empty());
+
+        // The outcome of a request that was in flight when the node left is ignored.
+        search_job_placer.record_search_result(removed_addr, true);
+        assert!(search_job_placer
+            .circuit_breakers
+            .lock()
+            .unwrap()
+            .is_empty());
+    }
 }
@@ -0,0 +1,179 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Mutex;
+use std::time::Duration;
+
+use lru::LruCache;
+use quickwit_config::SearchResponseCacheLimits;
+use quickwit_metastore::SplitMetadata;
+use quickwit_proto::search::{SearchRequest, SearchResponse};
+use tokio::time::Instant;
+
+/// A cache to memoize `root_search` responses.
+///
+/// Splits are immutable, so a response remains valid as long as the set of splits the request
+/// targets is unchanged. Entries nonetheless expire after a short TTL to bound staleness of the
+/// split list itself, which is only refreshed upon cache misses.
+///
+/// Expiration relies on the tokio clock, so that it can be tested with a paused clock.
+pub struct SearchResponseCache {
+    content: Mutex<LruCache<CacheKey, CacheEntry>>,
+    ttl: Duration,
+}
+
+struct CacheEntry {
+    search_response: SearchResponse,
+    expires_at: Instant,
+}
+
+impl SearchResponseCache {
+    pub fn new(limits: SearchResponseCacheLimits) -> SearchResponseCache {
+        SearchResponseCache {
+            content: Mutex::new(LruCache::new(limits.max_num_entries)),
+            ttl: limits.ttl(),
+        }
+    }
+
+    pub fn get(&self, cache_key: &CacheKey) -> Option<SearchResponse> {
+        let mut content_lock = self.content.lock().unwrap();
+        let cache_entry = content_lock.get(cache_key)?;
+
+        if cache_entry.expires_at <= Instant::now() {
+            content_lock.pop(cache_key);
+            return None;
+        }
+        Some(cache_entry.search_response.clone())
+    }
+
+    pub fn put(&self, cache_key: CacheKey, search_response: SearchResponse) {
+        let cache_entry = CacheEntry {
+            search_response,
+            expires_at: Instant::now() + self.ttl,
+        };
+        let mut content_lock = self.content.lock().unwrap();
+        content_lock.put(cache_key, cache_entry);
+    }
+}
+
+/// A key inside a [`SearchResponseCache`].
+#[derive(Debug, Hash, PartialEq, Eq)]
+pub struct CacheKey {
+    /// The request this entry matches.
+    search_request: SearchRequest,
+    /// The sorted ids of the splits targeted by the request.
+    split_ids: Vec<String>,
+}
+
+impl CacheKey {
+    pub fn new(search_request: &SearchRequest, split_metadatas: &[SplitMetadata]) -> Self {
+        let mut split_ids: Vec<String> = split_metadatas
+            .iter()
+            .map(|split_metadata| split_metadata.split_id.clone())
+            .collect();
+        split_ids.sort_unstable();
+
+        CacheKey {
+            search_request: search_request.clone(),
+            split_ids,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroUsize;
+
+    use quickwit_common::HumanDuration;
+
+    use super::*;
+
+    fn split_metadatas_for_test(split_ids: &[&str]) -> Vec<SplitMetadata> {
+        split_ids
+            .iter()
+            .map(|split_id| SplitMetadata {
+                split_id: split_id.to_string(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_search_response_cache() {
+        let cache = SearchResponseCache::new(SearchResponseCacheLimits::default());
+        let search_request = SearchRequest {
+            index_id_patterns: vec!["test-index".to_string()],
+            max_hits: 10,
+            ..Default::default()
+        };
+        let search_response = SearchResponse {
+            num_hits: 3,
+            ..Default::default()
+        };
+        let split_metadatas = split_metadatas_for_test(&["split1", "split2"]);
+
+        let cache_key = CacheKey::new(&search_request, &split_metadatas);
+        assert!(cache.get(&cache_key).is_none());
+
+        cache.put(cache_key, search_response.clone());
+
+        let cache_key = CacheKey::new(&search_request, &split_metadatas);
+        assert_eq!(cache.get(&cache_key).unwrap(), search_response);
+
+        // The order of the splits does not matter.
+        let split_metadatas = split_metadatas_for_test(&["split2", "split1"]);
+        let cache_key = CacheKey::new(&search_request, &split_metadatas);
+        assert_eq!(cache.get(&cache_key).unwrap(), search_response);
+
+        let split_metadatas = split_metadatas_for_test(&["split1", "split2", "split3"]);
+        let cache_key = CacheKey::new(&search_request, &split_metadatas);
+        assert!(cache.get(&cache_key).is_none());
+
+        let other_search_request = SearchRequest {
+            max_hits: 20,
+            ..search_request
+        };
+        let split_metadatas = split_metadatas_for_test(&["split1", "split2"]);
+        let cache_key = CacheKey::new(&other_search_request, &split_metadatas);
+        assert!(cache.get(&cache_key).is_none());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_search_response_cache_ttl() {
+        let cache = SearchResponseCache::new(SearchResponseCacheLimits {
+            max_num_entries: NonZeroUsize::new(10).unwrap(),
+            ttl: HumanDuration::from_secs(1),
+        });
+        let search_request = SearchRequest::default();
+        let split_metadatas = split_metadatas_for_test(&["split1"]);
+
+        cache.put(
+            CacheKey::new(&search_request, &split_metadatas),
+            SearchResponse::default(),
+        );
+        let cache_key = CacheKey::new(&search_request, &split_metadatas);
+        assert!(cache.get(&cache_key).is_some());
+
+        tokio::time::advance(Duration::from_millis(999)).await;
+        assert!(cache.get(&cache_key).is_some());
+
+        tokio::time::advance(Duration::from_millis(1)).await;
+        assert!(cache.get(&cache_key).is_none());
+    }
+}
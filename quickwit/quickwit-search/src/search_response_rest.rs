@@ -19,8 +19,11 @@
 
 use std::convert::TryFrom;
 
-use quickwit_common::truncate_str;
-use quickwit_proto::search::SearchResponse;
+use itertools::Itertools;
+use quickwit_common::{is_false, truncate_str};
+use quickwit_proto::search::{
+    HitCountAccuracy, IndexSearchError, SearchResponse, SearchStats, SearchWarning,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 
@@ -39,6 +42,10 @@ pub struct SearchResponseRest {
     #[schema(value_type = Vec<Object>)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub snippets: Option<Vec<JsonValue>>,
+    /// Index ID of each hit, in the same order as `hits`. Only set when the hits come from
+    /// several indexes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hit_index_ids: Option<Vec<String>>,
     /// Elapsed time.
     pub elapsed_time_micros: u64,
     /// Search errors.
@@ -47,6 +54,33 @@ pub struct SearchResponseRest {
     #[schema(value_type = Object)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub aggregations: Option<JsonValue>,
+    /// Cursor to pass as `search_after` to fetch the next page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_page_token: Option<String>,
+    /// Statistics about the splits searched to answer the request.
+    #[schema(value_type = Object)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stats: Option<SearchStats>,
+    /// Indexes that could not be searched. When not empty, the hits and aggregations only cover
+    /// the other indexes.
+    #[schema(value_type = Vec<Object>)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_indexes: Vec<IndexSearchError>,
+    /// Set to `lower_bound` when `num_hits` only counts the matching documents of some of the
+    /// indexes.
+    #[schema(value_type = Option<String>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub num_hits_accuracy: Option<HitCountAccuracy>,
+    /// Whether the search exceeded its timeout, in which case the response only covers the
+    /// documents scanned before the timeout.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub timed_out: bool,
+    /// Conditions that did not prevent the search from succeeding but may affect its results,
+    /// e.g. a `stale_metastore` warning when the splits were listed from a stale copy of the
+    /// metastore.
+    #[schema(value_type = Vec<Object>)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<SearchWarning>,
 }
 
 impl TryFrom<SearchResponse> for SearchResponseRest {
@@ -55,6 +89,7 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
     fn try_from(search_response: SearchResponse) -> Result<Self, Self::Error> {
         let mut documents = Vec::with_capacity(search_response.hits.len());
         let mut snippets = Vec::new();
+        let mut hit_index_ids = Vec::with_capacity(search_response.hits.len());
         for hit in search_response.hits {
             let document: JsonValue = serde_json::from_str(&hit.json).map_err(|err| {
                 SearchError::Internal(format!(
@@ -64,6 +99,7 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
                 ))
             })?;
             documents.push(document);
+            hit_index_ids.push(hit.index_id);
 
             if let Some(snippet_json) = hit.snippet {
                 let snippet_opt: JsonValue =
@@ -82,6 +118,12 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
             None
         };
 
+        let hit_index_ids_opt = if hit_index_ids.iter().all_equal() {
+            None
+        } else {
+            Some(hit_index_ids)
+        };
+
         let aggregations_opt = if let Some(aggregation_json) = search_response.aggregation {
             let aggregation: JsonValue = serde_json::from_str(&aggregation_json)
                 .map_err(|err| SearchError::Internal(err.to_string()))?;
@@ -90,13 +132,29 @@ impl TryFrom<SearchResponse> for SearchResponseRest {
             None
         };
 
+        let mut errors = search_response.errors;
+        errors.extend(
+            search_response
+                .failed_splits
+                .iter()
+                .map(|failed_split| failed_split.to_string()),
+        );
+
         Ok(SearchResponseRest {
             num_hits: search_response.num_hits,
             hits: documents,
             snippets: snippet_opt,
+            hit_index_ids: hit_index_ids_opt,
             elapsed_time_micros: search_response.elapsed_time_micros,
-            errors: search_response.errors,
+            errors,
             aggregations: aggregations_opt,
+            next_page_token: search_response.next_page_token,
+            stats: search_response.stats,
+            failed_indexes: search_response.failed_indexes,
+            num_hits_accuracy: HitCountAccuracy::from_i32(search_response.num_hits_accuracy)
+                .filter(|num_hits_accuracy| *num_hits_accuracy != HitCountAccuracy::Exact),
+            timed_out: search_response.timed_out,
+            warnings: search_response.warnings,
         })
     }
 }
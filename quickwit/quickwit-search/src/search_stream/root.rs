@@ -32,7 +32,7 @@ use tokio_stream::StreamMap;
 use tracing::*;
 
 use crate::cluster_client::ClusterClient;
-use crate::root::{refine_start_end_timestamp_from_ast, SearchJob};
+use crate::root::{refine_start_end_timestamp_from_ast, validate_timestamp_range, SearchJob};
 use crate::{list_relevant_splits, SearchError};
 
 /// Perform a distributed search stream.
@@ -58,6 +58,13 @@ pub async fn root_search_stream(
             SearchError::Internal(format!("failed to build doc mapper. cause: {err}"))
         })?;
 
+    validate_timestamp_range(
+        &*doc_mapper,
+        &search_stream_request.index_id,
+        search_stream_request.start_timestamp,
+        search_stream_request.end_timestamp,
+    )?;
+
     let query_ast: QueryAst = serde_json::from_str(&search_stream_request.query_ast)
         .map_err(|err| SearchError::InvalidQuery(err.to_string()))?;
     let query_ast_resolved = query_ast.parse_user_query(doc_mapper.default_search_fields())?;
@@ -17,23 +17,30 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::future::Future;
 use std::pin::Pin;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use quickwit_common::metrics::OwnedGaugeGuard;
 use quickwit_common::uri::Uri;
 use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::DocMapper;
-use quickwit_proto::metastore::MetastoreServiceClient;
+use quickwit_metastore::ListIndexesMetadataResponseExt;
+use quickwit_proto::metastore::{
+    ListIndexesMetadataRequest, MetastoreService, MetastoreServiceClient,
+};
 use quickwit_proto::search::{
-    FetchDocsRequest, FetchDocsResponse, GetKvRequest, Hit, LeafListFieldsRequest,
-    LeafListTermsRequest, LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse,
-    LeafSearchStreamRequest, LeafSearchStreamResponse, ListFieldsRequest, ListFieldsResponse,
-    ListTermsRequest, ListTermsResponse, PutKvRequest, ReportSplitsRequest, ReportSplitsResponse,
-    ScrollRequest, SearchRequest, SearchResponse, SearchStreamRequest, SnippetRequest,
+    ExplainResult, FetchDocsRequest, FetchDocsResponse, GetKvRequest, HealthCheckRequest,
+    HealthCheckResponse, Hit, HitCountAccuracy, LeafListFieldsRequest, LeafListTermsRequest,
+    LeafListTermsResponse, LeafSearchRequest, LeafSearchResponse, LeafSearchStreamRequest,
+    LeafSearchStreamResponse, ListFieldsRequest, ListFieldsResponse, ListTermsRequest,
+    ListTermsResponse, PutKvRequest, ReportSplitsRequest, ReportSplitsResponse, ScrollRequest,
+    SearchRequest, SearchResponse, SearchStreamRequest, SnippetRequest,
 };
 use quickwit_storage::{
     MemorySizedCache, QuickwitCache, SplitCache, StorageCache, StorageResolver,
@@ -41,16 +48,20 @@ use quickwit_storage::{
 use tantivy::aggregation::AggregationLimits;
 use tokio::sync::Semaphore;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::warn;
 
 use crate::leaf_cache::LeafSearchCache;
 use crate::list_fields::{leaf_list_fields, root_list_fields};
 use crate::list_fields_cache::ListFieldsCache;
-use crate::root::fetch_docs_phase;
+use crate::metastore_fallback_cache::MetastoreFallbackCache;
+use crate::root::{fetch_docs_phase, root_explain_search};
 use crate::scroll_context::{MiniKV, ScrollContext, ScrollKeyAndStartOffset};
+use crate::search_response_cache::SearchResponseCache;
 use crate::search_stream::{leaf_search_stream, root_search_stream};
+use crate::synonym_table_cache::SynonymTableCache;
 use crate::{
     fetch_docs, leaf_list_terms, leaf_search, root_list_terms, root_search, ClusterClient,
-    SearchError,
+    SearchError, SEARCH_METRICS,
 };
 
 #[derive(Clone)]
@@ -61,6 +72,10 @@ pub struct SearchServiceImpl {
     cluster_client: ClusterClient,
     searcher_context: Arc<SearcherContext>,
     search_after_cache: MiniKV,
+    start_instant: Instant,
+    // Number of indexes reported by the health check, refreshed in the background so that health
+    // checks never wait on the metastore.
+    num_indexes: Arc<AtomicU64>,
 }
 
 /// Trait representing a search service.
@@ -79,6 +94,12 @@ pub trait SearchService: 'static + Send + Sync {
     /// It is also in charge of merging back the responses.
     async fn root_search(&self, request: SearchRequest) -> crate::Result<SearchResponse>;
 
+    /// Root explain API.
+    /// Selects the splits targeted by the request like `root_search` does, and reports for each
+    /// of them whether it was pruned by the time range, its number of matching documents and its
+    /// top hits.
+    async fn explain_search(&self, request: SearchRequest) -> crate::Result<ExplainResult>;
+
     /// Performs a leaf search on a given set of splits.
     ///
     /// It is like a regular search except that:
@@ -150,6 +171,13 @@ pub trait SearchService: 'static + Send + Sync {
         &self,
         list_fields: LeafListFieldsRequest,
     ) -> crate::Result<ListFieldsResponse>;
+
+    /// Reports the uptime of the service and the number of indexes it can search, as of the last
+    /// refresh. It does not call the metastore.
+    async fn health_check(
+        &self,
+        health_check_request: HealthCheckRequest,
+    ) -> crate::Result<HealthCheckResponse>;
 }
 
 impl SearchServiceImpl {
@@ -166,6 +194,44 @@ impl SearchServiceImpl {
             cluster_client,
             searcher_context,
             search_after_cache: MiniKV::default(),
+            start_instant: Instant::now(),
+            num_indexes: Arc::default(),
+        }
+    }
+
+    /// Spawns a task refreshing the number of indexes reported by the health check every
+    /// [`NUM_INDEXES_REFRESH_INTERVAL`]. The task stops once the service is dropped.
+    pub(crate) fn spawn_num_indexes_refresh_loop(&self) {
+        let metastore = self.metastore.clone();
+        let num_indexes_weak = Arc::downgrade(&self.num_indexes);
+        tokio::spawn(async move {
+            let mut refresh_interval = tokio::time::interval(NUM_INDEXES_REFRESH_INTERVAL);
+            loop {
+                refresh_interval.tick().await;
+                let Some(num_indexes) = num_indexes_weak.upgrade() else {
+                    return;
+                };
+                refresh_num_indexes(metastore.clone(), &num_indexes).await;
+            }
+        });
+    }
+}
+
+/// Interval at which the number of indexes reported by the health check is refreshed.
+const NUM_INDEXES_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Counts the indexes of the metastore. On error, the previous count is kept.
+async fn refresh_num_indexes(mut metastore: MetastoreServiceClient, num_indexes: &AtomicU64) {
+    let num_indexes_res = metastore
+        .list_indexes_metadata(ListIndexesMetadataRequest::all())
+        .await
+        .and_then(|response| response.deserialize_indexes_metadata());
+    match num_indexes_res {
+        Ok(indexes_metadata) => {
+            num_indexes.store(indexes_metadata.len() as u64, Ordering::Relaxed);
+        }
+        Err(metastore_error) => {
+            warn!(error=?metastore_error, "failed to refresh the number of indexes");
         }
     }
 }
@@ -177,39 +243,145 @@ fn deserialize_doc_mapper(doc_mapper_str: &str) -> crate::Result<Arc<dyn DocMapp
     Ok(doc_mapper)
 }
 
+/// Runs a search service request, recording its count, duration and outcome.
+async fn with_request_metrics<T>(
+    operation: &'static str,
+    index: &str,
+    request_fut: impl Future<Output = crate::Result<T>>,
+) -> crate::Result<T> {
+    let start = Instant::now();
+    SEARCH_METRICS
+        .requests_total
+        .with_label_values([operation, index])
+        .inc();
+    // The guard decrements the gauge on drop, so cancelled requests are accounted for too.
+    let _in_flight_guard = OwnedGaugeGuard::from_gauge(
+        SEARCH_METRICS
+            .requests_in_flight
+            .with_label_values([operation]),
+    );
+    let result = request_fut.await;
+    let is_error = if result.is_err() {
+        SEARCH_METRICS
+            .request_errors_total
+            .with_label_values([operation, index])
+            .inc();
+        "true"
+    } else {
+        "false"
+    };
+    SEARCH_METRICS
+        .request_duration_seconds
+        .with_label_values([operation, index, is_error])
+        .observe(start.elapsed().as_secs_f64());
+    result
+}
+
+/// Returns the `index` metric label of a root request: its index ID if it targets a single index,
+/// `multi` otherwise. Index ID patterns are chosen by the client, so using them as labels would
+/// create an unbounded number of time series.
+fn root_request_index_label(index_id_patterns: &[String]) -> &str {
+    match index_id_patterns {
+        [index_id] if !index_id.contains('*') => index_id,
+        _ => "multi",
+    }
+}
+
+/// Records the splits that failed and the cache statistics of the splits searched by a leaf.
+fn record_leaf_search_metrics(index_id: &str, leaf_search_response: &LeafSearchResponse) {
+    SEARCH_METRICS
+        .leaf_search_failed_splits_total
+        .with_label_values([index_id])
+        .inc_by(leaf_search_response.failed_splits.len() as u64);
+    let (num_cache_hits, num_cache_misses) = leaf_search_response
+        .split_stats
+        .iter()
+        .filter_map(|split_stats| split_stats.cache_stats.as_ref())
+        .fold((0, 0), |(num_hits, num_misses), cache_stats| {
+            (
+                num_hits + cache_stats.num_hits,
+                num_misses + cache_stats.num_misses,
+            )
+        });
+    SEARCH_METRICS
+        .leaf_search_split_cache_hits_total
+        .with_label_values([index_id])
+        .inc_by(num_cache_hits);
+    SEARCH_METRICS
+        .leaf_search_split_cache_misses_total
+        .with_label_values([index_id])
+        .inc_by(num_cache_misses);
+}
+
 #[async_trait]
 impl SearchService for SearchServiceImpl {
     async fn root_search(&self, search_request: SearchRequest) -> crate::Result<SearchResponse> {
-        let search_result = root_search(
-            &self.searcher_context,
-            search_request,
-            self.metastore.clone(),
-            &self.cluster_client,
+        let index_label = root_request_index_label(&search_request.index_id_patterns).to_string();
+        let search_result = with_request_metrics(
+            "root_search",
+            &index_label,
+            root_search(
+                &self.searcher_context,
+                search_request,
+                self.metastore.clone(),
+                &self.cluster_client,
+            ),
         )
         .await?;
+        if let Some(stats) = &search_result.stats {
+            SEARCH_METRICS
+                .root_search_splits_total
+                .with_label_values([&index_label])
+                .inc_by(stats.num_attempted_splits);
+        }
         Ok(search_result)
     }
 
+    async fn explain_search(&self, search_request: SearchRequest) -> crate::Result<ExplainResult> {
+        let index_label = root_request_index_label(&search_request.index_id_patterns).to_string();
+        with_request_metrics(
+            "explain_search",
+            &index_label,
+            root_explain_search(
+                &self.searcher_context,
+                search_request,
+                self.metastore.clone(),
+                &self.cluster_client,
+            ),
+        )
+        .await
+    }
+
     async fn leaf_search(
         &self,
         leaf_search_request: LeafSearchRequest,
     ) -> crate::Result<LeafSearchResponse> {
-        let search_request: Arc<SearchRequest> = leaf_search_request
-            .search_request
-            .ok_or_else(|| SearchError::Internal("no search request".to_string()))?
-            .into();
-        let index_uri = Uri::from_str(&leaf_search_request.index_uri)?;
-        let storage = self.storage_resolver.resolve(&index_uri).await?;
-        let doc_mapper = deserialize_doc_mapper(&leaf_search_request.doc_mapper)?;
-
-        let leaf_search_response = leaf_search(
-            self.searcher_context.clone(),
-            search_request,
-            storage.clone(),
-            leaf_search_request.split_offsets,
-            doc_mapper,
-        )
-        .await?;
+        let index_id = leaf_search_request.index_id.clone();
+        let leaf_search_fut = async {
+            let search_request: Arc<SearchRequest> = leaf_search_request
+                .search_request
+                .ok_or_else(|| SearchError::Internal("no search request".to_string()))?
+                .into();
+            let index_uri = Uri::from_str(&leaf_search_request.index_uri)?;
+            let storage = self.storage_resolver.resolve(&index_uri).await?;
+            let doc_mapper = deserialize_doc_mapper(&leaf_search_request.doc_mapper)?;
+
+            leaf_search(
+                self.searcher_context.clone(),
+                search_request,
+                storage.clone(),
+                leaf_search_request.split_offsets,
+                doc_mapper,
+                leaf_search_request
+                    .timeout_millis
+                    .map(Duration::from_millis),
+                leaf_search_request.num_searchers.unwrap_or(1) as usize,
+            )
+            .await
+        };
+        let leaf_search_response =
+            with_request_metrics("leaf_search", &index_id, leaf_search_fut).await?;
+        record_leaf_search_metrics(&index_id, &leaf_search_response);
 
         Ok(leaf_search_response)
     }
@@ -218,20 +390,25 @@ impl SearchService for SearchServiceImpl {
         &self,
         fetch_docs_request: FetchDocsRequest,
     ) -> crate::Result<FetchDocsResponse> {
-        let index_uri = Uri::from_str(&fetch_docs_request.index_uri)?;
-        let storage = self.storage_resolver.resolve(&index_uri).await?;
-        let snippet_request_opt: Option<&SnippetRequest> =
-            fetch_docs_request.snippet_request.as_ref();
-        let doc_mapper = deserialize_doc_mapper(&fetch_docs_request.doc_mapper)?;
-        let fetch_docs_response = fetch_docs(
-            self.searcher_context.clone(),
-            fetch_docs_request.partial_hits,
-            storage,
-            &fetch_docs_request.split_offsets,
-            doc_mapper,
-            snippet_request_opt,
-        )
-        .await?;
+        let index_id = fetch_docs_request.index_id.clone();
+        let fetch_docs_fut = async {
+            let index_uri = Uri::from_str(&fetch_docs_request.index_uri)?;
+            let storage = self.storage_resolver.resolve(&index_uri).await?;
+            let snippet_request_opt: Option<&SnippetRequest> =
+                fetch_docs_request.snippet_request.as_ref();
+            let doc_mapper = deserialize_doc_mapper(&fetch_docs_request.doc_mapper)?;
+            fetch_docs(
+                self.searcher_context.clone(),
+                fetch_docs_request.partial_hits,
+                storage,
+                &fetch_docs_request.split_offsets,
+                doc_mapper,
+                snippet_request_opt,
+            )
+            .await
+        };
+        let fetch_docs_response =
+            with_request_metrics("fetch_docs", &index_id, fetch_docs_fut).await?;
 
         Ok(fetch_docs_response)
     }
@@ -358,6 +535,17 @@ impl SearchService for SearchServiceImpl {
         )
         .await
     }
+
+    async fn health_check(
+        &self,
+        _health_check_request: HealthCheckRequest,
+    ) -> crate::Result<HealthCheckResponse> {
+        let health_check_response = HealthCheckResponse {
+            uptime_secs: self.start_instant.elapsed().as_secs(),
+            num_indexes: self.num_indexes.load(Ordering::Relaxed),
+        };
+        Ok(health_check_response)
+    }
 }
 
 pub(crate) async fn scroll(
@@ -428,6 +616,13 @@ pub(crate) async fn scroll(
         scroll_id: next_scroll_id.as_ref().map(ToString::to_string),
         errors: Vec::new(),
         aggregation: None,
+        failed_splits: Vec::new(),
+        next_page_token: None,
+        stats: None,
+        failed_indexes: Vec::new(),
+        num_hits_accuracy: HitCountAccuracy::Exact as i32,
+        timed_out: false,
+        warnings: Vec::new(),
     })
 }
 /// [`SearcherContext`] provides a common set of variables
@@ -450,6 +645,13 @@ pub struct SearcherContext {
     pub split_cache_opt: Option<Arc<SplitCache>>,
     /// List fields cache. Caches the list fields response for a given split.
     pub list_fields_cache: ListFieldsCache,
+    /// Root search response cache. `None` if no search response cache is configured.
+    pub search_response_cache_opt: Option<SearchResponseCache>,
+    /// Synonym tables of the indexes, used to expand queries on the root.
+    pub synonym_table_cache: SynonymTableCache,
+    /// Last index metadata and split lists fetched from the metastore, served while the
+    /// metastore is unreachable.
+    pub metastore_fallback_cache: MetastoreFallbackCache,
 }
 
 impl std::fmt::Debug for SearcherContext {
@@ -490,6 +692,12 @@ impl SearcherContext {
             LeafSearchCache::new(searcher_config.partial_request_cache_capacity.as_u64() as usize);
         let list_fields_cache =
             ListFieldsCache::new(searcher_config.partial_request_cache_capacity.as_u64() as usize);
+        let search_response_cache_opt = searcher_config
+            .search_response_cache
+            .map(SearchResponseCache::new);
+        let synonym_table_cache = SynonymTableCache::new(StorageResolver::unconfigured());
+        let metastore_fallback_cache =
+            MetastoreFallbackCache::new(searcher_config.metastore_max_staleness());
 
         Self {
             searcher_config,
@@ -500,6 +708,9 @@ impl SearcherContext {
             leaf_search_cache,
             list_fields_cache,
             split_cache_opt,
+            search_response_cache_opt,
+            synonym_table_cache,
+            metastore_fallback_cache,
         }
     }
 
@@ -511,3 +722,76 @@ impl SearcherContext {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use quickwit_metastore::IndexMetadata;
+    use quickwit_proto::metastore::{ListIndexesMetadataResponse, MetastoreError};
+
+    use super::*;
+    use crate::SearchJobPlacer;
+
+    #[tokio::test]
+    async fn test_health_check_reports_cached_num_indexes() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        let num_list_calls = Arc::new(AtomicUsize::new(0));
+        let num_list_calls_clone = num_list_calls.clone();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .returning(move |_list_indexes_request| {
+                // The metastore becomes unavailable after the first call.
+                if num_list_calls_clone.fetch_add(1, Ordering::Relaxed) > 0 {
+                    return Err(MetastoreError::Unavailable(
+                        "metastore is unavailable".to_string(),
+                    ));
+                }
+                let indexes_metadata = vec![
+                    IndexMetadata::for_test("test-index-1", "ram:///test-index-1"),
+                    IndexMetadata::for_test("test-index-2", "ram:///test-index-2"),
+                ];
+                Ok(
+                    ListIndexesMetadataResponse::try_from_indexes_metadata(indexes_metadata)
+                        .unwrap(),
+                )
+            });
+        let search_service = SearchServiceImpl::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+            ClusterClient::new(SearchJobPlacer::default()),
+            Arc::new(SearcherContext::for_test()),
+        );
+        // Health checks do not call the metastore, so they succeed before the first refresh.
+        let health_check_response = search_service
+            .health_check(HealthCheckRequest {})
+            .await
+            .unwrap();
+        assert_eq!(health_check_response.num_indexes, 0);
+        assert_eq!(num_list_calls.load(Ordering::Relaxed), 0);
+
+        refresh_num_indexes(
+            search_service.metastore.clone(),
+            &search_service.num_indexes,
+        )
+        .await;
+        let health_check_response = search_service
+            .health_check(HealthCheckRequest {})
+            .await
+            .unwrap();
+        assert_eq!(health_check_response.num_indexes, 2);
+
+        // A failed refresh keeps the previous count and does not fail the health check.
+        refresh_num_indexes(
+            search_service.metastore.clone(),
+            &search_service.num_indexes,
+        )
+        .await;
+        let health_check_response = search_service
+            .health_check(HealthCheckRequest {})
+            .await
+            .unwrap();
+        assert_eq!(health_check_response.num_indexes, 2);
+        assert_eq!(num_list_calls.load(Ordering::Relaxed), 2);
+    }
+}
@@ -0,0 +1,227 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Context;
+use quickwit_config::SynonymsConfig;
+use quickwit_proto::types::IndexUid;
+use quickwit_query::query_ast::SynonymTable;
+use quickwit_storage::{load_file, StorageResolver};
+use tokio::time::Instant;
+use tracing::warn;
+
+/// A cache of the synonym tables of each index.
+///
+/// Tables backed by a rules file are reloaded once their refresh interval has elapsed. If the
+/// reload fails, the previous table keeps being served until the next refresh.
+pub struct SynonymTableCache {
+    storage_resolver: StorageResolver,
+    tables: Mutex<HashMap<IndexUid, CachedSynonymTable>>,
+}
+
+struct CachedSynonymTable {
+    synonyms_config: SynonymsConfig,
+    synonym_table: Arc<SynonymTable>,
+    loaded_at: Instant,
+}
+
+impl SynonymTableCache {
+    pub fn new(storage_resolver: StorageResolver) -> Self {
+        SynonymTableCache {
+            storage_resolver,
+            tables: Mutex::default(),
+        }
+    }
+
+    /// Returns the synonym table of the index, loading it if necessary.
+    pub async fn get(
+        &self,
+        index_uid: &IndexUid,
+        synonyms_config: &SynonymsConfig,
+    ) -> anyhow::Result<Arc<SynonymTable>> {
+        let refresh_interval = synonyms_config.refresh_interval();
+        let stale_synonym_table_opt = {
+            let tables_lock = self.tables.lock().unwrap();
+            match tables_lock.get(index_uid) {
+                Some(cached) if cached.synonyms_config == *synonyms_config => {
+                    if synonyms_config.rules_uri.is_none()
+                        || cached.loaded_at.elapsed() < refresh_interval
+                    {
+                        return Ok(cached.synonym_table.clone());
+                    }
+                    Some(cached.synonym_table.clone())
+                }
+                _ => None,
+            }
+        };
+        let synonym_table = match self.load(synonyms_config).await {
+            Ok(synonym_table) => Arc::new(synonym_table),
+            Err(error) => {
+                let Some(stale_synonym_table) = stale_synonym_table_opt else {
+                    return Err(error);
+                };
+                warn!(
+                    index_uid=%index_uid,
+                    error=?error,
+                    "failed to reload synonym rules, keeping previous rules"
+                );
+                stale_synonym_table
+            }
+        };
+        let cached = CachedSynonymTable {
+            synonyms_config: synonyms_config.clone(),
+            synonym_table: synonym_table.clone(),
+            loaded_at: Instant::now(),
+        };
+        self.tables
+            .lock()
+            .unwrap()
+            .insert(index_uid.clone(), cached);
+        Ok(synonym_table)
+    }
+
+    async fn load(&self, synonyms_config: &SynonymsConfig) -> anyhow::Result<SynonymTable> {
+        let rules_file_content = if let Some(rules_uri) = &synonyms_config.rules_uri {
+            let rules_file = load_file(&self.storage_resolver, rules_uri)
+                .await
+                .with_context(|| format!("failed to load synonym rules file `{rules_uri}`"))?;
+            String::from_utf8(rules_file.as_slice().to_vec())
+                .with_context(|| format!("synonym rules file `{rules_uri}` is not valid UTF-8"))?
+        } else {
+            String::new()
+        };
+        let rules = synonyms_config
+            .rules
+            .iter()
+            .map(String::as_str)
+            .chain(rules_file_content.lines());
+        SynonymTable::parse(rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    use quickwit_common::uri::Uri;
+
+    use super::*;
+
+    async fn write_rules(storage_resolver: &StorageResolver, rules: &str) {
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///synonyms"))
+            .await
+            .unwrap();
+        storage
+            .put(Path::new("rules.txt"), Box::new(rules.as_bytes().to_vec()))
+            .await
+            .unwrap();
+    }
+
+    fn synonyms_config(refresh_interval: &str) -> SynonymsConfig {
+        SynonymsConfig {
+            rules: vec!["k8s, kubernetes".to_string()],
+            rules_uri: Some(Uri::from_str("ram:///synonyms/rules.txt").unwrap()),
+            refresh_interval: refresh_interval.parse().unwrap(),
+            max_expansions: 10,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_synonym_table_cache_merges_inline_and_file_rules() {
+        let storage_resolver = StorageResolver::for_test();
+        write_rules(&storage_resolver, "lb => load balancer\n").await;
+        let synonym_table_cache = SynonymTableCache::new(storage_resolver);
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+
+        let synonym_table = synonym_table_cache
+            .get(&index_uid, &synonyms_config("1h"))
+            .await
+            .unwrap();
+        assert_eq!(synonym_table.expansions("k8s").unwrap(), ["kubernetes"]);
+        assert_eq!(synonym_table.expansions("lb").unwrap(), ["load balancer"]);
+    }
+
+    #[tokio::test]
+    async fn test_synonym_table_cache_reloads_rules_file() {
+        let storage_resolver = StorageResolver::for_test();
+        write_rules(&storage_resolver, "lb => load balancer\n").await;
+        let synonym_table_cache = SynonymTableCache::new(storage_resolver.clone());
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+
+        synonym_table_cache
+            .get(&index_uid, &synonyms_config("1h"))
+            .await
+            .unwrap();
+        write_rules(&storage_resolver, "lb => loadbalancer\n").await;
+
+        // The refresh interval has not elapsed yet.
+        let synonym_table = synonym_table_cache
+            .get(&index_uid, &synonyms_config("1h"))
+            .await
+            .unwrap();
+        assert_eq!(synonym_table.expansions("lb").unwrap(), ["load balancer"]);
+
+        let synonym_table = synonym_table_cache
+            .get(&index_uid, &synonyms_config("0s"))
+            .await
+            .unwrap();
+        assert_eq!(synonym_table.expansions("lb").unwrap(), ["loadbalancer"]);
+
+        // Invalid rules do not evict the previous table.
+        write_rules(&storage_resolver, "lb =>\n").await;
+        let synonym_table = synonym_table_cache
+            .get(&index_uid, &synonyms_config("0s"))
+            .await
+            .unwrap();
+        assert_eq!(synonym_table.expansions("lb").unwrap(), ["loadbalancer"]);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_synonym_table_cache_refreshes_after_interval() {
+        let storage_resolver = StorageResolver::for_test();
+        write_rules(&storage_resolver, "lb => load balancer\n").await;
+        let synonym_table_cache = SynonymTableCache::new(storage_resolver.clone());
+        let index_uid = IndexUid::new_with_random_ulid("test-index");
+
+        synonym_table_cache
+            .get(&index_uid, &synonyms_config("1m"))
+            .await
+            .unwrap();
+        write_rules(&storage_resolver, "lb => loadbalancer\n").await;
+
+        tokio::time::advance(Duration::from_secs(30)).await;
+        let synonym_table = synonym_table_cache
+            .get(&index_uid, &synonyms_config("1m"))
+            .await
+            .unwrap();
+        assert_eq!(synonym_table.expansions("lb").unwrap(), ["load balancer"]);
+
+        tokio::time::advance(Duration::from_secs(31)).await;
+        let synonym_table = synonym_table_cache
+            .get(&index_uid, &synonyms_config("1m"))
+            .await
+            .unwrap();
+        assert_eq!(synonym_table.expansions("lb").unwrap(), ["loadbalancer"]);
+    }
+}
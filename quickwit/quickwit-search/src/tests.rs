@@ -20,6 +20,7 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use assert_json_diff::{assert_json_eq, assert_json_include};
+use itertools::Itertools;
 use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
 use quickwit_doc_mapper::DefaultDocMapper;
@@ -30,8 +31,9 @@ use quickwit_proto::search::{
     SortValue,
 };
 use quickwit_query::query_ast::{
-    qast_helper, qast_json_helper, query_ast_from_user_text, QueryAst,
+    qast_helper, qast_json_helper, query_ast_from_user_text, QueryAst, UserInputQuery,
 };
+use quickwit_query::BooleanOperand;
 use serde_json::{json, Value as JsonValue};
 use tantivy::schema::OwnedValue as TantivyValue;
 use tantivy::time::OffsetDateTime;
@@ -81,6 +83,114 @@ async fn test_single_node_simple() -> anyhow::Result<()> {
     assert_json_include!(actual: hit_json, expected: expected_json);
     assert!(single_node_result.elapsed_time_micros > 10);
     assert!(single_node_result.elapsed_time_micros < 1_000_000);
+    let stats = single_node_result.stats.unwrap();
+    assert_eq!(stats.num_attempted_splits, 1);
+    assert_eq!(stats.split_stats.len(), 1);
+    assert_eq!(stats.split_stats[0].num_docs_scanned, 1);
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_fuzzy_search() -> anyhow::Result<()> {
+    let index_id = "single-node-fuzzy-search";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![
+            json!({"body": "snoopy"}),
+            json!({"body": "snoop dogg"}),
+            json!({"body": "beagle"}),
+        ])
+        .await?;
+    // Only the postings of the terms matched by the fuzzy term are warmed up, so the search
+    // fails if it needs any other posting.
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("snoopyy", &["body"]),
+        max_hits: 10,
+        fuzzy_distance: Some(1),
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 1);
+
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("snoopyy", &["body"]),
+        max_hits: 10,
+        fuzzy_distance: Some(2),
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    assert_eq!(single_node_result.num_hits, 2);
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_search_metrics() -> anyhow::Result<()> {
+    let index_id = "single-node-search-metrics";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    test_sandbox
+        .add_documents(vec![json!({"body": "snoopy"}), json!({"body": "beagle"})])
+        .await?;
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: qast_json_helper("snoopy", &["body"]),
+        max_hits: 2,
+        ..Default::default()
+    };
+    single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await?;
+    // The leaf search and fetch docs requests go through the search service, which labels them
+    // with the index ID.
+    for operation in ["leaf_search", "fetch_docs"] {
+        assert_eq!(
+            SEARCH_METRICS
+                .requests_total
+                .with_label_values([operation, index_id])
+                .get(),
+            1
+        );
+        assert_eq!(
+            SEARCH_METRICS
+                .request_errors_total
+                .with_label_values([operation, index_id])
+                .get(),
+            0
+        );
+    }
+    assert_eq!(
+        SEARCH_METRICS
+            .leaf_search_failed_splits_total
+            .with_label_values([index_id])
+            .get(),
+        0
+    );
     test_sandbox.assert_quit().await;
     Ok(())
 }
@@ -263,6 +373,99 @@ async fn test_slop_queries() {
     test_sandbox.assert_quit().await;
 }
 
+async fn search_titles_with_default_operator(
+    test_sandbox: &TestSandbox,
+    index_id: &str,
+    query: &str,
+    default_operator: BooleanOperand,
+) -> Vec<String> {
+    let query_ast: QueryAst = UserInputQuery {
+        user_text: query.to_string(),
+        default_fields: Some(vec!["body".to_string()]),
+        default_operator,
+    }
+    .into();
+    let search_request = SearchRequest {
+        index_id_patterns: vec![index_id.to_string()],
+        query_ast: serde_json::to_string(&query_ast).unwrap(),
+        max_hits: 10,
+        ..Default::default()
+    };
+    let single_node_result = single_node_search(
+        search_request,
+        test_sandbox.metastore(),
+        test_sandbox.storage_resolver(),
+    )
+    .await
+    .unwrap();
+    single_node_result
+        .hits
+        .iter()
+        .map(|hit| {
+            let hit_json: JsonValue = serde_json::from_str(&hit.json).unwrap();
+            hit_json["title"].as_str().unwrap().to_string()
+        })
+        .sorted()
+        .collect()
+}
+
+#[tokio::test]
+async fn test_phrase_queries_with_default_operator() {
+    let index_id = "phrase-query-default-operator";
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: title
+                type: text
+              - name: body
+                type: text
+                record: position
+        "#;
+
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"])
+        .await
+        .unwrap();
+    let docs = vec![
+        json!({"title": "one", "body": "the quick brown fox"}),
+        json!({"title": "two", "body": "the brown and quick fox"}),
+        json!({"title": "three", "body": "a lazy dog"}),
+    ];
+    test_sandbox.add_documents(docs).await.unwrap();
+
+    // The phrase only matches the document containing its tokens in order.
+    for default_operator in [BooleanOperand::And, BooleanOperand::Or] {
+        assert_eq!(
+            search_titles_with_default_operator(
+                &test_sandbox,
+                index_id,
+                "body:\"quick brown fox\"",
+                default_operator,
+            )
+            .await,
+            ["one"]
+        );
+    }
+    // The default operator combines the phrase with the field-scoped term.
+    assert!(search_titles_with_default_operator(
+        &test_sandbox,
+        index_id,
+        "body:\"quick brown\" title:three",
+        BooleanOperand::And,
+    )
+    .await
+    .is_empty());
+    assert_eq!(
+        search_titles_with_default_operator(
+            &test_sandbox,
+            index_id,
+            "body:\"quick brown\" title:three",
+            BooleanOperand::Or,
+        )
+        .await,
+        ["one", "three"]
+    );
+    test_sandbox.assert_quit().await;
+}
+
 // TODO remove me once `Iterator::is_sorted_by_key` is stabilized.
 fn is_sorted<E, I: Iterator<Item = E>>(mut it: I) -> bool
 where E: Ord {
@@ -502,8 +705,8 @@ async fn test_single_node_without_timestamp_with_query_start_timestamp_enabled(
     assert_eq!(
         single_node_response.err().map(|err| err.to_string()),
         Some(
-            "the timestamp field is not set in index: [\"single-node-no-timestamp\"] definition \
-             but start-timestamp or end-timestamp are set in the query"
+            "timestamp filtering is not supported for index `single-node-no-timestamp`: its doc \
+             mapping has no timestamp field but a start or end timestamp is set in the request"
                 .to_string()
         )
     );
@@ -1059,6 +1262,8 @@ async fn test_search_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
         test_sandbox.storage(),
         splits_offsets,
         test_sandbox.doc_mapper(),
+        None,
+        1,
     )
     .await
     .unwrap();
@@ -1069,6 +1274,72 @@ async fn test_search_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
         .collect::<Vec<u32>>()
 }
 
+#[tokio::test]
+async fn test_leaf_search_reports_failed_splits() -> anyhow::Result<()> {
+    let doc_mapping_yaml = r#"
+            field_mappings:
+              - name: body
+                type: text
+        "#;
+    let test_sandbox = TestSandbox::create(
+        "leaf_search_failed_splits",
+        doc_mapping_yaml,
+        "{}",
+        &["body"],
+    )
+    .await?;
+    let docs = vec![
+        json!({"body": "hello happy tax payer"}),
+        json!({"body": "hello"}),
+    ];
+    test_sandbox.add_documents(docs).await?;
+
+    let splits = test_sandbox
+        .metastore()
+        .list_splits(ListSplitsRequest::try_from_index_uid(test_sandbox.index_uid()).unwrap())
+        .await?
+        .collect_splits()
+        .await?;
+    let mut splits_offsets: Vec<_> = splits
+        .into_iter()
+        .map(|split| extract_split_and_footer_offsets(&split.split_metadata))
+        .collect();
+    // This split does not exist in the index storage, so opening it fails.
+    splits_offsets.push(SplitIdAndFooterOffsets {
+        split_id: "missing-split".to_string(),
+        split_footer_start: 0,
+        split_footer_end: 100,
+        ..Default::default()
+    });
+    let request = Arc::new(SearchRequest {
+        index_id_patterns: vec![test_sandbox.index_uid().index_id().to_string()],
+        query_ast: qast_json_helper("hello", &["body"]),
+        max_hits: 10,
+        ..Default::default()
+    });
+    let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default(), None));
+    let leaf_search_response = leaf_search(
+        searcher_context,
+        request,
+        test_sandbox.storage(),
+        splits_offsets,
+        test_sandbox.doc_mapper(),
+        None,
+        1,
+    )
+    .await?;
+    assert_eq!(leaf_search_response.num_hits, 2);
+    assert_eq!(leaf_search_response.failed_splits.len(), 1);
+
+    let failed_split = &leaf_search_response.failed_splits[0];
+    assert_eq!(failed_split.split_id, "missing-split");
+    assert!(!failed_split.error.is_empty());
+    assert!(failed_split.retryable_error);
+
+    test_sandbox.assert_quit().await;
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_search_dynamic_mode() -> anyhow::Result<()> {
     let doc_mapping_yaml = r#"
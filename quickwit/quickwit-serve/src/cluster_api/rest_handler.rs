@@ -19,7 +19,10 @@
 
 use std::convert::Infallible;
 
-use quickwit_cluster::{Cluster, ClusterSnapshot, NodeIdSchema};
+use quickwit_cluster::{
+    Cluster, ClusterMemberInfo, ClusterMemberState, ClusterSnapshot, NodeIdSchema,
+};
+use quickwit_search::{CircuitBreakerState, NodeCircuitBreakerInfo, SearchJobPlacer};
 use warp::{Filter, Rejection};
 
 use crate::format::extract_format_from_qs;
@@ -27,14 +30,30 @@ use crate::json_api_response::make_json_api_response;
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(get_cluster),
-    components(schemas(ClusterSnapshot, NodeIdSchema,))
+    paths(get_cluster, get_cluster_members, get_cluster_nodes),
+    components(schemas(
+        ClusterSnapshot,
+        NodeIdSchema,
+        ClusterMemberInfo,
+        ClusterMemberState,
+        NodeCircuitBreakerInfo,
+        CircuitBreakerState,
+    ))
 )]
 pub struct ClusterApi;
 
 /// Cluster handler.
 pub fn cluster_handler(
     cluster: Cluster,
+    search_job_placer: SearchJobPlacer,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    get_cluster_handler(cluster.clone())
+        .or(get_cluster_members_handler(cluster))
+        .or(get_cluster_nodes_handler(search_job_placer))
+}
+
+fn get_cluster_handler(
+    cluster: Cluster,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     warp::path!("cluster")
         .and(warp::path::end())
@@ -59,3 +78,58 @@ async fn get_cluster(cluster: Cluster) -> Result<ClusterSnapshot, Infallible> {
     let snapshot = cluster.snapshot().await;
     Ok(snapshot)
 }
+
+fn get_cluster_members_handler(
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "members")
+        .and(warp::get())
+        .and(warp::path::end().map(move || cluster.clone()))
+        .then(get_cluster_members)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/members",
+    responses(
+        (status = 200, description = "Successfully fetched the cluster members.", body = [ClusterMemberInfo])
+    )
+)]
+
+/// Get the members of the cluster known by the node, with their liveness state.
+async fn get_cluster_members(cluster: Cluster) -> Result<Vec<ClusterMemberInfo>, Infallible> {
+    let members_info = cluster.members_info().await;
+    Ok(members_info)
+}
+
+fn get_cluster_nodes_handler(
+    search_job_placer: SearchJobPlacer,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "nodes")
+        .and(warp::get())
+        .and(warp::path::end().map(move || search_job_placer.clone()))
+        .then(get_cluster_nodes)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/nodes",
+    responses(
+        (status = 200, description = "Successfully fetched the circuit breaker states of the searchers.", body = [NodeCircuitBreakerInfo])
+    )
+)]
+
+/// Get the circuit breaker state of the searcher nodes, as seen by the node when it places leaf
+/// search requests.
+async fn get_cluster_nodes(
+    search_job_placer: SearchJobPlacer,
+) -> Result<Vec<NodeCircuitBreakerInfo>, Infallible> {
+    let node_infos = search_job_placer.node_circuit_breaker_infos();
+    Ok(node_infos)
+}
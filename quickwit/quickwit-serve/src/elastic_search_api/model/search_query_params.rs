@@ -207,6 +207,20 @@ impl SearchQueryParams {
         })?;
         Ok(Some(duration))
     }
+
+    /// Returns the search timeout supplied by the user.
+    ///
+    /// This function returns an error if the timeout is not in the expected format. (`500ms`, `5s`
+    /// etc.)
+    pub fn parse_timeout(&self) -> Result<Option<Duration>, SearchError> {
+        let Some(timeout_str) = self.timeout.as_ref() else {
+            return Ok(None);
+        };
+        let duration: Duration = humantime::parse_duration(timeout_str).map_err(|_err| {
+            SearchError::InvalidArgument(format!("invalid timeout: `{timeout_str}`"))
+        })?;
+        Ok(Some(duration))
+    }
 }
 
 #[doc = "Whether to expand wildcard expression to concrete indices that are open, closed or both."]
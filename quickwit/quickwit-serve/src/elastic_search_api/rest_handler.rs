@@ -205,6 +205,9 @@ fn build_request_for_es_api(
 
     let scroll_duration: Option<Duration> = search_params.parse_scroll_ttl()?;
     let scroll_ttl_secs: Option<u32> = scroll_duration.map(|duration| duration.as_secs() as u32);
+    let timeout_millis: Option<u64> = search_params
+        .parse_timeout()?
+        .map(|timeout| timeout.as_millis() as u64);
 
     let has_doc_id_field = sort_fields.iter().any(is_doc_field);
     let search_after = partial_hit_from_search_after_param(search_body.search_after, &sort_fields)?;
@@ -223,6 +226,13 @@ fn build_request_for_es_api(
             scroll_ttl_secs,
             search_after,
             count_hits,
+            tags: Vec::new(),
+            disable_synonyms: false,
+            snapshot_id: None,
+            geo_filters: Vec::new(),
+            timeout_millis,
+            fuzzy_distance: None,
+            num_searchers: None,
         },
         has_doc_id_field,
     ))
@@ -479,7 +489,7 @@ fn convert_to_es_search_response(
         None
     };
     ElasticSearchResponse {
-        timed_out: false,
+        timed_out: resp.timed_out,
         hits: HitsMetadata {
             total: Some(TotalHits {
                 value: resp.num_hits,
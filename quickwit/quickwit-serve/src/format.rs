@@ -17,7 +17,11 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use hyper::header::{ACCEPT, CONTENT_TYPE};
+use mime_guess::Mime;
+use serde::de::DeserializeOwned;
 use serde::{self, Deserialize, Serialize, Serializer};
+use thiserror::Error;
 use warp::{Filter, Rejection};
 
 /// Body output format used for the REST API.
@@ -28,6 +32,9 @@ pub enum BodyFormat {
     Json,
     #[default]
     PrettyJson,
+    #[serde(rename = "msgpack")]
+    MessagePack,
+    Cbor,
 }
 
 impl BodyFormat {
@@ -49,6 +56,56 @@ impl BodyFormat {
             Self::PrettyJson => serde_json::to_vec_pretty(value).map_err(|_| {
                 tracing::error!("the response serialization failed");
             }),
+            // Structs are serialized as maps keyed by field name so that the payload is
+            // self-describing, like its JSON counterpart.
+            Self::MessagePack => rmp_serde::to_vec_named(value).map_err(|_| {
+                tracing::error!("the response serialization failed");
+            }),
+            Self::Cbor => {
+                let mut buffer = Vec::new();
+                ciborium::ser::into_writer(value, &mut buffer).map_err(|_| {
+                    tracing::error!("the response serialization failed");
+                })?;
+                Ok(buffer)
+            }
+        }
+    }
+
+    /// Deserializes a request body encoded in this format.
+    pub(crate) fn parse_body<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, String> {
+        match &self {
+            Self::Json | Self::PrettyJson => {
+                serde_json::from_slice(bytes).map_err(|error| error.to_string())
+            }
+            Self::MessagePack => rmp_serde::from_slice(bytes).map_err(|error| error.to_string()),
+            Self::Cbor => ciborium::de::from_reader(bytes).map_err(|error| error.to_string()),
+        }
+    }
+
+    /// Returns the value of the `Content-Type` header of bodies serialized in this format.
+    pub(crate) fn content_type(&self) -> &'static str {
+        match &self {
+            Self::Json | Self::PrettyJson => "application/json",
+            Self::MessagePack => "application/msgpack",
+            Self::Cbor => "application/cbor",
+        }
+    }
+
+    /// Returns `true` for the binary formats, which can only be negotiated through the
+    /// `Accept` and `Content-Type` headers.
+    pub(crate) fn is_binary(&self) -> bool {
+        matches!(self, Self::MessagePack | Self::Cbor)
+    }
+
+    fn from_mime(mime: &Mime) -> Option<BodyFormat> {
+        if mime.type_() != mime_guess::mime::APPLICATION {
+            return None;
+        }
+        match mime.subtype().as_str() {
+            "json" => Some(Self::Json),
+            "msgpack" | "x-msgpack" | "vnd.msgpack" => Some(Self::MessagePack),
+            "cbor" => Some(Self::Cbor),
+            _ => None,
         }
     }
 }
@@ -58,6 +115,8 @@ impl ToString for BodyFormat {
         match &self {
             Self::Json => "json".to_string(),
             Self::PrettyJson => "pretty_json".to_string(),
+            Self::MessagePack => "msgpack".to_string(),
+            Self::Cbor => "cbor".to_string(),
         }
     }
 }
@@ -84,3 +143,180 @@ pub(crate) fn extract_format_from_qs(
     serde_qs::warp::query::<FormatQueryString>(serde_qs::Config::default())
         .map(|format_qs: FormatQueryString| format_qs.format)
 }
+
+#[derive(Debug, Error)]
+#[error(
+    "none of the media types accepted by the client are supported. choices are application/json, \
+     application/msgpack and application/cbor"
+)]
+pub(crate) struct NotAcceptable;
+
+impl warp::reject::Reject for NotAcceptable {}
+
+/// Picks the response format from the value of an `Accept` header.
+///
+/// Returns `Ok(None)` when JSON should be used, in which case the format requested in the query
+/// string (compact or pretty JSON) applies. Media types that cannot be parsed are ignored, and so
+/// is the whole header if it only contains such media types. Returns an error if the header
+/// explicitly lists media types that are all unsupported.
+fn negotiate_accept_header(accept: &str) -> Result<Option<BodyFormat>, NotAcceptable> {
+    let mut best_candidate_opt: Option<(Option<BodyFormat>, f32)> = None;
+    let mut has_unparsable_media_type = false;
+
+    for media_range in accept.split(',').map(str::trim) {
+        if media_range.is_empty() {
+            continue;
+        }
+        let Ok(mime) = media_range.parse::<Mime>() else {
+            has_unparsable_media_type = true;
+            continue;
+        };
+        let quality = mime
+            .get_param("q")
+            .and_then(|quality| quality.as_str().parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if quality <= 0.0 {
+            continue;
+        }
+        let candidate = if mime.subtype() == mime_guess::mime::STAR
+            && (mime.type_() == mime_guess::mime::STAR
+                || mime.type_() == mime_guess::mime::APPLICATION)
+        {
+            None
+        } else {
+            match BodyFormat::from_mime(&mime) {
+                Some(body_format) if body_format.is_binary() => Some(body_format),
+                Some(_) => None,
+                None => continue,
+            }
+        };
+        if best_candidate_opt
+            .map(|(_, best_quality)| quality > best_quality)
+            .unwrap_or(true)
+        {
+            best_candidate_opt = Some((candidate, quality));
+        }
+    }
+    match best_candidate_opt {
+        Some((candidate, _)) => Ok(candidate),
+        None if has_unparsable_media_type || accept.trim().is_empty() => Ok(None),
+        None => Err(NotAcceptable),
+    }
+}
+
+/// Extracts the binary response format requested in the `Accept` header, if any.
+///
+/// Rejects the request with [`NotAcceptable`] if the client only accepts unsupported formats.
+pub(crate) fn extract_format_from_accept_header(
+) -> impl Filter<Extract = (Option<BodyFormat>,), Error = Rejection> + Clone {
+    warp::header::optional::<String>(ACCEPT.as_str()).and_then(
+        |accept_opt: Option<String>| async move {
+            let Some(accept) = accept_opt else {
+                return Ok(None);
+            };
+            negotiate_accept_header(&accept).map_err(warp::reject::custom)
+        },
+    )
+}
+
+/// Extracts the binary format of the request body from the `Content-Type` header, if any.
+///
+/// Other content types are not rejected: the body is then processed as JSON.
+pub(crate) fn extract_format_from_content_type(
+) -> impl Filter<Extract = (Option<BodyFormat>,), Error = Rejection> + Clone {
+    warp::header::optional::<Mime>(CONTENT_TYPE.as_str()).map(|mime_opt: Option<Mime>| {
+        mime_opt
+            .as_ref()
+            .and_then(BodyFormat::from_mime)
+            .filter(BodyFormat::is_binary)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_search::SearchResponseRest;
+    use serde_json::{json, Value as JsonValue};
+
+    use super::*;
+
+    #[test]
+    fn test_negotiate_accept_header() {
+        let test_cases = [
+            ("", Ok(None)),
+            ("*/*", Ok(None)),
+            ("application/*", Ok(None)),
+            ("application/json", Ok(None)),
+            ("application/msgpack", Ok(Some(BodyFormat::MessagePack))),
+            ("application/x-msgpack", Ok(Some(BodyFormat::MessagePack))),
+            ("application/vnd.msgpack", Ok(Some(BodyFormat::MessagePack))),
+            ("application/cbor", Ok(Some(BodyFormat::Cbor))),
+            ("text/html, application/cbor", Ok(Some(BodyFormat::Cbor))),
+            (
+                "application/json;q=0.5, application/cbor",
+                Ok(Some(BodyFormat::Cbor)),
+            ),
+            ("application/json, application/cbor;q=0.5", Ok(None)),
+            ("application/msgpack;q=0, */*", Ok(None)),
+            ("text/html, */*;q=0.8", Ok(None)),
+            ("not a media type", Ok(None)),
+            ("application/xml", Err(())),
+            ("text/csv, application/xml;q=0.9", Err(())),
+            ("application/cbor;q=0", Err(())),
+        ];
+        for (accept, expected) in test_cases {
+            assert_eq!(
+                negotiate_accept_header(accept).map_err(|_| ()),
+                expected,
+                "accept header: `{accept}`"
+            );
+        }
+    }
+
+    fn test_search_response_rest() -> SearchResponseRest {
+        SearchResponseRest {
+            num_hits: 2,
+            hits: vec![
+                json!({"title": "foo", "count": 42, "score": 1.5, "tags": ["a", "b"]}),
+                json!({"title": "bar", "count": -3, "nested": {"flag": true, "none": null}}),
+            ],
+            snippets: None,
+            hit_index_ids: None,
+            elapsed_time_micros: 16,
+            errors: vec!["split error".to_string()],
+            aggregations: Some(json!({"count_by_tag": {"buckets": []}})),
+            next_page_token: None,
+            stats: None,
+            failed_indexes: Vec::new(),
+            num_hits_accuracy: None,
+            timed_out: false,
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_body_format_msgpack_round_trip() {
+        let search_response = test_search_response_rest();
+        let body = BodyFormat::MessagePack
+            .result_to_vec::<_, ()>(&Ok(&search_response))
+            .unwrap();
+        let deserialized: SearchResponseRest = BodyFormat::MessagePack.parse_body(&body).unwrap();
+        assert_eq!(deserialized, search_response);
+        // Numbers are encoded natively rather than as strings.
+        let value: JsonValue = rmp_serde::from_slice(&body).unwrap();
+        assert!(value["num_hits"].is_u64());
+        assert!(value["hits"][0]["score"].is_f64());
+    }
+
+    #[test]
+    fn test_body_format_cbor_round_trip() {
+        let search_response = test_search_response_rest();
+        let body = BodyFormat::Cbor
+            .result_to_vec::<_, ()>(&Ok(&search_response))
+            .unwrap();
+        let deserialized: SearchResponseRest = BodyFormat::Cbor.parse_body(&body).unwrap();
+        assert_eq!(deserialized, search_response);
+        let value: JsonValue = ciborium::de::from_reader(&body[..]).unwrap();
+        assert!(value["num_hits"].is_u64());
+        assert!(value["hits"][1]["count"].is_i64());
+    }
+}
@@ -20,10 +20,12 @@
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
 use quickwit_common::tower::BoxFutureInfaillible;
 use quickwit_config::service::QuickwitService;
 use quickwit_opentelemetry::otlp::{OtlpGrpcLogsService, OtlpGrpcTracesService};
+use quickwit_proto::index_changes::index_changes_service_server::IndexChangesServiceServer;
 use quickwit_proto::indexing::IndexingServiceClient;
 use quickwit_proto::jaeger::storage::v1::span_reader_plugin_server::SpanReaderPluginServer;
 use quickwit_proto::opentelemetry::proto::collector::logs::v1::logs_service_server::LogsServiceServer;
@@ -33,8 +35,9 @@ use quickwit_proto::tonic::codegen::CompressionEncoding;
 use quickwit_proto::tonic::transport::Server;
 use tracing::*;
 
+use crate::index_changes_api::GrpcIndexChangesAdapter;
 use crate::search_api::GrpcSearchAdapter;
-use crate::QuickwitServices;
+use crate::{serve_with_drain_timeout, QuickwitServices};
 
 /// Starts and binds gRPC services to `grpc_listen_addr`.
 pub(crate) async fn start_grpc_server(
@@ -42,6 +45,7 @@ pub(crate) async fn start_grpc_server(
     services: Arc<QuickwitServices>,
     readiness_trigger: BoxFutureInfaillible<()>,
     shutdown_signal: BoxFutureInfaillible<()>,
+    drain_timeout: Duration,
 ) -> anyhow::Result<()> {
     let mut enabled_grpc_services = BTreeSet::new();
     let mut server = Server::builder();
@@ -153,6 +157,15 @@ pub(crate) async fn start_grpc_server(
         None
     };
 
+    // Mount gRPC index changes service if the index change log is available on node.
+    let index_changes_grpc_service =
+        if let Some(index_change_log) = services.index_change_log_opt.clone() {
+            enabled_grpc_services.insert("index-changes");
+            let grpc_index_changes_service = GrpcIndexChangesAdapter::from(index_change_log);
+            Some(IndexChangesServiceServer::new(grpc_index_changes_service))
+        } else {
+            None
+        };
     // Mount gRPC jaeger service if present.
     let jaeger_grpc_service = if let Some(jaeger_service) = services.jaeger_service_opt.clone() {
         enabled_grpc_services.insert("jaeger");
@@ -162,6 +175,7 @@ pub(crate) async fn start_grpc_server(
     };
     let server_router = server
         .add_optional_service(control_plane_grpc_service)
+        .add_optional_service(index_changes_grpc_service)
         .add_optional_service(indexing_grpc_service)
         .add_optional_service(ingest_api_grpc_service)
         .add_optional_service(ingest_router_grpc_service)
@@ -177,7 +191,14 @@ pub(crate) async fn start_grpc_server(
         grpc_listen_addr=?grpc_listen_addr,
         "Starting gRPC server listening on {grpc_listen_addr}."
     );
-    let serve_fut = server_router.serve_with_shutdown(grpc_listen_addr, shutdown_signal);
+    let serve_fut = serve_with_drain_timeout(
+        "gRPC",
+        |graceful_shutdown_signal| {
+            server_router.serve_with_shutdown(grpc_listen_addr, graceful_shutdown_signal)
+        },
+        shutdown_signal,
+        drain_timeout,
+    );
     let (serve_res, _trigger_res) = tokio::join!(serve_fut, readiness_trigger);
     serve_res?;
     Ok(())
@@ -20,6 +20,6 @@
 mod rest_handler;
 
 pub use self::rest_handler::{
-    index_management_handlers, IndexApi, ListSplitsQueryParams, ListSplitsResponse,
-    UnsupportedContentType,
+    index_management_handlers, IndexApi, IndexSummary, ListSplitsQueryParams, ListSplitsResponse,
+    SplitMetadataUpdate, UnsupportedContentType,
 };
@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use bytes::Bytes;
@@ -30,12 +31,15 @@ use quickwit_doc_mapper::{analyze_text, TokenizerConfig};
 use quickwit_index_management::{IndexService, IndexServiceError};
 use quickwit_metastore::{
     IndexMetadata, IndexMetadataResponseExt, ListIndexesMetadataResponseExt, ListSplitsQuery,
-    ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, Split, SplitInfo, SplitState,
+    ListSplitsRequestExt, MetastoreServiceStreamSplitsExt, Split, SplitInfo, SplitMetadataPatch,
+    SplitState, UpdateSplitMetadataRequestExt,
 };
 use quickwit_proto::metastore::{
-    DeleteSourceRequest, EntityKind, IndexMetadataRequest, ListIndexesMetadataRequest,
+    CreateIndexSnapshotRequest, DeleteIndexSnapshotRequest, DeleteSourceRequest, EntityKind,
+    IndexMetadataRequest, IndexSnapshot, ListIndexSnapshotsRequest, ListIndexesMetadataRequest,
     ListSplitsRequest, MarkSplitsForDeletionRequest, MetastoreError, MetastoreResult,
     MetastoreService, MetastoreServiceClient, ResetSourceCheckpointRequest, ToggleSourceRequest,
+    UpdateSplitMetadataRequest,
 };
 use quickwit_proto::types::IndexUid;
 use serde::de::DeserializeOwned;
@@ -56,15 +60,27 @@ use crate::with_arg;
         clear_index,
         delete_index,
         get_indexes_metadatas,
+        list_indexes_summaries,
         list_splits,
         describe_index,
         mark_splits_for_deletion,
+        update_split_metadata,
+        create_index_snapshot,
+        list_index_snapshots,
+        delete_index_snapshot,
         create_source,
         reset_source_checkpoint,
         toggle_source,
         delete_source,
     ),
-    components(schemas(ToggleSource, SplitsForDeletion, IndexStats))
+    components(schemas(
+        ToggleSource,
+        SplitsForDeletion,
+        SplitMetadataUpdate,
+        IndexStats,
+        IndexSummary,
+        IndexSnapshot
+    ))
 )]
 pub struct IndexApi;
 
@@ -73,7 +89,9 @@ pub fn index_management_handlers(
     node_config: Arc<NodeConfig>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     // Indexes handlers.
-    get_index_metadata_handler(index_service.metastore())
+    // The summaries handler must come first: `_summary` would otherwise be matched as an index ID.
+    list_indexes_summaries_handler(index_service.metastore())
+        .or(get_index_metadata_handler(index_service.metastore()))
         .or(get_indexes_metadatas_handler(index_service.metastore()))
         .or(create_index_handler(index_service.clone(), node_config))
         .or(clear_index_handler(index_service.clone()))
@@ -82,6 +100,11 @@ pub fn index_management_handlers(
         .or(list_splits_handler(index_service.metastore()))
         .or(describe_index_handler(index_service.metastore()))
         .or(mark_splits_for_deletion_handler(index_service.metastore()))
+        .or(update_split_metadata_handler(index_service.metastore()))
+        // Snapshots handlers.
+        .or(create_index_snapshot_handler(index_service.metastore()))
+        .or(list_index_snapshots_handler(index_service.metastore()))
+        .or(delete_index_snapshot_handler(index_service.metastore()))
         // Sources handlers.
         .or(reset_source_checkpoint_handler(index_service.metastore()))
         .or(toggle_source_handler(index_service.metastore()))
@@ -255,6 +278,88 @@ fn describe_index_handler(
         .map(make_json_api_response)
 }
 
+/// Summarizes an index with its published documents.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IndexSummary {
+    pub index_id: String,
+    #[schema(value_type = String)]
+    pub index_uri: Uri,
+    pub num_published_splits: usize,
+    pub num_published_docs: u64,
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexes",
+    path = "/indexes/_summary",
+    responses(
+        (status = 200, description = "Successfully fetched the summaries of all indexes.", body = [IndexSummary])
+    ),
+)]
+/// Lists the summaries of all indexes.
+///
+/// The published splits of all the indexes are listed with a single metastore request.
+async fn list_indexes_summaries(
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<Vec<IndexSummary>> {
+    let indexes_metadata = metastore
+        .list_indexes_metadata(ListIndexesMetadataRequest::all())
+        .await?
+        .deserialize_indexes_metadata()?;
+    if indexes_metadata.is_empty() {
+        return Ok(Vec::new());
+    }
+    let index_uids: Vec<IndexUid> = indexes_metadata
+        .iter()
+        .map(|index_metadata| index_metadata.index_uid.clone())
+        .collect();
+    let query = ListSplitsQuery::try_from_index_uids(index_uids)?
+        .with_split_state(SplitState::Published);
+    let list_splits_request = ListSplitsRequest::try_from_list_splits_query(query)?;
+    let published_splits = metastore
+        .list_splits(list_splits_request)
+        .await?
+        .collect_splits()
+        .await?;
+    let mut published_stats_per_index: HashMap<IndexUid, (usize, u64)> = HashMap::new();
+
+    for split in published_splits {
+        let (num_splits, num_docs) = published_stats_per_index
+            .entry(split.split_metadata.index_uid)
+            .or_default();
+        *num_splits += 1;
+        *num_docs += split.split_metadata.num_docs as u64;
+    }
+    let indexes_summaries = indexes_metadata
+        .into_iter()
+        .map(|index_metadata| {
+            let (num_published_splits, num_published_docs) = published_stats_per_index
+                .get(&index_metadata.index_uid)
+                .copied()
+                .unwrap_or_default();
+            let index_config = index_metadata.into_index_config();
+            IndexSummary {
+                index_id: index_config.index_id,
+                index_uri: index_config.index_uri,
+                num_published_splits,
+                num_published_docs,
+            }
+        })
+        .collect();
+    Ok(indexes_summaries)
+}
+
+fn list_indexes_summaries_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / "_summary")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_indexes_summaries)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
 /// This struct represents the QueryString passed to
 /// the rest API to filter splits.
 #[derive(Debug, Clone, Deserialize, Serialize, utoipa::IntoParams, utoipa::ToSchema, Default)]
@@ -428,6 +533,205 @@ fn mark_splits_for_deletion_handler(
         .map(make_json_api_response)
 }
 
+/// Attributes of a published split to update. Only the attributes set are updated.
+#[derive(Debug, Clone, Default, Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct SplitMetadataUpdate {
+    /// If set, the new number of documents of the split.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub num_docs: Option<usize>,
+    /// If set, the new uncompressed size of the documents of the split.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub uncompressed_docs_size_in_bytes: Option<u64>,
+    /// If set, the new lower bound of the time range of the split, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub time_range_start: Option<i64>,
+    /// If set, the new upper bound of the time range of the split, in seconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub time_range_end: Option<i64>,
+}
+
+#[utoipa::path(
+    put,
+    tag = "Splits",
+    path = "/indexes/{index_id}/splits/{split_id}/metadata",
+    request_body = SplitMetadataUpdate,
+    responses(
+        (status = 200, description = "Successfully updated the split metadata.")
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the split to update."),
+        ("split_id" = String, Path, description = "The ID of the published split to update."),
+    )
+)]
+/// Updates the metadata of a published split.
+async fn update_split_metadata(
+    index_id: String,
+    split_id: String,
+    split_metadata_update: SplitMetadataUpdate,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<()> {
+    let index_uid = resolve_index_uid(&index_id, &mut metastore).await?;
+    info!(index_id = %index_id, split_id = %split_id, update = ?split_metadata_update, "update-split-metadata");
+    let patch = SplitMetadataPatch {
+        num_docs: split_metadata_update.num_docs,
+        uncompressed_docs_size_in_bytes: split_metadata_update.uncompressed_docs_size_in_bytes,
+        time_range_start: split_metadata_update.time_range_start,
+        time_range_end: split_metadata_update.time_range_end,
+    };
+    let update_split_metadata_request =
+        UpdateSplitMetadataRequest::from_patch(index_uid, split_id, patch);
+    metastore
+        .update_split_metadata(update_split_metadata_request)
+        .await?;
+    Ok(())
+}
+
+fn update_split_metadata_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "splits" / String / "metadata")
+        .and(warp::put())
+        .and(json_body())
+        .and(with_arg(metastore))
+        .then(update_split_metadata)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+async fn resolve_index_uid(
+    index_id: &str,
+    metastore: &mut MetastoreServiceClient,
+) -> MetastoreResult<IndexUid> {
+    let index_metadata_request = IndexMetadataRequest::for_index_id(index_id.to_string());
+    let index_uid = metastore
+        .index_metadata(index_metadata_request)
+        .await?
+        .deserialize_index_metadata()?
+        .index_uid;
+    Ok(index_uid)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Snapshots",
+    path = "/indexes/{index_id}/snapshots",
+    responses(
+        (status = 200, description = "Successfully created a snapshot of the index.", body = IndexSnapshot)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to snapshot."),
+    )
+)]
+/// Creates a snapshot of the published splits of an index.
+async fn create_index_snapshot(
+    index_id: String,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<IndexSnapshot> {
+    let index_uid = resolve_index_uid(&index_id, &mut metastore).await?;
+    info!(index_id = %index_id, "create-index-snapshot");
+    let create_snapshot_request = CreateIndexSnapshotRequest {
+        index_uid: index_uid.to_string(),
+    };
+    metastore
+        .create_index_snapshot(create_snapshot_request)
+        .await
+}
+
+fn create_index_snapshot_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "snapshots")
+        .and(warp::post())
+        .and(with_arg(metastore))
+        .then(create_index_snapshot)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Snapshots",
+    path = "/indexes/{index_id}/snapshots",
+    responses(
+        (status = 200, description = "Successfully fetched the snapshots of the index.", body = [IndexSnapshot])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to list the snapshots of."),
+    )
+)]
+/// Lists the snapshots of an index.
+async fn list_index_snapshots(
+    index_id: String,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<Vec<IndexSnapshot>> {
+    let index_uid = resolve_index_uid(&index_id, &mut metastore).await?;
+    let list_snapshots_request = ListIndexSnapshotsRequest {
+        index_uid: index_uid.to_string(),
+    };
+    let snapshots = metastore
+        .list_index_snapshots(list_snapshots_request)
+        .await?
+        .snapshots;
+    Ok(snapshots)
+}
+
+fn list_index_snapshots_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "snapshots")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_index_snapshots)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Snapshots",
+    path = "/indexes/{index_id}/snapshots/{snapshot_id}",
+    responses(
+        (status = 200, description = "Successfully deleted the snapshot.")
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the snapshot."),
+        ("snapshot_id" = String, Path, description = "The ID of the snapshot to delete."),
+    )
+)]
+/// Deletes a snapshot. The splits it references can then be garbage collected.
+async fn delete_index_snapshot(
+    index_id: String,
+    snapshot_id: String,
+    mut metastore: MetastoreServiceClient,
+) -> MetastoreResult<()> {
+    let index_uid = resolve_index_uid(&index_id, &mut metastore).await?;
+    info!(index_id = %index_id, snapshot_id = %snapshot_id, "delete-index-snapshot");
+    let delete_snapshot_request = DeleteIndexSnapshotRequest {
+        index_uid: index_uid.to_string(),
+        snapshot_id,
+    };
+    metastore
+        .delete_index_snapshot(delete_snapshot_request)
+        .await?;
+    Ok(())
+}
+
+fn delete_index_snapshot_handler(
+    metastore: MetastoreServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "snapshots" / String)
+        .and(warp::delete())
+        .and(with_arg(metastore))
+        .then(delete_index_snapshot)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
 #[utoipa::path(
     get,
     tag = "Indexes",
@@ -1176,6 +1480,66 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_update_split_metadata() {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        mock_metastore
+            .expect_index_metadata()
+            .returning(|_| {
+                Ok(
+                    IndexMetadataResponse::try_from_index_metadata(IndexMetadata::for_test(
+                        "quickwit-demo-index",
+                        "ram:///indexes/quickwit-demo-index",
+                    ))
+                    .unwrap(),
+                )
+            })
+            .times(1);
+        mock_metastore
+            .expect_update_split_metadata()
+            .returning(
+                |update_split_metadata_request: UpdateSplitMetadataRequest| {
+                    let index_uid: IndexUid =
+                        update_split_metadata_request.index_uid.clone().into();
+                    assert_eq!(index_uid.index_id(), "quickwit-demo-index");
+                    assert_eq!(update_split_metadata_request.split_id, "split-1");
+
+                    let expected_patch = SplitMetadataPatch {
+                        num_docs: Some(42),
+                        time_range_end: Some(1_000),
+                        ..Default::default()
+                    };
+                    assert_eq!(update_split_metadata_request.patch(), expected_patch);
+                    Ok(EmptyResponse {})
+                },
+            )
+            .times(1);
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/splits/split-1/metadata")
+            .method("PUT")
+            .json(&true)
+            .body(r#"{"num_docs": 42, "time_range_end": 1000}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/splits/split-1/metadata")
+            .method("PUT")
+            .json(&true)
+            .body(r#"{"num_docs": 42, "unknown_field": 1}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
     #[tokio::test]
     async fn test_get_list_indexes() -> anyhow::Result<()> {
         let mut mock_metastore = MetastoreServiceClient::mock();
@@ -1216,6 +1580,73 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_list_indexes_summaries() -> anyhow::Result<()> {
+        let mut mock_metastore = MetastoreServiceClient::mock();
+        let index_metadata_1 =
+            IndexMetadata::for_test("test-index-1", "ram:///indexes/test-index-1");
+        let index_uid_1 = index_metadata_1.index_uid.clone();
+        let index_metadata_2 =
+            IndexMetadata::for_test("test-index-2", "ram:///indexes/test-index-2");
+        let index_uid_2 = index_metadata_2.index_uid.clone();
+        mock_metastore
+            .expect_list_indexes_metadata()
+            .return_once(|_list_indexes_request| {
+                Ok(ListIndexesMetadataResponse::try_from_indexes_metadata(vec![
+                    index_metadata_1,
+                    index_metadata_2,
+                ])
+                .unwrap())
+            });
+        let split_1 = MockSplitBuilder::new("split_1")
+            .with_index_uid(&index_uid_1)
+            .build();
+        let split_2 = MockSplitBuilder::new("split_2")
+            .with_index_uid(&index_uid_1)
+            .build();
+        mock_metastore
+            .expect_list_splits()
+            .times(1)
+            .withf(move |list_split_request| -> bool {
+                let list_split_query = list_split_request.deserialize_list_splits_query().unwrap();
+                list_split_query.index_uids == [index_uid_1.clone(), index_uid_2.clone()]
+                    && list_split_query.split_states == [SplitState::Published]
+            })
+            .return_once(move |_| {
+                let splits = ListSplitsResponse::try_from_splits(vec![split_1, split_2]).unwrap();
+                Ok(ServiceStream::from(vec![Ok(splits)]))
+            });
+        let index_service = IndexService::new(
+            MetastoreServiceClient::from(mock_metastore),
+            StorageResolver::unconfigured(),
+        );
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(NodeConfig::for_test()))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/_summary")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = serde_json::json!([
+            {
+                "index_id": "test-index-1",
+                "index_uri": "ram:///indexes/test-index-1",
+                "num_published_splits": 2,
+                "num_published_docs": 20,
+            },
+            {
+                "index_id": "test-index-2",
+                "index_uri": "ram:///indexes/test-index-2",
+                "num_published_splits": 0,
+                "num_published_docs": 0,
+            }
+        ]);
+        assert_eq!(actual_response_json, expected_response_json);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_clear_index() -> anyhow::Result<()> {
         let mut mock_metastore = MetastoreServiceClient::mock();
@@ -1382,6 +1813,62 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_create_list_delete_index_snapshot() {
+        let metastore = metastore_for_test();
+        let index_service = IndexService::new(metastore.clone(), StorageResolver::unconfigured());
+        let mut node_config = NodeConfig::for_test();
+        node_config.default_index_root_uri = Uri::for_test("file:///default-index-root-uri");
+        let index_management_handler =
+            super::index_management_handlers(index_service, Arc::new(node_config))
+                .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/snapshots")
+            .method("POST")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let snapshot: IndexSnapshot = serde_json::from_slice(resp.body()).unwrap();
+        assert!(snapshot.split_ids.is_empty());
+
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs/snapshots")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let snapshots: Vec<IndexSnapshot> = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(snapshots, [snapshot.clone()]);
+
+        let resp = warp::test::request()
+            .path(&format!(
+                "/indexes/hdfs-logs/snapshots/{}",
+                snapshot.snapshot_id
+            ))
+            .method("DELETE")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path(&format!(
+                "/indexes/hdfs-logs/snapshots/{}",
+                snapshot.snapshot_id
+            ))
+            .method("DELETE")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
     #[tokio::test]
     async fn test_create_delete_index_and_source() {
         let mut metastore = metastore_for_test();
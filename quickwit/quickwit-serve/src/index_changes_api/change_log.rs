@@ -0,0 +1,696 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::{stream, Stream};
+use quickwit_proto::types::{IndexId, IndexUid, SplitId};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+
+/// Maximum number of changes retained per index. Clients lagging further behind must resync.
+const MAX_NUM_CHANGES_PER_INDEX: usize = 1_000;
+
+const CHANGE_CHANNEL_CAPACITY: usize = 1_024;
+
+/// Type of a change of the split set of an index.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IndexChangeType {
+    /// New splits were published.
+    Publish,
+    /// Published splits were replaced by new splits, for instance by a merge.
+    Replace,
+    /// Splits were marked for deletion.
+    MarkForDeletion,
+    /// Splits were deleted from the metastore.
+    Delete,
+}
+
+/// A change of the split set of an index.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IndexChange {
+    pub index_id: String,
+    /// Version of the split set of the index after the change. Versions of an index start at 1
+    /// and increase by one with each change.
+    pub version: u64,
+    pub change_type: IndexChangeType,
+    /// IDs of the splits affected by the change. For a `replace` change, this includes both the
+    /// new and the replaced splits.
+    pub split_ids: Vec<String>,
+}
+
+/// Changes of the split set of an index that occurred after a given version.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IndexChangesResponse {
+    /// Current version of the split set of the index.
+    pub version: u64,
+    /// Changes ordered by version.
+    pub changes: Vec<IndexChange>,
+    /// Set when the requested changes are no longer available. The client must then discard
+    /// its state, resync from the current split set, and poll from `version`.
+    pub resync: bool,
+}
+
+impl IndexChangesResponse {
+    fn resync(version: u64) -> Self {
+        Self {
+            version,
+            changes: Vec::new(),
+            resync: true,
+        }
+    }
+}
+
+/// An event streamed to the clients watching the changes of an index.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type")]
+#[serde(rename_all = "snake_case")]
+pub enum IndexWatchEvent {
+    /// A change of the split set of the index.
+    Change(IndexChange),
+    /// Some changes are no longer available, for instance because the client lagged behind or
+    /// the index was recreated. The client must discard its state and resync from the current
+    /// split set. The following changes come after `version`.
+    Resync { version: u64 },
+}
+
+#[derive(Default)]
+struct IndexChanges {
+    version: u64,
+    changes: VecDeque<IndexChange>,
+}
+
+struct InnerIndexChangeLog {
+    per_index_changes: HashMap<IndexId, IndexChanges>,
+    max_num_changes_per_index: usize,
+}
+
+impl InnerIndexChangeLog {
+    fn versions(&self) -> HashMap<IndexId, u64> {
+        self.per_index_changes
+            .iter()
+            .map(|(index_id, index_changes)| (index_id.clone(), index_changes.version))
+            .collect()
+    }
+
+    fn changes_since(&self, index_id: &str, since_version: u64) -> IndexChangesResponse {
+        let Some(index_changes) = self.per_index_changes.get(index_id) else {
+            if since_version > 0 {
+                return IndexChangesResponse::resync(0);
+            }
+            return IndexChangesResponse {
+                version: 0,
+                changes: Vec::new(),
+                resync: false,
+            };
+        };
+        let version = index_changes.version;
+        let oldest_version = index_changes
+            .changes
+            .front()
+            .map(|change| change.version)
+            .unwrap_or(version);
+
+        // A version in the future comes from a previous incarnation of the log, while a version
+        // older than the oldest retained change means that some changes were evicted.
+        if since_version > version || since_version + 1 < oldest_version {
+            return IndexChangesResponse::resync(version);
+        }
+        let changes = index_changes
+            .changes
+            .iter()
+            .filter(|change| change.version > since_version)
+            .cloned()
+            .collect();
+        IndexChangesResponse {
+            version,
+            changes,
+            resync: false,
+        }
+    }
+}
+
+/// Records the changes of the split sets of the indexes applied by the local metastore and
+/// broadcasts them to subscribers. The changes are recorded by the
+/// [`IndexChangeLogLayer`](super::IndexChangeLogLayer) on the mutation path of the metastore.
+///
+/// The log is held in memory by the nodes running the metastore service and only retains the
+/// most recent changes of each index. Versions restart from zero when the node restarts, in which
+/// case clients are told to resync.
+#[derive(Clone)]
+pub struct IndexChangeLog {
+    inner: Arc<Mutex<InnerIndexChangeLog>>,
+    change_tx: broadcast::Sender<IndexChange>,
+}
+
+impl Default for IndexChangeLog {
+    fn default() -> Self {
+        Self::with_max_num_changes_per_index(MAX_NUM_CHANGES_PER_INDEX)
+    }
+}
+
+impl IndexChangeLog {
+    fn with_max_num_changes_per_index(max_num_changes_per_index: usize) -> Self {
+        let inner = InnerIndexChangeLog {
+            per_index_changes: HashMap::new(),
+            max_num_changes_per_index,
+        };
+        let (change_tx, _change_rx) = broadcast::channel(CHANGE_CHANNEL_CAPACITY);
+        Self {
+            inner: Arc::new(Mutex::new(inner)),
+            change_tx,
+        }
+    }
+
+    /// Returns a receiver of all the changes recorded from now on. Changes of a given index are
+    /// received in version order.
+    pub fn subscribe(&self) -> broadcast::Receiver<IndexChange> {
+        self.change_tx.subscribe()
+    }
+
+    /// Returns the changes of index `index_id` that occurred after `since_version`.
+    pub fn changes_since(&self, index_id: &str, since_version: u64) -> IndexChangesResponse {
+        let inner = self.inner.lock().expect("lock should not be poisoned");
+        inner.changes_since(index_id, since_version)
+    }
+
+    /// Returns the changes of index `index_id` that occurred after `since_version` along with a
+    /// receiver of the changes recorded afterwards. No change is both returned and received.
+    fn subscribe_since(
+        &self,
+        index_id: &str,
+        since_version: u64,
+    ) -> (IndexChangesResponse, broadcast::Receiver<IndexChange>) {
+        // Changes are broadcast while holding the lock, so holding it here ensures that the
+        // receiver only gets the changes recorded after the response was computed.
+        let inner = self.inner.lock().expect("lock should not be poisoned");
+        let change_rx = self.change_tx.subscribe();
+        let response = inner.changes_since(index_id, since_version);
+        (response, change_rx)
+    }
+
+    /// Returns the current version of every index along with a receiver of the changes recorded
+    /// afterwards.
+    fn subscribe_with_versions(&self) -> (HashMap<IndexId, u64>, broadcast::Receiver<IndexChange>) {
+        let inner = self.inner.lock().expect("lock should not be poisoned");
+        let change_rx = self.change_tx.subscribe();
+        (inner.versions(), change_rx)
+    }
+
+    /// Streams the changes of index `index_id` that occurred after `since_version`, then the
+    /// changes as they are recorded. Changes are streamed in version order, and a resync event is
+    /// emitted whenever some changes cannot be streamed.
+    pub fn watch_changes(
+        &self,
+        index_id: String,
+        since_version: u64,
+    ) -> impl Stream<Item = IndexWatchEvent> + Send + 'static {
+        let watch_state = IndexWatchState::new(self.clone(), index_id, since_version);
+        stream::unfold(watch_state, |mut watch_state| async move {
+            let event = watch_state.next_event().await?;
+            Some((event, watch_state))
+        })
+    }
+
+    /// Streams the changes of the indexes whose ID matches `index_id_regex` as they are recorded.
+    /// Each event comes with the ID of its index. The changes of an index are streamed in version
+    /// order, and a resync event is emitted for an index whenever some of its changes cannot be
+    /// streamed.
+    pub fn watch_matching_changes(
+        &self,
+        index_id_regex: Regex,
+    ) -> impl Stream<Item = (IndexId, IndexWatchEvent)> + Send + 'static {
+        let watch_state = MatchingIndexesWatchState::new(self.clone(), index_id_regex);
+        stream::unfold(watch_state, |mut watch_state| async move {
+            let event = watch_state.next_event().await?;
+            Some((event, watch_state))
+        })
+    }
+
+    /// Same as [`IndexChangeLog::changes_since`] but waits up to `timeout` for a change to occur
+    /// if there are none yet.
+    pub async fn wait_for_changes_since(
+        &self,
+        index_id: &str,
+        since_version: u64,
+        timeout: Duration,
+    ) -> IndexChangesResponse {
+        let (response, mut change_rx) = self.subscribe_since(index_id, since_version);
+
+        if response.resync || !response.changes.is_empty() || timeout.is_zero() {
+            return response;
+        }
+        let wait_for_change = async {
+            loop {
+                match change_rx.recv().await {
+                    Ok(change) if change.index_id != index_id => continue,
+                    Ok(_) | Err(RecvError::Lagged(_)) | Err(RecvError::Closed) => break,
+                }
+            }
+        };
+        let _ = tokio::time::timeout(timeout, wait_for_change).await;
+        self.changes_since(index_id, since_version)
+    }
+
+    pub(super) fn record_change(
+        &self,
+        index_uid: String,
+        change_type: IndexChangeType,
+        split_ids: Vec<SplitId>,
+    ) {
+        if split_ids.is_empty() {
+            return;
+        }
+        let Ok(index_uid) = IndexUid::parse(index_uid) else {
+            return;
+        };
+        let index_id = index_uid.index_id().to_string();
+        let mut inner = self.inner.lock().expect("lock should not be poisoned");
+        let max_num_changes_per_index = inner.max_num_changes_per_index;
+        let index_changes = inner.per_index_changes.entry(index_id.clone()).or_default();
+        index_changes.version += 1;
+
+        let change = IndexChange {
+            index_id,
+            version: index_changes.version,
+            change_type,
+            split_ids,
+        };
+        if index_changes.changes.len() >= max_num_changes_per_index {
+            index_changes.changes.pop_front();
+        }
+        index_changes.changes.push_back(change.clone());
+        // Sending while holding the lock guarantees that the changes of an index are broadcast in
+        // version order. Sending only fails when there are no receivers.
+        let _ = self.change_tx.send(change);
+    }
+
+    /// Forgets the changes of a deleted index. Clients watching the index are told to resync,
+    /// which is also what they need if the index is recreated.
+    pub(super) fn remove_index(&self, index_uid: String) {
+        let Ok(index_uid) = IndexUid::parse(index_uid) else {
+            return;
+        };
+        self.inner
+            .lock()
+            .expect("lock should not be poisoned")
+            .per_index_changes
+            .remove(index_uid.index_id());
+    }
+}
+
+struct IndexWatchState {
+    change_log: IndexChangeLog,
+    index_id: String,
+    // Version of the last event queued for the client.
+    version: u64,
+    pending_events: VecDeque<IndexWatchEvent>,
+    change_rx: broadcast::Receiver<IndexChange>,
+}
+
+impl IndexWatchState {
+    fn new(change_log: IndexChangeLog, index_id: String, since_version: u64) -> Self {
+        let (response, change_rx) = change_log.subscribe_since(&index_id, since_version);
+        let mut watch_state = Self {
+            change_log,
+            index_id,
+            version: since_version,
+            pending_events: VecDeque::new(),
+            change_rx,
+        };
+        watch_state.queue_response(response);
+        watch_state
+    }
+
+    fn queue_response(&mut self, response: IndexChangesResponse) {
+        if response.resync {
+            self.pending_events.push_back(IndexWatchEvent::Resync {
+                version: response.version,
+            });
+        } else {
+            self.pending_events
+                .extend(response.changes.into_iter().map(IndexWatchEvent::Change));
+        }
+        self.version = response.version;
+    }
+
+    async fn next_event(&mut self) -> Option<IndexWatchEvent> {
+        loop {
+            if let Some(event) = self.pending_events.pop_front() {
+                return Some(event);
+            }
+            match self.change_rx.recv().await {
+                Ok(change) if change.index_id != self.index_id => continue,
+                Ok(change) if change.version == self.version + 1 => {
+                    self.version = change.version;
+                    return Some(IndexWatchEvent::Change(change));
+                }
+                // Either the versions of the index restarted because it was recreated, or some
+                // changes were dropped by the channel. In both cases, we catch up from the log.
+                Ok(_) | Err(RecvError::Lagged(_)) => {
+                    let (response, change_rx) = self
+                        .change_log
+                        .subscribe_since(&self.index_id, self.version);
+                    self.change_rx = change_rx;
+                    self.queue_response(response);
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+struct MatchingIndexesWatchState {
+    change_log: IndexChangeLog,
+    index_id_regex: Regex,
+    // Version of the last event queued for the client, per matching index.
+    versions: HashMap<IndexId, u64>,
+    pending_events: VecDeque<(IndexId, IndexWatchEvent)>,
+    change_rx: broadcast::Receiver<IndexChange>,
+}
+
+impl MatchingIndexesWatchState {
+    fn new(change_log: IndexChangeLog, index_id_regex: Regex) -> Self {
+        let (versions, change_rx) = change_log.subscribe_with_versions();
+        let versions = versions
+            .into_iter()
+            .filter(|(index_id, _)| index_id_regex.is_match(index_id))
+            .collect();
+        Self {
+            change_log,
+            index_id_regex,
+            versions,
+            pending_events: VecDeque::new(),
+            change_rx,
+        }
+    }
+
+    async fn next_event(&mut self) -> Option<(IndexId, IndexWatchEvent)> {
+        loop {
+            if let Some(event) = self.pending_events.pop_front() {
+                return Some(event);
+            }
+            match self.change_rx.recv().await {
+                Ok(change) if !self.index_id_regex.is_match(&change.index_id) => continue,
+                Ok(change) => {
+                    let index_id = change.index_id.clone();
+                    let version = self.versions.entry(index_id.clone()).or_default();
+                    let expected_version = *version + 1;
+                    *version = change.version;
+
+                    // The change is already applied, so a client resyncing from the current
+                    // split set of the index gets it.
+                    let event = if change.version == expected_version {
+                        IndexWatchEvent::Change(change)
+                    } else {
+                        IndexWatchEvent::Resync {
+                            version: change.version,
+                        }
+                    };
+                    return Some((index_id, event));
+                }
+                // Some changes were dropped by the channel: the indexes whose version moved must
+                // be resynced.
+                Err(RecvError::Lagged(_)) => {
+                    let (versions, change_rx) = self.change_log.subscribe_with_versions();
+                    self.change_rx = change_rx;
+
+                    for (index_id, version) in versions {
+                        if !self.index_id_regex.is_match(&index_id) {
+                            continue;
+                        }
+                        if self.versions.get(&index_id) != Some(&version) {
+                            self.pending_events
+                                .push_back((index_id.clone(), IndexWatchEvent::Resync { version }));
+                            self.versions.insert(index_id, version);
+                        }
+                    }
+                }
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_index_change_log() {
+        let change_log = IndexChangeLog::with_max_num_changes_per_index(2);
+        let mut change_rx = change_log.subscribe();
+
+        let response = change_log.changes_since("test-index", 0);
+        assert_eq!(response.version, 0);
+        assert!(response.changes.is_empty());
+        assert!(!response.resync);
+
+        change_log.record_change(
+            "test-index:0".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-1".to_string()],
+        );
+        let change = change_rx.recv().await.unwrap();
+        assert_eq!(
+            change,
+            IndexChange {
+                index_id: "test-index".to_string(),
+                version: 1,
+                change_type: IndexChangeType::Publish,
+                split_ids: vec!["split-1".to_string()],
+            }
+        );
+        change_log.record_change(
+            "test-index:0".to_string(),
+            IndexChangeType::Delete,
+            vec!["split-0".to_string()],
+        );
+        let change = change_rx.recv().await.unwrap();
+        assert_eq!(change.version, 2);
+        assert_eq!(change.change_type, IndexChangeType::Delete);
+        assert_eq!(change.split_ids, vec!["split-0".to_string()]);
+
+        let response = change_log.changes_since("test-index", 0);
+        assert_eq!(response.version, 2);
+        assert_eq!(response.changes.len(), 2);
+        assert!(!response.resync);
+
+        let response = change_log.changes_since("test-index", 1);
+        assert_eq!(response.version, 2);
+        assert_eq!(response.changes.len(), 1);
+        assert_eq!(response.changes[0].version, 2);
+        assert!(!response.resync);
+
+        let response = change_log.changes_since("test-index", 2);
+        assert_eq!(response.version, 2);
+        assert!(response.changes.is_empty());
+        assert!(!response.resync);
+
+        // A third change evicts the oldest change from the log.
+        change_log.record_change(
+            "test-index:0".to_string(),
+            IndexChangeType::Replace,
+            vec![
+                "split-3".to_string(),
+                "split-1".to_string(),
+                "split-2".to_string(),
+            ],
+        );
+        let change = change_rx.recv().await.unwrap();
+        assert_eq!(change.version, 3);
+        assert_eq!(change.change_type, IndexChangeType::Replace);
+        assert_eq!(change.split_ids, ["split-3", "split-1", "split-2"]);
+
+        let response = change_log.changes_since("test-index", 1);
+        assert_eq!(response.changes.len(), 2);
+        assert!(!response.resync);
+
+        let response = change_log.changes_since("test-index", 0);
+        assert_eq!(response, IndexChangesResponse::resync(3));
+
+        let response = change_log.changes_since("test-index", 42);
+        assert_eq!(response, IndexChangesResponse::resync(3));
+
+        let response = change_log.changes_since("other-index", 1);
+        assert_eq!(response, IndexChangesResponse::resync(0));
+    }
+
+    #[tokio::test]
+    async fn test_index_change_log_watch_changes() {
+        let change_log = IndexChangeLog::with_max_num_changes_per_index(2);
+
+        change_log.record_change(
+            "test-index:0".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-1".to_string()],
+        );
+        // The recorded changes are replayed first.
+        let mut watch_stream = Box::pin(change_log.watch_changes("test-index".to_string(), 0));
+        let event = watch_stream.next().await.unwrap();
+        let IndexWatchEvent::Change(change) = event else {
+            panic!("expected a change, got `{event:?}`");
+        };
+        assert_eq!(change.version, 1);
+        assert_eq!(change.change_type, IndexChangeType::Publish);
+
+        change_log.record_change(
+            "test-index:0".to_string(),
+            IndexChangeType::Delete,
+            vec!["split-0".to_string()],
+        );
+        let event = watch_stream.next().await.unwrap();
+        let IndexWatchEvent::Change(change) = event else {
+            panic!("expected a change, got `{event:?}`");
+        };
+        assert_eq!(change.version, 2);
+        assert_eq!(change.change_type, IndexChangeType::Delete);
+        assert_eq!(change.split_ids, vec!["split-0".to_string()]);
+
+        // The changes of the other indexes are not streamed.
+        change_log.record_change(
+            "other-index:0".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-2".to_string()],
+        );
+        // When the index is recreated, its versions restart and the client must resync.
+        change_log.remove_index("test-index:0".to_string());
+        change_log.record_change(
+            "test-index:1".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-3".to_string()],
+        );
+        let event = watch_stream.next().await.unwrap();
+        assert_eq!(event, IndexWatchEvent::Resync { version: 1 });
+
+        change_log.record_change(
+            "test-index:1".to_string(),
+            IndexChangeType::MarkForDeletion,
+            vec!["split-3".to_string()],
+        );
+        let event = watch_stream.next().await.unwrap();
+        let IndexWatchEvent::Change(change) = event else {
+            panic!("expected a change, got `{event:?}`");
+        };
+        assert_eq!(change.version, 2);
+        assert_eq!(change.change_type, IndexChangeType::MarkForDeletion);
+
+        // A watch starting from an evicted version starts with a resync.
+        change_log.record_change(
+            "test-index:1".to_string(),
+            IndexChangeType::Delete,
+            vec!["split-3".to_string()],
+        );
+        let mut watch_stream = Box::pin(change_log.watch_changes("test-index".to_string(), 0));
+        let event = watch_stream.next().await.unwrap();
+        assert_eq!(event, IndexWatchEvent::Resync { version: 3 });
+    }
+
+    #[tokio::test]
+    async fn test_index_change_log_watch_matching_changes() {
+        let change_log = IndexChangeLog::default();
+
+        change_log.record_change(
+            "test-index-1:0".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-1".to_string()],
+        );
+        let index_id_regex = Regex::new("^test-index-.*$").unwrap();
+        let mut watch_stream = Box::pin(change_log.watch_matching_changes(index_id_regex));
+
+        // Only the changes of the matching indexes are streamed.
+        change_log.record_change(
+            "other-index:0".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-2".to_string()],
+        );
+        change_log.record_change(
+            "test-index-1:0".to_string(),
+            IndexChangeType::Delete,
+            vec!["split-0".to_string()],
+        );
+        change_log.record_change(
+            "test-index-2:0".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-3".to_string()],
+        );
+        let (index_id, event) = watch_stream.next().await.unwrap();
+        assert_eq!(index_id, "test-index-1");
+        let IndexWatchEvent::Change(change) = event else {
+            panic!("expected a change, got `{event:?}`");
+        };
+        assert_eq!(change.version, 2);
+        assert_eq!(change.change_type, IndexChangeType::Delete);
+
+        let (index_id, event) = watch_stream.next().await.unwrap();
+        assert_eq!(index_id, "test-index-2");
+        let IndexWatchEvent::Change(change) = event else {
+            panic!("expected a change, got `{event:?}`");
+        };
+        assert_eq!(change.version, 1);
+
+        // When an index is recreated, its versions restart and the client must resync.
+        change_log.remove_index("test-index-1:0".to_string());
+        change_log.record_change(
+            "test-index-1:1".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-4".to_string()],
+        );
+        let (index_id, event) = watch_stream.next().await.unwrap();
+        assert_eq!(index_id, "test-index-1");
+        assert_eq!(event, IndexWatchEvent::Resync { version: 1 });
+    }
+
+    #[tokio::test]
+    async fn test_index_change_log_wait_for_changes() {
+        let change_log = IndexChangeLog::default();
+
+        let response = change_log
+            .wait_for_changes_since("test-index", 0, Duration::from_millis(50))
+            .await;
+        assert!(response.changes.is_empty());
+
+        let change_log_clone = change_log.clone();
+        let wait_handle = tokio::spawn(async move {
+            change_log_clone
+                .wait_for_changes_since("test-index", 0, Duration::from_secs(10))
+                .await
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        change_log.record_change(
+            "test-index:0".to_string(),
+            IndexChangeType::MarkForDeletion,
+            vec!["split-1".to_string()],
+        );
+        let response = wait_handle.await.unwrap();
+        assert_eq!(response.version, 1);
+        assert_eq!(response.changes.len(), 1);
+        assert_eq!(
+            response.changes[0].change_type,
+            IndexChangeType::MarkForDeletion
+        );
+    }
+}
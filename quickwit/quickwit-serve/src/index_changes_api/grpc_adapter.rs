@@ -0,0 +1,122 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use async_trait::async_trait;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use itertools::Itertools;
+use quickwit_config::validate_index_id_pattern;
+use quickwit_proto::index_changes::{
+    index_changes_service_server as grpc, IndexChangeEvent, WatchIndexChangesRequest,
+};
+use quickwit_proto::{set_parent_span_from_request_metadata, tonic};
+use regex::Regex;
+use tracing::instrument;
+
+use super::change_log::{IndexChangeLog, IndexChangeType, IndexWatchEvent};
+
+#[derive(Clone)]
+pub struct GrpcIndexChangesAdapter(IndexChangeLog);
+
+impl From<IndexChangeLog> for GrpcIndexChangesAdapter {
+    fn from(change_log: IndexChangeLog) -> Self {
+        GrpcIndexChangesAdapter(change_log)
+    }
+}
+
+#[async_trait]
+impl grpc::IndexChangesService for GrpcIndexChangesAdapter {
+    type WatchIndexChangesStream = BoxStream<'static, Result<IndexChangeEvent, tonic::Status>>;
+
+    #[instrument(skip(self, request))]
+    async fn watch_index_changes(
+        &self,
+        request: tonic::Request<WatchIndexChangesRequest>,
+    ) -> Result<tonic::Response<Self::WatchIndexChangesStream>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let index_id_pattern = request.into_inner().index_id_pattern;
+        let index_id_regex = build_index_id_regex(&index_id_pattern)
+            .map_err(|error| tonic::Status::invalid_argument(error.to_string()))?;
+        let event_stream = self
+            .0
+            .watch_matching_changes(index_id_regex)
+            .map(|(index_id, event)| Ok(convert_watch_event(index_id, event)))
+            .boxed();
+        Ok(tonic::Response::new(event_stream))
+    }
+}
+
+/// Builds a regex matching the index IDs matched by `index_id_pattern`, in which `*` matches any
+/// sequence of characters.
+fn build_index_id_regex(index_id_pattern: &str) -> anyhow::Result<Regex> {
+    validate_index_id_pattern(index_id_pattern)?;
+    let regex_expr = format!(
+        "^{}$",
+        index_id_pattern.split('*').map(regex::escape).join(".*")
+    );
+    let regex = Regex::new(&regex_expr)?;
+    Ok(regex)
+}
+
+fn convert_watch_event(index_id: String, event: IndexWatchEvent) -> IndexChangeEvent {
+    use quickwit_proto::index_changes::IndexChangeType as ProtoIndexChangeType;
+
+    match event {
+        IndexWatchEvent::Change(change) => {
+            let change_type = match change.change_type {
+                IndexChangeType::Publish => ProtoIndexChangeType::Publish,
+                IndexChangeType::Replace => ProtoIndexChangeType::Replace,
+                IndexChangeType::MarkForDeletion => ProtoIndexChangeType::MarkForDeletion,
+                IndexChangeType::Delete => ProtoIndexChangeType::Delete,
+            };
+            IndexChangeEvent {
+                index_id,
+                version: change.version,
+                change_type: change_type as i32,
+                split_ids: change.split_ids,
+                resync: false,
+            }
+        }
+        IndexWatchEvent::Resync { version } => IndexChangeEvent {
+            index_id,
+            version,
+            change_type: ProtoIndexChangeType::Unspecified as i32,
+            split_ids: Vec::new(),
+            resync: true,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_index_id_regex() {
+        let regex = build_index_id_regex("test-index-*").unwrap();
+        assert!(regex.is_match("test-index-1"));
+        assert!(!regex.is_match("other-index"));
+
+        let regex = build_index_id_regex("test.index").unwrap();
+        assert!(regex.is_match("test.index"));
+        assert!(!regex.is_match("test-index"));
+
+        build_index_id_regex("").unwrap_err();
+    }
+}
@@ -0,0 +1,301 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use quickwit_common::tower::BoxFuture;
+use quickwit_proto::metastore::{
+    DeleteIndexRequest, DeleteSplitsRequest, MarkSplitsForDeletionRequest, PublishSplitsRequest,
+};
+use tower::{Layer, Service};
+
+use super::change_log::{IndexChangeLog, IndexChangeType};
+
+/// A metastore request that changes the split set of an index.
+pub trait IndexChangeRequest: Clone {
+    /// Whether the request deletes the index.
+    const DELETES_INDEX: bool = false;
+
+    fn index_uid(&self) -> &str;
+
+    /// Records the change made by the request once the metastore has applied it.
+    fn record_change(self, change_log: &IndexChangeLog);
+}
+
+impl IndexChangeRequest for PublishSplitsRequest {
+    fn index_uid(&self) -> &str {
+        &self.index_uid
+    }
+
+    fn record_change(self, change_log: &IndexChangeLog) {
+        if self.replaced_split_ids.is_empty() {
+            change_log.record_change(
+                self.index_uid,
+                IndexChangeType::Publish,
+                self.staged_split_ids,
+            );
+        } else {
+            let split_ids = self
+                .staged_split_ids
+                .into_iter()
+                .chain(self.replaced_split_ids)
+                .collect();
+            change_log.record_change(self.index_uid, IndexChangeType::Replace, split_ids);
+        }
+    }
+}
+
+impl IndexChangeRequest for MarkSplitsForDeletionRequest {
+    fn index_uid(&self) -> &str {
+        &self.index_uid
+    }
+
+    fn record_change(self, change_log: &IndexChangeLog) {
+        change_log.record_change(
+            self.index_uid,
+            IndexChangeType::MarkForDeletion,
+            self.split_ids,
+        );
+    }
+}
+
+impl IndexChangeRequest for DeleteSplitsRequest {
+    fn index_uid(&self) -> &str {
+        &self.index_uid
+    }
+
+    fn record_change(self, change_log: &IndexChangeLog) {
+        change_log.record_change(self.index_uid, IndexChangeType::Delete, self.split_ids);
+    }
+}
+
+impl IndexChangeRequest for DeleteIndexRequest {
+    const DELETES_INDEX: bool = true;
+
+    fn index_uid(&self) -> &str {
+        &self.index_uid
+    }
+
+    fn record_change(self, change_log: &IndexChangeLog) {
+        change_log.remove_index(self.index_uid);
+    }
+}
+
+type IndexLocks = Arc<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>;
+
+/// Records the changes of the split sets of the indexes in an [`IndexChangeLog`] on the mutation
+/// path of the metastore.
+///
+/// The mutations of an index are serialized by the layer and each change is recorded before the
+/// next mutation of the index starts, so the versions of the changes follow the order in which
+/// the metastore applied them.
+#[derive(Clone)]
+pub struct IndexChangeLogLayer {
+    change_log: IndexChangeLog,
+    index_locks: IndexLocks,
+}
+
+impl IndexChangeLogLayer {
+    pub fn new(change_log: IndexChangeLog) -> Self {
+        Self {
+            change_log,
+            index_locks: IndexLocks::default(),
+        }
+    }
+}
+
+impl<S> Layer<S> for IndexChangeLogLayer {
+    type Service = IndexChangeLogService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        IndexChangeLogService {
+            inner,
+            change_log: self.change_log.clone(),
+            index_locks: self.index_locks.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct IndexChangeLogService<S> {
+    inner: S,
+    change_log: IndexChangeLog,
+    index_locks: IndexLocks,
+}
+
+impl<S> IndexChangeLogService<S> {
+    fn index_lock(&self, index_uid: &str) -> Arc<tokio::sync::Mutex<()>> {
+        self.index_locks
+            .lock()
+            .expect("lock should not be poisoned")
+            .entry(index_uid.to_string())
+            .or_default()
+            .clone()
+    }
+}
+
+impl<S, R> Service<R> for IndexChangeLogService<S>
+where
+    S: Service<R> + Clone + Send + 'static,
+    S::Response: Send + 'static,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    R: IndexChangeRequest + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        // The service polled ready is the one that must handle the request.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let change_log = self.change_log.clone();
+        let index_locks = self.index_locks.clone();
+        let index_lock = self.index_lock(request.index_uid());
+
+        Box::pin(async move {
+            let _index_guard = index_lock.lock().await;
+            let response = inner.call(request.clone()).await?;
+
+            if R::DELETES_INDEX {
+                index_locks
+                    .lock()
+                    .expect("lock should not be poisoned")
+                    .remove(request.index_uid());
+            }
+            request.record_change(&change_log);
+            Ok(response)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use tokio::sync::Notify;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_index_change_log_layer_records_changes() {
+        let change_log = IndexChangeLog::default();
+        let layer = IndexChangeLogLayer::new(change_log.clone());
+        let mut service = layer.layer(tower::service_fn(
+            |request: PublishSplitsRequest| async move {
+                if request.staged_split_ids.is_empty() {
+                    Err(())
+                } else {
+                    Ok(())
+                }
+            },
+        ));
+        let request = PublishSplitsRequest {
+            index_uid: "test-index:0".to_string(),
+            staged_split_ids: vec!["split-2".to_string()],
+            replaced_split_ids: vec!["split-1".to_string()],
+            ..Default::default()
+        };
+        service.call(request).await.unwrap();
+
+        let response = change_log.changes_since("test-index", 0);
+        assert_eq!(response.version, 1);
+        assert_eq!(response.changes[0].change_type, IndexChangeType::Replace);
+        assert_eq!(response.changes[0].split_ids, ["split-2", "split-1"]);
+
+        // Failed mutations are not recorded.
+        let request = PublishSplitsRequest {
+            index_uid: "test-index:0".to_string(),
+            ..Default::default()
+        };
+        service.call(request).await.unwrap_err();
+
+        let response = change_log.changes_since("test-index", 0);
+        assert_eq!(response.version, 1);
+
+        let mut service = layer.layer(tower::service_fn(|_request: DeleteIndexRequest| async {
+            Ok::<_, ()>(())
+        }));
+        let request = DeleteIndexRequest {
+            index_uid: "test-index:0".to_string(),
+        };
+        service.call(request).await.unwrap();
+
+        let response = change_log.changes_since("test-index", 0);
+        assert_eq!(response.version, 0);
+        assert!(layer.index_locks.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_index_change_log_layer_serializes_index_mutations() {
+        let change_log = IndexChangeLog::default();
+        let layer = IndexChangeLogLayer::new(change_log.clone());
+        let entered = Arc::new(Notify::new());
+        let release = Arc::new(Notify::new());
+
+        let entered_clone = entered.clone();
+        let release_clone = release.clone();
+        let mut service = layer.layer(tower::service_fn(move |request: PublishSplitsRequest| {
+            let entered = entered_clone.clone();
+            let release = release_clone.clone();
+            async move {
+                if request.staged_split_ids == ["split-1"] {
+                    entered.notify_one();
+                    release.notified().await;
+                }
+                Ok::<_, ()>(())
+            }
+        }));
+        let first_request = PublishSplitsRequest {
+            index_uid: "test-index:0".to_string(),
+            staged_split_ids: vec!["split-1".to_string()],
+            ..Default::default()
+        };
+        let first_handle = tokio::spawn(service.call(first_request));
+        entered.notified().await;
+
+        let second_request = PublishSplitsRequest {
+            index_uid: "test-index:0".to_string(),
+            staged_split_ids: vec!["split-2".to_string()],
+            ..Default::default()
+        };
+        let second_handle = tokio::spawn(service.call(second_request));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // The second mutation waits for the first one to be applied and recorded.
+        assert!(!second_handle.is_finished());
+        assert_eq!(change_log.changes_since("test-index", 0).version, 0);
+
+        release.notify_one();
+        first_handle.await.unwrap().unwrap();
+        second_handle.await.unwrap().unwrap();
+
+        let response = change_log.changes_since("test-index", 0);
+        assert_eq!(response.version, 2);
+        assert_eq!(response.changes[0].split_ids, ["split-1"]);
+        assert_eq!(response.changes[1].split_ids, ["split-2"]);
+    }
+}
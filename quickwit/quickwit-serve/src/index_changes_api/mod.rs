@@ -0,0 +1,30 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod change_log;
+mod grpc_adapter;
+mod metastore_layer;
+mod rest_handler;
+
+pub use change_log::{
+    IndexChange, IndexChangeLog, IndexChangeType, IndexChangesResponse, IndexWatchEvent,
+};
+pub use grpc_adapter::GrpcIndexChangesAdapter;
+pub use metastore_layer::{IndexChangeLogLayer, IndexChangeLogService, IndexChangeRequest};
+pub use rest_handler::{index_changes_handler, IndexChangesApi};
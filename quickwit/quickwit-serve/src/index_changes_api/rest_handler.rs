@@ -0,0 +1,264 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use futures::StreamExt;
+use serde::Deserialize;
+use warp::sse::Event;
+use warp::{Filter, Rejection, Reply};
+
+use super::{IndexChange, IndexChangeLog, IndexChangeType, IndexChangesResponse, IndexWatchEvent};
+use crate::format::extract_format_from_qs;
+use crate::json_api_response::make_json_api_response;
+use crate::require;
+
+/// Maximum time a request waits for a change to occur.
+const MAX_TIMEOUT_SECS: u64 = 60;
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(get_index_changes, watch_index_changes),
+    components(schemas(IndexChangesResponse, IndexChange, IndexChangeType, IndexWatchEvent))
+)]
+pub struct IndexChangesApi;
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct IndexChangesQueryParams {
+    /// Last version of the split set of the index observed by the client.
+    #[serde(default)]
+    since_version: u64,
+    /// If there are no changes yet, maximum time to wait for one, in seconds (max. 60).
+    #[serde(default)]
+    timeout_secs: u64,
+}
+
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+struct WatchIndexChangesQueryParams {
+    /// Last version of the split set of the index observed by the client. Defaults to the
+    /// `Last-Event-ID` header sent by reconnecting clients.
+    since_version: Option<u64>,
+}
+
+/// Index changes handlers. They are only available on nodes running the metastore service.
+pub fn index_changes_handler(
+    index_change_log_opt: Option<IndexChangeLog>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    get_index_changes_handler(index_change_log_opt.clone())
+        .or(watch_index_changes_handler(index_change_log_opt))
+}
+
+fn get_index_changes_handler(
+    index_change_log_opt: Option<IndexChangeLog>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!(String / "changes")
+        .and(warp::get())
+        .and(warp::query::<IndexChangesQueryParams>())
+        .and(require(index_change_log_opt))
+        .then(get_index_changes)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+fn watch_index_changes_handler(
+    index_change_log_opt: Option<IndexChangeLog>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!(String / "changes" / "watch")
+        .and(warp::get())
+        .and(warp::query::<WatchIndexChangesQueryParams>())
+        .and(warp::header::optional::<u64>("last-event-id"))
+        .and(require(index_change_log_opt))
+        .map(watch_index_changes)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Splits",
+    path = "/{index_id}/changes",
+    responses(
+        (status = 200, description = "Successfully fetched the changes of the split set of the index.", body = IndexChangesResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to watch."),
+        IndexChangesQueryParams,
+    )
+)]
+/// Get Index Changes
+///
+/// Returns the changes of the split set of an index that occurred after `since_version`. If
+/// there are none, the request waits up to `timeout_secs` for a change to occur. When the
+/// requested changes are no longer available, `resync` is set and the client must resync from
+/// the current split set.
+async fn get_index_changes(
+    index_id: String,
+    query_params: IndexChangesQueryParams,
+    index_change_log: IndexChangeLog,
+) -> Result<IndexChangesResponse, Infallible> {
+    let timeout = Duration::from_secs(query_params.timeout_secs.min(MAX_TIMEOUT_SECS));
+    let response = index_change_log
+        .wait_for_changes_since(&index_id, query_params.since_version, timeout)
+        .await;
+    Ok(response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Splits",
+    path = "/{index_id}/changes/watch",
+    responses(
+        (status = 200, description = "Successfully started streaming the changes of the split set of the index as server-sent events.", body = IndexWatchEvent)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to watch."),
+        WatchIndexChangesQueryParams,
+    )
+)]
+/// Watch Index Changes
+///
+/// Streams the changes of the split set of an index that occurred after `since_version`, then the
+/// changes as they occur, as server-sent events. The ID of each event is the version of the split
+/// set after the event, so that reconnecting clients resume where they left off.
+fn watch_index_changes(
+    index_id: String,
+    query_params: WatchIndexChangesQueryParams,
+    last_event_id_opt: Option<u64>,
+    index_change_log: IndexChangeLog,
+) -> impl Reply {
+    let since_version = query_params
+        .since_version
+        .or(last_event_id_opt)
+        .unwrap_or_default();
+    let event_stream = index_change_log
+        .watch_changes(index_id, since_version)
+        .map(|watch_event| {
+            let version = match &watch_event {
+                IndexWatchEvent::Change(change) => change.version,
+                IndexWatchEvent::Resync { version } => *version,
+            };
+            Event::default()
+                .id(version.to_string())
+                .json_data(watch_event)
+        });
+    warp::sse::reply(warp::sse::keep_alive().stream(event_stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::body::HttpBody;
+
+    use super::*;
+    use crate::rest::recover_fn;
+
+    #[tokio::test]
+    async fn test_index_changes_handler() {
+        let index_change_log = IndexChangeLog::default();
+        index_change_log.record_change(
+            "test-index:0".to_string(),
+            IndexChangeType::Publish,
+            vec!["split-1".to_string()],
+        );
+
+        let handler = index_changes_handler(Some(index_change_log)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/test-index/changes?since_version=0")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let response_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "version": 1,
+            "changes": [{
+                "index_id": "test-index",
+                "version": 1,
+                "change_type": "publish",
+                "split_ids": ["split-1"],
+            }],
+            "resync": false,
+        });
+        assert_eq!(response_json, expected_response_json);
+
+        let resp = warp::test::request()
+            .path("/test-index/changes?since_version=10")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let response_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(response_json["resync"], true);
+
+        let handler = index_changes_handler(None).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/test-index/changes")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/test-index/changes/watch")
+            .reply(&handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_watch_index_changes_handler() {
+        let index_change_log = IndexChangeLog::default();
+        let handler = index_changes_handler(Some(index_change_log.clone()));
+
+        for split_id in ["split-1", "split-2"] {
+            index_change_log.record_change(
+                "test-index:0".to_string(),
+                IndexChangeType::Publish,
+                vec![split_id.to_string()],
+            );
+        }
+        // The `since_version` parameter takes precedence over the `Last-Event-ID` header.
+        let reply = warp::test::request()
+            .path("/test-index/changes/watch?since_version=1")
+            .header("last-event-id", "0")
+            .filter(&handler)
+            .await
+            .unwrap();
+        let resp = reply.into_response();
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            resp.headers().get("content-type").unwrap(),
+            "text/event-stream"
+        );
+        let mut body = resp.into_body();
+        let event_bytes = body.data().await.unwrap().unwrap();
+        let event_str = std::str::from_utf8(&event_bytes).unwrap();
+        assert!(event_str.contains("id:2\n"));
+        assert!(event_str.contains(r#""type":"change""#));
+        assert!(event_str.contains(r#""split_ids":["split-2"]"#));
+
+        let reply = warp::test::request()
+            .path("/test-index/changes/watch")
+            .header("last-event-id", "42")
+            .filter(&handler)
+            .await
+            .unwrap();
+        let mut body = reply.into_response().into_body();
+        let event_bytes = body.data().await.unwrap().unwrap();
+        let event_str = std::str::from_utf8(&event_bytes).unwrap();
+        assert!(event_str.contains(r#"{"type":"resync","version":2}"#));
+    }
+}
@@ -30,11 +30,15 @@ use quickwit_proto::ingest::router::{
 use quickwit_proto::ingest::DocBatchV2;
 use quickwit_proto::types::IndexId;
 use serde::Deserialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
 use thiserror::Error;
 use warp::{Filter, Rejection};
 
-use crate::format::extract_format_from_qs;
+use crate::format::{
+    extract_format_from_accept_header, extract_format_from_content_type, extract_format_from_qs,
+};
 use crate::json_api_response::make_json_api_response;
+use crate::rest::InvalidArgument;
 use crate::{with_arg, BodyFormat};
 
 #[derive(utoipa::OpenApi)]
@@ -78,10 +82,7 @@ fn ingest_filter(
 ) -> impl Filter<Extract = (String, Bytes, IngestOptions), Error = Rejection> + Clone {
     warp::path!(String / "ingest")
         .and(warp::post())
-        .and(warp::body::content_length_limit(
-            config.content_length_limit.as_u64(),
-        ))
-        .and(warp::body::bytes())
+        .and(docs_body_filter(config))
         .and(serde_qs::warp::query::<IngestOptions>(
             serde_qs::Config::default(),
         ))
@@ -92,9 +93,18 @@ fn ingest_handler(
     config: IngestApiConfig,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     ingest_filter(config)
+        .and(extract_format_from_accept_header())
         .and(with_arg(ingest_service))
-        .then(ingest)
-        .map(|result| make_json_api_response(result, BodyFormat::default()))
+        .then(
+            |index_id: String,
+             body: Bytes,
+             ingest_options: IngestOptions,
+             accepted_format_opt: Option<BodyFormat>,
+             ingest_service: IngestServiceClient| async move {
+                let result = ingest(index_id, body, ingest_options, ingest_service).await;
+                make_json_api_response(result, accepted_format_opt.unwrap_or_default())
+            },
+        )
 }
 
 fn ingest_v2_filter(
@@ -102,10 +112,7 @@ fn ingest_v2_filter(
 ) -> impl Filter<Extract = (String, Bytes, IngestOptions), Error = Rejection> + Clone {
     warp::path!(String / "ingest-v2")
         .and(warp::post())
-        .and(warp::body::content_length_limit(
-            config.content_length_limit.as_u64(),
-        ))
-        .and(warp::body::bytes())
+        .and(docs_body_filter(config))
         .and(serde_qs::warp::query::<IngestOptions>(
             serde_qs::Config::default(),
         ))
@@ -116,10 +123,55 @@ fn ingest_v2_handler(
     config: IngestApiConfig,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     ingest_v2_filter(config)
+        .and(extract_format_from_accept_header())
         .and(with_arg(ingest_router))
-        .then(ingest_v2)
-        .and(with_arg(BodyFormat::default()))
-        .map(make_json_api_response)
+        .then(
+            |index_id: IndexId,
+             body: Bytes,
+             ingest_options: IngestOptions,
+             accepted_format_opt: Option<BodyFormat>,
+             ingest_router: IngestRouterServiceClient| async move {
+                let result = ingest_v2(index_id, body, ingest_options, ingest_router).await;
+                make_json_api_response(result, accepted_format_opt.unwrap_or_default())
+            },
+        )
+}
+
+/// Extracts the documents to ingest from the request body as NDJSON.
+///
+/// Bodies sent with a MessagePack or CBOR content type must contain an array of documents, each
+/// document being a map. They are converted to NDJSON so that the rest of the ingest path is
+/// unchanged.
+fn docs_body_filter(
+    config: IngestApiConfig,
+) -> impl Filter<Extract = (Bytes,), Error = Rejection> + Clone {
+    warp::body::content_length_limit(config.content_length_limit.as_u64())
+        .and(warp::body::bytes())
+        .and(extract_format_from_content_type())
+        .and_then(
+            |body: Bytes, body_format_opt: Option<BodyFormat>| async move {
+                let Some(body_format) = body_format_opt else {
+                    return Ok(body);
+                };
+                docs_to_ndjson(&body, body_format)
+                    .map_err(|message| warp::reject::custom(InvalidArgument(message)))
+            },
+        )
+}
+
+fn docs_to_ndjson(body: &[u8], body_format: BodyFormat) -> Result<Bytes, String> {
+    let docs: Vec<JsonMap<String, JsonValue>> = body_format.parse_body(body).map_err(|error| {
+        format!(
+            "failed to parse request body as an array of {} documents: {error}",
+            body_format.to_string()
+        )
+    })?;
+    let mut ndjson = BytesMut::with_capacity(body.len() * 2).writer();
+    for doc in docs {
+        serde_json::to_writer(&mut ndjson, &doc).map_err(|error| error.to_string())?;
+        ndjson.get_mut().put_u8(b'\n');
+    }
+    Ok(ndjson.into_inner().freeze())
 }
 
 async fn ingest_v2(
@@ -193,7 +245,7 @@ fn convert_ingest_response_v2(
     post,
     tag = "Ingest",
     path = "/{index_id}/ingest",
-    request_body(content = String, description = "Documents to ingest in NDJSON format and limited to 10MB", content_type = "application/json"),
+    request_body(content = String, description = "Documents to ingest in NDJSON format, or as a MessagePack or CBOR array of documents, and limited to 10MB", content_type = "application/json"),
     responses(
         (status = 200, description = "Successfully ingested documents.", body = IngestResponse)
     ),
@@ -278,9 +330,12 @@ pub(crate) mod tests {
         QUEUES_DIR_NAME,
     };
     use quickwit_proto::ingest::router::IngestRouterServiceClient;
+    use serde_json::json;
+    use warp::Filter;
 
     use super::ingest_api_handlers;
     use crate::ingest_api::lines;
+    use crate::{recover_fn, BodyFormat};
 
     #[test]
     fn test_process_lines() {
@@ -388,6 +443,88 @@ pub(crate) mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_ingest_api_binary_content_types() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
+        let ingest_router = IngestRouterServiceClient::mock().into();
+        let ingest_api_handlers =
+            ingest_api_handlers(ingest_router, ingest_service, IngestApiConfig::default())
+                .recover(recover_fn);
+        let docs = json!([
+            {"id": 1, "message": "push", "score": 0.5},
+            {"id": 2, "message": "push", "tags": ["a", "b"]},
+        ]);
+        let msgpack_body = rmp_serde::to_vec_named(&docs).unwrap();
+        let mut cbor_body = Vec::new();
+        ciborium::ser::into_writer(&docs, &mut cbor_body).unwrap();
+
+        for (content_type, body) in [
+            ("application/msgpack", msgpack_body),
+            ("application/cbor", cbor_body),
+        ] {
+            let resp = warp::test::request()
+                .path("/my-index/ingest")
+                .method("POST")
+                .header("content-type", content_type)
+                .header("accept", content_type)
+                .body(body)
+                .reply(&ingest_api_handlers)
+                .await;
+            assert_eq!(resp.status(), 200);
+            assert_eq!(resp.headers()["content-type"], content_type);
+            let body_format = if content_type == "application/msgpack" {
+                BodyFormat::MessagePack
+            } else {
+                BodyFormat::Cbor
+            };
+            let ingest_response: IngestResponse = body_format.parse_body(resp.body()).unwrap();
+            assert_eq!(ingest_response.num_docs_for_processing, 2);
+        }
+        let resp = warp::test::request()
+            .path("/my-index/tail")
+            .method("GET")
+            .reply(&ingest_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let fetch_response: FetchResponse = serde_json::from_slice(resp.body()).unwrap();
+        let doc_batch = fetch_response.doc_batch.unwrap();
+        assert_eq!(doc_batch.num_docs(), 4);
+        let doc_buffer = String::from_utf8_lossy(&doc_batch.doc_buffer);
+        assert!(doc_buffer.contains(r#"{"id":1,"message":"push","score":0.5}"#));
+
+        // Documents must be maps.
+        let resp = warp::test::request()
+            .path("/my-index/ingest")
+            .method("POST")
+            .header("content-type", "application/msgpack")
+            .body(rmp_serde::to_vec(&json!([1, 2])).unwrap())
+            .reply(&ingest_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 400);
+        assert_eq!(resp.headers()["content-type"], "application/json");
+
+        // The request is rejected before ingesting anything if the response format is not
+        // acceptable.
+        let resp = warp::test::request()
+            .path("/my-index/ingest")
+            .method("POST")
+            .header("accept", "application/xml")
+            .body(r#"{"id": 5, "message": "push"}"#)
+            .reply(&ingest_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 406);
+        let resp = warp::test::request()
+            .path("/my-index/tail")
+            .method("GET")
+            .reply(&ingest_api_handlers)
+            .await;
+        let fetch_response: FetchResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(fetch_response.doc_batch.unwrap().num_docs(), 4);
+
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_ingest_api_return_429_if_above_limits() {
         let config = IngestApiConfig {
@@ -427,6 +427,13 @@ mod tests {
                     errors: vec![],
                     aggregation: None,
                     scroll_id: None,
+                    failed_splits: Vec::new(),
+                    next_page_token: None,
+                    stats: None,
+                    failed_indexes: Vec::new(),
+                    num_hits_accuracy: quickwit_proto::search::HitCountAccuracy::Exact as i32,
+                    timed_out: false,
+                    warnings: Vec::new(),
                 })
             });
         let mock_search_service = Arc::new(mock_search_service);
@@ -458,6 +465,13 @@ mod tests {
                     errors: vec![],
                     aggregation: None,
                     scroll_id: None,
+                    failed_splits: Vec::new(),
+                    next_page_token: None,
+                    stats: None,
+                    failed_indexes: Vec::new(),
+                    num_hits_accuracy: quickwit_proto::search::HitCountAccuracy::Exact as i32,
+                    timed_out: false,
+                    warnings: Vec::new(),
                 })
             });
         let mock_search_service = Arc::new(mock_search_service);
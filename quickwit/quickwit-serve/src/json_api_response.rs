@@ -68,8 +68,12 @@ pub(crate) fn make_json_api_response<T: serde::Serialize, E: ServiceError>(
 }
 
 /// A JSON reply for the REST API.
+///
+/// Despite its name, the body may also be serialized in one of the binary formats negotiated with
+/// the client.
 pub struct JsonApiResponse {
     status_code: status::StatusCode,
+    content_type: &'static str,
     inner: Result<Vec<u8>, ()>,
 }
 
@@ -80,7 +84,11 @@ impl JsonApiResponse {
         body_format: &BodyFormat,
     ) -> Self {
         let inner = body_format.result_to_vec(result);
-        JsonApiResponse { status_code, inner }
+        JsonApiResponse {
+            status_code,
+            content_type: body_format.content_type(),
+            inner,
+        }
     }
 }
 
@@ -92,7 +100,7 @@ impl Reply for JsonApiResponse {
                 let mut response = Response::new(body.into());
                 response
                     .headers_mut()
-                    .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+                    .insert(CONTENT_TYPE, HeaderValue::from_static(self.content_type));
                 *response.status_mut() = self.status_code;
                 response
             }
@@ -25,6 +25,7 @@ mod format;
 mod grpc;
 mod health_check_api;
 mod index_api;
+mod index_changes_api;
 mod indexing_api;
 mod ingest_api;
 mod jaeger_api;
@@ -42,6 +43,7 @@ mod ui_handler;
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 use std::fs;
+use std::future::Future;
 use std::net::SocketAddr;
 use std::num::NonZeroUsize;
 use std::path::PathBuf;
@@ -51,7 +53,7 @@ use std::time::Duration;
 use anyhow::Context;
 use bytesize::ByteSize;
 pub use format::BodyFormat;
-use futures::{Stream, StreamExt};
+use futures::{future, Stream, StreamExt};
 use itertools::Itertools;
 use quickwit_actors::{ActorExitStatus, Mailbox, Universe};
 use quickwit_cluster::{
@@ -59,6 +61,7 @@ use quickwit_cluster::{
 };
 use quickwit_common::pubsub::{EventBroker, EventSubscriptionHandle};
 use quickwit_common::rate_limiter::RateLimiterSettings;
+use quickwit_common::retry_budget::{set_global_retry_budget, RetryBudget};
 use quickwit_common::runtimes::RuntimesConfig;
 use quickwit_common::tower::{
     BalanceChannel, BoxFutureInfaillible, BufferLayer, Change, ConstantRate, EstimateRateLayer,
@@ -95,7 +98,7 @@ use quickwit_proto::search::ReportSplitsRequest;
 use quickwit_proto::types::NodeId;
 use quickwit_search::{
     create_search_client_from_channel, start_searcher_service, SearchJobPlacer, SearchService,
-    SearchServiceClient, SearcherContext, SearcherPool,
+    SearchServiceClient, SearcherContext, SearcherPool, SynonymTableCache,
 };
 use quickwit_storage::{SplitCache, StorageResolver};
 use tokio::sync::oneshot;
@@ -105,7 +108,10 @@ use tracing::{debug, error, info, warn};
 use warp::{Filter, Rejection};
 
 pub use crate::build_info::{BuildInfo, RuntimeInfo};
-pub use crate::index_api::{ListSplitsQueryParams, ListSplitsResponse};
+pub use crate::index_api::{
+    IndexSummary, ListSplitsQueryParams, ListSplitsResponse, SplitMetadataUpdate,
+};
+use crate::index_changes_api::{IndexChangeLog, IndexChangeLogLayer};
 pub use crate::metrics::SERVE_METRICS;
 use crate::rate_modulator::RateModulator;
 #[cfg(test)]
@@ -137,6 +143,10 @@ struct QuickwitServices {
     /// It is only used to serve the rest API calls and will only execute
     /// the root requests.
     pub search_service: Arc<dyn SearchService>,
+    /// Places the leaf requests of the root searches and tracks the health of the searchers.
+    pub search_job_placer: SearchJobPlacer,
+    /// Changes of the split sets of the indexes, only recorded on nodes running the metastore.
+    pub index_change_log_opt: Option<IndexChangeLog>,
 
     /// The control plane listens to various events.
     /// We must maintain a reference to the subscription handles to continue receiving
@@ -266,7 +276,22 @@ pub async fn serve_quickwit(
     metastore_resolver: MetastoreResolver,
     storage_resolver: StorageResolver,
     shutdown_signal: BoxFutureInfaillible<()>,
+    drain_timeout: Duration,
+    metrics_listen_addr_opt: Option<SocketAddr>,
 ) -> anyhow::Result<HashMap<String, ActorExitStatus>> {
+    // The retry budget must be set before any retry layer is instantiated.
+    if let Some(retry_budget_config) = node_config.searcher_config.retry_budget {
+        let retry_budget = RetryBudget::new(
+            retry_budget_config.retry_ratio_percent.get(),
+            retry_budget_config.max_retries.get(),
+        );
+        if !set_global_retry_budget(retry_budget) {
+            warn!("retry budget already set, ignoring `searcher.retry_budget` config");
+        }
+    }
+    let metrics_server_builder_opt = metrics_listen_addr_opt
+        .map(rest::bind_metrics_server)
+        .transpose()?;
     let cluster = start_cluster_service(&node_config).await?;
 
     let event_broker = EventBroker::default();
@@ -274,24 +299,39 @@ pub async fn serve_quickwit(
     let ingester_pool = IngesterPool::default();
     let universe = Universe::new();
 
-    // Instantiate a metastore "server" if the `metastore` role is enabled on the node.
-    let metastore_server_opt: Option<MetastoreServiceClient> =
-        if node_config.is_service_enabled(QuickwitService::Metastore) {
-            let metastore: MetastoreServiceClient = metastore_resolver
-                .resolve(&node_config.metastore_uri)
-                .await?;
-            let broker_layer = EventListenerLayer::new(event_broker.clone());
-            let metastore = MetastoreServiceClient::tower()
-                .create_index_layer(broker_layer.clone())
-                .delete_index_layer(broker_layer.clone())
-                .add_source_layer(broker_layer.clone())
-                .delete_source_layer(broker_layer.clone())
-                .toggle_source_layer(broker_layer)
-                .build(metastore);
-            Some(metastore)
-        } else {
-            None
-        };
+    // Instantiate a metastore "server" if the `metastore` role is enabled on the node. The changes
+    // of the split sets of the indexes are recorded on the mutation path of the metastore, so they
+    // are only available on the nodes running the metastore service.
+    let (metastore_server_opt, index_change_log_opt): (
+        Option<MetastoreServiceClient>,
+        Option<IndexChangeLog>,
+    ) = if node_config.is_service_enabled(QuickwitService::Metastore) {
+        let metastore: MetastoreServiceClient = metastore_resolver
+            .resolve(&node_config.metastore_uri)
+            .await?;
+        let index_change_log = IndexChangeLog::default();
+        let change_log_layer = IndexChangeLogLayer::new(index_change_log.clone());
+        let metastore = MetastoreServiceClient::tower()
+            .delete_index_layer(change_log_layer.clone())
+            .publish_splits_layer(change_log_layer.clone())
+            .mark_splits_for_deletion_layer(change_log_layer.clone())
+            .delete_splits_layer(change_log_layer)
+            .build(metastore);
+        let broker_layer = EventListenerLayer::new(event_broker.clone());
+        let metastore = MetastoreServiceClient::tower()
+            .create_index_layer(broker_layer.clone())
+            .delete_index_layer(broker_layer.clone())
+            .add_source_layer(broker_layer.clone())
+            .delete_source_layer(broker_layer.clone())
+            .toggle_source_layer(broker_layer.clone())
+            .publish_splits_layer(broker_layer.clone())
+            .mark_splits_for_deletion_layer(broker_layer.clone())
+            .delete_splits_layer(broker_layer)
+            .build(metastore);
+        (Some(metastore), Some(index_change_log))
+    } else {
+        (None, None)
+    };
     // Instantiate a metastore client, either local if available or remote otherwise.
     let metastore_client: MetastoreServiceClient =
         if let Some(metastore_server) = &metastore_server_opt {
@@ -437,10 +477,10 @@ pub async fn serve_quickwit(
             None
         };
 
-    let searcher_context = Arc::new(SearcherContext::new(
-        node_config.searcher_config.clone(),
-        split_cache_opt,
-    ));
+    let mut searcher_context =
+        SearcherContext::new(node_config.searcher_config.clone(), split_cache_opt);
+    searcher_context.synonym_table_cache = SynonymTableCache::new(storage_resolver.clone());
+    let searcher_context = Arc::new(searcher_context);
 
     let (search_job_placer, search_service) = setup_searcher(
         cluster_change_stream,
@@ -476,7 +516,7 @@ pub async fn serve_quickwit(
             &universe,
             &node_config,
             metastore_through_control_plane.clone(),
-            search_job_placer,
+            search_job_placer.clone(),
             storage_resolver.clone(),
             event_broker.clone(),
         )
@@ -516,6 +556,8 @@ pub async fn serve_quickwit(
         janitor_service_opt,
         jaeger_service_opt,
         search_service,
+        search_job_placer,
+        index_change_log_opt,
     });
     // Setup and start gRPC server.
     let (grpc_readiness_trigger_tx, grpc_readiness_signal_rx) = oneshot::channel::<()>();
@@ -535,6 +577,7 @@ pub async fn serve_quickwit(
         quickwit_services.clone(),
         grpc_readiness_trigger,
         grpc_shutdown_signal,
+        drain_timeout,
     );
     // Setup and start REST server.
     let (rest_readiness_trigger_tx, rest_readiness_signal_rx) = oneshot::channel::<()>();
@@ -554,12 +597,29 @@ pub async fn serve_quickwit(
         quickwit_services,
         rest_readiness_trigger,
         rest_shutdown_signal,
+        drain_timeout,
+        metrics_listen_addr_opt.is_none(),
     );
+    // Setup and start the metrics server, which serves the `/metrics` route on its own address
+    // instead of the REST server when `metrics_listen_addr_opt` is set.
+    let (metrics_shutdown_trigger_tx, metrics_shutdown_signal_rx) = oneshot::channel::<()>();
+    let metrics_join_handle_opt = metrics_server_builder_opt.map(|metrics_server_builder| {
+        let metrics_shutdown_signal = Box::pin(async move {
+            if metrics_shutdown_signal_rx.await.is_err() {
+                debug!("metrics server shutdown trigger sender was dropped");
+            }
+        });
+        tokio::spawn(rest::start_metrics_server(
+            metrics_server_builder,
+            metrics_shutdown_signal,
+            drain_timeout,
+        ))
+    });
 
     // Node readiness indicates that the server is ready to receive requests.
     // Thus readiness task is started once gRPC and REST servers are started.
-    tokio::spawn(node_readiness_reporting_task(
-        cluster,
+    let node_readiness_reporting_handle = tokio::spawn(node_readiness_reporting_task(
+        cluster.clone(),
         metastore_through_control_plane,
         grpc_readiness_signal_rx,
         rest_readiness_signal_rx,
@@ -567,6 +627,12 @@ pub async fn serve_quickwit(
     let shutdown_handle = tokio::spawn(async move {
         shutdown_signal.await;
 
+        // We mark the node as not ready so that peers stop routing requests and search jobs to
+        // it while the in-flight ones are drained.
+        info!("shutting down node, marking it as not ready");
+        node_readiness_reporting_handle.abort();
+        cluster.set_self_node_readiness(false).await;
+
         // We must decommission the ingester first before terminating the indexing pipelines that
         // may consume from it. We also need to keep the gRPC server running while doing so.
         wait_for_ingester_decommission(ingester_service_opt).await;
@@ -578,6 +644,9 @@ pub async fn serve_quickwit(
         if rest_shutdown_trigger_tx.send(()).is_err() {
             debug!("REST server shutdown signal receiver was dropped");
         }
+        if metrics_shutdown_trigger_tx.send(()).is_err() {
+            debug!("metrics server shutdown signal receiver was dropped");
+        }
         actor_exit_statuses
     });
     let grpc_join_handle = tokio::spawn(grpc_server);
@@ -592,6 +661,14 @@ pub async fn serve_quickwit(
     if let Err(rest_err) = rest_res {
         error!("REST server failed: {:?}", rest_err);
     }
+    if let Some(metrics_join_handle) = metrics_join_handle_opt {
+        let metrics_res = metrics_join_handle
+            .await
+            .expect("the task running the metrics server should not panic or be cancelled");
+        if let Err(metrics_err) = metrics_res {
+            error!("metrics server failed: {:?}", metrics_err);
+        }
+    }
     let actor_exit_statuses = shutdown_handle.await?;
     Ok(actor_exit_statuses)
 }
@@ -689,6 +766,14 @@ async fn setup_searcher(
 ) -> anyhow::Result<(SearchJobPlacer, Arc<dyn SearchService>)> {
     let searcher_pool = SearcherPool::default();
     let search_job_placer = SearchJobPlacer::new(searcher_pool.clone());
+    // Leaf search requests may carry their own deadline, derived from the cost of their splits.
+    // The channel timeout must not cut them short.
+    let channel_timeout = searcher_context
+        .searcher_config
+        .leaf_search_timeout
+        .map(|leaf_search_timeout| leaf_search_timeout.max_timeout())
+        .unwrap_or_default()
+        .max(Duration::from_secs(30));
     let search_service = start_searcher_service(
         metastore,
         storage_resolver,
@@ -697,8 +782,10 @@ async fn setup_searcher(
     )
     .await?;
     let search_service_clone = search_service.clone();
+    let search_job_placer_clone = search_job_placer.clone();
     let searcher_change_stream = cluster_change_stream.filter_map(move |cluster_change| {
         let search_service_clone = search_service_clone.clone();
+        let search_job_placer_clone = search_job_placer_clone.clone();
         Box::pin(async move {
             match cluster_change {
                 ClusterChange::Add(node)
@@ -711,13 +798,17 @@ async fn setup_searcher(
                             SearchServiceClient::from_service(search_service_clone, grpc_addr);
                         Some(Change::Insert(grpc_addr, search_client))
                     } else {
-                        let timeout_channel = Timeout::new(node.channel(), Duration::from_secs(30));
+                        let timeout_channel = Timeout::new(node.channel(), channel_timeout);
                         let search_client =
                             create_search_client_from_channel(grpc_addr, timeout_channel);
                         Some(Change::Insert(grpc_addr, search_client))
                     }
                 }
-                ClusterChange::Remove(node) => Some(Change::Remove(node.grpc_advertise_addr())),
+                ClusterChange::Remove(node) => {
+                    let grpc_addr = node.grpc_advertise_addr();
+                    search_job_placer_clone.remove_node(&grpc_addr);
+                    Some(Change::Remove(grpc_addr))
+                }
                 _ => None,
             }
         })
@@ -870,6 +961,44 @@ async fn node_readiness_reporting_task(
     }
 }
 
+/// Runs a server until its graceful shutdown completes, giving it at most `drain_timeout` after
+/// `shutdown_signal` fires to finish serving in-flight requests.
+///
+/// `serve` is passed the signal that initiates the graceful shutdown: the server must stop
+/// accepting new connections once it resolves.
+async fn serve_with_drain_timeout<F, E>(
+    server_name: &'static str,
+    serve: impl FnOnce(BoxFutureInfaillible<()>) -> F,
+    shutdown_signal: BoxFutureInfaillible<()>,
+    drain_timeout: Duration,
+) -> Result<(), E>
+where
+    F: Future<Output = Result<(), E>>,
+{
+    let (drain_started_tx, drain_started_rx) = oneshot::channel::<()>();
+    let graceful_shutdown_signal = Box::pin(async move {
+        shutdown_signal.await;
+        let _ = drain_started_tx.send(());
+    });
+    let drain_deadline = async move {
+        if drain_started_rx.await.is_ok() {
+            tokio::time::sleep(drain_timeout).await;
+        } else {
+            future::pending::<()>().await;
+        }
+    };
+    tokio::select! {
+        serve_res = serve(graceful_shutdown_signal) => serve_res,
+        _ = drain_deadline => {
+            warn!(
+                drain_timeout=?drain_timeout,
+                "{server_name} server did not drain in-flight requests before timeout"
+            );
+            Ok(())
+        }
+    }
+}
+
 /// Displays some warnings if the cluster runs a file-backed metastore or serves file-backed
 /// indexes.
 async fn check_cluster_configuration(
@@ -997,6 +1126,96 @@ mod tests {
         assert!(!cluster.is_self_node_ready().await);
     }
 
+    #[tokio::test]
+    async fn test_serve_with_drain_timeout_completes_in_flight_requests() {
+        let tcp_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let listen_addr = tcp_listener.local_addr().unwrap();
+
+        let (request_received_tx, mut request_received_rx) = mpsc::channel::<()>(1);
+        let slow_route = warp::any().then(move || {
+            let request_received_tx = request_received_tx.clone();
+            async move {
+                request_received_tx.send(()).await.unwrap();
+                tokio::time::sleep(Duration::from_millis(200)).await;
+                "done"
+            }
+        });
+        let service = warp::service(slow_route);
+        let (shutdown_trigger_tx, shutdown_signal_rx) = oneshot::channel::<()>();
+        let shutdown_signal = Box::pin(async move {
+            let _ = shutdown_signal_rx.await;
+        });
+        let server_handle = tokio::spawn(serve_with_drain_timeout(
+            "test",
+            move |graceful_shutdown_signal| {
+                hyper::Server::from_tcp(tcp_listener)
+                    .unwrap()
+                    .serve(tower::make::Shared::new(service))
+                    .with_graceful_shutdown(graceful_shutdown_signal)
+            },
+            shutdown_signal,
+            Duration::from_secs(5),
+        ));
+        let uri: hyper::Uri = format!("http://{listen_addr}/").parse().unwrap();
+        let request_handle = tokio::spawn({
+            let uri = uri.clone();
+            async move { hyper::Client::new().get(uri).await }
+        });
+        request_received_rx.recv().await.unwrap();
+        shutdown_trigger_tx.send(()).unwrap();
+
+        let response = request_handle.await.unwrap().unwrap();
+        assert_eq!(response.status(), hyper::StatusCode::OK);
+        let body = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        assert_eq!(body, "done");
+
+        server_handle.await.unwrap().unwrap();
+
+        let error = hyper::Client::new().get(uri).await.unwrap_err();
+        assert!(error.is_connect());
+    }
+
+    #[tokio::test]
+    async fn test_serve_with_drain_timeout_expires() {
+        let tcp_listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let listen_addr = tcp_listener.local_addr().unwrap();
+
+        let (request_received_tx, mut request_received_rx) = mpsc::channel::<()>(1);
+        let stuck_route = warp::any().then(move || {
+            let request_received_tx = request_received_tx.clone();
+            async move {
+                request_received_tx.send(()).await.unwrap();
+                future::pending::<&str>().await
+            }
+        });
+        let service = warp::service(stuck_route);
+        let (shutdown_trigger_tx, shutdown_signal_rx) = oneshot::channel::<()>();
+        let shutdown_signal = Box::pin(async move {
+            let _ = shutdown_signal_rx.await;
+        });
+        let server_handle = tokio::spawn(serve_with_drain_timeout(
+            "test",
+            move |graceful_shutdown_signal| {
+                hyper::Server::from_tcp(tcp_listener)
+                    .unwrap()
+                    .serve(tower::make::Shared::new(service))
+                    .with_graceful_shutdown(graceful_shutdown_signal)
+            },
+            shutdown_signal,
+            Duration::from_millis(50),
+        ));
+        let uri: hyper::Uri = format!("http://{listen_addr}/").parse().unwrap();
+        tokio::spawn(async move { hyper::Client::new().get(uri).await });
+        request_received_rx.recv().await.unwrap();
+        shutdown_trigger_tx.send(()).unwrap();
+
+        tokio::time::timeout(Duration::from_secs(5), server_handle)
+            .await
+            .expect("the server should stop once the drain timeout expires")
+            .unwrap()
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_setup_indexer_pool() {
         let universe = Universe::with_accelerated_time();
@@ -19,8 +19,11 @@
 
 use std::net::SocketAddr;
 use std::sync::Arc;
+use std::time::Duration;
 
+use anyhow::Context;
 use hyper::http::HeaderValue;
+use hyper::server::conn::AddrIncoming;
 use hyper::{http, Method};
 use quickwit_common::tower::BoxFutureInfaillible;
 use quickwit_proto::ServiceErrorCode;
@@ -37,15 +40,18 @@ use crate::delete_task_api::delete_task_api_handlers;
 use crate::elastic_search_api::elastic_api_handlers;
 use crate::health_check_api::health_check_handlers;
 use crate::index_api::index_management_handlers;
+use crate::index_changes_api::index_changes_handler;
 use crate::indexing_api::indexing_get_handler;
 use crate::ingest_api::ingest_api_handlers;
 use crate::jaeger_api::jaeger_api_handlers;
 use crate::json_api_response::{ApiError, JsonApiResponse};
 use crate::metrics_api::metrics_handler;
 use crate::node_info_handler::node_info_handler;
-use crate::search_api::{search_get_handler, search_post_handler, search_stream_handler};
+use crate::search_api::{
+    search_explain_handler, search_get_handler, search_post_handler, search_stream_handler,
+};
 use crate::ui_handler::ui_handler;
-use crate::{BodyFormat, BuildInfo, QuickwitServices, RuntimeInfo};
+use crate::{serve_with_drain_timeout, BodyFormat, BuildInfo, QuickwitServices, RuntimeInfo};
 
 /// The minimum size a response body must be in order to
 /// be automatically compressed with gzip.
@@ -67,6 +73,8 @@ pub(crate) async fn start_rest_server(
     quickwit_services: Arc<QuickwitServices>,
     readiness_trigger: BoxFutureInfaillible<()>,
     shutdown_signal: BoxFutureInfaillible<()>,
+    drain_timeout: Duration,
+    serve_metrics_route: bool,
 ) -> anyhow::Result<()> {
     let request_counter = warp::log::custom(|_| {
         crate::SERVE_METRICS.http_requests_total.inc();
@@ -83,8 +91,8 @@ pub(crate) async fn start_rest_server(
         quickwit_services.janitor_service_opt.clone(),
     );
 
-    // `/metrics` route.
-    let metrics_routes = warp::path("metrics").and(warp::get()).map(metrics_handler);
+    // `/metrics` route, unless it is served by the metrics server.
+    let metrics_routes = metrics_routes(serve_metrics_route);
 
     // `/api/v1/*` routes.
     let api_v1_root_route = api_v1_routes(quickwit_services.clone());
@@ -132,24 +140,77 @@ pub(crate) async fn start_rest_server(
         "Starting REST server listening on {rest_listen_addr}."
     );
 
-    // `graceful_shutdown()` seems to be blocking in presence of existing connections.
-    // The following approach of dropping the serve supposedly is not bullet proof, but it seems to
-    // work in our unit test.
+    // The graceful shutdown lets in-flight requests complete, but it may block on idle keep-alive
+    // connections, so the server is dropped once the drain timeout expires.
     //
     // See more of the discussion here:
     // https://github.com/hyperium/hyper/issues/2386
-    let serve_fut = async move {
-        tokio::select! {
-             res = hyper::Server::bind(&rest_listen_addr).serve(Shared::new(service)) => { res }
-             _ = shutdown_signal => { Ok(()) }
-        }
-    };
+    let serve_fut = serve_with_drain_timeout(
+        "REST",
+        |graceful_shutdown_signal| {
+            hyper::Server::bind(&rest_listen_addr)
+                .serve(Shared::new(service))
+                .with_graceful_shutdown(graceful_shutdown_signal)
+        },
+        shutdown_signal,
+        drain_timeout,
+    );
 
     let (serve_res, _trigger_res) = tokio::join!(serve_fut, readiness_trigger);
     serve_res?;
     Ok(())
 }
 
+/// Binds the listen address of the metrics server, so that an address already in use is reported
+/// before the node starts instead of failing the server in the background.
+pub(crate) fn bind_metrics_server(
+    metrics_listen_addr: SocketAddr,
+) -> anyhow::Result<hyper::server::Builder<AddrIncoming>> {
+    let metrics_server_builder = hyper::Server::try_bind(&metrics_listen_addr)
+        .with_context(|| format!("failed to bind metrics server to `{metrics_listen_addr}`"))?;
+    info!(
+        metrics_listen_addr=?metrics_listen_addr,
+        "Starting metrics server listening on {metrics_listen_addr}."
+    );
+    Ok(metrics_server_builder)
+}
+
+/// Starts a server exposing only the `/metrics` route, so that Prometheus can scrape the node
+/// without the REST API being reachable from the same address.
+pub(crate) async fn start_metrics_server(
+    metrics_server_builder: hyper::server::Builder<AddrIncoming>,
+    shutdown_signal: BoxFutureInfaillible<()>,
+    drain_timeout: Duration,
+) -> anyhow::Result<()> {
+    let warp_service = warp::service(metrics_routes(true).recover(recover_fn));
+    serve_with_drain_timeout(
+        "metrics",
+        |graceful_shutdown_signal| {
+            metrics_server_builder
+                .serve(Shared::new(warp_service))
+                .with_graceful_shutdown(graceful_shutdown_signal)
+        },
+        shutdown_signal,
+        drain_timeout,
+    )
+    .await?;
+    Ok(())
+}
+
+fn metrics_routes(
+    enabled: bool,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path("metrics")
+        .and(warp::get())
+        .and_then(move || async move {
+            if enabled {
+                Ok(metrics_handler())
+            } else {
+                Err(warp::reject::not_found())
+            }
+        })
+}
+
 fn api_v1_routes(
     quickwit_services: Arc<QuickwitServices>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -166,42 +227,51 @@ fn api_v1_routes(
     }
     let api_v1_root_url = warp::path!("api" / "v1" / ..);
     api_v1_root_url.and(
-        cluster_handler(quickwit_services.cluster.clone())
-            .or(node_info_handler(
-                BuildInfo::get(),
-                RuntimeInfo::get(),
-                quickwit_services.node_config.clone(),
-            ))
-            .or(indexing_get_handler(
-                quickwit_services.indexing_service_opt.clone(),
-            ))
-            .or(search_get_handler(quickwit_services.search_service.clone()))
-            .or(search_post_handler(
-                quickwit_services.search_service.clone(),
-            ))
-            .or(search_stream_handler(
-                quickwit_services.search_service.clone(),
-            ))
-            .or(ingest_api_handlers(
-                quickwit_services.ingest_router_service.clone(),
-                quickwit_services.ingest_service.clone(),
-                quickwit_services.node_config.ingest_api_config.clone(),
-            ))
-            .or(index_management_handlers(
-                quickwit_services.index_manager.clone(),
-                quickwit_services.node_config.clone(),
-            ))
-            .or(delete_task_api_handlers(
-                quickwit_services.metastore_client.clone(),
-            ))
-            .or(jaeger_api_handlers(
-                quickwit_services.jaeger_service_opt.clone(),
-            ))
-            .or(elastic_api_handlers(
-                quickwit_services.node_config.clone(),
-                quickwit_services.search_service.clone(),
-                quickwit_services.ingest_service.clone(),
-            )),
+        cluster_handler(
+            quickwit_services.cluster.clone(),
+            quickwit_services.search_job_placer.clone(),
+        )
+        .or(node_info_handler(
+            BuildInfo::get(),
+            RuntimeInfo::get(),
+            quickwit_services.node_config.clone(),
+        ))
+        .or(indexing_get_handler(
+            quickwit_services.indexing_service_opt.clone(),
+        ))
+        .or(search_get_handler(quickwit_services.search_service.clone()))
+        .or(search_post_handler(
+            quickwit_services.search_service.clone(),
+        ))
+        .or(search_stream_handler(
+            quickwit_services.search_service.clone(),
+        ))
+        .or(search_explain_handler(
+            quickwit_services.search_service.clone(),
+        ))
+        .or(ingest_api_handlers(
+            quickwit_services.ingest_router_service.clone(),
+            quickwit_services.ingest_service.clone(),
+            quickwit_services.node_config.ingest_api_config.clone(),
+        ))
+        .or(index_management_handlers(
+            quickwit_services.index_manager.clone(),
+            quickwit_services.node_config.clone(),
+        ))
+        .or(delete_task_api_handlers(
+            quickwit_services.metastore_client.clone(),
+        ))
+        .or(index_changes_handler(
+            quickwit_services.index_change_log_opt.clone(),
+        ))
+        .or(jaeger_api_handlers(
+            quickwit_services.jaeger_service_opt.clone(),
+        ))
+        .or(elastic_api_handlers(
+            quickwit_services.node_config.clone(),
+            quickwit_services.search_service.clone(),
+            quickwit_services.ingest_service.clone(),
+        )),
     )
 }
 
@@ -232,6 +302,11 @@ fn get_status_with_error(rejection: Rejection) -> ApiError {
             service_code: ServiceErrorCode::UnsupportedMediaType,
             message: error.to_string(),
         }
+    } else if let Some(error) = rejection.find::<crate::format::NotAcceptable>() {
+        ApiError {
+            service_code: ServiceErrorCode::NotAcceptable,
+            message: error.to_string(),
+        }
     } else if rejection.is_not_found() {
         ApiError {
             service_code: ServiceErrorCode::NotFound,
@@ -346,7 +421,7 @@ mod tests {
     use quickwit_proto::control_plane::ControlPlaneServiceClient;
     use quickwit_proto::ingest::router::IngestRouterServiceClient;
     use quickwit_proto::metastore::MetastoreServiceClient;
-    use quickwit_search::MockSearchService;
+    use quickwit_search::{MockSearchService, SearchJobPlacer};
     use quickwit_storage::StorageResolver;
     use tower::Service;
 
@@ -596,6 +671,7 @@ mod tests {
             cluster,
             control_plane_service,
             indexing_service_opt: None,
+            index_change_log_opt: None,
             index_manager: index_service,
             ingest_service: ingest_service_client(),
 
@@ -608,6 +684,7 @@ mod tests {
             metastore_server_opt: None,
             node_config: Arc::new(node_config.clone()),
             search_service: Arc::new(MockSearchService::new()),
+            search_job_placer: SearchJobPlacer::default(),
             jaeger_service_opt: None,
         };
 
@@ -647,4 +724,65 @@ mod tests {
             "custom-value-2"
         );
     }
+
+    #[tokio::test]
+    async fn test_metrics_routes() {
+        let handler = metrics_routes(true).recover(recover_fn);
+        let resp = warp::test::request().path("/metrics").reply(&handler).await;
+        assert_eq!(resp.status(), 200);
+
+        let handler = metrics_routes(false).recover(recover_fn);
+        let resp = warp::test::request().path("/metrics").reply(&handler).await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_server() {
+        let metrics_listen_port = quickwit_common::net::find_available_tcp_port().unwrap();
+        let metrics_listen_addr: SocketAddr = ([127, 0, 0, 1], metrics_listen_port).into();
+        let (shutdown_trigger_tx, shutdown_signal_rx) = tokio::sync::oneshot::channel::<()>();
+        let shutdown_signal = Box::pin(async move {
+            let _ = shutdown_signal_rx.await;
+        });
+        let metrics_server_builder = bind_metrics_server(metrics_listen_addr).unwrap();
+        let metrics_server_handle = tokio::spawn(start_metrics_server(
+            metrics_server_builder,
+            shutdown_signal,
+            Duration::from_secs(1),
+        ));
+        let metrics_uri: hyper::Uri = format!("http://{metrics_listen_addr}/metrics")
+            .parse()
+            .unwrap();
+        let mut response_opt = None;
+
+        for _ in 0..50 {
+            if let Ok(response) = hyper::Client::new().get(metrics_uri.clone()).await {
+                response_opt = Some(response);
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        let response = response_opt.expect("the metrics server should be listening");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let version_uri: hyper::Uri = format!("http://{metrics_listen_addr}/api/v1/version")
+            .parse()
+            .unwrap();
+        let response = hyper::Client::new().get(version_uri).await.unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        shutdown_trigger_tx.send(()).unwrap();
+        metrics_server_handle.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bind_metrics_server_address_in_use() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let metrics_listen_addr = listener.local_addr().unwrap();
+        let error = bind_metrics_server(metrics_listen_addr).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            format!("failed to bind metrics server to `{metrics_listen_addr}`")
+        );
+    }
 }
@@ -23,9 +23,9 @@ use async_trait::async_trait;
 use futures::TryStreamExt;
 use quickwit_proto::error::convert_to_grpc_result;
 use quickwit_proto::search::{
-    search_service_server as grpc, GetKvRequest, GetKvResponse, LeafListFieldsRequest,
-    LeafSearchStreamRequest, LeafSearchStreamResponse, ListFieldsRequest, ListFieldsResponse,
-    ReportSplitsRequest, ReportSplitsResponse,
+    search_service_server as grpc, GetKvRequest, GetKvResponse, HealthCheckRequest,
+    HealthCheckResponse, LeafListFieldsRequest, LeafSearchStreamRequest, LeafSearchStreamResponse,
+    ListFieldsRequest, ListFieldsResponse, ReportSplitsRequest, ReportSplitsResponse,
 };
 use quickwit_proto::{set_parent_span_from_request_metadata, tonic, ServiceError};
 use quickwit_search::SearchService;
@@ -53,6 +53,17 @@ impl grpc::SearchService for GrpcSearchAdapter {
         convert_to_grpc_result(search_result)
     }
 
+    #[instrument(skip(self, request))]
+    async fn explain_search(
+        &self,
+        request: tonic::Request<quickwit_proto::search::SearchRequest>,
+    ) -> Result<tonic::Response<quickwit_proto::search::ExplainResult>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let search_request = request.into_inner();
+        let explain_result = self.0.explain_search(search_request).await;
+        convert_to_grpc_result(explain_result)
+    }
+
     #[instrument(skip(self, request))]
     async fn leaf_search(
         &self,
@@ -183,4 +194,14 @@ impl grpc::SearchService for GrpcSearchAdapter {
         let resp = self.0.leaf_list_fields(request.into_inner()).await;
         convert_to_grpc_result(resp)
     }
+
+    #[instrument(skip(self, request))]
+    async fn health_check(
+        &self,
+        request: tonic::Request<HealthCheckRequest>,
+    ) -> Result<tonic::Response<HealthCheckResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let health_check_result = self.0.health_check(request.into_inner()).await;
+        convert_to_grpc_result(health_check_result)
+    }
 }
@@ -23,8 +23,9 @@ mod rest_handler;
 pub use self::grpc_adapter::GrpcSearchAdapter;
 pub(crate) use self::rest_handler::extract_index_id_patterns;
 pub use self::rest_handler::{
-    search_get_handler, search_post_handler, search_request_from_api_request,
-    search_stream_handler, SearchApi, SearchRequestQueryString, SortBy,
+    search_explain_handler, search_get_handler, search_post_handler,
+    search_request_from_api_request, search_stream_handler, SearchApi, SearchRequestQueryString,
+    SortBy,
 };
 
 #[cfg(test)]
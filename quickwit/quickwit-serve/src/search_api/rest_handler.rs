@@ -24,11 +24,16 @@ use futures::stream::StreamExt;
 use hyper::header::HeaderValue;
 use hyper::HeaderMap;
 use percent_encoding::percent_decode_str;
+use quickwit_common::geo::{GeoBoundingBoxFilter, GeoDistanceFilter, GeoFilter};
+use quickwit_common::{is_false, HumanDuration};
 use quickwit_config::validate_index_id_pattern;
-use quickwit_proto::search::{CountHits, OutputFormat, SortField, SortOrder};
+use quickwit_proto::search::{CountHits, ExplainResult, OutputFormat, SortField, SortOrder};
 use quickwit_proto::ServiceError;
-use quickwit_query::query_ast::query_ast_from_user_text;
-use quickwit_search::{SearchError, SearchResponseRest, SearchService};
+use quickwit_query::query_ast::{
+    query_ast_from_user_text, BoolQuery, FieldPresenceQuery, QueryAst, UserInputQuery,
+};
+use quickwit_query::BooleanOperand;
+use quickwit_search::{decode_page_token, SearchError, SearchResponseRest, SearchService};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value as JsonValue;
 use tracing::info;
@@ -36,15 +41,22 @@ use warp::hyper::header::CONTENT_TYPE;
 use warp::hyper::StatusCode;
 use warp::{reply, Filter, Rejection, Reply};
 
+use crate::format::extract_format_from_accept_header;
 use crate::json_api_response::make_json_api_response;
 use crate::simple_list::{from_simple_list, to_simple_list};
 use crate::{with_arg, BodyFormat};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(search_get_handler, search_post_handler, search_stream_handler,),
+    paths(
+        search_get_handler,
+        search_post_handler,
+        search_stream_handler,
+        search_explain_handler,
+    ),
     components(schemas(
         BodyFormat,
+        ExplainResult,
         OutputFormat,
         SearchRequestQueryString,
         SearchResponseRest,
@@ -226,6 +238,82 @@ pub struct SearchRequestQueryString {
     #[serde(with = "count_hits_from_bool")]
     #[serde(default = "count_hits_from_bool::default")]
     pub count_all: CountHits,
+    /// If set, restrict search to splits carrying at least one of these tags.
+    #[serde(default)]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "to_simple_list")]
+    pub tags: Option<Vec<String>>,
+    /// If set, restrict search to documents in which a value is present for each of these
+    /// fields. Equivalent to adding `_exists_:field` clauses to the query.
+    #[serde(default)]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "to_simple_list")]
+    pub exists: Option<Vec<String>>,
+    /// If set, restrict search to documents in which no value is present for any of these
+    /// fields. Equivalent to adding `NOT _exists_:field` clauses to the query.
+    #[serde(default)]
+    #[serde(deserialize_with = "from_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(serialize_with = "to_simple_list")]
+    pub missing: Option<Vec<String>>,
+    /// If set, the query is not expanded with the synonyms configured on the indexes.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub disable_synonyms: bool,
+    /// If set, the search runs against the splits recorded in this index snapshot.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_id: Option<String>,
+    /// If set, restrict search to documents whose `geo_point` field lies within this
+    /// bounding box.
+    #[param(value_type = Object)]
+    #[schema(value_type = Object)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geo_bbox: Option<GeoBoundingBoxFilter>,
+    /// If set, restrict search to documents whose `geo_point` field lies within
+    /// `radius_m` meters of `center`.
+    #[param(value_type = Object)]
+    #[schema(value_type = Object)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub geo_distance: Option<GeoDistanceFilter>,
+    /// If set, only the hits sorted after this cursor are returned. The cursor is the
+    /// `next_page_token` of the response of the previous page.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub search_after: Option<String>,
+    /// If set, the response includes statistics about the splits searched, like the time spent
+    /// searching each of them.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub debug: bool,
+    /// If set, the search returns the results collected so far once this duration has elapsed,
+    /// instead of waiting for all the splits to be searched (e.g. `500ms`, `5s`).
+    #[param(value_type = String)]
+    #[schema(value_type = String)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<HumanDuration>,
+    /// If set, the terms of the query match the terms of indexed text fields within this
+    /// Levenshtein distance (0 to 2).
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fuzzy_distance: Option<u32>,
+    /// Number of searchers running concurrently on the segments of each split. Raising it speeds
+    /// up the search of splits made of several segments on leaves with idle cores. Defaults to 1.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub num_searchers: Option<u32>,
+    /// Operator combining the clauses of the query that are not joined by an explicit `AND` or
+    /// `OR`: `AND` or `OR`. Defaults to `AND`.
+    #[param(value_type = String)]
+    #[schema(value_type = String)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_operator: Option<BooleanOperand>,
 }
 
 mod count_hits_from_bool {
@@ -256,6 +344,25 @@ mod count_hits_from_bool {
     }
 }
 
+/// Combines the query with the `exists` and `missing` field presence filters.
+fn add_field_presence_filters(
+    query_ast: QueryAst,
+    exists: Vec<String>,
+    missing: Vec<String>,
+) -> QueryAst {
+    if exists.is_empty() && missing.is_empty() {
+        return query_ast;
+    }
+    let field_presence_query_ast = |field: String| QueryAst::from(FieldPresenceQuery { field });
+    BoolQuery {
+        must: vec![query_ast],
+        filter: exists.into_iter().map(field_presence_query_ast).collect(),
+        must_not: missing.into_iter().map(field_presence_query_ast).collect(),
+        ..Default::default()
+    }
+    .into()
+}
+
 pub fn search_request_from_api_request(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
@@ -263,8 +370,28 @@ pub fn search_request_from_api_request(
     // The query ast below may still contain user input query. The actual
     // parsing of the user query will happen in the root service, and might require
     // the user of the docmapper default fields (which we do not have at this point).
-    let query_ast = query_ast_from_user_text(&search_request.query, search_request.search_fields);
+    // Like `query_ast_from_user_text`, the default operator is `AND` unless specified.
+    let user_input_query_ast: QueryAst = UserInputQuery {
+        user_text: search_request.query,
+        default_fields: search_request.search_fields,
+        default_operator: search_request
+            .default_operator
+            .unwrap_or(BooleanOperand::And),
+    }
+    .into();
+    let query_ast = add_field_presence_filters(
+        user_input_query_ast,
+        search_request.exists.unwrap_or_default(),
+        search_request.missing.unwrap_or_default(),
+    );
     let query_ast_json = serde_json::to_string(&query_ast)?;
+    let geo_filters = search_request
+        .geo_bbox
+        .map(GeoFilter::from)
+        .into_iter()
+        .chain(search_request.geo_distance.map(GeoFilter::from))
+        .map(|geo_filter| serde_json::to_string(&geo_filter))
+        .collect::<Result<Vec<String>, _>>()?;
     let search_request = quickwit_proto::search::SearchRequest {
         index_id_patterns,
         query_ast: query_ast_json,
@@ -278,8 +405,21 @@ pub fn search_request_from_api_request(
             .map(|agg| serde_json::to_string(&agg).expect("could not serialize JsonValue")),
         sort_fields: search_request.sort_by.sort_fields,
         scroll_ttl_secs: None,
-        search_after: None,
+        search_after: search_request
+            .search_after
+            .as_deref()
+            .map(decode_page_token)
+            .transpose()?,
         count_hits: search_request.count_all.into(),
+        tags: search_request.tags.unwrap_or_default(),
+        disable_synonyms: search_request.disable_synonyms,
+        snapshot_id: search_request.snapshot_id,
+        geo_filters,
+        timeout_millis: search_request
+            .timeout
+            .map(|timeout| timeout.as_duration().as_millis() as u64),
+        fuzzy_distance: search_request.fuzzy_distance,
+        num_searchers: search_request.num_searchers,
     };
     Ok(search_request)
 }
@@ -289,9 +429,13 @@ async fn search_endpoint(
     search_request: SearchRequestQueryString,
     search_service: &dyn SearchService,
 ) -> Result<SearchResponseRest, SearchError> {
+    let debug = search_request.debug;
     let search_request = search_request_from_api_request(index_id_patterns, search_request)?;
     let search_response = search_service.root_search(search_request).await?;
-    let search_response_rest = SearchResponseRest::try_from(search_response)?;
+    let mut search_response_rest = SearchResponseRest::try_from(search_response)?;
+    if !debug {
+        search_response_rest.stats = None;
+    }
     Ok(search_response_rest)
 }
 
@@ -315,10 +459,13 @@ fn search_post_filter(
 async fn search(
     index_id_patterns: Vec<String>,
     search_request: SearchRequestQueryString,
+    accepted_format_opt: Option<BodyFormat>,
     search_service: Arc<dyn SearchService>,
 ) -> impl warp::Reply {
     info!(request =? search_request, "search");
-    let body_format = search_request.format;
+    // A binary format requested through the `Accept` header takes precedence over the JSON
+    // flavor selected in the query string.
+    let body_format = accepted_format_opt.unwrap_or(search_request.format);
     let result = search_endpoint(index_id_patterns, search_request, &*search_service).await;
     make_json_api_response(result, body_format)
 }
@@ -342,6 +489,7 @@ pub fn search_get_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_get_filter()
+        .and(extract_format_from_accept_header())
         .and(with_arg(search_service))
         .then(search)
 }
@@ -367,10 +515,65 @@ pub fn search_post_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_post_filter()
+        .and(extract_format_from_accept_header())
         .and(with_arg(search_service))
         .then(search)
 }
 
+async fn search_explain_endpoint(
+    index_id_patterns: Vec<String>,
+    search_request: SearchRequestQueryString,
+    search_service: &dyn SearchService,
+) -> Result<ExplainResult, SearchError> {
+    let search_request = search_request_from_api_request(index_id_patterns, search_request)?;
+    let explain_result = search_service.explain_search(search_request).await?;
+    Ok(explain_result)
+}
+
+fn search_explain_filter(
+) -> impl Filter<Extract = (Vec<String>, SearchRequestQueryString), Error = Rejection> + Clone {
+    warp::path!(String / "search" / "explain")
+        .and_then(extract_index_id_patterns)
+        .and(warp::post())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+}
+
+async fn search_explain(
+    index_id_patterns: Vec<String>,
+    search_request: SearchRequestQueryString,
+    search_service: Arc<dyn SearchService>,
+) -> impl warp::Reply {
+    info!(request =? search_request, "search_explain");
+    let body_format = search_request.format;
+    let result = search_explain_endpoint(index_id_patterns, search_request, &*search_service).await;
+    make_json_api_response(result, body_format)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/{index_id}/search/explain",
+    request_body = SearchRequestQueryString,
+    responses(
+        (status = 200, description = "Successfully explained search.", body = ExplainResult)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to search."),
+    )
+)]
+/// Explain Search
+///
+/// Reports, split by split, whether the split was pruned by the time range, its number of
+/// matching documents and its top hits.
+pub fn search_explain_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    search_explain_filter()
+        .and(with_arg(search_service))
+        .then(search_explain)
+}
+
 #[utoipa::path(
     get,
     tag = "Search",
@@ -516,6 +719,8 @@ fn search_stream_filter(
 
 #[cfg(test)]
 mod tests {
+    use std::time::Duration;
+
     use assert_json_diff::{assert_json_eq, assert_json_include};
     use bytes::Bytes;
     use mockall::predicate;
@@ -531,7 +736,8 @@ mod tests {
         let mock_search_service_in_arc = Arc::new(mock_search_service);
         search_get_handler(mock_search_service_in_arc.clone())
             .or(search_post_handler(mock_search_service_in_arc.clone()))
-            .or(search_stream_handler(mock_search_service_in_arc))
+            .or(search_stream_handler(mock_search_service_in_arc.clone()))
+            .or(search_explain_handler(mock_search_service_in_arc))
             .recover(recover_fn)
     }
 
@@ -564,9 +770,16 @@ mod tests {
             num_hits: 55,
             hits: Vec::new(),
             snippets: None,
+            hit_index_ids: None,
             elapsed_time_micros: 0u64,
             errors: Vec::new(),
             aggregations: None,
+            next_page_token: None,
+            stats: None,
+            failed_indexes: Vec::new(),
+            num_hits_accuracy: None,
+            timed_out: false,
+            warnings: Vec::new(),
         };
         let search_response_json: JsonValue = serde_json::to_value(search_response)?;
         let expected_search_response_json: JsonValue = json!({
@@ -581,6 +794,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_serialize_search_response_with_failed_indexes() -> anyhow::Result<()> {
+        let search_response = quickwit_proto::search::SearchResponse {
+            num_hits: 3,
+            failed_indexes: vec![quickwit_proto::search::IndexSearchError {
+                index_id: "broken-index".to_string(),
+                error_code: "timeout".to_string(),
+                message: "timeout: listing the splits took too long".to_string(),
+            }],
+            num_hits_accuracy: quickwit_proto::search::HitCountAccuracy::LowerBound as i32,
+            ..Default::default()
+        };
+        let search_response_rest = SearchResponseRest::try_from(search_response)?;
+        let search_response_json: JsonValue = serde_json::to_value(search_response_rest)?;
+        let expected_search_response_json: JsonValue = json!({
+            "num_hits": 3,
+            "failed_indexes": [{
+                "index_id": "broken-index",
+                "error_code": "timeout",
+                "message": "timeout: listing the splits took too long",
+            }],
+            "num_hits_accuracy": "lower_bound",
+        });
+        assert_json_include!(
+            actual: search_response_json,
+            expected: expected_search_response_json
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_route_post() {
         let rest_search_api_filter = search_post_filter();
@@ -732,6 +975,199 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_route_tags() {
+        let rest_search_api_filter = search_get_filter();
+        let (indexes, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&tags=tenant:acme,region:us-east")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(indexes, vec!["quickwit-demo-index".to_string()]);
+        assert_eq!(
+            req.tags,
+            Some(vec![
+                "tenant:acme".to_string(),
+                "region:us-east".to_string()
+            ])
+        );
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(
+            search_request.tags,
+            vec!["tenant:acme".to_string(), "region:us-east".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_exists_and_missing() {
+        let rest_search_api_filter = search_get_filter();
+        let (_, req) = warp::test::request()
+            .path(
+                "/quickwit-demo-index/search?query=level:ERROR&exists=error.stack&missing=user_id",
+            )
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.exists, Some(vec!["error.stack".to_string()]));
+        assert_eq!(req.missing, Some(vec!["user_id".to_string()]));
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast).unwrap();
+        let QueryAst::Bool(bool_query) = query_ast else {
+            panic!("expected a bool query, got {query_ast:?}");
+        };
+        assert!(matches!(&bool_query.must[..], [QueryAst::UserInput(_)]));
+        assert_eq!(
+            bool_query.filter,
+            vec![QueryAst::from(FieldPresenceQuery {
+                field: "error.stack".to_string()
+            })]
+        );
+        assert_eq!(
+            bool_query.must_not,
+            vec![QueryAst::from(FieldPresenceQuery {
+                field: "user_id".to_string()
+            })]
+        );
+        assert!(bool_query.should.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_geo_filters() {
+        let rest_search_api_filter = search_post_filter();
+        let (_, req) = warp::test::request()
+            .method("POST")
+            .path("/quickwit-demo-index/search")
+            .json(&true)
+            .body(
+                r#"{"query": "*", "geo_bbox": {"field": "location", "top_left": {"lat": 48.9, "lon": 2.2}, "bottom_right": "48.8,2.4"}, "geo_distance": {"field": "location", "center": [2.35, 48.85], "radius_m": 1000.0}}"#,
+            )
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.geo_bbox.as_ref().unwrap().field, "location");
+        assert_eq!(req.geo_distance.as_ref().unwrap().radius_m, 1000.0);
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(search_request.geo_filters.len(), 2);
+        let geo_filter: GeoFilter = serde_json::from_str(&search_request.geo_filters[1]).unwrap();
+        assert!(matches!(geo_filter, GeoFilter::GeoDistance(_)));
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_geo_bbox_get() {
+        let rest_search_api_filter = search_get_filter();
+        let (_, req) = warp::test::request()
+            .path(
+                "/quickwit-demo-index/search?query=*&geo_bbox[field]=location&\
+                 geo_bbox[top_left]=48.9,2.2&geo_bbox[bottom_right]=48.8,2.4",
+            )
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        let geo_bbox = req.geo_bbox.unwrap();
+        assert_eq!(geo_bbox.field, "location");
+        assert_eq!(geo_bbox.top_left.lat(), 48.9);
+        assert_eq!(geo_bbox.bottom_right.lon(), 2.4);
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_disable_synonyms() {
+        let rest_search_api_filter = search_get_filter();
+        let (_, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=k8s&disable_synonyms=true")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert!(req.disable_synonyms);
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert!(search_request.disable_synonyms);
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_timeout() {
+        let rest_search_api_filter = search_get_filter();
+        let (_, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=k8s&timeout=1500ms")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(
+            req.timeout,
+            Some(HumanDuration::new(Duration::from_millis(1_500)))
+        );
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(search_request.timeout_millis, Some(1_500));
+
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=k8s&timeout=soon")
+            .reply(&search_handler(MockSearchService::new()))
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_fuzzy_distance() {
+        let rest_search_api_filter = search_get_filter();
+        let (_, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=kubernets&fuzzy_distance=1")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.fuzzy_distance, Some(1));
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(search_request.fuzzy_distance, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_num_searchers() {
+        let rest_search_api_filter = search_get_filter();
+        let (_, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&num_searchers=4")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.num_searchers, Some(4));
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        assert_eq!(search_request.num_searchers, Some(4));
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_default_operator() {
+        let rest_search_api_filter = search_get_filter();
+        let (_, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=hello%20world&default_operator=OR")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        assert_eq!(req.default_operator, Some(BooleanOperand::Or));
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast).unwrap();
+        let QueryAst::UserInput(user_input_query) = query_ast else {
+            panic!("expected a user input query, got {query_ast:?}");
+        };
+        assert_eq!(user_input_query.default_operator, BooleanOperand::Or);
+
+        let (_, req) = warp::test::request()
+            .path("/quickwit-demo-index/search?query=hello%20world")
+            .filter(&rest_search_api_filter)
+            .await
+            .unwrap();
+        let search_request =
+            search_request_from_api_request(vec!["quickwit-demo-index".to_string()], req).unwrap();
+        let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast).unwrap();
+        let QueryAst::UserInput(user_input_query) = query_ast else {
+            panic!("expected a user input query, got {query_ast:?}");
+        };
+        assert_eq!(user_input_query.default_operator, BooleanOperand::And);
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_route_simple_default_num_hits_default_offset() {
         let rest_search_api_filter = search_get_filter();
@@ -950,6 +1386,48 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_route_post_with_negative_max_hits() -> anyhow::Result<()> {
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/quickwit-demo-index/search")
+            .json(&true)
+            .body(r#"{"query": "*", "max_hits": -1}"#)
+            .reply(&search_handler(MockSearchService::new()))
+            .await;
+        assert_eq!(resp.status(), 400);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert!(resp_json["message"]
+            .as_str()
+            .unwrap()
+            .contains("invalid value: integer `-1`"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_route_post_index_not_found() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            Err(SearchError::IndexesNotFound {
+                index_ids: vec!["not-found-index".to_string()],
+            })
+        });
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/not-found-index/search")
+            .json(&true)
+            .body(r#"{"query": "*", "max_hits": 10}"#)
+            .reply(&search_handler(mock_search_service))
+            .await;
+        assert_eq!(resp.status(), 404);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert!(resp_json["message"]
+            .as_str()
+            .unwrap()
+            .contains("not-found-index"));
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_route_serialize_with_results() -> anyhow::Result<()> {
         let mut mock_search_service = MockSearchService::new();
@@ -978,6 +1456,62 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_explain_api() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_explain_search()
+            .withf(|search_request| {
+                search_request.index_id_patterns == vec!["quickwit-demo-index".to_string()]
+                    && search_request.start_timestamp == Some(100)
+            })
+            .returning(|_| {
+                Ok(ExplainResult {
+                    splits: vec![
+                        quickwit_proto::search::SplitExplanation {
+                            split_id: "split-1".to_string(),
+                            index_id: "quickwit-demo-index".to_string(),
+                            num_candidate_docs: 3,
+                            searcher_addr: "127.0.0.1:7281".to_string(),
+                            ..Default::default()
+                        },
+                        quickwit_proto::search::SplitExplanation {
+                            split_id: "split-2".to_string(),
+                            index_id: "quickwit-demo-index".to_string(),
+                            pruned_by_time_range: true,
+                            ..Default::default()
+                        },
+                    ],
+                })
+            });
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/quickwit-demo-index/search/explain")
+            .json(&true)
+            .body(r#"{"query": "*", "start_timestamp": 100}"#)
+            .reply(&search_handler(mock_search_service))
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = serde_json::json!({
+            "splits": [
+                {
+                    "split_id": "split-1",
+                    "pruned_by_time_range": false,
+                    "num_candidate_docs": 3,
+                    "searcher_addr": "127.0.0.1:7281",
+                },
+                {
+                    "split_id": "split-2",
+                    "pruned_by_time_range": true,
+                    "num_candidate_docs": 0,
+                }
+            ]
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_start_offset_and_num_hits_parameter() -> anyhow::Result<()> {
         let mut mock_search_service = MockSearchService::new();
@@ -1001,6 +1535,98 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_search_after_parameter() -> anyhow::Result<()> {
+        let search_after = quickwit_proto::search::PartialHit {
+            sort_value: Some(quickwit_proto::search::SortValue::I64(42).into()),
+            sort_value2: None,
+            split_id: "split-1".to_string(),
+            segment_ord: 0,
+            doc_id: 7,
+        };
+        let page_token = quickwit_search::encode_page_token(&search_after);
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(
+                move |search_request: &quickwit_proto::search::SearchRequest| {
+                    search_request.search_after.as_ref() == Some(&search_after)
+                },
+            ))
+            .returning(|_| Ok(Default::default()));
+        let rest_search_api_handler = search_handler(mock_search_service);
+        assert_eq!(
+            warp::test::request()
+                .path(&format!(
+                    "/quickwit-demo-index/search?query=*&search_after={page_token}"
+                ))
+                .reply(&rest_search_api_handler)
+                .await
+                .status(),
+            200
+        );
+        assert_eq!(
+            warp::test::request()
+                .path("/quickwit-demo-index/search?query=*&search_after=not-a-token")
+                .reply(&rest_search_api_handler)
+                .await
+                .status(),
+            400
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_debug_parameter() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            Ok(quickwit_proto::search::SearchResponse {
+                stats: Some(quickwit_proto::search::SearchStats {
+                    num_attempted_splits: 1,
+                    split_stats: vec![quickwit_proto::search::SplitSearchStats {
+                        split_id: "split-1".to_string(),
+                        searcher_addr: "127.0.0.1:7281".to_string(),
+                        search_duration_micros: 1_000,
+                        num_docs_scanned: 10,
+                        cache_stats: Some(quickwit_proto::search::DirectoryCacheStats {
+                            num_hits: 3,
+                            num_misses: 2,
+                            ..Default::default()
+                        }),
+                        partial: false,
+                    }],
+                }),
+                ..Default::default()
+            })
+        });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert!(resp_json.get("stats").is_none());
+
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*&debug=true")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert_eq!(resp_json["stats"]["num_attempted_splits"], 1);
+        assert_eq!(
+            resp_json["stats"]["split_stats"][0]["search_duration_micros"],
+            1_000
+        );
+        assert_eq!(
+            resp_json["stats"]["split_stats"][0]["cache_stats"]["num_hits"],
+            3
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_with_index_does_not_exist() -> anyhow::Result<()> {
         let mut mock_search_service = MockSearchService::new();
@@ -1199,6 +1825,156 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_route_serialize_hit_index_ids() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            let hits = ["index-1", "index-2", "index-1"]
+                .into_iter()
+                .map(|index_id| quickwit_proto::search::Hit {
+                    json: r#"{"title": "foo"}"#.to_string(),
+                    partial_hit: None,
+                    snippet: None,
+                    index_id: index_id.to_string(),
+                })
+                .collect();
+            Ok(quickwit_proto::search::SearchResponse {
+                hits,
+                num_hits: 3,
+                elapsed_time_micros: 16,
+                ..Default::default()
+            })
+        });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let resp = warp::test::request()
+            .path("/index-1,index-2/search?query=foo")
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert_eq!(
+            resp_json["hit_index_ids"],
+            serde_json::json!(["index-1", "index-2", "index-1"])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_api_content_negotiation() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .returning(|search_request| {
+                if search_request.query_ast.contains("fail") {
+                    return Err(SearchError::InvalidQuery("invalid query".to_string()));
+                }
+                Ok(quickwit_proto::search::SearchResponse {
+                    hits: vec![quickwit_proto::search::Hit {
+                        json: r#"{"title": "foo", "count": 3, "score": 0.5}"#.to_string(),
+                        partial_hit: None,
+                        snippet: None,
+                        index_id: "my-index".to_string(),
+                    }],
+                    num_hits: 1,
+                    elapsed_time_micros: 16,
+                    ..Default::default()
+                })
+            });
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let expected_hit = json!({"title": "foo", "count": 3, "score": 0.5});
+
+        // (path, accept header, expected status, expected body format)
+        let test_cases = [
+            (
+                "/my-index/search?query=foo",
+                None,
+                200,
+                Some(BodyFormat::PrettyJson),
+            ),
+            (
+                "/my-index/search?query=foo",
+                Some("*/*"),
+                200,
+                Some(BodyFormat::PrettyJson),
+            ),
+            (
+                "/my-index/search?query=foo&format=json",
+                Some("application/json"),
+                200,
+                Some(BodyFormat::Json),
+            ),
+            (
+                "/my-index/search?query=foo",
+                Some("application/msgpack"),
+                200,
+                Some(BodyFormat::MessagePack),
+            ),
+            (
+                "/my-index/search?query=foo&format=json",
+                Some("application/cbor"),
+                200,
+                Some(BodyFormat::Cbor),
+            ),
+            (
+                "/my-index/search?query=foo",
+                Some("text/html, application/cbor;q=0.9, */*;q=0.8"),
+                200,
+                Some(BodyFormat::Cbor),
+            ),
+            (
+                "/my-index/search?query=foo",
+                Some("definitely not a media type"),
+                200,
+                Some(BodyFormat::PrettyJson),
+            ),
+            (
+                "/my-index/search?query=fail",
+                Some("application/msgpack"),
+                400,
+                Some(BodyFormat::MessagePack),
+            ),
+            (
+                "/my-index/search?query=foo",
+                Some("application/xml"),
+                406,
+                None,
+            ),
+        ];
+        for (path, accept_opt, expected_status, expected_body_format_opt) in test_cases {
+            let mut request = warp::test::request().path(path);
+            if let Some(accept) = accept_opt {
+                request = request.header("accept", accept);
+            }
+            let resp = request.reply(&rest_search_api_handler).await;
+            assert_eq!(resp.status(), expected_status, "accept: {accept_opt:?}");
+            let Some(expected_body_format) = expected_body_format_opt else {
+                let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+                assert!(resp_json["message"]
+                    .as_str()
+                    .unwrap()
+                    .contains("application/msgpack"));
+                continue;
+            };
+            assert_eq!(
+                resp.headers()[CONTENT_TYPE],
+                expected_body_format.content_type()
+            );
+            let resp_value: JsonValue = expected_body_format
+                .parse_body(resp.body())
+                .map_err(anyhow::Error::msg)?;
+            if expected_status == 200 {
+                assert_eq!(resp_value["hits"][0], expected_hit);
+                assert!(resp_value["num_hits"].is_u64());
+            } else {
+                assert!(resp_value["message"].as_str().is_some());
+            }
+            if expected_body_format == BodyFormat::Json {
+                assert!(!resp.body().contains(&b'\n'));
+            }
+        }
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_multi_indexes() {
         {
@@ -368,6 +368,17 @@ impl ByteRangeCache {
             .unwrap()
             .put_slice(path, byte_range, bytes)
     }
+
+    /// Returns the number of bytes held by the cache.
+    pub fn num_bytes(&self) -> u64 {
+        self.inner.lock().unwrap().num_bytes
+    }
+
+    /// Returns the number of byte ranges held by the cache. Overlapping byte ranges are merged
+    /// into a single one.
+    pub fn num_items(&self) -> u64 {
+        self.inner.lock().unwrap().num_items
+    }
 }
 
 #[cfg(test)]
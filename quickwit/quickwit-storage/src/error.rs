@@ -22,6 +22,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use std::{fmt, io};
 
+use quickwit_common::retry::Retryable;
 use serde::{Deserialize, Serialize};
 use tantivy::directory::error::{OpenDirectoryError, OpenReadError};
 use thiserror::Error;
@@ -120,6 +121,15 @@ impl StorageError {
     }
 }
 
+impl Retryable for StorageError {
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind,
+            StorageErrorKind::Internal | StorageErrorKind::Timeout | StorageErrorKind::Io
+        )
+    }
+}
+
 impl From<io::Error> for StorageError {
     fn from(err: io::Error) -> StorageError {
         match err.kind() {
@@ -0,0 +1,684 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Two-class scheduling of storage requests.
+//!
+//! Every storage request is tagged either [`IoClass::Foreground`] (query traffic) or
+//! [`IoClass::Background`] (merges, cache prefetching, ...). The tag is carried by a task-local
+//! set with [`with_io_class`] rather than by an explicit parameter, so the [`Storage`] trait is
+//! left untouched. Requests issued outside of any [`with_io_class`] scope are foreground.
+//! Note that task-locals are not inherited by spawned tasks.
+//!
+//! The scheduler is a token bucket split in two:
+//! - a shared bucket, refilled at `max_requests_per_sec - background_min_requests_per_sec`;
+//! - a reserved bucket, refilled at `background_min_requests_per_sec`.
+//!
+//! Foreground requests draw from the shared bucket, and from the reserved bucket when no
+//! background request is waiting. Background requests draw from the reserved bucket, which
+//! guarantees they never fully starve, and from the shared bucket only when it is not needed by
+//! foreground requests: no foreground request is waiting for a token, the number of foreground
+//! requests in flight does not exceed `foreground_queue_depth_threshold`, and the shared bucket
+//! retains a headroom of tokens for the next foreground requests.
+//!
+//! [`Storage`]: crate::Storage
+
+use std::future::Future;
+use std::num::NonZeroU32;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use once_cell::sync::Lazy;
+use quickwit_common::get_from_env;
+use quickwit_common::uri::Uri;
+use tokio::io::AsyncRead;
+use tokio::time::Instant;
+use tracing::{error, warn};
+
+use crate::storage::SendableAsync;
+use crate::{BulkDeleteError, OwnedBytes, PutPayload, Storage, StorageResult, STORAGE_METRICS};
+
+/// Upper bound on the time a waiting request sleeps before checking the buckets again.
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+tokio::task_local! {
+    static IO_CLASS: IoClass;
+}
+
+/// Scheduling class of a storage request.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum IoClass {
+    /// Latency-sensitive requests, such as search warmup and fetch docs.
+    #[default]
+    Foreground,
+    /// Throughput-oriented requests, such as merge downloads and split cache prefetching.
+    Background,
+}
+
+impl IoClass {
+    /// Returns the label used for this class in metrics.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IoClass::Foreground => "foreground",
+            IoClass::Background => "background",
+        }
+    }
+}
+
+/// Runs `future` with all the storage requests it issues tagged with `io_class`.
+pub async fn with_io_class<F: Future>(io_class: IoClass, future: F) -> F::Output {
+    IO_CLASS.scope(io_class, future).await
+}
+
+/// Returns the class of the storage requests issued by the current task.
+pub fn current_io_class() -> IoClass {
+    IO_CLASS.try_with(|io_class| *io_class).unwrap_or_default()
+}
+
+/// Settings of an [`IoScheduler`].
+#[derive(Clone, Copy, Debug)]
+pub struct IoSchedulerSettings {
+    /// Total number of requests per second shared by both classes.
+    pub max_requests_per_sec: NonZeroU32,
+    /// Number of requests per second reserved for background requests. Must be lower than
+    /// `max_requests_per_sec`.
+    pub background_min_requests_per_sec: NonZeroU32,
+    /// Number of in-flight foreground requests above which background requests are restricted
+    /// to their reserved rate.
+    pub foreground_queue_depth_threshold: usize,
+}
+
+impl IoSchedulerSettings {
+    /// Reads the settings from the environment. Returns `None` if `QW_STORAGE_MAX_REQUESTS_PER_SEC`
+    /// is not set, in which case storage requests are not scheduled.
+    fn from_env() -> Option<IoSchedulerSettings> {
+        let max_requests_per_sec: u32 = get_from_env("QW_STORAGE_MAX_REQUESTS_PER_SEC", 0);
+        if max_requests_per_sec == 0 {
+            return None;
+        }
+        // Background requests are guaranteed 10% of the budget by default.
+        let default_background_min_requests_per_sec = max_requests_per_sec / 10;
+        let background_min_requests_per_sec: u32 = get_from_env(
+            "QW_STORAGE_BACKGROUND_MIN_REQUESTS_PER_SEC",
+            default_background_min_requests_per_sec,
+        );
+        let foreground_queue_depth_threshold: usize =
+            get_from_env("QW_STORAGE_FOREGROUND_QUEUE_DEPTH_THRESHOLD", 32);
+        Self::from_rates(
+            max_requests_per_sec,
+            background_min_requests_per_sec,
+            foreground_queue_depth_threshold,
+        )
+    }
+
+    /// Builds settings under which both token buckets are refilled: the background rate is
+    /// clamped between 1 and `max_requests_per_sec - 1`. Returns `None` if `max_requests_per_sec`
+    /// is too low to be split between the two classes.
+    fn from_rates(
+        max_requests_per_sec: u32,
+        background_min_requests_per_sec: u32,
+        foreground_queue_depth_threshold: usize,
+    ) -> Option<IoSchedulerSettings> {
+        if max_requests_per_sec < 2 {
+            error!(
+                max_requests_per_sec=%max_requests_per_sec,
+                "`QW_STORAGE_MAX_REQUESTS_PER_SEC` must be at least 2, storage requests will not \
+                 be scheduled"
+            );
+            return None;
+        }
+        let clamped_background_min_requests_per_sec =
+            background_min_requests_per_sec.clamp(1, max_requests_per_sec - 1);
+
+        if clamped_background_min_requests_per_sec != background_min_requests_per_sec {
+            warn!(
+                background_min_requests_per_sec=%background_min_requests_per_sec,
+                max_requests_per_sec=%max_requests_per_sec,
+                "`QW_STORAGE_BACKGROUND_MIN_REQUESTS_PER_SEC` must be between 1 and \
+                 `QW_STORAGE_MAX_REQUESTS_PER_SEC` - 1, using \
+                 {clamped_background_min_requests_per_sec}"
+            );
+        }
+        Some(IoSchedulerSettings {
+            max_requests_per_sec: NonZeroU32::new(max_requests_per_sec)?,
+            background_min_requests_per_sec: NonZeroU32::new(
+                clamped_background_min_requests_per_sec,
+            )?,
+            foreground_queue_depth_threshold,
+        })
+    }
+}
+
+/// A token bucket refilled continuously, holding at most one second worth of tokens.
+#[derive(Debug)]
+struct TokenBucket {
+    num_tokens: f64,
+    tokens_per_sec: f64,
+}
+
+impl TokenBucket {
+    fn new(tokens_per_sec: u32) -> Self {
+        TokenBucket {
+            num_tokens: tokens_per_sec as f64,
+            tokens_per_sec: tokens_per_sec as f64,
+        }
+    }
+
+    fn refill(&mut self, elapsed: Duration) {
+        self.num_tokens = self
+            .tokens_per_sec
+            .min(self.num_tokens + self.tokens_per_sec * elapsed.as_secs_f64());
+    }
+
+    fn try_acquire(&mut self) -> bool {
+        self.try_acquire_above(0.0)
+    }
+
+    /// Acquires a token only if at least `headroom` tokens remain afterwards.
+    fn try_acquire_above(&mut self, headroom: f64) -> bool {
+        if self.num_tokens >= 1.0 + headroom {
+            self.num_tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the number of tokens that background requests must leave in the bucket.
+    fn foreground_headroom(&self) -> f64 {
+        (self.tokens_per_sec / 10.0).max(1.0)
+    }
+
+    /// Returns the time until the next token becomes available.
+    fn time_to_next_token(&self) -> Duration {
+        Duration::from_secs_f64((1.0 - self.num_tokens).max(0.0) / self.tokens_per_sec)
+    }
+}
+
+#[derive(Debug)]
+struct TokenBuckets {
+    shared: TokenBucket,
+    reserved: TokenBucket,
+    refilled_at: Instant,
+}
+
+impl TokenBuckets {
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now - self.refilled_at;
+        self.shared.refill(elapsed);
+        self.reserved.refill(elapsed);
+        self.refilled_at = now;
+    }
+}
+
+#[derive(Debug, Default)]
+struct IoSchedulerCounters {
+    /// Number of foreground requests waiting for a token.
+    num_foreground_waiting: AtomicUsize,
+    /// Number of foreground requests waiting for a token or in flight.
+    num_foreground_queued: AtomicUsize,
+    /// Number of background requests waiting for a token.
+    num_background_waiting: AtomicUsize,
+}
+
+/// Decrements a counter when dropped.
+struct CounterGuard {
+    counters: Arc<IoSchedulerCounters>,
+    counter_fn: fn(&IoSchedulerCounters) -> &AtomicUsize,
+}
+
+impl CounterGuard {
+    fn new(
+        counters: Arc<IoSchedulerCounters>,
+        counter_fn: fn(&IoSchedulerCounters) -> &AtomicUsize,
+    ) -> Self {
+        counter_fn(&counters).fetch_add(1, Ordering::Relaxed);
+        CounterGuard {
+            counters,
+            counter_fn,
+        }
+    }
+}
+
+impl Drop for CounterGuard {
+    fn drop(&mut self) {
+        (self.counter_fn)(&self.counters).fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+/// A permit to issue one storage request. Foreground permits count toward the foreground queue
+/// depth until they are dropped.
+pub struct IoPermit {
+    _foreground_queued_guard_opt: Option<CounterGuard>,
+}
+
+/// Schedules storage requests according to their [`IoClass`]. See the module documentation.
+pub struct IoScheduler {
+    token_buckets: Mutex<TokenBuckets>,
+    counters: Arc<IoSchedulerCounters>,
+    foreground_queue_depth_threshold: usize,
+}
+
+impl IoScheduler {
+    /// Creates a new scheduler.
+    pub fn new(settings: IoSchedulerSettings) -> IoScheduler {
+        let max_requests_per_sec = settings.max_requests_per_sec.get();
+        let background_min_requests_per_sec = settings.background_min_requests_per_sec.get();
+        assert!(
+            background_min_requests_per_sec < max_requests_per_sec,
+            "`background_min_requests_per_sec` ({background_min_requests_per_sec}) should be \
+             lower than `max_requests_per_sec` ({max_requests_per_sec})"
+        );
+        let token_buckets = TokenBuckets {
+            shared: TokenBucket::new(max_requests_per_sec - background_min_requests_per_sec),
+            reserved: TokenBucket::new(background_min_requests_per_sec),
+            refilled_at: Instant::now(),
+        };
+        IoScheduler {
+            token_buckets: Mutex::new(token_buckets),
+            counters: Arc::default(),
+            foreground_queue_depth_threshold: settings.foreground_queue_depth_threshold,
+        }
+    }
+
+    /// Waits until a request of the given class may be issued.
+    pub async fn acquire(&self, io_class: IoClass) -> IoPermit {
+        let start = Instant::now();
+        let permit = match io_class {
+            IoClass::Foreground => self.acquire_foreground().await,
+            IoClass::Background => self.acquire_background().await,
+        };
+        let io_class_label = io_class.as_str();
+        STORAGE_METRICS
+            .io_scheduler_requests_total
+            .with_label_values([io_class_label])
+            .inc();
+        STORAGE_METRICS
+            .io_scheduler_wait_duration_seconds
+            .with_label_values([io_class_label])
+            .observe(start.elapsed().as_secs_f64());
+        permit
+    }
+
+    async fn acquire_foreground(&self) -> IoPermit {
+        let foreground_queued_guard = CounterGuard::new(self.counters.clone(), |counters| {
+            &counters.num_foreground_queued
+        });
+        let _foreground_waiting_guard = CounterGuard::new(self.counters.clone(), |counters| {
+            &counters.num_foreground_waiting
+        });
+        loop {
+            let wait = {
+                let mut token_buckets = self.token_buckets.lock().unwrap();
+                token_buckets.refill();
+
+                if token_buckets.shared.try_acquire() {
+                    break;
+                }
+                let background_waiting =
+                    self.counters.num_background_waiting.load(Ordering::Relaxed) > 0;
+
+                if !background_waiting && token_buckets.reserved.try_acquire() {
+                    break;
+                }
+                token_buckets.shared.time_to_next_token()
+            };
+            tokio::time::sleep(wait.min(MAX_POLL_INTERVAL)).await;
+        }
+        IoPermit {
+            _foreground_queued_guard_opt: Some(foreground_queued_guard),
+        }
+    }
+
+    async fn acquire_background(&self) -> IoPermit {
+        let _background_waiting_guard = CounterGuard::new(self.counters.clone(), |counters| {
+            &counters.num_background_waiting
+        });
+        loop {
+            let wait = {
+                let mut token_buckets = self.token_buckets.lock().unwrap();
+                token_buckets.refill();
+
+                if token_buckets.reserved.try_acquire() {
+                    break;
+                }
+                let headroom = token_buckets.shared.foreground_headroom();
+
+                if self.foreground_has_leftover_capacity()
+                    && token_buckets.shared.try_acquire_above(headroom)
+                {
+                    break;
+                }
+                token_buckets.reserved.time_to_next_token()
+            };
+            tokio::time::sleep(wait.min(MAX_POLL_INTERVAL)).await;
+        }
+        IoPermit {
+            _foreground_queued_guard_opt: None,
+        }
+    }
+
+    fn foreground_has_leftover_capacity(&self) -> bool {
+        self.counters.num_foreground_waiting.load(Ordering::Relaxed) == 0
+            && self.counters.num_foreground_queued.load(Ordering::Relaxed)
+                <= self.foreground_queue_depth_threshold
+    }
+}
+
+/// The scheduler shared by all the storages of the node, configured from the environment.
+static IO_SCHEDULER: Lazy<Option<IoScheduler>> =
+    Lazy::new(|| IoSchedulerSettings::from_env().map(IoScheduler::new));
+
+/// Wraps `storage` so that its requests go through the node's [`IoScheduler`], if one is
+/// configured.
+pub(crate) fn wrap_storage_with_io_scheduler(storage: Arc<dyn Storage>) -> Arc<dyn Storage> {
+    match IO_SCHEDULER.as_ref() {
+        Some(io_scheduler) => Arc::new(IoScheduledStorage {
+            underlying: storage,
+            io_scheduler,
+        }),
+        None => storage,
+    }
+}
+
+/// A storage wrapper acquiring an [`IoPermit`] for the class of the current task before each
+/// request.
+struct IoScheduledStorage {
+    underlying: Arc<dyn Storage>,
+    io_scheduler: &'static IoScheduler,
+}
+
+impl IoScheduledStorage {
+    async fn acquire(&self) -> IoPermit {
+        self.io_scheduler.acquire(current_io_class()).await
+    }
+}
+
+impl std::fmt::Debug for IoScheduledStorage {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("IoScheduledStorage")
+            .field("uri", self.underlying.uri())
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Storage for IoScheduledStorage {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    async fn put(&self, path: &Path, payload: Box<dyn PutPayload>) -> StorageResult<()> {
+        let _permit = self.acquire().await;
+        self.underlying.put(path, payload).await
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        let _permit = self.acquire().await;
+        self.underlying.copy_to(path, output).await
+    }
+
+    async fn copy_to_file(&self, path: &Path, output_path: &Path) -> StorageResult<u64> {
+        let _permit = self.acquire().await;
+        self.underlying.copy_to_file(path, output_path).await
+    }
+
+    async fn get_slice(&self, path: &Path, range: Range<usize>) -> StorageResult<OwnedBytes> {
+        let _permit = self.acquire().await;
+        self.underlying.get_slice(path, range).await
+    }
+
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        let _permit = self.acquire().await;
+        self.underlying.get_slice_stream(path, range).await
+    }
+
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        let _permit = self.acquire().await;
+        self.underlying.get_all(path).await
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        let _permit = self.acquire().await;
+        self.underlying.delete(path).await
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        let _permit = self.acquire().await;
+        self.underlying.bulk_delete(paths).await
+    }
+
+    async fn exists(&self, path: &Path) -> StorageResult<bool> {
+        let _permit = self.acquire().await;
+        self.underlying.exists(path).await
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        let _permit = self.acquire().await;
+        self.underlying.file_num_bytes(path).await
+    }
+
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicU64;
+
+    use super::*;
+
+    fn io_scheduler_for_test() -> Arc<IoScheduler> {
+        Arc::new(IoScheduler::new(IoSchedulerSettings {
+            max_requests_per_sec: NonZeroU32::new(100).unwrap(),
+            background_min_requests_per_sec: NonZeroU32::new(10).unwrap(),
+            foreground_queue_depth_threshold: 4,
+        }))
+    }
+
+    /// Spawns `num_tasks` tasks issuing background requests in a loop. Each request holds its
+    /// permit for 5ms. Returns the counter of completed requests.
+    fn spawn_background_load(io_scheduler: &Arc<IoScheduler>, num_tasks: usize) -> Arc<AtomicU64> {
+        let num_background_requests = Arc::new(AtomicU64::default());
+
+        for _ in 0..num_tasks {
+            let io_scheduler = io_scheduler.clone();
+            let num_background_requests = num_background_requests.clone();
+            tokio::spawn(with_io_class(IoClass::Background, async move {
+                loop {
+                    let _permit = io_scheduler.acquire(current_io_class()).await;
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    num_background_requests.fetch_add(1, Ordering::Relaxed);
+                }
+            }));
+        }
+        num_background_requests
+    }
+
+    /// Issues 50 foreground requests at 40 requests per second and returns the maximum time a
+    /// request waited for its permit.
+    async fn run_foreground_load(io_scheduler: &IoScheduler) -> Duration {
+        let mut max_wait = Duration::ZERO;
+
+        for _ in 0..50 {
+            let start = Instant::now();
+            let _permit = io_scheduler.acquire(current_io_class()).await;
+            max_wait = max_wait.max(start.elapsed());
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        max_wait
+    }
+
+    #[tokio::test]
+    async fn test_io_class_task_local() {
+        assert_eq!(current_io_class(), IoClass::Foreground);
+
+        let io_class = with_io_class(IoClass::Background, async { current_io_class() }).await;
+        assert_eq!(io_class, IoClass::Background);
+
+        assert_eq!(current_io_class(), IoClass::Foreground);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_io_scheduler_foreground_unaffected_by_background_load() {
+        let io_scheduler = io_scheduler_for_test();
+        let max_wait_alone = run_foreground_load(&io_scheduler).await;
+
+        let io_scheduler = io_scheduler_for_test();
+        let _num_background_requests = spawn_background_load(&io_scheduler, 16);
+        // Let the background load drain the buckets.
+        tokio::time::sleep(Duration::from_secs(2)).await;
+
+        let max_wait_with_background_load = run_foreground_load(&io_scheduler).await;
+        assert!(
+            max_wait_with_background_load <= max_wait_alone + MAX_POLL_INTERVAL,
+            "foreground requests waited up to {max_wait_with_background_load:?} under background \
+             load vs. {max_wait_alone:?} alone"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_io_scheduler_background_progresses_at_floor_rate() {
+        let io_scheduler = io_scheduler_for_test();
+        let num_background_requests = spawn_background_load(&io_scheduler, 16);
+
+        // Saturate the scheduler with foreground requests for 5 seconds.
+        let mut foreground_handles = Vec::new();
+
+        for _ in 0..16 {
+            let io_scheduler = io_scheduler.clone();
+            foreground_handles.push(tokio::spawn(async move {
+                let deadline = Instant::now() + Duration::from_secs(5);
+                let mut num_foreground_requests = 0u64;
+
+                while Instant::now() < deadline {
+                    let _permit = io_scheduler.acquire(IoClass::Foreground).await;
+                    num_foreground_requests += 1;
+                }
+                num_foreground_requests
+            }));
+        }
+        let mut num_foreground_requests = 0;
+
+        for foreground_handle in foreground_handles {
+            num_foreground_requests += foreground_handle.await.unwrap();
+        }
+        let num_background_requests = num_background_requests.load(Ordering::Relaxed);
+
+        // The initial burst aside, background requests get their floor of 10 requests per second
+        // and foreground requests get the rest.
+        assert!(
+            (45..=80).contains(&num_background_requests),
+            "{num_background_requests}"
+        );
+        assert!(
+            (430..=600).contains(&num_foreground_requests),
+            "{num_foreground_requests}"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_io_scheduler_background_uses_leftover_capacity() {
+        let io_scheduler = io_scheduler_for_test();
+        let num_background_requests = spawn_background_load(&io_scheduler, 16);
+
+        tokio::time::sleep(Duration::from_secs(5)).await;
+
+        // Without foreground traffic, background requests can use the whole budget.
+        let num_background_requests = num_background_requests.load(Ordering::Relaxed);
+        assert!(
+            (450..=650).contains(&num_background_requests),
+            "{num_background_requests}"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_io_scheduler_background_yields_to_foreground_queue_depth() {
+        let io_scheduler = io_scheduler_for_test();
+        let num_background_requests = spawn_background_load(&io_scheduler, 16);
+
+        // Hold more foreground permits than the queue depth threshold for 5 seconds.
+        let mut foreground_permits = Vec::new();
+
+        for _ in 0..5 {
+            foreground_permits.push(io_scheduler.acquire(IoClass::Foreground).await);
+        }
+        let num_background_requests_before = num_background_requests.load(Ordering::Relaxed);
+        tokio::time::sleep(Duration::from_secs(5)).await;
+        let num_background_requests_during =
+            num_background_requests.load(Ordering::Relaxed) - num_background_requests_before;
+        drop(foreground_permits);
+
+        // Background requests are restricted to their floor rate.
+        assert!(
+            (45..=65).contains(&num_background_requests_during),
+            "{num_background_requests_during}"
+        );
+    }
+
+    #[test]
+    fn test_io_scheduler_settings_from_rates() {
+        assert!(IoSchedulerSettings::from_rates(0, 0, 32).is_none());
+        assert!(IoSchedulerSettings::from_rates(1, 0, 32).is_none());
+        assert!(IoSchedulerSettings::from_rates(1, 1, 32).is_none());
+
+        for (max_requests_per_sec, background_min_requests_per_sec, expected) in [
+            (2, 0, 1),
+            (2, 1, 1),
+            (2, 2, 1),
+            (10, 0, 1),
+            (10, 1, 1),
+            (10, 9, 9),
+            (10, 10, 9),
+            (10, u32::MAX, 9),
+            (u32::MAX, 0, 1),
+        ] {
+            let settings = IoSchedulerSettings::from_rates(
+                max_requests_per_sec,
+                background_min_requests_per_sec,
+                32,
+            )
+            .unwrap();
+            assert_eq!(settings.max_requests_per_sec.get(), max_requests_per_sec);
+            assert_eq!(settings.background_min_requests_per_sec.get(), expected);
+            // The scheduler accepts the clamped settings.
+            IoScheduler::new(settings);
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_io_scheduler_invalid_settings() {
+        IoScheduler::new(IoSchedulerSettings {
+            max_requests_per_sec: NonZeroU32::new(10).unwrap(),
+            background_min_requests_per_sec: NonZeroU32::new(10).unwrap(),
+            foreground_queue_depth_threshold: 4,
+        });
+    }
+}
@@ -32,6 +32,7 @@
 //! - The `BundleStorage` bundles together multiple files into a single file.
 mod cache;
 mod debouncer;
+mod io_scheduler;
 mod metrics;
 mod storage;
 pub use debouncer::AsyncDebouncer;
@@ -48,6 +49,7 @@ mod object_storage;
 mod payload;
 mod prefix_storage;
 mod ram_storage;
+mod retry_storage;
 mod split;
 mod split_cache;
 mod storage_factory;
@@ -65,6 +67,9 @@ pub use self::cache::MockStorageCache;
 pub use self::cache::{
     wrap_storage_with_cache, ByteRangeCache, MemorySizedCache, QuickwitCache, StorageCache,
 };
+pub use self::io_scheduler::{
+    current_io_class, with_io_class, IoClass, IoPermit, IoScheduler, IoSchedulerSettings,
+};
 pub use self::local_file_storage::{LocalFileStorage, LocalFileStorageFactory};
 #[cfg(feature = "azure")]
 pub use self::object_storage::{AzureBlobStorage, AzureBlobStorageFactory};
@@ -72,6 +77,7 @@ pub use self::object_storage::{
     MultiPartPolicy, S3CompatibleObjectStorage, S3CompatibleObjectStorageFactory,
 };
 pub use self::ram_storage::{RamStorage, RamStorageBuilder};
+pub use self::retry_storage::RetryStorage;
 pub use self::split::{SplitPayload, SplitPayloadBuilder};
 #[cfg(any(test, feature = "testsuite"))]
 pub use self::storage::MockStorage;
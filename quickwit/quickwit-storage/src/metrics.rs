@@ -20,7 +20,10 @@
 // See https://prometheus.io/docs/practices/naming/
 
 use once_cell::sync::Lazy;
-use quickwit_common::metrics::{new_counter, new_gauge, IntCounter, IntGauge};
+use quickwit_common::metrics::{
+    new_counter, new_counter_vec, new_gauge, new_histogram_vec, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge,
+};
 
 /// Counters associated to storage operations.
 pub struct StorageMetrics {
@@ -34,6 +37,8 @@ pub struct StorageMetrics {
     pub object_storage_put_parts: IntCounter,
     pub object_storage_download_num_bytes: IntCounter,
     pub object_storage_upload_num_bytes: IntCounter,
+    pub io_scheduler_requests_total: IntCounterVec<1>,
+    pub io_scheduler_wait_duration_seconds: HistogramVec<1>,
 }
 
 impl Default for StorageMetrics {
@@ -71,6 +76,18 @@ impl Default for StorageMetrics {
                 "Amount of data uploaded to an object storage.",
                 "quickwit_storage",
             ),
+            io_scheduler_requests_total: new_counter_vec(
+                "io_scheduler_requests_total",
+                "Number of storage requests admitted by the I/O scheduler, per I/O class.",
+                "quickwit_storage",
+                ["io_class"],
+            ),
+            io_scheduler_wait_duration_seconds: new_histogram_vec(
+                "io_scheduler_wait_duration_seconds",
+                "Time spent by storage requests waiting for the I/O scheduler, per I/O class.",
+                "quickwit_storage",
+                ["io_class"],
+            ),
         }
     }
 }
@@ -0,0 +1,368 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use quickwit_common::retry::{retry, Retry, RetryParams, Retryable};
+use quickwit_common::uri::Uri;
+use tokio::io::AsyncRead;
+
+use crate::storage::SendableAsync;
+use crate::{BulkDeleteError, DeleteFailure, OwnedBytes, PutPayload, Storage, StorageResult};
+
+/// A storage wrapper retrying the `get_all`, `put`, `delete`, and `bulk_delete` requests that fail
+/// with a retryable error, with exponential backoff and jitter.
+///
+/// Errors that are not retryable, such as `NotFound` or `Unauthorized`, are returned immediately.
+/// The other requests are forwarded as is to the underlying storage.
+pub struct RetryStorage {
+    underlying: Arc<dyn Storage>,
+    retry_params: RetryParams,
+}
+
+impl RetryStorage {
+    /// Wraps `storage` so that its `get_all`, `put`, `delete`, and `bulk_delete` requests are
+    /// retried according to `retry_params`.
+    pub fn new(storage: Arc<dyn Storage>, retry_params: RetryParams) -> Self {
+        RetryStorage {
+            underlying: storage,
+            retry_params,
+        }
+    }
+}
+
+impl fmt::Debug for RetryStorage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryStorage")
+            .field("uri", self.underlying.uri())
+            .field("max_attempts", &self.retry_params.max_attempts)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Storage for RetryStorage {
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    async fn put(&self, path: &Path, payload: Box<dyn PutPayload>) -> StorageResult<()> {
+        retry(&self.retry_params, || {
+            self.underlying.put(path, payload.clone())
+        })
+        .await
+    }
+
+    async fn copy_to(&self, path: &Path, output: &mut dyn SendableAsync) -> StorageResult<()> {
+        self.underlying.copy_to(path, output).await
+    }
+
+    async fn copy_to_file(&self, path: &Path, output_path: &Path) -> StorageResult<u64> {
+        self.underlying.copy_to_file(path, output_path).await
+    }
+
+    async fn get_slice(&self, path: &Path, range: Range<usize>) -> StorageResult<OwnedBytes> {
+        self.underlying.get_slice(path, range).await
+    }
+
+    async fn get_slice_stream(
+        &self,
+        path: &Path,
+        range: Range<usize>,
+    ) -> StorageResult<Box<dyn AsyncRead + Send + Unpin>> {
+        self.underlying.get_slice_stream(path, range).await
+    }
+
+    async fn get_all(&self, path: &Path) -> StorageResult<OwnedBytes> {
+        retry(&self.retry_params, || self.underlying.get_all(path)).await
+    }
+
+    async fn delete(&self, path: &Path) -> StorageResult<()> {
+        retry(&self.retry_params, || self.underlying.delete(path)).await
+    }
+
+    async fn bulk_delete<'a>(&self, paths: &[&'a Path]) -> Result<(), BulkDeleteError> {
+        // Each attempt only deletes the files that failed with a retryable error or were not
+        // attempted by the previous one. The final error also reports the files deleted and the
+        // permanent failures of the previous attempts.
+        let state = Mutex::new(BulkDeleteState {
+            remaining_paths: paths.iter().map(|path| path.to_path_buf()).collect(),
+            ..Default::default()
+        });
+        let state_ref = &state;
+        let bulk_delete_res = retry(&self.retry_params, || async move {
+            let remaining_paths = state_ref.lock().unwrap().remaining_paths.clone();
+            let remaining_path_refs: Vec<&Path> =
+                remaining_paths.iter().map(PathBuf::as_path).collect();
+            let Err(bulk_delete_error) = self.underlying.bulk_delete(&remaining_path_refs).await
+            else {
+                return Ok(());
+            };
+            Err(state_ref.lock().unwrap().record_error(bulk_delete_error))
+        })
+        .await;
+        let state = state.into_inner().unwrap();
+        let mut bulk_delete_error = match bulk_delete_res {
+            Ok(()) if state.permanent_failures.is_empty() => return Ok(()),
+            // The last attempt deleted all the remaining files.
+            Ok(()) => BulkDeleteError {
+                successes: state.remaining_paths,
+                ..Default::default()
+            },
+            Err(retry_error) => retry_error.into_inner(),
+        };
+        bulk_delete_error.successes.extend(state.successes);
+        bulk_delete_error.failures.extend(state.permanent_failures);
+        Err(bulk_delete_error)
+    }
+
+    async fn exists(&self, path: &Path) -> StorageResult<bool> {
+        self.underlying.exists(path).await
+    }
+
+    async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
+        self.underlying.file_num_bytes(path).await
+    }
+
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+}
+
+#[derive(Default)]
+struct BulkDeleteState {
+    remaining_paths: Vec<PathBuf>,
+    successes: Vec<PathBuf>,
+    permanent_failures: HashMap<PathBuf, DeleteFailure>,
+}
+
+impl BulkDeleteState {
+    /// Records the outcome of a failed attempt and decides whether the remaining files should be
+    /// deleted again. The returned error only holds the retryable failures of the attempt.
+    fn record_error(&mut self, mut bulk_delete_error: BulkDeleteError) -> Retry<BulkDeleteError> {
+        self.successes.append(&mut bulk_delete_error.successes);
+
+        let failures = std::mem::take(&mut bulk_delete_error.failures);
+        self.remaining_paths.clear();
+
+        for (path, failure) in failures {
+            let is_retryable = failure
+                .error
+                .as_ref()
+                .map_or(false, |storage_error| storage_error.is_retryable());
+            if is_retryable {
+                self.remaining_paths.push(path.clone());
+                bulk_delete_error.failures.insert(path, failure);
+            } else {
+                self.permanent_failures.insert(path, failure);
+            }
+        }
+        let is_batch_error_retryable = bulk_delete_error
+            .error
+            .as_ref()
+            .map_or(true, |storage_error| storage_error.is_retryable());
+
+        if is_batch_error_retryable {
+            self.remaining_paths
+                .extend(bulk_delete_error.unattempted.iter().cloned());
+        }
+        if is_batch_error_retryable && !self.remaining_paths.is_empty() {
+            Retry::Transient(bulk_delete_error)
+        } else {
+            Retry::Permanent(bulk_delete_error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::{MockStorage, StorageErrorKind};
+
+    fn delete_failure(storage_error_kind: StorageErrorKind) -> DeleteFailure {
+        DeleteFailure {
+            error: Some(storage_error_kind.with_error(anyhow::anyhow!("delete failed"))),
+            ..Default::default()
+        }
+    }
+
+    fn retry_storage_for_test(mock_storage: MockStorage) -> RetryStorage {
+        let retry_params = RetryParams {
+            max_attempts: 3,
+            ..RetryParams::for_test()
+        };
+        RetryStorage::new(Arc::new(mock_storage), retry_params)
+    }
+
+    #[tokio::test]
+    async fn test_retry_storage_get_all_retries_transient_errors() {
+        let num_attempts = Arc::new(AtomicUsize::new(0));
+        let num_attempts_clone = num_attempts.clone();
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_all().returning(move |_path| {
+            if num_attempts_clone.fetch_add(1, Ordering::Relaxed) < 2 {
+                return Err(StorageErrorKind::Timeout.with_error(anyhow::anyhow!("timeout")));
+            }
+            Ok(OwnedBytes::new(b"hello".to_vec()))
+        });
+        let retry_storage = retry_storage_for_test(mock_storage);
+        let bytes = retry_storage.get_all(Path::new("foo")).await.unwrap();
+        assert_eq!(bytes.as_slice(), b"hello");
+        assert_eq!(num_attempts.load(Ordering::Relaxed), 3);
+    }
+
+    #[tokio::test]
+    async fn test_retry_storage_gives_up_after_max_attempts() {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_delete()
+            .times(3)
+            .returning(|_path| Err(StorageErrorKind::Io.with_error(anyhow::anyhow!("io"))));
+        let retry_storage = retry_storage_for_test(mock_storage);
+        let storage_error = retry_storage.delete(Path::new("foo")).await.unwrap_err();
+        assert_eq!(storage_error.kind(), StorageErrorKind::Io);
+    }
+
+    #[tokio::test]
+    async fn test_retry_storage_does_not_retry_permanent_errors() {
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_get_all().times(1).returning(|_path| {
+            Err(StorageErrorKind::NotFound.with_error(anyhow::anyhow!("not found")))
+        });
+        let retry_storage = retry_storage_for_test(mock_storage);
+        let storage_error = retry_storage.get_all(Path::new("foo")).await.unwrap_err();
+        assert_eq!(storage_error.kind(), StorageErrorKind::NotFound);
+    }
+
+    #[tokio::test]
+    async fn test_retry_storage_put_resends_payload() {
+        let num_attempts = Arc::new(AtomicUsize::new(0));
+        let num_attempts_clone = num_attempts.clone();
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_put().returning(move |_path, payload| {
+            assert_eq!(payload.len(), 5);
+            if num_attempts_clone.fetch_add(1, Ordering::Relaxed) == 0 {
+                return Err(StorageErrorKind::Internal.with_error(anyhow::anyhow!("internal")));
+            }
+            Ok(())
+        });
+        let retry_storage = retry_storage_for_test(mock_storage);
+        retry_storage
+            .put(Path::new("foo"), Box::new(b"hello".to_vec()))
+            .await
+            .unwrap();
+        assert_eq!(num_attempts.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn test_retry_storage_bulk_delete_retries_failed_files() {
+        let num_attempts = Arc::new(AtomicUsize::new(0));
+        let num_attempts_clone = num_attempts.clone();
+        let mut mock_storage = MockStorage::new();
+        mock_storage.expect_bulk_delete().returning(move |paths| {
+            let attempt = num_attempts_clone.fetch_add(1, Ordering::Relaxed);
+            if attempt == 0 {
+                assert_eq!(paths.len(), 4);
+                let bulk_delete_error = BulkDeleteError {
+                    successes: vec![PathBuf::from("foo")],
+                    failures: HashMap::from_iter([
+                        (
+                            PathBuf::from("bar"),
+                            delete_failure(StorageErrorKind::Timeout),
+                        ),
+                        (
+                            PathBuf::from("baz"),
+                            delete_failure(StorageErrorKind::Unauthorized),
+                        ),
+                    ]),
+                    unattempted: vec![PathBuf::from("qux")],
+                    ..Default::default()
+                };
+                return Err(bulk_delete_error);
+            }
+            // Only the files that failed with a retryable error or were not attempted are
+            // deleted again.
+            let mut paths = paths.to_vec();
+            paths.sort();
+            assert_eq!(paths, [Path::new("bar"), Path::new("qux")]);
+            Ok(())
+        });
+        let retry_storage = retry_storage_for_test(mock_storage);
+        let bulk_delete_error = retry_storage
+            .bulk_delete(&[
+                Path::new("foo"),
+                Path::new("bar"),
+                Path::new("baz"),
+                Path::new("qux"),
+            ])
+            .await
+            .unwrap_err();
+        assert_eq!(num_attempts.load(Ordering::Relaxed), 2);
+        // The second attempt deleted the remaining files, but the permanent failure of the first
+        // attempt is still reported.
+        assert!(bulk_delete_error.error.is_none());
+        let mut successes = bulk_delete_error.successes;
+        successes.sort();
+        assert_eq!(
+            successes,
+            [
+                PathBuf::from("bar"),
+                PathBuf::from("foo"),
+                PathBuf::from("qux")
+            ]
+        );
+        assert!(bulk_delete_error.unattempted.is_empty());
+        assert_eq!(bulk_delete_error.failures.len(), 1);
+        assert!(bulk_delete_error.failures.contains_key(Path::new("baz")));
+    }
+
+    #[tokio::test]
+    async fn test_retry_storage_bulk_delete_gives_up_after_max_attempts() {
+        let mut mock_storage = MockStorage::new();
+        mock_storage
+            .expect_bulk_delete()
+            .times(3)
+            .returning(|paths| {
+                assert_eq!(paths, [Path::new("foo")]);
+                let bulk_delete_error = BulkDeleteError {
+                    error: Some(StorageErrorKind::Io.with_error(anyhow::anyhow!("io"))),
+                    unattempted: vec![PathBuf::from("foo")],
+                    ..Default::default()
+                };
+                Err(bulk_delete_error)
+            });
+        let retry_storage = retry_storage_for_test(mock_storage);
+        let bulk_delete_error = retry_storage
+            .bulk_delete(&[Path::new("foo")])
+            .await
+            .unwrap_err();
+        assert_eq!(
+            bulk_delete_error.error.unwrap().kind(),
+            StorageErrorKind::Io
+        );
+        assert_eq!(bulk_delete_error.unattempted, [PathBuf::from("foo")]);
+    }
+}
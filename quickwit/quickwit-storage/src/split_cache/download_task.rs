@@ -28,7 +28,7 @@ use tracing::{error, instrument};
 use ulid::Ulid;
 
 use crate::split_cache::split_table::{CandidateSplit, DownloadOpportunity, SplitTable};
-use crate::StorageResolver;
+use crate::{with_io_class, IoClass, StorageResolver};
 
 /// Removes the evicted split files from the file system.
 /// This function just logs errors, and swallows them.
@@ -60,9 +60,8 @@ async fn download_split(
     let split_filename = split_file(*split_ulid);
     let target_filepath = root_path.join(&split_filename);
     let storage = storage_resolver.resolve(storage_uri).await?;
-    let num_bytes = storage
-        .copy_to_file(Path::new(&split_filename), &target_filepath)
-        .await?;
+    let copy_to_file_future = storage.copy_to_file(Path::new(&split_filename), &target_filepath);
+    let num_bytes = with_io_class(IoClass::Background, copy_to_file_future).await?;
     Ok(num_bytes)
 }
 
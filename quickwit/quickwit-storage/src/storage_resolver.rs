@@ -22,14 +22,19 @@ use std::fmt;
 use std::sync::Arc;
 
 use once_cell::sync::Lazy;
+use quickwit_common::get_from_env;
+use quickwit_common::retry::RetryParams;
 use quickwit_common::uri::{Protocol, Uri};
 use quickwit_config::{StorageBackend, StorageConfigs};
 
+use crate::io_scheduler::wrap_storage_with_io_scheduler;
 use crate::local_file_storage::LocalFileStorageFactory;
 use crate::ram_storage::RamStorageFactory;
 #[cfg(feature = "azure")]
 use crate::AzureBlobStorageFactory;
-use crate::{S3CompatibleObjectStorageFactory, Storage, StorageFactory, StorageResolverError};
+use crate::{
+    RetryStorage, S3CompatibleObjectStorageFactory, Storage, StorageFactory, StorageResolverError,
+};
 
 /// Returns the [`Storage`] instance associated with the protocol of a URI. The actual creation of
 /// storage objects is delegated to pre-registered [`StorageFactory`]. The resolver is only
@@ -37,6 +42,7 @@ use crate::{S3CompatibleObjectStorageFactory, Storage, StorageFactory, StorageRe
 #[derive(Clone)]
 pub struct StorageResolver {
     per_backend_factories: Arc<HashMap<StorageBackend, Box<dyn StorageFactory>>>,
+    retry_params_opt: Option<RetryParams>,
 }
 
 impl fmt::Debug for StorageResolver {
@@ -71,6 +77,17 @@ impl StorageResolver {
             StorageResolverError::UnsupportedBackend(message)
         })?;
         let storage = storage_factory.resolve(uri).await?;
+        let storage = wrap_storage_with_io_scheduler(storage);
+
+        // The S3 and Azure clients already retry their requests, so wrapping them would multiply
+        // the number of attempts.
+        let has_builtin_retries = matches!(backend, StorageBackend::Azure | StorageBackend::S3);
+
+        if let Some(retry_params) = self.retry_params_opt {
+            if !has_builtin_retries {
+                return Ok(Arc::new(RetryStorage::new(storage, retry_params)));
+            }
+        }
         Ok(storage)
     }
 
@@ -87,6 +104,9 @@ impl StorageResolver {
     }
 
     /// Creates and returns a [`StorageResolver`].
+    ///
+    /// The resolved storages, except the S3 and Azure ones which retry on their own, retry their
+    /// failed requests if `QW_STORAGE_RETRY_MAX_ATTEMPTS` is set to a value greater than 1.
     pub fn configured(storage_configs: &StorageConfigs) -> Self {
         let mut builder = StorageResolver::builder()
             .register(LocalFileStorageFactory)
@@ -109,6 +129,14 @@ impl StorageResolver {
                 "Quickwit was compiled without the `azure` feature.",
             ))
         }
+        let retry_max_attempts: usize = get_from_env("QW_STORAGE_RETRY_MAX_ATTEMPTS", 1);
+
+        if retry_max_attempts > 1 {
+            builder = builder.retry_params(RetryParams {
+                max_attempts: retry_max_attempts,
+                ..Default::default()
+            });
+        }
         builder
             .build()
             .expect("Storage factory and config backends should match.")
@@ -129,6 +157,7 @@ impl StorageResolver {
 #[derive(Default)]
 pub struct StorageResolverBuilder {
     per_backend_factories: HashMap<StorageBackend, Box<dyn StorageFactory>>,
+    retry_params_opt: Option<RetryParams>,
 }
 
 impl StorageResolverBuilder {
@@ -139,10 +168,19 @@ impl StorageResolverBuilder {
         self
     }
 
+    /// Wraps the resolved storages in a [`RetryStorage`] retrying their failed requests according
+    /// to `retry_params`. S3 and Azure storages are not wrapped because their clients already
+    /// retry.
+    pub fn retry_params(mut self, retry_params: RetryParams) -> Self {
+        self.retry_params_opt = Some(retry_params);
+        self
+    }
+
     /// Builds the [`StorageResolver`].
     pub fn build(self) -> anyhow::Result<StorageResolver> {
         let storage_resolver = StorageResolver {
             per_backend_factories: Arc::new(self.per_backend_factories),
+            retry_params_opt: self.retry_params_opt,
         };
         Ok(storage_resolver)
     }
@@ -218,6 +256,48 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_storage_resolver_with_retry_params() {
+        let storage_resolver = StorageResolver::builder()
+            .register(RamStorageFactory::default())
+            .retry_params(RetryParams::for_test())
+            .build()
+            .unwrap();
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///"))
+            .await
+            .unwrap();
+        assert!(format!("{storage:?}").starts_with("RetryStorage"));
+
+        let storage_resolver = StorageResolver::for_test();
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("ram:///"))
+            .await
+            .unwrap();
+        assert!(!format!("{storage:?}").starts_with("RetryStorage"));
+    }
+
+    #[tokio::test]
+    async fn test_storage_resolver_does_not_retry_backends_with_builtin_retries() {
+        let mut s3_storage_factory = MockStorageFactory::new();
+        s3_storage_factory
+            .expect_backend()
+            .returning(|| StorageBackend::S3);
+        s3_storage_factory
+            .expect_resolve()
+            .returning(|_uri| Ok(Arc::new(RamStorage::default())));
+        let storage_resolver = StorageResolver::builder()
+            .register(s3_storage_factory)
+            .retry_params(RetryParams::for_test())
+            .build()
+            .unwrap();
+        let storage = storage_resolver
+            .resolve(&Uri::for_test("s3://bucket/indexes"))
+            .await
+            .unwrap();
+        assert!(!format!("{storage:?}").starts_with("RetryStorage"));
+    }
+
     #[tokio::test]
     async fn test_storage_resolver_unsupported_protocol() {
         let storage_resolver = StorageResolver::unconfigured();